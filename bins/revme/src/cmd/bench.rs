@@ -1,5 +1,6 @@
 pub mod analysis;
 pub mod burntpix;
+pub mod large_payload;
 pub mod snailtracer;
 pub mod transfer;
 
@@ -9,6 +10,7 @@ use clap::{Parser, ValueEnum};
 pub enum BenchName {
     Analysis,
     Burntpix,
+    LargePayload,
     Snailtracer,
     Transfer,
 }
@@ -17,6 +19,7 @@ impl BenchName {
     pub const ALL: &[BenchName] = &[
         BenchName::Analysis,
         BenchName::Burntpix,
+        BenchName::LargePayload,
         BenchName::Snailtracer,
         BenchName::Transfer,
     ];
@@ -25,6 +28,7 @@ impl BenchName {
         match self {
             BenchName::Analysis => "analysis",
             BenchName::Burntpix => "burntpix",
+            BenchName::LargePayload => "large_payload",
             BenchName::Snailtracer => "snailtracer",
             BenchName::Transfer => "transfer",
         }
@@ -44,6 +48,7 @@ impl Cmd {
         match self.name {
             BenchName::Analysis => analysis::run(),
             BenchName::Burntpix => burntpix::run(),
+            BenchName::LargePayload => large_payload::run(),
             BenchName::Snailtracer => snailtracer::run(),
             BenchName::Transfer => transfer::run(),
         }
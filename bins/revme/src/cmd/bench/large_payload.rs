@@ -0,0 +1,130 @@
+//! Transactions with payloads larger than the largest contract code, reporting the bytes they
+//! allocate along with their time.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+use revm::{
+    bytecode::Bytecode,
+    primitives::{bytes, Bytes, TxKind},
+    Context, ExecuteEvm,
+};
+
+/// Size of the calldata and of the deployed code.
+const PAYLOAD_SIZE: usize = 128 * 1024;
+
+/// Allocator of `revme` counting the allocated bytes.
+struct CountingAllocator;
+
+/// Bytes allocated since the start of the process.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Bytes currently allocated.
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+/// Highest value of [`LIVE`] since the last [`Allocations::start`].
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+impl CountingAllocator {
+    fn grow(&self, size: usize) {
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.grow(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            self.grow(new_size - layout.size());
+        } else {
+            LIVE.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations made since [`Allocations::start`].
+struct Allocations {
+    allocated: usize,
+    live: usize,
+}
+
+impl Allocations {
+    fn start() -> Self {
+        let live = LIVE.load(Ordering::Relaxed);
+        PEAK.store(live, Ordering::Relaxed);
+        Self {
+            allocated: ALLOCATED.load(Ordering::Relaxed),
+            live,
+        }
+    }
+
+    /// Returns the allocated bytes and the peak of the allocated bytes.
+    fn end(self) -> (usize, usize) {
+        (
+            ALLOCATED.load(Ordering::Relaxed) - self.allocated,
+            PEAK.load(Ordering::Relaxed) - self.live,
+        )
+    }
+}
+
+fn exec(name: &str, mut context: impl ExecuteEvm) {
+    let _ = context.exec_previous();
+
+    let allocations = Allocations::start();
+    let time = Instant::now();
+    let _ = context.exec_previous();
+    let elapsed = time.elapsed();
+    let (allocated, peak) = allocations.end();
+    println!("{name}: {elapsed:?}, {allocated} bytes allocated, {peak} bytes peak");
+}
+
+pub fn run() {
+    // CALLDATASIZE PUSH0 PUSH0 CALLDATACOPY CALLDATASIZE PUSH0 RETURN
+    let bytecode = Bytecode::new_raw(bytes!("365f5f37365ff3"));
+    let context = Context::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode))
+        .modify_tx_chained(|tx| {
+            tx.caller = BENCH_CALLER;
+            tx.kind = TxKind::Call(BENCH_TARGET);
+            tx.data = Bytes::from(vec![0xff; PAYLOAD_SIZE]);
+        });
+    exec("Call", context);
+
+    // PUSH3 len PUSH1 14 PUSH0 CODECOPY PUSH3 len PUSH0 RETURN, followed by the deployed code.
+    let len = &(PAYLOAD_SIZE as u32).to_be_bytes()[1..];
+    let mut init_code = [
+        &[0x62],
+        len,
+        &[0x60, 14, 0x5f, 0x39, 0x62],
+        len,
+        &[0x5f, 0xf3],
+    ]
+    .concat();
+    init_code.resize(init_code.len() + PAYLOAD_SIZE, 0xff);
+    let context = Context::builder()
+        .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+        .modify_cfg_chained(|cfg| cfg.limit_contract_code_size = Some(PAYLOAD_SIZE * 2))
+        .modify_tx_chained(|tx| {
+            tx.caller = BENCH_CALLER;
+            tx.kind = TxKind::Create;
+            tx.gas_limit = 100_000_000;
+            tx.data = init_code.into();
+        });
+    exec("Create", context);
+}
@@ -1,10 +1,7 @@
-mod test_suite;
-
-pub use test_suite::{TestResult, TestSuite, TestUnit, TestVector};
-
 use crate::{cmd::Error, dir_utils::find_all_json_tests};
 use clap::Parser;
-use revm::bytecode::eof::{validate_raw_eof_inner, CodeType, EofError};
+use revm::bytecode::eof::EofError;
+use statetest_types::{EofTestSuite, SpecName};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
@@ -69,25 +66,20 @@ pub fn run_test(path: &Path) -> Result<(), Error> {
     let mut types_of_error: BTreeMap<ErrorType, usize> = BTreeMap::new();
     for test_file in test_files {
         let s = std::fs::read_to_string(test_file).unwrap();
-        let suite: TestSuite = serde_json::from_str(&s).unwrap();
+        let suite: EofTestSuite = serde_json::from_str(&s).unwrap();
         for (name, test_unit) in suite.0 {
             for (vector_name, test_vector) in test_unit.vectors {
                 if skip_test(&vector_name) {
                     continue;
                 }
                 test_sum += 1;
-                let kind = if test_vector.container_kind.is_some() {
-                    Some(CodeType::ReturnContract)
-                } else {
-                    Some(CodeType::ReturnOrStop)
-                };
                 // In future this can be generalized to cover multiple forks, Not just Osaka.
-                let Some(test_result) = test_vector.results.get("Osaka") else {
+                let Some(test_result) = test_vector.results.get(&SpecName::Osaka) else {
                     // if test does not have a result that we can compare to, we skip it
                     println!("Test without result: {} - {}", name, vector_name);
                     continue;
                 };
-                let res = validate_raw_eof_inner(test_vector.code.clone(), kind);
+                let res = test_vector.validate();
                 if test_result.result != res.is_ok() {
                     println!(
                         "\nTest failed: {} - {}\nresult:{:?}\nrevm err_result:{:#?}\nExpected exception:{:?}\nbytes:{:?}\n",
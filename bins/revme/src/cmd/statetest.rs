@@ -1,12 +1,16 @@
-pub mod merkle_trie;
+pub use statetest_types::merkle_trie;
+mod optimism;
 mod runner;
 pub mod utils;
 
-pub use runner::TestError as Error;
+pub use runner::{
+    find_all_json_tests, run_tests, Report, RunnerConfig, SkipReason, TestError as Error,
+    TestErrorKind, TestOutcome, TestStatus,
+};
 
 use clap::Parser;
-use runner::{find_all_json_tests, run, TestError};
-use std::path::PathBuf;
+use statetest_types::SpecName;
+use std::{path::PathBuf, time::Duration};
 
 /// `statetest` subcommand
 #[derive(Parser, Debug)]
@@ -36,22 +40,73 @@ pub struct Cmd {
     /// Keep going after a test failure
     #[clap(long, alias = "no-fail-fast")]
     keep_going: bool,
+    /// Only run tests of the given forks, e.g. `Cancun`
+    #[clap(long = "fork", value_parser = parse_fork)]
+    forks: Vec<SpecName>,
+    /// Skip the remaining tests of a file after this many seconds
+    #[clap(long)]
+    timeout: Option<u64>,
+    /// Write a JSON report with the outcome of every test to this file
+    #[clap(long)]
+    report: Option<PathBuf>,
+}
+
+fn parse_fork(name: &str) -> Result<SpecName, String> {
+    match serde_json::from_value(serde_json::Value::String(name.to_string())) {
+        Ok(SpecName::Unknown) | Err(_) => Err(format!("unknown fork: {name}")),
+        Ok(fork) => Ok(fork),
+    }
 }
 
 impl Cmd {
     /// Runs `statetest` command.
-    pub fn run(&self) -> Result<(), TestError> {
+    pub fn run(&self) -> Result<(), Error> {
+        let config = RunnerConfig {
+            threads: if self.single_thread { 1 } else { 0 },
+            forks: self.forks.clone(),
+            timeout: self.timeout.map(Duration::from_secs),
+            keep_going: self.keep_going,
+            trace: self.json,
+            json_outcome: self.json_outcome,
+            dump_first_failure: true,
+            progress: true,
+        };
+        let mut report = Report::default();
         for path in &self.paths {
             println!("\nRunning tests in {}...", path.display());
             let test_files = find_all_json_tests(path);
-            run(
-                test_files,
-                self.single_thread,
-                self.json,
-                self.json_outcome,
-                self.keep_going,
-            )?
+            let path_report = run_tests(test_files, &config);
+            println!(
+                "Finished execution. Total CPU time: {:.6}s",
+                path_report.cpu_time
+            );
+            let failed = path_report.failed > 0;
+            report.append(path_report);
+            if failed && !self.keep_going {
+                break;
+            }
+        }
+
+        if let Some(path) = &self.report {
+            let json = serde_json::to_string_pretty(&report).unwrap();
+            if let Err(e) = std::fs::write(path, json) {
+                println!("Failed to write report to {}: {e}", path.display());
+            }
         }
-        Ok(())
+
+        let total = report.passed + report.failed;
+        let Some(error) = report
+            .tests
+            .into_iter()
+            .find_map(|outcome| outcome.into_error())
+        else {
+            println!("All tests passed!");
+            return Ok(());
+        };
+        println!(
+            "Encountered {} errors out of {total} total tests",
+            report.failed
+        );
+        Err(error)
     }
 }
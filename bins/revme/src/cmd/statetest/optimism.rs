@@ -0,0 +1,133 @@
+//! Execution of the state tests of Optimism forks with the optimism handler.
+//!
+//! Deposits are declared by the `sourceHash`, `mint` and `isSystemTx` fields of the transaction.
+//! The L1 block info is read from the storage of the `L1Block` predeploy in the pre state, and the
+//! L1 data fee of the transaction bytes is checked against the `l1Cost` of the test if set.
+use super::runner::{check_evm_execution, TestErrorKind};
+use database::{CacheState, State};
+use optimism::{
+    api::exec_op::transact_op_commit,
+    transaction::deposit::{DepositTransactionParts, DEPOSIT_TRANSACTION_TYPE},
+    L1BlockInfo, L1BlockInfoGetter, OpSpec, OpSpecId, OpTransaction, OpTransactionError,
+};
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::result::EVMError,
+    primitives::Bytes,
+    Context,
+};
+use statetest_types::{Test, TransactionParts};
+
+/// Executes the transaction of a test of an Optimism fork and checks its result.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn execute_op_test(
+    test: &Test,
+    transaction: &TransactionParts,
+    expected_output: Option<&Bytes>,
+    test_name: &str,
+    cache_state: &CacheState,
+    (block, tx, cfg): (&BlockEnv, &TxEnv, &CfgEnv),
+    op_spec: OpSpecId,
+    print_json_outcome: bool,
+) -> Result<(), TestErrorKind> {
+    let spec = OpSpec::Op(op_spec);
+    let txbytes = test.txbytes.clone().unwrap_or_default();
+    let mut op_tx = OpTransaction::new(tx.clone());
+    op_tx.enveloped_tx = Some(txbytes.clone());
+    if let Some(source_hash) = transaction.source_hash {
+        op_tx.base.tx_type = DEPOSIT_TRANSACTION_TYPE;
+        op_tx.deposit = DepositTransactionParts::new(
+            source_hash,
+            transaction.mint.map(|mint| mint.saturating_to()),
+            transaction.is_system_tx,
+        );
+    }
+
+    let mut cache = cache_state.clone();
+    cache.set_state_clear_flag(true);
+    let mut state = State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build();
+    let mut ctx = Context::default()
+        .with_block(block.clone())
+        .with_tx(op_tx)
+        .with_cfg(cfg.clone().with_spec(spec))
+        .with_chain(L1BlockInfo::default())
+        .with_db(&mut state);
+
+    // Errors other than invalid transactions of the base spec are not expected exceptions.
+    let exec_result = transact_op_commit(&mut ctx).map_err(|error| match error {
+        EVMError::Transaction(OpTransactionError::Base(error)) => EVMError::Transaction(error),
+        EVMError::Transaction(error) => EVMError::Custom(error.to_string()),
+        EVMError::Header(error) => EVMError::Header(error),
+        EVMError::Database(error) => match error {},
+        EVMError::Custom(error) => EVMError::Custom(error),
+        EVMError::Precompile(error) => EVMError::Precompile(error),
+    });
+    let l1_cost = ctx.calculate_tx_l1_cost(&txbytes, spec);
+
+    check_evm_execution(
+        test,
+        expected_output,
+        test_name,
+        &exec_result,
+        ctx.journaled_state.database,
+        spec,
+        print_json_outcome,
+    )?;
+    match test.l1_cost {
+        Some(expected) if expected != l1_cost => Err(TestErrorKind::L1CostMismatch {
+            got: l1_cost,
+            expected,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, TxKind, U256};
+
+    #[test]
+    fn expected_exception_and_l1_cost() {
+        let mut test: Test = serde_json::from_str(
+            r#"{
+                "expectException": "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS",
+                "indexes": { "data": 0, "gas": 0, "value": 0 },
+                "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "logs": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "txbytes": "0x02facade"
+            }"#,
+        )
+        .unwrap();
+        let tx = TxEnv {
+            caller: address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b"),
+            kind: TxKind::Call(address!("0000000000000000000000000000000000001000")),
+            gas_price: 1,
+            gas_priority_fee: None,
+            ..Default::default()
+        };
+        let execute = |test: &Test| {
+            execute_op_test(
+                test,
+                &TransactionParts::default(),
+                None,
+                "test",
+                &CacheState::new(true),
+                (&BlockEnv::default(), &tx, &CfgEnv::default()),
+                OpSpecId::ECOTONE,
+                false,
+            )
+        };
+
+        // The caller can not pay for gas, and no L1 block info is set.
+        assert!(execute(&test).is_ok());
+        test.l1_cost = Some(U256::from(1));
+        assert!(matches!(
+            execute(&test),
+            Err(TestErrorKind::L1CostMismatch { .. })
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 use super::{
     merkle_trie::{log_rlp_hash, state_merkle_trie_root},
+    optimism::execute_op_test,
     utils::recover_address,
 };
 use database::State;
@@ -10,25 +11,30 @@ use revm::{
     context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
     context_interface::{
         block::calc_excess_blob_gas,
-        result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+        result::{EVMError, ExecutionResult, HaltReasonTrait, InvalidTransaction},
         Cfg,
     },
     database_interface::EmptyDB,
-    primitives::{keccak256, Bytes, TxKind, B256},
+    primitives::{keccak256, Bytes, TxKind, B256, U256},
     specification::{eip4844::TARGET_BLOB_GAS_PER_BLOCK_CANCUN, hardfork::SpecId},
     Context, ExecuteCommitEvm,
 };
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 use serde_json::json;
-use statetest_types::{SpecName, Test, TestSuite};
+use statetest_types::{
+    BlobSchedule, ConstructorError, ExpectedException, SpecName, Test, TestSuite,
+    UnknownExceptionError,
+};
 
 use std::{
     convert::Infallible,
     fmt::Debug,
     io::stderr,
+    panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicBool, AtomicUsize, Ordering},
-        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        Mutex,
     },
     time::{Duration, Instant},
 };
@@ -56,6 +62,12 @@ pub enum TestErrorKind {
         expected_exception: Option<String>,
         got_exception: Option<String>,
     },
+    #[error(transparent)]
+    UnknownException(#[from] UnknownExceptionError),
+    #[error(transparent)]
+    PreState(#[from] ConstructorError),
+    #[error("L1 data fee mismatch: got {got}, expected {expected}")]
+    L1CostMismatch { got: U256, expected: U256 },
     #[error("unexpected output: got {got_output:?}, expected {expected_output:?}")]
     UnexpectedOutput {
         expected_output: Option<Bytes>,
@@ -67,6 +79,173 @@ pub enum TestErrorKind {
     Panic,
 }
 
+/// Reason a test was not executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum SkipReason {
+    #[error("file is in the skip list")]
+    SkipList,
+    /// Constantinople was immediately replaced by Petersburg and is not supported.
+    #[error("fork is not supported")]
+    UnsupportedFork,
+    #[error("transaction type is invalid and an exception is expected")]
+    InvalidTransactionType,
+    #[error("file timed out")]
+    TimedOut,
+}
+
+/// Outcome of a test, see [`TestOutcome`].
+///
+/// Serialized as a `status` field of `pass`, `fail` or `skip`, and a `reason` field for the
+/// latter two.
+#[derive(Debug)]
+pub enum TestStatus {
+    Pass,
+    Fail(TestErrorKind),
+    Skip(SkipReason),
+}
+
+impl Serialize for TestStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (status, reason) = match self {
+            Self::Pass => ("pass", None),
+            Self::Fail(kind) => ("fail", Some(kind.to_string())),
+            Self::Skip(reason) => ("skip", Some(reason.to_string())),
+        };
+        let mut state = serializer.serialize_struct("TestStatus", 1 + reason.is_some() as usize)?;
+        state.serialize_field("status", status)?;
+        if let Some(reason) = reason {
+            state.serialize_field("reason", &reason)?;
+        }
+        state.end()
+    }
+}
+
+/// Outcome of a test of a state test file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestOutcome {
+    pub path: String,
+    /// Name of the test, [`None`] if the outcome is the one of the whole file.
+    pub name: Option<String>,
+    /// Fork, [`None`] if the outcome is the one of all forks of the test.
+    pub fork: Option<SpecName>,
+    /// Index of the test in the post state of the fork, [`None`] if the outcome is the one of
+    /// all its tests.
+    pub index: Option<usize>,
+    #[serde(flatten)]
+    pub status: TestStatus,
+}
+
+impl TestOutcome {
+    fn new(path: &str, name: Option<&str>, fork: Option<SpecName>, status: TestStatus) -> Self {
+        Self {
+            path: path.to_string(),
+            name: name.map(str::to_string),
+            fork,
+            index: None,
+            status,
+        }
+    }
+
+    fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Converts a failed outcome into the error of the test.
+    pub fn into_error(self) -> Option<TestError> {
+        let TestStatus::Fail(kind) = self.status else {
+            return None;
+        };
+        Some(TestError {
+            name: self.name.unwrap_or_else(|| "Unknown".to_string()),
+            path: self.path,
+            kind,
+        })
+    }
+}
+
+/// Machine-readable report of a [`run_tests`] run.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Time spent executing transactions summed over all threads, in seconds.
+    pub cpu_time: f64,
+    /// Outcome of every test, in the order of the test files.
+    pub tests: Vec<TestOutcome>,
+}
+
+impl Report {
+    fn push(&mut self, outcome: TestOutcome) {
+        match outcome.status {
+            TestStatus::Pass => self.passed += 1,
+            TestStatus::Fail(_) => self.failed += 1,
+            TestStatus::Skip(_) => self.skipped += 1,
+        }
+        self.tests.push(outcome);
+    }
+
+    /// Appends the outcomes of another report.
+    pub fn append(&mut self, other: Report) {
+        self.cpu_time += other.cpu_time;
+        for outcome in other.tests {
+            self.push(outcome);
+        }
+    }
+
+    /// Returns the failed tests.
+    pub fn failures(&self) -> impl Iterator<Item = &TestOutcome> {
+        self.tests
+            .iter()
+            .filter(|outcome| matches!(outcome.status, TestStatus::Fail(_)))
+    }
+}
+
+/// Configuration of [`run_tests`].
+#[derive(Clone, Debug, Default)]
+pub struct RunnerConfig {
+    /// Number of threads, the available parallelism if zero.
+    pub threads: usize,
+    /// Forks to run, all of them if empty. Tests of other forks are not reported.
+    pub forks: Vec<SpecName>,
+    /// Time after which the remaining tests of a file are skipped.
+    ///
+    /// Checked between tests, a running transaction is not interrupted.
+    pub timeout: Option<Duration>,
+    /// Keep running tests after a failure, otherwise no test is started after the first one.
+    pub keep_going: bool,
+    /// Print the EIP-3155 trace of every transaction to stderr. Implies `json_outcome`.
+    pub trace: bool,
+    /// Print the outcome of every test as JSON to stderr. Runs on a single thread.
+    pub json_outcome: bool,
+    /// Print the trace and environment of the first failed test to stdout.
+    pub dump_first_failure: bool,
+    /// Show the progress of the files on stdout.
+    pub progress: bool,
+}
+
+impl RunnerConfig {
+    fn runs_fork(&self, fork: SpecName) -> bool {
+        self.forks.is_empty() || self.forks.contains(&fork)
+    }
+}
+
+/// State shared by the threads of a run.
+#[derive(Debug, Default)]
+struct RunState {
+    elapsed: Mutex<Duration>,
+    failed: AtomicBool,
+    dumped: AtomicBool,
+}
+
+impl RunState {
+    fn stopped(&self, config: &RunnerConfig) -> bool {
+        !config.keep_going && self.failed.load(Ordering::SeqCst)
+    }
+}
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     if path.is_file() {
         vec![path.to_path_buf()]
@@ -133,13 +312,17 @@ fn skip_test(path: &Path) -> bool {
     )
 }
 
-fn check_evm_execution(
+/// Checks the result of the transaction of a test and the state it left in `db`.
+///
+/// Optimism forks are checked with the base halt reasons and invalid transaction errors of their
+/// result, see [`super::optimism`].
+pub(super) fn check_evm_execution<H: HaltReasonTrait>(
     test: &Test,
     expected_output: Option<&Bytes>,
     test_name: &str,
-    exec_result: &Result<ExecutionResult<HaltReason>, EVMError<Infallible, InvalidTransaction>>,
+    exec_result: &Result<ExecutionResult<H>, EVMError<Infallible, InvalidTransaction>>,
     db: &mut State<EmptyDB>,
-    spec: SpecId,
+    spec: impl Serialize,
     print_json_outcome: bool,
 ) -> Result<(), TestErrorKind> {
     let logs_root = log_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
@@ -183,7 +366,13 @@ fn check_evm_execution(
     // it does not matter.
     // Test where this happens: `tests/GeneralStateTests/stTransactionTest/NoSrcAccountCreate.json`
     // and you can check that we have only two "hash" values for before and after state clear.
-    match (&test.expect_exception, exec_result) {
+    let expected_exception = test
+        .expect_exception
+        .as_deref()
+        .map(str::parse::<ExpectedException>)
+        .transpose()
+        .inspect_err(|error| print_json_output(Some(error.to_string())))?;
+    match (&expected_exception, exec_result) {
         // Do nothing
         (None, Ok(result)) => {
             // Check output
@@ -198,8 +387,8 @@ fn check_evm_execution(
                 }
             }
         }
-        // Return okay, exception is expected.
-        (Some(_), Err(_)) => return Ok(()),
+        // Return okay, the expected exception is returned.
+        (Some(expected), Err(error)) if expected.matches(error) => return Ok(()),
         _ => {
             let kind = TestErrorKind::UnexpectedException {
                 expected_exception: test.expect_exception.clone(),
@@ -233,25 +422,45 @@ fn check_evm_execution(
     Ok(())
 }
 
-pub fn execute_test_suite(
-    path: &Path,
-    elapsed: &Arc<Mutex<Duration>>,
-    trace: bool,
-    print_json_outcome: bool,
-) -> Result<(), TestError> {
+fn execute_test_suite(path: &Path, config: &RunnerConfig, run: &RunState) -> Vec<TestOutcome> {
+    let path_str = path.to_string_lossy().into_owned();
+    let path_str = path_str.as_str();
     if skip_test(path) {
-        return Ok(());
+        return vec![TestOutcome::new(
+            path_str,
+            None,
+            None,
+            TestStatus::Skip(SkipReason::SkipList),
+        )];
     }
 
     let s = std::fs::read_to_string(path).unwrap();
-    let path = path.to_string_lossy().into_owned();
-    let suite: TestSuite = serde_json::from_str(&s).map_err(|e| TestError {
-        name: "Unknown".to_string(),
-        path: path.clone(),
-        kind: e.into(),
-    })?;
-
-    for (name, unit) in suite.0 {
+    let suite: TestSuite = match serde_json::from_str(&s) {
+        Ok(suite) => suite,
+        Err(e) => {
+            return vec![TestOutcome::new(
+                path_str,
+                None,
+                None,
+                TestStatus::Fail(e.into()),
+            )]
+        }
+    };
+
+    let deadline = config.timeout.map(|timeout| Instant::now() + timeout);
+    let mut outcomes = Vec::new();
+    for (name, mut unit) in suite.0 {
+        if let Err(error) = unit.deploy_pre_state() {
+            outcomes.push(TestOutcome::new(
+                path_str,
+                Some(&name),
+                None,
+                TestStatus::Fail(error.into()),
+            ));
+            run.failed.store(true, Ordering::SeqCst);
+            continue;
+        }
+
         // Create database and insert cache
         let mut cache_state = database::CacheState::new(false);
         for (address, info) in unit.pre {
@@ -270,8 +479,16 @@ pub fn execute_test_suite(
         let mut cfg = CfgEnv::default();
         let mut block = BlockEnv::default();
         let mut tx = TxEnv::default();
-        // For mainnet
-        cfg.chain_id = 1;
+        // Mainnet if the test does not set it
+        cfg.chain_id = unit
+            .config
+            .chain_id
+            .map_or(1, |chain_id| chain_id.saturating_to());
+        tx.chain_id = Some(cfg.chain_id);
+        let blob_target_and_max_count = unit.config.blob_target_and_max_count();
+        if !blob_target_and_max_count.is_empty() {
+            cfg.set_blob_max_and_target_count(blob_target_and_max_count);
+        }
 
         // Block env
         block.number = unit.env.current_number.try_into().unwrap_or(u64::MAX);
@@ -291,12 +508,18 @@ pub fn execute_test_suite(
         // Tx env
         tx.caller = if let Some(address) = unit.transaction.sender {
             address
+        } else if let Some(address) = recover_address(unit.transaction.secret_key.as_slice()) {
+            address
         } else {
-            recover_address(unit.transaction.secret_key.as_slice()).ok_or_else(|| TestError {
-                name: name.clone(),
-                path: path.clone(),
-                kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
-            })?
+            let kind = TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key);
+            outcomes.push(TestOutcome::new(
+                path_str,
+                Some(&name),
+                None,
+                TestStatus::Fail(kind),
+            ));
+            run.failed.store(true, Ordering::SeqCst);
+            continue;
         };
         tx.gas_price = unit
             .transaction
@@ -319,36 +542,55 @@ pub fn execute_test_suite(
 
         // Post and execution
         for (spec_name, tests) in unit.post {
+            if !config.runs_fork(spec_name) {
+                continue;
+            }
             // Constantinople was immediately extended by Petersburg.
             // There isn't any production Constantinople transaction
             // so we don't support it and skip right to Petersburg.
             if spec_name == SpecName::Constantinople {
+                outcomes.push(TestOutcome::new(
+                    path_str,
+                    Some(&name),
+                    Some(spec_name),
+                    TestStatus::Skip(SkipReason::UnsupportedFork),
+                ));
                 continue;
             }
 
             cfg.spec = spec_name.to_spec_id();
 
-            // EIP-4844
-            if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
-                block.set_blob_excess_gas_and_price(
-                    current_excess_blob_gas.to(),
-                    cfg.spec.is_enabled_in(SpecId::PRAGUE),
-                );
-            } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
-                unit.env.parent_blob_gas_used,
-                unit.env.parent_excess_blob_gas,
-            ) {
-                block.set_blob_excess_gas_and_price(
-                    calc_excess_blob_gas(
+            // EIP-4844, with the blob parameters of the fork if the test sets them.
+            let blob_schedule = unit.config.blob_schedule(cfg.spec);
+            let excess_blob_gas =
+                if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
+                    Some(current_excess_blob_gas.to())
+                } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
+                    unit.env.parent_blob_gas_used,
+                    unit.env.parent_excess_blob_gas,
+                ) {
+                    Some(calc_excess_blob_gas(
                         parent_blob_gas_used.to(),
                         parent_excess_blob_gas.to(),
-                        unit.env
-                            .parent_target_blobs_per_block
-                            .map(|i| i.to())
+                        blob_schedule
+                            .map(BlobSchedule::target_blob_gas_per_block)
+                            .or(unit.env.parent_target_blobs_per_block.map(|i| i.to()))
                             .unwrap_or(TARGET_BLOB_GAS_PER_BLOCK_CANCUN),
+                    ))
+                } else {
+                    None
+                };
+            if let Some(excess_blob_gas) = excess_blob_gas {
+                match blob_schedule {
+                    Some(schedule) => {
+                        block.blob_excess_gas_and_price =
+                            Some(schedule.blob_excess_gas_and_price(excess_blob_gas))
+                    }
+                    None => block.set_blob_excess_gas_and_price(
+                        excess_blob_gas,
+                        cfg.spec.is_enabled_in(SpecId::PRAGUE),
                     ),
-                    cfg.spec.is_enabled_in(SpecId::PRAGUE),
-                );
+                }
             }
 
             if cfg.spec.is_enabled_in(SpecId::MERGE) && block.prevrandao.is_none() {
@@ -357,8 +599,26 @@ pub fn execute_test_suite(
             }
 
             for (index, test) in tests.into_iter().enumerate() {
+                if run.stopped(config) {
+                    return outcomes;
+                }
+                let outcome =
+                    TestOutcome::new(path_str, Some(&name), Some(spec_name), TestStatus::Pass)
+                        .with_index(index);
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    outcomes.push(TestOutcome {
+                        status: TestStatus::Skip(SkipReason::TimedOut),
+                        ..outcome
+                    });
+                    continue;
+                }
+
                 let Some(tx_type) = unit.transaction.tx_type(test.indexes.data) else {
                     if test.expect_exception.is_some() {
+                        outcomes.push(TestOutcome {
+                            status: TestStatus::Skip(SkipReason::InvalidTransactionType),
+                            ..outcome
+                        });
                         continue;
                     } else {
                         panic!("Invalid transaction type without expected exception");
@@ -405,6 +665,30 @@ pub fn execute_test_suite(
                 };
                 tx.kind = to;
 
+                if let Some(op_spec) = spec_name.to_op_spec_id() {
+                    let timer = Instant::now();
+                    let output = execute_op_test(
+                        &test,
+                        &unit.transaction,
+                        unit.out.as_ref(),
+                        &name,
+                        &cache_state,
+                        (&block, &tx, &cfg),
+                        op_spec,
+                        config.json_outcome,
+                    );
+                    *run.elapsed.lock().unwrap() += timer.elapsed();
+                    let status = match output {
+                        Ok(()) => TestStatus::Pass,
+                        Err(e) => {
+                            run.failed.store(true, Ordering::SeqCst);
+                            TestStatus::Fail(e)
+                        }
+                    };
+                    outcomes.push(TestOutcome { status, ..outcome });
+                    continue;
+                }
+
                 let mut cache = cache_state.clone();
                 cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
                 let mut state = database::State::builder()
@@ -418,7 +702,7 @@ pub fn execute_test_suite(
                     .with_db(&mut state);
 
                 // Do the deed
-                let (e, exec_result) = if trace {
+                let (e, exec_result) = if config.trace {
                     let mut ctx = Context::builder()
                         .with_block(&block)
                         .with_tx(&tx)
@@ -429,7 +713,7 @@ pub fn execute_test_suite(
                     let res = ctx.inspect_commit_previous(
                         TracerEip3155::new(Box::new(stderr())).without_summary(),
                     );
-                    *elapsed.lock().unwrap() += timer.elapsed();
+                    *run.elapsed.lock().unwrap() += timer.elapsed();
 
                     let spec = cfg.spec();
                     let db = &mut ctx.journaled_state.database;
@@ -441,16 +725,17 @@ pub fn execute_test_suite(
                         &res,
                         db,
                         spec,
-                        print_json_outcome,
+                        config.json_outcome,
                     );
                     let Err(e) = output else {
+                        outcomes.push(outcome);
                         continue;
                     };
                     (e, res)
                 } else {
                     let timer = Instant::now();
                     let res = ctx.exec_commit_previous();
-                    *elapsed.lock().unwrap() += timer.elapsed();
+                    *run.elapsed.lock().unwrap() += timer.elapsed();
 
                     let spec = cfg.spec();
                     let db = ctx.journaled_state.database;
@@ -462,173 +747,142 @@ pub fn execute_test_suite(
                         &res,
                         db,
                         spec,
-                        print_json_outcome,
+                        config.json_outcome,
                     );
                     let Err(e) = output else {
+                        outcomes.push(outcome);
                         continue;
                     };
                     (e, res)
                 };
+                run.failed.store(true, Ordering::SeqCst);
+
+                // Print only once or if we are already in trace mode, just record the failure.
+                // If trace is true that json_outcome will be also true.
+                if config.dump_first_failure
+                    && !config.json_outcome
+                    && !run.dumped.swap(true, Ordering::SeqCst)
+                {
+                    // Re-build to run with tracing
+                    let mut cache = cache_state.clone();
+                    cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+                    let mut state = database::State::builder()
+                        .with_cached_prestate(cache)
+                        .with_bundle_update()
+                        .build();
+
+                    println!("\nTraces:");
 
-                // Print only once or if we are already in trace mode, just return error
-                // If trace is true that print_json_outcome will be also true.
-                static FAILED: AtomicBool = AtomicBool::new(false);
-                if print_json_outcome || FAILED.swap(true, Ordering::SeqCst) {
-                    return Err(TestError {
-                        name: name.clone(),
-                        path: path.clone(),
-                        kind: e,
-                    });
-                }
+                    let mut ctx = Context::builder()
+                        .with_db(&mut state)
+                        .with_block(&block)
+                        .with_tx(&tx)
+                        .with_cfg(&cfg);
 
-                // Re-build to run with tracing
-                let mut cache = cache_state.clone();
-                cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
-                let mut state = database::State::builder()
-                    .with_cached_prestate(cache)
-                    .with_bundle_update()
-                    .build();
+                    let _ = ctx.inspect_commit_previous(
+                        TracerEip3155::new(Box::new(stderr())).without_summary(),
+                    );
 
-                println!("\nTraces:");
+                    println!("\nExecution result: {exec_result:#?}");
+                    println!("\nExpected exception: {:?}", test.expect_exception);
+                    println!("\nState before: {cache_state:#?}");
+                    println!("\nState after: {:#?}", ctx.journaled_state.database.cache);
+                    println!("\nSpecification: {:?}", cfg.spec);
+                    println!("\nTx: {tx:#?}");
+                    println!("Block: {block:#?}");
+                    println!("Cfg: {cfg:#?}");
+                    println!(
+                        "\nTest name: {name:?} (index: {index}, path: {path_str:?}) failed:\n{e}"
+                    );
+                }
 
-                let mut ctx = Context::builder()
-                    .with_db(&mut state)
-                    .with_block(&block)
-                    .with_tx(&tx)
-                    .with_cfg(&cfg);
-
-                let _ = ctx.inspect_commit_previous(
-                    TracerEip3155::new(Box::new(stderr())).without_summary(),
-                );
-
-                println!("\nExecution result: {exec_result:#?}");
-                println!("\nExpected exception: {:?}", test.expect_exception);
-                println!("\nState before: {cache_state:#?}");
-                println!("\nState after: {:#?}", ctx.journaled_state.database.cache);
-                println!("\nSpecification: {:?}", cfg.spec);
-                println!("\nTx: {tx:#?}");
-                println!("Block: {block:#?}");
-                println!("Cfg: {cfg:#?}");
-                println!("\nTest name: {name:?} (index: {index}, path: {path:?}) failed:\n{e}");
-
-                return Err(TestError {
-                    path: path.clone(),
-                    name: name.clone(),
-                    kind: e,
+                outcomes.push(TestOutcome {
+                    status: TestStatus::Fail(e),
+                    ..outcome
                 });
             }
         }
     }
-    Ok(())
+    outcomes
 }
 
-pub fn run(
-    test_files: Vec<PathBuf>,
-    mut single_thread: bool,
-    trace: bool,
-    mut print_outcome: bool,
-    keep_going: bool,
-) -> Result<(), TestError> {
-    // Trace implies print_outcome
-    if trace {
-        print_outcome = true;
-    }
-    // `print_outcome` or trace implies single_thread
-    if print_outcome {
-        single_thread = true;
+/// Runs the state tests of `test_files` in parallel and reports the outcome of every test.
+///
+/// Files are distributed between the threads, a panic while running a file is reported as the
+/// failure of the whole file.
+pub fn run_tests(test_files: Vec<PathBuf>, config: &RunnerConfig) -> Report {
+    let mut config = config.clone();
+    // Trace implies json_outcome
+    if config.trace {
+        config.json_outcome = true;
     }
+    let config = &config;
     let n_files = test_files.len();
 
-    let n_errors = Arc::new(AtomicUsize::new(0));
-    let console_bar = Arc::new(ProgressBar::with_draw_target(
+    let console_bar = ProgressBar::with_draw_target(
         Some(n_files as u64),
-        ProgressDrawTarget::stdout(),
-    ));
-    let queue = Arc::new(Mutex::new((0usize, test_files)));
-    let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
-
-    let num_threads = match (single_thread, std::thread::available_parallelism()) {
-        (true, _) | (false, Err(_)) => 1,
-        (false, Ok(n)) => n.get(),
+        if config.progress {
+            ProgressDrawTarget::stdout()
+        } else {
+            ProgressDrawTarget::hidden()
+        },
+    );
+    let queue = Mutex::new(test_files.iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(n_files));
+    let run = RunState::default();
+
+    // `json_outcome` or trace implies single thread
+    let num_threads = match (config.threads, std::thread::available_parallelism()) {
+        _ if config.json_outcome => 1,
+        (0, Ok(n)) => n.get(),
+        (0, Err(_)) => 1,
+        (n, _) => n,
     };
     let num_threads = num_threads.min(n_files);
-    let mut handles = Vec::with_capacity(num_threads);
-    for i in 0..num_threads {
-        let queue = queue.clone();
-        let n_errors = n_errors.clone();
-        let console_bar = console_bar.clone();
-        let elapsed = elapsed.clone();
-
-        let thread = std::thread::Builder::new().name(format!("runner-{i}"));
-
-        let f = move || loop {
-            if !keep_going && n_errors.load(Ordering::SeqCst) > 0 {
-                return Ok(());
-            }
-
-            let (_index, test_path) = {
-                let (current_idx, queue) = &mut *queue.lock().unwrap();
-                let prev_idx = *current_idx;
-                let Some(test_path) = queue.get(prev_idx).cloned() else {
-                    return Ok(());
+    std::thread::scope(|scope| {
+        for i in 0..num_threads {
+            let worker = || loop {
+                if run.stopped(config) {
+                    return;
+                }
+                let Some((index, test_path)) = queue.lock().unwrap().next() else {
+                    return;
                 };
-                *current_idx = prev_idx + 1;
-                (prev_idx, test_path)
-            };
-
-            let result = execute_test_suite(&test_path, &elapsed, trace, print_outcome);
 
-            // Increment after the test is done.
-            console_bar.inc(1);
-
-            if let Err(err) = result {
-                n_errors.fetch_add(1, Ordering::SeqCst);
-                if !keep_going {
-                    return Err(err);
-                }
-            }
-        };
-        handles.push(thread.spawn(f).unwrap());
-    }
+                let outcomes = catch_unwind(AssertUnwindSafe(|| {
+                    execute_test_suite(test_path, config, &run)
+                }))
+                .unwrap_or_else(|_| {
+                    run.failed.store(true, Ordering::SeqCst);
+                    let path = test_path.to_string_lossy();
+                    vec![TestOutcome::new(
+                        &path,
+                        None,
+                        None,
+                        TestStatus::Fail(TestErrorKind::Panic),
+                    )]
+                });
 
-    // join all threads before returning an error
-    let mut thread_errors = Vec::new();
-    for (i, handle) in handles.into_iter().enumerate() {
-        match handle.join() {
-            Ok(Ok(())) => {}
-            Ok(Err(e)) => thread_errors.push(e),
-            Err(_) => thread_errors.push(TestError {
-                name: format!("thread {i} panicked"),
-                path: "".to_string(),
-                kind: TestErrorKind::Panic,
-            }),
+                // Increment after the test is done.
+                console_bar.inc(1);
+                results.lock().unwrap().push((index, outcomes));
+            };
+            std::thread::Builder::new()
+                .name(format!("runner-{i}"))
+                .spawn_scoped(scope, worker)
+                .unwrap();
         }
-    }
+    });
     console_bar.finish();
 
-    println!(
-        "Finished execution. Total CPU time: {:.6}s",
-        elapsed.lock().unwrap().as_secs_f64()
-    );
-
-    let n_errors = n_errors.load(Ordering::SeqCst);
-    let n_thread_errors = thread_errors.len();
-    if n_errors == 0 && n_thread_errors == 0 {
-        println!("All tests passed!");
-        Ok(())
-    } else {
-        println!("Encountered {n_errors} errors out of {n_files} total tests");
-
-        if n_thread_errors == 0 {
-            std::process::exit(1);
-        }
-
-        if n_thread_errors > 1 {
-            println!("{n_thread_errors} threads returned an error, out of {num_threads} total:");
-            for error in &thread_errors {
-                println!("{error}");
-            }
-        }
-        Err(thread_errors.swap_remove(0))
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    let mut report = Report {
+        cpu_time: run.elapsed.into_inner().unwrap().as_secs_f64(),
+        ..Report::default()
+    };
+    for outcome in results.into_iter().flat_map(|(_, outcomes)| outcomes) {
+        report.push(outcome);
     }
+    report
 }
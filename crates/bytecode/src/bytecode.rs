@@ -205,6 +205,24 @@ impl Bytecode {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Bytecode {
+    /// Generates legacy bytecode, EIP-7702 delegations and EOF containers.
+    ///
+    /// EOF containers are raw bytes prefixed with the EOF magic, generated as legacy bytecode
+    /// if they fail to decode.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0u8..=2)? {
+            0 => Self::new_legacy(u.arbitrary()?),
+            1 => Self::new_eip7702(u.arbitrary()?),
+            _ => {
+                let raw: Bytes = [&EOF_MAGIC_BYTES[..], u.arbitrary()?].concat().into();
+                Self::new_raw_checked(raw.clone()).unwrap_or_else(|_| Self::new_legacy(raw))
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Bytecode, Eof};
@@ -227,4 +245,19 @@ mod tests {
             panic!("Original bytecode is not Eof");
         }
     }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_bytecode() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data: std::vec::Vec<u8> = (0..=255).rev().collect();
+        let mut u = Unstructured::new(&data);
+        let mut generated = [false; 2];
+        while !u.is_empty() {
+            let bytecode = Bytecode::arbitrary(&mut u).unwrap();
+            generated[bytecode.is_eip7702() as usize] = true;
+        }
+        assert_eq!(generated, [true, true]);
+    }
 }
@@ -24,7 +24,15 @@ impl From<Eip7702DecodeError> for BytecodeDecodeError {
     }
 }
 
-impl core::error::Error for BytecodeDecodeError {}
+impl core::error::Error for BytecodeDecodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        // Displayed as the wrapped error, so its source is the next one.
+        match self {
+            Self::Eof(e) => e.source(),
+            Self::Eip7702(e) => e.source(),
+        }
+    }
+}
 
 impl fmt::Display for BytecodeDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -164,7 +164,14 @@ impl fmt::Display for EofError {
     }
 }
 
-impl core::error::Error for EofError {}
+impl core::error::Error for EofError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EofError::Decode(e) => Some(e),
+            EofError::Validation(e) => Some(e),
+        }
+    }
+}
 
 /// EOF Validation Error
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
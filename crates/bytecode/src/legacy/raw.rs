@@ -14,13 +14,28 @@ impl LegacyRawBytecode {
         analyze_legacy(&self.0)
     }
 
+    /// Analyzes and pads the bytecode.
+    ///
+    /// If the bytes are not shared, e.g. the output of a create frame, their allocation is grown
+    /// by the padding instead of being copied, so large code is not held in memory twice.
     pub fn into_analyzed(self) -> LegacyAnalyzedBytecode {
         let jump_table = self.analysis();
         let len = self.0.len();
-        let mut padded_bytecode = Vec::with_capacity(len + 33);
-        padded_bytecode.extend_from_slice(&self.0);
+        let mut padded_bytecode = match self.0 .0.try_into_mut() {
+            Ok(unique) => {
+                // Grows the allocation by the padding only, `BytesMut` would double it.
+                let mut padded_bytecode = Vec::from(unique);
+                padded_bytecode.reserve_exact(33);
+                padded_bytecode
+            }
+            Err(shared) => {
+                let mut padded_bytecode = Vec::with_capacity(len + 33);
+                padded_bytecode.extend_from_slice(&shared);
+                padded_bytecode
+            }
+        };
         padded_bytecode.resize(len + 33, 0);
-        LegacyAnalyzedBytecode::new(padded_bytecode.into(), len, jump_table)
+        LegacyAnalyzedBytecode::new(Bytes::from(padded_bytecode), len, jump_table)
     }
 }
 
@@ -72,3 +87,25 @@ pub fn analyze_legacy(bytetecode: &[u8]) -> JumpTable {
 
     JumpTable(Arc::new(jumps))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_unique_bytes_in_place() {
+        let mut code = Vec::with_capacity(100_033);
+        code.resize(100_000, opcode::JUMPDEST);
+        let ptr = code.as_ptr();
+        let analyzed = LegacyRawBytecode(code.into()).into_analyzed();
+        assert_eq!(analyzed.bytecode().as_ptr(), ptr);
+        assert_eq!(analyzed.bytecode().len(), 100_033);
+        assert_eq!(analyzed.original_len(), 100_000);
+        assert!(analyzed.bytecode()[100_000..].iter().all(|byte| *byte == 0));
+
+        let shared = Bytes::from(vec![opcode::JUMPDEST; 100]);
+        let analyzed = LegacyRawBytecode(shared.clone()).into_analyzed();
+        assert_ne!(analyzed.bytecode().as_ptr(), shared.as_ptr());
+        assert_eq!(analyzed.original_bytes(), shared);
+    }
+}
@@ -48,8 +48,12 @@ pub trait Block {
     ///
     /// Incorporated as part of the Cancun upgrade via [EIP-4844].
     ///
+    /// Returns `None` if `Cancun` is not enabled.
+    ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
-    fn blob_excess_gas_and_price(&self) -> Option<BlobExcessGasAndPrice>;
+    fn blob_excess_gas_and_price(&self) -> Option<BlobExcessGasAndPrice> {
+        None
+    }
 
     /// See [EIP-4844] and [`calc_blob_gasprice`].
     ///
@@ -68,6 +72,16 @@ pub trait Block {
     fn blob_excess_gas(&self) -> Option<u64> {
         self.blob_excess_gas_and_price().map(|a| a.excess_blob_gas)
     }
+
+    /// The root of the parent beacon block, stored in the beacon roots contract before the
+    /// transactions of the block. See [EIP-4788].
+    ///
+    /// Returns `None` if `Cancun` is not enabled.
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    fn parent_beacon_block_root(&self) -> Option<B256> {
+        None
+    }
 }
 
 #[auto_impl(&, &mut, Box, Arc)]
@@ -92,3 +106,49 @@ impl<T: BlockSetter> BlockSetter for Box<T> {
         (**self).set_block(block)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct PreCancunBlock;
+
+    impl Block for PreCancunBlock {
+        fn number(&self) -> u64 {
+            1
+        }
+
+        fn beneficiary(&self) -> Address {
+            Address::ZERO
+        }
+
+        fn timestamp(&self) -> u64 {
+            1
+        }
+
+        fn gas_limit(&self) -> u64 {
+            30_000_000
+        }
+
+        fn basefee(&self) -> u64 {
+            7
+        }
+
+        fn difficulty(&self) -> U256 {
+            U256::ZERO
+        }
+
+        fn prevrandao(&self) -> Option<B256> {
+            Some(B256::ZERO)
+        }
+    }
+
+    #[test]
+    fn cancun_defaults() {
+        let block = PreCancunBlock;
+        assert_eq!(block.blob_excess_gas_and_price(), None);
+        assert_eq!(block.blob_gasprice(), None);
+        assert_eq!(block.blob_excess_gas(), None);
+        assert_eq!(block.parent_beacon_block_root(), None);
+    }
+}
@@ -22,15 +22,29 @@ pub trait Cfg {
 
     fn is_eip3607_disabled(&self) -> bool;
 
-    fn is_balance_check_disabled(&self) -> bool;
+    /// Skips the check that the caller can pay the maximum fee and the value of the transaction.
+    ///
+    /// Disabled checks let simulations like `eth_call` run without a custom handler.
+    fn is_balance_check_disabled(&self) -> bool {
+        false
+    }
 
     fn is_gas_refund_disabled(&self) -> bool;
 
-    fn is_block_gas_limit_disabled(&self) -> bool;
+    /// Skips the check that the gas limit of the transaction fits in the one of the block.
+    fn is_block_gas_limit_disabled(&self) -> bool {
+        false
+    }
 
-    fn is_nonce_check_disabled(&self) -> bool;
+    /// Skips the check of the transaction nonce against the nonce of the caller.
+    fn is_nonce_check_disabled(&self) -> bool {
+        false
+    }
 
-    fn is_base_fee_check_disabled(&self) -> bool;
+    /// Skips the check that the gas price of the transaction covers the base fee of the block.
+    fn is_base_fee_check_disabled(&self) -> bool {
+        false
+    }
 }
 
 /// What bytecode analysis to perform
@@ -1,4 +1,7 @@
-use core::ops::{Deref, DerefMut};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
 use database_interface::{Database, DatabaseGetter};
 use primitives::{Address, Bytes, HashSet, Log, B256, U256};
 use specification::hardfork::SpecId;
@@ -156,6 +159,18 @@ pub enum TransferError {
     CreateCollision,
 }
 
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfFunds => f.write_str("caller does not have enough funds"),
+            Self::OverflowPayment => f.write_str("balance of the target overflows"),
+            Self::CreateCollision => f.write_str("created address already has code or nonce"),
+        }
+    }
+}
+
+impl core::error::Error for TransferError {}
+
 /// SubRoutine checkpoint that will help us to go back from this
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -11,6 +11,9 @@ pub mod context;
 pub mod errors;
 pub mod host;
 pub mod journaled_state;
+#[cfg(feature = "serde")]
+mod quantity;
+pub mod receipt;
 pub mod result;
 pub mod transaction;
 
@@ -0,0 +1,59 @@
+//! Serde of integers as RPC quantities, `0x` followed by the hex digits without leading zeros.
+//!
+//! Formats that are not human readable keep the plain integer.
+use core::fmt;
+use serde::{
+    de::{self, Unexpected, Visitor},
+    Deserialize, Deserializer, Serializer,
+};
+
+pub(crate) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<u128>,
+    S: Serializer,
+{
+    let value: u128 = (*value).into();
+    if serializer.is_human_readable() {
+        serializer.collect_str(&format_args!("{value:#x}"))
+    } else {
+        serializer.serialize_u128(value)
+    }
+}
+
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: TryFrom<u128>,
+    D: Deserializer<'de>,
+{
+    let value = if deserializer.is_human_readable() {
+        deserializer.deserialize_any(QuantityVisitor)?
+    } else {
+        u128::deserialize(deserializer)?
+    };
+    T::try_from(value).map_err(|_| de::Error::custom(format_args!("quantity {value:#x} overflows")))
+}
+
+struct QuantityVisitor;
+
+impl Visitor<'_> for QuantityVisitor {
+    type Value = u128;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a hex quantity")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u128, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<u128, E> {
+        Ok(value)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u128, E> {
+        value
+            .strip_prefix("0x")
+            .and_then(|digits| u128::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+    }
+}
@@ -0,0 +1,124 @@
+//! Receipts of the transactions of a block, built from their execution results.
+use crate::result::{CumulativeBloom, ExecutionResult, HaltReasonTrait};
+use primitives::{Bloom, Log};
+use std::vec::Vec;
+
+/// Receipt of a transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Receipt {
+    /// Type of the transaction, see [`Transaction::tx_type`][crate::Transaction::tx_type].
+    #[cfg_attr(feature = "serde", serde(rename = "type", with = "crate::quantity"))]
+    pub tx_type: u8,
+    /// Whether execution was successful.
+    pub success: bool,
+    /// Gas used by the transaction and the ones before it in the block.
+    #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+    pub cumulative_gas_used: u64,
+    /// Bloom filter of the logs.
+    pub logs_bloom: Bloom,
+    /// Logs emitted by the transaction, empty if execution was not successful.
+    pub logs: Vec<Log>,
+}
+
+/// Builds the receipts of consecutive transactions, e.g. of a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReceiptBuilder {
+    cumulative_gas_used: u64,
+    bloom: CumulativeBloom,
+}
+
+impl ReceiptBuilder {
+    /// Creates a builder for the first transaction of a block.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the receipt of the next transaction.
+    pub fn build<HaltReasonT: HaltReasonTrait>(
+        &mut self,
+        tx_type: u8,
+        result: &ExecutionResult<HaltReasonT>,
+    ) -> Receipt {
+        self.cumulative_gas_used += result.gas_used();
+        Receipt {
+            tx_type,
+            success: result.is_success(),
+            cumulative_gas_used: self.cumulative_gas_used,
+            logs_bloom: self.bloom.accrue_result(result),
+            logs: result.logs().to_vec(),
+        }
+    }
+
+    /// Returns the gas used by the transactions so far.
+    pub fn cumulative_gas_used(&self) -> u64 {
+        self.cumulative_gas_used
+    }
+
+    /// Returns the bloom filter of the logs of the transactions so far, e.g. of the block header.
+    pub fn logs_bloom(&self) -> Bloom {
+        self.bloom.bloom()
+    }
+}
+
+/// Returns the receipts of consecutive transactions, given the type and result of each.
+pub fn build_receipts<'a, HaltReasonT: HaltReasonTrait + 'a>(
+    results: impl IntoIterator<Item = (u8, &'a ExecutionResult<HaltReasonT>)>,
+) -> Vec<Receipt> {
+    let mut builder = ReceiptBuilder::new();
+    results
+        .into_iter()
+        .map(|(tx_type, result)| builder.build(tx_type, result))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::{HaltReason, Output, SuccessReason};
+    use primitives::{Address, Bytes};
+    use std::vec;
+
+    #[test]
+    fn cumulative_receipts() {
+        let log = Log::new_unchecked(Address::with_last_byte(1), vec![], Bytes::new());
+        let success = ExecutionResult::<HaltReason>::Success {
+            reason: SuccessReason::Stop,
+            gas_used: 30_000,
+            gas_refunded: 0,
+            effective_gas_price: 1,
+            logs: vec![log.clone()],
+            output: Output::Call(Bytes::new()),
+        };
+        let revert = ExecutionResult::<HaltReason>::Revert {
+            gas_used: 21_000,
+            gas_refunded: 0,
+            effective_gas_price: 1,
+            output: Bytes::new(),
+        };
+
+        let receipts = build_receipts([(2, &success), (0, &revert)]);
+        let mut bloom = Bloom::ZERO;
+        bloom.accrue_log(&log);
+        assert_eq!(
+            receipts,
+            vec![
+                Receipt {
+                    tx_type: 2,
+                    success: true,
+                    cumulative_gas_used: 30_000,
+                    logs_bloom: bloom,
+                    logs: vec![log],
+                },
+                Receipt {
+                    tx_type: 0,
+                    success: false,
+                    cumulative_gas_used: 51_000,
+                    logs_bloom: Bloom::ZERO,
+                    logs: vec![],
+                },
+            ]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::transaction::TransactionError;
 use core::fmt::{self, Debug};
 use database_interface::DBErrorMarker;
-use primitives::{Address, Bytes, Log, U256};
+use primitives::{Address, Bloom, Bytes, Log, U256};
 use state::EvmState;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -32,27 +32,69 @@ impl<HaltReasonT: HaltReasonTrait> ResultAndState<HaltReasonT> {
             state: self.state,
         }
     }
+
+    /// Returns the bloom filter of the logs, see [`ExecutionResult::logs_bloom`].
+    pub fn logs_bloom(&self) -> Bloom {
+        self.result.logs_bloom()
+    }
 }
 
 /// Result of a transaction execution
+///
+/// Serialized with a `status` of `success`, `revert` or `halt`, camel case fields and gas values as
+/// hex quantities, as in RPC responses.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        tag = "status",
+        rename_all = "camelCase",
+        rename_all_fields = "camelCase"
+    )
+)]
 pub enum ExecutionResult<HaltReasonT: HaltReasonTrait> {
     /// Returned successfully
     Success {
         reason: SuccessReason,
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
         gas_used: u64,
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
         gas_refunded: u64,
+        /// Price per gas paid by the caller, including the priority fee.
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+        effective_gas_price: u128,
         logs: Vec<Log>,
         output: Output,
     },
     /// Reverted by `REVERT` opcode that doesn't spend all gas
-    Revert { gas_used: u64, output: Bytes },
+    Revert {
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+        gas_used: u64,
+        /// Always zero as refunds are only applied to successful transactions.
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+        gas_refunded: u64,
+        /// Price per gas paid by the caller, including the priority fee.
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+        effective_gas_price: u128,
+        output: Bytes,
+    },
     /// Reverted for various reasons and spend all gas
     Halt {
         reason: HaltReasonT,
         /// Halting will spend all the gas, and will be equal to gas_limit.
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
         gas_used: u64,
+        /// Price per gas paid by the caller, including the priority fee.
+        #[cfg_attr(feature = "serde", serde(with = "crate::quantity"))]
+        effective_gas_price: u128,
+        /// Instruction that halted, [`None`] if the halt did not come from the bytecode, e.g. a
+        /// precompile failed or the created code was rejected.
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        debug: Option<Box<HaltDebug>>,
     },
 }
 
@@ -76,19 +118,38 @@ impl<HaltReasonT: HaltReasonTrait> ExecutionResult<HaltReasonT> {
                 reason,
                 gas_used,
                 gas_refunded,
+                effective_gas_price,
                 logs,
                 output,
             } => ExecutionResult::Success {
                 reason,
                 gas_used,
                 gas_refunded,
+                effective_gas_price,
                 logs,
                 output,
             },
-            Self::Revert { gas_used, output } => ExecutionResult::Revert { gas_used, output },
-            Self::Halt { reason, gas_used } => ExecutionResult::Halt {
+            Self::Revert {
+                gas_used,
+                gas_refunded,
+                effective_gas_price,
+                output,
+            } => ExecutionResult::Revert {
+                gas_used,
+                gas_refunded,
+                effective_gas_price,
+                output,
+            },
+            Self::Halt {
+                reason,
+                gas_used,
+                effective_gas_price,
+                debug,
+            } => ExecutionResult::Halt {
                 reason: op(reason),
                 gas_used,
+                effective_gas_price,
+                debug,
             },
         }
     }
@@ -145,6 +206,70 @@ impl<HaltReasonT: HaltReasonTrait> ExecutionResult<HaltReasonT> {
         }
     }
 
+    /// Returns the bloom filter of the logs, as in the receipt of the transaction.
+    ///
+    /// Empty if execution was not successful, as only successful executions keep their logs.
+    pub fn logs_bloom(&self) -> Bloom {
+        let mut bloom = Bloom::ZERO;
+        for log in self.logs() {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    /// Decodes the revert data if execution was reverted.
+    ///
+    /// Returns [`None`] if execution did not revert or the revert data is empty.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        match self {
+            Self::Revert { output, .. } => RevertReason::decode(output),
+            _ => None,
+        }
+    }
+
+    /// Returns the message of a `revert("...")` or a failed `require(..., "...")`.
+    pub fn revert_string(&self) -> Option<String> {
+        match self.revert_reason()? {
+            RevertReason::Error(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// Returns the code of a Solidity panic, see [`RevertReason::panic_description`].
+    pub fn panic_code(&self) -> Option<U256> {
+        match self.revert_reason()? {
+            RevertReason::Panic(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Decodes the output of a successful execution as a value of `T`, e.g. the return value of a
+    /// call.
+    ///
+    /// Returns [`None`] if execution was not successful.
+    #[cfg(feature = "alloy-sol-types")]
+    pub fn decode_output<T: alloy_sol_types::SolType>(
+        &self,
+    ) -> Option<alloy_sol_types::Result<T::RustType>> {
+        match self {
+            Self::Success { output, .. } => Some(T::abi_decode(output.data(), true)),
+            _ => None,
+        }
+    }
+
+    /// Decodes the revert data as the custom error `E`, including its selector.
+    ///
+    /// Returns [`None`] if execution did not revert.
+    #[cfg(feature = "alloy-sol-types")]
+    pub fn decode_revert<E: alloy_sol_types::SolError>(
+        &self,
+    ) -> Option<alloy_sol_types::Result<E>> {
+        match self {
+            Self::Revert { output, .. } => Some(E::abi_decode(output, true)),
+            _ => None,
+        }
+    }
+
     /// Returns the gas used.
     pub fn gas_used(&self) -> u64 {
         match *self {
@@ -153,11 +278,104 @@ impl<HaltReasonT: HaltReasonTrait> ExecutionResult<HaltReasonT> {
             | Self::Halt { gas_used, .. } => gas_used,
         }
     }
+
+    /// Returns the gas refunded, zero if the execution did not succeed.
+    pub fn gas_refunded(&self) -> u64 {
+        match *self {
+            Self::Success { gas_refunded, .. } | Self::Revert { gas_refunded, .. } => gas_refunded,
+            Self::Halt { .. } => 0,
+        }
+    }
+
+    /// Returns the price per gas paid by the caller.
+    pub fn effective_gas_price(&self) -> u128 {
+        match *self {
+            Self::Success {
+                effective_gas_price,
+                ..
+            }
+            | Self::Revert {
+                effective_gas_price,
+                ..
+            }
+            | Self::Halt {
+                effective_gas_price,
+                ..
+            } => effective_gas_price,
+        }
+    }
+
+    /// Returns the fee paid by the caller, gas used times the effective gas price.
+    pub fn fee(&self) -> u128 {
+        (self.gas_used() as u128).saturating_mul(self.effective_gas_price())
+    }
+}
+
+/// Bloom filter of the logs of consecutive transactions, e.g. the logs bloom of a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CumulativeBloom {
+    bloom: Bloom,
+}
+
+impl CumulativeBloom {
+    /// Creates an empty bloom.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the logs of a transaction, returning the bloom of its receipt.
+    pub fn accrue_result<HaltReasonT: HaltReasonTrait>(
+        &mut self,
+        result: &ExecutionResult<HaltReasonT>,
+    ) -> Bloom {
+        let bloom = result.logs_bloom();
+        self.bloom.accrue_bloom(&bloom);
+        bloom
+    }
+
+    /// Adds a log.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.bloom.accrue_log(log);
+    }
+
+    /// Returns the bloom of the logs added so far.
+    pub fn bloom(&self) -> Bloom {
+        self.bloom
+    }
+}
+
+/// Location of the instruction that halted a frame.
+///
+/// Allows triaging an out of gas or an invalid opcode without tracing the transaction again.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HaltDebug {
+    /// Program counter of the instruction.
+    pub pc: usize,
+    /// Opcode of the instruction.
+    pub opcode: u8,
+    /// Call depth of the frame, 0 for the frame of the transaction.
+    pub depth: usize,
+    /// Address of the account whose code was executed.
+    pub address: Address,
+}
+
+impl fmt::Display for HaltDebug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "opcode 0x{:02x} at pc {} of {} at depth {}",
+            self.opcode, self.pc, self.address, self.depth
+        )
+    }
 }
 
 /// Output of a transaction execution
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum Output {
     Call(Bytes),
     Create(Bytes, Option<Address>),
@@ -189,6 +407,99 @@ impl Output {
     }
 }
 
+/// Decoded revert data of a `REVERT`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// `Error(string)` emitted by `revert("...")` and `require(..., "...")`.
+    Error(String),
+    /// `Panic(uint256)` emitted by failed `assert`, arithmetic overflow and similar checks.
+    Panic(U256),
+    /// Custom error, selector followed by the ABI encoded arguments.
+    Custom { selector: [u8; 4], data: Bytes },
+    /// Revert data that is too short to contain a selector.
+    Raw(Bytes),
+}
+
+impl RevertReason {
+    /// Selector of `Error(string)`.
+    pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    /// Selector of `Panic(uint256)`.
+    pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    /// Decodes revert data.
+    ///
+    /// `Error(string)` and `Panic(uint256)` with malformed arguments are returned as
+    /// [`RevertReason::Custom`]. Returns [`None`] if `data` is empty.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+        let Some((selector, args)) = data.split_first_chunk::<4>() else {
+            return Some(Self::Raw(Bytes::copy_from_slice(data)));
+        };
+        let decoded = match *selector {
+            Self::ERROR_SELECTOR => decode_abi_string(args).map(Self::Error),
+            Self::PANIC_SELECTOR if args.len() == 32 => {
+                Some(Self::Panic(U256::from_be_slice(args)))
+            }
+            _ => None,
+        };
+        Some(decoded.unwrap_or_else(|| Self::Custom {
+            selector: *selector,
+            data: Bytes::copy_from_slice(args),
+        }))
+    }
+
+    /// Returns the description of a known Solidity panic code.
+    pub fn panic_description(code: U256) -> Option<&'static str> {
+        Some(match code.try_into().ok()? {
+            0x00u8 => "generic panic",
+            0x01 => "assertion failed",
+            0x11 => "arithmetic underflow or overflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "invalid enum value",
+            0x22 => "invalid storage byte array encoding",
+            0x31 => "pop on empty array",
+            0x32 => "array index out of bounds",
+            0x41 => "out of memory",
+            0x51 => "call to uninitialized function",
+            _ => return None,
+        })
+    }
+}
+
+/// Decodes ABI encoded `string` arguments. Invalid UTF-8 is replaced.
+fn decode_abi_string(args: &[u8]) -> Option<String> {
+    let word = |offset: usize| -> Option<usize> {
+        let word = args.get(offset..offset.checked_add(32)?)?;
+        usize::try_from(U256::from_be_slice(word)).ok()
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = args.get(start..start.checked_add(len)?)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(reason) => f.write_str(reason),
+            Self::Panic(code) => match Self::panic_description(*code) {
+                Some(description) => write!(f, "panic: {description} (0x{code:02x})"),
+                None => write!(f, "panic: 0x{code:02x}"),
+            },
+            Self::Custom { selector, data } => write!(
+                f,
+                "custom error 0x{}: {data}",
+                primitives::hex::encode(selector)
+            ),
+            Self::Raw(data) => write!(f, "{data}"),
+        }
+    }
+}
+
 /// Main EVM error
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -470,6 +781,7 @@ impl fmt::Display for InvalidHeader {
 /// Reason a transaction successfully completed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum SuccessReason {
     Stop,
     Return,
@@ -482,6 +794,7 @@ pub enum SuccessReason {
 /// This causes execution to immediately end with all gas being consumed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum HaltReason {
     OutOfGas(OutOfGasError),
     OpcodeNotFound,
@@ -516,10 +829,13 @@ pub enum HaltReason {
     SubRoutineStackOverflow,
     /// Check for target address validity is only done inside subcall.
     InvalidEXTCALLTarget,
+    /// EOF code that was not validated is malformed, e.g. it calls a missing code section.
+    InvalidEOFCode,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum OutOfGasError {
     // Basic OOG error
     Basic,
@@ -535,3 +851,203 @@ pub enum OutOfGasError {
     // When performing SSTORE the gasleft is less than or equal to 2300
     ReentrancySentry,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{bytes, hex};
+
+    #[test]
+    fn decode_revert_reason() {
+        assert_eq!(RevertReason::decode(&[]), None);
+        assert_eq!(
+            RevertReason::decode(&[0x01, 0x02]),
+            Some(RevertReason::Raw(bytes!("0102")))
+        );
+
+        // Error("revert")
+        let error = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000006"
+            "7265766572740000000000000000000000000000000000000000000000000000"
+        );
+        let reason = RevertReason::decode(&error).unwrap();
+        assert_eq!(reason, RevertReason::Error("revert".into()));
+        assert_eq!(reason.to_string(), "revert");
+
+        // Panic(0x11)
+        let panic = hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000011"
+        );
+        let reason = RevertReason::decode(&panic).unwrap();
+        assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+        assert_eq!(
+            reason.to_string(),
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+
+        // Error(string) with out of bounds length is kept as custom error.
+        let custom = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "00000000000000000000000000000000000000000000000000000000000000ff"
+        );
+        assert_eq!(
+            RevertReason::decode(&custom),
+            Some(RevertReason::Custom {
+                selector: RevertReason::ERROR_SELECTOR,
+                data: custom[4..].to_vec().into(),
+            })
+        );
+
+        let result = ExecutionResult::<HaltReason>::Revert {
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            output: panic.into(),
+        };
+        assert_eq!(
+            result.revert_reason(),
+            Some(RevertReason::Panic(U256::from(0x11)))
+        );
+        assert_eq!(result.panic_code(), Some(U256::from(0x11)));
+        assert_eq!(result.revert_string(), None);
+    }
+
+    #[cfg(feature = "alloy-sol-types")]
+    #[test]
+    fn decode_sol_types() {
+        use alloy_sol_types::{sol, sol_data, SolError, SolValue};
+
+        sol! {
+            #[derive(Debug, PartialEq)]
+            error Unauthorized(address caller);
+        }
+
+        let caller = Address::with_last_byte(1);
+        let success = ExecutionResult::<HaltReason>::Success {
+            reason: SuccessReason::Return,
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            logs: vec![],
+            output: Output::Call((U256::from(7), caller).abi_encode().into()),
+        };
+        assert_eq!(
+            success
+                .decode_output::<(sol_data::Uint<256>, sol_data::Address)>()
+                .unwrap()
+                .unwrap(),
+            (U256::from(7), caller)
+        );
+        assert!(success.decode_revert::<Unauthorized>().is_none());
+
+        let revert = ExecutionResult::<HaltReason>::Revert {
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            output: Unauthorized { caller }.abi_encode().into(),
+        };
+        assert_eq!(
+            revert.decode_revert::<Unauthorized>().unwrap().unwrap(),
+            Unauthorized { caller }
+        );
+        assert!(revert.decode_output::<sol_data::Address>().is_none());
+    }
+
+    #[test]
+    fn logs_bloom() {
+        let log = |address: Address, topic: u8| {
+            Log::new_unchecked(address, vec![[topic; 32].into()], Bytes::new())
+        };
+        let first = log(Address::with_last_byte(1), 1);
+        let second = log(Address::with_last_byte(2), 2);
+        let success = |logs| ExecutionResult::<HaltReason>::Success {
+            reason: SuccessReason::Stop,
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            logs,
+            output: Output::Call(Bytes::new()),
+        };
+
+        let mut cumulative = CumulativeBloom::new();
+        let bloom = cumulative.accrue_result(&success(vec![first.clone()]));
+        assert!(bloom.contains_log(&first));
+        assert!(!bloom.contains_log(&second));
+        let revert = ExecutionResult::<HaltReason>::Revert {
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            output: Bytes::new(),
+        };
+        assert_eq!(cumulative.accrue_result(&revert), Bloom::ZERO);
+        cumulative.accrue_result(&success(vec![second.clone()]));
+
+        let both = success(vec![first, second]).logs_bloom();
+        assert_eq!(cumulative.bloom(), both);
+        assert_eq!(bloom.bit_and(both), bloom);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rpc_format() {
+        let result = ExecutionResult::<HaltReason>::Halt {
+            reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+            gas_used: 21_000,
+            effective_gas_price: 1_000_000_000,
+            debug: Some(Box::new(HaltDebug {
+                pc: 2,
+                opcode: 0x5b,
+                depth: 1,
+                address: Address::with_last_byte(1),
+            })),
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "status": "halt",
+                "reason": { "outOfGas": "basic" },
+                "gasUsed": "0x5208",
+                "effectiveGasPrice": "0x3b9aca00",
+                "debug": {
+                    "pc": 2,
+                    "opcode": 0x5b,
+                    "depth": 1,
+                    "address": "0x0000000000000000000000000000000000000001",
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<ExecutionResult<HaltReason>>(json).unwrap(),
+            result
+        );
+
+        let result = ExecutionResult::<HaltReason>::Success {
+            reason: SuccessReason::Return,
+            gas_used: 0,
+            gas_refunded: 0,
+            effective_gas_price: 0,
+            logs: vec![],
+            output: Output::Create(Bytes::new(), Some(Address::ZERO)),
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(
+            json,
+            r#"{"status":"success","reason":"return","gasUsed":"0x0","gasRefunded":"0x0","effectiveGasPrice":"0x0","logs":[],"output":{"create":["0x","0x0000000000000000000000000000000000000000"]}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ExecutionResult<HaltReason>>(&json).unwrap(),
+            result
+        );
+        // Plain numbers are accepted too.
+        let json = json.replace(r#""gasUsed":"0x0""#, r#""gasUsed":0"#);
+        assert_eq!(
+            serde_json::from_str::<ExecutionResult<HaltReason>>(&json).unwrap(),
+            result
+        );
+    }
+}
@@ -93,10 +93,11 @@ pub trait Transaction {
     /// Note : EIP-4844 transaction field.
     fn max_fee_per_blob_gas(&self) -> u128;
 
-    /// Total gas for all blobs. Max number of blocks is already checked
-    /// so we dont need to check for overflow.
+    /// Total gas for all blobs.
+    ///
+    /// Saturates on overflow, as the max number of blobs is only checked in validation.
     fn total_blob_gas(&self) -> u64 {
-        GAS_PER_BLOB * self.blob_versioned_hashes().len() as u64
+        GAS_PER_BLOB.saturating_mul(self.blob_versioned_hashes().len() as u64)
     }
 
     /// Calculates the maximum [EIP-4844] `data_fee` of the transaction.
@@ -4,6 +4,7 @@ use primitives::{Address, B256, U256};
 /// The block environment
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct BlockEnv {
     /// The number of ancestor blocks of this block (block height)
     pub number: u64,
@@ -41,6 +42,12 @@ pub struct BlockEnv {
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    /// The root of the parent beacon block
+    ///
+    /// Incorporated as part of the Cancun upgrade via [EIP-4788].
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
 }
 
 impl BlockEnv {
@@ -92,6 +99,11 @@ impl Block for BlockEnv {
     fn blob_excess_gas_and_price(&self) -> Option<BlobExcessGasAndPrice> {
         self.blob_excess_gas_and_price
     }
+
+    #[inline]
+    fn parent_beacon_block_root(&self) -> Option<B256> {
+        self.parent_beacon_block_root
+    }
 }
 
 impl Default for BlockEnv {
@@ -105,6 +117,7 @@ impl Default for BlockEnv {
             difficulty: U256::ZERO,
             prevrandao: Some(B256::ZERO),
             blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(0, false)),
+            parent_beacon_block_root: None,
         }
     }
 }
@@ -8,7 +8,10 @@ use context_interface::{
 use database_interface::{Database, EmptyDB};
 use derive_where::derive_where;
 use interpreter::Host;
-use specification::hardfork::SpecId;
+use specification::{
+    hardfork::SpecId,
+    schedule::{HardforkSchedule, MAINNET, MAINNET_CHAIN_ID, SEPOLIA, SEPOLIA_CHAIN_ID},
+};
 
 /// EVM context contains data that EVM needs for execution.
 #[derive_where(Clone, Debug; BLOCK, CFG, CHAIN, TX, DB, JOURNAL, <DB as Database>::Error)]
@@ -44,6 +47,26 @@ impl Context {
     pub fn builder() -> Self {
         Self::new(EmptyDB::new(), SpecId::LATEST)
     }
+
+    /// Creates a context for Ethereum mainnet, with its chain id and latest hardfork.
+    ///
+    /// The spec of an older block is returned by [`MAINNET`]'s [`spec_at`][HardforkSchedule::spec_at].
+    pub fn mainnet() -> Self {
+        Self::preset(MAINNET_CHAIN_ID, &MAINNET)
+    }
+
+    /// Creates a context for the Sepolia testnet, with its chain id and latest hardfork.
+    ///
+    /// The spec of an older block is returned by [`SEPOLIA`]'s [`spec_at`][HardforkSchedule::spec_at].
+    pub fn sepolia() -> Self {
+        Self::preset(SEPOLIA_CHAIN_ID, &SEPOLIA)
+    }
+
+    fn preset(chain_id: u64, schedule: &HardforkSchedule<SpecId>) -> Self {
+        let mut context = Self::new(EmptyDB::new(), schedule.latest());
+        context.cfg.chain_id = chain_id;
+        context
+    }
 }
 
 impl<
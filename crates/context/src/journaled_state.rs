@@ -791,16 +791,30 @@ impl<DB: Database> JournaledState<DB> {
         // load or get account.
         let account = match self.state.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(vac) => vac.insert(
-                self.database
-                    .basic(address)?
-                    .map(|i| i.into())
-                    .unwrap_or(Account::new_not_existing()),
-            ),
+            Entry::Vacant(vac) => {
+                #[cfg(feature = "tracing")]
+                let _span =
+                    tracing::trace_span!("database", operation = "basic", address = %address)
+                        .entered();
+                vac.insert(
+                    self.database
+                        .basic(address)?
+                        .map(|i| i.into())
+                        .unwrap_or(Account::new_not_existing()),
+                )
+            }
         };
         // preload storages.
         for storage_key in storage_keys.into_iter() {
             if let Entry::Vacant(entry) = account.storage.entry(storage_key) {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::trace_span!(
+                    "database",
+                    operation = "storage",
+                    address = %address,
+                    slot = %storage_key,
+                )
+                .entered();
                 let storage = self.database.storage(address, storage_key)?;
                 entry.insert(EvmStorageSlot::new(storage));
             }
@@ -862,6 +876,10 @@ impl<DB: Database> JournaledState<DB> {
                 }
             }
             Entry::Vacant(vac) => {
+                #[cfg(feature = "tracing")]
+                let _span =
+                    tracing::trace_span!("database", operation = "basic", address = %address)
+                        .entered();
                 let account = if let Some(account) = self.database.basic(address)? {
                     account.into()
                 } else {
@@ -891,6 +909,13 @@ impl<DB: Database> JournaledState<DB> {
                     let empty = Bytecode::default();
                     info.code = Some(empty);
                 } else {
+                    #[cfg(feature = "tracing")]
+                    let _span = tracing::trace_span!(
+                        "database",
+                        operation = "code_by_hash",
+                        code_hash = %info.code_hash,
+                    )
+                    .entered();
                     let code = self.database.code_by_hash(info.code_hash)?;
                     info.code = Some(code);
                 }
@@ -922,6 +947,14 @@ impl<DB: Database> JournaledState<DB> {
                 let value = if is_newly_created {
                     U256::ZERO
                 } else {
+                    #[cfg(feature = "tracing")]
+                    let _span = tracing::trace_span!(
+                        "database",
+                        operation = "storage",
+                        address = %address,
+                        slot = %key,
+                    )
+                    .entered();
                     self.database.storage(address, key)?
                 };
 
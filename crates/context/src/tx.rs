@@ -1,5 +1,12 @@
+#[cfg(feature = "alloy")]
+mod alloy;
+
+#[cfg(feature = "alloy")]
+pub use alloy::TxEnvConversionError;
 use context_interface::transaction::AuthorizationItem;
 use context_interface::Transaction;
+#[cfg(feature = "arbitrary")]
+use context_interface::TransactionType;
 use core::fmt::Debug;
 use primitives::{Address, Bytes, TxKind, B256, U256};
 use std::vec::Vec;
@@ -96,6 +103,29 @@ impl Default for TxEnv {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TxEnv {
+    /// Generates only known transaction types, other fields are unconstrained.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            tx_type: u.int_in_range(0..=TransactionType::Eip7702 as u8)?,
+            caller: u.arbitrary()?,
+            gas_limit: u.arbitrary()?,
+            gas_price: u.arbitrary()?,
+            kind: u.arbitrary()?,
+            value: u.arbitrary()?,
+            data: u.arbitrary()?,
+            nonce: u.arbitrary()?,
+            chain_id: u.arbitrary()?,
+            access_list: u.arbitrary()?,
+            gas_priority_fee: u.arbitrary()?,
+            blob_hashes: u.arbitrary()?,
+            max_fee_per_blob_gas: u.arbitrary()?,
+            authorization_list: u.arbitrary()?,
+        })
+    }
+}
+
 impl Transaction for TxEnv {
     fn tx_type(&self) -> u8 {
         self.tx_type
@@ -0,0 +1,247 @@
+//! Conversions of alloy transactions and transaction requests into a [`TxEnv`].
+use super::TxEnv;
+use alloy_consensus::TxEnvelope;
+use alloy_rpc_types_eth::TransactionRequest;
+use context_interface::TransactionType;
+use core::fmt;
+use primitives::{alloy_primitives::SignatureError, Address, TxKind};
+
+/// Error converting an alloy transaction or transaction request into a [`TxEnv`].
+#[derive(Debug)]
+pub enum TxEnvConversionError {
+    /// The signer of the transaction could not be recovered from its signature.
+    InvalidSignature(SignatureError),
+    /// The request sets both `gasPrice` and `maxFeePerGas`.
+    ConflictingFeeFields,
+    /// The request creates a contract with an EIP-4844 or EIP-7702 transaction.
+    MissingRecipient,
+}
+
+impl fmt::Display for TxEnvConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature(error) => write!(f, "invalid signature: {error}"),
+            Self::ConflictingFeeFields => f.write_str("both gas price and max fee per gas are set"),
+            Self::MissingRecipient => {
+                f.write_str("EIP-4844 and EIP-7702 transactions can not create contracts")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TxEnvConversionError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidSignature(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl TxEnv {
+    /// Creates the environment of a transaction whose signer is already known.
+    ///
+    /// Authorities of EIP-7702 authorizations are recovered, invalid ones are kept without
+    /// authority so the handler skips them.
+    pub fn from_recovered_tx(tx: &impl alloy_consensus::Transaction, caller: Address) -> Self {
+        Self {
+            tx_type: tx.ty(),
+            caller,
+            gas_limit: tx.gas_limit(),
+            gas_price: tx.max_fee_per_gas(),
+            kind: tx.kind(),
+            value: tx.value(),
+            data: tx.input().clone(),
+            nonce: tx.nonce(),
+            chain_id: tx.chain_id(),
+            access_list: tx
+                .access_list()
+                .map(|access_list| {
+                    access_list
+                        .iter()
+                        .map(|item| (item.address, item.storage_keys.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            gas_priority_fee: tx.max_priority_fee_per_gas(),
+            blob_hashes: tx
+                .blob_versioned_hashes()
+                .map(<[_]>::to_vec)
+                .unwrap_or_default(),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas().unwrap_or_default(),
+            authorization_list: tx
+                .authorization_list()
+                .unwrap_or_default()
+                .iter()
+                .map(|authorization| {
+                    (
+                        authorization.recover_authority().ok(),
+                        authorization.chain_id,
+                        authorization.nonce,
+                        authorization.address,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&TxEnvelope> for TxEnv {
+    type Error = TxEnvConversionError;
+
+    /// Recovers the signer of the transaction.
+    fn try_from(tx: &TxEnvelope) -> Result<Self, Self::Error> {
+        let caller = tx
+            .recover_signer()
+            .map_err(TxEnvConversionError::InvalidSignature)?;
+        Ok(Self::from_recovered_tx(tx, caller))
+    }
+}
+
+impl TryFrom<TransactionRequest> for TxEnv {
+    type Error = TxEnvConversionError;
+
+    /// Fields missing from the request, e.g. of an `eth_call`, keep their [`Default`] values, and
+    /// the transaction type is inferred from the fields that are set.
+    fn try_from(request: TransactionRequest) -> Result<Self, Self::Error> {
+        if request.gas_price.is_some() && request.max_fee_per_gas.is_some() {
+            return Err(TxEnvConversionError::ConflictingFeeFields);
+        }
+        let tx_type = request
+            .transaction_type
+            .unwrap_or_else(|| request.preferred_type() as u8);
+        let kind = request.to.unwrap_or(TxKind::Create);
+        if kind.is_create()
+            && (tx_type == TransactionType::Eip4844 as u8
+                || tx_type == TransactionType::Eip7702 as u8)
+        {
+            return Err(TxEnvConversionError::MissingRecipient);
+        }
+
+        let default = Self::default();
+        Ok(Self {
+            tx_type,
+            caller: request.from.unwrap_or_default(),
+            gas_limit: request.gas.unwrap_or(default.gas_limit),
+            gas_price: request
+                .gas_price
+                .or(request.max_fee_per_gas)
+                .unwrap_or_default(),
+            kind,
+            value: request.value.unwrap_or_default(),
+            data: request.input.input().cloned().unwrap_or_default(),
+            nonce: request.nonce.unwrap_or_default(),
+            chain_id: request.chain_id,
+            access_list: request
+                .access_list
+                .map(|access_list| {
+                    access_list
+                        .0
+                        .into_iter()
+                        .map(|item| (item.address, item.storage_keys))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            gas_priority_fee: request.max_priority_fee_per_gas,
+            blob_hashes: request.blob_versioned_hashes.unwrap_or_default(),
+            max_fee_per_blob_gas: request.max_fee_per_blob_gas.unwrap_or_default(),
+            authorization_list: request
+                .authorization_list
+                .unwrap_or_default()
+                .iter()
+                .map(|authorization| {
+                    (
+                        authorization.recover_authority().ok(),
+                        authorization.chain_id,
+                        authorization.nonce,
+                        authorization.address,
+                    )
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxEip1559};
+    use alloy_rpc_types_eth::{AccessList, AccessListItem};
+    use primitives::{address, alloy_primitives::PrimitiveSignature, bytes, B256, U256};
+    use std::vec;
+
+    #[test]
+    fn envelope() {
+        let to = address!("0000000000000000000000000000000000001000");
+        let access_list = AccessList(vec![AccessListItem {
+            address: to,
+            storage_keys: vec![B256::with_last_byte(1)],
+        }]);
+        let tx = TxEip1559 {
+            chain_id: 1,
+            nonce: 2,
+            gas_limit: 50_000,
+            max_fee_per_gas: 10,
+            max_priority_fee_per_gas: 1,
+            to: TxKind::Call(to),
+            value: U256::from(3),
+            access_list,
+            input: bytes!("01"),
+        };
+        let envelope = TxEnvelope::from(tx.into_signed(PrimitiveSignature::test_signature()));
+
+        let tx_env = TxEnv::try_from(&envelope).unwrap();
+        assert_eq!(
+            tx_env,
+            TxEnv {
+                tx_type: TransactionType::Eip1559 as u8,
+                caller: envelope.recover_signer().unwrap(),
+                gas_limit: 50_000,
+                gas_price: 10,
+                kind: TxKind::Call(to),
+                value: U256::from(3),
+                data: bytes!("01"),
+                nonce: 2,
+                chain_id: Some(1),
+                access_list: vec![(to, vec![B256::with_last_byte(1)])],
+                gas_priority_fee: Some(1),
+                blob_hashes: vec![],
+                max_fee_per_blob_gas: 0,
+                authorization_list: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn request() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let mut request = TransactionRequest {
+            from: Some(caller),
+            to: Some(TxKind::Call(address!(
+                "0000000000000000000000000000000000001000"
+            ))),
+            gas_price: Some(7),
+            ..Default::default()
+        };
+        let tx_env = TxEnv::try_from(request.clone()).unwrap();
+        assert_eq!(tx_env.tx_type, TransactionType::Legacy as u8);
+        assert_eq!(tx_env.caller, caller);
+        assert_eq!(tx_env.gas_price, 7);
+        assert_eq!(tx_env.gas_limit, TxEnv::default().gas_limit);
+        assert_eq!(tx_env.chain_id, None);
+
+        request.max_fee_per_gas = Some(8);
+        assert!(matches!(
+            TxEnv::try_from(request),
+            Err(TxEnvConversionError::ConflictingFeeFields)
+        ));
+        let blob_create = TransactionRequest {
+            max_fee_per_blob_gas: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            TxEnv::try_from(blob_create),
+            Err(TxEnvConversionError::MissingRecipient)
+        ));
+    }
+}
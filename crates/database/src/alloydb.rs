@@ -24,7 +24,11 @@ impl Display for DBTransportError {
     }
 }
 
-impl Error for DBTransportError {}
+impl Error for DBTransportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
 
 impl From<TransportError> for DBTransportError {
     fn from(e: TransportError) -> Self {
@@ -1,3 +1,4 @@
+use crate::metrics::{record_cache_read, CacheType, Value};
 use core::convert::Infallible;
 use database_interface::{Database, DatabaseCommit, DatabaseRef, EmptyDB};
 use primitives::{address, hash_map::Entry, Address, HashMap, Log, B256, KECCAK_EMPTY, U256};
@@ -202,7 +203,13 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        let basic = match self.accounts.entry(address) {
+        let entry = self.accounts.entry(address);
+        record_cache_read(
+            CacheType::CacheDb,
+            Value::Account,
+            matches!(entry, Entry::Occupied(_)),
+        );
+        let basic = match entry {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => entry.insert(
                 self.db
@@ -218,7 +225,13 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     }
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        match self.contracts.entry(code_hash) {
+        let entry = self.contracts.entry(code_hash);
+        record_cache_read(
+            CacheType::CacheDb,
+            Value::Code,
+            matches!(entry, Entry::Occupied(_)),
+        );
+        match entry {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
                 // If you return code bytes when basic fn is called this function is not needed.
@@ -234,13 +247,20 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
         match self.accounts.entry(address) {
             Entry::Occupied(mut acc_entry) => {
                 let acc_entry = acc_entry.get_mut();
-                match acc_entry.storage.entry(index) {
+                let is_storage_known = matches!(
+                    acc_entry.account_state,
+                    AccountState::StorageCleared | AccountState::NotExisting
+                );
+                let entry = acc_entry.storage.entry(index);
+                record_cache_read(
+                    CacheType::CacheDb,
+                    Value::Storage,
+                    is_storage_known || matches!(entry, Entry::Occupied(_)),
+                );
+                match entry {
                     Entry::Occupied(entry) => Ok(*entry.get()),
                     Entry::Vacant(entry) => {
-                        if matches!(
-                            acc_entry.account_state,
-                            AccountState::StorageCleared | AccountState::NotExisting
-                        ) {
+                        if is_storage_known {
                             Ok(U256::ZERO)
                         } else {
                             let slot = self.db.storage_ref(address, index)?;
@@ -251,6 +271,7 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
                 }
             }
             Entry::Vacant(acc_entry) => {
+                record_cache_read(CacheType::CacheDb, Value::Storage, false);
                 // Acc needs to be loaded for us to access slots.
                 let info = self.db.basic_ref(address)?;
                 let (account, value) = if info.is_some() {
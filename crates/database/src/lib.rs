@@ -4,14 +4,21 @@
 mod alloydb;
 
 pub mod in_memory_db;
+mod metrics;
+#[cfg(feature = "std")]
+mod prefetch;
 pub mod states;
+mod witness;
 
 #[cfg(feature = "alloydb")]
 pub use alloydb::{AlloyDB, BlockId};
 
 pub use in_memory_db::*;
+#[cfg(feature = "std")]
+pub use prefetch::{HotSet, PrefetchDb, PrefetchTarget, Prefetcher};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+pub use witness::{Witness, WitnessDB, WitnessError};
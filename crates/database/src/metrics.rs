@@ -0,0 +1,51 @@
+//! Metrics of the caching databases, emitted through the [`metrics`] facade with the `metrics`
+//! feature.
+//!
+//! - `revm_database_cache_hits_total`: counter of the reads served from a cache.
+//! - `revm_database_cache_misses_total`: counter of the reads of the wrapped database.
+//!
+//! Both are labeled by `cache`, one of `cache_db`, `state` or `prefetch`, and by `value`, one of
+//! `account`, `code` or `storage`.
+
+/// Cache serving a read.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CacheType {
+    CacheDb,
+    State,
+    #[cfg(feature = "std")]
+    Prefetch,
+}
+
+/// Value read from a cache.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Value {
+    Account,
+    Code,
+    Storage,
+}
+
+/// Records a read served from the cache if `hit`, from the wrapped database otherwise.
+#[inline]
+pub(crate) fn record_cache_read(cache: CacheType, value: Value, hit: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        let cache = match cache {
+            CacheType::CacheDb => "cache_db",
+            CacheType::State => "state",
+            CacheType::Prefetch => "prefetch",
+        };
+        let value = match value {
+            Value::Account => "account",
+            Value::Code => "code",
+            Value::Storage => "storage",
+        };
+        let labels = [("cache", cache), ("value", value)];
+        if hit {
+            ::metrics::counter!("revm_database_cache_hits_total", &labels).increment(1);
+        } else {
+            ::metrics::counter!("revm_database_cache_misses_total", &labels).increment(1);
+        }
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (cache, value, hit);
+}
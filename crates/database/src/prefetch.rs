@@ -0,0 +1,336 @@
+//! Background prefetching of the state upcoming transactions will read.
+//!
+//! A [`Prefetcher`] reads accounts, code and storage slots on background threads into a
+//! [`PrefetchDb`], so the executor reading through the same [`PrefetchDb`] finds them cached
+//! instead of waiting on a slow database, e.g. an RPC provider or the disk.
+use crate::metrics::{record_cache_read, CacheType, Value};
+use core::mem;
+use database_interface::DatabaseRef;
+use primitives::{Address, HashMap, HashSet, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
+    thread::{self, JoinHandle},
+    vec::Vec,
+};
+
+/// A value to prefetch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PrefetchTarget {
+    /// The account and its code.
+    Account(Address),
+    Storage(Address, U256),
+}
+
+/// Accounts and storage slots read by the executor, e.g. to prefetch them for the next block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HotSet {
+    pub accounts: HashSet<Address>,
+    pub storage: HashSet<(Address, U256)>,
+}
+
+impl HotSet {
+    /// Returns the accounts then the storage slots to prefetch.
+    pub fn targets(&self) -> impl Iterator<Item = PrefetchTarget> + '_ {
+        self.accounts
+            .iter()
+            .map(|address| PrefetchTarget::Account(*address))
+            .chain(
+                self.storage
+                    .iter()
+                    .map(|(address, slot)| PrefetchTarget::Storage(*address, *slot)),
+            )
+    }
+}
+
+/// A [`DatabaseRef`] wrapper caching the values read from the wrapped database.
+///
+/// Values are cached for the lifetime of the wrapper, so the wrapped database must not change
+/// while it is used, e.g. it is created for each block on top of the state of its parent. Errors
+/// are not cached, a failed prefetch is retried by the executor that then gets the error.
+#[derive(Debug, Default)]
+pub struct PrefetchDb<DB> {
+    /// The wrapped database.
+    pub db: DB,
+    accounts: RwLock<HashMap<Address, Option<AccountInfo>>>,
+    contracts: RwLock<HashMap<B256, Bytecode>>,
+    storage: RwLock<HashMap<(Address, U256), U256>>,
+    /// Values read through [`DatabaseRef`], not by the prefetcher.
+    hot_set: Mutex<HotSet>,
+}
+
+impl<DB> PrefetchDb<DB> {
+    /// Wraps the database with an empty cache.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: RwLock::default(),
+            contracts: RwLock::default(),
+            storage: RwLock::default(),
+            hot_set: Mutex::default(),
+        }
+    }
+
+    /// Takes the accounts and storage slots read through [`DatabaseRef`] so far.
+    pub fn take_hot_set(&self) -> HotSet {
+        mem::take(&mut self.hot_set.lock().unwrap())
+    }
+}
+
+impl<DB: DatabaseRef> PrefetchDb<DB> {
+    /// Reads the target into the cache, ignoring errors.
+    pub fn prefetch(&self, target: PrefetchTarget) {
+        match target {
+            PrefetchTarget::Account(address) => {
+                if let Ok(Some(info)) = self.account(address, false) {
+                    if info.code.is_none() && info.code_hash != KECCAK_EMPTY {
+                        let _ = self.code(info.code_hash, false);
+                    }
+                }
+            }
+            PrefetchTarget::Storage(address, slot) => {
+                let _ = self.slot(address, slot, false);
+            }
+        }
+    }
+
+    // Reads of the executor are recorded in the metrics, the ones of the prefetcher are not.
+
+    fn account(&self, address: Address, record: bool) -> Result<Option<AccountInfo>, DB::Error> {
+        let cached = self.accounts.read().unwrap().get(&address).cloned();
+        if record {
+            record_cache_read(CacheType::Prefetch, Value::Account, cached.is_some());
+        }
+        if let Some(info) = cached {
+            return Ok(info);
+        }
+        let info = self.db.basic_ref(address)?;
+        self.accounts.write().unwrap().insert(address, info.clone());
+        Ok(info)
+    }
+
+    fn code(&self, code_hash: B256, record: bool) -> Result<Bytecode, DB::Error> {
+        let cached = self.contracts.read().unwrap().get(&code_hash).cloned();
+        if record {
+            record_cache_read(CacheType::Prefetch, Value::Code, cached.is_some());
+        }
+        if let Some(code) = cached {
+            return Ok(code);
+        }
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.contracts
+            .write()
+            .unwrap()
+            .insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn slot(&self, address: Address, slot: U256, record: bool) -> Result<U256, DB::Error> {
+        let cached = self.storage.read().unwrap().get(&(address, slot)).copied();
+        if record {
+            record_cache_read(CacheType::Prefetch, Value::Storage, cached.is_some());
+        }
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = self.db.storage_ref(address, slot)?;
+        self.storage.write().unwrap().insert((address, slot), value);
+        Ok(value)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for PrefetchDb<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.hot_set.lock().unwrap().accounts.insert(address);
+        self.account(address, true)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code(code_hash, true)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.hot_set
+            .lock()
+            .unwrap()
+            .storage
+            .insert((address, index));
+        self.slot(address, index, true)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+/// Prefetches targets into a [`PrefetchDb`] on background threads.
+///
+/// Targets are prefetched in the order they are sent. Dropping the prefetcher waits for the
+/// targets already sent.
+#[derive(Debug)]
+pub struct Prefetcher<DB> {
+    db: Arc<PrefetchDb<DB>>,
+    sender: Option<Sender<PrefetchTarget>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<DB: DatabaseRef + Send + Sync + 'static> Prefetcher<DB> {
+    /// Starts `threads` threads prefetching into `db`.
+    pub fn new(db: Arc<PrefetchDb<DB>>, threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let db = db.clone();
+                let receiver = receiver.clone();
+                thread::spawn(move || prefetch_targets(&db, &receiver))
+            })
+            .collect();
+        Self {
+            db,
+            sender: Some(sender),
+            workers,
+        }
+    }
+}
+
+impl<DB> Prefetcher<DB> {
+    /// Returns the database prefetched into.
+    pub fn db(&self) -> &Arc<PrefetchDb<DB>> {
+        &self.db
+    }
+
+    /// Prefetches the targets.
+    pub fn prefetch(&self, targets: impl IntoIterator<Item = PrefetchTarget>) {
+        let sender = self.sender.as_ref().expect("sender is dropped on drop");
+        for target in targets {
+            // Workers only stop when the sender is dropped.
+            let _ = sender.send(target);
+        }
+    }
+
+    /// Prefetches the accounts and storage slots of an access list, e.g. of a transaction.
+    pub fn prefetch_access_list<'a>(
+        &self,
+        access_list: impl IntoIterator<Item = (&'a Address, &'a [B256])>,
+    ) {
+        for (address, slots) in access_list {
+            let slots = slots
+                .iter()
+                .map(|slot| PrefetchTarget::Storage(*address, U256::from_be_bytes(slot.0)));
+            self.prefetch(core::iter::once(PrefetchTarget::Account(*address)).chain(slots));
+        }
+    }
+
+    /// Prefetches the accounts and storage slots read by a previous block.
+    pub fn prefetch_hot_set(&self, hot_set: &HotSet) {
+        self.prefetch(hot_set.targets());
+    }
+}
+
+impl<DB> Drop for Prefetcher<DB> {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn prefetch_targets<DB: DatabaseRef>(
+    db: &PrefetchDb<DB>,
+    receiver: &Mutex<Receiver<PrefetchTarget>>,
+) {
+    loop {
+        let target = receiver.lock().unwrap().recv();
+        match target {
+            Ok(target) => db.prefetch(target),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use primitives::address;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts the reads of the wrapped database.
+    #[derive(Default)]
+    struct CountingDb {
+        db: InMemoryDB,
+        reads: AtomicUsize,
+    }
+
+    impl DatabaseRef for CountingDb {
+        type Error = <InMemoryDB as DatabaseRef>::Error;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.db.basic_ref(address)
+        }
+
+        fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.db.code_by_hash_ref(code_hash)
+        }
+
+        fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.db.storage_ref(address, index)
+        }
+
+        fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash_ref(number)
+        }
+    }
+
+    #[test]
+    fn prefetched_values_are_cached() {
+        let address = address!("0000000000000000000000000000000000001000");
+        let mut db = CountingDb::default();
+        let code = Bytecode::new_raw([0x00].into());
+        db.db.insert_account_info(
+            address,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code.clone()),
+                ..Default::default()
+            },
+        );
+        db.db
+            .insert_account_storage(address, U256::from(1), U256::from(2))
+            .unwrap();
+        let db = Arc::new(PrefetchDb::new(db));
+
+        let slots = [B256::with_last_byte(1), B256::with_last_byte(2)];
+        let prefetcher = Prefetcher::new(db.clone(), 2);
+        prefetcher.prefetch_access_list([(&address, &slots[..])]);
+        drop(prefetcher);
+        assert_eq!(db.db.reads.load(Ordering::Relaxed), 3);
+
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(2)));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::ZERO));
+        assert_eq!(
+            db.basic_ref(address).unwrap().map(|info| info.code_hash),
+            Some(code.hash_slow())
+        );
+        assert_eq!(db.db.reads.load(Ordering::Relaxed), 3);
+
+        // The next block prefetches what this one read.
+        let hot_set = db.take_hot_set();
+        assert_eq!(hot_set.accounts.len(), 1);
+        assert_eq!(hot_set.storage.len(), 2);
+        let next = Arc::new(PrefetchDb::new(CountingDb::default()));
+        Prefetcher::new(next.clone(), 1).prefetch_hot_set(&hot_set);
+        assert_eq!(next.db.reads.load(Ordering::Relaxed), 3);
+        assert_eq!(next.take_hot_set(), HotSet::default());
+    }
+}
@@ -2,6 +2,7 @@ use super::{
     bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
     CacheAccount, StateBuilder, TransitionAccount, TransitionState,
 };
+use crate::metrics::{record_cache_read, CacheType, Value};
 use bytecode::Bytecode;
 use database_interface::{Database, DatabaseCommit, EmptyDB};
 use primitives::{hash_map, Address, HashMap, B256, BLOCK_HASH_HISTORY, U256};
@@ -194,10 +195,12 @@ impl<DB: Database> State<DB> {
                     if let Some(account) =
                         self.bundle_state.account(&address).cloned().map(Into::into)
                     {
+                        record_cache_read(CacheType::State, Value::Account, true);
                         return Ok(entry.insert(account));
                     }
                 }
                 // If not found in bundle, load it from database
+                record_cache_read(CacheType::State, Value::Account, false);
                 let info = self.database.basic(address)?;
                 let account = match info {
                     None => CacheAccount::new_loaded_not_existing(),
@@ -208,7 +211,10 @@ impl<DB: Database> State<DB> {
                 };
                 Ok(entry.insert(account))
             }
-            hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+            hash_map::Entry::Occupied(entry) => {
+                record_cache_read(CacheType::State, Value::Account, true);
+                Ok(entry.into_mut())
+            }
         }
     }
 
@@ -237,15 +243,20 @@ impl<DB: Database> Database for State<DB> {
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
         let res = match self.cache.contracts.entry(code_hash) {
-            hash_map::Entry::Occupied(entry) => Ok(entry.get().clone()),
+            hash_map::Entry::Occupied(entry) => {
+                record_cache_read(CacheType::State, Value::Code, true);
+                Ok(entry.get().clone())
+            }
             hash_map::Entry::Vacant(entry) => {
                 if self.use_preloaded_bundle {
                     if let Some(code) = self.bundle_state.contracts.get(&code_hash) {
+                        record_cache_read(CacheType::State, Value::Code, true);
                         entry.insert(code.clone());
                         return Ok(code.clone());
                     }
                 }
                 // If not found in bundle ask database
+                record_cache_read(CacheType::State, Value::Code, false);
                 let code = self.database.code_by_hash(code_hash)?;
                 entry.insert(code.clone());
                 Ok(code)
@@ -264,8 +275,12 @@ impl<DB: Database> Database for State<DB> {
                 .account
                 .as_mut()
                 .map(|account| match account.storage.entry(index) {
-                    hash_map::Entry::Occupied(entry) => Ok(*entry.get()),
+                    hash_map::Entry::Occupied(entry) => {
+                        record_cache_read(CacheType::State, Value::Storage, true);
+                        Ok(*entry.get())
+                    }
                     hash_map::Entry::Vacant(entry) => {
+                        record_cache_read(CacheType::State, Value::Storage, is_storage_known);
                         // If account was destroyed or account is newly built
                         // we return zero and don't ask database.
                         let value = if is_storage_known {
@@ -0,0 +1,233 @@
+use core::{error::Error, fmt};
+use database_interface::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use primitives::{Address, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{Account, AccountInfo, Bytecode};
+
+/// Values read from a database, as they were the first time they were read.
+///
+/// Holds the state an execution depends on, e.g. to reproduce it without the database.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness {
+    /// Account info where None means it is not existing.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Contracts loaded by their code hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Storage slots of each account.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl Witness {
+    /// Returns the code of the account, from its info or from the loaded contracts.
+    pub fn code<'a>(&'a self, info: &'a AccountInfo) -> Option<&'a Bytecode> {
+        info.code
+            .as_ref()
+            .or_else(|| self.contracts.get(&info.code_hash))
+    }
+}
+
+/// Serves the recorded values, so that an execution can be replayed offline.
+///
+/// Reading a value that was not recorded is an error, as it means the replay diverged from the
+/// recorded execution. Storage of accounts recorded as not existing is empty.
+impl DatabaseRef for Witness {
+    type Error = WitnessError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.accounts
+            .get(&address)
+            .cloned()
+            .ok_or(WitnessError::Account(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(WitnessError::Code(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self
+            .storage
+            .get(&address)
+            .and_then(|slots| slots.get(&index))
+        {
+            return Ok(*value);
+        }
+        match self.accounts.get(&address) {
+            Some(None) => Ok(U256::ZERO),
+            _ => Err(WitnessError::Storage { address, index }),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(WitnessError::BlockHash(number))
+    }
+}
+
+/// A value read from a [Witness] was not recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessError {
+    Account(Address),
+    Code(B256),
+    Storage { address: Address, index: U256 },
+    BlockHash(u64),
+}
+
+impl DBErrorMarker for WitnessError {}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(address) => write!(f, "account {address} was not recorded"),
+            Self::Code(code_hash) => write!(f, "code {code_hash} was not recorded"),
+            Self::Storage { address, index } => {
+                write!(f, "storage slot {index} of {address} was not recorded")
+            }
+            Self::BlockHash(number) => write!(f, "hash of block {number} was not recorded"),
+        }
+    }
+}
+
+impl Error for WitnessError {}
+
+/// A [Database] wrapper that records the values read from the wrapped database in a [Witness].
+///
+/// Only the first read of a value is recorded, so after executing transactions on top of each
+/// other through a caching database, the witness holds the state before the first one.
+#[derive(Debug, Default)]
+pub struct WitnessDB<DB> {
+    /// The wrapped database.
+    pub db: DB,
+    witness: Witness,
+}
+
+impl<DB> WitnessDB<DB> {
+    /// Wraps the database with an empty witness.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: Witness::default(),
+        }
+    }
+
+    /// Returns the values read so far.
+    pub fn witness(&self) -> &Witness {
+        &self.witness
+    }
+
+    /// Takes the values read so far, leaving an empty witness.
+    pub fn take_witness(&mut self) -> Witness {
+        core::mem::take(&mut self.witness)
+    }
+
+    /// Returns the wrapped database and the values read from it.
+    pub fn into_parts(self) -> (DB, Witness) {
+        (self.db, self.witness)
+    }
+}
+
+impl<DB: Database> Database for WitnessDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.witness
+            .accounts
+            .entry(address)
+            .or_insert_with(|| info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness
+            .contracts
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for WitnessDB<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use database_interface::DatabaseRef;
+    use primitives::address;
+
+    #[test]
+    fn records_first_reads() {
+        let address = address!("0000000000000000000000000000000000000001");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(address, U256::ZERO, U256::from(1))
+            .unwrap();
+
+        let mut db = WitnessDB::new(db);
+        db.storage(address, U256::ZERO).unwrap();
+        db.basic(address).unwrap();
+        db.db
+            .insert_account_storage(address, U256::ZERO, U256::from(2))
+            .unwrap();
+        assert_eq!(db.storage(address, U256::ZERO).unwrap(), U256::from(2));
+        db.basic(Address::ZERO).unwrap();
+
+        let (db, witness) = db.into_parts();
+        assert_eq!(witness.storage[&address][&U256::ZERO], U256::from(1));
+        assert_eq!(witness.accounts[&address], db.basic_ref(address).unwrap());
+        assert_eq!(witness.accounts[&Address::ZERO], None);
+
+        assert_eq!(witness.storage_ref(address, U256::ZERO), Ok(U256::from(1)));
+        assert_eq!(
+            witness.storage_ref(Address::ZERO, U256::ZERO),
+            Ok(U256::ZERO)
+        );
+        assert_eq!(
+            witness.storage_ref(address, U256::from(1)),
+            Err(WitnessError::Storage {
+                address,
+                index: U256::from(1)
+            })
+        );
+        assert_eq!(witness.block_hash_ref(0), Err(WitnessError::BlockHash(0)));
+    }
+}
@@ -4,6 +4,7 @@ use super::frame_data::*;
 use bytecode::{Eof, EOF_MAGIC_BYTES};
 use context_interface::{
     journaled_state::{Journal, JournalCheckpoint},
+    result::{FromStringError, HaltDebug},
     BlockGetter, Cfg, CfgGetter, ErrorGetter, JournalDBError, JournalGetter, Transaction,
     TransactionGetter,
 };
@@ -14,7 +15,7 @@ use handler_interface::{
 use interpreter::{
     gas,
     interpreter::{EthInterpreter, ExtBytecode},
-    interpreter_types::{LoopControl, ReturnData, RuntimeFlag},
+    interpreter_types::{Jumps, LoopControl, ReturnData, RuntimeFlag},
     return_ok, return_revert, CallInputs, CallOutcome, CallValue, CreateInputs, CreateOutcome,
     CreateScheme, EOFCreateInputs, EOFCreateKind, FrameInput, Gas, Host, InputsImpl,
     InstructionResult, Interpreter, InterpreterAction, InterpreterResult, InterpreterTypes,
@@ -28,7 +29,7 @@ use specification::{
 };
 use state::Bytecode;
 use std::borrow::ToOwned;
-use std::{rc::Rc, sync::Arc};
+use std::{boxed::Box, rc::Rc, sync::Arc};
 
 pub struct EthFrame<CTX, ERROR, IW: InterpreterTypes, FRAMECTX> {
     _phantom: core::marker::PhantomData<(FRAMECTX, CTX, ERROR)>,
@@ -69,7 +70,7 @@ where
 impl<CTX, ERROR, FRAMECTX> EthFrame<CTX, ERROR, EthInterpreter, FRAMECTX>
 where
     CTX: EthFrameContext,
-    ERROR: From<JournalDBError<CTX>> + From<PrecompileErrors>,
+    ERROR: From<JournalDBError<CTX>> + From<PrecompileErrors> + FromStringError,
     FRAMECTX: PrecompileProviderGetter<
             PrecompileProvider: PrecompileProvider<
                 Context = CTX,
@@ -101,6 +102,7 @@ where
                     result: instruction_result,
                     gas,
                     output: Bytes::new(),
+                    halt_debug: None,
                 },
                 memory_offset: inputs.return_memory_offset.clone(),
             })))
@@ -221,6 +223,7 @@ where
                     result: e,
                     gas: Gas::new(inputs.gas_limit),
                     output: Bytes::new(),
+                    halt_debug: None,
                 },
                 address: None,
             })))
@@ -324,6 +327,7 @@ where
                         result: e,
                         gas: Gas::new(inputs.gas_limit),
                         output: Bytes::new(),
+                        halt_debug: None,
                     },
                     address: None,
                 },
@@ -479,7 +483,7 @@ impl<PRECOMPILES: PrecompileProvider, INSTRUCTIONS: InstructionExecutor> Instruc
 impl<CTX, ERROR, FRAMECTX> Frame for EthFrame<CTX, ERROR, EthInterpreter<()>, FRAMECTX>
 where
     CTX: EthFrameContext,
-    ERROR: From<JournalDBError<CTX>> + From<PrecompileErrors>,
+    ERROR: From<JournalDBError<CTX>> + From<PrecompileErrors> + FromStringError,
     FRAMECTX: PrecompileProviderGetter<
             PrecompileProvider: PrecompileProvider<
                 Context = CTX,
@@ -538,6 +542,15 @@ where
         context: &mut Self::Context,
         frame_context: &mut Self::FrameContext,
     ) -> Result<FrameInitOrResult<Self>, Self::Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "frame",
+            depth = self.depth,
+            caller = %self.interpreter.input.caller_address,
+            callee = %self.interpreter.input.target_address,
+            gas = self.interpreter.control.gas().remaining(),
+        )
+        .entered();
         let spec = context.cfg().spec().into();
 
         // Run interpreter
@@ -546,8 +559,23 @@ where
         let mut interpreter_result = match next_action {
             InterpreterAction::NewFrame(new_frame) => return Ok(ItemOrResult::Item(new_frame)),
             InterpreterAction::Return { result } => result,
-            InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
+            InterpreterAction::None => {
+                return Err(ERROR::from_string(
+                    "interpreter stopped without an action".into(),
+                ))
+            }
         };
+        if interpreter_result.result.is_error() {
+            // The instruction pointer was moved past the opcode before it was executed.
+            let bytecode = &self.interpreter.bytecode;
+            let pc = bytecode.pc().saturating_sub(1);
+            interpreter_result.halt_debug = Some(Box::new(HaltDebug {
+                pc,
+                opcode: bytecode.bytes_ref().get(pc).copied().unwrap_or_default(),
+                depth: self.depth,
+                address: self.interpreter.input.target_address,
+            }));
+        }
 
         // Handle return from frame
         let result = match &self.data {
@@ -624,7 +652,9 @@ where
                 let target_len = min(mem_length, returned_len);
 
                 if ins_result == InstructionResult::FatalExternalError {
-                    panic!("Fatal external error in insert_call_outcome");
+                    return Err(ERROR::from_string(
+                        "fatal external error in call outcome".into(),
+                    ));
                 }
 
                 let item = {
@@ -668,11 +698,11 @@ where
                     buffer.clear();
                 };
 
-                assert_ne!(
-                    instruction_result,
-                    InstructionResult::FatalExternalError,
-                    "Fatal external error in insert_eofcreate_outcome"
-                );
+                if instruction_result == InstructionResult::FatalExternalError {
+                    return Err(ERROR::from_string(
+                        "fatal external error in create outcome".into(),
+                    ));
+                }
 
                 let this_gas = interpreter.control.gas();
                 if instruction_result.is_ok_or_revert() {
@@ -700,11 +730,11 @@ where
                     interpreter.return_data.buffer_mut().clear();
                 };
 
-                assert_ne!(
-                    instruction_result,
-                    InstructionResult::FatalExternalError,
-                    "Fatal external error in insert_eofcreate_outcome"
-                );
+                if instruction_result == InstructionResult::FatalExternalError {
+                    return Err(ERROR::from_string(
+                        "fatal external error in EOF create outcome".into(),
+                    ));
+                }
 
                 let this_gas = interpreter.control.gas();
                 if instruction_result.is_ok_or_revert() {
@@ -713,7 +743,7 @@ where
 
                 let stack_item = if instruction_result.is_ok() {
                     this_gas.record_refund(outcome.gas().refunded());
-                    outcome.address.expect("EOF Address").into_word().into()
+                    outcome.address.unwrap_or_default().into_word().into()
                 } else {
                     U256::ZERO
                 };
@@ -774,8 +804,10 @@ pub fn return_create<JOURNAL: Journal>(
     // If we have enough gas we can commit changes.
     journal.checkpoint_commit();
 
-    // Do analysis of bytecode straight away.
-    let bytecode = Bytecode::new_legacy(interpreter_result.output.clone());
+    // Do analysis of bytecode straight away. The output shares the allocation of the
+    // analyzed code instead of keeping a second copy of it.
+    let bytecode = Bytecode::new_legacy(core::mem::take(&mut interpreter_result.output));
+    interpreter_result.output = bytecode.original_bytes();
 
     // Set code
     journal.set_code(address, bytecode);
@@ -816,10 +848,15 @@ pub fn return_eofcreate<JOURNAL: Journal>(
         return;
     }
 
-    journal.checkpoint_commit();
-
     // Decode bytecode has a performance hit, but it has reasonable restrains.
-    let bytecode = Eof::decode(interpreter_result.output.clone()).expect("Eof is already verified");
+    // Decoding only fails if the init code was not validated.
+    let Ok(bytecode) = Eof::decode(interpreter_result.output.clone()) else {
+        journal.checkpoint_revert(checkpoint);
+        interpreter_result.result = InstructionResult::InvalidEOFCode;
+        return;
+    };
+
+    journal.checkpoint_commit();
 
     // Eof bytecode is going to be hashed.
     journal.set_code(address, Bytecode::Eof(Arc::new(bytecode)));
@@ -33,9 +33,42 @@ pub trait EthHandler {
     // TODO `HaltReason` should be a ExecResult trait, returned by the handler.
     type HaltReason: HaltReasonTrait;
 
+    /// Executes the transaction, then calls [`EthHandler::end`] and [`EthHandler::clear`] whether
+    /// it succeeded or not.
     fn run(
         &mut self,
         context: &mut Self::Context,
+    ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
+        #[cfg(feature = "tracing")]
+        let span = {
+            let tx = context.tx();
+            tracing::debug_span!(
+                "transaction",
+                caller = %tx.caller(),
+                callee = tx.kind().to().map(tracing::field::display),
+                gas_limit = tx.gas_limit(),
+                gas_used = tracing::field::Empty,
+            )
+            .entered()
+        };
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let output = self.run_without_end(context);
+        let output = self.end(context, output);
+        self.clear(context);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_transaction(&output, started.elapsed());
+        #[cfg(feature = "tracing")]
+        if let Ok(output) = &output {
+            span.record("gas_used", output.result.gas_used());
+        }
+        output
+    }
+
+    /// Executes the transaction, stopping at the first error without clearing the journal.
+    fn run_without_end(
+        &mut self,
+        context: &mut Self::Context,
     ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
         let init_and_floor_gas = self.validate(context)?;
         let eip7702_refund = self.pre_execution(context)? as i64;
@@ -60,6 +93,8 @@ pub trait EthHandler {
 
     /// Call all validation functions
     fn validate(&self, context: &mut Self::Context) -> Result<InitialAndFloorGas, Self::Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate").entered();
         self.validate_env(context)?;
         self.validate_tx_against_state(context)?;
         self.validate_initial_tx_gas(context)
@@ -293,7 +328,7 @@ pub trait EthHandler {
         result: <Self::Frame as Frame>::FrameResult,
     ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
         context.take_error()?;
-        Ok(post_execution::output(context, result))
+        post_execution::output(context, result)
     }
 
     /// Called when execution ends.
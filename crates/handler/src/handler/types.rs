@@ -2,7 +2,7 @@ use super::EthHandler;
 use crate::{instructions::InstructionExecutor, EthPrecompileProvider, FrameContext, FrameResult};
 use context::Context;
 use context_interface::{
-    result::{HaltReason, InvalidHeader, InvalidTransaction},
+    result::{FromStringError, HaltReason, InvalidHeader, InvalidTransaction},
     Block, BlockGetter, Cfg, CfgGetter, Database, DatabaseGetter, ErrorGetter, Journal,
     JournalDBError, JournalGetter, PerformantContextAccess, Transaction, TransactionGetter,
 };
@@ -69,7 +69,11 @@ pub trait EthContext:
 }
 
 pub trait EthError<CTX: JournalGetter>:
-    From<InvalidTransaction> + From<InvalidHeader> + From<JournalDBError<CTX>> + From<PrecompileErrors>
+    From<InvalidTransaction>
+    + From<InvalidHeader>
+    + From<JournalDBError<CTX>>
+    + From<PrecompileErrors>
+    + FromStringError
 {
 }
 
@@ -78,7 +82,8 @@ impl<
         T: From<InvalidTransaction>
             + From<InvalidHeader>
             + From<JournalDBError<CTX>>
-            + From<PrecompileErrors>,
+            + From<PrecompileErrors>
+            + FromStringError,
     > EthError<CTX> for T
 {
 }
@@ -12,6 +12,8 @@ mod frame;
 mod frame_data;
 pub mod handler;
 pub mod instructions;
+#[cfg(feature = "metrics")]
+mod metrics;
 pub mod post_execution;
 pub mod pre_execution;
 mod precompile_provider;
@@ -0,0 +1,43 @@
+//! Metrics of the handler, emitted through the [`metrics`] facade with the `metrics` feature.
+//!
+//! - `revm_transactions_total`: counter of executed transactions, labeled by `outcome`, one of
+//!   `success`, `revert`, `halt` or `error` for transactions that failed validation or execution.
+//! - `revm_gas_used_total`: counter of the gas used by the transactions.
+//! - `revm_transaction_duration_seconds`: histogram of the execution time of the transactions.
+//! - `revm_gas_per_second`: histogram of the gas used per second of execution.
+//! - `revm_precompile_duration_seconds`: histogram of the time of precompile calls, labeled by
+//!   `address`.
+use ::metrics::{counter, histogram};
+use context_interface::result::{ExecutionResult, HaltReasonTrait, ResultAndState};
+use primitives::Address;
+use std::{string::ToString, time::Duration};
+
+/// Records an executed transaction.
+pub(crate) fn record_transaction<HaltReasonT: HaltReasonTrait, E>(
+    output: &Result<ResultAndState<HaltReasonT>, E>,
+    elapsed: Duration,
+) {
+    let outcome = match output.as_ref().map(|output| &output.result) {
+        Ok(ExecutionResult::Success { .. }) => "success",
+        Ok(ExecutionResult::Revert { .. }) => "revert",
+        Ok(ExecutionResult::Halt { .. }) => "halt",
+        Err(_) => "error",
+    };
+    counter!("revm_transactions_total", "outcome" => outcome).increment(1);
+    histogram!("revm_transaction_duration_seconds").record(elapsed);
+
+    let Ok(output) = output else {
+        return;
+    };
+    let gas_used = output.result.gas_used();
+    counter!("revm_gas_used_total").increment(gas_used);
+    if !elapsed.is_zero() {
+        histogram!("revm_gas_per_second").record(gas_used as f64 / elapsed.as_secs_f64());
+    }
+}
+
+/// Records a precompile call.
+pub(crate) fn record_precompile(address: &Address, elapsed: Duration) {
+    histogram!("revm_precompile_duration_seconds", "address" => address.to_string())
+        .record(elapsed);
+}
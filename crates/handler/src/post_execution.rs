@@ -1,7 +1,7 @@
 use super::frame_data::FrameResult;
 use context_interface::{
     journaled_state::Journal,
-    result::{ExecutionResult, HaltReasonTrait, ResultAndState},
+    result::{ExecutionResult, FromStringError, HaltReasonTrait, ResultAndState},
     Block, BlockGetter, Cfg, CfgGetter, JournalDBError, JournalGetter, Transaction,
     TransactionGetter,
 };
@@ -9,7 +9,7 @@ use interpreter::{Gas, InitialAndFloorGas, SuccessOrHalt};
 use primitives::{Log, U256};
 use specification::hardfork::SpecId;
 use state::EvmState;
-use std::vec::Vec;
+use std::{format, vec::Vec};
 
 pub fn eip7623_check_gas_floor(gas: &mut Gas, init_and_floor_gas: InitialAndFloorGas) {
     // EIP-7623: Increase calldata cost
@@ -86,21 +86,28 @@ pub fn reward_beneficiary<CTX: CfgGetter + BlockGetter + TransactionGetter + Jou
 
 /// Calculate last gas spent and transform internal reason to external.
 ///
+/// Returns an error if the result is an internal return flag, which frames never return.
+///
 /// TODO make Journal FinalOutput more generic.
 pub fn output<
-    CTX: JournalGetter<Journal: Journal<FinalOutput = (EvmState, Vec<Log>)>>,
+    CTX: BlockGetter
+        + TransactionGetter
+        + JournalGetter<Journal: Journal<FinalOutput = (EvmState, Vec<Log>)>>,
     HALTREASON: HaltReasonTrait,
+    ERROR: FromStringError,
 >(
     context: &mut CTX,
     // TODO, make this more generic and nice.
     // FrameResult should be a generic that returns gas and interpreter result.
     result: FrameResult,
-) -> ResultAndState<HALTREASON> {
+) -> Result<ResultAndState<HALTREASON>, ERROR> {
     // Used gas with refund calculated.
     let gas_refunded = result.gas().refunded() as u64;
     let final_gas_used = result.gas().spent() - gas_refunded;
     let output = result.output();
     let instruction_result = result.into_interpreter_result();
+    let basefee = context.block().basefee() as u128;
+    let effective_gas_price = context.tx().effective_gas_price(basefee);
 
     // Reset journal and return present state.
     let (state, logs) = context.journal().finalize();
@@ -110,25 +117,29 @@ pub fn output<
             reason,
             gas_used: final_gas_used,
             gas_refunded,
+            effective_gas_price,
             logs,
             output,
         },
         SuccessOrHalt::Revert => ExecutionResult::Revert {
             gas_used: final_gas_used,
+            gas_refunded,
+            effective_gas_price,
             output: output.into_data(),
         },
         SuccessOrHalt::Halt(reason) => ExecutionResult::Halt {
             reason,
             gas_used: final_gas_used,
+            effective_gas_price,
+            debug: instruction_result.halt_debug,
         },
         // Only two internal return flags.
         flag @ (SuccessOrHalt::FatalExternalError | SuccessOrHalt::Internal(_)) => {
-            panic!(
-                "Encountered unexpected internal return flag: {:?} with instruction result: {:?}",
-                flag, instruction_result
-            )
+            return Err(ERROR::from_string(format!(
+                "encountered unexpected internal return flag: {flag:?} with instruction result: {instruction_result:?}"
+            )));
         }
     };
 
-    ResultAndState { result, state }
+    Ok(ResultAndState { result, state })
 }
@@ -61,19 +61,32 @@ where
         let Some(precompile) = self.precompiles.get(address) else {
             return Ok(None);
         };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("precompile", %address, gas_limit).entered();
 
         let mut result = InterpreterResult {
             result: InstructionResult::Return,
             gas: Gas::new(gas_limit),
             output: Bytes::new(),
+            halt_debug: None,
         };
 
-        match (*precompile)(bytes, gas_limit) {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let output = (*precompile)(bytes, gas_limit);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_precompile(address, started.elapsed());
+
+        match output {
             Ok(output) => {
-                let underflow = result.gas.record_cost(output.gas_used);
-                assert!(underflow, "Gas underflow is not possible");
-                result.result = InstructionResult::Return;
-                result.output = output.bytes;
+                if result.gas.record_cost(output.gas_used) {
+                    result.result = InstructionResult::Return;
+                    result.output = output.bytes;
+                } else {
+                    // Precompiles should fail instead of using more gas than the limit.
+                    result.gas.spend_all();
+                    result.result = InstructionResult::PrecompileOOG;
+                }
             }
             Err(PrecompileErrors::Error(e)) => {
                 result.result = if e.is_oog() {
@@ -243,10 +243,14 @@ pub fn validate_tx_against_account<CTX: TransactionGetter + CfgGetter>(
     // This EIP is introduced after london but there was no collision in past
     // so we can leave it enabled always
     if !context.cfg().is_eip3607_disabled() {
-        let bytecode = &account.code.as_ref().unwrap();
+        // Code is loaded before validation, missing code is treated as empty.
         // Allow EOAs whose code is a valid delegation designation,
         // i.e. 0xef0100 || address, to continue to originate transactions.
-        if !bytecode.is_empty() && !bytecode.is_eip7702() {
+        if account
+            .code
+            .as_ref()
+            .is_some_and(|bytecode| !bytecode.is_empty() && !bytecode.is_eip7702())
+        {
             return Err(InvalidTransaction::RejectCallerWithCode);
         }
     }
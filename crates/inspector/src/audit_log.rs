@@ -0,0 +1,397 @@
+//! AuditLog. Inspector that writes the committed state changes of each transaction as JSON lines.
+use crate::{Inspector, OpcodeFilter, TxOutcome};
+use revm::{
+    bytecode::opcode,
+    context_interface::DatabaseGetter,
+    database_interface::Database,
+    interpreter::{
+        interpreter_types::{InputsTrait, Jumps, StackTrait},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterTypes,
+    },
+    primitives::{Address, HashMap, B256, U256},
+};
+use serde::Serialize;
+#[cfg(feature = "std")]
+use std::io::Write;
+use std::{boxed::Box, vec::Vec};
+
+/// State change of an [`AuditRecord`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum StateChange {
+    Balance {
+        from: U256,
+        to: U256,
+    },
+    Nonce {
+        from: u64,
+        to: u64,
+    },
+    CodeHash {
+        from: B256,
+        to: B256,
+    },
+    Storage {
+        slot: U256,
+        from: U256,
+        to: U256,
+    },
+    /// The account was selfdestructed, its balance, nonce, code and storage are cleared.
+    Destroyed,
+}
+
+/// Committed change of one account field or storage slot, written as one JSON line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Hash of the transaction, see [`AuditLog::set_tx_hash`].
+    pub tx_hash: Option<B256>,
+    /// Address of the frame that made the change.
+    ///
+    /// [`None`] for changes made by the transaction itself, e.g. the gas payment, the nonce of
+    /// the caller or the value of the transaction.
+    pub frame: Option<Address>,
+    /// Account that changed.
+    pub address: Address,
+    #[serde(flatten)]
+    pub change: StateChange,
+}
+
+/// Destination of the audit records.
+enum Sink {
+    #[cfg(feature = "std")]
+    Writer(Box<dyn Write>),
+    Callback(Box<dyn FnMut(&AuditRecord)>),
+}
+
+/// Account, and storage slot if any, changed by a frame.
+type ChangeKey = (Address, Option<U256>);
+
+/// [`Inspector`] that emits a record for every account field and storage slot changed by a
+/// transaction, attributed to the frame that made the change.
+///
+/// Records are emitted when the transaction ends, ordered by address and slot, and only hold
+/// changes that were not reverted. Values a transaction changed and then restored are skipped.
+/// The state before the transaction is read from the database, so the inspector must be called
+/// before the state is committed, as [`InspectCommitEvm`](crate::exec::InspectCommitEvm) does.
+pub struct AuditLog {
+    output: Sink,
+    tx_hash: Option<B256>,
+    /// Changes of the frames that have not ended yet, innermost last.
+    frames: Vec<Vec<(ChangeKey, Address)>>,
+    /// Changes of ended frames that were not reverted, in the order they were made.
+    committed: Vec<(ChangeKey, Address)>,
+}
+
+impl AuditLog {
+    /// Creates a log writing one JSON object per line to `output`.
+    #[cfg(feature = "std")]
+    pub fn new(output: Box<dyn Write>) -> Self {
+        Self::with_sink(Sink::Writer(output))
+    }
+
+    /// Creates a log passing each record to `callback`.
+    pub fn with_callback(callback: impl FnMut(&AuditRecord) + 'static) -> Self {
+        Self::with_sink(Sink::Callback(Box::new(callback)))
+    }
+
+    fn with_sink(output: Sink) -> Self {
+        Self {
+            output,
+            tx_hash: None,
+            frames: Vec::new(),
+            committed: Vec::new(),
+        }
+    }
+
+    /// Sets the hash of the next transactions, recorded with their changes.
+    ///
+    /// The hash is not part of the transaction environment, so it has to be set before each
+    /// transaction.
+    pub fn set_tx_hash(&mut self, tx_hash: Option<B256>) {
+        self.tx_hash = tx_hash;
+    }
+
+    fn record_change(&mut self, key: ChangeKey, frame: Address) {
+        if let Some(changes) = self.frames.last_mut() {
+            changes.push((key, frame));
+        }
+    }
+
+    /// Keeps the changes of the ended frame if it succeeded.
+    fn frame_end(&mut self, success: bool) {
+        let Some(changes) = self.frames.pop() else {
+            return;
+        };
+        if !success {
+            return;
+        }
+        match self.frames.last_mut() {
+            Some(parent) => parent.extend(changes),
+            None => self.committed.extend(changes),
+        }
+    }
+
+    fn write(&mut self, record: &AuditRecord) {
+        match &mut self.output {
+            #[cfg(feature = "std")]
+            Sink::Writer(writer) => {
+                let _ = write_line(writer, record);
+            }
+            Sink::Callback(callback) => callback(record),
+        }
+    }
+}
+
+impl<CTX: DatabaseGetter, INTR: InterpreterTypes> Inspector<CTX, INTR> for AuditLog {
+    fn opcode_filter(&self) -> OpcodeFilter {
+        OpcodeFilter::none().with(opcode::SSTORE)
+    }
+
+    fn tx_start(&mut self, _context: &mut CTX) {
+        self.frames.clear();
+        self.committed.clear();
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        if interp.bytecode.opcode() != opcode::SSTORE {
+            return;
+        }
+        let address = interp.input.target_address();
+        if let Some(slot) = interp.stack.top().copied() {
+            self.record_change((address, Some(slot)), address);
+        }
+    }
+
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let mut changes = Vec::new();
+        // The value of the transaction is not transferred by a frame.
+        if !self.frames.is_empty() && inputs.transfers_value() {
+            changes.push(((inputs.caller, None), inputs.caller));
+            changes.push(((inputs.target_address, None), inputs.caller));
+        }
+        self.frames.push(changes);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(outcome.result.is_ok());
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        // The nonce of the creator is bumped even if the creation fails.
+        if !self.frames.is_empty() {
+            self.record_change((inputs.caller, None), inputs.caller);
+        }
+        let mut changes = Vec::new();
+        if !self.frames.is_empty() && !inputs.value.is_zero() {
+            changes.push(((inputs.caller, None), inputs.caller));
+        }
+        self.frames.push(changes);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let success = outcome.result.is_ok();
+        // The account of a create transaction is created by the transaction.
+        if success && self.frames.len() > 1 {
+            if let Some(address) = outcome.address {
+                self.record_change((address, None), inputs.caller);
+            }
+        }
+        self.frame_end(success);
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, _value: U256) {
+        self.record_change((contract, None), contract);
+        self.record_change((target, None), contract);
+    }
+
+    fn tx_end(&mut self, context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+        let frames: HashMap<ChangeKey, Address> = self.committed.drain(..).collect();
+        self.frames.clear();
+        let Some(outcome) = outcome else {
+            return;
+        };
+        let tx_hash = self.tx_hash;
+
+        let mut accounts: Vec<_> = outcome
+            .state
+            .iter()
+            .filter(|(_, account)| account.is_touched())
+            .collect();
+        accounts.sort_unstable_by_key(|(address, _)| **address);
+        for (&address, account) in accounts {
+            let record = |change, slot| AuditRecord {
+                tx_hash,
+                frame: frames.get(&(address, slot)).copied(),
+                address,
+                change,
+            };
+            let mut records = Vec::new();
+            if account.is_selfdestructed() {
+                records.push(record(StateChange::Destroyed, None));
+            } else {
+                let original = context
+                    .db()
+                    .basic(address)
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                let info = &account.info;
+                if original.balance != info.balance {
+                    records.push(record(
+                        StateChange::Balance {
+                            from: original.balance,
+                            to: info.balance,
+                        },
+                        None,
+                    ));
+                }
+                if original.nonce != info.nonce {
+                    records.push(record(
+                        StateChange::Nonce {
+                            from: original.nonce,
+                            to: info.nonce,
+                        },
+                        None,
+                    ));
+                }
+                if original.code_hash != info.code_hash {
+                    records.push(record(
+                        StateChange::CodeHash {
+                            from: original.code_hash,
+                            to: info.code_hash,
+                        },
+                        None,
+                    ));
+                }
+                let mut slots: Vec<_> = account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .collect();
+                slots.sort_unstable_by_key(|(slot, _)| **slot);
+                for (&slot, value) in slots {
+                    records.push(record(
+                        StateChange::Storage {
+                            slot,
+                            from: value.original_value,
+                            to: value.present_value,
+                        },
+                        Some(slot),
+                    ));
+                }
+            }
+            for record in &records {
+                self.write(record);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_line(writer: &mut dyn Write, value: &impl Serialize) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        primitives::{address, TxKind},
+        Context,
+    };
+    use std::{cell::RefCell, rc::Rc, vec};
+
+    #[test]
+    fn attributes_changes_to_frames() {
+        let callee = address!("0000000000000000000000000000000000001000");
+        // SSTORE(1, 7), CALL(gas, callee, value = 5, 0, 0, 0, 0)
+        let mut code = vec![opcode::PUSH1, 7, opcode::PUSH1, 1, opcode::SSTORE];
+        code.extend_from_slice(&[
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            5,
+            opcode::PUSH2,
+            0x10,
+            0x00,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::STOP,
+        ]);
+        let records = Rc::new(RefCell::new(Vec::new()));
+        let sink = records.clone();
+        let mut log = AuditLog::with_callback(move |record| sink.borrow_mut().push(record.clone()));
+        log.set_tx_hash(Some(B256::with_last_byte(1)));
+
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.value = U256::from(10);
+                tx.gas_limit = 100_000;
+                tx.gas_price = 0;
+                tx.gas_priority_fee = None;
+            });
+        ctx.inspect_previous(&mut log).unwrap();
+
+        let records = records.borrow();
+        let find = |address, slot: Option<U256>| {
+            records
+                .iter()
+                .find(|record| {
+                    record.address == address
+                        && match record.change {
+                            StateChange::Storage { slot: changed, .. } => Some(changed) == slot,
+                            _ => slot.is_none(),
+                        }
+                })
+                .unwrap()
+        };
+        assert!(records
+            .iter()
+            .all(|record| record.tx_hash == Some(B256::with_last_byte(1))));
+        assert_eq!(
+            find(BENCH_TARGET, Some(U256::from(1))),
+            &AuditRecord {
+                tx_hash: Some(B256::with_last_byte(1)),
+                frame: Some(BENCH_TARGET),
+                address: BENCH_TARGET,
+                change: StateChange::Storage {
+                    slot: U256::from(1),
+                    from: U256::ZERO,
+                    to: U256::from(7),
+                },
+            }
+        );
+        assert_eq!(
+            find(callee, None).change,
+            StateChange::Balance {
+                from: U256::ZERO,
+                to: U256::from(5),
+            }
+        );
+        assert_eq!(find(callee, None).frame, Some(BENCH_TARGET));
+        // The caller pays the value of the transaction itself.
+        assert_eq!(find(BENCH_CALLER, None).frame, None);
+
+        let line = serde_json::to_string(find(callee, None)).unwrap();
+        assert!(line.contains("\"kind\":\"balance\""), "{line}");
+        assert!(line.contains("\"txHash\""), "{line}");
+    }
+}
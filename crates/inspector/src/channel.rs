@@ -0,0 +1,440 @@
+//! ChannelInspector. Streams owned trace events over a bounded channel to a consumer thread.
+#[cfg(feature = "async")]
+use crate::stream::{trace_stream, StreamSender, TraceStream};
+use crate::{Inspector, TruncationConfig, TxOutcome};
+use revm::{
+    interpreter::{
+        interpreter_types::{Jumps, LoopControl},
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, EOFCreateInputs,
+        InstructionResult, Interpreter, InterpreterTypes,
+    },
+    primitives::{Address, Bytes, Log, U256},
+};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+
+/// Trace event sent by [`ChannelInspector`].
+///
+/// `depth` is zero for the first frame of a transaction. `*_len` fields hold the original length
+/// of data truncated by [`TruncationConfig`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
+pub enum TraceEvent {
+    TxStart,
+    /// End of a transaction, `success` is [`None`] if it was not executed.
+    TxEnd {
+        success: Option<bool>,
+        gas_used: u64,
+    },
+    /// Instruction about to be executed, sent only if enabled with [`ChannelInspector::with_steps`].
+    Step {
+        depth: usize,
+        pc: usize,
+        opcode: u8,
+        gas_remaining: u64,
+    },
+    Call {
+        depth: usize,
+        scheme: CallScheme,
+        caller: Address,
+        target: Address,
+        bytecode_address: Address,
+        value: U256,
+        input: Bytes,
+        input_len: Option<usize>,
+        gas_limit: u64,
+    },
+    CallEnd {
+        depth: usize,
+        result: InstructionResult,
+        gas_used: u64,
+        output: Bytes,
+        output_len: Option<usize>,
+    },
+    Create {
+        depth: usize,
+        caller: Address,
+        value: U256,
+        init_code: Bytes,
+        init_code_len: Option<usize>,
+        gas_limit: u64,
+    },
+    CreateEnd {
+        depth: usize,
+        address: Option<Address>,
+        result: InstructionResult,
+        gas_used: u64,
+        output: Bytes,
+        output_len: Option<usize>,
+    },
+    Log {
+        depth: usize,
+        log: Log,
+    },
+    Selfdestruct {
+        contract: Address,
+        target: Address,
+        value: U256,
+    },
+}
+
+/// What [`ChannelInspector`] does when the channel is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChannelOverflow {
+    /// Block execution until the consumer catches up, no event is lost.
+    #[default]
+    Block,
+    /// Drop the event and count it in [`ChannelInspector::dropped`].
+    Drop,
+}
+
+/// Outcome of sending an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SendStatus {
+    Sent,
+    Full,
+    Disconnected,
+}
+
+/// Sending end of the channel.
+#[derive(Debug)]
+enum Sender {
+    Sync(SyncSender<TraceEvent>),
+    #[cfg(feature = "async")]
+    Stream(StreamSender),
+}
+
+impl Sender {
+    /// Sends the event, waiting while the channel is full.
+    fn send(&self, event: TraceEvent) -> SendStatus {
+        match self {
+            Self::Sync(sender) => match sender.send(event) {
+                Ok(()) => SendStatus::Sent,
+                Err(_) => SendStatus::Disconnected,
+            },
+            #[cfg(feature = "async")]
+            Self::Stream(sender) => sender.send(event),
+        }
+    }
+
+    fn try_send(&self, event: TraceEvent) -> SendStatus {
+        match self {
+            Self::Sync(sender) => match sender.try_send(event) {
+                Ok(()) => SendStatus::Sent,
+                Err(TrySendError::Full(_)) => SendStatus::Full,
+                Err(TrySendError::Disconnected(_)) => SendStatus::Disconnected,
+            },
+            #[cfg(feature = "async")]
+            Self::Stream(sender) => sender.try_send(event),
+        }
+    }
+}
+
+/// [`Inspector`] that converts execution into owned [`TraceEvent`]s and sends them over a
+/// bounded channel, so heavy trace processing can run on another thread.
+///
+/// With the `async` feature, [`ChannelInspector::stream`] yields the events from an async
+/// stream instead, for services running the EVM on a blocking thread of an async runtime.
+///
+/// Call inputs and outputs are bounded with [`TruncationConfig`]. Once the receiver is
+/// dropped, no more events are produced.
+#[derive(Debug)]
+pub struct ChannelInspector {
+    sender: Sender,
+    overflow: ChannelOverflow,
+    truncation: TruncationConfig,
+    steps: bool,
+    depth: usize,
+    dropped: u64,
+    disconnected: bool,
+}
+
+impl ChannelInspector {
+    /// Creates an inspector sending to `sender`.
+    pub fn new(sender: SyncSender<TraceEvent>) -> Self {
+        Self::with_sender(Sender::Sync(sender))
+    }
+
+    /// Creates an inspector and the receiving end of a channel holding up to `capacity` events.
+    pub fn bounded(capacity: usize) -> (Self, Receiver<TraceEvent>) {
+        let (sender, receiver) = sync_channel(capacity);
+        (Self::new(sender), receiver)
+    }
+
+    /// Creates an inspector and a [`TraceStream`] yielding up to `capacity` buffered events.
+    ///
+    /// Unlike [`ChannelInspector::bounded`], the capacity is at least one event.
+    #[cfg(feature = "async")]
+    pub fn stream(capacity: usize) -> (Self, TraceStream) {
+        let (sender, stream) = trace_stream(capacity);
+        (Self::with_sender(Sender::Stream(sender)), stream)
+    }
+
+    fn with_sender(sender: Sender) -> Self {
+        Self {
+            sender,
+            overflow: ChannelOverflow::default(),
+            truncation: TruncationConfig::unlimited(),
+            steps: false,
+            depth: 0,
+            dropped: 0,
+            disconnected: false,
+        }
+    }
+
+    /// Sets what happens when the channel is full.
+    pub fn with_overflow(mut self, overflow: ChannelOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Limits the sent call inputs and outputs.
+    pub fn with_truncation(mut self, truncation: TruncationConfig) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Also send a [`TraceEvent::Step`] for every executed instruction.
+    pub fn with_steps(mut self) -> Self {
+        self.steps = true;
+        self
+    }
+
+    /// Returns the number of events dropped because the channel was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Returns `true` if the receiver was dropped.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    fn send(&mut self, event: TraceEvent) {
+        if self.disconnected {
+            return;
+        }
+        let status = match self.overflow {
+            ChannelOverflow::Block => self.sender.send(event),
+            ChannelOverflow::Drop => self.sender.try_send(event),
+        };
+        match status {
+            SendStatus::Sent => {}
+            SendStatus::Full => self.dropped += 1,
+            SendStatus::Disconnected => self.disconnected = true,
+        }
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for ChannelInspector {
+    fn tx_start(&mut self, _context: &mut CTX) {
+        self.depth = 0;
+        self.send(TraceEvent::TxStart);
+    }
+
+    fn tx_end(&mut self, _context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+        self.send(TraceEvent::TxEnd {
+            success: outcome.map(|outcome| outcome.success),
+            gas_used: outcome.map_or(0, |outcome| outcome.gas_used),
+        });
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        if !self.steps || self.disconnected {
+            return;
+        }
+        self.send(TraceEvent::Step {
+            depth: self.depth.saturating_sub(1),
+            pc: interp.bytecode.pc(),
+            opcode: interp.bytecode.opcode(),
+            gas_remaining: interp.control.gas().remaining(),
+        });
+    }
+
+    fn log(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX, log: &Log) {
+        self.send(TraceEvent::Log {
+            depth: self.depth.saturating_sub(1),
+            log: log.clone(),
+        });
+    }
+
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let (input, input_len) = self.truncation.input(&inputs.input);
+        self.send(TraceEvent::Call {
+            depth: self.depth,
+            scheme: inputs.scheme,
+            caller: inputs.caller,
+            target: inputs.target_address,
+            bytecode_address: inputs.bytecode_address,
+            value: inputs.call_value(),
+            input,
+            input_len,
+            gas_limit: inputs.gas_limit,
+        });
+        self.depth += 1;
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.depth = self.depth.saturating_sub(1);
+        let (output, output_len) = self.truncation.output(&outcome.result.output);
+        self.send(TraceEvent::CallEnd {
+            depth: self.depth,
+            result: outcome.result.result,
+            gas_used: outcome.result.gas.spent(),
+            output,
+            output_len,
+        });
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let (init_code, init_code_len) = self.truncation.input(&inputs.init_code);
+        self.send(TraceEvent::Create {
+            depth: self.depth,
+            caller: inputs.caller,
+            value: inputs.value,
+            init_code,
+            init_code_len,
+            gas_limit: inputs.gas_limit,
+        });
+        self.depth += 1;
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        let (output, output_len) = self.truncation.output(&outcome.result.output);
+        self.send(TraceEvent::CreateEnd {
+            depth: self.depth,
+            address: outcome.address,
+            result: outcome.result.result,
+            gas_used: outcome.result.gas.spent(),
+            output,
+            output_len,
+        });
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.send(TraceEvent::Create {
+            depth: self.depth,
+            caller: inputs.caller,
+            value: inputs.value,
+            init_code: Bytes::new(),
+            init_code_len: None,
+            gas_limit: inputs.gas_limit,
+        });
+        self.depth += 1;
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        let (output, output_len) = self.truncation.output(&outcome.result.output);
+        self.send(TraceEvent::CreateEnd {
+            depth: self.depth,
+            address: outcome.address,
+            result: outcome.result.result,
+            gas_used: outcome.result.gas.spent(),
+            output,
+            output_len,
+        });
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        self.send(TraceEvent::Selfdestruct {
+            contract,
+            target,
+            value,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::TxKind,
+        Context,
+    };
+    use std::{thread, vec::Vec};
+
+    #[test]
+    fn streams_to_consumer_thread() {
+        let code = [opcode::PUSH1, 0x01, opcode::STOP];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+
+        // Capacity of one event forces the execution to wait for the consumer.
+        let (inspector, receiver) = ChannelInspector::bounded(1);
+        let consumer = thread::spawn(move || receiver.iter().collect::<Vec<_>>());
+        ctx.inspect_previous(inspector.with_steps()).unwrap();
+        let events = consumer.join().unwrap();
+
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0], TraceEvent::TxStart);
+        assert!(matches!(
+            events[1],
+            TraceEvent::Call { depth: 0, target, .. } if target == BENCH_TARGET
+        ));
+        assert!(matches!(
+            events[3],
+            TraceEvent::Step {
+                depth: 0,
+                pc: 2,
+                opcode: opcode::STOP,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[4],
+            TraceEvent::CallEnd {
+                result: InstructionResult::Stop,
+                ..
+            }
+        ));
+        assert_eq!(
+            events[5],
+            TraceEvent::TxEnd {
+                success: Some(true),
+                gas_used: 21_003,
+            }
+        );
+    }
+
+    #[test]
+    fn drops_when_full() {
+        let (inspector, receiver) = ChannelInspector::bounded(1);
+        let mut inspector = inspector.with_overflow(ChannelOverflow::Drop);
+        inspector.send(TraceEvent::TxStart);
+        inspector.send(TraceEvent::TxStart);
+        assert_eq!(inspector.dropped(), 1);
+
+        drop(receiver);
+        inspector.send(TraceEvent::TxStart);
+        assert!(inspector.is_disconnected());
+    }
+}
@@ -0,0 +1,240 @@
+//! CheatcodeInspector. Dispatches calls to a magic address to user provided handlers.
+use crate::Inspector;
+use revm::{
+    interpreter::{CallInputs, CallOutcome, InstructionResult, InterpreterTypes},
+    primitives::{address, Address, Bytes, HashMap},
+};
+use std::boxed::Box;
+
+/// Default cheatcode address, `address(bytes20(uint160(uint256(keccak256("hevm cheat code")))))`.
+pub const CHEATCODE_ADDRESS: Address = address!("7109709ECfa91a80626fF3989D68f67F5b1DD12D");
+
+/// Call to the cheatcode address, passed to a [`CheatcodeHandler`].
+pub struct Cheatcall<'a, CTX> {
+    /// Context of the running transaction, giving access to the journal.
+    pub context: &'a mut CTX,
+    /// Inputs of the call, `inputs.input` starts with the selector of the cheatcode.
+    pub inputs: &'a CallInputs,
+    /// Caller override of the next call, see [`Prank`].
+    pub prank: &'a mut Option<Prank>,
+}
+
+impl<CTX> Cheatcall<'_, CTX> {
+    /// Returns the ABI encoded arguments of the cheatcode, following its selector.
+    pub fn args(&self) -> &[u8] {
+        self.inputs.input.get(4..).unwrap_or_default()
+    }
+}
+
+/// Replaces the caller of the next call made by `sender`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Prank {
+    /// Account whose next call is pranked, usually the caller of the cheatcode.
+    pub sender: Address,
+    /// Caller seen by the called contract.
+    pub caller: Address,
+}
+
+/// Handler of a cheatcode.
+///
+/// Returns the output of the call, or the revert data if the cheatcode failed.
+pub type CheatcodeHandler<CTX> = Box<dyn FnMut(&mut Cheatcall<'_, CTX>) -> Result<Bytes, Bytes>>;
+
+/// [`Inspector`] that intercepts calls to a cheatcode address and dispatches them by selector
+/// to registered handlers.
+///
+/// Cheatcode calls don't execute any code and don't spend gas. Calls with an unknown or missing
+/// selector revert with empty revert data.
+pub struct CheatcodeInspector<CTX> {
+    address: Address,
+    handlers: HashMap<[u8; 4], CheatcodeHandler<CTX>>,
+    prank: Option<Prank>,
+}
+
+impl<CTX> Default for CheatcodeInspector<CTX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CTX> core::fmt::Debug for CheatcodeInspector<CTX> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CheatcodeInspector")
+            .field("address", &self.address)
+            .field("selectors", &self.handlers.keys())
+            .field("prank", &self.prank)
+            .finish()
+    }
+}
+
+impl<CTX> CheatcodeInspector<CTX> {
+    /// Creates an inspector listening on [`CHEATCODE_ADDRESS`] without handlers.
+    pub fn new() -> Self {
+        Self::with_address(CHEATCODE_ADDRESS)
+    }
+
+    /// Creates an inspector listening on `address` without handlers.
+    pub fn with_address(address: Address) -> Self {
+        Self {
+            address,
+            handlers: HashMap::default(),
+            prank: None,
+        }
+    }
+
+    /// Registers the handler of the cheatcode with the given selector, replacing the previous one.
+    pub fn with_handler(
+        mut self,
+        selector: [u8; 4],
+        handler: impl FnMut(&mut Cheatcall<'_, CTX>) -> Result<Bytes, Bytes> + 'static,
+    ) -> Self {
+        self.insert_handler(selector, handler);
+        self
+    }
+
+    /// Registers the handler of the cheatcode with the given selector, returns the previous one.
+    pub fn insert_handler(
+        &mut self,
+        selector: [u8; 4],
+        handler: impl FnMut(&mut Cheatcall<'_, CTX>) -> Result<Bytes, Bytes> + 'static,
+    ) -> Option<CheatcodeHandler<CTX>> {
+        self.handlers.insert(selector, Box::new(handler))
+    }
+
+    /// Returns the address the inspector listens on.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the pending prank.
+    pub fn prank(&self) -> Option<&Prank> {
+        self.prank.as_ref()
+    }
+
+    fn dispatch(&mut self, context: &mut CTX, inputs: &CallInputs) -> CallOutcome {
+        let handler = inputs
+            .input
+            .get(..4)
+            .and_then(|selector| self.handlers.get_mut(selector));
+        let result = match handler {
+            Some(handler) => handler(&mut Cheatcall {
+                context,
+                inputs,
+                prank: &mut self.prank,
+            }),
+            None => Err(Bytes::new()),
+        };
+        match result {
+            Ok(output) => CallOutcome::from_inputs(inputs, InstructionResult::Return, output),
+            Err(output) => CallOutcome::from_inputs(inputs, InstructionResult::Revert, output),
+        }
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for CheatcodeInspector<CTX> {
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if inputs.target_address == self.address {
+            return Some(self.dispatch(context, inputs));
+        }
+        if let Some(prank) = self.prank.take_if(|prank| prank.sender == inputs.caller) {
+            inputs.caller = prank.caller;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::JournalGetter,
+        primitives::{TxKind, U256},
+        Context,
+    };
+    use std::vec;
+
+    #[test]
+    fn dispatches_by_selector() {
+        let selector = [0x12, 0x34, 0x56, 0x78];
+        // mstore(0, selector), mstore(4, 42), call(gas, CHEATCODE_ADDRESS, 0, 0, 36, 0, 32),
+        // return(0, 32)
+        let mut code = vec![
+            opcode::PUSH4,
+            0x12,
+            0x34,
+            0x56,
+            0x78,
+            opcode::PUSH1,
+            0xe0,
+            opcode::SHL,
+            opcode::PUSH1,
+            0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            42,
+            opcode::PUSH1,
+            4,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            36,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH20,
+        ];
+        code.extend_from_slice(CHEATCODE_ADDRESS.as_slice());
+        code.extend([
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH1,
+            0,
+            opcode::RETURN,
+        ]);
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+
+        // Sets the balance of the caller to the argument and returns the argument plus one.
+        type Ctx = Context<BlockEnv, TxEnv, CfgEnv, BenchmarkDB>;
+        let mut inspector = CheatcodeInspector::<&mut Ctx>::new().with_handler(selector, |call| {
+            let value = U256::from_be_slice(call.args());
+            let caller = call.inputs.caller;
+            let account = call.context.journal().load_account(caller).unwrap().data;
+            account.info.balance = value;
+            *call.prank = Some(Prank {
+                sender: caller,
+                caller: Address::ZERO,
+            });
+            Ok((value + U256::from(1)).to_be_bytes::<32>().to_vec().into())
+        });
+        let result = ctx.inspect_previous(&mut inspector).unwrap();
+
+        assert_eq!(
+            result.result.output().map(|output| output.to_vec()),
+            Some(U256::from(43).to_be_bytes::<32>().to_vec())
+        );
+        assert_eq!(result.state[&BENCH_TARGET].info.balance, U256::from(42));
+        assert_eq!(
+            inspector.prank(),
+            Some(&Prank {
+                sender: BENCH_TARGET,
+                caller: Address::ZERO,
+            })
+        );
+    }
+}
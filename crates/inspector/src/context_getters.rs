@@ -0,0 +1,266 @@
+//! Forwarding of the context getter traits to a wrapped context.
+
+/// Implements the context getter traits for a wrapper type by forwarding them to one of its fields.
+///
+/// Lets a context carrying extra data, e.g. labels or cheatcode state read by an inspector, be
+/// used wherever the wrapped context is, including [`InspectorContext`](crate::inspector_context::InspectorContext).
+/// Each trait is only implemented when the field implements it:
+/// [`BlockGetter`], [`BlockSetter`], [`TransactionGetter`], [`TransactionSetter`], [`CfgGetter`],
+/// [`DatabaseGetter`], [`JournalGetter`], [`ErrorGetter`], [`PerformantContextAccess`],
+/// [`Host`] and [`JournalExtGetter`](crate::journal::JournalExtGetter). [`EthContext`] is
+/// implemented for the wrapper and a mutable reference to it, so it can be executed with
+/// [`inspect_main`](crate::exec::inspect_main).
+///
+/// Generic parameters of the impls are given in square brackets, followed by the wrapper type,
+/// the field and its type, and optional where clauses added to every impl.
+///
+/// ```
+/// use revm_inspector::forward_context_getters;
+///
+/// pub struct Labeled<CTX> {
+///     pub inner: CTX,
+///     pub labels: Vec<String>,
+/// }
+///
+/// forward_context_getters!(impl[CTX] Labeled<CTX> => inner: CTX);
+/// ```
+///
+/// [`BlockGetter`]: revm::context_interface::BlockGetter
+/// [`BlockSetter`]: revm::context_interface::block::BlockSetter
+/// [`TransactionGetter`]: revm::context_interface::TransactionGetter
+/// [`TransactionSetter`]: revm::context_interface::transaction::TransactionSetter
+/// [`CfgGetter`]: revm::context_interface::CfgGetter
+/// [`DatabaseGetter`]: revm::context_interface::DatabaseGetter
+/// [`JournalGetter`]: revm::context_interface::JournalGetter
+/// [`ErrorGetter`]: revm::context_interface::ErrorGetter
+/// [`PerformantContextAccess`]: revm::context_interface::PerformantContextAccess
+/// [`Host`]: revm::context_interface::host::Host
+/// [`EthContext`]: revm::handler::handler::EthContext
+#[macro_export]
+macro_rules! forward_context_getters {
+    (impl[$($gen:tt)*] $ty:ty => $field:ident: $inner:ty $(where $($bound:tt)*)?) => {
+        impl<$($gen)*> $crate::__private::revm::context_interface::CfgGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::CfgGetter,
+            $($($bound)*)?
+        {
+            type Cfg = <$inner as $crate::__private::revm::context_interface::CfgGetter>::Cfg;
+
+            fn cfg(&self) -> &Self::Cfg {
+                self.$field.cfg()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::BlockGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::BlockGetter,
+            $($($bound)*)?
+        {
+            type Block = <$inner as $crate::__private::revm::context_interface::BlockGetter>::Block;
+
+            fn block(&self) -> &Self::Block {
+                self.$field.block()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::block::BlockSetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::block::BlockSetter,
+            $($($bound)*)?
+        {
+            fn set_block(
+                &mut self,
+                block: <Self as $crate::__private::revm::context_interface::BlockGetter>::Block,
+            ) {
+                self.$field.set_block(block);
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::TransactionGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::TransactionGetter,
+            $($($bound)*)?
+        {
+            type Transaction =
+                <$inner as $crate::__private::revm::context_interface::TransactionGetter>::Transaction;
+
+            fn tx(&self) -> &Self::Transaction {
+                self.$field.tx()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::transaction::TransactionSetter
+            for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::transaction::TransactionSetter,
+            $($($bound)*)?
+        {
+            fn set_tx(
+                &mut self,
+                tx: <Self as $crate::__private::revm::context_interface::TransactionGetter>::Transaction,
+            ) {
+                self.$field.set_tx(tx);
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::DatabaseGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::DatabaseGetter,
+            $($($bound)*)?
+        {
+            type Database =
+                <$inner as $crate::__private::revm::context_interface::DatabaseGetter>::Database;
+
+            fn db(&mut self) -> &mut Self::Database {
+                self.$field.db()
+            }
+
+            fn db_ref(&self) -> &Self::Database {
+                self.$field.db_ref()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::JournalGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::JournalGetter,
+            $($($bound)*)?
+        {
+            type Journal =
+                <$inner as $crate::__private::revm::context_interface::JournalGetter>::Journal;
+
+            fn journal(&mut self) -> &mut Self::Journal {
+                self.$field.journal()
+            }
+
+            fn journal_ref(&self) -> &Self::Journal {
+                self.$field.journal_ref()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::ErrorGetter for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::ErrorGetter,
+            $($($bound)*)?
+        {
+            type Error = <$inner as $crate::__private::revm::context_interface::ErrorGetter>::Error;
+
+            fn take_error(&mut self) -> Result<(), Self::Error> {
+                self.$field.take_error()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::PerformantContextAccess for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::PerformantContextAccess,
+            $($($bound)*)?
+        {
+            type Error =
+                <$inner as $crate::__private::revm::context_interface::PerformantContextAccess>::Error;
+
+            fn load_access_list(&mut self) -> Result<(), Self::Error> {
+                self.$field.load_access_list()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::context_interface::host::Host for $ty
+        where
+            $inner: $crate::__private::revm::context_interface::host::Host,
+            $($($bound)*)?
+        {
+            fn set_error(
+                &mut self,
+                error: <<<Self as $crate::__private::revm::context_interface::JournalGetter>::Journal
+                    as $crate::__private::revm::context_interface::Journal>::Database
+                    as $crate::__private::revm::context_interface::Database>::Error,
+            ) {
+                self.$field.set_error(error);
+            }
+        }
+
+        impl<$($gen)*> $crate::journal::JournalExtGetter for $ty
+        where
+            $inner: $crate::journal::JournalExtGetter,
+            $($($bound)*)?
+        {
+            type JournalExt = <$inner as $crate::journal::JournalExtGetter>::JournalExt;
+
+            fn journal_ext(&self) -> &Self::JournalExt {
+                self.$field.journal_ext()
+            }
+        }
+
+        impl<$($gen)*> $crate::__private::revm::handler::handler::EthContext for $ty
+        where
+            $inner: $crate::__private::revm::handler::handler::EthContext,
+            $($($bound)*)?
+        {
+        }
+
+        impl<$($gen)*> $crate::__private::revm::handler::handler::EthContext for &mut $ty
+        where
+            $inner: $crate::__private::revm::handler::handler::EthContext,
+            $($($bound)*)?
+        {
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{exec::inspect_main, inspector_context::InspectorContext, Inspector};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        interpreter::{interpreter::EthInterpreter, CallInputs, CallOutcome},
+        primitives::{Address, HashMap, TxKind},
+        Context,
+    };
+    use std::vec::Vec;
+
+    struct Labeled<CTX> {
+        inner: CTX,
+        labels: HashMap<Address, &'static str>,
+    }
+
+    forward_context_getters!(impl[CTX] Labeled<CTX> => inner: CTX);
+
+    /// Records the label of each called contract.
+    #[derive(Default)]
+    struct LabelRecorder {
+        called: Vec<&'static str>,
+    }
+
+    impl<CTX> Inspector<&mut Labeled<CTX>, EthInterpreter> for LabelRecorder {
+        fn call(
+            &mut self,
+            context: &mut &mut Labeled<CTX>,
+            inputs: &mut CallInputs,
+        ) -> Option<CallOutcome> {
+            let label = context.labels.get(&inputs.target_address);
+            self.called.push(label.copied().unwrap_or("unknown"));
+            None
+        }
+    }
+
+    #[test]
+    fn wrapped_context() {
+        let inner = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                [opcode::STOP].to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut ctx = Labeled {
+            inner,
+            labels: HashMap::from_iter([(BENCH_TARGET, "target")]),
+        };
+
+        let mut recorder = LabelRecorder::default();
+        let result = inspect_main(&mut InspectorContext::new(&mut ctx, &mut recorder)).unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(recorder.called, ["target"]);
+    }
+}
@@ -0,0 +1,280 @@
+//! CoverageInspector. Records executed program counters per bytecode for coverage guided fuzzing.
+use crate::Inspector;
+use revm::{
+    bytecode::opcode,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{Jumps, LoopControl},
+        InstructionResult, Interpreter,
+    },
+    primitives::{HashMap, B256},
+};
+use std::{vec, vec::Vec};
+
+/// Number of bits in the edge map of a single bytecode.
+pub const EDGE_MAP_SIZE: usize = 1 << 16;
+
+/// Growable bitmap indexed by program counter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PcBitmap {
+    words: Vec<u64>,
+}
+
+impl PcBitmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a bitmap that can hold `len` bits without reallocating.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    /// Sets the bit at `index`, returns `true` if it was not set before.
+    #[inline]
+    pub fn set(&mut self, index: usize) -> bool {
+        let word = index / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let mask = 1 << (index % 64);
+        let is_new = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        is_new
+    }
+
+    /// Returns `true` if the bit at `index` is set.
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// Returns the number of set bits.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns `true` if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Unsets all bits, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Sets all bits that are set in `other`, returns the number of newly set bits.
+    pub fn merge(&mut self, other: &Self) -> usize {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        let mut new = 0;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            new += (other & !*word).count_ones() as usize;
+            *word |= other;
+        }
+        new
+    }
+
+    /// Iterates over the indices of set bits in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// Coverage of a single bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeCoverage {
+    /// Executed program counters.
+    pub pcs: PcBitmap,
+    /// Taken `JUMP`/`JUMPI` edges, hashed into [`EDGE_MAP_SIZE`] bits.
+    ///
+    /// Empty if edge coverage is disabled.
+    pub edges: PcBitmap,
+}
+
+impl CodeCoverage {
+    /// Unsets all bits, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.pcs.clear();
+        self.edges.clear();
+    }
+
+    /// Merges `other` into `self`, returns the number of newly covered program counters and edges.
+    pub fn merge(&mut self, other: &Self) -> usize {
+        self.pcs.merge(&other.pcs) + self.edges.merge(&other.edges)
+    }
+}
+
+/// Returns the index of the `from -> to` edge in the edge map.
+#[inline]
+pub fn edge_index(from: usize, to: usize) -> usize {
+    (((from as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) ^ to as u64) as usize
+        & (EDGE_MAP_SIZE - 1)
+}
+
+/// [`Inspector`] that records executed program counters, and optionally taken jump edges,
+/// for every executed bytecode keyed by its code hash.
+///
+/// Maps are kept between transactions. [`CoverageInspector::reset`] clears them while keeping
+/// their allocations so the inspector can be reused for every fuzz input, and
+/// [`CoverageInspector::merge`] accumulates a run into a corpus wide coverage.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    coverage: HashMap<B256, CodeCoverage>,
+    edges: bool,
+    /// Code hash and program counter of the last executed jump.
+    jump: Option<(B256, usize)>,
+}
+
+impl CoverageInspector {
+    /// Creates an inspector that records program counters only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an inspector that records program counters and jump edges.
+    pub fn with_edges() -> Self {
+        Self {
+            edges: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the coverage of every executed bytecode.
+    pub fn coverage(&self) -> &HashMap<B256, CodeCoverage> {
+        &self.coverage
+    }
+
+    /// Returns the coverage of the bytecode with the given hash.
+    pub fn get(&self, code_hash: &B256) -> Option<&CodeCoverage> {
+        self.coverage.get(code_hash)
+    }
+
+    /// Returns the total number of covered program counters.
+    pub fn covered_pcs(&self) -> usize {
+        self.coverage.values().map(|c| c.pcs.count()).sum()
+    }
+
+    /// Clears the coverage, keeping the allocated maps.
+    pub fn reset(&mut self) {
+        self.coverage.values_mut().for_each(CodeCoverage::clear);
+        self.jump = None;
+    }
+
+    /// Merges the coverage of `other` into `self`, returns the number of newly covered
+    /// program counters and edges.
+    pub fn merge(&mut self, other: &Self) -> usize {
+        other
+            .coverage
+            .iter()
+            .map(|(hash, coverage)| self.coverage.entry(*hash).or_default().merge(coverage))
+            .sum()
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for CoverageInspector {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let hash = match interp.bytecode.hash() {
+            Some(hash) => hash,
+            None => interp.bytecode.regenerate_hash(),
+        };
+        let pc = interp.bytecode.pc();
+        self.coverage.entry(hash).or_default().pcs.set(pc);
+
+        let opcode = interp.bytecode.opcode();
+        if self.edges && matches!(opcode, opcode::JUMP | opcode::JUMPI) {
+            self.jump = Some((hash, pc));
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        // `step_end` of a jump directly follows its `step`, the program counter is the jump target.
+        let Some((hash, from)) = self.jump.take() else {
+            return;
+        };
+        if interp.control.instruction_result() != InstructionResult::Continue {
+            return;
+        }
+        let to = interp.bytecode.pc();
+        let edges = &mut self.coverage.entry(hash).or_default().edges;
+        if edges.words.is_empty() {
+            *edges = PcBitmap::with_len(EDGE_MAP_SIZE);
+        }
+        edges.set(edge_index(from, to));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{bytecode::Bytecode, primitives::TxKind, Context};
+
+    #[test]
+    fn bitmap_merge() {
+        let mut a = PcBitmap::new();
+        assert!(a.set(3));
+        assert!(!a.set(3));
+        let mut b = PcBitmap::with_len(8);
+        b.set(3);
+        b.set(130);
+        assert_eq!(a.merge(&b), 1);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [3, 130]);
+        a.clear();
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn records_pcs_and_edges() {
+        // PUSH1 1, PUSH1 6, JUMPI, INVALID, JUMPDEST, STOP
+        let code = Bytecode::new_legacy(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x06,
+                opcode::JUMPI,
+                opcode::INVALID,
+                opcode::JUMPDEST,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+        let hash = code.hash_slow();
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut inspector = CoverageInspector::with_edges();
+        ctx.inspect_previous(&mut inspector).unwrap();
+
+        let coverage = inspector.get(&hash).unwrap();
+        assert_eq!(coverage.pcs.iter().collect::<Vec<_>>(), [0, 2, 4, 6, 7]);
+        assert_eq!(coverage.edges.count(), 1);
+        assert!(coverage.edges.contains(edge_index(4, 6)));
+
+        let mut corpus = CoverageInspector::new();
+        assert_eq!(corpus.merge(&inspector), 6);
+        assert_eq!(corpus.merge(&inspector), 0);
+
+        inspector.reset();
+        assert_eq!(inspector.covered_pcs(), 0);
+    }
+}
@@ -0,0 +1,203 @@
+//! CreateTracker. Inspector that records every contract creation.
+use crate::{
+    journal::{JournalExt, JournalExtGetter},
+    Inspector,
+};
+use revm::{
+    context_interface::{Journal, JournalGetter},
+    interpreter::{CreateInputs, CreateOutcome, CreateScheme, InstructionResult, InterpreterTypes},
+    primitives::{keccak256, Address, B256, U256},
+};
+use std::vec::Vec;
+
+/// Contract creation recorded by [`CreateTracker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContractCreation {
+    /// Account that executed the `CREATE`/`CREATE2`, or the caller of a create transaction.
+    pub creator: Address,
+    /// Address derived for the new contract.
+    ///
+    /// Set even if the creation failed before the account was created.
+    pub address: Address,
+    /// Create scheme.
+    pub scheme: CreateScheme,
+    /// Salt of a `CREATE2`.
+    pub salt: Option<U256>,
+    /// Keccak256 hash of the init code.
+    pub init_code_hash: B256,
+    /// Value sent to the new contract.
+    pub value: U256,
+    /// Call depth at which the creation was started, zero for a create transaction.
+    pub depth: usize,
+    /// Result of the creation, [`None`] until the creation has ended.
+    pub result: Option<InstructionResult>,
+    /// Size of the deployed code, zero if the creation failed.
+    pub code_size: usize,
+}
+
+impl ContractCreation {
+    /// Returns `true` if the contract was deployed.
+    pub fn is_success(&self) -> bool {
+        self.result.is_some_and(|result| result.is_ok())
+    }
+}
+
+/// [`Inspector`] that records every `CREATE` and `CREATE2`, including create transactions.
+///
+/// Creations are recorded in the order they are started.
+#[derive(Clone, Debug, Default)]
+pub struct CreateTracker {
+    creations: Vec<ContractCreation>,
+    /// Indices into `creations` of the creations that have not ended yet.
+    pending: Vec<usize>,
+}
+
+impl CreateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns recorded creations.
+    pub fn creations(&self) -> &[ContractCreation] {
+        &self.creations
+    }
+
+    /// Consumes the tracker and returns recorded creations.
+    pub fn into_creations(self) -> Vec<ContractCreation> {
+        self.creations
+    }
+
+    /// Returns successfully deployed contracts.
+    pub fn deployed(&self) -> impl Iterator<Item = &ContractCreation> {
+        self.creations.iter().filter(|c| c.is_success())
+    }
+
+    /// Clears recorded creations, making the tracker ready to be used again.
+    pub fn clear(&mut self) {
+        self.creations.clear();
+        self.pending.clear();
+    }
+}
+
+impl<CTX: JournalGetter + JournalExtGetter, INTR: InterpreterTypes> Inspector<CTX, INTR>
+    for CreateTracker
+{
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        // Caller is loaded at this point, its nonce is bumped only when the frame is created.
+        let nonce = context
+            .journal_ext()
+            .evm_state()
+            .get(&inputs.caller)
+            .map(|account| account.info.nonce)
+            .unwrap_or_default();
+        let init_code_hash = keccak256(&inputs.init_code);
+        let (address, salt) = match inputs.scheme {
+            CreateScheme::Create => (inputs.caller.create(nonce), None),
+            CreateScheme::Create2 { salt } => (
+                inputs.caller.create2(salt.to_be_bytes(), init_code_hash),
+                Some(salt),
+            ),
+        };
+
+        self.pending.push(self.creations.len());
+        self.creations.push(ContractCreation {
+            creator: inputs.caller,
+            address,
+            scheme: inputs.scheme,
+            salt,
+            init_code_hash,
+            value: inputs.value,
+            depth: context.journal_ref().depth(),
+            result: None,
+            code_size: 0,
+        });
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let Some(creation) = self
+            .pending
+            .pop()
+            .and_then(|index| self.creations.get_mut(index))
+        else {
+            return;
+        };
+        let result = *outcome.instruction_result();
+        creation.result = Some(result);
+        if result.is_ok() {
+            creation.code_size = outcome.output().len();
+            if let Some(address) = outcome.address {
+                creation.address = address;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::TxKind,
+        Context,
+    };
+
+    #[test]
+    fn records_create2() {
+        // Init code returning two bytes of code.
+        let init_code = [opcode::PUSH1, 0x02, opcode::PUSH1, 0x00, opcode::RETURN];
+        let mut code = vec![opcode::PUSH5];
+        code.extend_from_slice(&init_code);
+        code.extend_from_slice(&[
+            opcode::PUSH1,
+            0x00,
+            opcode::MSTORE,
+            // CREATE2(value = 0, offset = 27, size = 5, salt = 0x42)
+            opcode::PUSH1,
+            0x42,
+            opcode::PUSH1,
+            0x05,
+            opcode::PUSH1,
+            0x1b,
+            opcode::PUSH1,
+            0x00,
+            opcode::CREATE2,
+            opcode::STOP,
+        ]);
+
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut tracker = CreateTracker::new();
+        ctx.inspect_previous(&mut tracker).unwrap();
+
+        let salt = U256::from(0x42);
+        let init_code_hash = keccak256(init_code);
+        assert_eq!(
+            tracker.creations(),
+            [ContractCreation {
+                creator: BENCH_TARGET,
+                address: BENCH_TARGET.create2(salt.to_be_bytes(), init_code_hash),
+                scheme: CreateScheme::Create2 { salt },
+                salt: Some(salt),
+                init_code_hash,
+                value: U256::ZERO,
+                depth: 1,
+                result: Some(InstructionResult::Return),
+                code_size: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,502 @@
+//! Differential execution. Runs a transaction on revm and on a reference EVM and compares them.
+use crate::{exec::InspectEvm, journal::JournalExt, Inspector};
+use core::fmt;
+use revm::{
+    context::Cfg,
+    context_interface::{
+        result::{EVMError, ExecutionResult, HaltReasonTrait, InvalidTransaction, ResultAndState},
+        Block, Journal, JournalGetter, Transaction,
+    },
+    database_interface::Database,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{Jumps, LoopControl},
+        Interpreter,
+    },
+    primitives::{Address, Bytes, Log, B256, U256},
+    state::EvmState,
+    Context,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Outcome of a transaction, see [`Execution::status`].
+///
+/// Halt reasons are not compared, implementations rarely agree on them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExecutionStatus {
+    Success,
+    Revert,
+    Halt,
+}
+
+/// State of an account touched by the transaction, after it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountDiff {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code_hash: B256,
+    /// Present value of the storage slots changed by the transaction.
+    pub storage: BTreeMap<U256, U256>,
+    /// `true` if the account was selfdestructed.
+    pub destroyed: bool,
+}
+
+/// Instruction executed by a transaction, recorded before it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    /// Call depth, zero for the first frame of the transaction.
+    pub depth: usize,
+    pub pc: usize,
+    pub opcode: u8,
+    pub gas_remaining: u64,
+    /// Stack, with the top of the stack last.
+    pub stack: Vec<U256>,
+}
+
+/// Execution of a transaction, normalized so executions of different EVMs can be compared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Execution {
+    pub status: ExecutionStatus,
+    pub gas_used: u64,
+    /// Returned or reverted data, empty if the transaction halted.
+    pub output: Bytes,
+    /// Logs emitted by the transaction, empty if it did not succeed.
+    pub logs: Vec<Log>,
+    /// Accounts touched by the transaction.
+    pub state: BTreeMap<Address, AccountDiff>,
+    /// Executed instructions, empty if the EVM does not record them.
+    pub trace: Vec<TraceStep>,
+}
+
+impl Execution {
+    /// Normalizes a revm execution result. The trace is left empty.
+    pub fn from_result<HaltReasonT: HaltReasonTrait>(result: &ResultAndState<HaltReasonT>) -> Self {
+        let (status, gas_used, output, logs) = match &result.result {
+            ExecutionResult::Success {
+                gas_used,
+                logs,
+                output,
+                ..
+            } => (
+                ExecutionStatus::Success,
+                *gas_used,
+                output.data().clone(),
+                logs.clone(),
+            ),
+            ExecutionResult::Revert {
+                gas_used, output, ..
+            } => (
+                ExecutionStatus::Revert,
+                *gas_used,
+                output.clone(),
+                Vec::new(),
+            ),
+            ExecutionResult::Halt { gas_used, .. } => {
+                (ExecutionStatus::Halt, *gas_used, Bytes::new(), Vec::new())
+            }
+        };
+        Self {
+            status,
+            gas_used,
+            output,
+            logs,
+            state: state_diff(&result.state),
+            trace: Vec::new(),
+        }
+    }
+}
+
+fn state_diff(state: &EvmState) -> BTreeMap<Address, AccountDiff> {
+    state
+        .iter()
+        .filter(|(_, account)| account.is_touched())
+        .map(|(address, account)| {
+            let diff = AccountDiff {
+                balance: account.info.balance,
+                nonce: account.info.nonce,
+                code_hash: account.info.code_hash,
+                storage: account
+                    .changed_storage_slots()
+                    .map(|(slot, value)| (*slot, value.present_value()))
+                    .collect(),
+                destroyed: account.is_selfdestructed(),
+            };
+            (*address, diff)
+        })
+        .collect()
+}
+
+/// EVM implementation revm is compared against by [`DifferentialHarness`], e.g. an FFI binding to
+/// evmone.
+pub trait ReferenceEvm<CTX> {
+    type Error;
+
+    /// Executes the transaction of `context` with its block and configuration, on the state of
+    /// its database, without committing it.
+    ///
+    /// The trace can be left empty if the implementation can't record it, the first divergent
+    /// step is then not reported.
+    fn execute(&mut self, context: &CTX) -> Result<Execution, Self::Error>;
+}
+
+/// First difference found between two [`Execution`]s, in the order the fields are compared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    Status,
+    GasUsed,
+    Output,
+    /// Number of emitted logs.
+    LogCount,
+    /// Log at the given index.
+    Log(usize),
+    /// Account touched by only one of the executions, or with a different balance, nonce, code
+    /// hash or destroyed flag.
+    Account(Address),
+    /// Storage slot of an account.
+    Storage {
+        address: Address,
+        slot: U256,
+    },
+    /// Only the traces differ, see [`Divergence::step`].
+    Trace,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status => f.write_str("status"),
+            Self::GasUsed => f.write_str("gas used"),
+            Self::Output => f.write_str("output"),
+            Self::LogCount => f.write_str("log count"),
+            Self::Log(index) => write!(f, "log {index}"),
+            Self::Account(address) => write!(f, "account {address}"),
+            Self::Storage { address, slot } => write!(f, "storage slot {slot} of {address}"),
+            Self::Trace => f.write_str("trace"),
+        }
+    }
+}
+
+impl Mismatch {
+    /// Compares two executions, ignoring their traces. Never returns [`Mismatch::Trace`].
+    pub fn find(revm: &Execution, reference: &Execution) -> Option<Self> {
+        if revm.status != reference.status {
+            return Some(Self::Status);
+        }
+        if revm.gas_used != reference.gas_used {
+            return Some(Self::GasUsed);
+        }
+        if revm.output != reference.output {
+            return Some(Self::Output);
+        }
+        if revm.logs.len() != reference.logs.len() {
+            return Some(Self::LogCount);
+        }
+        if let Some(index) = revm
+            .logs
+            .iter()
+            .zip(&reference.logs)
+            .position(|(a, b)| a != b)
+        {
+            return Some(Self::Log(index));
+        }
+        let addresses: BTreeSet<_> = revm.state.keys().chain(reference.state.keys()).collect();
+        addresses.into_iter().find_map(|address| {
+            Self::account(
+                *address,
+                revm.state.get(address),
+                reference.state.get(address),
+            )
+        })
+    }
+
+    fn account(
+        address: Address,
+        revm: Option<&AccountDiff>,
+        reference: Option<&AccountDiff>,
+    ) -> Option<Self> {
+        let (Some(revm), Some(reference)) = (revm, reference) else {
+            return Some(Self::Account(address));
+        };
+        if revm.balance != reference.balance
+            || revm.nonce != reference.nonce
+            || revm.code_hash != reference.code_hash
+            || revm.destroyed != reference.destroyed
+        {
+            return Some(Self::Account(address));
+        }
+        let slots: BTreeSet<_> = revm
+            .storage
+            .keys()
+            .chain(reference.storage.keys())
+            .collect();
+        slots
+            .into_iter()
+            .find(|slot| revm.storage.get(slot) != reference.storage.get(slot))
+            .map(|slot| Self::Storage {
+                address,
+                slot: *slot,
+            })
+    }
+
+    /// Address of the account the mismatch is in.
+    pub fn address(&self) -> Option<Address> {
+        match self {
+            Self::Account(address) | Self::Storage { address, .. } => Some(*address),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the index of the first step where two traces differ, [`None`] if they are equal or
+/// either of them is empty.
+///
+/// If one trace is a prefix of the other, the index is the length of the shorter one.
+pub fn first_divergent_step(revm: &[TraceStep], reference: &[TraceStep]) -> Option<usize> {
+    if revm.is_empty() || reference.is_empty() {
+        return None;
+    }
+    revm.iter()
+        .zip(reference)
+        .position(|(a, b)| a != b)
+        .or_else(|| (revm.len() != reference.len()).then(|| revm.len().min(reference.len())))
+}
+
+/// Divergence between revm and a [`ReferenceEvm`] found by [`DifferentialHarness`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub mismatch: Mismatch,
+    /// Index in the traces of the first step that differs, see [`first_divergent_step`].
+    pub step: Option<usize>,
+    pub revm: Execution,
+    pub reference: Execution,
+}
+
+impl Divergence {
+    /// Returns the steps of revm and of the reference at the first divergent step.
+    pub fn divergent_steps(&self) -> Option<(Option<&TraceStep>, Option<&TraceStep>)> {
+        self.step
+            .map(|step| (self.revm.trace.get(step), self.reference.trace.get(step)))
+    }
+}
+
+/// Error of a [`DifferentialHarness`] run.
+#[derive(Debug)]
+pub enum DifferentialError<DBError, RefError> {
+    /// revm returned an error, e.g. the transaction is invalid.
+    Revm(EVMError<DBError, InvalidTransaction>),
+    Reference(RefError),
+}
+
+impl<DBError: fmt::Display, RefError: fmt::Display> fmt::Display
+    for DifferentialError<DBError, RefError>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Revm(e) => write!(f, "revm: {e}"),
+            Self::Reference(e) => write!(f, "reference: {e}"),
+        }
+    }
+}
+
+impl<DBError, RefError> core::error::Error for DifferentialError<DBError, RefError>
+where
+    DBError: core::error::Error + 'static,
+    RefError: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Revm(e) => Some(e),
+            Self::Reference(e) => Some(e),
+        }
+    }
+}
+
+/// Executes transactions on revm and on a [`ReferenceEvm`] and reports the first divergence.
+///
+/// Gas used, status, output, logs and state of the touched accounts are compared. revm is traced
+/// while executing, so a divergence comes with the index of the first differing step when the
+/// reference records a trace too. Neither execution is committed.
+#[derive(Clone, Debug, Default)]
+pub struct DifferentialHarness<R> {
+    reference: R,
+    compare_traces: bool,
+}
+
+impl<R> DifferentialHarness<R> {
+    pub fn new(reference: R) -> Self {
+        Self {
+            reference,
+            compare_traces: false,
+        }
+    }
+
+    /// Also report a divergence when only the traces differ.
+    ///
+    /// Disabled by default, as implementations may differ in how they report gas and depth of
+    /// individual steps while agreeing on the outcome.
+    pub fn compare_traces(mut self) -> Self {
+        self.compare_traces = true;
+        self
+    }
+
+    pub fn reference(&mut self) -> &mut R {
+        &mut self.reference
+    }
+
+    pub fn into_reference(self) -> R {
+        self.reference
+    }
+
+    /// Executes the transaction of `context` on both EVMs, returning the divergence if any.
+    pub fn run<BLOCK, TX, CFG, DB, JOURNAL, CHAIN>(
+        &mut self,
+        context: &mut Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN>,
+    ) -> Result<Option<Divergence>, DifferentialError<DB::Error, R::Error>>
+    where
+        BLOCK: Block,
+        TX: Transaction,
+        CFG: Cfg,
+        DB: Database,
+        JOURNAL: Journal<Database = DB, FinalOutput = (EvmState, Vec<Log>)> + JournalExt,
+        R: ReferenceEvm<Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN>>,
+    {
+        let reference = self
+            .reference
+            .execute(context)
+            .map_err(DifferentialError::Reference)?;
+
+        let mut tracer = StepTracer::default();
+        let result = context
+            .inspect_previous(&mut tracer)
+            .map_err(DifferentialError::Revm)?;
+        let mut revm = Execution::from_result(&result);
+        revm.trace = tracer.steps;
+
+        let step = first_divergent_step(&revm.trace, &reference.trace);
+        let mismatch = match Mismatch::find(&revm, &reference) {
+            Some(mismatch) => mismatch,
+            None if self.compare_traces && step.is_some() => Mismatch::Trace,
+            None => return Ok(None),
+        };
+        Ok(Some(Divergence {
+            mismatch,
+            step,
+            revm,
+            reference,
+        }))
+    }
+}
+
+/// Records every executed instruction.
+#[derive(Default)]
+pub(crate) struct StepTracer {
+    pub(crate) steps: Vec<TraceStep>,
+}
+
+impl<CTX: JournalGetter> Inspector<CTX, EthInterpreter> for StepTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        self.steps.push(TraceStep {
+            depth: context.journal_ref().depth().saturating_sub(1),
+            pc: interp.bytecode.pc(),
+            opcode: interp.bytecode.opcode(),
+            gas_remaining: interp.control.gas().remaining(),
+            stack: interp.stack.data().clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, TxEnv},
+        primitives::TxKind,
+    };
+
+    type TestContext = Context<BlockEnv, TxEnv, CfgEnv, BenchmarkDB>;
+
+    /// Executes the transaction with revm and tampers with the execution.
+    struct TamperedRevm<F>(F);
+
+    impl<F: FnMut(&mut Execution)> ReferenceEvm<TestContext> for TamperedRevm<F> {
+        type Error = Infallible;
+
+        fn execute(&mut self, context: &TestContext) -> Result<Execution, Infallible> {
+            let mut context = context.clone();
+            let mut tracer = StepTracer::default();
+            let mut execution =
+                Execution::from_result(&context.inspect_previous(&mut tracer).unwrap());
+            execution.trace = tracer.steps;
+            (self.0)(&mut execution);
+            Ok(execution)
+        }
+    }
+
+    fn context() -> TestContext {
+        let code = [
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            })
+    }
+
+    #[test]
+    fn equal_executions() {
+        let mut harness =
+            DifferentialHarness::new(TamperedRevm(|_: &mut Execution| {})).compare_traces();
+        assert_eq!(harness.run(&mut context()).unwrap(), None);
+    }
+
+    #[test]
+    fn storage_divergence() {
+        let mut harness = DifferentialHarness::new(TamperedRevm(|execution: &mut Execution| {
+            let account = execution.state.get_mut(&BENCH_TARGET).unwrap();
+            account.storage.insert(U256::ZERO, U256::from(2));
+            execution.trace[2].stack[0] = U256::from(2);
+        }));
+        let divergence = harness.run(&mut context()).unwrap().unwrap();
+        assert_eq!(
+            divergence.mismatch,
+            Mismatch::Storage {
+                address: BENCH_TARGET,
+                slot: U256::ZERO
+            }
+        );
+        assert_eq!(divergence.step, Some(2));
+        let (revm, reference) = divergence.divergent_steps().unwrap();
+        assert_eq!(revm.unwrap().opcode, opcode::SSTORE);
+        assert_ne!(revm, reference);
+    }
+
+    #[test]
+    fn trace_divergence() {
+        let tamper = |execution: &mut Execution| {
+            execution.trace.pop();
+        };
+        let mut harness = DifferentialHarness::new(TamperedRevm(tamper));
+        assert_eq!(harness.run(&mut context()).unwrap(), None);
+
+        let mut harness = harness.compare_traces();
+        let divergence = harness.run(&mut context()).unwrap().unwrap();
+        assert_eq!(divergence.mismatch, Mismatch::Trace);
+        assert_eq!(divergence.step, Some(3));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{inspectors::GasInspector, Inspector};
+use crate::{
+    inspectors::GasInspector, Inspector, MemoryCapture, MemoryCaptureMode, TruncationConfig,
+};
 use revm::interpreter::interpreter_types::{RuntimeFlag, SubRoutineStack};
 use revm::{
     bytecode::opcode::OpCode,
@@ -6,17 +8,34 @@ use revm::{
     context_interface::{CfgGetter, Journal, JournalGetter, Transaction, TransactionGetter},
     interpreter::{
         interpreter_types::{Jumps, LoopControl, MemoryTrait, StackTrait},
-        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterResult,
-        InterpreterTypes, Stack,
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+        InterpreterResult, InterpreterTypes, Stack,
     },
     primitives::{hex, HashMap, B256, U256},
 };
 use serde::Serialize;
+#[cfg(feature = "std")]
 use std::io::Write;
+use std::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Destination of the trace lines.
+enum Sink {
+    #[cfg(feature = "std")]
+    Writer(Box<dyn Write>),
+    Callback(Box<dyn FnMut(&str)>),
+}
 
 /// [EIP-3155](https://eips.ethereum.org/EIPS/eip-3155) tracer [Inspector].
+///
+/// Writes one JSON object per line to an [`std::io::Write`], or without `std` passes each line
+/// to a callback, see [`TracerEip3155::with_callback`].
 pub struct TracerEip3155<CTX, INTR> {
-    output: Box<dyn Write>,
+    output: Sink,
     gas_inspector: GasInspector,
     /// Print summary of the execution.
     print_summary: bool,
@@ -29,9 +48,11 @@ pub struct TracerEip3155<CTX, INTR> {
     refunded: i64,
     mem_size: usize,
     skip: bool,
-    include_memory: bool,
+    memory_capture: Option<MemoryCapture>,
     memory: Option<String>,
-    _phantom: std::marker::PhantomData<(CTX, INTR)>,
+    memory_offset: Option<usize>,
+    truncation: TruncationConfig,
+    _phantom: core::marker::PhantomData<(CTX, INTR)>,
 }
 
 // # Output
@@ -75,6 +96,9 @@ struct Output {
     /// Array of all allocated values
     #[serde(default, skip_serializing_if = "Option::is_none")]
     memory: Option<String>,
+    /// Offset of `memory` if only a part of the memory was captured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memory_offset: Option<usize>,
     /// Array of all stored values
     #[serde(default, skip_serializing_if = "Option::is_none")]
     storage: Option<HashMap<String, String>>,
@@ -92,6 +116,9 @@ struct Summary {
     state_root: String,
     /// Return values of the function
     output: String,
+    /// Length of the original output, present if `output` was truncated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output_len: Option<usize>,
     /// All gas used by the transaction
     gas_used: String,
     /// Bool whether transaction was executed successfully
@@ -112,8 +139,14 @@ where
     INTR:,
 {
     /// Sets the writer to use for the output.
+    #[cfg(feature = "std")]
     pub fn set_writer(&mut self, writer: Box<dyn Write>) {
-        self.output = writer;
+        self.output = Sink::Writer(writer);
+    }
+
+    /// Sets the callback receiving each trace line, without the trailing newline.
+    pub fn set_callback(&mut self, callback: impl FnMut(&str) + 'static) {
+        self.output = Sink::Callback(Box::new(callback));
     }
 
     /// Resets the Tracer to its initial state of [Self::new].
@@ -128,8 +161,12 @@ where
             refunded,
             mem_size,
             skip,
+            memory_capture,
             ..
         } = self;
+        if let Some(memory_capture) = memory_capture {
+            memory_capture.reset();
+        }
         *gas_inspector = GasInspector::new();
         stack.clear();
         *pc = 0;
@@ -140,14 +177,27 @@ where
         *skip = false;
     }
 
+    /// Creates a tracer writing to `output`.
+    #[cfg(feature = "std")]
     pub fn new(output: Box<dyn Write>) -> Self {
+        Self::with_sink(Sink::Writer(output))
+    }
+
+    /// Creates a tracer passing each trace line, without the trailing newline, to `callback`.
+    pub fn with_callback(callback: impl FnMut(&str) + 'static) -> Self {
+        Self::with_sink(Sink::Callback(Box::new(callback)))
+    }
+
+    fn with_sink(output: Sink) -> Self {
         Self {
             output,
             gas_inspector: GasInspector::new(),
             print_summary: true,
-            include_memory: false,
+            memory_capture: None,
             stack: Default::default(),
             memory: Default::default(),
+            memory_offset: None,
+            truncation: TruncationConfig::unlimited(),
             pc: 0,
             section: None,
             function_depth: None,
@@ -167,30 +217,61 @@ where
     }
 
     /// Include a memory field for each step. This significantly increases processing time and output size.
-    pub fn with_memory(mut self) -> Self {
-        self.include_memory = true;
+    pub fn with_memory(self) -> Self {
+        self.with_memory_capture(MemoryCapture::full())
+    }
+
+    /// Include a memory field for each step, captured with the given options.
+    ///
+    /// Windowed captures add a `memoryOffset` field with the offset of the captured memory.
+    /// Limits of the capture are reset after every transaction.
+    pub fn with_memory_capture(mut self, memory_capture: MemoryCapture) -> Self {
+        self.memory_capture = Some(memory_capture);
         self
     }
 
-    fn write_value(&mut self, value: &impl serde::Serialize) -> std::io::Result<()> {
-        serde_json::to_writer(&mut *self.output, value)?;
-        self.output.write_all(b"\n")?;
-        self.output.flush()
+    /// Limits the output of the summary with [`TruncationConfig::max_output`].
+    pub fn with_truncation(mut self, truncation: TruncationConfig) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    fn write_value(&mut self, value: &impl serde::Serialize) {
+        match &mut self.output {
+            #[cfg(feature = "std")]
+            Sink::Writer(writer) => {
+                let _ = write_line(writer, value);
+            }
+            Sink::Callback(callback) => {
+                if let Ok(line) = serde_json::to_string(value) {
+                    callback(&line);
+                }
+            }
+        }
+    }
+
+    /// Drops the memory shared with the previous step, which belongs to another frame.
+    fn frame_changed(&mut self) {
+        if let Some(memory_capture) = &mut self.memory_capture {
+            memory_capture.frame_changed();
+        }
     }
 
     fn print_summary(&mut self, result: &InterpreterResult, context: &mut CTX) {
         if self.print_summary {
             let spec = context.cfg().spec().into();
             let gas_limit = context.tx().gas_limit();
+            let (output, output_len) = self.truncation.output(&result.output);
             let value = Summary {
                 state_root: B256::ZERO.to_string(),
-                output: result.output.to_string(),
+                output: output.to_string(),
+                output_len,
                 gas_used: hex_number(gas_limit - self.gas_inspector.gas_remaining()),
                 pass: result.is_ok(),
                 time: None,
                 fork: Some(spec.to_string()),
             };
-            let _ = self.write_value(&value);
+            self.write_value(&value);
         }
     }
 }
@@ -217,12 +298,20 @@ where
     fn step(&mut self, interp: &mut Interpreter<INTR>, _: &mut CTX) {
         self.gas_inspector.step(interp.control.gas());
         self.stack = interp.stack.clone_from();
-        self.memory = if self.include_memory {
-            Some(hex::encode_prefixed(
-                interp.memory.slice(0..usize::MAX).as_ref(),
-            ))
-        } else {
-            None
+        self.opcode = interp.bytecode.opcode();
+        (self.memory, self.memory_offset) = match &mut self.memory_capture {
+            Some(capture) => {
+                let memory = interp.memory.slice(0..interp.memory.size());
+                match capture.capture(self.opcode, &self.stack, memory.as_ref()) {
+                    Some(captured) => (
+                        Some(hex::encode_prefixed(captured.data)),
+                        (capture.mode() == MemoryCaptureMode::Referenced)
+                            .then_some(captured.offset),
+                    ),
+                    None => (None, None),
+                }
+            }
+            None => (None, None),
         };
         self.pc = interp.bytecode.pc() as u64;
         self.section = if interp.runtime_flag.is_eof() {
@@ -235,7 +324,6 @@ where
         } else {
             None
         };
-        self.opcode = interp.bytecode.opcode();
         self.mem_size = interp.memory.size();
         self.gas = interp.control.gas().remaining();
         self.refunded = interp.control.gas().refunded();
@@ -268,14 +356,21 @@ where
                 None
             },
             memory: self.memory.take(),
+            memory_offset: self.memory_offset.take(),
             storage: None,
             return_stack: None,
         };
-        let _ = self.write_value(&value);
+        self.write_value(&value);
+    }
+
+    fn call(&mut self, _: &mut CTX, _: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_changed();
+        None
     }
 
     fn call_end(&mut self, context: &mut CTX, _: &CallInputs, outcome: &mut CallOutcome) {
         self.gas_inspector.call_end(outcome);
+        self.frame_changed();
 
         if context.journal().depth() == 0 {
             self.print_summary(&outcome.result, context);
@@ -284,8 +379,14 @@ where
         }
     }
 
+    fn create(&mut self, _: &mut CTX, _: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_changed();
+        None
+    }
+
     fn create_end(&mut self, context: &mut CTX, _: &CreateInputs, outcome: &mut CreateOutcome) {
         self.gas_inspector.create_end(outcome);
+        self.frame_changed();
 
         if context.journal().depth() == 0 {
             self.print_summary(&outcome.result, context);
@@ -294,6 +395,22 @@ where
             self.clear();
         }
     }
+
+    fn eofcreate(&mut self, _: &mut CTX, _: &mut EOFCreateInputs) -> Option<CreateOutcome> {
+        self.frame_changed();
+        None
+    }
+
+    fn eofcreate_end(&mut self, _: &mut CTX, _: &EOFCreateInputs, _: &mut CreateOutcome) {
+        self.frame_changed();
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_line(writer: &mut dyn Write, value: &impl serde::Serialize) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
 }
 
 fn hex_number(uint: u64) -> String {
@@ -309,3 +426,101 @@ fn hex_number_u256(b: &U256) -> String {
         format!("0x{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use core::cell::RefCell;
+    use database::{BenchmarkDB, CacheDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        database_interface::EmptyDB,
+        primitives::{address, TxKind},
+        state::AccountInfo,
+        Context,
+    };
+    use std::rc::Rc;
+
+    #[test]
+    fn callback_receives_lines() {
+        let code = [opcode::PUSH1, 0x01, opcode::STOP];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = lines.clone();
+        ctx.inspect_previous(TracerEip3155::with_callback(move |line| {
+            sink.borrow_mut().push(line.to_string())
+        }))
+        .unwrap();
+
+        let lines = lines.borrow();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with(r#"{"pc":0,"op":96,"gas":"#));
+        assert!(lines[1].contains(r#""stack":["0x1"]"#));
+        assert!(lines[2].starts_with(r#"{"stateRoot":"#));
+        assert!(lines.iter().all(|line| !line.ends_with('\n')));
+    }
+
+    #[test]
+    fn memory_not_shared_across_frames() {
+        let child = address!("00000000000000000000000000000000000000bb");
+        let mut db = CacheDB::new(EmptyDB::default());
+        // Stores 0xbb at 0 and stops, the memory has the size of the memory of the caller.
+        db.insert_account_info(
+            child,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(
+                [
+                    opcode::PUSH1,
+                    0xbb,
+                    opcode::PUSH0,
+                    opcode::MSTORE,
+                    opcode::STOP,
+                ]
+                .into(),
+            )),
+        );
+        // Stores 0xaa at 0, calls the child without return data and pops the result.
+        let mut code = vec![opcode::PUSH1, 0xaa, opcode::PUSH0, opcode::MSTORE];
+        code.extend([opcode::PUSH0; 5]);
+        code.extend([opcode::PUSH1, 0xbb, opcode::GAS, opcode::CALL, opcode::POP]);
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+
+        let mut ctx = Context::default().with_db(db).modify_tx_chained(|tx| {
+            tx.caller = BENCH_CALLER;
+            tx.kind = TxKind::Call(BENCH_TARGET);
+            tx.gas_limit = 100_000;
+        });
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = lines.clone();
+        ctx.inspect_previous(
+            TracerEip3155::with_callback(move |line| sink.borrow_mut().push(line.to_string()))
+                .with_memory_capture(MemoryCapture::full()),
+        )
+        .unwrap();
+
+        let lines = lines.borrow();
+        let memory = |op: u8| {
+            let line = lines
+                .iter()
+                .find(|line| {
+                    line.starts_with(r#"{"pc":"#) && line.contains(&format!(r#""op":{op},"#))
+                })
+                .unwrap();
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["memory"].as_str().unwrap().to_string()
+        };
+        assert!(memory(opcode::STOP).ends_with("bb"));
+        assert!(memory(opcode::POP).ends_with("aa"));
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     inspector_context::InspectorContext,
     inspector_instruction::InspectorInstructionExecutor,
     journal::{JournalExt, JournalExtGetter},
-    Inspector, InspectorCtx, InspectorHandlerImpl,
+    Inspector, InspectorCtx, InspectorHandlerImpl, TxOutcome,
 };
 use revm::{
     context::Cfg,
@@ -74,6 +74,28 @@ pub trait InspectCommitEvm<CTX, INTR: InterpreterTypes>:
     fn inspect_commit_previous<'a, 'b, INSP>(&'a mut self, inspector: INSP) -> Self::CommitOutput
     where
         INSP: Inspector<&'a mut Self, INTR> + 'b;
+
+    /// Inspects and commits `txs` in order as a single block.
+    ///
+    /// The same inspector is used for all transactions, with [`Inspector::block_start`] and
+    /// [`Inspector::block_end`] called around them. Failed transactions are not committed and
+    /// don't stop the execution of the following ones.
+    fn inspect_commit_block<INSP>(
+        &mut self,
+        txs: impl IntoIterator<Item = Self::Transaction>,
+        mut inspector: INSP,
+    ) -> Vec<Self::CommitOutput>
+    where
+        INSP: for<'a> Inspector<&'a mut Self, INTR>,
+    {
+        inspector.block_start(&mut &mut *self);
+        let outputs = txs
+            .into_iter()
+            .map(|tx| self.inspect_commit(tx, &mut inspector))
+            .collect();
+        inspector.block_end(&mut &mut *self);
+        outputs
+    }
 }
 
 impl<
@@ -103,11 +125,14 @@ pub fn inspect_main<
 >(
     ctx: &mut CTX,
 ) -> Result<ResultAndState<HaltReason>, EVMError<<DB as Database>::Error, InvalidTransaction>> {
-    InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
+    ctx.tx_start();
+    let result = InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
         MainnetHandler::<_, _, _, _, InspectorInstructionExecutor<EthInterpreter, CTX>>::default(),
         make_instruction_table(),
     )
-    .run(ctx)
+    .run(ctx);
+    ctx.tx_end(result.as_ref().ok().map(TxOutcome::new).as_ref());
+    result
 }
 
 pub fn inspect_main_commit<
@@ -124,3 +149,139 @@ pub fn inspect_main_commit<
         res.result
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::{CacheDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::TxEnv,
+        database_interface::EmptyDB,
+        interpreter::{
+            interpreter_types::InputsTrait, CallInputs, CallOutcome, Interpreter, InterpreterTypes,
+        },
+        primitives::{address, Bytes, TxKind, U256},
+        state::AccountInfo,
+    };
+
+    #[derive(Default)]
+    struct BlockGas {
+        blocks: usize,
+        txs: usize,
+        failed: usize,
+        cumulative_gas: u64,
+        open: bool,
+    }
+
+    impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for BlockGas {
+        fn block_start(&mut self, _context: &mut CTX) {
+            self.open = true;
+        }
+
+        fn block_end(&mut self, _context: &mut CTX) {
+            self.open = false;
+            self.blocks += 1;
+        }
+
+        fn tx_start(&mut self, _context: &mut CTX) {
+            assert!(self.open);
+            self.txs += 1;
+        }
+
+        fn tx_end(&mut self, _context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+            match outcome {
+                Some(outcome) => self.cumulative_gas += outcome.gas_used,
+                None => self.failed += 1,
+            }
+        }
+    }
+
+    #[test]
+    fn block_hooks() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::from_bytecode(Bytecode::new_legacy([opcode::STOP].into())),
+        );
+        db.insert_account_info(
+            BENCH_CALLER,
+            AccountInfo::from_balance(U256::from(10_000_000)),
+        );
+        let mut ctx = Context::default().with_db(db);
+        let tx = |nonce| TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(BENCH_TARGET),
+            gas_limit: 100_000,
+            nonce,
+            ..Default::default()
+        };
+
+        let mut inspector = BlockGas::default();
+        // Second transaction reuses the nonce of the first one and is invalid.
+        let outputs = ctx.inspect_commit_block([tx(0), tx(0), tx(1)], &mut inspector);
+
+        assert!(outputs[0].is_ok() && outputs[1].is_err() && outputs[2].is_ok());
+        assert_eq!(inspector.blocks, 1);
+        assert_eq!(inspector.txs, 3);
+        assert_eq!(inspector.failed, 1);
+        assert_eq!(inspector.cumulative_gas, 2 * 21_000);
+    }
+
+    /// Addresses of the calldata of the calls and of the interpreters running them.
+    #[derive(Default)]
+    struct CalldataPointers {
+        calls: Vec<*const u8>,
+        interpreters: Vec<*const u8>,
+    }
+
+    impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for CalldataPointers {
+        fn initialize_interp(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+            self.interpreters.push(interp.input.input().as_ptr());
+        }
+
+        fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+            self.calls.push(inputs.input.as_ptr());
+            None
+        }
+    }
+
+    #[test]
+    fn calldata_is_shared() {
+        let child = address!("00000000000000000000000000000000000000bb");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            child,
+            AccountInfo::from_bytecode(Bytecode::new_legacy([opcode::STOP].into())),
+        );
+        // Copies the calldata to the memory and forwards it to the child.
+        let mut code = vec![opcode::CALLDATASIZE, opcode::PUSH0, opcode::PUSH0];
+        code.extend([opcode::CALLDATACOPY, opcode::PUSH0, opcode::PUSH0]);
+        code.extend([opcode::CALLDATASIZE, opcode::PUSH0, opcode::PUSH0]);
+        code.extend([opcode::PUSH1, 0xbb, opcode::GAS, opcode::CALL, opcode::STOP]);
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+
+        let data = Bytes::from(vec![0xff; 100_000]);
+        let mut ctx = Context::default().with_db(db).with_tx(TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(BENCH_TARGET),
+            gas_limit: 30_000_000,
+            data: data.clone(),
+            ..Default::default()
+        });
+        let mut inspector = CalldataPointers::default();
+        assert!(ctx
+            .inspect_previous(&mut inspector)
+            .unwrap()
+            .result
+            .is_success());
+
+        // The calldata of the transaction and of each call is not copied by the frames.
+        assert_eq!(inspector.calls.len(), 2);
+        assert_eq!(inspector.calls[0], data.as_ptr());
+        assert_eq!(inspector.interpreters, inspector.calls);
+    }
+}
@@ -0,0 +1,238 @@
+//! GasAttribution. Reports gas spent by each contract, split into self and descendant gas.
+use crate::Inspector;
+use revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InterpreterTypes,
+    },
+    primitives::{Address, HashMap},
+};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Gas spent by a single contract, see [`GasAttribution`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddressGas {
+    /// Number of frames that executed the code of the contract.
+    pub calls: u64,
+    /// Gas spent executing the code of the contract, excluding the frames it started.
+    pub self_gas: u64,
+    /// Gas spent by the frames of the contract including the frames they started.
+    ///
+    /// Frames re-entering the contract are not counted twice.
+    pub total_gas: u64,
+}
+
+impl AddressGas {
+    /// Gas spent by the frames started by the contract, directly or indirectly.
+    pub fn descendant_gas(&self) -> u64 {
+        self.total_gas.saturating_sub(self.self_gas)
+    }
+}
+
+/// Frame that has not ended yet.
+#[derive(Clone, Debug)]
+struct Frame {
+    /// Code address of a call, [`None`] for creations until they end.
+    address: Option<Address>,
+    children_gas: u64,
+}
+
+/// [`Inspector`] that attributes the gas of every frame to the contract whose code ran in it.
+///
+/// Calls are attributed to the address of the executed code, so `DELEGATECALL`s are reported
+/// under the implementation rather than the proxy, and creations to the created address.
+/// The report accumulates over all inspected transactions, e.g. a bundle, until
+/// [`GasAttribution::clear`] is called. Intrinsic gas of transactions is not attributed.
+#[derive(Clone, Debug, Default)]
+pub struct GasAttribution {
+    report: BTreeMap<Address, AddressGas>,
+    stack: Vec<Frame>,
+    /// Number of open frames of each address, used to skip re-entrant frames in `total_gas`.
+    active: HashMap<Address, usize>,
+}
+
+impl GasAttribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the gas spent by each contract.
+    pub fn report(&self) -> &BTreeMap<Address, AddressGas> {
+        &self.report
+    }
+
+    /// Returns the gas spent by the given contract.
+    pub fn get(&self, address: &Address) -> Option<&AddressGas> {
+        self.report.get(address)
+    }
+
+    /// Returns the contracts sorted by descending self gas.
+    pub fn by_self_gas(&self) -> Vec<(Address, AddressGas)> {
+        let mut sorted: Vec<_> = self.report.iter().map(|(a, gas)| (*a, *gas)).collect();
+        sorted.sort_by(|a, b| b.1.self_gas.cmp(&a.1.self_gas).then(a.0.cmp(&b.0)));
+        sorted
+    }
+
+    /// Clears the report, making the inspector ready to be used again.
+    pub fn clear(&mut self) {
+        self.report.clear();
+        self.stack.clear();
+        self.active.clear();
+    }
+
+    fn frame_start(&mut self, address: Option<Address>) {
+        if let Some(address) = address {
+            *self.active.entry(address).or_default() += 1;
+        }
+        self.stack.push(Frame {
+            address,
+            children_gas: 0,
+        });
+    }
+
+    fn frame_end(&mut self, address: Option<Address>, gas: u64) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children_gas += gas;
+        }
+        let Some(address) = frame.address.or(address) else {
+            return;
+        };
+
+        let reentrant = match self.active.get_mut(&address) {
+            Some(active) if frame.address.is_some() => {
+                *active -= 1;
+                *active > 0
+            }
+            Some(active) => *active > 0,
+            None => false,
+        };
+        let entry = self.report.entry(address).or_default();
+        entry.calls += 1;
+        entry.self_gas += gas.saturating_sub(frame.children_gas);
+        if !reentrant {
+            entry.total_gas += gas;
+        }
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for GasAttribution {
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start(Some(inputs.bytecode_address));
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(None, outcome.result.gas.spent());
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start(None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(outcome.address, outcome.result.gas.spent());
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frame_start(None);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(outcome.address, outcome.result.gas.spent());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{address, TxKind},
+        Context,
+    };
+
+    #[test]
+    fn self_and_descendant_gas() {
+        // If called by the caller of the transaction, calls itself once, then calls the
+        // identity precompile.
+        let code = [
+            opcode::CALLER,
+            opcode::ORIGIN,
+            opcode::EQ,
+            opcode::ISZERO,
+            opcode::PUSH1,
+            0x13,
+            opcode::JUMPI,
+            // call(gas, address, 0, 0, 0, 0, 0)
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::ADDRESS,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::POP,
+            opcode::STOP,
+            opcode::INVALID,
+            // 0x13: call(gas, 0x04, 0, 0, 0, 0, 0)
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::PUSH1,
+            0x04,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::STOP,
+        ];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut attribution = GasAttribution::new();
+        let result = ctx.inspect_previous(&mut attribution).unwrap();
+
+        let identity = attribution
+            .get(&address!("0000000000000000000000000000000000000004"))
+            .unwrap();
+        assert_eq!(identity.calls, 1);
+        assert_eq!(identity.descendant_gas(), 0);
+
+        let target = attribution.get(&BENCH_TARGET).unwrap();
+        assert_eq!(target.calls, 2);
+        assert_eq!(target.total_gas, result.result.gas_used() - 21_000);
+        assert_eq!(target.descendant_gas(), identity.self_gas);
+        assert_eq!(attribution.by_self_gas()[0].0, BENCH_TARGET);
+    }
+}
@@ -0,0 +1,279 @@
+//! GasProfiler. Aggregates call tree gas and exports it as flamegraph or callgrind profile.
+use crate::Inspector;
+use core::fmt::Write;
+use revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InterpreterTypes,
+    },
+    primitives::{hex, Address, Bytes},
+};
+use std::{collections::BTreeMap, format, string::String, vec::Vec};
+
+/// Frame of the call tree recorded by [`GasProfiler`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasProfileNode {
+    /// Name of the frame, `<address>` or `<address>:<selector>` for calls and
+    /// `create:<address>` for creations.
+    pub label: String,
+    /// Index of the parent frame in [`GasProfiler::nodes`].
+    pub parent: Option<usize>,
+    /// Gas spent by the frame, including its children.
+    pub gas: u64,
+    /// Gas spent by the children of the frame.
+    pub children_gas: u64,
+}
+
+impl GasProfileNode {
+    /// Gas spent by the frame itself.
+    pub fn self_gas(&self) -> u64 {
+        self.gas.saturating_sub(self.children_gas)
+    }
+}
+
+/// [`Inspector`] that records gas spent by every frame of the call tree.
+///
+/// The profile can be exported in the folded stack format consumed by flamegraph tools
+/// with [`GasProfiler::folded_stacks`], or as a callgrind profile viewable in
+/// KCachegrind/QCachegrind with [`GasProfiler::callgrind`].
+///
+/// Gas of the first frame does not include the intrinsic gas of the transaction.
+#[derive(Clone, Debug, Default)]
+pub struct GasProfiler {
+    nodes: Vec<GasProfileNode>,
+    /// Indices of the frames that have not ended yet.
+    stack: Vec<usize>,
+}
+
+impl GasProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded frames in the order they were started.
+    pub fn nodes(&self) -> &[GasProfileNode] {
+        &self.nodes
+    }
+
+    /// Clears the recorded profile, making the profiler ready to be used again.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.stack.clear();
+    }
+
+    /// Returns the stack of labels from the root frame to the frame at `index`.
+    fn path(&self, mut index: usize) -> Vec<&str> {
+        let mut path = Vec::new();
+        loop {
+            let node = &self.nodes[index];
+            path.push(node.label.as_str());
+            match node.parent {
+                Some(parent) => index = parent,
+                None => break,
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// Exports the profile in the folded stack format, one `a;b;c <self gas>` line per unique stack.
+    ///
+    /// The output can be passed to `inferno-flamegraph` or `flamegraph.pl`.
+    pub fn folded_stacks(&self) -> String {
+        let mut stacks = BTreeMap::<String, u64>::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            *stacks.entry(self.path(index).join(";")).or_default() += node.self_gas();
+        }
+
+        let mut out = String::new();
+        for (stack, gas) in stacks {
+            let _ = writeln!(out, "{stack} {gas}");
+        }
+        out
+    }
+
+    /// Exports the profile in the callgrind format with a single `Gas` event.
+    ///
+    /// Every label becomes a function, self gas is attributed to it and gas of its
+    /// children is reported as inclusive cost of the calls.
+    pub fn callgrind(&self) -> String {
+        #[derive(Default)]
+        struct Function<'a> {
+            self_gas: u64,
+            /// Callee label to number of calls and inclusive gas.
+            callees: BTreeMap<&'a str, (u64, u64)>,
+        }
+
+        let mut functions = BTreeMap::<&str, Function<'_>>::new();
+        for node in &self.nodes {
+            functions.entry(&node.label).or_default().self_gas += node.self_gas();
+            if let Some(parent) = node.parent {
+                let callee = functions
+                    .entry(&self.nodes[parent].label)
+                    .or_default()
+                    .callees
+                    .entry(&node.label)
+                    .or_default();
+                callee.0 += 1;
+                callee.1 += node.gas;
+            }
+        }
+
+        let mut out = String::from(
+            "# callgrind format\nversion: 1\ncreator: revm\npositions: line\nevents: Gas\n",
+        );
+        for (name, function) in functions {
+            let _ = write!(out, "\nfn={name}\n0 {}\n", function.self_gas);
+            for (callee, (calls, gas)) in function.callees {
+                let _ = write!(out, "cfn={callee}\ncalls={calls} 0\n0 {gas}\n");
+            }
+        }
+        out
+    }
+
+    fn frame_start(&mut self) {
+        self.stack.push(self.nodes.len());
+        self.nodes.push(GasProfileNode {
+            label: String::new(),
+            parent: self.stack.iter().rev().nth(1).copied(),
+            gas: 0,
+            children_gas: 0,
+        });
+    }
+
+    fn frame_end(&mut self, label: String, gas: u64) {
+        let Some(index) = self.stack.pop() else {
+            return;
+        };
+        let node = &mut self.nodes[index];
+        node.label = label;
+        node.gas = gas;
+        if let Some(parent) = node.parent {
+            self.nodes[parent].children_gas += gas;
+        }
+    }
+}
+
+fn call_label(address: Address, input: &Bytes) -> String {
+    match input.get(..4) {
+        Some(selector) => format!("{address}:{}", hex::encode_prefixed(selector)),
+        None => format!("{address}"),
+    }
+}
+
+fn create_label(address: Option<Address>) -> String {
+    match address {
+        Some(address) => format!("create:{address}"),
+        None => String::from("create"),
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for GasProfiler {
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(
+            call_label(inputs.bytecode_address, &inputs.input),
+            outcome.result.gas.spent(),
+        );
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(create_label(outcome.address), outcome.result.gas.spent());
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(create_label(outcome.address), outcome.result.gas.spent());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{address, TxKind},
+        Context,
+    };
+
+    #[test]
+    fn folded_and_callgrind() {
+        // CALL(0xffff, 0x04, 0, 0, 0, 0, 0) twice, then STOP.
+        let call = [
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0x04,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            opcode::POP,
+        ];
+        let code = [&call[..], &call[..], &[opcode::STOP]].concat();
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut profiler = GasProfiler::new();
+        ctx.inspect_previous(&mut profiler).unwrap();
+
+        let identity = address!("0000000000000000000000000000000000000004");
+        let nodes = profiler.nodes();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[1].parent, Some(0));
+        // Identity precompile with empty input costs its base gas.
+        assert_eq!(nodes[1].gas, 15);
+        assert_eq!(nodes[0].children_gas, 30);
+
+        let root = format!("{BENCH_TARGET}");
+        assert_eq!(
+            profiler.folded_stacks(),
+            format!("{root} {}\n{root};{identity} 30\n", nodes[0].self_gas())
+        );
+
+        let callgrind = profiler.callgrind();
+        assert!(callgrind.contains(&format!("fn={root}\n0 {}\n", nodes[0].self_gas())));
+        assert!(callgrind.contains(&format!("cfn={identity}\ncalls=2 0\n0 30\n")));
+        assert!(callgrind.contains(&format!("fn={identity}\n0 30\n")));
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{
     inspector_instruction::InspectorInstructionExecutor,
     journal::{JournalExt, JournalExtGetter},
+    OpcodeFilter,
 };
 use auto_impl::auto_impl;
+use core::cell::RefCell;
 use revm::{
-    context_interface::Journal,
+    context_interface::{
+        result::{HaltReasonTrait, ResultAndState},
+        Journal,
+    },
     database_interface::Database,
     handler::{
         handler::{EthContext, EthError, EthHandler},
@@ -13,15 +18,48 @@ use revm::{
     handler_interface::{Frame, ItemOrResult, PrecompileProvider},
     interpreter::{
         table::InstructionTable, CallInputs, CallOutcome, CreateInputs, CreateOutcome,
-        EOFCreateInputs, FrameInput, Interpreter, InterpreterTypes,
+        EOFCreateInputs, FrameInput, InitialAndFloorGas, Interpreter, InterpreterResult,
+        InterpreterTypes, SharedMemory,
     },
-    primitives::{Address, Log, U256},
+    primitives::{Address, Bytes, Log, U256},
+    state::EvmState,
     Context,
 };
+use std::rc::Rc;
 
 /// EVM [Interpreter] callbacks.
 #[auto_impl(&mut, Box)]
 pub trait Inspector<CTX, INTR: InterpreterTypes> {
+    /// Called before the first transaction of a block executed with
+    /// [`InspectCommitEvm::inspect_commit_block`](crate::exec::InspectCommitEvm::inspect_commit_block).
+    ///
+    /// Together with [`Inspector::block_end`] this lets a single inspector aggregate a whole block.
+    #[inline]
+    fn block_start(&mut self, context: &mut CTX) {
+        let _ = context;
+    }
+
+    /// Called after the last transaction of a block has been executed and committed.
+    #[inline]
+    fn block_end(&mut self, context: &mut CTX) {
+        let _ = context;
+    }
+
+    /// Called before the transaction set in the context is validated and executed.
+    #[inline]
+    fn tx_start(&mut self, context: &mut CTX) {
+        let _ = context;
+    }
+
+    /// Called after the transaction has been executed, before its state is committed.
+    ///
+    /// `outcome` is [`None`] if the transaction was not executed, e.g. because it is invalid.
+    #[inline]
+    fn tx_end(&mut self, context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+        let _ = context;
+        let _ = outcome;
+    }
+
     /// Called before the interpreter is initialized.
     ///
     /// If `interp.instruction_result` is set to anything other than [revm::interpreter::InstructionResult::Continue] then the execution of the interpreter
@@ -32,14 +70,28 @@ pub trait Inspector<CTX, INTR: InterpreterTypes> {
         let _ = context;
     }
 
+    /// Returns the set of opcodes for which [`Inspector::step`] and [`Inspector::step_end`] are called.
+    ///
+    /// Defaults to every opcode. Selective tracers (e.g. only `CALL`/`SSTORE`) can narrow
+    /// this down so the interpreter skips the step hooks for all other instructions.
+    ///
+    /// The filter is read once every time the interpreter loop of a frame is (re)entered.
+    #[inline]
+    fn opcode_filter(&self) -> OpcodeFilter {
+        OpcodeFilter::all()
+    }
+
     /// Called on each step of the interpreter.
     ///
     /// Information about the current execution, including the memory, stack and more is available
     /// on `interp` (see [Interpreter]).
     ///
+    /// State of the transaction, e.g. balances, warm accounts and the call depth, can be read
+    /// through [`JournalExtGetter`] if `CTX` implements it, as [`Context`] does.
+    ///
     /// # Example
     ///
-    /// To get the current opcode, use `interp.current_opcode()`.
+    /// To get the current opcode, use `interp.bytecode.opcode()`.
     #[inline]
     fn step(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX) {
         let _ = interp;
@@ -66,7 +118,8 @@ pub trait Inspector<CTX, INTR: InterpreterTypes> {
 
     /// Called whenever a call to a contract is about to start.
     ///
-    /// InstructionResulting anything other than [revm::interpreter::InstructionResult::Continue] overrides the result of the call.
+    /// If this returns `Some` then the call is not executed and the [CallOutcome] is used as
+    /// its result, see [`CallOutcome::from_inputs`] to mock a call.
     #[inline]
     fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
         let _ = context;
@@ -78,7 +131,10 @@ pub trait Inspector<CTX, INTR: InterpreterTypes> {
     ///
     /// The returned [CallOutcome] is used as the result of the call.
     ///
-    /// This allows the inspector to modify the given `result` before returning it.
+    /// This allows the inspector to modify the given `result` before returning it, e.g. to
+    /// replace the return data or to force the call to revert with [`CallOutcome::revert_with`].
+    /// State changes of a successful call are reverted if the outcome is changed to a failure,
+    /// changes of a failed call are not restored if it is changed to a success.
     #[inline]
     fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
         let _ = context;
@@ -102,6 +158,8 @@ pub trait Inspector<CTX, INTR: InterpreterTypes> {
     ///
     /// InstructionResulting anything other than the values passed to this function (`(ret, remaining_gas,
     /// address, out)`) will alter the result of the create.
+    ///
+    /// As with [`Inspector::call_end`], changing a successful outcome to a failure reverts the creation.
     #[inline]
     fn create_end(
         &mut self,
@@ -139,13 +197,166 @@ pub trait Inspector<CTX, INTR: InterpreterTypes> {
         let _ = outcome;
     }
 
+    /// Called after a `TLOAD` instruction loaded `value` from the transient storage of `address`.
+    #[inline]
+    fn tload(
+        &mut self,
+        interp: &mut Interpreter<INTR>,
+        context: &mut CTX,
+        address: Address,
+        key: U256,
+        value: U256,
+    ) {
+        let _ = interp;
+        let _ = context;
+        let _ = address;
+        let _ = key;
+        let _ = value;
+    }
+
+    /// Called after a `TSTORE` instruction changed the transient storage of `address`
+    /// from `old_value` to `new_value`.
+    #[inline]
+    fn tstore(
+        &mut self,
+        interp: &mut Interpreter<INTR>,
+        context: &mut CTX,
+        address: Address,
+        key: U256,
+        old_value: U256,
+        new_value: U256,
+    ) {
+        let _ = interp;
+        let _ = context;
+        let _ = address;
+        let _ = key;
+        let _ = old_value;
+        let _ = new_value;
+    }
+
+    /// Called when a transient storage write is discarded because the frame that made it reverted.
+    ///
+    /// Writes are reported in reverse order, `reverted_value` is the value being discarded
+    /// and `restored_value` the value the slot holds again afterwards.
+    #[inline]
+    fn tstore_revert(
+        &mut self,
+        context: &mut CTX,
+        address: Address,
+        key: U256,
+        reverted_value: U256,
+        restored_value: U256,
+    ) {
+        let _ = context;
+        let _ = address;
+        let _ = key;
+        let _ = reverted_value;
+        let _ = restored_value;
+    }
+
+    /// Called when a precompile has been executed, before the `call_end` of its call.
+    ///
+    /// `result` contains the output and gas of the precompile, or
+    /// [`InstructionResult::PrecompileError`]/[`InstructionResult::PrecompileOOG`] if it failed.
+    ///
+    /// [`InstructionResult::PrecompileError`]: revm::interpreter::InstructionResult::PrecompileError
+    /// [`InstructionResult::PrecompileOOG`]: revm::interpreter::InstructionResult::PrecompileOOG
+    #[inline]
+    fn precompile(
+        &mut self,
+        context: &mut CTX,
+        address: Address,
+        input: &Bytes,
+        gas_limit: u64,
+        result: &InterpreterResult,
+    ) {
+        let _ = context;
+        let _ = address;
+        let _ = input;
+        let _ = gas_limit;
+        let _ = result;
+    }
+
     /// Called when a contract has been self-destructed with funds transferred to target.
+    ///
+    /// Not called for an [EIP-6780] `SELFDESTRUCT` to itself that does not change any state,
+    /// see [`Inspector::selfdestruct_info`] for a hook that is called for every `SELFDESTRUCT`.
+    ///
+    /// [EIP-6780]: https://eips.ethereum.org/EIPS/eip-6780
     #[inline]
     fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
         let _ = contract;
         let _ = target;
         let _ = value;
     }
+
+    /// Called after every successful `SELFDESTRUCT` instruction, after [`Inspector::selfdestruct`].
+    ///
+    /// `info` contains the beneficiary, the value moved to it and whether the account was
+    /// actually destroyed or only had its balance moved under the [EIP-6780] rules.
+    ///
+    /// [EIP-6780]: https://eips.ethereum.org/EIPS/eip-6780
+    #[inline]
+    fn selfdestruct_info(
+        &mut self,
+        interp: &mut Interpreter<INTR>,
+        context: &mut CTX,
+        info: &SelfDestructInfo,
+    ) {
+        let _ = interp;
+        let _ = context;
+        let _ = info;
+    }
+}
+
+/// Information about an executed `SELFDESTRUCT`, see [`Inspector::selfdestruct_info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfDestructInfo {
+    /// Address of the self-destructed contract.
+    pub contract: Address,
+    /// Address that received the balance of the contract.
+    pub beneficiary: Address,
+    /// Balance moved from the contract to the beneficiary.
+    ///
+    /// If the beneficiary is the contract itself and the account is destroyed, this value is burnt.
+    pub value: U256,
+    /// Whether the account is destroyed at the end of the transaction.
+    ///
+    /// Since [EIP-6780] only contracts created in the same transaction are destroyed.
+    ///
+    /// [EIP-6780]: https://eips.ethereum.org/EIPS/eip-6780
+    pub destroyed: bool,
+    /// Whether the account was already self-destructed earlier in the transaction.
+    pub previously_destroyed: bool,
+    /// Whether the Cancun [EIP-6780] same transaction rules were applied.
+    ///
+    /// [EIP-6780]: https://eips.ethereum.org/EIPS/eip-6780
+    pub eip6780: bool,
+}
+
+/// Outcome of an executed transaction, see [`Inspector::tx_end`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxOutcome<'a> {
+    /// Whether the transaction succeeded, i.e. did not revert or halt.
+    pub success: bool,
+    /// Gas used by the transaction, after refunds.
+    pub gas_used: u64,
+    /// Logs emitted by the transaction, empty if it failed.
+    pub logs: &'a [Log],
+    /// State changed by the transaction, not committed yet.
+    pub state: &'a EvmState,
+}
+
+impl<'a> TxOutcome<'a> {
+    pub fn new<HaltReasonT: HaltReasonTrait>(result: &'a ResultAndState<HaltReasonT>) -> Self {
+        Self {
+            success: result.result.is_success(),
+            gas_used: result.result.gas_used(),
+            logs: result.result.logs(),
+            state: &result.state,
+        }
+    }
 }
 
 /// Provides access to an `Inspector` instance.
@@ -158,13 +369,35 @@ pub trait GetInspector<CTX, INTR: InterpreterTypes> {
 pub trait InspectorCtx {
     type IT: InterpreterTypes;
 
+    fn opcode_filter(&mut self) -> OpcodeFilter;
+    fn tx_start(&mut self);
+    fn tx_end(&mut self, outcome: Option<&TxOutcome<'_>>);
     fn step(&mut self, interp: &mut Interpreter<Self::IT>);
     fn step_end(&mut self, interp: &mut Interpreter<Self::IT>);
     fn initialize_interp(&mut self, interp: &mut Interpreter<Self::IT>);
     fn frame_start(&mut self, frame_input: &mut FrameInput) -> Option<FrameResult>;
     fn frame_end(&mut self, frame_output: &mut FrameResult);
-    fn inspector_selfdestruct(&mut self, contract: Address, target: Address, value: U256);
+    fn inspector_selfdestruct(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        info: SelfDestructInfo,
+    );
     fn inspector_log(&mut self, interp: &mut Interpreter<Self::IT>, log: &Log);
+    fn inspector_tload(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        address: Address,
+        key: U256,
+        value: U256,
+    );
+    fn inspector_tstore(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        address: Address,
+        key: U256,
+        old_value: U256,
+        new_value: U256,
+    );
 }
 
 impl<CTX, INTR: InterpreterTypes, INSP: Inspector<CTX, INTR>> GetInspector<CTX, INTR> for INSP {
@@ -184,6 +417,10 @@ impl<BLOCK, TX, CFG, DB: Database, JOURNAL: Journal<Database = DB> + JournalExt,
     }
 }
 
+/// Handler calling the inspector hooks around the frames of the wrapped `handler`.
+///
+/// Every other step of the transaction is delegated to the wrapped handler, so an inspected
+/// transaction is validated, charged and finalized exactly as when it is executed.
 pub struct InspectorHandlerImpl<CTX, ERROR, FRAME, HANDLER, PRECOMPILES, INTR: InterpreterTypes> {
     pub handler: HANDLER,
     pub base_instructions: InstructionTable<INTR, CTX>,
@@ -206,6 +443,9 @@ pub trait FrameInterpreterGetter {
     type IT: InterpreterTypes;
 
     fn interpreter(&mut self) -> &mut Interpreter<Self::IT>;
+
+    /// Returns the memory shared between the frame and its children.
+    fn shared_memory(&self) -> &Rc<RefCell<SharedMemory>>;
 }
 
 impl<CTX, ERROR, IW: InterpreterTypes, FRAMECTX> FrameInterpreterGetter
@@ -216,6 +456,10 @@ impl<CTX, ERROR, IW: InterpreterTypes, FRAMECTX> FrameInterpreterGetter
     fn interpreter(&mut self) -> &mut Interpreter<Self::IT> {
         &mut self.interpreter
     }
+
+    fn shared_memory(&self) -> &Rc<RefCell<SharedMemory>> {
+        &self.memory
+    }
 }
 
 impl<CTX, ERROR, FRAME, HANDLER, PRECOMPILES, INTR> EthHandler
@@ -247,6 +491,92 @@ where
         InspectorInstructionExecutor::new(self.base_instructions)
     }
 
+    fn precompile(&self, context: &mut Self::Context) -> Self::Precompiles {
+        self.handler.precompile(context)
+    }
+
+    fn validate_env(&self, context: &Self::Context) -> Result<(), Self::Error> {
+        self.handler.validate_env(context)
+    }
+
+    fn validate_tx_against_state(&self, context: &mut Self::Context) -> Result<(), Self::Error> {
+        self.handler.validate_tx_against_state(context)
+    }
+
+    fn validate_initial_tx_gas(
+        &self,
+        context: &Self::Context,
+    ) -> Result<InitialAndFloorGas, Self::Error> {
+        self.handler.validate_initial_tx_gas(context)
+    }
+
+    fn load_accounts(&self, context: &mut Self::Context) -> Result<(), Self::Error> {
+        self.handler.load_accounts(context)
+    }
+
+    fn apply_eip7702_auth_list(&self, context: &mut Self::Context) -> Result<u64, Self::Error> {
+        self.handler.apply_eip7702_auth_list(context)
+    }
+
+    fn deduct_caller(&self, context: &mut Self::Context) -> Result<(), Self::Error> {
+        self.handler.deduct_caller(context)
+    }
+
+    fn eip7623_check_gas_floor(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+        init_and_floor_gas: InitialAndFloorGas,
+    ) {
+        self.handler
+            .eip7623_check_gas_floor(context, exec_result, init_and_floor_gas)
+    }
+
+    fn refund(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+        eip7702_refund: i64,
+    ) {
+        self.handler.refund(context, exec_result, eip7702_refund)
+    }
+
+    fn reimburse_caller(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        self.handler.reimburse_caller(context, exec_result)
+    }
+
+    fn reward_beneficiary(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        self.handler.reward_beneficiary(context, exec_result)
+    }
+
+    fn output(
+        &self,
+        context: &mut Self::Context,
+        result: <Self::Frame as Frame>::FrameResult,
+    ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
+        self.handler.output(context, result)
+    }
+
+    fn end(
+        &self,
+        context: &mut Self::Context,
+        end_output: Result<ResultAndState<Self::HaltReason>, Self::Error>,
+    ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
+        self.handler.end(context, end_output)
+    }
+
+    fn clear(&self, context: &mut Self::Context) {
+        self.handler.clear(context)
+    }
+
     fn frame_init_first(
         &mut self,
         context: &mut Self::Context,
@@ -266,14 +596,9 @@ where
             .handler
             .frame_init_first(context, frame_context, frame_input);
 
-        match &mut ret {
-            Ok(ItemOrResult::Result(res)) => {
-                context.frame_end(res);
-            }
-            Ok(ItemOrResult::Item(frame)) => {
-                context.initialize_interp(frame.interpreter());
-            }
-            _ => (),
+        // Immediate results are passed to `frame_end` by `frame_return_result` or `last_frame_result`.
+        if let Ok(ItemOrResult::Item(frame)) = &mut ret {
+            context.initialize_interp(frame.interpreter());
         }
         ret
     }
@@ -292,19 +617,16 @@ where
         Self::Error,
     > {
         if let Some(output) = context.frame_start(&mut frame_input) {
+            // Parent frame frees the memory context of the child when the result is returned.
+            frame.shared_memory().borrow_mut().new_context();
             return Ok(ItemOrResult::Result(output));
         }
         let mut ret = self
             .handler
             .frame_init(frame, context, frame_context, frame_input);
-        match &mut ret {
-            Ok(ItemOrResult::Result(res)) => {
-                context.frame_end(res);
-            }
-            Ok(ItemOrResult::Item(frame)) => {
-                context.initialize_interp(frame.interpreter());
-            }
-            _ => (),
+        // Immediate results are passed to `frame_end` by `frame_return_result` or `last_frame_result`.
+        if let Ok(ItemOrResult::Item(frame)) = &mut ret {
+            context.initialize_interp(frame.interpreter());
         }
         ret
     }
@@ -1,16 +1,17 @@
 use revm::{
+    context_interface::journaled_state::JournalCheckpoint,
     context_interface::{
-        block::BlockSetter, transaction::TransactionSetter, BlockGetter, CfgGetter, DatabaseGetter,
-        ErrorGetter, JournalGetter, PerformantContextAccess, TransactionGetter,
+        BlockGetter, CfgGetter, DatabaseGetter, ErrorGetter, Journal, JournalGetter,
+        TransactionGetter,
     },
     database_interface::Database,
-    handler::{handler::EthContext, FrameResult},
-    interpreter::{interpreter::EthInterpreter, FrameInput, Host, Interpreter},
+    handler::FrameResult,
+    interpreter::{interpreter::EthInterpreter, FrameInput, Host, InstructionResult, Interpreter},
     primitives::{Address, Log, U256},
 };
 use std::vec::Vec;
 
-use crate::{journal::JournalExtGetter, GetInspector, Inspector, InspectorCtx};
+use crate::{GetInspector, Inspector, InspectorCtx, OpcodeFilter, SelfDestructInfo, TxOutcome};
 
 /// EVM context contains data that EVM needs for execution.
 #[derive(Clone, Debug)]
@@ -21,22 +22,33 @@ where
     pub inspector: INSP,
     pub inner: CTX,
     pub frame_input_stack: Vec<FrameInput>,
+    /// Transient storage writes that can still be reverted.
+    transient_writes: Vec<TransientStorageWrite>,
+    /// Bookkeeping for each frame in `frame_input_stack`.
+    frame_info_stack: Vec<FrameInfo>,
 }
 
-impl<
-        INSP: Inspector<CTX, EthInterpreter>,
-        DB: Database,
-        CTX: EthContext + DatabaseGetter<Database = DB>,
-    > EthContext for InspectorContext<INSP, DB, CTX>
-{
+/// Per frame bookkeeping, pushed and popped together with `frame_input_stack`.
+#[derive(Clone, Debug)]
+struct FrameInfo {
+    /// Length of `transient_writes` at the start of the frame.
+    transient_checkpoint: usize,
+    /// Inspector returned the frame result from its `call`/`create` hook.
+    overridden: bool,
+    /// Interpreter was started for the frame.
+    interpreter_started: bool,
+    /// Journal checkpoint taken before the frame, used to revert it if the inspector
+    /// turns a successful outcome into a failure. [`None`] for overridden frames.
+    checkpoint: Option<JournalCheckpoint>,
 }
 
-impl<
-        INSP: Inspector<CTX, EthInterpreter>,
-        DB: Database,
-        CTX: EthContext + DatabaseGetter<Database = DB>,
-    > EthContext for &mut InspectorContext<INSP, DB, CTX>
-{
+/// Transient storage write recorded so it can be reported when its frame reverts.
+#[derive(Clone, Debug)]
+struct TransientStorageWrite {
+    address: Address,
+    key: U256,
+    old_value: U256,
+    new_value: U256,
 }
 
 impl<INSP, DB, CTX> InspectorContext<INSP, DB, CTX>
@@ -54,6 +66,8 @@ where
             inner,
             inspector,
             frame_input_stack: Vec::new(),
+            transient_writes: Vec::new(),
+            frame_info_stack: Vec::new(),
         }
     }
 }
@@ -61,10 +75,24 @@ where
 impl<INSP, DB, CTX> InspectorCtx for InspectorContext<INSP, DB, CTX>
 where
     INSP: GetInspector<CTX, EthInterpreter>,
-    CTX: DatabaseGetter<Database = DB>,
+    CTX: JournalGetter + DatabaseGetter<Database = DB>,
 {
     type IT = EthInterpreter;
 
+    fn opcode_filter(&mut self) -> OpcodeFilter {
+        self.inspector.get_inspector().opcode_filter()
+    }
+
+    fn tx_start(&mut self) {
+        self.inspector.get_inspector().tx_start(&mut self.inner);
+    }
+
+    fn tx_end(&mut self, outcome: Option<&TxOutcome<'_>>) {
+        self.inspector
+            .get_inspector()
+            .tx_end(&mut self.inner, outcome);
+    }
+
     fn step(&mut self, interp: &mut Interpreter<Self::IT>) {
         self.inspector.get_inspector().step(interp, &mut self.inner);
     }
@@ -76,6 +104,9 @@ where
     }
 
     fn initialize_interp(&mut self, interp: &mut Interpreter<Self::IT>) {
+        if let Some(info) = self.frame_info_stack.last_mut() {
+            info.interpreter_started = true;
+        }
         self.inspector
             .get_inspector()
             .initialize_interp(interp, &mut self.inner);
@@ -89,39 +120,78 @@ where
     fn frame_start(&mut self, frame_input: &mut FrameInput) -> Option<FrameResult> {
         let insp = self.inspector.get_inspector();
         let context = &mut self.inner;
-        match frame_input {
-            FrameInput::Call(i) => {
-                if let Some(output) = insp.call(context, i) {
-                    return Some(FrameResult::Call(output));
-                }
-            }
-            FrameInput::Create(i) => {
-                if let Some(output) = insp.create(context, i) {
-                    return Some(FrameResult::Create(output));
-                }
-            }
-            FrameInput::EOFCreate(i) => {
-                if let Some(output) = insp.eofcreate(context, i) {
-                    return Some(FrameResult::EOFCreate(output));
-                }
-            }
-        }
+        let output = match frame_input {
+            FrameInput::Call(i) => insp.call(context, i).map(FrameResult::Call),
+            FrameInput::Create(i) => insp.create(context, i).map(FrameResult::Create),
+            FrameInput::EOFCreate(i) => insp.eofcreate(context, i).map(FrameResult::EOFCreate),
+        };
+        // Remember where the frame starts in the journal without changing its depth.
+        let checkpoint = output.is_none().then(|| {
+            let checkpoint = context.journal().checkpoint();
+            context.journal().checkpoint_commit();
+            checkpoint
+        });
+        // Every started frame is ended by `frame_end`, including the ones overridden by the inspector.
         self.frame_input_stack.push(frame_input.clone());
-        None
+        self.frame_info_stack.push(FrameInfo {
+            transient_checkpoint: self.transient_writes.len(),
+            overridden: output.is_some(),
+            interpreter_started: false,
+            checkpoint,
+        });
+        output
     }
 
     fn frame_end(&mut self, frame_output: &mut FrameResult) {
         let insp = self.inspector.get_inspector();
         let context = &mut self.inner;
         let Some(frame_input) = self.frame_input_stack.pop() else {
-            // case where call returns immediately will not push to call stack.
             return;
         };
+
+        let Some(info) = self.frame_info_stack.pop() else {
+            return;
+        };
+
+        let was_ok = frame_output.instruction_result().is_ok();
+        // Journal already reverted the transient writes of a failed frame, report them.
+        if !was_ok {
+            report_transient_reverts(
+                insp,
+                context,
+                &mut self.transient_writes,
+                info.transient_checkpoint,
+            );
+        }
+
         match frame_output {
             FrameResult::Call(outcome) => {
                 let FrameInput::Call(i) = frame_input else {
                     panic!("FrameInput::Call expected");
                 };
+                // Precompiles return without starting an interpreter, with one of their own results.
+                if !info.overridden
+                    && !info.interpreter_started
+                    && !i.scheme.is_ext_delegate_call()
+                    && matches!(
+                        outcome.result.result,
+                        InstructionResult::Return
+                            | InstructionResult::PrecompileOOG
+                            | InstructionResult::PrecompileError
+                    )
+                    && context
+                        .journal_ref()
+                        .precompile_addresses()
+                        .contains(&i.bytecode_address)
+                {
+                    insp.precompile(
+                        context,
+                        i.bytecode_address,
+                        &i.input,
+                        i.gas_limit,
+                        &outcome.result,
+                    );
+                }
                 insp.call_end(context, &i, outcome);
             }
             FrameResult::Create(outcome) => {
@@ -137,137 +207,545 @@ where
                 insp.eofcreate_end(context, &i, outcome);
             }
         }
+
+        // Frame was committed, but the inspector changed its outcome to a failure.
+        if was_ok && !frame_output.instruction_result().is_ok() {
+            if let Some(checkpoint) = info.checkpoint {
+                // `checkpoint_revert` closes a checkpoint, open one so the depth is kept.
+                context.journal().checkpoint();
+                context.journal().checkpoint_revert(checkpoint);
+                report_transient_reverts(
+                    insp,
+                    context,
+                    &mut self.transient_writes,
+                    info.transient_checkpoint,
+                );
+            }
+        }
+
+        if self.frame_input_stack.is_empty() {
+            self.transient_writes.clear();
+        }
     }
 
-    fn inspector_selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+    fn inspector_tload(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        address: Address,
+        key: U256,
+        value: U256,
+    ) {
         self.inspector
             .get_inspector()
-            .selfdestruct(contract, target, value)
-    }
-}
-
-impl<INSP, DB, CTX> CfgGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: CfgGetter + DatabaseGetter<Database = DB>,
-{
-    type Cfg = <CTX as CfgGetter>::Cfg;
-
-    fn cfg(&self) -> &Self::Cfg {
-        self.inner.cfg()
+            .tload(interp, &mut self.inner, address, key, value);
     }
-}
 
-impl<INSP, DB, CTX> JournalGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: JournalGetter + DatabaseGetter<Database = DB>,
-    DB: Database,
-{
-    type Journal = <CTX as JournalGetter>::Journal;
-
-    fn journal(&mut self) -> &mut Self::Journal {
-        self.inner.journal()
+    fn inspector_tstore(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        address: Address,
+        key: U256,
+        old_value: U256,
+        new_value: U256,
+    ) {
+        if old_value != new_value {
+            self.transient_writes.push(TransientStorageWrite {
+                address,
+                key,
+                old_value,
+                new_value,
+            });
+        }
+        self.inspector.get_inspector().tstore(
+            interp,
+            &mut self.inner,
+            address,
+            key,
+            old_value,
+            new_value,
+        );
     }
 
-    fn journal_ref(&self) -> &Self::Journal {
-        self.inner.journal_ref()
+    fn inspector_selfdestruct(
+        &mut self,
+        interp: &mut Interpreter<Self::IT>,
+        info: SelfDestructInfo,
+    ) {
+        let insp = self.inspector.get_inspector();
+        // Keep the legacy hook silent for EIP-6780 selfdestructs that don't change state.
+        if info.destroyed || info.beneficiary != info.contract {
+            insp.selfdestruct(info.contract, info.beneficiary, info.value);
+        }
+        insp.selfdestruct_info(interp, &mut self.inner, &info);
     }
 }
 
-impl<INSP: GetInspector<CTX, EthInterpreter>, DB: Database, CTX> Host
-    for InspectorContext<INSP, DB, CTX>
-where
-    CTX: Host + DatabaseGetter<Database = DB>,
-{
-    fn set_error(&mut self, error: <DB as Database>::Error) {
-        self.inner.set_error(error);
+/// Reports transient storage writes made since `checkpoint` as reverted, newest first.
+fn report_transient_reverts<CTX, INSP: Inspector<CTX, EthInterpreter>>(
+    insp: &mut INSP,
+    context: &mut CTX,
+    writes: &mut Vec<TransientStorageWrite>,
+    checkpoint: usize,
+) {
+    for write in writes.drain(checkpoint..).rev() {
+        insp.tstore_revert(
+            context,
+            write.address,
+            write.key,
+            write.new_value,
+            write.old_value,
+        );
     }
 }
 
-impl<INSP, DB, CTX> DatabaseGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: DatabaseGetter<Database = DB>,
-    DB: Database,
-{
-    type Database = <CTX as DatabaseGetter>::Database;
+crate::forward_context_getters! {
+    impl[INSP, DB, CTX] InspectorContext<INSP, DB, CTX> => inner: CTX
+    where
+        CTX: DatabaseGetter<Database = DB>,
+        DB: Database,
+}
 
-    fn db(&mut self) -> &mut Self::Database {
-        self.inner.db()
+#[cfg(test)]
+mod tests {
+    use crate::{exec::InspectEvm, Inspector, SelfDestructInfo};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context_interface::result::ExecutionResult,
+        interpreter::{
+            interpreter::EthInterpreter, interpreter_types::Jumps, CallInputs, CallOutcome,
+            InstructionResult, Interpreter, InterpreterResult,
+        },
+        primitives::{address, Address, Bytes, TxKind, U256},
+        specification::hardfork::SpecId,
+        Context,
+    };
+    use std::{vec, vec::Vec};
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Event {
+        Step(u8),
+        Call(Address),
+        CallEnd(Address),
+        TLoad(U256, U256),
+        TStore(U256, U256, U256),
+        TStoreRevert(U256, U256, U256),
+        Precompile(Address, u64, InstructionResult),
+        SelfDestruct(Address, Address, U256),
+        SelfDestructInfo(SelfDestructInfo),
     }
 
-    fn db_ref(&self) -> &Self::Database {
-        self.inner.db_ref()
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
     }
-}
 
-impl<INSP, DB, CTX> ErrorGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: ErrorGetter + JournalGetter<Database = DB>,
-{
-    type Error = <CTX as ErrorGetter>::Error;
+    impl<CTX> Inspector<CTX, EthInterpreter> for Recorder {
+        fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+            self.events.push(Event::Step(interp.bytecode.opcode()));
+        }
+
+        fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+            self.events.push(Event::Call(inputs.target_address));
+            None
+        }
+
+        fn call_end(
+            &mut self,
+            _context: &mut CTX,
+            inputs: &CallInputs,
+            _outcome: &mut CallOutcome,
+        ) {
+            self.events.push(Event::CallEnd(inputs.target_address));
+        }
+
+        fn tload(
+            &mut self,
+            _interp: &mut Interpreter<EthInterpreter>,
+            _context: &mut CTX,
+            _address: Address,
+            key: U256,
+            value: U256,
+        ) {
+            self.events.push(Event::TLoad(key, value));
+        }
+
+        fn tstore(
+            &mut self,
+            _interp: &mut Interpreter<EthInterpreter>,
+            _context: &mut CTX,
+            _address: Address,
+            key: U256,
+            old_value: U256,
+            new_value: U256,
+        ) {
+            self.events.push(Event::TStore(key, old_value, new_value));
+        }
+
+        fn precompile(
+            &mut self,
+            _context: &mut CTX,
+            address: Address,
+            _input: &Bytes,
+            gas_limit: u64,
+            result: &InterpreterResult,
+        ) {
+            self.events
+                .push(Event::Precompile(address, gas_limit, result.result));
+        }
+
+        fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+            self.events
+                .push(Event::SelfDestruct(contract, target, value));
+        }
 
-    fn take_error(&mut self) -> Result<(), Self::Error> {
-        self.inner.take_error()
+        fn selfdestruct_info(
+            &mut self,
+            _interp: &mut Interpreter<EthInterpreter>,
+            _context: &mut CTX,
+            info: &SelfDestructInfo,
+        ) {
+            self.events.push(Event::SelfDestructInfo(*info));
+        }
+
+        fn tstore_revert(
+            &mut self,
+            _context: &mut CTX,
+            _address: Address,
+            key: U256,
+            reverted_value: U256,
+            restored_value: U256,
+        ) {
+            self.events
+                .push(Event::TStoreRevert(key, reverted_value, restored_value));
+        }
     }
-}
 
-impl<INSP, DB, CTX> TransactionGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: TransactionGetter + DatabaseGetter<Database = DB>,
-{
-    type Transaction = <CTX as TransactionGetter>::Transaction;
+    fn inspect(code: Vec<u8>) -> Vec<Event> {
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut recorder = Recorder::default();
+        ctx.inspect_previous(&mut recorder).unwrap();
+        recorder.events
+    }
 
-    fn tx(&self) -> &Self::Transaction {
-        self.inner.tx()
+    #[test]
+    fn transient_storage_hooks_and_revert() {
+        let events = inspect(vec![
+            opcode::PUSH1,
+            0x07,
+            opcode::PUSH1,
+            0x01,
+            opcode::TSTORE,
+            opcode::PUSH1,
+            0x01,
+            opcode::TLOAD,
+            opcode::PUSH1,
+            0x00,
+            opcode::DUP1,
+            opcode::REVERT,
+        ]);
+        let hooks: Vec<_> = events
+            .into_iter()
+            .filter(|e| !matches!(e, Event::Step(_)))
+            .collect();
+        assert_eq!(
+            hooks,
+            vec![
+                Event::Call(BENCH_TARGET),
+                Event::TStore(U256::from(1), U256::ZERO, U256::from(7)),
+                Event::TLoad(U256::from(1), U256::from(7)),
+                Event::TStoreRevert(U256::from(1), U256::from(7), U256::ZERO),
+                Event::CallEnd(BENCH_TARGET),
+            ]
+        );
     }
-}
 
-impl<INSP, DB, CTX> TransactionSetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: TransactionSetter + DatabaseGetter<Database = DB>,
-{
-    fn set_tx(&mut self, tx: <Self as TransactionGetter>::Transaction) {
-        self.inner.set_tx(tx);
+    #[test]
+    fn immediate_frame_result_ends_once() {
+        let empty = address!("0000000000000000000000000000000000001234");
+        // CALL(0xffff, 0x1234, 0, 0, 0, 0, 0) to an account without code, then STOP.
+        let events = inspect(vec![
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH2,
+            0x12,
+            0x34,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            opcode::STOP,
+        ]);
+        let tail = &events[events.len() - 5..];
+        assert_eq!(
+            tail,
+            [
+                Event::Step(opcode::CALL),
+                Event::Call(empty),
+                Event::CallEnd(empty),
+                Event::Step(opcode::STOP),
+                Event::CallEnd(BENCH_TARGET),
+            ]
+        );
     }
-}
 
-impl<INSP, DB, CTX> BlockGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: BlockGetter + DatabaseGetter<Database = DB>,
-{
-    type Block = <CTX as BlockGetter>::Block;
+    #[test]
+    fn overridden_frame_ends_once() {
+        use revm::interpreter::{Gas, InstructionResult, InterpreterResult};
+
+        /// Records calls, returning a result for the calls to `mocked` instead of running them.
+        struct Mocker {
+            mocked: Address,
+            events: Vec<Event>,
+        }
+
+        impl<CTX> Inspector<CTX, EthInterpreter> for Mocker {
+            fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+                self.events.push(Event::Call(inputs.target_address));
+                (inputs.target_address == self.mocked).then(|| {
+                    let result = InterpreterResult::new(
+                        InstructionResult::Return,
+                        Default::default(),
+                        Gas::new(inputs.gas_limit),
+                    );
+                    CallOutcome::new(result, inputs.return_memory_offset.clone())
+                })
+            }
+
+            fn call_end(
+                &mut self,
+                _context: &mut CTX,
+                inputs: &CallInputs,
+                _outcome: &mut CallOutcome,
+            ) {
+                self.events.push(Event::CallEnd(inputs.target_address));
+            }
+        }
 
-    fn block(&self) -> &Self::Block {
-        self.inner.block()
+        let mocked = address!("0000000000000000000000000000000000001234");
+        // CALL(0xffff, 0x1234, 0, 0, 0, 0, 0), then STOP.
+        let code = vec![
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH2,
+            0x12,
+            0x34,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            opcode::STOP,
+        ];
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut mocker = Mocker {
+            mocked,
+            events: Vec::new(),
+        };
+        ctx.inspect_previous(&mut mocker).unwrap();
+        assert_eq!(
+            mocker.events,
+            [
+                Event::Call(BENCH_TARGET),
+                Event::Call(mocked),
+                Event::CallEnd(mocked),
+                Event::CallEnd(BENCH_TARGET),
+            ]
+        );
     }
-}
 
-impl<INSP, DB, CTX> BlockSetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: BlockSetter + DatabaseGetter<Database = DB>,
-{
-    fn set_block(&mut self, block: <Self as BlockGetter>::Block) {
-        self.inner.set_block(block);
+    #[test]
+    fn precompile_hook() {
+        let identity = address!("0000000000000000000000000000000000000004");
+        // CALL(0xffff, 0x04, 0, 0, 0, 0, 0), then STOP.
+        let events = inspect(vec![
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0x04,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            opcode::STOP,
+        ]);
+        let tail = &events[events.len() - 5..];
+        assert_eq!(
+            tail,
+            [
+                Event::Call(identity),
+                Event::Precompile(identity, 0xffff, InstructionResult::Return),
+                Event::CallEnd(identity),
+                Event::Step(opcode::STOP),
+                Event::CallEnd(BENCH_TARGET),
+            ]
+        );
+        // Calls to regular accounts are not reported as precompiles.
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, Event::Precompile(..)))
+                .count(),
+            1
+        );
     }
-}
 
-impl<INSP, DB, CTX> JournalExtGetter for InspectorContext<INSP, DB, CTX>
-where
-    CTX: JournalExtGetter + DatabaseGetter<Database = DB>,
-{
-    type JournalExt = <CTX as JournalExtGetter>::JournalExt;
+    #[test]
+    fn selfdestruct_info_eip6780() {
+        let beneficiary = address!("0000000000000000000000000000000000001234");
+        let events = inspect(vec![opcode::PUSH2, 0x12, 0x34, opcode::SELFDESTRUCT]);
+        let value = U256::from(10000000);
+        let hooks: Vec<_> = events
+            .into_iter()
+            .filter(|e| matches!(e, Event::SelfDestruct(..) | Event::SelfDestructInfo(..)))
+            .collect();
+        assert_eq!(
+            hooks,
+            vec![
+                Event::SelfDestruct(BENCH_TARGET, beneficiary, value),
+                Event::SelfDestructInfo(SelfDestructInfo {
+                    contract: BENCH_TARGET,
+                    beneficiary,
+                    value,
+                    destroyed: false,
+                    previously_destroyed: false,
+                    eip6780: true,
+                }),
+            ]
+        );
+    }
 
-    fn journal_ext(&self) -> &Self::JournalExt {
-        self.inner.journal_ext()
+    /// Mocks calls to `mocked` and forces the first frame to revert.
+    struct Overrider {
+        mocked: Address,
+        transient_reverts: usize,
     }
-}
 
-impl<INSP, DB: Database, CTX> PerformantContextAccess for InspectorContext<INSP, DB, CTX>
-where
-    CTX: PerformantContextAccess<Error = DB::Error> + DatabaseGetter<Database = DB>,
-{
-    type Error = <CTX as PerformantContextAccess>::Error;
+    impl<CTX> Inspector<CTX, EthInterpreter> for Overrider {
+        fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+            (inputs.target_address == self.mocked).then(|| {
+                CallOutcome::from_inputs(inputs, InstructionResult::Return, Bytes::from([0x42; 32]))
+            })
+        }
 
-    fn load_access_list(&mut self) -> Result<(), Self::Error> {
-        self.inner.load_access_list()
+        fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+            if inputs.target_address == BENCH_TARGET {
+                outcome.revert_with(outcome.output().clone());
+            }
+        }
+
+        fn tstore_revert(
+            &mut self,
+            _context: &mut CTX,
+            _address: Address,
+            _key: U256,
+            _reverted_value: U256,
+            _restored_value: U256,
+        ) {
+            self.transient_reverts += 1;
+        }
+    }
+
+    #[test]
+    fn override_outcomes() {
+        let mocked = address!("0000000000000000000000000000000000001234");
+        let code = vec![
+            // SSTORE(1, 7), TSTORE(1, 7)
+            opcode::PUSH1,
+            0x07,
+            opcode::PUSH1,
+            0x01,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0x07,
+            opcode::PUSH1,
+            0x01,
+            opcode::TSTORE,
+            // CALL(0xffff, 0x1234, 0, 0, 0, 0, 32)
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH2,
+            0x12,
+            0x34,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            // RETURN(0, 32)
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH1,
+            0,
+            opcode::RETURN,
+        ];
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut overrider = Overrider {
+            mocked,
+            transient_reverts: 0,
+        };
+        let result = ctx.inspect_previous(&mut overrider).unwrap();
+
+        // Mocked return data is bubbled up by the first frame, which is forced to revert.
+        assert!(matches!(
+            result.result,
+            ExecutionResult::Revert { ref output, .. } if output[..] == [0x42; 32]
+        ));
+        let storage = &result.state[&BENCH_TARGET].storage;
+        assert!(storage
+            .get(&U256::from(1))
+            .is_none_or(|slot| !slot.is_changed()));
+        assert_eq!(overrider.transient_reverts, 1);
     }
 }
@@ -1,21 +1,23 @@
 use core::mem::MaybeUninit;
 use revm::{
     bytecode::opcode::OpCode,
-    context_interface::JournalGetter,
+    context_interface::{Cfg, CfgGetter, JournalGetter},
     handler::instructions::InstructionExecutor,
     interpreter::{
-        instructions::host::{log, selfdestruct},
-        interpreter_types::{Jumps, LoopControl},
+        instructions::host::{log, selfdestruct, tload, tstore},
+        interpreter_types::{InputsTrait, Jumps, LoopControl, StackTrait},
         table::{make_instruction_table, CustomInstruction, InstructionTable},
         Host, Instruction, InstructionResult, Interpreter, InterpreterAction, InterpreterTypes,
     },
+    primitives::U256,
+    specification::hardfork::SpecId,
     JournalEntry,
 };
 use std::rc::Rc;
 
 use crate::{
     journal::{JournalExt, JournalExtGetter},
-    InspectorCtx,
+    InspectorCtx, SelfDestructInfo,
 };
 
 #[derive(Clone)]
@@ -118,26 +120,82 @@ where
             },
         };
 
+        /* Transient storage instructions */
+        table[OpCode::TLOAD.as_usize()] = InspectorInstruction {
+            instruction: |interp, context| {
+                // Peek the key, stack underflow is handled by the instruction itself.
+                let key = interp.stack.top().map(|key| *key);
+                tload::<WIRE, HOST>(interp, context);
+                if interp.control.instruction_result() != InstructionResult::Continue {
+                    return;
+                }
+                if let (Some(key), Some(value)) = (key, interp.stack.top().map(|value| *value)) {
+                    let address = interp.input.target_address();
+                    context.inspector_tload(interp, address, key, value);
+                }
+            },
+        };
+        table[OpCode::TSTORE.as_usize()] = InspectorInstruction {
+            instruction: |interp, context| {
+                // Peek key and value, stack underflow is handled by the instruction itself.
+                let operands = interp.stack.popn::<2>();
+                if let Some([key, value]) = operands {
+                    let _ = interp.stack.push(value);
+                    let _ = interp.stack.push(key);
+                }
+                let address = interp.input.target_address();
+                let old_value = operands.map(|[key, _]| context.tload(address, key));
+                tstore::<WIRE, HOST>(interp, context);
+                if interp.control.instruction_result() != InstructionResult::Continue {
+                    return;
+                }
+                if let (Some([key, new_value]), Some(old_value)) = (operands, old_value) {
+                    context.inspector_tstore(interp, address, key, old_value, new_value);
+                }
+            },
+        };
+
         table[OpCode::SELFDESTRUCT.as_usize()] = InspectorInstruction {
             instruction: |interp, context| {
+                let contract = interp.input.target_address();
                 selfdestruct::<WIRE, HOST>(interp, context);
                 if interp.control.instruction_result() == InstructionResult::SelfDestruct {
-                    match context.journal_ext().last_journal().last() {
+                    let eip6780 = context.cfg().spec().into().is_enabled_in(SpecId::CANCUN);
+                    let info = match context.journal_ext().last_journal().last() {
                         Some(JournalEntry::AccountDestroyed {
                             address,
                             target,
                             had_balance,
-                            ..
-                        }) => {
-                            context.inspector_selfdestruct(*address, *target, *had_balance);
-                        }
+                            was_destroyed,
+                        }) => SelfDestructInfo {
+                            contract: *address,
+                            beneficiary: *target,
+                            value: *had_balance,
+                            destroyed: true,
+                            previously_destroyed: *was_destroyed,
+                            eip6780,
+                        },
                         Some(JournalEntry::BalanceTransfer {
                             from, to, balance, ..
-                        }) => {
-                            context.inspector_selfdestruct(*from, *to, *balance);
-                        }
-                        _ => {}
-                    }
+                        }) => SelfDestructInfo {
+                            contract: *from,
+                            beneficiary: *to,
+                            value: *balance,
+                            destroyed: false,
+                            previously_destroyed: false,
+                            eip6780,
+                        },
+                        // EIP-6780 selfdestruct to itself of a pre-existing contract does not change state.
+                        _ => SelfDestructInfo {
+                            contract,
+                            beneficiary: contract,
+                            value: U256::ZERO,
+                            destroyed: false,
+                            previously_destroyed: false,
+                            eip6780,
+                        },
+                    };
+                    context.inspector_selfdestruct(interp, info);
                 }
             },
         };
@@ -164,15 +222,21 @@ where
     ) -> Self::Output {
         interpreter.reset_control();
 
+        // Opcodes for which step hooks are called.
+        let filter = context.opcode_filter();
+
         // Main loop
         while interpreter.control.instruction_result().is_continue() {
             // Get current opcode.
             let opcode = interpreter.bytecode.opcode();
+            let inspect_step = filter.contains(opcode);
 
             // Call Inspector step.
-            context.step(interpreter);
-            if interpreter.control.instruction_result() != InstructionResult::Continue {
-                break;
+            if inspect_step {
+                context.step(interpreter);
+                if interpreter.control.instruction_result() != InstructionResult::Continue {
+                    break;
+                }
             }
 
             // SAFETY: In analysis we are doing padding of bytecode so that we are sure that last
@@ -184,7 +248,9 @@ where
             self.instruction_table[opcode as usize].exec(interpreter, context);
 
             // Call step_end.
-            context.step_end(interpreter);
+            if inspect_step {
+                context.step_end(interpreter);
+            }
         }
 
         interpreter.take_next_action()
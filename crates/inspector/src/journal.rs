@@ -1,9 +1,13 @@
 use auto_impl::auto_impl;
 use revm::{
-    context::JournaledState, database_interface::Database, primitives::Log, state::EvmState,
+    context::JournaledState,
+    database_interface::Database,
+    primitives::{Address, HashSet, Log, U256},
+    state::{Account, AccountStatus, EvmState, TransientStorage},
     JournalEntry,
 };
 
+/// Read access to the journaled state, available to inspector hooks through [`JournalExtGetter`].
 #[auto_impl(&mut, Box)]
 pub trait JournalExt {
     fn logs(&self) -> &[Log];
@@ -13,6 +17,53 @@ pub trait JournalExt {
     fn evm_state(&self) -> &EvmState;
 
     fn evm_state_mut(&mut self) -> &mut EvmState;
+
+    /// Returns the current call depth, equal to the number of open checkpoints.
+    fn depth(&self) -> usize;
+
+    /// Returns the transient storage of the transaction.
+    fn transient_storage(&self) -> &TransientStorage;
+
+    /// Returns the addresses that are warm without being loaded, e.g. from the access list.
+    fn warm_preloaded_addresses(&self) -> &HashSet<Address>;
+
+    /// Returns the account if it was loaded by the transaction.
+    #[inline]
+    fn account(&self, address: &Address) -> Option<&Account> {
+        self.evm_state().get(address)
+    }
+
+    /// Returns the current balance of a loaded account.
+    #[inline]
+    fn balance(&self, address: &Address) -> Option<U256> {
+        self.account(address).map(|account| account.info.balance)
+    }
+
+    /// Returns the current value of a loaded storage slot.
+    #[inline]
+    fn storage(&self, address: &Address, key: &U256) -> Option<U256> {
+        self.account(address)?
+            .storage
+            .get(key)
+            .map(|slot| slot.present_value)
+    }
+
+    /// Returns `true` if accessing the account would not cost cold access gas.
+    #[inline]
+    fn is_address_warm(&self, address: &Address) -> bool {
+        self.warm_preloaded_addresses().contains(address)
+            || self
+                .account(address)
+                .is_some_and(|account| !account.status.contains(AccountStatus::Cold))
+    }
+
+    /// Returns `true` if accessing the storage slot would not cost cold access gas.
+    #[inline]
+    fn is_storage_warm(&self, address: &Address, key: &U256) -> bool {
+        self.account(address)
+            .and_then(|account| account.storage.get(key))
+            .is_some_and(|slot| !slot.is_cold)
+    }
 }
 
 impl<DB: Database> JournalExt for JournaledState<DB> {
@@ -31,6 +82,18 @@ impl<DB: Database> JournalExt for JournaledState<DB> {
     fn evm_state_mut(&mut self) -> &mut EvmState {
         &mut self.state
     }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn transient_storage(&self) -> &TransientStorage {
+        &self.transient_storage
+    }
+
+    fn warm_preloaded_addresses(&self) -> &HashSet<Address> {
+        &self.warm_preloaded_addresses
+    }
 }
 
 #[auto_impl(&, &mut, Box, Arc)]
@@ -39,3 +102,96 @@ pub trait JournalExtGetter {
 
     fn journal_ext(&self) -> &Self::JournalExt;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exec::InspectEvm, Inspector};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        interpreter::{interpreter::EthInterpreter, interpreter_types::Jumps, Interpreter},
+        primitives::{address, TxKind},
+        Context,
+    };
+    use std::vec::Vec;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Snapshot {
+        opcode: u8,
+        depth: usize,
+        balance: Option<U256>,
+        address_warm: bool,
+        storage_warm: bool,
+        storage: Option<U256>,
+    }
+
+    #[derive(Default)]
+    struct StateReader {
+        snapshots: Vec<Snapshot>,
+    }
+
+    impl<CTX: JournalExtGetter> Inspector<CTX, EthInterpreter> for StateReader {
+        fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+            let journal = context.journal_ext();
+            let key = U256::from(1);
+            self.snapshots.push(Snapshot {
+                opcode: interp.bytecode.opcode(),
+                depth: journal.depth(),
+                balance: journal.balance(&BENCH_TARGET),
+                address_warm: journal
+                    .is_address_warm(&address!("0000000000000000000000000000000000001234")),
+                storage_warm: journal.is_storage_warm(&BENCH_TARGET, &key),
+                storage: journal.storage(&BENCH_TARGET, &key),
+            });
+        }
+    }
+
+    #[test]
+    fn step_reads_journal() {
+        // SSTORE(1, 7), STOP
+        let code = [
+            opcode::PUSH1,
+            0x07,
+            opcode::PUSH1,
+            0x01,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut reader = StateReader::default();
+        ctx.inspect_previous(&mut reader).unwrap();
+
+        let balance = Some(U256::from(10000000));
+        assert_eq!(
+            reader.snapshots[2],
+            Snapshot {
+                opcode: opcode::SSTORE,
+                depth: 1,
+                balance,
+                address_warm: false,
+                storage_warm: false,
+                storage: None,
+            }
+        );
+        assert_eq!(
+            reader.snapshots[3],
+            Snapshot {
+                opcode: opcode::STOP,
+                depth: 1,
+                balance,
+                address_warm: false,
+                storage_warm: true,
+                storage: Some(U256::from(7)),
+            }
+        );
+    }
+}
@@ -5,22 +5,73 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
-#[cfg(all(feature = "std", feature = "serde-json"))]
+#[cfg(feature = "serde-json")]
+mod audit_log;
+#[cfg(feature = "std")]
+mod channel;
+mod cheatcodes;
+mod context_getters;
+mod coverage;
+mod create_tracker;
+pub mod differential;
+#[cfg(feature = "serde-json")]
 mod eip3155;
 pub mod exec;
 mod gas;
+mod gas_attribution;
+mod gas_profiler;
 mod inspector;
 pub mod inspector_context;
 pub mod inspector_instruction;
 pub mod journal;
+mod memory_capture;
 mod noop;
+mod opcode_filter;
+pub mod parity_trace;
+pub mod replay;
+mod revert_decoder;
+#[cfg(feature = "serde-json")]
+pub mod snapshot;
+pub mod state_diff;
+#[cfg(feature = "async")]
+mod stream;
+mod time_travel;
+mod truncation;
+#[cfg(feature = "alloy")]
+pub mod verifier;
 
 pub use inspector::*;
+pub use memory_capture::{referenced_region, CapturedMemory, MemoryCapture, MemoryCaptureMode};
+pub use opcode_filter::OpcodeFilter;
+pub use truncation::{truncate, TruncationConfig};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use revm;
+}
 
 /// [Inspector] implementations.
 pub mod inspectors {
-    #[cfg(all(feature = "std", feature = "serde-json"))]
+    #[cfg(feature = "serde-json")]
+    pub use super::audit_log::{AuditLog, AuditRecord, StateChange};
+    #[cfg(feature = "std")]
+    pub use super::channel::{ChannelInspector, ChannelOverflow, TraceEvent};
+    pub use super::cheatcodes::{
+        Cheatcall, CheatcodeHandler, CheatcodeInspector, Prank, CHEATCODE_ADDRESS,
+    };
+    pub use super::coverage::{
+        edge_index, CodeCoverage, CoverageInspector, PcBitmap, EDGE_MAP_SIZE,
+    };
+    pub use super::create_tracker::{ContractCreation, CreateTracker};
+    #[cfg(feature = "serde-json")]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
+    pub use super::gas_attribution::{AddressGas, GasAttribution};
+    pub use super::gas_profiler::{GasProfileNode, GasProfiler};
     pub use super::noop::NoOpInspector;
+    pub use super::parity_trace::ParityTracer;
+    pub use super::revert_decoder::{RevertDecoder, RevertedFrame};
+    #[cfg(feature = "async")]
+    pub use super::stream::TraceStream;
+    pub use super::time_travel::{RecordGranularity, Snapshot, TimeTravelRecorder};
 }
@@ -0,0 +1,271 @@
+//! Bounded memory capture for tracing inspectors.
+use core::ops::Range;
+use revm::{
+    bytecode::opcode,
+    primitives::{Bytes, U256},
+};
+
+/// Which part of the memory is captured on each step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MemoryCaptureMode {
+    /// Whole memory.
+    #[default]
+    Full,
+    /// Only the memory regions read or written by the current opcode.
+    ///
+    /// Opcodes that don't access memory capture nothing.
+    Referenced,
+}
+
+/// Memory captured for a single step by [`MemoryCapture`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapturedMemory {
+    /// Offset of `data` in the memory, zero in [`MemoryCaptureMode::Full`].
+    pub offset: usize,
+    /// Captured bytes.
+    pub data: Bytes,
+    /// `true` if `data` was cut short by one of the limits.
+    pub truncated: bool,
+}
+
+/// Memory capture options and state of a tracing inspector.
+///
+/// Captures are shared copy-on-write: when the previous opcode could not have written to the
+/// memory and its size did not change, the previous capture is reused without copying.
+/// [`MemoryCapture::frame_changed`] must be called when a frame starts or ends, as the previous
+/// capture was then taken from the memory of another frame.
+/// Only newly copied bytes count towards [`MemoryCapture::with_max_total_bytes`].
+#[derive(Clone, Debug)]
+pub struct MemoryCapture {
+    mode: MemoryCaptureMode,
+    max_step_bytes: usize,
+    max_total_bytes: usize,
+    /// Bytes copied since the last [`MemoryCapture::reset`].
+    total_bytes: usize,
+    /// Last full memory capture and the size of the memory it was taken from.
+    last: Option<(CapturedMemory, usize)>,
+    /// Opcode of the last captured step may have written to the memory.
+    last_writes: bool,
+}
+
+impl Default for MemoryCapture {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl MemoryCapture {
+    /// Captures the whole memory on each step, without limits.
+    pub const fn full() -> Self {
+        Self::new(MemoryCaptureMode::Full)
+    }
+
+    /// Captures the memory regions referenced by the current opcode, without limits.
+    pub const fn referenced() -> Self {
+        Self::new(MemoryCaptureMode::Referenced)
+    }
+
+    /// Creates a capture with the given mode and without limits.
+    pub const fn new(mode: MemoryCaptureMode) -> Self {
+        Self {
+            mode,
+            max_step_bytes: usize::MAX,
+            max_total_bytes: usize::MAX,
+            total_bytes: 0,
+            last: None,
+            last_writes: true,
+        }
+    }
+
+    /// Caps the number of bytes captured on a single step.
+    pub const fn with_max_step_bytes(mut self, max: usize) -> Self {
+        self.max_step_bytes = max;
+        self
+    }
+
+    /// Caps the number of bytes copied until the next [`MemoryCapture::reset`].
+    ///
+    /// Once reached, steps capture empty, truncated memory.
+    pub const fn with_max_total_bytes(mut self, max: usize) -> Self {
+        self.max_total_bytes = max;
+        self
+    }
+
+    /// Returns the capture mode.
+    pub const fn mode(&self) -> MemoryCaptureMode {
+        self.mode
+    }
+
+    /// Returns the number of bytes copied since the last reset.
+    pub const fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Resets the copied bytes counter and drops the shared capture.
+    pub fn reset(&mut self) {
+        self.total_bytes = 0;
+        self.frame_changed();
+    }
+
+    /// Drops the shared capture, to be called when a call or create frame starts or ends.
+    pub fn frame_changed(&mut self) {
+        self.last = None;
+        self.last_writes = true;
+    }
+
+    /// Captures the memory for a step that is about to execute `opcode`.
+    ///
+    /// `stack` is the stack before the opcode, with the top of the stack as the last element.
+    /// Returns [`None`] if nothing is captured for the opcode.
+    pub fn capture(&mut self, opcode: u8, stack: &[U256], memory: &[u8]) -> Option<CapturedMemory> {
+        let writes = writes_memory(opcode);
+        let captured = match self.mode {
+            MemoryCaptureMode::Full => {
+                let reusable = !self.last_writes;
+                match &self.last {
+                    Some((last, size)) if reusable && *size == memory.len() => Some(last.clone()),
+                    _ => {
+                        let captured = self.copy(0..memory.len(), memory);
+                        self.last = Some((captured.clone(), memory.len()));
+                        Some(captured)
+                    }
+                }
+            }
+            MemoryCaptureMode::Referenced => referenced_region(opcode, stack)
+                .map(|region| self.copy(region.start..region.end.min(memory.len()), memory)),
+        };
+        self.last_writes = writes;
+        captured
+    }
+
+    /// Copies `region` of the memory within the limits.
+    fn copy(&mut self, region: Range<usize>, memory: &[u8]) -> CapturedMemory {
+        let region = region.start.min(memory.len())..region.end;
+        let available = self.max_total_bytes.saturating_sub(self.total_bytes);
+        let len = region.len().min(self.max_step_bytes).min(available);
+        self.total_bytes += len;
+        CapturedMemory {
+            offset: region.start,
+            data: Bytes::copy_from_slice(&memory[region.start..region.start + len]),
+            truncated: len < region.len(),
+        }
+    }
+}
+
+/// Returns `true` if `opcode` can write to the memory.
+const fn writes_memory(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcode::MSTORE
+            | opcode::MSTORE8
+            | opcode::MCOPY
+            | opcode::CALLDATACOPY
+            | opcode::CODECOPY
+            | opcode::EXTCODECOPY
+            | opcode::RETURNDATACOPY
+            | opcode::DATACOPY
+            | opcode::CALL
+            | opcode::CALLCODE
+            | opcode::DELEGATECALL
+            | opcode::STATICCALL
+    )
+}
+
+/// Returns the memory region read or written by `opcode`, covering all accessed ranges.
+///
+/// `stack` has the top of the stack as the last element.
+pub fn referenced_region(opcode: u8, stack: &[U256]) -> Option<Range<usize>> {
+    let arg = |i: usize| -> Option<usize> {
+        let value = stack.get(stack.len().checked_sub(i + 1)?)?;
+        usize::try_from(*value).ok()
+    };
+    let range = |offset: usize, len: usize| -> Option<Range<usize>> {
+        let len = arg(len).filter(|len| *len != 0)?;
+        let offset = arg(offset)?;
+        Some(offset..offset.checked_add(len)?)
+    };
+    let fixed = |offset: usize, len: usize| Some(arg(offset)?..arg(offset)?.checked_add(len)?);
+    let union = |a: Option<Range<usize>>, b: Option<Range<usize>>| match (a, b) {
+        (Some(a), Some(b)) => Some(a.start.min(b.start)..a.end.max(b.end)),
+        (a, b) => a.or(b),
+    };
+
+    match opcode {
+        opcode::MLOAD | opcode::MSTORE => fixed(0, 32),
+        opcode::MSTORE8 => fixed(0, 1),
+        opcode::KECCAK256
+        | opcode::RETURN
+        | opcode::REVERT
+        | opcode::LOG0
+        | opcode::LOG1
+        | opcode::LOG2
+        | opcode::LOG3
+        | opcode::LOG4 => range(0, 1),
+        opcode::CALLDATACOPY | opcode::CODECOPY | opcode::RETURNDATACOPY | opcode::DATACOPY => {
+            range(0, 2)
+        }
+        opcode::EXTCODECOPY => range(1, 3),
+        opcode::MCOPY => union(range(0, 2), range(1, 2)),
+        opcode::CREATE | opcode::CREATE2 => range(1, 2),
+        opcode::CALL | opcode::CALLCODE => union(range(3, 4), range(5, 6)),
+        opcode::DELEGATECALL | opcode::STATICCALL => union(range(2, 3), range(4, 5)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(args: &[u64]) -> Vec<U256> {
+        // Arguments are given top first.
+        args.iter().rev().map(|arg| U256::from(*arg)).collect()
+    }
+
+    #[test]
+    fn referenced_regions() {
+        assert_eq!(
+            referenced_region(opcode::MSTORE, &stack(&[64, 1])),
+            Some(64..96)
+        );
+        assert_eq!(referenced_region(opcode::RETURN, &stack(&[10, 0])), None);
+        assert_eq!(
+            referenced_region(opcode::MCOPY, &stack(&[100, 10, 5])),
+            Some(10..105)
+        );
+        // Arguments at 0..4, return data at 64..96.
+        assert_eq!(
+            referenced_region(opcode::STATICCALL, &stack(&[1000, 1, 0, 4, 64, 32])),
+            Some(0..96)
+        );
+        assert_eq!(referenced_region(opcode::ADD, &stack(&[1, 2])), None);
+    }
+
+    #[test]
+    fn shared_and_bounded() {
+        let memory = [1u8; 64];
+        let mut capture = MemoryCapture::full().with_max_total_bytes(100);
+
+        let first = capture.capture(opcode::MSTORE, &[], &memory).unwrap();
+        assert_eq!(first.data.len(), 64);
+        // Memory may have changed after MSTORE, it is copied again up to the limit.
+        let second = capture.capture(opcode::ADD, &[], &memory).unwrap();
+        assert_eq!(second.data.len(), 36);
+        assert!(second.truncated);
+        // ADD doesn't write to the memory, the capture is shared.
+        let third = capture.capture(opcode::ADD, &[], &memory).unwrap();
+        assert_eq!(third, second);
+        assert_eq!(third.data.as_ptr(), second.data.as_ptr());
+        assert_eq!(capture.total_bytes(), 100);
+
+        capture.reset();
+        let mut capture = MemoryCapture::referenced().with_max_step_bytes(8);
+        let region = capture
+            .capture(opcode::MLOAD, &stack(&[16]), &memory)
+            .unwrap();
+        assert_eq!(region.offset, 16);
+        assert_eq!(region.data.len(), 8);
+        assert!(region.truncated);
+        assert_eq!(capture.capture(opcode::ADD, &[], &memory), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Opcode interest set used to skip `step`/`step_end` calls for uninteresting opcodes.
+use revm::bytecode::opcode::OpCode;
+
+/// Set of opcodes an [`Inspector`][crate::Inspector] wants `step` and `step_end` to be called for.
+///
+/// Internally a 256-bit bitmap indexed by opcode byte, so lookups in the
+/// interpreter loop are a shift and a mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpcodeFilter {
+    bits: [u64; 4],
+}
+
+impl Default for OpcodeFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl OpcodeFilter {
+    /// Filter that matches every opcode. This is the default.
+    pub const fn all() -> Self {
+        Self {
+            bits: [u64::MAX; 4],
+        }
+    }
+
+    /// Filter that matches no opcode.
+    pub const fn none() -> Self {
+        Self { bits: [0; 4] }
+    }
+
+    /// Creates a filter matching only the given opcodes.
+    pub fn from_opcodes(opcodes: impl IntoIterator<Item = u8>) -> Self {
+        let mut filter = Self::none();
+        for opcode in opcodes {
+            filter.insert(opcode);
+        }
+        filter
+    }
+
+    /// Returns a filter with `opcode` added.
+    pub const fn with(mut self, opcode: u8) -> Self {
+        self.bits[(opcode >> 6) as usize] |= 1 << (opcode & 63);
+        self
+    }
+
+    /// Returns a filter with `opcode` removed.
+    pub const fn without(mut self, opcode: u8) -> Self {
+        self.bits[(opcode >> 6) as usize] &= !(1 << (opcode & 63));
+        self
+    }
+
+    /// Adds `opcode` to the filter.
+    #[inline]
+    pub fn insert(&mut self, opcode: u8) {
+        *self = self.with(opcode);
+    }
+
+    /// Removes `opcode` from the filter.
+    #[inline]
+    pub fn remove(&mut self, opcode: u8) {
+        *self = self.without(opcode);
+    }
+
+    /// Returns `true` if `opcode` is part of the filter.
+    #[inline]
+    pub const fn contains(&self, opcode: u8) -> bool {
+        self.bits[(opcode >> 6) as usize] & (1 << (opcode & 63)) != 0
+    }
+
+    /// Returns `true` if every opcode is part of the filter.
+    #[inline]
+    pub const fn is_all(&self) -> bool {
+        self.bits[0] == u64::MAX
+            && self.bits[1] == u64::MAX
+            && self.bits[2] == u64::MAX
+            && self.bits[3] == u64::MAX
+    }
+
+    /// Returns `true` if no opcode is part of the filter.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.bits[0] == 0 && self.bits[1] == 0 && self.bits[2] == 0 && self.bits[3] == 0
+    }
+
+    /// Returns the union of two filters.
+    pub const fn union(mut self, other: Self) -> Self {
+        self.bits[0] |= other.bits[0];
+        self.bits[1] |= other.bits[1];
+        self.bits[2] |= other.bits[2];
+        self.bits[3] |= other.bits[3];
+        self
+    }
+
+    /// Iterates over all opcodes that are part of the filter.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|op| self.contains(*op))
+    }
+
+    /// Iterates over all known [`OpCode`]s that are part of the filter.
+    pub fn opcodes(&self) -> impl Iterator<Item = OpCode> + '_ {
+        self.iter().filter_map(OpCode::new)
+    }
+}
+
+impl FromIterator<u8> for OpcodeFilter {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        Self::from_opcodes(iter)
+    }
+}
+
+impl From<OpCode> for OpcodeFilter {
+    fn from(opcode: OpCode) -> Self {
+        Self::none().with(opcode.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exec::InspectEvm, Inspector};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        interpreter::{interpreter::EthInterpreter, interpreter_types::Jumps, Interpreter},
+        primitives::TxKind,
+        Context,
+    };
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct SstoreTracer {
+        steps: Vec<u8>,
+        step_ends: usize,
+    }
+
+    impl<CTX> Inspector<CTX, EthInterpreter> for SstoreTracer {
+        fn opcode_filter(&self) -> OpcodeFilter {
+            OpcodeFilter::none().with(opcode::SSTORE)
+        }
+
+        fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+            self.steps.push(interp.bytecode.opcode());
+        }
+
+        fn step_end(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+            self.step_ends += 1;
+        }
+    }
+
+    #[test]
+    fn insert_remove_contains() {
+        let mut filter = OpcodeFilter::none();
+        assert!(filter.is_empty());
+        filter.insert(opcode::CALL);
+        filter.insert(opcode::SSTORE);
+        filter.insert(0xFF);
+        filter.insert(0x00);
+        assert!(filter.contains(opcode::CALL));
+        assert!(filter.contains(opcode::SSTORE));
+        assert!(filter.contains(0xFF));
+        assert!(filter.contains(0x00));
+        assert!(!filter.contains(opcode::SLOAD));
+        assert_eq!(filter.iter().count(), 4);
+
+        filter.remove(opcode::CALL);
+        assert!(!filter.contains(opcode::CALL));
+
+        let all = OpcodeFilter::all();
+        assert!(all.is_all());
+        assert!(all.without(opcode::ADD).iter().all(|op| op != opcode::ADD));
+        assert_eq!(OpcodeFilter::none().union(filter), filter);
+    }
+
+    #[test]
+    fn step_hooks_only_for_filtered_opcodes() {
+        let bytecode = Bytecode::new_legacy(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x01,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x02,
+                opcode::PUSH1,
+                0x02,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+
+        let mut tracer = SstoreTracer::default();
+        ctx.inspect_previous(&mut tracer).unwrap();
+
+        assert_eq!(tracer.steps, [opcode::SSTORE, opcode::SSTORE]);
+        assert_eq!(tracer.step_ends, 2);
+    }
+}
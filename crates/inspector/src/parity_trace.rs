@@ -0,0 +1,919 @@
+//! ParityTracer. Builds parity/OpenEthereum `trace`, `vmTrace` and `stateDiff` structures.
+use crate::{referenced_region, Inspector, TruncationConfig};
+use core::ops::Range;
+use revm::{
+    bytecode::opcode::{self, OpCode},
+    database_interface::Database,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{Jumps, LoopControl, MemoryTrait},
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+        EOFCreateInputs, InstructionResult, Interpreter, InterpreterResult,
+    },
+    primitives::{alloy_primitives::U64, Address, Bytes, B256, U256},
+    state::EvmState,
+};
+use std::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+
+/// Parity `trace_replayTransaction` result.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TraceResults {
+    /// Output of the transaction.
+    pub output: Bytes,
+    /// Call traces, present if requested.
+    pub trace: Vec<TransactionTrace>,
+    /// Virtual machine trace, present if requested.
+    pub vm_trace: Option<VmTrace>,
+    /// State changes, present if requested.
+    pub state_diff: Option<StateDiff>,
+}
+
+/// Single call, create or selfdestruct of the flat parity trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TransactionTrace {
+    /// Action of the trace, serialized as `type` and `action` fields.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub action: Action,
+    /// Error of a failed trace.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Result of a successful call or create.
+    pub result: Option<TraceOutput>,
+    /// Number of direct children.
+    pub subtraces: usize,
+    /// Indices of the trace and of its parents in the call tree, empty for the first frame.
+    pub trace_address: Vec<usize>,
+}
+
+/// Action of a [`TransactionTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "action", rename_all = "lowercase")
+)]
+pub enum Action {
+    Call(CallAction),
+    Create(CreateAction),
+    #[cfg_attr(feature = "serde", serde(rename = "suicide"))]
+    Selfdestruct(SelfdestructAction),
+}
+
+/// Call type of a [`CallAction`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CallType {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+}
+
+impl From<CallScheme> for CallType {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call | CallScheme::ExtCall => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall | CallScheme::ExtDelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall | CallScheme::ExtStaticCall => Self::StaticCall,
+        }
+    }
+}
+
+/// Call action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallAction {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas: U64,
+    pub input: Bytes,
+    pub call_type: CallType,
+    /// Length of the original `input`, present if it was truncated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub input_len: Option<usize>,
+}
+
+/// Creation method of a [`CreateAction`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CreationMethod {
+    Create,
+    Create2,
+    EofCreate,
+}
+
+/// Create action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateAction {
+    pub from: Address,
+    pub value: U256,
+    pub gas: U64,
+    pub init: Bytes,
+    pub creation_method: CreationMethod,
+    /// Length of the original `init`, present if it was truncated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub init_len: Option<usize>,
+}
+
+/// Selfdestruct action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SelfdestructAction {
+    pub address: Address,
+    pub refund_address: Address,
+    pub balance: U256,
+}
+
+/// Result of a successful call or create.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum TraceOutput {
+    Create(CreateOutput),
+    Call(CallOutput),
+}
+
+/// Result of a successful call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallOutput {
+    pub gas_used: U64,
+    pub output: Bytes,
+    /// Length of the original `output`, present if it was truncated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output_len: Option<usize>,
+}
+
+/// Result of a successful create.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateOutput {
+    pub gas_used: U64,
+    pub code: Bytes,
+    pub address: Address,
+    /// Length of the original `code`, present if it was truncated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code_len: Option<usize>,
+}
+
+/// Virtual machine trace of a single frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmTrace {
+    /// Executed bytecode, empty for frames without code.
+    pub code: Bytes,
+    /// Executed instructions.
+    pub ops: Vec<VmInstruction>,
+}
+
+/// Executed instruction of a [`VmTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmInstruction {
+    pub pc: usize,
+    /// Gas cost of the instruction, excluding gas spent by a started frame.
+    pub cost: u64,
+    /// Effects of the instruction, [`None`] if it failed.
+    pub ex: Option<VmExecutedOperation>,
+    /// Trace of the frame started by the instruction.
+    pub sub: Option<VmTrace>,
+    /// Name of the instruction.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub op: Option<String>,
+}
+
+/// Effects of an executed [`VmInstruction`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmExecutedOperation {
+    /// Gas remaining after the instruction.
+    pub used: u64,
+    /// Values pushed to the stack, bottom first.
+    pub push: Vec<U256>,
+    /// Memory written by the instruction.
+    pub mem: Option<MemoryDelta>,
+    /// Storage written by the instruction.
+    pub store: Option<StorageDelta>,
+}
+
+/// Memory write of a [`VmExecutedOperation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryDelta {
+    pub off: usize,
+    pub data: Bytes,
+}
+
+/// Storage write of a [`VmExecutedOperation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageDelta {
+    pub key: U256,
+    pub val: U256,
+}
+
+/// State changes of a transaction, see [`state_diff`].
+pub type StateDiff = BTreeMap<Address, AccountDiff>;
+
+/// Changes of a single account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+    pub balance: Delta<U256>,
+    pub nonce: Delta<U64>,
+    pub code: Delta<Bytes>,
+    pub storage: BTreeMap<B256, Delta<B256>>,
+}
+
+/// Change of a value, serialized as `"="`, `{"+": v}`, `{"-": v}` or `{"*": {"from", "to"}}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Delta<T> {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "="))]
+    Unchanged,
+    #[cfg_attr(feature = "serde", serde(rename = "+"))]
+    Added(T),
+    #[cfg_attr(feature = "serde", serde(rename = "-"))]
+    Removed(T),
+    #[cfg_attr(feature = "serde", serde(rename = "*"))]
+    Changed(ChangedType<T>),
+}
+
+/// Previous and new value of [`Delta::Changed`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangedType<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq> Delta<T> {
+    /// Creates a delta between two optional values.
+    pub fn new(from: Option<T>, to: Option<T>) -> Self {
+        match (from, to) {
+            (None, None) => Self::Unchanged,
+            (None, Some(to)) => Self::Added(to),
+            (Some(from), None) => Self::Removed(from),
+            (Some(from), Some(to)) if from == to => Self::Unchanged,
+            (Some(from), Some(to)) => Self::Changed(ChangedType { from, to }),
+        }
+    }
+
+    /// Returns `true` if the value did not change.
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Self::Unchanged)
+    }
+}
+
+/// Computes the parity state diff of the `state` returned by a transaction, reading the
+/// pre-transaction values from `db`.
+///
+/// `db` must not have the changes committed yet, [`DatabaseRef`] implementations can be passed
+/// wrapped in [`WrapDatabaseRef`]. Destroyed accounts are reported as removed.
+///
+/// [`DatabaseRef`]: revm::database_interface::DatabaseRef
+/// [`WrapDatabaseRef`]: revm::database_interface::WrapDatabaseRef
+pub fn state_diff<DB: Database>(state: &EvmState, db: &mut DB) -> Result<StateDiff, DB::Error> {
+    let mut diff = StateDiff::new();
+    for (address, account) in state {
+        if !account.is_touched() {
+            continue;
+        }
+        let original = db.basic(*address)?.filter(|info| !info.is_empty());
+        let present =
+            (!account.is_selfdestructed() && !account.is_empty()).then_some(&account.info);
+        if original.is_none() && present.is_none() {
+            continue;
+        }
+
+        let original_code = match &original {
+            Some(info) if present.is_none_or(|present| present.code_hash != info.code_hash) => {
+                Some(match &info.code {
+                    Some(code) => code.original_bytes(),
+                    None => db.code_by_hash(info.code_hash)?.original_bytes(),
+                })
+            }
+            Some(_) => None,
+            None => None,
+        };
+        let present_code = present.map(|info| {
+            info.code
+                .as_ref()
+                .map(|code| code.original_bytes())
+                .unwrap_or_default()
+        });
+
+        let mut storage = BTreeMap::new();
+        for (key, slot) in &account.storage {
+            let from = (original.is_some() && !slot.original_value.is_zero())
+                .then(|| B256::from(slot.original_value));
+            let to = (present.is_some() && !slot.present_value.is_zero())
+                .then(|| B256::from(slot.present_value));
+            let delta = Delta::new(from, to);
+            if !delta.is_unchanged() {
+                storage.insert(B256::from(*key), delta);
+            }
+        }
+
+        let account_diff = AccountDiff {
+            balance: Delta::new(
+                original.as_ref().map(|info| info.balance),
+                present.map(|info| info.balance),
+            ),
+            nonce: Delta::new(
+                original.as_ref().map(|info| U64::from(info.nonce)),
+                present.map(|info| U64::from(info.nonce)),
+            ),
+            code: match (&original, present) {
+                // Code of an existing account is compared by hash.
+                (Some(_), Some(_)) if original_code.is_none() => Delta::Unchanged,
+                _ => Delta::new(original_code, present_code),
+            },
+            storage,
+        };
+        if account_diff.balance.is_unchanged()
+            && account_diff.nonce.is_unchanged()
+            && account_diff.code.is_unchanged()
+            && account_diff.storage.is_empty()
+        {
+            continue;
+        }
+        diff.insert(*address, account_diff);
+    }
+    Ok(diff)
+}
+
+/// Instruction that is being executed, recorded in `step` and completed in `step_end`.
+#[derive(Clone, Debug)]
+struct PendingInstruction {
+    pc: usize,
+    opcode: u8,
+    gas_remaining: u64,
+    store: Option<StorageDelta>,
+    mem: Option<Range<usize>>,
+}
+
+/// [`Inspector`] that builds the parity call trace and, optionally, the vm trace of a transaction.
+///
+/// The state diff is computed after execution with [`state_diff`].
+#[derive(Clone, Debug, Default)]
+pub struct ParityTracer {
+    traces: Vec<TransactionTrace>,
+    /// Indices of the traces of the frames that have not ended yet.
+    trace_stack: Vec<usize>,
+    vm_trace_enabled: bool,
+    /// Vm traces of the frames that have not ended yet.
+    vm_stack: Vec<VmTrace>,
+    vm_trace: Option<VmTrace>,
+    pending: Option<PendingInstruction>,
+    truncation: TruncationConfig,
+}
+
+impl ParityTracer {
+    /// Creates a tracer that records call traces only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also record the vm trace.
+    pub fn with_vm_trace(mut self) -> Self {
+        self.vm_trace_enabled = true;
+        self
+    }
+
+    /// Limits the captured call inputs and outputs.
+    pub fn with_truncation(mut self, truncation: TruncationConfig) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Returns the recorded call traces in the order they were started.
+    pub fn traces(&self) -> &[TransactionTrace] {
+        &self.traces
+    }
+
+    /// Returns the recorded vm trace, available once the transaction has ended.
+    pub fn vm_trace(&self) -> Option<&VmTrace> {
+        self.vm_trace.as_ref()
+    }
+
+    /// Consumes the tracer and returns the results of the traced transaction.
+    pub fn into_trace_results(self, output: Bytes, state_diff: Option<StateDiff>) -> TraceResults {
+        TraceResults {
+            output,
+            trace: self.traces,
+            vm_trace: self.vm_trace,
+            state_diff,
+        }
+    }
+
+    /// Clears recorded traces, making the tracer ready to be used again.
+    pub fn clear(&mut self) {
+        self.traces.clear();
+        self.trace_stack.clear();
+        self.vm_stack.clear();
+        self.vm_trace = None;
+        self.pending = None;
+    }
+
+    /// Adds a trace as a child of the current frame, returns its index.
+    fn push_trace(&mut self, action: Action) -> usize {
+        let trace_address = match self.trace_stack.last() {
+            Some(&parent) => {
+                let parent = &mut self.traces[parent];
+                let mut trace_address = parent.trace_address.clone();
+                trace_address.push(parent.subtraces);
+                parent.subtraces += 1;
+                trace_address
+            }
+            None => Vec::new(),
+        };
+        self.traces.push(TransactionTrace {
+            action,
+            error: None,
+            result: None,
+            subtraces: 0,
+            trace_address,
+        });
+        self.traces.len() - 1
+    }
+
+    fn frame_start(&mut self, action: Action) {
+        let index = self.push_trace(action);
+        self.trace_stack.push(index);
+        if self.vm_trace_enabled {
+            self.vm_stack.push(VmTrace::default());
+        }
+    }
+
+    fn frame_end(&mut self, result: &InterpreterResult, output: impl FnOnce(U64) -> TraceOutput) {
+        let Some(index) = self.trace_stack.pop() else {
+            return;
+        };
+        let trace = &mut self.traces[index];
+        if result.is_ok() {
+            trace.result = Some(output(U64::from(result.gas.spent())));
+        } else {
+            trace.error = Some(error_message(result.result));
+        }
+
+        let Some(vm_trace) = self.vm_stack.pop() else {
+            return;
+        };
+        match self
+            .vm_stack
+            .last_mut()
+            .and_then(|parent| parent.ops.last_mut())
+        {
+            Some(instruction) => instruction.sub = Some(vm_trace),
+            None => self.vm_trace = Some(vm_trace),
+        }
+    }
+
+    /// Sets the value pushed by the call or create instruction of the current frame.
+    fn push_frame_result(&mut self, value: U256) {
+        if let Some(ex) = self
+            .vm_stack
+            .last_mut()
+            .and_then(|parent| parent.ops.last_mut())
+            .and_then(|instruction| instruction.ex.as_mut())
+        {
+            ex.push = vec![value];
+        }
+    }
+}
+
+/// Returns the parity error message of a failed frame.
+fn error_message(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "Reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "Out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "Bad instruction",
+        InstructionResult::InvalidJump => "Bad jump destination",
+        InstructionResult::StackUnderflow => "Stack underflow",
+        InstructionResult::StackOverflow => "Out of stack",
+        InstructionResult::CallTooDeep => "Call stack is too deep",
+        InstructionResult::OutOfFunds => "Insufficient balance for transfer",
+        InstructionResult::StateChangeDuringStaticCall => "Mutable Call In Static Context",
+        InstructionResult::PrecompileError => "Built-in failed",
+        _ => return format!("{result:?}"),
+    }
+    .into()
+}
+
+/// Returns the memory region written by `opcode` before a frame is started.
+fn written_region(opcode: u8, stack: &[U256]) -> Option<Range<usize>> {
+    match opcode {
+        opcode::MSTORE
+        | opcode::MSTORE8
+        | opcode::CALLDATACOPY
+        | opcode::CODECOPY
+        | opcode::EXTCODECOPY
+        | opcode::RETURNDATACOPY => referenced_region(opcode, stack),
+        opcode::MCOPY => {
+            let dst = usize::try_from(*stack.last()?).ok()?;
+            let len = usize::try_from(*stack.get(stack.len().checked_sub(3)?)?).ok()?;
+            (len != 0).then(|| dst..dst + len)
+        }
+        _ => None,
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for ParityTracer {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if let Some(vm_trace) = self.vm_stack.last_mut() {
+            vm_trace.code = interp.bytecode.original_bytes();
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if self.vm_stack.is_empty() {
+            return;
+        }
+        let opcode = interp.bytecode.opcode();
+        let stack = interp.stack.data();
+        let store = match stack.as_slice() {
+            [.., val, key] if opcode == opcode::SSTORE => Some(StorageDelta {
+                key: *key,
+                val: *val,
+            }),
+            _ => None,
+        };
+        self.pending = Some(PendingInstruction {
+            pc: interp.bytecode.pc(),
+            opcode,
+            gas_remaining: interp.control.gas().remaining(),
+            store,
+            mem: written_region(opcode, stack),
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let (Some(pending), Some(vm_trace)) = (self.pending.take(), self.vm_stack.last_mut())
+        else {
+            return;
+        };
+        let remaining = interp.control.gas().remaining();
+        let result = interp.control.instruction_result();
+        let op = OpCode::new(pending.opcode);
+
+        let executed = result.is_ok()
+            || matches!(
+                result,
+                InstructionResult::CallOrCreate | InstructionResult::Revert
+            );
+        let ex = executed.then(|| {
+            // Calls and creates push their result when the started frame returns.
+            let starts_frame = matches!(
+                pending.opcode,
+                opcode::CALL
+                    | opcode::CALLCODE
+                    | opcode::DELEGATECALL
+                    | opcode::STATICCALL
+                    | opcode::CREATE
+                    | opcode::CREATE2
+                    | opcode::EXTCALL
+                    | opcode::EXTDELEGATECALL
+                    | opcode::EXTSTATICCALL
+                    | opcode::EOFCREATE
+            );
+            let stack = interp.stack.data();
+            let outputs = op.map_or(0, |op| op.outputs() as usize);
+            let push = if starts_frame || outputs > stack.len() {
+                Vec::new()
+            } else {
+                stack[stack.len() - outputs..].to_vec()
+            };
+            let memory_size = interp.memory.size();
+            let mem = pending.mem.map(|region| {
+                let region = region.start.min(memory_size)..region.end.min(memory_size);
+                MemoryDelta {
+                    off: region.start,
+                    data: Bytes::copy_from_slice(&interp.memory.slice(region)),
+                }
+            });
+            VmExecutedOperation {
+                used: remaining,
+                push,
+                mem,
+                store: pending.store,
+            }
+        });
+
+        vm_trace.ops.push(VmInstruction {
+            pc: pending.pc,
+            cost: pending.gas_remaining.saturating_sub(remaining),
+            ex,
+            sub: None,
+            op: op.map(|op| op.as_str().into()),
+        });
+    }
+
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let (input, input_len) = self.truncation.input(&inputs.input);
+        self.frame_start(Action::Call(CallAction {
+            from: inputs.caller,
+            to: inputs.target_address,
+            value: inputs.call_value(),
+            gas: U64::from(inputs.gas_limit),
+            input,
+            input_len,
+            call_type: inputs.scheme.into(),
+        }));
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        let (output, output_len) = self.truncation.output(&outcome.result.output);
+        self.frame_end(&outcome.result, |gas_used| {
+            TraceOutput::Call(CallOutput {
+                gas_used,
+                output,
+                output_len,
+            })
+        });
+        self.push_frame_result(U256::from(outcome.result.is_ok()));
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let (init, init_len) = self.truncation.input(&inputs.init_code);
+        self.frame_start(Action::Create(CreateAction {
+            from: inputs.caller,
+            value: inputs.value,
+            gas: U64::from(inputs.gas_limit),
+            init,
+            init_len,
+            creation_method: match inputs.scheme {
+                CreateScheme::Create => CreationMethod::Create,
+                CreateScheme::Create2 { .. } => CreationMethod::Create2,
+            },
+        }));
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let address = outcome.address.unwrap_or_default();
+        let (code, code_len) = self.truncation.output(&outcome.result.output);
+        self.frame_end(&outcome.result, |gas_used| {
+            TraceOutput::Create(CreateOutput {
+                gas_used,
+                code,
+                code_len,
+                address,
+            })
+        });
+        let pushed = if outcome.result.is_ok() {
+            address.into_word().into()
+        } else {
+            U256::ZERO
+        };
+        self.push_frame_result(pushed);
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frame_start(Action::Create(CreateAction {
+            from: inputs.caller,
+            value: inputs.value,
+            gas: U64::from(inputs.gas_limit),
+            init: Bytes::new(),
+            init_len: None,
+            creation_method: CreationMethod::EofCreate,
+        }));
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let address = outcome.address.unwrap_or_default();
+        let (code, code_len) = self.truncation.output(&outcome.result.output);
+        self.frame_end(&outcome.result, |gas_used| {
+            TraceOutput::Create(CreateOutput {
+                gas_used,
+                code,
+                code_len,
+                address,
+            })
+        });
+        let pushed = if outcome.result.is_ok() {
+            address.into_word().into()
+        } else {
+            U256::ZERO
+        };
+        self.push_frame_result(pushed);
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        self.push_trace(Action::Selfdestruct(SelfdestructAction {
+            address: contract,
+            refund_address: target,
+            balance: value,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        primitives::{address, TxKind},
+        Context,
+    };
+
+    fn trace(code: Vec<u8>) -> (ParityTracer, StateDiff) {
+        let mut db = BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into()));
+        let mut ctx = Context::default()
+            .with_db(db.clone())
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut tracer = ParityTracer::new().with_vm_trace();
+        let result = ctx.inspect_previous(&mut tracer).unwrap();
+        let diff = state_diff(&result.state, &mut db).unwrap();
+        (tracer, diff)
+    }
+
+    #[test]
+    fn call_tree_and_vm_trace() {
+        let identity = address!("0000000000000000000000000000000000000004");
+        let (tracer, diff) = trace(vec![
+            // SSTORE(1, 7)
+            opcode::PUSH1,
+            0x07,
+            opcode::PUSH1,
+            0x01,
+            opcode::SSTORE,
+            // CALL(0xffff, 0x04, 0, 0, 0, 0, 0)
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0x04,
+            opcode::PUSH2,
+            0xff,
+            0xff,
+            opcode::CALL,
+            // MSTORE8(0, 0xaa), REVERT(0, 1)
+            opcode::PUSH1,
+            0xaa,
+            opcode::PUSH1,
+            0,
+            opcode::MSTORE8,
+            opcode::PUSH1,
+            1,
+            opcode::PUSH1,
+            0,
+            opcode::REVERT,
+        ]);
+
+        let traces = tracer.traces();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].subtraces, 1);
+        assert_eq!(traces[0].error.as_deref(), Some("Reverted"));
+        assert_eq!(traces[0].result, None);
+        assert_eq!(traces[1].trace_address, [0]);
+        let Action::Call(call) = &traces[1].action else {
+            panic!("call expected");
+        };
+        assert_eq!((call.from, call.to), (BENCH_TARGET, identity));
+        assert_eq!(call.call_type, CallType::Call);
+        assert!(matches!(traces[1].result, Some(TraceOutput::Call(_))));
+
+        let vm_trace = tracer.vm_trace().unwrap();
+        assert_eq!(vm_trace.ops.len(), 17);
+        let sstore = vm_trace.ops[2].ex.as_ref().unwrap();
+        assert_eq!(
+            sstore.store,
+            Some(StorageDelta {
+                key: U256::from(1),
+                val: U256::from(7),
+            })
+        );
+        let call = &vm_trace.ops[10];
+        assert_eq!(call.op.as_deref(), Some("CALL"));
+        assert_eq!(call.ex.as_ref().unwrap().push, [U256::from(1)]);
+        assert_eq!(call.sub, Some(VmTrace::default()));
+        let mstore8 = vm_trace.ops[13].ex.as_ref().unwrap();
+        assert_eq!(
+            mstore8.mem,
+            Some(MemoryDelta {
+                off: 0,
+                data: Bytes::from_static(&[0xaa]),
+            })
+        );
+        assert_eq!(vm_trace.ops[16].ex.as_ref().unwrap().push, []);
+
+        // Reverted transaction only pays for gas.
+        assert!(!diff.contains_key(&BENCH_TARGET));
+        assert!(matches!(
+            diff[&BENCH_CALLER].nonce,
+            Delta::Changed(ChangedType { from, to }) if from == U64::ZERO && to == U64::from(1)
+        ));
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn parity_json() {
+        let trace = TransactionTrace {
+            action: Action::Call(CallAction {
+                from: BENCH_CALLER,
+                to: BENCH_TARGET,
+                value: U256::ZERO,
+                gas: U64::from(0x10),
+                input: Bytes::new(),
+                call_type: CallType::Call,
+                input_len: None,
+            }),
+            error: None,
+            result: Some(TraceOutput::Call(CallOutput {
+                gas_used: U64::from(1),
+                output: Bytes::new(),
+                output_len: None,
+            })),
+            subtraces: 0,
+            trace_address: vec![],
+        };
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["type"], "call");
+        assert_eq!(json["action"]["callType"], "call");
+        assert_eq!(json["action"]["gas"], "0x10");
+        assert_eq!(json["result"]["gasUsed"], "0x1");
+        assert_eq!(json["traceAddress"], serde_json::json!([]));
+        assert_eq!(
+            serde_json::from_value::<TransactionTrace>(json).unwrap(),
+            trace
+        );
+
+        let delta = Delta::new(Some(U64::ZERO), Some(U64::from(1)));
+        assert_eq!(
+            serde_json::to_value(&delta).unwrap(),
+            serde_json::json!({"*": {"from": "0x0", "to": "0x1"}})
+        );
+        assert_eq!(
+            serde_json::to_value(Delta::<U64>::Unchanged).unwrap(),
+            serde_json::json!("=")
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Recording of executions that can be replayed offline.
+//!
+//! A [`ReplayBundle`] holds the environment of a transaction and every database value its
+//! execution read. Replaying it does not need the database, so it can be shared to reproduce an
+//! execution on another machine, e.g. when a node diverges from the others.
+use database::{Witness, WitnessDB, WitnessError};
+use revm::{
+    context::{BlockEnv, CfgEnv, TxEnv},
+    context_interface::result::{EVMError, HaltReason, InvalidTransaction, ResultAndState},
+    database_interface::{Database, WrapDatabaseRef},
+    Context, ExecuteEvm,
+};
+
+/// Context replaying a [`ReplayBundle`], reading the database from its [`Witness`].
+pub type ReplayContext<'a> =
+    Context<&'a BlockEnv, &'a TxEnv, &'a CfgEnv, WrapDatabaseRef<&'a Witness>>;
+
+/// Environment of an executed transaction and the database values the execution read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayBundle {
+    pub block: BlockEnv,
+    pub tx: TxEnv,
+    pub cfg: CfgEnv,
+    pub witness: Witness,
+}
+
+impl ReplayBundle {
+    /// Executes the transaction on top of `db` without committing, recording the values read.
+    ///
+    /// The bundle is returned even if the execution failed, as replaying it reproduces the
+    /// failure, unless the failure is a database error.
+    #[allow(clippy::type_complexity)]
+    pub fn record<DB: Database>(
+        block: BlockEnv,
+        tx: TxEnv,
+        cfg: CfgEnv,
+        db: DB,
+    ) -> (
+        Self,
+        Result<ResultAndState<HaltReason>, EVMError<DB::Error, InvalidTransaction>>,
+    ) {
+        let mut db = WitnessDB::new(db);
+        let result = Context::builder()
+            .with_block(&block)
+            .with_tx(&tx)
+            .with_cfg(&cfg)
+            .with_db(&mut db)
+            .exec_previous();
+        let bundle = Self {
+            block,
+            tx,
+            cfg,
+            witness: db.take_witness(),
+        };
+        (bundle, result)
+    }
+
+    /// Returns a context that replays the transaction, to execute it with an inspector.
+    pub fn context(&self) -> ReplayContext<'_> {
+        Context::builder()
+            .with_block(&self.block)
+            .with_tx(&self.tx)
+            .with_cfg(&self.cfg)
+            .with_db(WrapDatabaseRef(&self.witness))
+    }
+
+    /// Re-executes the transaction on the recorded values.
+    ///
+    /// Reading a value that was not recorded fails with [`EVMError::Database`], it means that
+    /// the replay took a different path than the recorded execution.
+    pub fn replay(
+        &self,
+    ) -> Result<ResultAndState<HaltReason>, EVMError<WitnessError, InvalidTransaction>> {
+        self.context().exec_previous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{TxKind, U256},
+    };
+
+    fn record() -> ReplayBundle {
+        // Stores the value of slot 1 plus the hash of block 0 at slot 2.
+        let code = [
+            opcode::PUSH1,
+            0x00,
+            opcode::BLOCKHASH,
+            opcode::PUSH1,
+            0x01,
+            opcode::SLOAD,
+            opcode::ADD,
+            opcode::PUSH1,
+            0x02,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let db = BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into()));
+        let tx = TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(BENCH_TARGET),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let block = BlockEnv {
+            number: 1,
+            ..Default::default()
+        };
+        let (bundle, result) = ReplayBundle::record(block, tx, CfgEnv::default(), db);
+        assert_eq!(bundle.replay().unwrap(), result.unwrap());
+        bundle
+    }
+
+    #[test]
+    fn replay_recorded_execution() {
+        let bundle = record();
+        assert!(bundle.witness.block_hashes.contains_key(&0));
+        assert_eq!(
+            bundle.witness.storage[&BENCH_TARGET][&U256::from(1)],
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn replay_divergence() {
+        let mut bundle = record();
+        let recorded = bundle.replay().unwrap();
+        bundle
+            .witness
+            .storage
+            .get_mut(&BENCH_TARGET)
+            .unwrap()
+            .insert(U256::from(1), U256::from(1));
+        let replayed = bundle.replay().unwrap();
+        assert_ne!(replayed.state, recorded.state);
+
+        bundle.witness.block_hashes.clear();
+        assert_eq!(
+            bundle.replay(),
+            Err(EVMError::Database(WitnessError::BlockHash(0)))
+        );
+    }
+}
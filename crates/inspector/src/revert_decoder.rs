@@ -0,0 +1,217 @@
+//! RevertDecoder. Inspector that decodes revert reasons of reverted frames.
+use crate::{Inspector, TruncationConfig};
+use revm::{
+    context_interface::result::RevertReason,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InstructionResult,
+        InterpreterResult, InterpreterTypes,
+    },
+    primitives::{Address, Bytes},
+};
+use std::vec::Vec;
+
+/// Frame that ended with `REVERT`, recorded by [`RevertDecoder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RevertedFrame {
+    /// Address of the account that started the frame.
+    pub caller: Address,
+    /// Called account, or the created account if known.
+    pub target: Option<Address>,
+    /// Call depth of the frame, zero for the first frame of the transaction.
+    pub depth: usize,
+    /// `true` if the frame is a contract creation.
+    pub is_create: bool,
+    /// Raw revert data.
+    pub output: Bytes,
+    /// Length of the original revert data, present if `output` was truncated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output_len: Option<usize>,
+    /// Decoded revert data, [`None`] if the revert data is empty.
+    ///
+    /// Always decoded from the full revert data.
+    pub reason: Option<RevertReason>,
+}
+
+/// [`Inspector`] that records and decodes the revert data of every reverted frame.
+///
+/// Frames are recorded in the order they end, so inner reverts come before the
+/// reverts of frames that bubbled them up.
+#[derive(Clone, Debug, Default)]
+pub struct RevertDecoder {
+    reverts: Vec<RevertedFrame>,
+    depth: usize,
+    truncation: TruncationConfig,
+}
+
+impl RevertDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the captured revert data with [`TruncationConfig::max_return_data`].
+    pub fn with_truncation(mut self, truncation: TruncationConfig) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Returns recorded reverted frames.
+    pub fn reverts(&self) -> &[RevertedFrame] {
+        &self.reverts
+    }
+
+    /// Consumes the decoder and returns recorded reverted frames.
+    pub fn into_reverts(self) -> Vec<RevertedFrame> {
+        self.reverts
+    }
+
+    /// Returns the decoded reason of the innermost revert, usually the origin of a failed transaction.
+    pub fn first_reason(&self) -> Option<&RevertReason> {
+        self.reverts.iter().find_map(|frame| frame.reason.as_ref())
+    }
+
+    /// Clears recorded frames, making the decoder ready to be used again.
+    pub fn clear(&mut self) {
+        self.reverts.clear();
+        self.depth = 0;
+    }
+
+    fn frame_end(
+        &mut self,
+        caller: Address,
+        target: Option<Address>,
+        is_create: bool,
+        result: &InterpreterResult,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        if result.result != InstructionResult::Revert {
+            return;
+        }
+        let (output, output_len) = self.truncation.return_data(&result.output);
+        self.reverts.push(RevertedFrame {
+            caller,
+            target,
+            depth: self.depth,
+            is_create,
+            output,
+            output_len,
+            reason: RevertReason::decode(&result.output),
+        });
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for RevertDecoder {
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(
+            inputs.caller,
+            Some(inputs.target_address),
+            false,
+            &outcome.result,
+        );
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(inputs.caller, outcome.address, true, &outcome.result);
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(inputs.caller, outcome.address, true, &outcome.result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{TxKind, U256},
+        Context,
+    };
+
+    #[test]
+    fn decodes_panic() {
+        // mstore(0, Panic(0x01)) and revert with the 36 bytes of it.
+        let code = [
+            opcode::PUSH4,
+            0x4e,
+            0x48,
+            0x7b,
+            0x71,
+            opcode::PUSH1,
+            0xe0,
+            opcode::SHL,
+            opcode::PUSH1,
+            0x00,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x04,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x24,
+            opcode::PUSH1,
+            0x00,
+            opcode::REVERT,
+        ];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut decoder = RevertDecoder::new()
+            .with_truncation(TruncationConfig::unlimited().with_max_return_data(4));
+        let result = ctx.inspect_previous(&mut decoder).unwrap();
+
+        let reason = RevertReason::Panic(U256::from(1));
+        assert_eq!(result.result.revert_reason(), Some(reason.clone()));
+        assert_eq!(decoder.reverts().len(), 1);
+        let frame = &decoder.reverts()[0];
+        assert_eq!(frame.caller, BENCH_CALLER);
+        assert_eq!(frame.target, Some(BENCH_TARGET));
+        assert_eq!(frame.depth, 0);
+        assert!(!frame.is_create);
+        assert_eq!(frame.output, Bytes::from_static(&[0x4e, 0x48, 0x7b, 0x71]));
+        assert_eq!(frame.output_len, Some(36));
+        assert_eq!(frame.reason, Some(reason.clone()));
+        assert_eq!(decoder.first_reason(), Some(&reason));
+    }
+}
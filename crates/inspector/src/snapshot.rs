@@ -0,0 +1,235 @@
+//! Canonical JSON snapshots of execution results, for snapshot testing.
+//!
+//! Maps of the state are serialized with sorted keys and bundle reverts are sorted by address, so
+//! the same execution always produces the same snapshot. Comparing a snapshot with a stored one
+//! lists the values that changed instead of the whole state.
+use core::fmt;
+use database::BundleState;
+use revm::context_interface::result::{HaltReasonTrait, ResultAndState};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Canonical JSON of a [`ResultAndState`] or a [`BundleState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateSnapshot(Value);
+
+impl StateSnapshot {
+    /// Takes the snapshot of a transaction result and the state it changed.
+    pub fn of_result<HaltReasonT: HaltReasonTrait + Serialize>(
+        result: &ResultAndState<HaltReasonT>,
+    ) -> Result<Self, serde_json::Error> {
+        Self::of(result)
+    }
+
+    /// Takes the snapshot of a bundle, with the reverts of each block sorted by address.
+    pub fn of_bundle(bundle: &BundleState) -> Result<Self, serde_json::Error> {
+        let mut bundle = bundle.clone();
+        bundle.reverts.sort();
+        Self::of(&bundle)
+    }
+
+    /// Takes the snapshot of any serializable value.
+    pub fn of<T: Serialize + ?Sized>(value: &T) -> Result<Self, serde_json::Error> {
+        serde_json::to_value(value).map(Self::from_value)
+    }
+
+    /// Creates a snapshot from JSON, e.g. a stored snapshot.
+    pub fn from_value(value: Value) -> Self {
+        Self(canonicalize(value))
+    }
+
+    /// Parses a stored snapshot.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json).map(Self::from_value)
+    }
+
+    /// Returns the JSON of the snapshot.
+    pub fn as_value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Returns the pretty printed JSON of the snapshot, to store it.
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self.0).expect("value is serializable")
+    }
+
+    /// Returns the values that differ from the `expected` snapshot.
+    pub fn diff(&self, expected: &Self) -> Vec<SnapshotDiff> {
+        let mut diffs = Vec::new();
+        diff_values(String::new(), Some(&expected.0), Some(&self.0), &mut diffs);
+        diffs
+    }
+
+    /// Asserts that the snapshot matches the `expected` one, listing the differences otherwise.
+    #[track_caller]
+    pub fn assert_eq(&self, expected: &Self) {
+        let diffs = self.diff(expected);
+        if !diffs.is_empty() {
+            let diffs: Vec<_> = diffs.iter().map(ToString::to_string).collect();
+            panic!("snapshot mismatch:\n{}", diffs.join("\n"));
+        }
+    }
+}
+
+impl fmt::Display for StateSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_json_pretty())
+    }
+}
+
+/// A value that differs between two snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// JSON pointer of the value, e.g. `/state/0x.../info/balance`.
+    pub path: String,
+    /// Value of the expected snapshot, [`None`] if it is missing.
+    pub expected: Option<Value>,
+    /// Value of the new snapshot, [`None`] if it is missing.
+    pub got: Option<Value>,
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<Value>| {
+            value
+                .as_ref()
+                .map_or_else(|| "missing".to_string(), Value::to_string)
+        };
+        write!(
+            f,
+            "{}: expected {}, got {}",
+            self.path,
+            value(&self.expected),
+            value(&self.got)
+        )
+    }
+}
+
+/// Sorts the keys of the objects, which keep their insertion order if serde_json preserves it.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
+
+fn diff_values(
+    path: String,
+    expected: Option<&Value>,
+    got: Option<&Value>,
+    diffs: &mut Vec<SnapshotDiff>,
+) {
+    match (expected, got) {
+        (Some(Value::Object(expected)), Some(Value::Object(got))) => {
+            let keys: BTreeSet<_> = expected.keys().chain(got.keys()).collect();
+            for key in keys {
+                let key_path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                diff_values(key_path, expected.get(key), got.get(key), diffs);
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(got))) => {
+            for index in 0..expected.len().max(got.len()) {
+                diff_values(
+                    format!("{path}/{index}"),
+                    expected.get(index),
+                    got.get(index),
+                    diffs,
+                );
+            }
+        }
+        (expected, got) if expected != got => diffs.push(SnapshotDiff {
+            path,
+            expected: expected.cloned(),
+            got: got.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::states::reverts::{AccountInfoRevert, AccountRevert, Reverts};
+    use revm::{
+        context_interface::result::{ExecutionResult, HaltReason, Output, SuccessReason},
+        primitives::{address, Bytes, U256},
+        state::{Account, AccountInfo},
+    };
+    use std::vec;
+
+    #[test]
+    fn result_snapshot_diff() {
+        let first = address!("0000000000000000000000000000000000000001");
+        let second = address!("0000000000000000000000000000000000000002");
+        let mut result = ResultAndState::<HaltReason> {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used: 21_000,
+                gas_refunded: 0,
+                effective_gas_price: 0,
+                logs: vec![],
+                output: Output::Call(Bytes::new()),
+            },
+            state: [first, second]
+                .into_iter()
+                .map(|address| (address, Account::default()))
+                .collect(),
+        };
+        let snapshot = StateSnapshot::of_result(&result).unwrap();
+        let stored = StateSnapshot::from_json(&snapshot.to_json_pretty()).unwrap();
+        assert_eq!(stored, snapshot);
+        stored.assert_eq(&snapshot);
+
+        result.state.get_mut(&second).unwrap().info.balance = U256::from(1);
+        let changed = StateSnapshot::of_result(&result).unwrap();
+        assert_eq!(
+            changed.diff(&snapshot),
+            vec![SnapshotDiff {
+                path: format!("/state/{second}/info/balance"),
+                expected: Some(Value::from("0x0")),
+                got: Some(Value::from("0x1")),
+            }]
+        );
+    }
+
+    #[test]
+    fn bundle_snapshot_is_ordered() {
+        let revert = |nonce| AccountRevert {
+            account: AccountInfoRevert::RevertTo(AccountInfo {
+                nonce,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let first = address!("0000000000000000000000000000000000000001");
+        let second = address!("0000000000000000000000000000000000000002");
+        let bundle = |reverts| BundleState {
+            reverts: Reverts::new(vec![reverts]),
+            ..Default::default()
+        };
+
+        let snapshot =
+            StateSnapshot::of_bundle(&bundle(vec![(first, revert(1)), (second, revert(2))]))
+                .unwrap();
+        let reordered =
+            StateSnapshot::of_bundle(&bundle(vec![(second, revert(2)), (first, revert(1))]))
+                .unwrap();
+        assert!(reordered.diff(&snapshot).is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+//! Semantic comparison of post states.
+//!
+//! States produced by different clients, or by different revm databases, represent the same state
+//! differently: a slot set to zero may be kept or dropped, an empty account may be kept or
+//! removed and maps have no order. [`NormalizedState`] drops those differences so two states can
+//! be compared by what they contain.
+use core::fmt;
+use database::BundleState;
+use revm::{
+    primitives::{Address, B256, KECCAK_EMPTY, U256},
+    state::{AccountInfo, EvmState},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Account of a [`NormalizedState`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizedAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    /// [`KECCAK_EMPTY`] if the account has no code, a zero hash is normalized to it.
+    pub code_hash: B256,
+    /// Non-zero storage slots.
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl NormalizedAccount {
+    /// Returns `true` if the account has no balance, nonce, code or storage.
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_zero()
+            && self.nonce == 0
+            && self.code_hash == KECCAK_EMPTY
+            && self.storage.is_empty()
+    }
+}
+
+/// Post state without zero slots and empty accounts, ordered by address and slot.
+///
+/// Removed, selfdestructed and empty accounts are all absent, and a missing slot equals a zero
+/// one, so two states are equal if they hold the same values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizedState {
+    pub accounts: BTreeMap<Address, NormalizedAccount>,
+}
+
+impl NormalizedState {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts an account, [`None`] if it does not exist, with its storage.
+    ///
+    /// Zero slots are skipped and empty accounts are removed.
+    pub fn insert(
+        &mut self,
+        address: Address,
+        info: Option<&AccountInfo>,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) {
+        let Some(info) = info else {
+            self.accounts.remove(&address);
+            return;
+        };
+        let code_hash = if info.code_hash == B256::ZERO {
+            KECCAK_EMPTY
+        } else {
+            info.code_hash
+        };
+        let account = NormalizedAccount {
+            balance: info.balance,
+            nonce: info.nonce,
+            code_hash,
+            storage: storage
+                .into_iter()
+                .filter(|(_, value)| !value.is_zero())
+                .collect(),
+        };
+        if account.is_empty() {
+            self.accounts.remove(&address);
+        } else {
+            self.accounts.insert(address, account);
+        }
+    }
+
+    /// Normalizes the state changed by a transaction.
+    ///
+    /// Accounts that were not touched are skipped, selfdestructed ones are absent.
+    pub fn from_evm_state(state: &EvmState) -> Self {
+        let mut normalized = Self::new();
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+            let info = (!account.is_selfdestructed()).then_some(&account.info);
+            let storage = account
+                .storage
+                .iter()
+                .map(|(slot, value)| (*slot, value.present_value));
+            normalized.insert(*address, info, storage);
+        }
+        normalized
+    }
+
+    /// Normalizes the present state of the accounts of a bundle.
+    pub fn from_bundle(bundle: &BundleState) -> Self {
+        let mut normalized = Self::new();
+        for (address, account) in &bundle.state {
+            let storage = account
+                .storage
+                .iter()
+                .map(|(slot, value)| (*slot, value.present_value));
+            normalized.insert(*address, account.info.as_ref(), storage);
+        }
+        normalized
+    }
+
+    /// Returns the differences with the `expected` state, ordered by address and slot.
+    pub fn diff(&self, expected: &Self) -> Vec<StateDifference> {
+        let mut differences = Vec::new();
+        let addresses: BTreeSet<_> = self
+            .accounts
+            .keys()
+            .chain(expected.accounts.keys())
+            .collect();
+        for &address in addresses {
+            let (got, expected) =
+                match (self.accounts.get(&address), expected.accounts.get(&address)) {
+                    (Some(got), Some(expected)) => (got, expected),
+                    (got, expected) => {
+                        differences.push(StateDifference::Existence {
+                            address,
+                            expected: expected.is_some(),
+                            got: got.is_some(),
+                        });
+                        continue;
+                    }
+                };
+            if got.balance != expected.balance {
+                differences.push(StateDifference::Balance {
+                    address,
+                    expected: expected.balance,
+                    got: got.balance,
+                });
+            }
+            if got.nonce != expected.nonce {
+                differences.push(StateDifference::Nonce {
+                    address,
+                    expected: expected.nonce,
+                    got: got.nonce,
+                });
+            }
+            if got.code_hash != expected.code_hash {
+                differences.push(StateDifference::CodeHash {
+                    address,
+                    expected: expected.code_hash,
+                    got: got.code_hash,
+                });
+            }
+            let slots: BTreeSet<_> = got.storage.keys().chain(expected.storage.keys()).collect();
+            for &slot in slots {
+                let value = |storage: &BTreeMap<U256, U256>| {
+                    storage.get(&slot).copied().unwrap_or_default()
+                };
+                if value(&got.storage) != value(&expected.storage) {
+                    differences.push(StateDifference::Storage {
+                        address,
+                        slot,
+                        expected: value(&expected.storage),
+                        got: value(&got.storage),
+                    });
+                }
+            }
+        }
+        differences
+    }
+}
+
+/// Difference between two [`NormalizedState`]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateDifference {
+    /// The account exists, with a value that is not empty, in only one of the states.
+    Existence {
+        address: Address,
+        expected: bool,
+        got: bool,
+    },
+    Balance {
+        address: Address,
+        expected: U256,
+        got: U256,
+    },
+    Nonce {
+        address: Address,
+        expected: u64,
+        got: u64,
+    },
+    CodeHash {
+        address: Address,
+        expected: B256,
+        got: B256,
+    },
+    Storage {
+        address: Address,
+        slot: U256,
+        expected: U256,
+        got: U256,
+    },
+}
+
+impl StateDifference {
+    /// Address of the account that differs.
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Existence { address, .. }
+            | Self::Balance { address, .. }
+            | Self::Nonce { address, .. }
+            | Self::CodeHash { address, .. }
+            | Self::Storage { address, .. } => *address,
+        }
+    }
+}
+
+impl fmt::Display for StateDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exists = |exists: &bool| if *exists { "existing" } else { "missing" };
+        match self {
+            Self::Existence {
+                address,
+                expected,
+                got,
+            } => write!(
+                f,
+                "account {address}: expected {}, got {}",
+                exists(expected),
+                exists(got)
+            ),
+            Self::Balance {
+                address,
+                expected,
+                got,
+            } => write!(f, "balance of {address}: expected {expected}, got {got}"),
+            Self::Nonce {
+                address,
+                expected,
+                got,
+            } => write!(f, "nonce of {address}: expected {expected}, got {got}"),
+            Self::CodeHash {
+                address,
+                expected,
+                got,
+            } => write!(f, "code hash of {address}: expected {expected}, got {got}"),
+            Self::Storage {
+                address,
+                slot,
+                expected,
+                got,
+            } => write!(
+                f,
+                "storage slot {slot} of {address}: expected {expected}, got {got}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        primitives::{address, HashMap},
+        state::{Account, EvmStorageSlot},
+    };
+    use std::vec;
+
+    #[test]
+    fn normalize_representation() {
+        let contract = address!("0000000000000000000000000000000000001000");
+        let empty = address!("0000000000000000000000000000000000001001");
+        let info = AccountInfo {
+            nonce: 1,
+            ..Default::default()
+        };
+
+        let mut account = Account::from(info.clone());
+        account.mark_touch();
+        account.storage = HashMap::from_iter([
+            (
+                U256::from(1),
+                EvmStorageSlot::new_changed(U256::ZERO, U256::from(2)),
+            ),
+            (
+                U256::from(2),
+                EvmStorageSlot::new_changed(U256::from(3), U256::ZERO),
+            ),
+        ]);
+        let mut empty_account = Account::default();
+        empty_account.mark_touch();
+        let state: EvmState = [(contract, account), (empty, empty_account)]
+            .into_iter()
+            .collect();
+
+        let mut expected = NormalizedState::new();
+        expected.insert(
+            contract,
+            Some(&AccountInfo {
+                code_hash: B256::ZERO,
+                ..info.clone()
+            }),
+            [(U256::from(1), U256::from(2))],
+        );
+        let normalized = NormalizedState::from_evm_state(&state);
+        assert_eq!(normalized, expected);
+        assert!(normalized.diff(&expected).is_empty());
+
+        expected.insert(empty, Some(&AccountInfo::from_balance(U256::from(1))), []);
+        expected.insert(contract, Some(&info), [(U256::from(2), U256::from(4))]);
+        assert_eq!(
+            normalized.diff(&expected),
+            vec![
+                StateDifference::Storage {
+                    address: contract,
+                    slot: U256::from(1),
+                    expected: U256::ZERO,
+                    got: U256::from(2),
+                },
+                StateDifference::Storage {
+                    address: contract,
+                    slot: U256::from(2),
+                    expected: U256::from(4),
+                    got: U256::ZERO,
+                },
+                StateDifference::Existence {
+                    address: empty,
+                    expected: true,
+                    got: false,
+                },
+            ]
+        );
+    }
+}
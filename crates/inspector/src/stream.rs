@@ -0,0 +1,247 @@
+//! TraceStream. Bounded channel whose receiving end is an async [`Stream`] of trace events.
+use crate::{channel::SendStatus, inspectors::TraceEvent};
+use futures_core::Stream;
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Debug)]
+struct State {
+    queue: VecDeque<TraceEvent>,
+    capacity: usize,
+    waker: Option<Waker>,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when an event is taken from the queue or the receiver is dropped.
+    not_full: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The lock is never held while running user code, a poisoned state is still consistent.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Creates a channel holding up to `capacity` events, whose receiving end is a [`TraceStream`].
+///
+/// The capacity is at least one.
+pub(crate) fn trace_stream(capacity: usize) -> (StreamSender, TraceStream) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            capacity: capacity.max(1),
+            waker: None,
+            sender_dropped: false,
+            receiver_dropped: false,
+        }),
+        not_full: Condvar::new(),
+    });
+    (
+        StreamSender {
+            shared: shared.clone(),
+        },
+        TraceStream { shared },
+    )
+}
+
+/// Sending end of a [`TraceStream`], used from the thread executing the EVM.
+#[derive(Debug)]
+pub(crate) struct StreamSender {
+    shared: Arc<Shared>,
+}
+
+impl StreamSender {
+    /// Sends the event, blocking the current thread while the channel is full.
+    pub(crate) fn send(&self, event: TraceEvent) -> SendStatus {
+        let mut state = self.shared.lock();
+        while state.queue.len() >= state.capacity && !state.receiver_dropped {
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        Self::push(state, event)
+    }
+
+    /// Sends the event if the channel is not full.
+    pub(crate) fn try_send(&self, event: TraceEvent) -> SendStatus {
+        let state = self.shared.lock();
+        if state.queue.len() >= state.capacity && !state.receiver_dropped {
+            return SendStatus::Full;
+        }
+        Self::push(state, event)
+    }
+
+    fn push(mut state: MutexGuard<'_, State>, event: TraceEvent) -> SendStatus {
+        if state.receiver_dropped {
+            return SendStatus::Disconnected;
+        }
+        state.queue.push_back(event);
+        let waker = state.waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        SendStatus::Sent
+    }
+}
+
+impl Drop for StreamSender {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.sender_dropped = true;
+        let waker = state.waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// [`Stream`] of the events sent by a [`ChannelInspector`](crate::inspectors::ChannelInspector)
+/// created with [`ChannelInspector::stream`](crate::inspectors::ChannelInspector::stream).
+///
+/// The stream ends once the inspector is dropped and all events were yielded. Polling never
+/// blocks, so it can be consumed from an async runtime while the EVM runs on a blocking thread.
+#[derive(Debug)]
+pub struct TraceStream {
+    shared: Arc<Shared>,
+}
+
+impl TraceStream {
+    /// Returns the number of events waiting to be yielded.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    /// Returns `true` if no event is waiting to be yielded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes the next event without waiting, [`None`] if none is waiting.
+    pub fn try_next(&mut self) -> Option<TraceEvent> {
+        let event = self.shared.lock().queue.pop_front();
+        if event.is_some() {
+            self.shared.not_full.notify_one();
+        }
+        event
+    }
+}
+
+impl Stream for TraceStream {
+    type Item = TraceEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut state = self.shared.lock();
+        if let Some(event) = state.queue.pop_front() {
+            drop(state);
+            self.shared.not_full.notify_one();
+            return Poll::Ready(Some(event));
+        }
+        if state.sender_dropped {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), None)
+    }
+}
+
+impl Drop for TraceStream {
+    fn drop(&mut self) {
+        self.shared.lock().receiver_dropped = true;
+        self.shared.not_full.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exec::InspectEvm, inspectors::ChannelInspector};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::TxKind,
+    };
+    use std::{
+        task::Wake,
+        thread::{self, Thread},
+        vec::Vec,
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Collects the stream on the current thread, parking it while no event is ready.
+    fn collect(mut stream: TraceStream) -> Vec<TraceEvent> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut events = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(event)) => events.push(event),
+                Poll::Ready(None) => return events,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn streams_events() {
+        let (inspector, stream) = ChannelInspector::stream(1);
+        let evm = thread::spawn(move || {
+            let code = [opcode::PUSH1, 0x01, opcode::STOP];
+            let mut ctx = revm::Context::default()
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                    code.to_vec().into(),
+                )))
+                .modify_tx_chained(|tx| {
+                    tx.caller = BENCH_CALLER;
+                    tx.kind = TxKind::Call(BENCH_TARGET);
+                    tx.gas_limit = 100_000;
+                });
+            ctx.inspect_previous(inspector.with_steps()).unwrap();
+        });
+        let events = collect(stream);
+        evm.join().unwrap();
+
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0], TraceEvent::TxStart);
+        assert!(matches!(
+            events[5],
+            TraceEvent::TxEnd {
+                success: Some(true),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn dropped_stream_disconnects() {
+        let (sender, stream) = trace_stream(1);
+        assert_eq!(sender.try_send(TraceEvent::TxStart), SendStatus::Sent);
+        assert_eq!(sender.try_send(TraceEvent::TxStart), SendStatus::Full);
+        drop(stream);
+        // Does not block on the full channel.
+        assert_eq!(sender.send(TraceEvent::TxStart), SendStatus::Disconnected);
+    }
+}
@@ -0,0 +1,270 @@
+//! TimeTravelRecorder. Records journal checkpoints during execution so state can be rewound.
+use crate::{Inspector, TxOutcome};
+use revm::{
+    bytecode::opcode,
+    context_interface::{journaled_state::JournalCheckpoint, Journal, JournalGetter},
+    interpreter::{
+        interpreter_types::{Jumps, LoopControl, MemoryTrait, StackTrait},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+        InterpreterTypes,
+    },
+};
+use std::vec::Vec;
+
+/// Which instructions [`TimeTravelRecorder`] records.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RecordGranularity {
+    /// Every executed instruction.
+    #[default]
+    Step,
+    /// Only instructions starting a new frame, i.e. calls and creates.
+    Call,
+}
+
+/// Point of the execution recorded before an instruction by [`TimeTravelRecorder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Journal checkpoint reverting all state changes made from this point on.
+    pub checkpoint: JournalCheckpoint,
+    /// Journal depth, one for the first frame of the transaction.
+    pub depth: usize,
+    pub pc: usize,
+    pub opcode: u8,
+    /// Gas remaining before the instruction.
+    pub gas_remaining: u64,
+    pub stack_len: usize,
+    pub memory_size: usize,
+    /// `false` once the state of this point can no longer be restored, e.g. because the
+    /// frame reverted, an earlier point was restored or the transaction ended.
+    pub rewindable: bool,
+}
+
+/// [`Inspector`] that records a journal checkpoint and a minimal view of the interpreter
+/// before each instruction, so a debugger can rewind the state to any recorded point.
+///
+/// Checkpoints are cheap, they split the journal without copying state. Rewinding is done with
+/// [`TimeTravelRecorder::rewind`] from a hook while the transaction is still executing; it
+/// restores accounts, storage, transient storage and logs, but not the interpreter, whose
+/// recorded state is only available for inspection.
+#[derive(Clone, Debug, Default)]
+pub struct TimeTravelRecorder {
+    granularity: RecordGranularity,
+    snapshots: Vec<Snapshot>,
+    /// Index of the first snapshot of each open frame.
+    frames: Vec<usize>,
+}
+
+impl TimeTravelRecorder {
+    /// Creates a recorder with the given granularity.
+    pub fn new(granularity: RecordGranularity) -> Self {
+        Self {
+            granularity,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the recorded snapshots in execution order.
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+
+    /// Clears recorded snapshots, making the recorder ready to be used again.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.frames.clear();
+    }
+
+    /// Reverts the journal to the snapshot at `index`, undoing all state changes made after it.
+    ///
+    /// Returns `false` and leaves the journal untouched if the snapshot is not rewindable.
+    /// Snapshots after `index` stop being rewindable.
+    pub fn rewind<J: Journal>(&mut self, journal: &mut J, index: usize) -> bool {
+        let Some(snapshot) = self.snapshots.get(index).filter(|s| s.rewindable) else {
+            return false;
+        };
+        // Reverting a checkpoint closes it, open a new one to keep the depth unchanged.
+        journal.checkpoint();
+        journal.checkpoint_revert(snapshot.checkpoint);
+        self.invalidate_from(index + 1);
+        true
+    }
+
+    fn frame_start(&mut self) {
+        self.frames.push(self.snapshots.len());
+    }
+
+    fn frame_end(&mut self, success: bool) {
+        // Changes of a failed frame are reverted by the frame itself.
+        if let Some(start) = self.frames.pop().filter(|_| !success) {
+            self.invalidate_from(start);
+        }
+    }
+
+    fn invalidate_from(&mut self, index: usize) {
+        for snapshot in self.snapshots.iter_mut().skip(index) {
+            snapshot.rewindable = false;
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for TimeTravelRecorder
+where
+    CTX: JournalGetter,
+    INTR: InterpreterTypes,
+{
+    fn tx_start(&mut self, _context: &mut CTX) {
+        self.frames.clear();
+    }
+
+    fn tx_end(&mut self, _context: &mut CTX, _outcome: Option<&TxOutcome<'_>>) {
+        self.invalidate_from(0);
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX) {
+        let opcode = interp.bytecode.opcode();
+        if self.granularity == RecordGranularity::Call
+            && !matches!(
+                opcode,
+                opcode::CALL
+                    | opcode::CALLCODE
+                    | opcode::DELEGATECALL
+                    | opcode::STATICCALL
+                    | opcode::CREATE
+                    | opcode::CREATE2
+                    | opcode::EXTCALL
+                    | opcode::EXTDELEGATECALL
+                    | opcode::EXTSTATICCALL
+                    | opcode::EOFCREATE
+            )
+        {
+            return;
+        }
+
+        let journal = context.journal();
+        // Splits the journal at this point without opening a new depth.
+        let checkpoint = journal.checkpoint();
+        journal.checkpoint_commit();
+        self.snapshots.push(Snapshot {
+            checkpoint,
+            depth: journal.depth(),
+            pc: interp.bytecode.pc(),
+            opcode,
+            gas_remaining: interp.control.gas().remaining(),
+            stack_len: interp.stack.len(),
+            memory_size: interp.memory.size(),
+            rewindable: true,
+        });
+    }
+
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(outcome.result.is_ok());
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(outcome.result.is_ok());
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(outcome.result.is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::InspectEvm;
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        interpreter::interpreter::EthInterpreter,
+        primitives::{TxKind, U256},
+        Context,
+    };
+
+    /// Rewinds to the snapshot at `index` when `STOP` is reached.
+    struct Debugger {
+        recorder: TimeTravelRecorder,
+        index: usize,
+    }
+
+    impl<CTX: JournalGetter> Inspector<CTX, EthInterpreter> for Debugger {
+        fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+            self.recorder.step(interp, context);
+            if interp.bytecode.opcode() == opcode::STOP {
+                assert!(self.recorder.rewind(context.journal(), self.index));
+            }
+        }
+    }
+
+    #[test]
+    fn rewinds_storage() {
+        // SSTORE(1, 7), SSTORE(2, 8), STOP
+        let code = [
+            opcode::PUSH1,
+            7,
+            opcode::PUSH1,
+            1,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            8,
+            opcode::PUSH1,
+            2,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+        let mut debugger = Debugger {
+            recorder: TimeTravelRecorder::new(RecordGranularity::Step),
+            index: 5,
+        };
+        let result = ctx.inspect_previous(&mut debugger).unwrap();
+
+        let snapshots = debugger.recorder.snapshots();
+        assert_eq!(snapshots.len(), 7);
+        assert_eq!(snapshots[5].opcode, opcode::SSTORE);
+        assert_eq!((snapshots[5].pc, snapshots[5].stack_len), (9, 2));
+        assert!(snapshots[5].rewindable && !snapshots[6].rewindable);
+
+        // Second store was undone, the first one is kept.
+        let storage = &result.state[&BENCH_TARGET].storage;
+        assert_eq!(storage[&U256::from(1)].present_value, U256::from(7));
+        assert_eq!(storage[&U256::from(2)].present_value, U256::ZERO);
+    }
+}
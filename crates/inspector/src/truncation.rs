@@ -0,0 +1,111 @@
+//! Size limits of the call data captured by tracing inspectors.
+use revm::primitives::Bytes;
+
+/// Maximum sizes of the call inputs, outputs and return data captured by a tracing inspector.
+///
+/// Truncated data is copied so the captured bytes don't keep the original buffer alive, and
+/// the original length is reported next to it as the truncation marker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TruncationConfig {
+    /// Maximum captured size of call inputs and init codes.
+    pub max_input: usize,
+    /// Maximum captured size of call outputs and deployed codes.
+    pub max_output: usize,
+    /// Maximum captured size of return data, e.g. revert data.
+    pub max_return_data: usize,
+}
+
+impl Default for TruncationConfig {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl TruncationConfig {
+    /// Captures all data in full.
+    pub const fn unlimited() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// Applies the same limit to inputs, outputs and return data.
+    pub const fn new(max: usize) -> Self {
+        Self {
+            max_input: max,
+            max_output: max,
+            max_return_data: max,
+        }
+    }
+
+    pub const fn with_max_input(mut self, max: usize) -> Self {
+        self.max_input = max;
+        self
+    }
+
+    pub const fn with_max_output(mut self, max: usize) -> Self {
+        self.max_output = max;
+        self
+    }
+
+    pub const fn with_max_return_data(mut self, max: usize) -> Self {
+        self.max_return_data = max;
+        self
+    }
+
+    /// Returns `true` if no limit is set.
+    pub const fn is_unlimited(&self) -> bool {
+        self.max_input == usize::MAX
+            && self.max_output == usize::MAX
+            && self.max_return_data == usize::MAX
+    }
+
+    /// Captures a call input, see [`truncate`].
+    pub fn input(&self, data: &Bytes) -> (Bytes, Option<usize>) {
+        truncate(data, self.max_input)
+    }
+
+    /// Captures a call output, see [`truncate`].
+    pub fn output(&self, data: &Bytes) -> (Bytes, Option<usize>) {
+        truncate(data, self.max_output)
+    }
+
+    /// Captures return data, see [`truncate`].
+    pub fn return_data(&self, data: &Bytes) -> (Bytes, Option<usize>) {
+        truncate(data, self.max_return_data)
+    }
+}
+
+/// Returns the first `max` bytes of `data` and, if it was longer, its original length.
+pub fn truncate(data: &Bytes, max: usize) -> (Bytes, Option<usize>) {
+    if data.len() <= max {
+        (data.clone(), None)
+    } else {
+        (Bytes::copy_from_slice(&data[..max]), Some(data.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_each_kind() {
+        let data = Bytes::from_static(&[1, 2, 3, 4]);
+        let config = TruncationConfig::unlimited()
+            .with_max_input(2)
+            .with_max_return_data(4);
+        assert!(!config.is_unlimited());
+
+        let (input, len) = config.input(&data);
+        assert_eq!(input, Bytes::from_static(&[1, 2]));
+        assert_eq!(len, Some(4));
+        assert_ne!(input.as_ptr(), data.as_ptr());
+
+        assert_eq!(config.output(&data), (data.clone(), None));
+        assert_eq!(config.return_data(&data), (data.clone(), None));
+        assert_eq!(
+            TruncationConfig::new(0).output(&data),
+            (Bytes::new(), Some(4))
+        );
+    }
+}
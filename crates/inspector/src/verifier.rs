@@ -0,0 +1,487 @@
+//! Verification of historical blocks. Executes blocks and compares their receipts with the
+//! receipts of the chain, e.g. to validate a hardfork implementation against mainnet.
+use crate::{
+    differential::{StepTracer, TraceStep},
+    exec::InspectEvm,
+};
+use alloy_consensus::{
+    proofs::calculate_receipt_root, Eip658Value, ReceiptEnvelope, ReceiptWithBloom,
+};
+use core::fmt;
+use revm::{
+    context::Cfg,
+    context_interface::{
+        receipt::{Receipt, ReceiptBuilder},
+        result::{EVMError, InvalidTransaction},
+        Block, Transaction, TransactionType,
+    },
+    database_interface::{Database, DatabaseCommit},
+    primitives::B256,
+    Context, ExecuteEvm,
+};
+use std::{boxed::Box, vec::Vec};
+
+/// Block to verify, with the transactions to execute and the outcome of the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoricalBlock<BLOCK, TX> {
+    pub block: BLOCK,
+    pub txs: Vec<TX>,
+    pub expected: ExpectedOutcome,
+}
+
+/// Outcome of a block on the chain, from its header and receipts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedOutcome {
+    /// Gas used by the block.
+    pub gas_used: u64,
+    /// Root of the receipts of the block.
+    pub receipts_root: B256,
+    /// Receipts of the transactions, to find the first diverging transaction. If [`None`], only
+    /// the gas used and the receipts root are compared.
+    pub receipts: Option<Vec<Receipt>>,
+}
+
+/// Returns the root of the receipts of a block.
+///
+/// Receipts are encoded with their EIP-658 status, as since Byzantium. Returns [`None`] if a
+/// receipt has an unknown transaction type.
+pub fn receipts_root(receipts: &[Receipt]) -> Option<B256> {
+    let envelopes = receipts
+        .iter()
+        .map(|receipt| {
+            let receipt_with_bloom = ReceiptWithBloom {
+                receipt: alloy_consensus::Receipt {
+                    status: Eip658Value::Eip658(receipt.success),
+                    cumulative_gas_used: receipt.cumulative_gas_used,
+                    logs: receipt.logs.clone(),
+                },
+                logs_bloom: receipt.logs_bloom,
+            };
+            Some(match TransactionType::from(receipt.tx_type) {
+                TransactionType::Legacy => ReceiptEnvelope::Legacy(receipt_with_bloom),
+                TransactionType::Eip2930 => ReceiptEnvelope::Eip2930(receipt_with_bloom),
+                TransactionType::Eip1559 => ReceiptEnvelope::Eip1559(receipt_with_bloom),
+                TransactionType::Eip4844 => ReceiptEnvelope::Eip4844(receipt_with_bloom),
+                TransactionType::Eip7702 => ReceiptEnvelope::Eip7702(receipt_with_bloom),
+                TransactionType::Custom => return None,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(calculate_receipt_root(&envelopes))
+}
+
+/// Field of a block or of a receipt that differs from the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DivergentField {
+    TxType {
+        expected: u8,
+        got: u8,
+    },
+    Status {
+        expected: bool,
+        got: bool,
+    },
+    CumulativeGasUsed {
+        expected: u64,
+        got: u64,
+    },
+    Logs,
+    LogsBloom,
+    /// Number of receipts, when the block has more transactions than expected receipts.
+    ReceiptCount {
+        expected: usize,
+        got: usize,
+    },
+    GasUsed {
+        expected: u64,
+        got: u64,
+    },
+    /// Receipts root, [`None`] if a receipt has an unknown transaction type.
+    ReceiptsRoot {
+        expected: B256,
+        got: Option<B256>,
+    },
+}
+
+impl fmt::Display for DivergentField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TxType { expected, got } => {
+                write!(f, "transaction type: expected {expected}, got {got}")
+            }
+            Self::Status { expected, got } => {
+                write!(f, "status: expected {expected}, got {got}")
+            }
+            Self::CumulativeGasUsed { expected, got } => {
+                write!(f, "cumulative gas used: expected {expected}, got {got}")
+            }
+            Self::Logs => f.write_str("logs"),
+            Self::LogsBloom => f.write_str("logs bloom"),
+            Self::ReceiptCount { expected, got } => {
+                write!(f, "receipt count: expected {expected}, got {got}")
+            }
+            Self::GasUsed { expected, got } => {
+                write!(f, "gas used: expected {expected}, got {got}")
+            }
+            Self::ReceiptsRoot { expected, got } => match got {
+                Some(got) => write!(f, "receipts root: expected {expected}, got {got}"),
+                None => write!(
+                    f,
+                    "receipts root: expected {expected}, got unknown receipt type"
+                ),
+            },
+        }
+    }
+}
+
+/// First divergence of a block from the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockDivergence {
+    pub block_number: u64,
+    /// Index of the diverging transaction, [`None`] if only the block differs, e.g. without
+    /// expected receipts.
+    pub tx_index: Option<usize>,
+    pub field: DivergentField,
+    /// Instructions executed by the diverging transaction, if traced.
+    pub trace: Option<Vec<TraceStep>>,
+}
+
+impl fmt::Display for BlockDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block {}", self.block_number)?;
+        if let Some(index) = self.tx_index {
+            write!(f, ", transaction {index}")?;
+        }
+        write!(f, ": {}", self.field)
+    }
+}
+
+/// Outcome of [`BlockVerifier::verify`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Number of blocks matching the chain.
+    pub verified_blocks: usize,
+    /// First divergence, the blocks after it are not executed.
+    pub divergence: Option<BlockDivergence>,
+}
+
+/// Error of the transaction at `tx_index` of a block, e.g. it is invalid on top of the state.
+#[derive(Debug)]
+pub struct VerificationError<DBError> {
+    pub block_number: u64,
+    pub tx_index: usize,
+    pub error: Box<EVMError<DBError, InvalidTransaction>>,
+}
+
+impl<DBError: fmt::Display> fmt::Display for VerificationError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {}, transaction {}: {}",
+            self.block_number, self.tx_index, self.error
+        )
+    }
+}
+
+impl<DBError: core::error::Error + 'static> core::error::Error for VerificationError<DBError> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Executes consecutive blocks and reports the first divergence from the chain.
+///
+/// The state of each transaction is committed to the database, which must hold the state of the
+/// parent of the first block. Rewards of the block, withdrawals and system calls are not applied,
+/// as they do not change the receipts; they have to be committed between blocks if the next
+/// blocks read them.
+#[derive(Clone, Debug, Default)]
+pub struct BlockVerifier<CFG> {
+    cfg: CFG,
+    trace: bool,
+}
+
+impl<CFG: Cfg> BlockVerifier<CFG> {
+    pub fn new(cfg: CFG) -> Self {
+        Self { cfg, trace: false }
+    }
+
+    /// Traces the diverging transaction, executing it again with a tracer.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
+    /// Verifies the blocks in order, stopping at the first divergence.
+    pub fn verify<DB, BLOCK, TX>(
+        &self,
+        db: &mut DB,
+        blocks: impl IntoIterator<Item = HistoricalBlock<BLOCK, TX>>,
+    ) -> Result<VerificationReport, VerificationError<DB::Error>>
+    where
+        DB: Database + DatabaseCommit,
+        BLOCK: Block,
+        TX: Transaction,
+    {
+        let mut verified_blocks = 0;
+        for block in blocks {
+            if let Some(divergence) = self.verify_block(db, &block)? {
+                return Ok(VerificationReport {
+                    verified_blocks,
+                    divergence: Some(divergence),
+                });
+            }
+            verified_blocks += 1;
+        }
+        Ok(VerificationReport {
+            verified_blocks,
+            divergence: None,
+        })
+    }
+
+    fn verify_block<DB, BLOCK, TX>(
+        &self,
+        db: &mut DB,
+        historical: &HistoricalBlock<BLOCK, TX>,
+    ) -> Result<Option<BlockDivergence>, VerificationError<DB::Error>>
+    where
+        DB: Database + DatabaseCommit,
+        BLOCK: Block,
+        TX: Transaction,
+    {
+        let block_number = historical.block.number();
+        let expected = &historical.expected;
+        let mut builder = ReceiptBuilder::new();
+        let mut receipts = Vec::with_capacity(historical.txs.len());
+        for (tx_index, tx) in historical.txs.iter().enumerate() {
+            let result = Context::builder()
+                .with_block(&historical.block)
+                .with_tx(tx)
+                .with_cfg(&self.cfg)
+                .with_db(&mut *db)
+                .exec_previous()
+                .map_err(|error| VerificationError {
+                    block_number,
+                    tx_index,
+                    error: Box::new(error),
+                })?;
+            let receipt = builder.build(tx.tx_type(), &result.result);
+
+            if let Some(expected_receipts) = &expected.receipts {
+                let field = match expected_receipts.get(tx_index) {
+                    Some(expected) => compare_receipts(expected, &receipt),
+                    None => Some(DivergentField::ReceiptCount {
+                        expected: expected_receipts.len(),
+                        got: historical.txs.len(),
+                    }),
+                };
+                if let Some(field) = field {
+                    // The state of the transaction is not committed, so it executes again on
+                    // the same state.
+                    let trace = self.trace.then(|| {
+                        let mut tracer = StepTracer::default();
+                        let _ = Context::builder()
+                            .with_block(&historical.block)
+                            .with_tx(tx)
+                            .with_cfg(&self.cfg)
+                            .with_db(&mut *db)
+                            .inspect_previous(&mut tracer);
+                        tracer.steps
+                    });
+                    return Ok(Some(BlockDivergence {
+                        block_number,
+                        tx_index: Some(tx_index),
+                        field,
+                        trace,
+                    }));
+                }
+            }
+            db.commit(result.state);
+            receipts.push(receipt);
+        }
+
+        let divergence = |field| {
+            Some(BlockDivergence {
+                block_number,
+                tx_index: None,
+                field,
+                trace: None,
+            })
+        };
+        if let Some(expected_receipts) = &expected.receipts {
+            if expected_receipts.len() != receipts.len() {
+                return Ok(divergence(DivergentField::ReceiptCount {
+                    expected: expected_receipts.len(),
+                    got: receipts.len(),
+                }));
+            }
+        }
+        if builder.cumulative_gas_used() != expected.gas_used {
+            return Ok(divergence(DivergentField::GasUsed {
+                expected: expected.gas_used,
+                got: builder.cumulative_gas_used(),
+            }));
+        }
+        let root = receipts_root(&receipts);
+        if root != Some(expected.receipts_root) {
+            return Ok(divergence(DivergentField::ReceiptsRoot {
+                expected: expected.receipts_root,
+                got: root,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+/// Compares the fields of a receipt with the expected receipt, in the order they are encoded.
+fn compare_receipts(expected: &Receipt, got: &Receipt) -> Option<DivergentField> {
+    if expected.tx_type != got.tx_type {
+        return Some(DivergentField::TxType {
+            expected: expected.tx_type,
+            got: got.tx_type,
+        });
+    }
+    if expected.success != got.success {
+        return Some(DivergentField::Status {
+            expected: expected.success,
+            got: got.success,
+        });
+    }
+    if expected.cumulative_gas_used != got.cumulative_gas_used {
+        return Some(DivergentField::CumulativeGasUsed {
+            expected: expected.cumulative_gas_used,
+            got: got.cumulative_gas_used,
+        });
+    }
+    if expected.logs_bloom != got.logs_bloom {
+        return Some(DivergentField::LogsBloom);
+    }
+    if expected.logs != got.logs {
+        return Some(DivergentField::Logs);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::EMPTY_ROOT_HASH;
+    use database::InMemoryDB;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, TxEnv},
+        primitives::{address, Address, TxKind, U256},
+        state::AccountInfo,
+    };
+    use std::vec;
+
+    const CALLER: Address = Address::with_last_byte(1);
+    const EMITTER: Address = address!("0000000000000000000000000000000000001000");
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        // Emits an empty log with topic 1.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::LOG1,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+        db.insert_account_info(
+            EMITTER,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    fn block() -> HistoricalBlock<BlockEnv, TxEnv> {
+        let tx = |nonce| TxEnv {
+            caller: CALLER,
+            nonce,
+            kind: TxKind::Call(EMITTER),
+            gas_limit: 100_000,
+            gas_priority_fee: None,
+            ..Default::default()
+        };
+        let txs = vec![tx(0), tx(1)];
+
+        // Receipts of the chain, as executed by this version.
+        let mut db = db();
+        let mut builder = ReceiptBuilder::new();
+        let receipts: Vec<_> = txs
+            .iter()
+            .map(|tx| {
+                let result = Context::builder()
+                    .with_tx(tx)
+                    .with_db(&mut db)
+                    .exec_previous()
+                    .unwrap();
+                db.commit(result.state);
+                builder.build(tx.tx_type, &result.result)
+            })
+            .collect();
+        HistoricalBlock {
+            block: BlockEnv::default(),
+            txs,
+            expected: ExpectedOutcome {
+                gas_used: builder.cumulative_gas_used(),
+                receipts_root: receipts_root(&receipts).unwrap(),
+                receipts: Some(receipts),
+            },
+        }
+    }
+
+    #[test]
+    fn verifies_blocks() {
+        assert_eq!(receipts_root(&[]), Some(EMPTY_ROOT_HASH));
+
+        let verifier = BlockVerifier::new(CfgEnv::default()).with_trace();
+        let report = verifier.verify(&mut db(), [block()]).unwrap();
+        assert_eq!(
+            report,
+            VerificationReport {
+                verified_blocks: 1,
+                divergence: None,
+            }
+        );
+
+        let mut diverging = block();
+        let receipts = diverging.expected.receipts.as_mut().unwrap();
+        receipts[1].cumulative_gas_used += 1;
+        let report = verifier.verify(&mut db(), [diverging]).unwrap();
+        assert_eq!(report.verified_blocks, 0);
+        let divergence = report.divergence.unwrap();
+        assert_eq!(divergence.tx_index, Some(1));
+        assert!(matches!(
+            divergence.field,
+            DivergentField::CumulativeGasUsed { .. }
+        ));
+        let trace = divergence.trace.unwrap();
+        assert_eq!(trace.last().unwrap().opcode, opcode::STOP);
+
+        // Without receipts, only the block is compared.
+        let mut diverging = block();
+        diverging.expected.receipts = None;
+        diverging.expected.receipts_root = B256::ZERO;
+        let divergence = verifier
+            .verify(&mut db(), [diverging])
+            .unwrap()
+            .divergence
+            .unwrap();
+        assert_eq!(divergence.tx_index, None);
+        assert!(matches!(
+            divergence.field,
+            DivergentField::ReceiptsRoot { .. }
+        ));
+    }
+}
@@ -98,6 +98,8 @@ pub enum InstructionResult {
     EofAuxDataTooSmall,
     /// `EXT*CALL` target address needs to be padded with 0s.
     InvalidEXTCALLTarget,
+    /// EOF code that was not validated is malformed, e.g. it calls a missing code section.
+    InvalidEOFCode,
 }
 
 impl From<TransferError> for InstructionResult {
@@ -156,6 +158,7 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::EofAuxDataTooSmall => Self::EofAuxDataTooSmall,
             HaltReason::SubRoutineStackOverflow => Self::SubRoutineStackOverflow,
             HaltReason::InvalidEXTCALLTarget => Self::InvalidEXTCALLTarget,
+            HaltReason::InvalidEOFCode => Self::InvalidEOFCode,
         }
     }
 }
@@ -215,6 +218,7 @@ macro_rules! return_error {
             | $crate::InstructionResult::EofAuxDataTooSmall
             | $crate::InstructionResult::EofAuxDataOverflow
             | $crate::InstructionResult::InvalidEXTCALLTarget
+            | $crate::InstructionResult::InvalidEOFCode
     };
 }
 
@@ -389,6 +393,7 @@ impl<HaltReasonT: HaltReasonTrait> From<InstructionResult> for SuccessOrHalt<Hal
             InstructionResult::InvalidEXTCALLTarget => {
                 Self::Halt(HaltReason::InvalidEXTCALLTarget.into())
             }
+            InstructionResult::InvalidEOFCode => Self::Halt(HaltReason::InvalidEOFCode.into()),
             InstructionResult::InvalidExtDelegateCallTarget => {
                 Self::Internal(InternalResult::InvalidExtDelegateCallTarget)
             }
@@ -460,6 +465,7 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::InvalidEOFCode,
         ];
 
         for result in error_results {
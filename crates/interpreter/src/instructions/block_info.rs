@@ -49,10 +49,10 @@ pub fn difficulty<WIRE: InterpreterTypes, H: Host + ?Sized>(
 ) {
     gas!(interpreter, gas::BASE);
     if interpreter.runtime_flag.spec_id().is_enabled_in(MERGE) {
-        // Unwrap is safe as this fields is checked in validation handler.
+        // Prevrandao is checked in validation handler, default to zero if it is skipped.
         push!(
             interpreter,
-            (host.block().prevrandao().unwrap()).into_u256()
+            host.block().prevrandao().unwrap_or_default().into_u256()
         );
     } else {
         push!(interpreter, host.block().difficulty());
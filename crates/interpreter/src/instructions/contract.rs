@@ -33,11 +33,17 @@ pub fn eofcreate<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     popn!([value, salt, data_offset, data_size], interpreter);
 
-    let container = interpreter
+    // Containers are checked by EOF validation, unvalidated code can reference a missing one.
+    let Some(container) = interpreter
         .bytecode
         .eof_container(initcontainer_index as usize)
-        .expect("valid container")
-        .clone();
+        .cloned()
+    else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
+    };
 
     // Resize memory and get return range.
     let Some(input_range) = resize_memory(interpreter, data_offset, data_size) else {
@@ -50,12 +56,16 @@ pub fn eofcreate<WIRE: InterpreterTypes, H: Host + ?Sized>(
         Bytes::new()
     };
 
-    let eof = Eof::decode(container.clone()).expect("Subcontainer is verified");
-
-    if !eof.body.is_data_filled {
-        // Should be always false as it is verified by eof verification.
-        panic!("Panic if data section is not full");
-    }
+    // Subcontainer is valid and its data section is full if the code was validated.
+    let Some(eof) = Eof::decode(container.clone())
+        .ok()
+        .filter(|eof| eof.body.is_data_filled)
+    else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
+    };
 
     // Deduct gas for hash that is needed to calculate address.
     gas_or_fail!(interpreter, cost_per_word(container.len(), KECCAK256WORD));
@@ -98,16 +108,29 @@ pub fn return_contract<H: Host + ?Sized>(
     let deploy_container_index = interpreter.bytecode.read_u8();
     popn!([aux_data_offset, aux_data_size], interpreter);
     let aux_data_size = as_usize_or_fail!(interpreter, aux_data_size);
-    let container = interpreter
+    let Some(container) = interpreter
         .bytecode
         .eof_container(deploy_container_index as usize)
-        .expect("valid container")
-        .clone();
+        .cloned()
+    else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
+    };
 
     // Convert to EOF so we can check data section size.
-    let (eof_header, _) = EofHeader::decode(&container).expect("valid EOF header");
-
-    let static_aux_size = eof_header.eof_size() - container.len();
+    let Some((eof_header, static_aux_size)) =
+        EofHeader::decode(&container).ok().and_then(|(header, _)| {
+            let static_aux_size = header.eof_size().checked_sub(container.len())?;
+            (static_aux_size <= header.data_size as usize).then_some((header, static_aux_size))
+        })
+    else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
+    };
 
     // Important: Offset must be ignored if len is zeros
     let mut output = if aux_data_size != 0 {
@@ -148,11 +171,7 @@ pub fn return_contract<H: Host + ?Sized>(
     let gas = *interpreter.control.gas();
     interpreter.control.set_next_action(
         crate::InterpreterAction::Return {
-            result: InterpreterResult {
-                output,
-                gas,
-                result,
-            },
+            result: InterpreterResult::new(result, output, gas),
         },
         result,
     );
@@ -108,14 +108,22 @@ pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     let idx = interpreter.bytecode.read_u16() as usize;
 
-    // Get target types
-    let Some(types) = interpreter.bytecode.code_section_info(idx) else {
-        panic!("Invalid EOF in execution, expecting correct intermediate in callf")
+    // Get target types, a missing section is only possible if the code was not validated.
+    let (Some(types), Some(pc)) = (
+        interpreter.bytecode.code_section_info(idx),
+        interpreter.bytecode.code_section_pc(idx),
+    ) else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
     };
 
     // Check max stack height for target code section.
-    // Safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    // Max stack height is more than inputs in validated code.
+    if interpreter.stack.len() + types.max_stack_size.saturating_sub(types.inputs as u16) as usize
+        > 1024
+    {
         interpreter
             .control
             .set_instruction_result(InstructionResult::StackOverflow);
@@ -133,10 +141,6 @@ pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::SubRoutineStackOverflow);
         return;
     };
-    let pc = interpreter
-        .bytecode
-        .code_section_pc(idx)
-        .expect("Invalid code section index");
     interpreter.bytecode.absolute_jump(pc);
 }
 
@@ -147,8 +151,12 @@ pub fn retf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     require_eof!(interpreter);
     gas!(interpreter, gas::RETF_GAS);
 
+    // Validated code never returns from the first code section.
     let Some(jump) = interpreter.sub_routine.pop() else {
-        panic!("Expected function frame")
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
     };
 
     interpreter.bytecode.absolute_jump(jump);
@@ -163,25 +171,28 @@ pub fn jumpf<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     let idx = interpreter.bytecode.read_u16() as usize;
 
-    // Get target types
-    let types = interpreter
-        .bytecode
-        .code_section_info(idx)
-        .expect("Invalid code section index");
+    // Get target types, a missing section is only possible if the code was not validated.
+    let (Some(types), Some(pc)) = (
+        interpreter.bytecode.code_section_info(idx),
+        interpreter.bytecode.code_section_pc(idx),
+    ) else {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::InvalidEOFCode);
+        return;
+    };
 
     // Check max stack height for target code section.
-    // Safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    // Max stack height is more than inputs in validated code.
+    if interpreter.stack.len() + types.max_stack_size.saturating_sub(types.inputs as u16) as usize
+        > 1024
+    {
         interpreter
             .control
             .set_instruction_result(InstructionResult::StackOverflow);
         return;
     }
     interpreter.sub_routine.set_routine_idx(idx);
-    let pc = interpreter
-        .bytecode
-        .code_section_pc(idx)
-        .expect("Invalid code section index");
     interpreter.bytecode.absolute_jump(pc);
 }
 
@@ -214,11 +225,7 @@ fn return_inner(
     let gas = *interpreter.control.gas();
     interpreter.control.set_next_action(
         InterpreterAction::Return {
-            result: InterpreterResult {
-                output,
-                gas,
-                result: instruction_result,
-            },
+            result: InterpreterResult::new(instruction_result, output, gas),
         },
         instruction_result,
     );
@@ -14,6 +14,7 @@ use crate::{
     table::{CustomInstruction, InstructionTable},
     Gas, Host, Instruction, InstructionResult, InterpreterAction,
 };
+use context_interface::result::HaltDebug;
 use core::cell::RefCell;
 pub use ext_bytecode::ExtBytecode;
 pub use input::InputsImpl;
@@ -24,7 +25,7 @@ pub use runtime_flags::RuntimeFlags;
 pub use shared_memory::{num_words, MemoryGetter, SharedMemory, EMPTY_SHARED_MEMORY};
 use specification::hardfork::SpecId;
 pub use stack::{Stack, STACK_LIMIT};
-use std::rc::Rc;
+use std::{boxed::Box, rc::Rc};
 use subroutine_stack::SubRoutineImpl;
 
 #[derive(Debug, Clone)]
@@ -145,6 +146,7 @@ impl<IW: InterpreterTypes> Interpreter<IW> {
                 // Return empty bytecode
                 output: Bytes::new(),
                 gas: *self.control.gas(),
+                halt_debug: None,
             },
         }
     }
@@ -176,6 +178,8 @@ pub struct InterpreterResult {
     pub output: Bytes,
     /// The gas usage information.
     pub gas: Gas,
+    /// Instruction that halted the frame, set by the frame when the result is an error.
+    pub halt_debug: Option<Box<HaltDebug>>,
 }
 
 impl InterpreterResult {
@@ -185,6 +189,7 @@ impl InterpreterResult {
             result,
             output,
             gas,
+            halt_debug: None,
         }
     }
 
@@ -1,4 +1,4 @@
-use crate::{Gas, InstructionResult, InterpreterResult};
+use crate::{CallInputs, Gas, InstructionResult, InterpreterResult};
 use core::ops::Range;
 use primitives::Bytes;
 
@@ -34,6 +34,26 @@ impl CallOutcome {
         }
     }
 
+    /// Constructs a [`CallOutcome`] for the given call that ends with `result` and `output`
+    /// without spending any gas.
+    ///
+    /// Used to mock the result of a call, the output is written to the return memory of the call.
+    pub fn from_inputs(inputs: &CallInputs, result: InstructionResult, output: Bytes) -> Self {
+        Self::new(
+            InterpreterResult::new(result, output, Gas::new(inputs.gas_limit)),
+            inputs.return_memory_offset.clone(),
+        )
+    }
+
+    /// Changes the outcome to a revert with the given revert data.
+    ///
+    /// Gas refunds of the call are discarded, as they are for any reverted call.
+    pub fn revert_with(&mut self, output: Bytes) {
+        self.result.result = InstructionResult::Revert;
+        self.result.output = output;
+        self.result.gas.set_refund(0);
+    }
+
     /// Returns a reference to the instruction result.
     ///
     /// Provides access to the result of the executed instruction.
@@ -2,3 +2,4 @@ pub mod exec;
 pub mod exec_op;
 pub mod inspect;
 pub mod into_optimism;
+pub mod op_evm;
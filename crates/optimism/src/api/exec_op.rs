@@ -1,15 +1,21 @@
 use revm::{
     context_interface::{
         result::{EVMError, ExecutionResult, ResultAndState},
-        Cfg, CfgGetter, DatabaseGetter,
+        transaction::TransactionSetter,
+        Block, Cfg, CfgGetter, DatabaseGetter, TransactionGetter,
     },
     handler::{instructions::EthInstructionExecutor, EthContext, EthFrame, EthHandler},
     interpreter::interpreter::EthInterpreter,
+    primitives::{Address, I256, U256},
     Database, DatabaseCommit,
 };
+use std::{collections::BTreeMap, vec::Vec};
 
 use crate::{
-    handler::{precompiles::OpPrecompileProvider, OpHandler},
+    handler::{
+        precompiles::{CustomPrecompiles, OpPrecompileProvider},
+        BaseFeeRouting, OpHandler,
+    },
     transaction::abstraction::OpTxGetter,
     L1BlockInfoGetter, OpSpec, OpTransactionError, OptimismHaltReason,
 };
@@ -21,6 +27,21 @@ pub fn transact_op<CTX: EthContext + OpTxGetter + L1BlockInfoGetter>(
     ResultAndState<OptimismHaltReason>,
     EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>,
 >
+where
+    <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
+{
+    transact_op_with_precompiles(ctx, CustomPrecompiles::default())
+}
+
+/// Executes a transaction like [`transact_op`], with precompiles of the chain added to the ones of
+/// the optimism specs.
+pub fn transact_op_with_precompiles<CTX: EthContext + OpTxGetter + L1BlockInfoGetter>(
+    ctx: &mut CTX,
+    precompiles: CustomPrecompiles,
+) -> Result<
+    ResultAndState<OptimismHaltReason>,
+    EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>,
+>
 where
     <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
 {
@@ -30,10 +51,37 @@ where
         EthFrame<CTX, _, _, _>,
         OpPrecompileProvider<CTX, _>,
         EthInstructionExecutor<EthInterpreter, CTX>,
-    >::new();
+    >::with_precompiles(precompiles);
     op.run(ctx)
 }
 
+/// Decodes a raw EIP-2718 transaction, sets it as the transaction of the context and executes it.
+///
+/// Envelopes that can not be decoded, or whose signer can not be recovered, are reported as
+/// [`EVMError::Custom`] errors.
+#[cfg(feature = "alloy")]
+pub fn transact_op_raw<CTX>(
+    ctx: &mut CTX,
+    raw: revm::primitives::Bytes,
+) -> Result<
+    ResultAndState<OptimismHaltReason>,
+    EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>,
+>
+where
+    CTX: EthContext
+        + OpTxGetter
+        + L1BlockInfoGetter
+        + revm::context_interface::transaction::TransactionSetter<
+            Transaction = crate::OpTransaction<revm::context::TxEnv>,
+        >,
+    <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
+{
+    let tx = crate::OpTransaction::decode_2718(raw)
+        .map_err(|error| EVMError::Custom(error.to_string()))?;
+    ctx.set_tx(tx);
+    transact_op(ctx)
+}
+
 pub fn transact_op_commit<CTX: EthContext + OpTxGetter + L1BlockInfoGetter>(
     ctx: &mut CTX,
 ) -> Result<
@@ -49,3 +97,640 @@ where
         r.result
     })
 }
+
+/// Results of the transactions of a block executed with [`transact_op_block`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpBlockResult {
+    /// Results of the transactions, in order.
+    pub results: Vec<ExecutionResult<OptimismHaltReason>>,
+    /// Change of the balance of each fee vault over the block, including the recipient of a
+    /// [`BaseFeeRouting::Split`] of the base fee. Balances are the ones in the gas token of the
+    /// chain if it has one.
+    pub vault_deltas: BTreeMap<Address, I256>,
+}
+
+/// Executes the transactions of a block in order, committing the changes of each one before
+/// executing the next.
+///
+/// The L1 block info is loaded once for the block, by its first transaction that is not a deposit
+/// or decoded from its L1 attributes deposit, and shared by all of its transactions. `on_tx` is
+/// called with the context of each transaction and its result, before its state is committed. An
+/// invalid transaction makes the block invalid, its error is returned.
+pub fn transact_op_block_with<CTX>(
+    ctx: &mut CTX,
+    txs: impl IntoIterator<Item = <CTX as TransactionGetter>::Transaction>,
+    mut on_tx: impl FnMut(&CTX, &ResultAndState<OptimismHaltReason>),
+) -> Result<(), EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>>
+where
+    CTX: EthContext + OpTxGetter + L1BlockInfoGetter + TransactionSetter,
+    <CTX as DatabaseGetter>::Database: DatabaseCommit,
+    <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
+{
+    // The cached info may be the one of a block replayed at the same height.
+    ctx.l1_block_info_mut().reset();
+
+    for tx in txs {
+        ctx.set_tx(tx);
+        let result = transact_op(ctx)?;
+        on_tx(ctx, &result);
+        ctx.db().commit(result.state);
+    }
+    Ok(())
+}
+
+/// Executes the transactions of a block like [`transact_op_block_with`], and returns their
+/// results with the change of the balances of the fee vaults.
+///
+/// The beneficiary and the fee vaults are preloaded into the database cache before the first
+/// transaction.
+pub fn transact_op_block<CTX>(
+    ctx: &mut CTX,
+    txs: impl IntoIterator<Item = <CTX as TransactionGetter>::Transaction>,
+) -> Result<
+    OpBlockResult,
+    EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>,
+>
+where
+    CTX: EthContext + OpTxGetter + L1BlockInfoGetter + TransactionSetter,
+    <CTX as DatabaseGetter>::Database: DatabaseCommit,
+    <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
+{
+    let l1_block_info = ctx.l1_block_info();
+    let recipients = l1_block_info.fee_recipients;
+    let mut vaults = Vec::from([
+        recipients.base_fee,
+        recipients.l1_fee,
+        recipients.operator_fee,
+    ]);
+    if let BaseFeeRouting::Split { recipient, .. } = l1_block_info.base_fee_routing {
+        vaults.push(recipient);
+    }
+    let beneficiary = ctx.block().beneficiary();
+    ctx.db().basic(beneficiary).map_err(EVMError::Database)?;
+    let mut balances = BTreeMap::new();
+    for vault in vaults {
+        balances.insert(vault, fee_balance(ctx, vault).map_err(EVMError::Database)?);
+    }
+
+    let mut results = Vec::new();
+    transact_op_block_with(ctx, txs, |_, result| results.push(result.result.clone()))?;
+
+    let mut vault_deltas = BTreeMap::new();
+    for (vault, before) in balances {
+        let after = fee_balance(ctx, vault).map_err(EVMError::Database)?;
+        let delta = I256::from_raw(after).wrapping_sub(I256::from_raw(before));
+        vault_deltas.insert(vault, delta);
+    }
+    Ok(OpBlockResult {
+        results,
+        vault_deltas,
+    })
+}
+
+/// Returns the balance of `account` in the database, in the gas token of the chain if it has one.
+fn fee_balance<CTX: DatabaseGetter + L1BlockInfoGetter>(
+    ctx: &mut CTX,
+    account: Address,
+) -> Result<U256, <<CTX as DatabaseGetter>::Database as Database>::Error> {
+    match ctx.l1_block_info().gas_token {
+        Some(gas_token) => ctx
+            .db()
+            .storage(gas_token.address, gas_token.balance_slot(account)),
+        None => Ok(ctx.db().basic(account)?.unwrap_or_default().balance),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        l1block::{ECOTONE_L1_BLOB_BASE_FEE_SLOT, ECOTONE_L1_FEE_SCALARS_SLOT, L1_BASE_FEE_SLOT},
+        transaction::deposit::DEPOSIT_TRANSACTION_TYPE,
+        GasToken, L1BlockInfo, OpSpecId, OpTransaction, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT,
+        L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT,
+    };
+    use database::{CacheDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::result::InvalidTransaction,
+        database_interface::EmptyDB,
+        primitives::{address, hex, Bytes, TxKind, B256, U256},
+        specification::hardfork::SpecId,
+        state::{AccountInfo, EvmState},
+        Context, JournaledState,
+    };
+
+    type TestContext = Context<
+        BlockEnv,
+        OpTransaction<TxEnv>,
+        CfgEnv<OpSpec>,
+        CacheDB<EmptyDB>,
+        JournaledState<CacheDB<EmptyDB>>,
+        L1BlockInfo,
+    >;
+
+    const TEST_GAS_TOKEN: GasToken = GasToken {
+        address: address!("4200000000000000000000000000000000000777"),
+        balances_slot: U256::from_limbs([1, 0, 0, 0]),
+    };
+    const BENEFICIARY: Address = address!("00000000000000000000000000000000000000cb");
+
+    /// Calls `target` with `code` from a caller holding one ether natively and in the gas token,
+    /// paying a priority fee of 2.
+    fn fee_mode_ctx(gas_token: Option<GasToken>, target: Address, code: Bytes) -> TestContext {
+        let balance = U256::from(10).pow(U256::from(18));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(BENCH_CALLER, AccountInfo::from_balance(balance));
+        db.insert_account_info(
+            target,
+            AccountInfo {
+                balance: U256::from(1),
+                ..AccountInfo::from_bytecode(Bytecode::new_legacy(code))
+            },
+        );
+        db.insert_account_info(
+            TEST_GAS_TOKEN.address,
+            AccountInfo::from_bytecode(Bytecode::new()),
+        );
+        db.insert_account_storage(
+            TEST_GAS_TOKEN.address,
+            TEST_GAS_TOKEN.balance_slot(BENCH_CALLER),
+            balance,
+        )
+        .unwrap();
+
+        let mut tx = OpTransaction::new(TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(target),
+            gas_limit: 100_000,
+            gas_price: 12,
+            gas_priority_fee: Some(2),
+            ..Default::default()
+        });
+        tx.enveloped_tx = Some(Bytes::new());
+        Context::default()
+            .with_db(db)
+            .with_block(BlockEnv {
+                basefee: 10,
+                beneficiary: BENEFICIARY,
+                ..Default::default()
+            })
+            .with_tx(tx)
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::REGOLITH)))
+            .with_chain(L1BlockInfo {
+                l2_block: Some(0),
+                gas_token,
+                ..Default::default()
+            })
+    }
+
+    #[test]
+    fn execute_block() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BENCH_CALLER,
+            AccountInfo::from_balance(U256::from(10).pow(U256::from(18))),
+        );
+        db.insert_account_info(
+            L1_BLOCK_CONTRACT,
+            AccountInfo::from_bytecode(Bytecode::new()),
+        );
+        let l1_block_storage = [
+            (L1_BASE_FEE_SLOT, U256::from(1_000_000_000)),
+            (ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(10)),
+            (
+                ECOTONE_L1_FEE_SCALARS_SLOT,
+                (U256::from(1368) << 96) | (U256::from(810949) << 64),
+            ),
+        ];
+        for (slot, value) in l1_block_storage {
+            db.insert_account_storage(L1_BLOCK_CONTRACT, slot, value)
+                .unwrap();
+        }
+
+        let spec = OpSpec::Op(OpSpecId::FJORD);
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_tx(OpTransaction::default())
+            .with_cfg(CfgEnv::default().with_spec(spec))
+            // Info cached for another block at the same height, without L1 fees.
+            .with_chain(L1BlockInfo {
+                l2_block: Some(0),
+                ..Default::default()
+            });
+        ctx.block.basefee = 10;
+
+        let depositor = address!("00000000000000000000000000000000000000aa");
+        let mut deposit = OpTransaction::new(TxEnv {
+            tx_type: DEPOSIT_TRANSACTION_TYPE,
+            caller: depositor,
+            kind: TxKind::Call(BENCH_TARGET),
+            gas_price: 0,
+            gas_priority_fee: None,
+            ..Default::default()
+        });
+        deposit.deposit.mint = Some(10);
+        let enveloped_tx = Bytes::from_static(&[0xFA; 200]);
+        let transfer = |nonce| {
+            let mut tx = OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                nonce,
+                gas_limit: 100_000,
+                gas_price: 10,
+                gas_priority_fee: None,
+                ..Default::default()
+            });
+            tx.enveloped_tx = Some(enveloped_tx.clone());
+            tx
+        };
+
+        let block = transact_op_block(&mut ctx, [deposit, transfer(0), transfer(1)]).unwrap();
+        assert_eq!(block.results.len(), 3);
+        assert!(block.results.iter().all(ExecutionResult::is_success));
+        assert_eq!(ctx.l1_block_info().l2_block, Some(0));
+
+        // The L1 block info was loaded again for the block.
+        let l1_block_info = L1BlockInfo::try_fetch(ctx.db(), spec).unwrap();
+        let l1_cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, spec);
+        assert!(!l1_cost.is_zero());
+        let gas_used = block.results[1].gas_used() + block.results[2].gas_used();
+        assert_eq!(
+            block.vault_deltas,
+            BTreeMap::from([
+                (BASE_FEE_RECIPIENT, I256::try_from(10 * gas_used).unwrap()),
+                (L1_FEE_RECIPIENT, I256::from_raw(l1_cost * U256::from(2))),
+                (OPERATOR_FEE_RECIPIENT, I256::ZERO),
+            ])
+        );
+    }
+    #[test]
+    fn custom_precompiles() {
+        const CUSTOM: Address = address!("0000000000000000000000000000000000000a11");
+        const IDENTITY: Address = address!("0000000000000000000000000000000000000004");
+        let custom: precompile::PrecompileFn = |_, _| {
+            Ok(precompile::PrecompileOutput::new(
+                100,
+                Bytes::from_static(b"custom"),
+            ))
+        };
+        let reverse: precompile::PrecompileFn = |input, _| {
+            let output: Vec<u8> = input.iter().rev().copied().collect();
+            Ok(precompile::PrecompileOutput::new(10, output.into()))
+        };
+        let precompiles = CustomPrecompiles::new()
+            .with(OpSpecId::FJORD, (CUSTOM, custom).into())
+            .with(OpSpecId::ECOTONE, (IDENTITY, reverse).into());
+
+        let call = |spec: OpSpecId, target: Address| {
+            let mut ctx = Context::default()
+                .with_db(CacheDB::new(EmptyDB::default()))
+                .with_tx(OpTransaction::new(TxEnv {
+                    caller: BENCH_CALLER,
+                    kind: TxKind::Call(target),
+                    data: Bytes::from_static(&[1, 2, 3]),
+                    gas_price: 0,
+                    gas_priority_fee: None,
+                    ..Default::default()
+                }))
+                .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(spec)))
+                .with_chain(L1BlockInfo::default());
+            ctx.tx.enveloped_tx = Some(Bytes::from_static(&[0xFA; 10]));
+            let result = transact_op_with_precompiles(&mut ctx, precompiles.clone()).unwrap();
+            assert!(result.result.is_success());
+            result.result.output().cloned().unwrap_or_default()
+        };
+
+        // Precompiles are added from the spec they are registered for.
+        assert_eq!(call(OpSpecId::ECOTONE, CUSTOM), Bytes::new());
+        assert_eq!(call(OpSpecId::FJORD, CUSTOM), Bytes::from_static(b"custom"));
+        assert_eq!(
+            call(OpSpecId::ISTHMUS, CUSTOM),
+            Bytes::from_static(b"custom")
+        );
+
+        // Precompiles of the spec are replaced.
+        assert_eq!(
+            call(OpSpecId::CANYON, IDENTITY),
+            Bytes::from_static(&[1, 2, 3])
+        );
+        assert_eq!(
+            call(OpSpecId::ECOTONE, IDENTITY),
+            Bytes::from_static(&[3, 2, 1])
+        );
+    }
+
+    #[test]
+    fn gas_token() {
+        let gas_token = TEST_GAS_TOKEN;
+        let token_balance = |state: &EvmState, account| {
+            state[&gas_token.address].storage[&gas_token.balance_slot(account)].present_value
+        };
+        let reverting = address!("00000000000000000000000000000000000000bb");
+        let depositor = address!("00000000000000000000000000000000000000aa");
+        let beneficiary = BENEFICIARY;
+
+        let balance = U256::from(10).pow(U256::from(18));
+        let mut db = CacheDB::new(EmptyDB::default());
+        // The native balance of the caller pays nothing.
+        db.insert_account_info(BENCH_CALLER, AccountInfo::from_balance(balance));
+        db.insert_account_info(
+            reverting,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(
+                [opcode::PUSH1, 0, opcode::PUSH1, 0, opcode::REVERT].into(),
+            )),
+        );
+        db.insert_account_info(
+            gas_token.address,
+            AccountInfo::from_bytecode(Bytecode::new()),
+        );
+        db.insert_account_storage(
+            gas_token.address,
+            gas_token.balance_slot(BENCH_CALLER),
+            balance,
+        )
+        .unwrap();
+
+        let enveloped_tx = Bytes::from_static(&[0xFA; 10]);
+        let transfer = |target, value| {
+            let mut tx = OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(target),
+                value,
+                gas_limit: 100_000,
+                gas_price: 12,
+                gas_priority_fee: Some(2),
+                ..Default::default()
+            });
+            tx.enveloped_tx = Some(enveloped_tx.clone());
+            tx
+        };
+        let deposit = |mint, value| {
+            let mut tx = OpTransaction::new(TxEnv {
+                tx_type: DEPOSIT_TRANSACTION_TYPE,
+                caller: depositor,
+                kind: TxKind::Call(BENCH_TARGET),
+                value: U256::from(value),
+                gas_price: 0,
+                gas_priority_fee: None,
+                ..Default::default()
+            });
+            tx.deposit.mint = Some(mint);
+            tx
+        };
+
+        let spec = OpSpec::Op(OpSpecId::REGOLITH);
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_block(BlockEnv {
+                basefee: 10,
+                beneficiary,
+                ..Default::default()
+            })
+            .with_tx(transfer(BENCH_TARGET, U256::from(1_000)))
+            .with_cfg(CfgEnv::default().with_spec(spec))
+            .with_chain(L1BlockInfo {
+                l2_block: Some(0),
+                l1_base_fee: U256::from(1_000),
+                l1_fee_overhead: Some(U256::from(100)),
+                l1_base_fee_scalar: U256::from(1_000_000),
+                gas_token: Some(gas_token),
+                ..Default::default()
+            });
+        let l1_cost = ctx.calculate_tx_l1_cost(&enveloped_tx, spec);
+        assert!(!l1_cost.is_zero());
+
+        // The value and the fees are paid in the gas token.
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        let gas_used = U256::from(result.gas_used());
+        assert_eq!(
+            token_balance(&state, BENCH_CALLER),
+            balance - U256::from(1_000) - U256::from(12) * gas_used - l1_cost
+        );
+        assert_eq!(token_balance(&state, BENCH_TARGET), U256::from(1_000));
+        assert_eq!(token_balance(&state, beneficiary), U256::from(2) * gas_used);
+        assert_eq!(
+            token_balance(&state, BASE_FEE_RECIPIENT),
+            U256::from(10) * gas_used
+        );
+        assert_eq!(token_balance(&state, L1_FEE_RECIPIENT), l1_cost);
+        assert_eq!(state[&BENCH_CALLER].info.balance, balance);
+        assert_eq!(state[&BENCH_CALLER].info.nonce, 1);
+
+        // The value is returned if the transaction reverts.
+        ctx.tx = transfer(reverting, U256::from(1_000));
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(matches!(result, ExecutionResult::Revert { .. }));
+        let gas_used = U256::from(result.gas_used());
+        assert_eq!(
+            token_balance(&state, BENCH_CALLER),
+            balance - U256::from(12) * gas_used - l1_cost
+        );
+        assert_eq!(token_balance(&state, reverting), U256::ZERO);
+
+        // The balance of the caller in the gas token is checked.
+        ctx.tx = transfer(BENCH_TARGET, balance);
+        assert!(matches!(
+            transact_op(&mut ctx),
+            Err(EVMError::Transaction(OpTransactionError::Base(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            )))
+        ));
+
+        // Deposits mint the gas token.
+        ctx.tx = deposit(500, 200);
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        assert_eq!(token_balance(&state, depositor), U256::from(300));
+        assert_eq!(token_balance(&state, BENCH_TARGET), U256::from(200));
+        assert_eq!(state[&depositor].info.balance, U256::ZERO);
+
+        // The mint is kept if the deposit fails.
+        ctx.tx = deposit(500, 1_000);
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_halt());
+        assert_eq!(token_balance(&state, depositor), U256::from(500));
+        assert_eq!(state[&depositor].info.nonce, 1);
+    }
+
+    #[test]
+    fn beneficiary_reward() {
+        let code = Bytes::from_static(&[opcode::STOP]);
+
+        let mut ctx = fee_mode_ctx(None, BENCH_TARGET, code.clone());
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        assert_eq!(
+            state[&BENEFICIARY].info.balance,
+            U256::from(2 * result.gas_used())
+        );
+
+        let mut ctx = fee_mode_ctx(Some(TEST_GAS_TOKEN), BENCH_TARGET, code);
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        let slot = TEST_GAS_TOKEN.balance_slot(BENEFICIARY);
+        assert_eq!(
+            state[&TEST_GAS_TOKEN.address].storage[&slot].present_value,
+            U256::from(2 * result.gas_used())
+        );
+        assert!(state
+            .get(&BENEFICIARY)
+            .is_none_or(|beneficiary| beneficiary.info.balance.is_zero()));
+    }
+
+    #[test]
+    fn gas_token_inner_values() {
+        let sender = address!("00000000000000000000000000000000000000bb");
+        // Calls 0xcc with a value of 1 and stores whether the call succeeded.
+        let code = Bytes::from_static(&[
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            1,
+            opcode::PUSH1,
+            0xcc,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH1,
+            0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]);
+        let call_succeeded = |state: &EvmState| state[&sender].storage[&U256::ZERO].present_value;
+
+        let mut ctx = fee_mode_ctx(None, sender, code.clone());
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        assert_eq!(call_succeeded(&state), U256::from(1));
+
+        // The native balance of the sender is not moved by the call in the gas token mode.
+        let mut ctx = fee_mode_ctx(Some(TEST_GAS_TOKEN), sender, code);
+        let ResultAndState { result, state } = transact_op(&mut ctx).unwrap();
+        assert!(result.is_success());
+        assert_eq!(call_succeeded(&state), U256::ZERO);
+        assert_eq!(state[&sender].info.balance, U256::from(1));
+    }
+
+    #[test]
+    fn isthmus_p256verify() {
+        const P256VERIFY: Address = address!("0000000000000000000000000000000000000100");
+        let input = hex!("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d604aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e");
+
+        let mut tx = OpTransaction::new(TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(P256VERIFY),
+            data: Bytes::copy_from_slice(&input),
+            gas_limit: 100_000,
+            gas_price: 0,
+            gas_priority_fee: None,
+            ..Default::default()
+        });
+        tx.enveloped_tx = Some(Bytes::new());
+        let mut ctx = Context::default()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .with_tx(tx)
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::ISTHMUS)))
+            .with_chain(L1BlockInfo {
+                l2_block: Some(0),
+                ..Default::default()
+            });
+
+        let result = transact_op(&mut ctx).unwrap().result;
+        assert!(result.is_success());
+        assert_eq!(result.output(), Some(&Bytes::from(B256::with_last_byte(1))));
+    }
+
+    /// Configuration of `eth_call` like simulations, skipping the checks of the transaction.
+    struct SimulationCfg(CfgEnv<OpSpec>);
+
+    impl Cfg for SimulationCfg {
+        type Spec = OpSpec;
+
+        fn chain_id(&self) -> u64 {
+            self.0.chain_id()
+        }
+
+        fn spec(&self) -> OpSpec {
+            self.0.spec()
+        }
+
+        fn blob_max_count(&self, spec_id: SpecId) -> u8 {
+            self.0.blob_max_count(spec_id)
+        }
+
+        fn max_code_size(&self) -> usize {
+            self.0.max_code_size()
+        }
+
+        fn is_eip3607_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_gas_refund_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_balance_check_disabled(&self) -> bool {
+            true
+        }
+
+        fn is_block_gas_limit_disabled(&self) -> bool {
+            true
+        }
+
+        fn is_nonce_check_disabled(&self) -> bool {
+            true
+        }
+
+        fn is_base_fee_check_disabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn simulation_without_checks() {
+        let cfg = CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::ECOTONE));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(BENCH_CALLER, AccountInfo::default());
+        let mut tx = OpTransaction::new(TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(BENCH_TARGET),
+            nonce: 5,
+            gas_limit: 200_000,
+            gas_price: 0,
+            gas_priority_fee: None,
+            ..Default::default()
+        });
+        tx.enveloped_tx = Some(Bytes::from_static(&[0xFA; 10]));
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_block(BlockEnv {
+                basefee: 10,
+                gas_limit: 100_000,
+                ..Default::default()
+            })
+            .with_tx(tx)
+            .with_cfg(cfg.clone())
+            .with_chain(L1BlockInfo {
+                l2_block: Some(0),
+                l1_base_fee: U256::from(1_000),
+                l1_base_fee_scalar: U256::from(1_000),
+                ..Default::default()
+            });
+
+        // The caller can not pay the L1 data fee, and the nonce, base fee and gas limit are invalid.
+        assert!(transact_op(&mut ctx).is_err());
+
+        let mut ctx = ctx.with_cfg(SimulationCfg(cfg));
+        let result = transact_op(&mut ctx).unwrap();
+        assert!(result.result.is_success());
+    }
+}
@@ -9,7 +9,7 @@ use inspector::{
     inspector_context::InspectorContext,
     inspector_instruction::InspectorInstructionExecutor,
     journal::{JournalExt, JournalExtGetter},
-    Inspector, InspectorCtx, InspectorHandlerImpl,
+    Inspector, InspectorCtx, InspectorHandlerImpl, TxOutcome,
 };
 use revm::{
     context::Cfg,
@@ -61,6 +61,26 @@ pub trait InspectCommitEvm<CTX, INTR: InterpreterTypes>:
     fn inspect_commit_previous<'a, 'b, INSP>(&'a mut self, inspector: INSP) -> Self::CommitOutput
     where
         INSP: Inspector<&'a mut Self, INTR> + 'b;
+
+    /// Inspects and commits `txs` in order as a single block.
+    ///
+    /// See [`inspector::exec::InspectCommitEvm::inspect_commit_block`].
+    fn inspect_commit_block<INSP>(
+        &mut self,
+        txs: impl IntoIterator<Item = Self::Transaction>,
+        mut inspector: INSP,
+    ) -> Vec<Self::CommitOutput>
+    where
+        INSP: for<'a> Inspector<&'a mut Self, INTR>,
+    {
+        inspector.block_start(&mut &mut *self);
+        let outputs = txs
+            .into_iter()
+            .map(|tx| self.inspect_commit(tx, &mut inspector))
+            .collect();
+        inspector.block_end(&mut &mut *self);
+        outputs
+    }
 }
 
 impl<
@@ -97,7 +117,8 @@ where
     // Have Cfg with OpSpec
     <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
 {
-    InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
+    ctx.tx_start();
+    let result = InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
         OpHandler::<
             CTX,
             _,
@@ -116,7 +137,9 @@ where
         >::default(),
         make_instruction_table(),
     )
-    .run(ctx)
+    .run(ctx);
+    ctx.tx_end(result.as_ref().ok().map(TxOutcome::new).as_ref());
+    result
 }
 
 pub fn inspect_op_commit<DB: Database + DatabaseCommit, CTX>(
@@ -140,3 +163,173 @@ where
         res.result
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::exec_op::transact_op, transaction::deposit::DEPOSIT_TRANSACTION_TYPE, L1BlockInfo,
+        OpSpecId, OpTransaction,
+    };
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, JournaledState, TxEnv},
+        interpreter::{
+            interpreter_types::Jumps, CallInputs, CallOutcome, InstructionResult, Interpreter,
+        },
+        primitives::{Address, TxKind, U256},
+        Context,
+    };
+    use std::{vec, vec::Vec};
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Event {
+        TxStart,
+        Step(u8),
+        Log,
+        Call(Address),
+        CallEnd(Address, InstructionResult),
+        TxEnd(bool, u64),
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl<CTX> Inspector<CTX, EthInterpreter> for Recorder {
+        fn tx_start(&mut self, _context: &mut CTX) {
+            self.events.push(Event::TxStart);
+        }
+
+        fn tx_end(&mut self, _context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+            if let Some(outcome) = outcome {
+                self.events
+                    .push(Event::TxEnd(outcome.success, outcome.gas_used));
+            }
+        }
+
+        fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+            self.events.push(Event::Step(interp.bytecode.opcode()));
+        }
+
+        fn log(
+            &mut self,
+            _interp: &mut Interpreter<EthInterpreter>,
+            _context: &mut CTX,
+            _log: &Log,
+        ) {
+            self.events.push(Event::Log);
+        }
+
+        fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+            self.events.push(Event::Call(inputs.target_address));
+            None
+        }
+
+        fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+            self.events
+                .push(Event::CallEnd(inputs.target_address, outcome.result.result));
+        }
+    }
+
+    fn context(
+        code: &[u8],
+        deposit: bool,
+    ) -> Context<
+        BlockEnv,
+        OpTransaction<TxEnv>,
+        CfgEnv<OpSpec>,
+        BenchmarkDB,
+        JournaledState<BenchmarkDB>,
+        L1BlockInfo,
+    > {
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                code.to_vec().into(),
+            )))
+            .with_tx(OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                gas_limit: 100_000,
+                gas_price: 0,
+                gas_priority_fee: None,
+                ..Default::default()
+            }))
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::REGOLITH)))
+            .with_chain(L1BlockInfo::default());
+        if deposit {
+            ctx.tx.base.tx_type = DEPOSIT_TRANSACTION_TYPE;
+            ctx.tx.deposit.mint = Some(10);
+        } else {
+            ctx.tx.enveloped_tx = Some([0xFA; 10].into());
+        }
+        ctx
+    }
+
+    fn inspect(code: &[u8], deposit: bool) -> (ResultAndState<OptimismHaltReason>, Vec<Event>) {
+        let mut ctx = context(code, deposit);
+        let mut recorder = Recorder::default();
+        let result = inspect_op(&mut InspectorContext::new(&mut ctx, &mut recorder)).unwrap();
+        (result, recorder.events)
+    }
+
+    #[test]
+    fn deposit_inspector_hooks() {
+        // LOG0(0, 0), then STOP.
+        let logs = [opcode::PUSH1, 0, opcode::DUP1, opcode::LOG0, opcode::STOP];
+        let (result, events) = inspect(&logs, true);
+        assert_eq!(result, transact_op(&mut context(&logs, true)).unwrap());
+        assert_eq!(
+            events,
+            [
+                Event::TxStart,
+                Event::Call(BENCH_TARGET),
+                Event::Step(opcode::PUSH1),
+                Event::Step(opcode::DUP1),
+                Event::Step(opcode::LOG0),
+                Event::Log,
+                Event::Step(opcode::STOP),
+                Event::CallEnd(BENCH_TARGET, InstructionResult::Stop),
+                Event::TxEnd(true, result.result.gas_used()),
+            ]
+        );
+        // The deposit is traced as a regular transaction, and mints its value.
+        assert_eq!(inspect(&logs, false).1, events);
+        assert_eq!(
+            result.state[&BENCH_CALLER].info.balance,
+            BenchmarkDB::new_bytecode(Bytecode::new())
+                .basic(BENCH_CALLER)
+                .unwrap()
+                .unwrap()
+                .balance
+                + U256::from(10)
+        );
+
+        // A halted deposit is reported as a failed deposit, to the inspector as well.
+        let (result, events) = inspect(&[opcode::INVALID], true);
+        assert_eq!(
+            result,
+            transact_op(&mut context(&[opcode::INVALID], true)).unwrap()
+        );
+        assert!(matches!(
+            result.result,
+            ExecutionResult::Halt {
+                reason: OptimismHaltReason::Deposit(_),
+                gas_used: 100_000,
+                ..
+            }
+        ));
+        assert_eq!(
+            events,
+            vec![
+                Event::TxStart,
+                Event::Call(BENCH_TARGET),
+                Event::Step(opcode::INVALID),
+                Event::CallEnd(BENCH_TARGET, InstructionResult::InvalidFEOpcode),
+                Event::TxEnd(false, 100_000),
+            ]
+        );
+    }
+}
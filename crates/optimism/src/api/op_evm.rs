@@ -0,0 +1,79 @@
+//! Constructors of a ready to use optimism EVM.
+//!
+//! The returned [`OpContext`] executes and inspects transactions with the optimism handler, see
+//! [`ExecuteEvm`][revm::ExecuteEvm] and [`InspectEvm`][inspector::exec::InspectEvm]. The
+//! precompiles are the ones of its spec, and the L1 block info is loaded from the `L1Block`
+//! predeploy by the first transaction of each block.
+use crate::{context::OpContext, L1BlockInfo, OpChainSpec, OpSpec, OpTransaction};
+use revm::{
+    context::{BlockEnv, CfgEnv, TxEnv},
+    Context, Database,
+};
+
+/// Returns an EVM of the chain on top of `db`, with the chain id and the latest hardfork of the
+/// chain.
+pub fn op_evm<DB: Database>(
+    db: DB,
+    chain_spec: &OpChainSpec,
+) -> OpContext<BlockEnv, OpTransaction<TxEnv>, CfgEnv<OpSpec>, DB> {
+    OpContext(
+        Context::default()
+            .with_db(db)
+            .with_tx(OpTransaction::default())
+            .with_cfg(
+                CfgEnv::default()
+                    .with_chain_id(chain_spec.chain_id)
+                    .with_spec(OpSpec::Op(chain_spec.latest())),
+            )
+            .with_chain(L1BlockInfo::default()),
+    )
+}
+
+/// Returns an EVM of the chain on top of `db` executing transactions of the given block, with
+/// the hardfork active at its timestamp.
+pub fn op_evm_at<DB: Database>(
+    db: DB,
+    chain_spec: &OpChainSpec,
+    block: BlockEnv,
+) -> OpContext<BlockEnv, OpTransaction<TxEnv>, CfgEnv<OpSpec>, DB> {
+    let mut evm = op_evm(db, chain_spec);
+    evm.0.cfg.spec = OpSpec::Op(chain_spec.spec_at(block.timestamp));
+    evm.0.block = block;
+    evm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OpSpecId, BASE_CHAIN_ID, BASE_CHAIN_SPEC};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        primitives::{Bytes, TxKind},
+        ExecuteEvm,
+    };
+
+    #[test]
+    fn execute_with_op_evm() {
+        let db = BenchmarkDB::new_bytecode(Bytecode::new());
+        let block = BlockEnv {
+            timestamp: 1_720_627_201,
+            ..Default::default()
+        };
+        let mut evm = op_evm_at(db, &BASE_CHAIN_SPEC, block);
+        assert_eq!(evm.0.cfg.chain_id, BASE_CHAIN_ID);
+        assert!(matches!(evm.0.cfg.spec, OpSpec::Op(OpSpecId::FJORD)));
+
+        let mut tx = OpTransaction::new(TxEnv {
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(BENCH_TARGET),
+            chain_id: Some(BASE_CHAIN_ID),
+            gas_price: 0,
+            gas_priority_fee: None,
+            ..Default::default()
+        });
+        tx.enveloped_tx = Some(Bytes::from_static(&[0xFA; 10]));
+        let result = evm.exec(tx).unwrap();
+        assert!(result.result.is_success());
+    }
+}
@@ -1,21 +1,80 @@
+//! bn128 precompiles of the optimism specs.
+//!
+//! They run with the substrate-bn implementation of the `precompile` crate, or with arkworks
+//! with the `bn128-arkworks` feature. Both backends have the same outputs, gas and errors.
 use precompile::{
-    bn128, {PrecompileError, PrecompileResult, PrecompileWithAddress},
+    bn128::{self, pair::ADDRESS},
+    u64_to_address, PrecompileError, PrecompileResult, PrecompileWithAddress,
 };
 
+#[cfg(feature = "bn128-arkworks")]
+pub mod arkworks;
+
+#[cfg(feature = "bn128-arkworks")]
+use arkworks as backend;
+#[cfg(not(feature = "bn128-arkworks"))]
+use bn128 as backend;
+
+pub mod add {
+    use super::*;
+
+    pub const ISTANBUL: PrecompileWithAddress =
+        PrecompileWithAddress(u64_to_address(6), |input, gas_limit| {
+            backend::run_add(input, bn128::add::ISTANBUL_ADD_GAS_COST, gas_limit)
+        });
+
+    pub const BYZANTIUM: PrecompileWithAddress =
+        PrecompileWithAddress(u64_to_address(6), |input, gas_limit| {
+            backend::run_add(input, bn128::add::BYZANTIUM_ADD_GAS_COST, gas_limit)
+        });
+}
+
+pub mod mul {
+    use super::*;
+
+    pub const ISTANBUL: PrecompileWithAddress =
+        PrecompileWithAddress(u64_to_address(7), |input, gas_limit| {
+            backend::run_mul(input, bn128::mul::ISTANBUL_MUL_GAS_COST, gas_limit)
+        });
+
+    pub const BYZANTIUM: PrecompileWithAddress =
+        PrecompileWithAddress(u64_to_address(7), |input, gas_limit| {
+            backend::run_mul(input, bn128::mul::BYZANTIUM_MUL_GAS_COST, gas_limit)
+        });
+}
+
 pub mod pair {
     use super::*;
 
+    pub const ISTANBUL: PrecompileWithAddress =
+        PrecompileWithAddress(ADDRESS, |input, gas_limit| {
+            backend::run_pair(
+                input,
+                bn128::pair::ISTANBUL_PAIR_PER_POINT,
+                bn128::pair::ISTANBUL_PAIR_BASE,
+                gas_limit,
+            )
+        });
+
+    pub const BYZANTIUM: PrecompileWithAddress =
+        PrecompileWithAddress(ADDRESS, |input, gas_limit| {
+            backend::run_pair(
+                input,
+                bn128::pair::BYZANTIUM_PAIR_PER_POINT,
+                bn128::pair::BYZANTIUM_PAIR_BASE,
+                gas_limit,
+            )
+        });
+
     pub const GRANITE_MAX_INPUT_SIZE: usize = 112687;
     pub const GRANITE: PrecompileWithAddress =
-        PrecompileWithAddress(bn128::pair::ADDRESS, |input, gas_limit| {
-            run_pair(input, gas_limit)
-        });
+        PrecompileWithAddress(ADDRESS, |input, gas_limit| run_pair(input, gas_limit));
 
     pub fn run_pair(input: &[u8], gas_limit: u64) -> PrecompileResult {
         if input.len() > GRANITE_MAX_INPUT_SIZE {
             return Err(PrecompileError::Bn128PairLength.into());
         }
-        bn128::run_pair(
+        backend::run_pair(
             input,
             bn128::pair::ISTANBUL_PAIR_PER_POINT,
             bn128::pair::ISTANBUL_PAIR_BASE,
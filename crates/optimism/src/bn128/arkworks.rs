@@ -0,0 +1,246 @@
+//! Arkworks backend of the bn128 precompiles, with the inputs, outputs, gas and errors of the
+//! substrate-bn implementation of the `precompile` crate.
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, BigInteger, One, PrimeField, Zero};
+use precompile::{
+    bn128::{ADD_INPUT_LEN, MUL_INPUT_LEN, PAIR_ELEMENT_LEN},
+    utilities::{bool_to_bytes32, right_pad},
+    PrecompileError, PrecompileOutput, PrecompileResult,
+};
+use std::vec::Vec;
+
+/// Reads a big endian `Fq` from the first 32 bytes of the input.
+fn read_fq(input: &[u8]) -> Result<Fq, PrecompileError> {
+    let mut limbs = [0u64; 4];
+    for (limb, word) in limbs.iter_mut().rev().zip(input[..32].chunks_exact(8)) {
+        *limb = u64::from_be_bytes(word.try_into().unwrap());
+    }
+    Fq::from_bigint(BigInt::new(limbs)).ok_or(PrecompileError::Bn128FieldPointNotAMember)
+}
+
+/// Reads a `G1` point from the first 64 bytes of the input, zero coordinates are the point at
+/// infinity.
+fn read_g1(input: &[u8]) -> Result<G1Affine, PrecompileError> {
+    let x = read_fq(&input[..32])?;
+    let y = read_fq(&input[32..64])?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    // The cofactor of G1 is one, points of the curve are in the group.
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(PrecompileError::Bn128AffineGFailedToCreate);
+    }
+    Ok(point)
+}
+
+/// Reads a `G2` point from the first 128 bytes of the input, imaginary parts first.
+fn read_g2(input: &[u8]) -> Result<G2Affine, PrecompileError> {
+    let x = Fq2::new(read_fq(&input[32..64])?, read_fq(&input[..32])?);
+    let y = Fq2::new(read_fq(&input[96..128])?, read_fq(&input[64..96])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(PrecompileError::Bn128AffineGFailedToCreate);
+    }
+    Ok(point)
+}
+
+/// Encodes a `G1` point as its big endian coordinates, zero for the point at infinity.
+fn encode_g1(point: G1Affine) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        output[..32].copy_from_slice(&x.into_bigint().to_bytes_be());
+        output[32..].copy_from_slice(&y.into_bigint().to_bytes_be());
+    }
+    output
+}
+
+/// Same as [`precompile::bn128::run_add`].
+pub fn run_add(input: &[u8], gas_cost: u64, gas_limit: u64) -> PrecompileResult {
+    if gas_cost > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let input = right_pad::<ADD_INPUT_LEN>(input);
+
+    let p1 = read_g1(&input[..64])?;
+    let p2 = read_g1(&input[64..])?;
+
+    let output = encode_g1((p1 + p2).into_affine());
+    Ok(PrecompileOutput::new(gas_cost, output.into()))
+}
+
+/// Same as [`precompile::bn128::run_mul`].
+pub fn run_mul(input: &[u8], gas_cost: u64, gas_limit: u64) -> PrecompileResult {
+    if gas_cost > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let input = right_pad::<MUL_INPUT_LEN>(input);
+
+    let p = read_g1(&input[..64])?;
+    let fr = Fr::from_be_bytes_mod_order(&input[64..96]);
+
+    let output = encode_g1((p * fr).into_affine());
+    Ok(PrecompileOutput::new(gas_cost, output.into()))
+}
+
+/// Same as [`precompile::bn128::run_pair`].
+pub fn run_pair(
+    input: &[u8],
+    pair_per_point_cost: u64,
+    pair_base_cost: u64,
+    gas_limit: u64,
+) -> PrecompileResult {
+    let gas_used = (input.len() / PAIR_ELEMENT_LEN) as u64 * pair_per_point_cost + pair_base_cost;
+    if gas_used > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    if !input.len().is_multiple_of(PAIR_ELEMENT_LEN) {
+        return Err(PrecompileError::Bn128PairLength.into());
+    }
+
+    let elements = input.len() / PAIR_ELEMENT_LEN;
+    let mut g1 = Vec::with_capacity(elements);
+    let mut g2 = Vec::with_capacity(elements);
+    for element in input.chunks_exact(PAIR_ELEMENT_LEN) {
+        g1.push(read_g1(&element[..64])?);
+        g2.push(read_g2(&element[64..])?);
+    }
+
+    let success = Bn254::multi_pairing(g1, g2).0.is_one();
+    Ok(PrecompileOutput::new(gas_used, bool_to_bytes32(success)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use precompile::{
+        bn128::{
+            self,
+            pair::{ISTANBUL_PAIR_BASE, ISTANBUL_PAIR_PER_POINT},
+        },
+        PrecompileErrors,
+    };
+    use revm::primitives::hex;
+
+    const G1: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        0000000000000000000000000000000000000000000000000000000000000002";
+    const G2: &str = "\
+        198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+        1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+        090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+        12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    // Negation of the generator of G1.
+    const NEG_G1: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    const ZERO: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000";
+    const NOT_ON_CURVE: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        0000000000000000000000000000000000000000000000000000000000000003";
+    const NOT_IN_FIELD: &str = "\
+        30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47\
+        0000000000000000000000000000000000000000000000000000000000000002";
+    const SCALARS: [&str; 4] = [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    ];
+
+    fn input(parts: &[&str]) -> Vec<u8> {
+        hex::decode(parts.concat()).unwrap()
+    }
+
+    fn assert_same(arkworks: PrecompileResult, substrate: PrecompileResult) {
+        match (arkworks, substrate) {
+            (Ok(arkworks), Ok(substrate)) => assert_eq!(arkworks, substrate),
+            (Err(PrecompileErrors::Error(arkworks)), Err(PrecompileErrors::Error(substrate))) => {
+                assert_eq!(arkworks, substrate)
+            }
+            (arkworks, substrate) => panic!("{arkworks:?} != {substrate:?}"),
+        }
+    }
+
+    #[test]
+    fn add_equivalence() {
+        let points = [G1, NEG_G1, ZERO, NOT_ON_CURVE, NOT_IN_FIELD];
+        for a in points {
+            for b in points {
+                let input = input(&[a, b]);
+                assert_same(run_add(&input, 150, 150), bn128::run_add(&input, 150, 150));
+            }
+        }
+        // Short inputs are padded with zeros.
+        let input = input(&[G1]);
+        assert_same(run_add(&input, 150, 150), bn128::run_add(&input, 150, 150));
+        assert_same(run_add(&input, 150, 149), bn128::run_add(&input, 150, 149));
+    }
+
+    #[test]
+    fn mul_equivalence() {
+        for point in [G1, NEG_G1, ZERO, NOT_ON_CURVE, NOT_IN_FIELD] {
+            for scalar in SCALARS {
+                let input = input(&[point, scalar]);
+                assert_same(
+                    run_mul(&input, 6_000, 6_000),
+                    bn128::run_mul(&input, 6_000, 6_000),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pair_equivalence() {
+        let g2_zero = [ZERO, ZERO].concat();
+        // Not in the subgroup of G2.
+        let g2_invalid = [
+            &G2[..192],
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        ]
+        .concat();
+        let inputs = [
+            input(&[]),
+            input(&[G1, G2]),
+            input(&[G1, G2, NEG_G1, G2]),
+            input(&[G1, G2, G1, G2]),
+            input(&[ZERO, G2]),
+            input(&[G1, &g2_zero]),
+            input(&[G1, &g2_invalid]),
+            input(&[NOT_ON_CURVE, G2]),
+            input(&[NOT_IN_FIELD, G2]),
+            input(&[G1, G2, G1]),
+        ];
+        for input in inputs {
+            assert_same(
+                run_pair(
+                    &input,
+                    ISTANBUL_PAIR_PER_POINT,
+                    ISTANBUL_PAIR_BASE,
+                    1_000_000,
+                ),
+                bn128::run_pair(
+                    &input,
+                    ISTANBUL_PAIR_PER_POINT,
+                    ISTANBUL_PAIR_BASE,
+                    1_000_000,
+                ),
+            );
+        }
+        assert!(
+            run_pair(&input(&[G1, G2, NEG_G1, G2]), 0, 0, 0)
+                .unwrap()
+                .bytes[31]
+                == 1
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use crate::{
-    api::exec_op::transact_op,
+    api::{exec_op::transact_op, op_evm::op_evm},
     transaction::{abstraction::OpTxGetter, OpTxTrait},
-    L1BlockInfo, L1BlockInfoGetter, OpSpec, OpSpecId, OpTransaction, OpTransactionError,
-    OptimismHaltReason,
+    L1BlockInfo, L1BlockInfoGetter, OpChainSpec, OpSpec, OpSpecId, OpTransaction,
+    OpTransactionError, OptimismHaltReason, BASE_CHAIN_SPEC, OP_MAINNET_CHAIN_SPEC,
 };
 use derive_more::derive::{AsMut, AsRef, Deref, DerefMut};
 use inspector::journal::{JournalExt, JournalExtGetter};
@@ -19,6 +19,7 @@ use revm::{
     database_interface::EmptyDB,
     handler::EthContext,
     interpreter::Host,
+    primitives::U256,
     state::EvmState,
     Context, Database, DatabaseCommit, ExecuteCommitEvm, ExecuteEvm, JournaledState,
 };
@@ -45,6 +46,26 @@ impl Default for OpContext {
 }
 
 impl OpContext {
+    /// Creates a context for OP mainnet, with its chain id and latest hardfork.
+    ///
+    /// The spec of an older block is returned by [`OP_MAINNET_CHAIN_SPEC`]'s
+    /// [`spec_at`][OpChainSpec::spec_at].
+    pub fn op_mainnet() -> Self {
+        Self::preset(&OP_MAINNET_CHAIN_SPEC)
+    }
+
+    /// Creates a context for Base mainnet, with its chain id and latest hardfork.
+    ///
+    /// The spec of an older block is returned by [`BASE_CHAIN_SPEC`]'s
+    /// [`spec_at`][OpChainSpec::spec_at].
+    pub fn base() -> Self {
+        Self::preset(&BASE_CHAIN_SPEC)
+    }
+
+    fn preset(chain_spec: &OpChainSpec) -> Self {
+        op_evm(EmptyDB::default(), chain_spec)
+    }
+
     pub fn default_ctx() -> Context<
         BlockEnv,
         OpTransaction<TxEnv>,
@@ -166,6 +187,10 @@ impl<BLOCK, TX, CFG, DB: Database, JOURNAL: Journal<Database = DB>> L1BlockInfoG
     fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo {
         self.0.l1_block_info_mut()
     }
+
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+        self.0.calculate_tx_l1_cost(input, spec_id)
+    }
 }
 
 impl<BLOCK, TX, CFG, DB: Database, JOURNAL: Journal<Database = DB>> DatabaseGetter
@@ -294,4 +319,30 @@ mod test {
         // run default tx for optimism;
         let _ = op_ctx.exec_previous();
     }
+
+    #[test]
+    fn presets() {
+        let mainnet = Context::mainnet();
+        assert_eq!(mainnet.cfg.chain_id, 1);
+        assert_eq!(
+            mainnet.cfg.spec,
+            revm::specification::hardfork::SpecId::PRAGUE
+        );
+
+        let mut base = OpContext::base();
+        assert_eq!(base.cfg.chain_id, crate::BASE_CHAIN_ID);
+        assert_eq!(
+            base.cfg.spec.into_eth_spec(),
+            OpSpecId::ISTHMUS.into_eth_spec()
+        );
+        assert_eq!(
+            BASE_CHAIN_SPEC.spec_at(1_710_374_401) as u8,
+            OpSpecId::ECOTONE as u8
+        );
+        base.modify_tx(|tx| {
+            tx.base.gas_limit = 30_000;
+            tx.base.chain_id = Some(crate::BASE_CHAIN_ID);
+        });
+        base.exec_previous().unwrap();
+    }
 }
@@ -0,0 +1,228 @@
+//! EIP-1559 base fee parameters of OP stack chains.
+//!
+//! Since Holocene, the base fee change denominator and the elasticity multiplier are set by the
+//! system config and encoded in the `extraData` of each block header, the base fee of a block is
+//! computed with the parameters of its parent.
+use crate::{OpSpec, OpSpecId};
+use core::fmt;
+
+/// Version of the Holocene `extraData` encoding.
+pub const HOLOCENE_EXTRA_DATA_VERSION: u8 = 0;
+
+/// Length of the Holocene `extraData`: the version, the denominator and the elasticity.
+pub const HOLOCENE_EXTRA_DATA_LENGTH: usize = 9;
+
+/// EIP-1559 base fee change denominator and elasticity multiplier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eip1559Params {
+    /// Bounds the amount the base fee can change between blocks.
+    pub denominator: u32,
+    /// Bounds the maximum gas limit of a block relative to its gas target.
+    pub elasticity: u32,
+}
+
+/// Parameters of OP mainnet and Base mainnet since Canyon, used until set by the system config.
+pub const CANYON_EIP1559_PARAMS: Eip1559Params = Eip1559Params {
+    denominator: 250,
+    elasticity: 6,
+};
+
+/// Error decoding the EIP-1559 parameters from the `extraData` of a Holocene block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HoloceneExtraDataError {
+    /// The `extraData` is not [`HOLOCENE_EXTRA_DATA_LENGTH`] bytes long.
+    InvalidLength(usize),
+    /// The `extraData` version is not [`HOLOCENE_EXTRA_DATA_VERSION`].
+    InvalidVersion(u8),
+    /// The denominator is zero but the elasticity is not.
+    ZeroDenominator,
+}
+
+impl fmt::Display for HoloceneExtraDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(length) => write!(
+                f,
+                "extra data is {length} bytes long, expected {HOLOCENE_EXTRA_DATA_LENGTH}"
+            ),
+            Self::InvalidVersion(version) => {
+                write!(f, "unsupported extra data version {version}")
+            }
+            Self::ZeroDenominator => {
+                f.write_str("base fee change denominator is zero with a non zero elasticity")
+            }
+        }
+    }
+}
+
+impl core::error::Error for HoloceneExtraDataError {}
+
+impl Eip1559Params {
+    /// Decodes the parameters from the `extraData` of a Holocene block.
+    ///
+    /// The `extraData` is the version byte followed by the big endian denominator and elasticity.
+    /// Zero parameters were not yet set by the system config, the `default` ones are returned.
+    pub fn decode_holocene_extra_data(
+        extra_data: &[u8],
+        default: Eip1559Params,
+    ) -> Result<Self, HoloceneExtraDataError> {
+        let extra_data: &[u8; HOLOCENE_EXTRA_DATA_LENGTH] = extra_data
+            .try_into()
+            .map_err(|_| HoloceneExtraDataError::InvalidLength(extra_data.len()))?;
+        if extra_data[0] != HOLOCENE_EXTRA_DATA_VERSION {
+            return Err(HoloceneExtraDataError::InvalidVersion(extra_data[0]));
+        }
+        let denominator = u32::from_be_bytes(extra_data[1..5].try_into().unwrap());
+        let elasticity = u32::from_be_bytes(extra_data[5..9].try_into().unwrap());
+        match (denominator, elasticity) {
+            (0, 0) => Ok(default),
+            (0, _) => Err(HoloceneExtraDataError::ZeroDenominator),
+            _ => Ok(Self {
+                denominator,
+                elasticity,
+            }),
+        }
+    }
+
+    /// Encodes the parameters as the `extraData` of a Holocene block.
+    pub fn encode_holocene_extra_data(&self) -> [u8; HOLOCENE_EXTRA_DATA_LENGTH] {
+        let mut extra_data = [HOLOCENE_EXTRA_DATA_VERSION; HOLOCENE_EXTRA_DATA_LENGTH];
+        extra_data[1..5].copy_from_slice(&self.denominator.to_be_bytes());
+        extra_data[5..9].copy_from_slice(&self.elasticity.to_be_bytes());
+        extra_data
+    }
+
+    /// Returns the base fee of the block following the parent with the given gas usage, gas limit
+    /// and base fee, as specified by [EIP-1559].
+    ///
+    /// [EIP-1559]: https://eips.ethereum.org/EIPS/eip-1559
+    pub fn next_block_base_fee(&self, gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
+        let gas_target = (gas_limit / self.elasticity.max(1) as u64) as u128;
+        let gas_used = gas_used as u128;
+        let base_fee = base_fee as u128;
+        let denominator = self.denominator.max(1) as u128;
+        if gas_target == 0 || gas_used == gas_target {
+            return base_fee as u64;
+        }
+        if gas_used > gas_target {
+            let delta = base_fee * (gas_used - gas_target) / gas_target / denominator;
+            (base_fee + delta.max(1)) as u64
+        } else {
+            let delta = base_fee * (gas_target - gas_used) / gas_target / denominator;
+            base_fee.saturating_sub(delta) as u64
+        }
+    }
+}
+
+/// Returns the base fee of the block following the given parent block.
+///
+/// If the parent is a Holocene block, the parameters are decoded from its `extraData`, otherwise
+/// the `default` parameters of the chain are used. The result is the `basefee` of the
+/// [`BlockEnv`][revm::context::BlockEnv] of the next block, and the base fee its header is
+/// validated against.
+pub fn next_block_base_fee(
+    parent_spec: OpSpec,
+    parent_extra_data: &[u8],
+    parent_gas_used: u64,
+    parent_gas_limit: u64,
+    parent_base_fee: u64,
+    default: Eip1559Params,
+) -> Result<u64, HoloceneExtraDataError> {
+    let params = if parent_spec.is_enabled_in(OpSpecId::HOLOCENE) {
+        Eip1559Params::decode_holocene_extra_data(parent_extra_data, default)?
+    } else {
+        default
+    };
+    Ok(params.next_block_base_fee(parent_gas_used, parent_gas_limit, parent_base_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holocene_extra_data() {
+        let params = Eip1559Params {
+            denominator: 250,
+            elasticity: 2,
+        };
+        let extra_data = params.encode_holocene_extra_data();
+        assert_eq!(extra_data, [0, 0, 0, 0, 250, 0, 0, 0, 2]);
+        assert_eq!(
+            Eip1559Params::decode_holocene_extra_data(&extra_data, CANYON_EIP1559_PARAMS),
+            Ok(params)
+        );
+        assert_eq!(
+            Eip1559Params::decode_holocene_extra_data(&[0; 9], CANYON_EIP1559_PARAMS),
+            Ok(CANYON_EIP1559_PARAMS)
+        );
+        assert_eq!(
+            Eip1559Params::decode_holocene_extra_data(&[0; 8], CANYON_EIP1559_PARAMS),
+            Err(HoloceneExtraDataError::InvalidLength(8))
+        );
+        assert_eq!(
+            Eip1559Params::decode_holocene_extra_data(&[1; 9], CANYON_EIP1559_PARAMS),
+            Err(HoloceneExtraDataError::InvalidVersion(1))
+        );
+        assert_eq!(
+            Eip1559Params::decode_holocene_extra_data(
+                &[0, 0, 0, 0, 0, 0, 0, 0, 1],
+                CANYON_EIP1559_PARAMS
+            ),
+            Err(HoloceneExtraDataError::ZeroDenominator)
+        );
+    }
+
+    #[test]
+    fn base_fee() {
+        let gas_limit = 30_000_000;
+        let extra_data = Eip1559Params {
+            denominator: 50,
+            elasticity: 2,
+        }
+        .encode_holocene_extra_data();
+
+        // The gas target is 15M with the parameters of the extra data.
+        let holocene = OpSpec::Op(OpSpecId::HOLOCENE);
+        let next = |gas_used| {
+            next_block_base_fee(
+                holocene,
+                &extra_data,
+                gas_used,
+                gas_limit,
+                1_000_000,
+                CANYON_EIP1559_PARAMS,
+            )
+            .unwrap()
+        };
+        assert_eq!(next(15_000_000), 1_000_000);
+        assert_eq!(next(30_000_000), 1_020_000);
+        assert_eq!(next(0), 980_000);
+
+        // Before Holocene, the extra data is ignored and the gas target is 5M.
+        let granite = OpSpec::Op(OpSpecId::GRANITE);
+        assert_eq!(
+            next_block_base_fee(
+                granite,
+                &[],
+                5_000_000,
+                gas_limit,
+                1_000_000,
+                CANYON_EIP1559_PARAMS
+            ),
+            Ok(1_000_000)
+        );
+        assert_eq!(
+            next_block_base_fee(
+                holocene,
+                &[],
+                5_000_000,
+                gas_limit,
+                1_000_000,
+                CANYON_EIP1559_PARAMS
+            ),
+            Err(HoloceneExtraDataError::InvalidLength(0))
+        );
+    }
+}
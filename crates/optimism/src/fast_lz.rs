@@ -1,9 +1,20 @@
+use std::{boxed::Box, vec::Vec};
+
 /// Returns the length of the data after compression through FastLZ, based on
 /// <https://github.com/Vectorized/solady/blob/5315d937d79b335c668896d7533ac603adac5315/js/solady.js>
 ///
 /// The u32s match op-geth's Go port:
 /// <https://github.com/ethereum-optimism/op-geth/blob/647c346e2bef36219cc7b47d76b1cb87e7ca29e4/core/types/rollup_cost.go#L411>
-pub(crate) fn flz_compress_len(input: &[u8]) -> u32 {
+///
+/// The input is only read, e.g. [`Bytes`][revm::primitives::Bytes] are passed without copy. Inputs
+/// assembled from parts are compressed with [`FlzCompressLen`].
+pub fn flz_compress_len(input: &[u8]) -> u32 {
+    flz_compress_len_with(input, &mut [0; 8192])
+}
+
+/// [`flz_compress_len`] with the given hash table, cleared first, so it is reused across inputs.
+pub(crate) fn flz_compress_len_with(input: &[u8], htab: &mut [u32; 8192]) -> u32 {
+    htab.fill(0);
     let mut idx: u32 = 2;
 
     let idx_limit: u32 = if input.len() < 13 {
@@ -16,8 +27,6 @@ pub(crate) fn flz_compress_len(input: &[u8]) -> u32 {
 
     let mut size = 0;
 
-    let mut htab = [0; 8192];
-
     while idx < idx_limit {
         let mut r: u32;
         let mut distance: u32;
@@ -50,14 +59,141 @@ pub(crate) fn flz_compress_len(input: &[u8]) -> u32 {
         let len = cmp(input, r + 3, idx + 3, idx_limit + 9);
         size = flz_match(len, size);
 
-        idx = set_next_hash(&mut htab, input, idx + len);
-        idx = set_next_hash(&mut htab, input, idx);
+        idx = set_next_hash(htab, input, idx + len);
+        idx = set_next_hash(htab, input, idx);
         anchor = idx;
     }
 
     literals(input.len() as u32 - anchor, size)
 }
 
+/// Maximum distance of a match.
+const MAX_DISTANCE: u32 = 8192;
+
+/// Streaming variant of [`flz_compress_len`], fed with consecutive chunks of the input.
+///
+/// Chunks are compressed as they are fed, as far as more input can not change the result, and only
+/// the bytes matches can refer to are kept.
+#[derive(Clone, Debug)]
+pub struct FlzCompressLen {
+    /// Input from `offset`.
+    window: Vec<u8>,
+    /// Position of the first byte of `window` in the input.
+    offset: u32,
+    /// Length of the input fed so far.
+    len: u32,
+    idx: u32,
+    anchor: u32,
+    size: u32,
+    htab: Box<[u32; 8192]>,
+}
+
+impl Default for FlzCompressLen {
+    fn default() -> Self {
+        Self {
+            window: Vec::new(),
+            offset: 0,
+            len: 0,
+            idx: 2,
+            anchor: 0,
+            size: 0,
+            htab: Box::new([0; 8192]),
+        }
+    }
+}
+
+impl FlzCompressLen {
+    /// Creates an empty compressor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk of the input.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.window.extend_from_slice(chunk);
+        self.len += chunk.len() as u32;
+        self.advance(self.len.saturating_sub(13), false);
+
+        // Drop the bytes before the window of matches, once they outweigh it.
+        let start = self.idx.saturating_sub(MAX_DISTANCE);
+        if start - self.offset > 2 * MAX_DISTANCE {
+            self.window.drain(..(start - self.offset) as usize);
+            self.offset = start;
+        }
+    }
+
+    /// Returns the length of the input after compression, see [`flz_compress_len`].
+    pub fn finish(mut self) -> u32 {
+        self.advance(self.len.saturating_sub(13), true);
+        literals(self.len - self.anchor, self.size)
+    }
+
+    /// Runs [`flz_compress_len`] up to `idx_limit`.
+    ///
+    /// If the input is not `complete`, `idx_limit` is a lower bound of the final one, and the
+    /// compression stops before a decision depending on the final limit.
+    fn advance(&mut self, idx_limit: u32, complete: bool) {
+        while self.idx < idx_limit {
+            let idx = self.idx;
+            let seq = self.u24(idx);
+            let hash = hash(seq) as usize;
+            let r = self.htab[hash];
+            if idx - r >= MAX_DISTANCE || seq != self.u24(r) {
+                self.htab[hash] = idx;
+                self.idx += 1;
+                continue;
+            }
+            if idx + 1 >= idx_limit {
+                // The match is only compressed if the input continues.
+                if complete {
+                    self.htab[hash] = idx;
+                    self.idx = idx + 1;
+                }
+                return;
+            }
+            let Some(len) = self.cmp(r + 3, idx + 3, idx_limit + 9, complete) else {
+                return;
+            };
+            self.htab[hash] = idx;
+            if idx > self.anchor {
+                self.size = literals(idx - self.anchor, self.size);
+            }
+            self.size = flz_match(len, self.size);
+
+            let idx = self.set_next_hash(idx + len);
+            self.idx = self.set_next_hash(idx);
+            self.anchor = self.idx;
+        }
+    }
+
+    /// Returns the match length of [`cmp`], or `None` if it extends to the limit of an incomplete
+    /// input.
+    fn cmp(&self, p: u32, q: u32, r: u32, complete: bool) -> Option<u32> {
+        let len = (r - q) as usize;
+        let p = &self.window[(p - self.offset) as usize..][..len];
+        let q = &self.window[(q - self.offset) as usize..][..len];
+        match mismatch(p, q) {
+            Some(l) => Some(l as u32 + 1),
+            None => complete.then_some(len as u32),
+        }
+    }
+
+    fn set_next_hash(&mut self, idx: u32) -> u32 {
+        self.htab[hash(self.u24(idx)) as usize] = idx;
+        idx + 1
+    }
+
+    fn byte(&self, idx: u32) -> u8 {
+        self.window[(idx - self.offset) as usize]
+    }
+
+    fn u24(&self, idx: u32) -> u32 {
+        u32::from(self.byte(idx))
+            + (u32::from(self.byte(idx + 1)) << 8)
+            + (u32::from(self.byte(idx + 2)) << 16)
+    }
+}
+
 fn literals(r: u32, size: u32) -> u32 {
     let size = size + 0x21 * (r / 0x20);
     let r = r % 0x20;
@@ -69,15 +205,102 @@ fn literals(r: u32, size: u32) -> u32 {
 }
 
 fn cmp(input: &[u8], p: u32, q: u32, r: u32) -> u32 {
-    let mut l = 0;
-    let mut r = r - q;
-    while l < r {
-        if input[(p + l) as usize] != input[(q + l) as usize] {
-            r = 0;
+    let len = (r - q) as usize;
+    match mismatch(&input[p as usize..][..len], &input[q as usize..][..len]) {
+        Some(l) => l as u32 + 1,
+        None => len as u32,
+    }
+}
+
+/// Returns the index of the first byte differing between two slices of the same length.
+///
+/// With the `simd` feature, slices are compared by vectors of 16 or 32 bytes.
+#[inline]
+fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 is supported by the CPU.
+            return unsafe { simd::mismatch_avx2(a, b) };
+        }
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        unsafe { simd::mismatch_sse2(a, b) }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        // SAFETY: NEON is part of the aarch64 baseline.
+        unsafe { simd::mismatch_neon(a, b) }
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        mismatch_scalar(a, b)
+    }
+}
+
+fn mismatch_scalar(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter().zip(b).position(|(a, b)| a != b)
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod simd {
+    use super::mismatch_scalar;
+
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    #[cfg(target_arch = "aarch64")]
+    use core::arch::aarch64::*;
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn mismatch_sse2(a: &[u8], b: &[u8]) -> Option<usize> {
+        let len = a.len().min(b.len());
+        let mut i = 0;
+        while i + 16 <= len {
+            let va = _mm_loadu_si128(a.as_ptr().add(i) as *const __m128i);
+            let vb = _mm_loadu_si128(b.as_ptr().add(i) as *const __m128i);
+            let eq = _mm_movemask_epi8(_mm_cmpeq_epi8(va, vb)) as u32;
+            if eq != 0xffff {
+                return Some(i + (!eq).trailing_zeros() as usize);
+            }
+            i += 16;
+        }
+        mismatch_scalar(&a[i..len], &b[i..len]).map(|l| i + l)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn mismatch_avx2(a: &[u8], b: &[u8]) -> Option<usize> {
+        let len = a.len().min(b.len());
+        let mut i = 0;
+        while i + 32 <= len {
+            let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+            let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+            let eq = _mm256_movemask_epi8(_mm256_cmpeq_epi8(va, vb)) as u32;
+            if eq != u32::MAX {
+                return Some(i + (!eq).trailing_zeros() as usize);
+            }
+            i += 32;
         }
-        l += 1;
+        mismatch_sse2(&a[i..len], &b[i..len]).map(|l| i + l)
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn mismatch_neon(a: &[u8], b: &[u8]) -> Option<usize> {
+        let len = a.len().min(b.len());
+        let mut i = 0;
+        while i + 16 <= len {
+            let va = vld1q_u8(a.as_ptr().add(i));
+            let vb = vld1q_u8(b.as_ptr().add(i));
+            // All lanes are 0xff if the vectors are equal.
+            if vminvq_u8(vceqq_u8(va, vb)) != u8::MAX {
+                return mismatch_scalar(&a[i..i + 16], &b[i..i + 16]).map(|l| i + l);
+            }
+            i += 16;
+        }
+        mismatch_scalar(&a[i..len], &b[i..len]).map(|l| i + l)
     }
-    l
 }
 
 fn flz_match(l: u32, size: u32) -> u32 {
@@ -147,6 +370,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_flz_compress_len_streaming() {
+        let sample = bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e");
+        // Pseudo random words with repetitions, longer than the window of matches.
+        let mut seed = 0x9e3779b9u32;
+        let long: Vec<u8> = (0..40_000)
+            .map(|i| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                if i % 100 < 60 {
+                    (seed % 4) as u8
+                } else {
+                    seed as u8
+                }
+            })
+            .collect();
+
+        for input in [
+            &[][..],
+            &[0; 1000],
+            &[42; 14],
+            &bytes!("FACADE"),
+            &sample,
+            &long,
+        ] {
+            let expected = flz_compress_len(input);
+            for chunk_size in [1, 2, 7, 13, 64, 1000, 9000, input.len().max(1)] {
+                let mut compressor = FlzCompressLen::new();
+                for chunk in input.chunks(chunk_size) {
+                    compressor.update(chunk);
+                }
+                assert_eq!(compressor.finish(), expected, "chunks of {chunk_size}");
+            }
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    proptest::proptest! {
+        #[test]
+        fn test_mismatch_simd(
+            a in proptest::collection::vec(proptest::num::u8::ANY, 0..200),
+            flip in proptest::num::usize::ANY,
+        ) {
+            // Equal prefixes of any length, so that every vector width and tail is compared.
+            let mut b = a.clone();
+            if !b.is_empty() {
+                let len = b.len();
+                b[flip % len] ^= 1 << (flip % 8);
+            }
+            proptest::prop_assert_eq!(mismatch(&a, &b), mismatch_scalar(&a, &b));
+            proptest::prop_assert_eq!(mismatch(&a, &a), None);
+        }
+
+        #[test]
+        fn test_flz_compress_len_simd(
+            input in proptest::collection::vec(0..8u8, 0..2000),
+            chunk in 1..64usize,
+        ) {
+            // Both paths compare matches with `mismatch`, at different window offsets.
+            let mut compressor = FlzCompressLen::new();
+            input.chunks(chunk).for_each(|chunk| compressor.update(chunk));
+            proptest::prop_assert_eq!(compressor.finish(), flz_compress_len(&input));
+        }
+    }
+
     #[rstest]
     #[case::short_hex(bytes!("FACADE"))]
     #[case::sample_contract_call(bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e"))]
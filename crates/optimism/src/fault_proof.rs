@@ -0,0 +1,559 @@
+//! Deterministic execution of a block for fault proofs.
+//!
+//! Fault proof programs, e.g. op-program running on Cannon, re-execute a block from the state root
+//! of its parent, with the preimages of the trie nodes, the code and the headers provided by an
+//! oracle. [`OracleDatabase`] reads the state by walking the tries through a [`PreimageOracle`],
+//! checks every preimage against its hash, and keeps the changes of the executed transactions in
+//! ordered maps. The execution only depends on the oracle: it does not read the time, spawn
+//! threads nor iterate over hash maps, and it does not need the standard library.
+use crate::{
+    api::exec_op::transact_op_block_with, transaction::abstraction::OpTxGetter, L1BlockInfoGetter,
+    OpReceipt, OpReceiptBuilder, OpSpec, OpTransaction, OpTransactionError,
+};
+use alloy_rlp::{Decodable, Header};
+use core::fmt;
+use revm::{
+    bytecode::Bytecode,
+    context::TxEnv,
+    context_interface::{
+        result::EVMError, transaction::TransactionSetter, Cfg, CfgGetter, DatabaseGetter,
+    },
+    database_interface::DBErrorMarker,
+    handler::EthContext,
+    primitives::{b256, keccak256, Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256},
+    state::{Account, AccountInfo},
+    Database, DatabaseCommit,
+};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Root of an empty Merkle Patricia trie.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Oracle providing the preimages of keccak256 hashes.
+pub trait PreimageOracle {
+    /// Error of the oracle.
+    type Error: core::error::Error;
+
+    /// Returns the preimage of the given keccak256 hash.
+    fn preimage(&mut self, hash: B256) -> Result<Bytes, Self::Error>;
+}
+
+/// Error reading the state from a [`PreimageOracle`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OracleDatabaseError<E> {
+    /// The oracle failed to provide a preimage.
+    Oracle(E),
+    /// The preimage returned by the oracle does not hash to the requested hash.
+    InvalidPreimage(B256),
+    /// The preimage of the hash is not a valid trie node, account, storage value or header.
+    InvalidData(B256),
+    /// The hash of a block that is not an ancestor of the executed block was requested.
+    BlockHashUnavailable(u64),
+}
+
+impl<E: fmt::Display> fmt::Display for OracleDatabaseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Oracle(error) => write!(f, "preimage oracle error: {error}"),
+            Self::InvalidPreimage(hash) => write!(f, "preimage does not match hash {hash}"),
+            Self::InvalidData(hash) => write!(f, "preimage of {hash} is malformed"),
+            Self::BlockHashUnavailable(number) => {
+                write!(f, "hash of block {number} is unavailable")
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for OracleDatabaseError<E> {}
+
+impl<E> DBErrorMarker for OracleDatabaseError<E> {}
+
+/// Account read from the state trie or written by an executed transaction.
+#[derive(Clone, Debug)]
+struct OracleAccount {
+    info: AccountInfo,
+    /// Root of the storage trie of the account before the block, [`EMPTY_ROOT_HASH`] once the
+    /// account is created or destroyed.
+    storage_root: B256,
+}
+
+/// [`Database`] reading the state of the parent of a block through a [`PreimageOracle`].
+///
+/// Accounts, storage slots and code are loaded from the state trie with the given root, the
+/// hashes of previous blocks by walking the headers back from the parent block. The changes of
+/// the committed transactions are kept in memory and shadow the trie.
+#[derive(Debug)]
+pub struct OracleDatabase<O> {
+    oracle: O,
+    state_root: B256,
+    accounts: BTreeMap<Address, Option<OracleAccount>>,
+    storage: BTreeMap<(Address, U256), U256>,
+    code: BTreeMap<B256, Bytecode>,
+    /// Hashes of the ancestors of the block, the earliest one being the next walked back from.
+    block_hashes: BTreeMap<u64, B256>,
+}
+
+impl<O: PreimageOracle> OracleDatabase<O> {
+    /// Creates a database reading the state with the given root, of the parent block with the
+    /// given number and hash.
+    pub fn new(oracle: O, state_root: B256, parent_number: u64, parent_hash: B256) -> Self {
+        Self {
+            oracle,
+            state_root,
+            accounts: BTreeMap::new(),
+            storage: BTreeMap::new(),
+            code: BTreeMap::new(),
+            block_hashes: BTreeMap::from([(parent_number, parent_hash)]),
+        }
+    }
+
+    /// Returns the accounts read or written, in address order, `None` for accounts that do not
+    /// exist.
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, Option<&AccountInfo>)> {
+        self.accounts
+            .iter()
+            .map(|(address, account)| (address, account.as_ref().map(|account| &account.info)))
+    }
+
+    /// Returns the storage slots read or written, in address and slot order.
+    pub fn storage_slots(&self) -> impl Iterator<Item = (&(Address, U256), &U256)> {
+        self.storage.iter()
+    }
+
+    /// Returns the preimage of `hash`, checking that it hashes to it.
+    fn preimage(&mut self, hash: B256) -> Result<Bytes, OracleDatabaseError<O::Error>> {
+        let preimage = self
+            .oracle
+            .preimage(hash)
+            .map_err(OracleDatabaseError::Oracle)?;
+        if keccak256(&preimage) != hash {
+            return Err(OracleDatabaseError::InvalidPreimage(hash));
+        }
+        Ok(preimage)
+    }
+
+    /// Returns the value of the trie with the given root at the given key.
+    fn trie_get(
+        &mut self,
+        root: B256,
+        key: B256,
+    ) -> Result<Option<Bytes>, OracleDatabaseError<O::Error>> {
+        if root == EMPTY_ROOT_HASH {
+            return Ok(None);
+        }
+        let invalid = || OracleDatabaseError::InvalidData(root);
+        let path: Vec<u8> = key
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xf])
+            .collect();
+        let mut depth = 0;
+        let mut node = self.preimage(root)?;
+        loop {
+            let items = rlp_list(&node).ok_or_else(invalid)?;
+            let child = match items.as_slice() {
+                [children @ .., value] if children.len() == 16 => {
+                    if depth == path.len() {
+                        let value = rlp_string(value).ok_or_else(invalid)?;
+                        return Ok((!value.is_empty()).then(|| Bytes::copy_from_slice(value)));
+                    }
+                    depth += 1;
+                    children[path[depth - 1] as usize]
+                }
+                [encoded_path, value] => {
+                    let encoded_path = rlp_string(encoded_path).ok_or_else(invalid)?;
+                    let (is_leaf, nibbles) = decode_hex_prefix(encoded_path).ok_or_else(invalid)?;
+                    if !path[depth..].starts_with(&nibbles) {
+                        return Ok(None);
+                    }
+                    depth += nibbles.len();
+                    if is_leaf {
+                        let value = rlp_string(value).ok_or_else(invalid)?;
+                        return Ok((depth == path.len()).then(|| Bytes::copy_from_slice(value)));
+                    }
+                    value
+                }
+                _ => return Err(invalid()),
+            };
+            // Children are referenced by hash, or inlined if their encoding is shorter.
+            node = match rlp_string(child) {
+                Some([]) => return Ok(None),
+                Some(hash) if hash.len() == 32 => self.preimage(B256::from_slice(hash))?,
+                Some(_) => return Err(invalid()),
+                None => Bytes::copy_from_slice(child),
+            };
+        }
+    }
+
+    /// Returns the account at the given address, loading it from the state trie.
+    fn account(
+        &mut self,
+        address: Address,
+    ) -> Result<Option<&OracleAccount>, OracleDatabaseError<O::Error>> {
+        if !self.accounts.contains_key(&address) {
+            let account = match self.trie_get(self.state_root, keccak256(address))? {
+                Some(encoded) => Some(
+                    decode_account(&encoded)
+                        .ok_or(OracleDatabaseError::InvalidData(self.state_root))?,
+                ),
+                None => None,
+            };
+            self.accounts.insert(address, account);
+        }
+        Ok(self.accounts[&address].as_ref())
+    }
+}
+
+impl<O: PreimageOracle> Database for OracleDatabase<O> {
+    type Error = OracleDatabaseError<O::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self.account(address)?.map(|account| account.info.clone()))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        if let Some(code) = self.code.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        let code = Bytecode::new_raw(self.preimage(code_hash)?);
+        self.code.insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+        let Some(storage_root) = self.account(address)?.map(|account| account.storage_root) else {
+            return Ok(U256::ZERO);
+        };
+        let value = match self.trie_get(storage_root, keccak256(index.to_be_bytes::<32>()))? {
+            Some(encoded) => U256::decode(&mut encoded.as_ref())
+                .map_err(|_| OracleDatabaseError::InvalidData(storage_root))?,
+            None => U256::ZERO,
+        };
+        self.storage.insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        // Walk the headers back from the earliest known ancestor, the parent hash is the first
+        // field of a header.
+        loop {
+            let (&earliest, &hash) = self
+                .block_hashes
+                .first_key_value()
+                .expect("parent is known");
+            if number >= earliest {
+                return self
+                    .block_hashes
+                    .get(&number)
+                    .copied()
+                    .ok_or(OracleDatabaseError::BlockHashUnavailable(number));
+            }
+            let header = self.preimage(hash)?;
+            let parent_hash = rlp_list(&header)
+                .and_then(|fields| fields.first().copied().and_then(rlp_string))
+                .filter(|parent_hash| parent_hash.len() == 32)
+                .ok_or(OracleDatabaseError::InvalidData(hash))?;
+            self.block_hashes
+                .insert(earliest - 1, B256::from_slice(parent_hash));
+        }
+    }
+}
+
+impl<O> DatabaseCommit for OracleDatabase<O> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        // Changes are applied in address order, so that the maps do not depend on the iteration
+        // order of the changes.
+        let changes: BTreeMap<Address, Account> = changes
+            .into_iter()
+            .filter(|(_, account)| account.is_touched())
+            .collect();
+        for (address, account) in changes {
+            let previous = self.accounts.get(&address).cloned().flatten();
+            let storage_root = match previous {
+                Some(previous) if !account.is_created() && !account.is_selfdestructed() => {
+                    previous.storage_root
+                }
+                _ => {
+                    self.storage
+                        .retain(|(slot_address, _), _| *slot_address != address);
+                    EMPTY_ROOT_HASH
+                }
+            };
+            if account.is_selfdestructed() {
+                self.accounts.insert(address, None);
+                continue;
+            }
+            if let Some(code) = &account.info.code {
+                self.code.insert(account.info.code_hash, code.clone());
+            }
+            for (index, slot) in account.storage {
+                self.storage.insert((address, index), slot.present_value());
+            }
+            self.accounts.insert(
+                address,
+                Some(OracleAccount {
+                    info: account.info,
+                    storage_root,
+                }),
+            );
+        }
+    }
+}
+
+/// Executes the transactions of a block with [`transact_op_block_with`], and returns their
+/// receipts.
+///
+/// The block, the configuration and the L1 block info are the ones of the context.
+pub fn execute_block<CTX>(
+    ctx: &mut CTX,
+    spec: OpSpec,
+    txs: impl IntoIterator<Item = OpTransaction<TxEnv>>,
+) -> Result<
+    Vec<OpReceipt>,
+    EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, OpTransactionError>,
+>
+where
+    CTX: EthContext
+        + OpTxGetter<OpTransaction = OpTransaction<TxEnv>>
+        + L1BlockInfoGetter
+        + TransactionSetter<Transaction = OpTransaction<TxEnv>>,
+    <CTX as DatabaseGetter>::Database: DatabaseCommit,
+    <CTX as CfgGetter>::Cfg: Cfg<Spec = OpSpec>,
+{
+    let mut builder = OpReceiptBuilder::new(spec);
+    let mut receipts = Vec::new();
+    transact_op_block_with(ctx, txs, |ctx, result| {
+        receipts.push(builder.build_with_state(ctx.op_tx(), result))
+    })?;
+    Ok(receipts)
+}
+
+/// Returns the raw items of an RLP list.
+fn rlp_list(mut buf: &[u8]) -> Option<Vec<&[u8]>> {
+    let header = Header::decode(&mut buf).ok()?;
+    if !header.list || header.payload_length != buf.len() {
+        return None;
+    }
+    let mut items = Vec::new();
+    while !buf.is_empty() {
+        let mut item = buf;
+        let header = Header::decode(&mut item).ok()?;
+        let length = buf.len() - item.len() + header.payload_length;
+        items.push(buf.get(..length)?);
+        buf = &buf[length..];
+    }
+    Some(items)
+}
+
+/// Returns the payload of an RLP string, `None` if the item is a list.
+fn rlp_string(mut item: &[u8]) -> Option<&[u8]> {
+    let header = Header::decode(&mut item).ok()?;
+    (!header.list && header.payload_length == item.len()).then_some(item)
+}
+
+/// Decodes the hex-prefix encoded path of a trie node into whether the node is a leaf and the
+/// nibbles of the path.
+fn decode_hex_prefix(encoded: &[u8]) -> Option<(bool, Vec<u8>)> {
+    let (&first, rest) = encoded.split_first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0xf);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0xf]));
+    Some((flag & 2 == 2, nibbles))
+}
+
+/// Decodes an account of the state trie: its nonce, balance, storage root and code hash.
+fn decode_account(mut encoded: &[u8]) -> Option<OracleAccount> {
+    let header = Header::decode(&mut encoded).ok()?;
+    if !header.list {
+        return None;
+    }
+    let nonce = u64::decode(&mut encoded).ok()?;
+    let balance = U256::decode(&mut encoded).ok()?;
+    let storage_root = B256::decode(&mut encoded).ok()?;
+    let code_hash = B256::decode(&mut encoded).ok()?;
+    encoded.is_empty().then(|| OracleAccount {
+        info: AccountInfo {
+            balance,
+            nonce,
+            code_hash,
+            code: None,
+        },
+        storage_root,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{transaction::deposit::DEPOSIT_TRANSACTION_TYPE, L1BlockInfo, OpSpecId};
+    use alloy_rlp::Encodable;
+    use revm::{
+        context::CfgEnv,
+        primitives::{address, bytes, TxKind},
+        Context,
+    };
+
+    #[derive(Debug, Default)]
+    struct MemoryOracle(BTreeMap<B256, Bytes>);
+
+    impl MemoryOracle {
+        /// Stores the preimage, returning the reference of a trie node to it.
+        fn insert(&mut self, preimage: Vec<u8>) -> B256 {
+            let hash = keccak256(&preimage);
+            self.0.insert(hash, preimage.into());
+            hash
+        }
+    }
+
+    impl PreimageOracle for MemoryOracle {
+        type Error = core::convert::Infallible;
+
+        fn preimage(&mut self, hash: B256) -> Result<Bytes, Self::Error> {
+            Ok(self.0.get(&hash).cloned().unwrap_or_default())
+        }
+    }
+
+    fn rlp_list_of(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload: Vec<u8> = items.concat();
+        let mut out = Vec::new();
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend(payload);
+        out
+    }
+
+    fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        bytes.encode(&mut out);
+        out
+    }
+
+    /// Returns a leaf node with the nibbles of `key` from `depth` on.
+    fn leaf(key: B256, depth: usize, value: &[u8]) -> Vec<u8> {
+        let nibbles: Vec<u8> = key
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xf])
+            .collect();
+        let nibbles = &nibbles[depth..];
+        let mut path = vec![if nibbles.len() % 2 == 1 {
+            0x30 | nibbles[0]
+        } else {
+            0x20
+        }];
+        path.extend(
+            nibbles[nibbles.len() % 2..]
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair[1]),
+        );
+        rlp_list_of(&[rlp_bytes(&path), rlp_bytes(value)])
+    }
+
+    /// Returns a trie with the values at the given keys, whose first nibbles differ.
+    fn trie(oracle: &mut MemoryOracle, entries: &[(B256, Vec<u8>)]) -> B256 {
+        let mut children = vec![rlp_bytes(&[]); 17];
+        for (key, value) in entries {
+            let hash = oracle.insert(leaf(*key, 1, value));
+            children[(key[0] >> 4) as usize] = rlp_bytes(hash.as_slice());
+        }
+        oracle.insert(rlp_list_of(&children))
+    }
+
+    fn account(nonce: u64, balance: u64, code_hash: B256) -> Vec<u8> {
+        let mut fields = vec![Vec::new(); 4];
+        nonce.encode(&mut fields[0]);
+        U256::from(balance).encode(&mut fields[1]);
+        EMPTY_ROOT_HASH.encode(&mut fields[2]);
+        code_hash.encode(&mut fields[3]);
+        rlp_list_of(&fields)
+    }
+
+    #[test]
+    fn execute_block_from_oracle() {
+        let caller = address!("00000000000000000000000000000000000000aa");
+        let counter = address!("00000000000000000000000000000000000000bb");
+        assert_ne!(keccak256(caller)[0] >> 4, keccak256(counter)[0] >> 4);
+
+        // The counter increments its first slot.
+        let mut oracle = MemoryOracle::default();
+        let code_hash = oracle.insert(bytes!("60005460010160005500").to_vec());
+        let state_root = trie(
+            &mut oracle,
+            &[
+                (keccak256(caller), account(0, 1_000, KECCAK_EMPTY)),
+                (keccak256(counter), account(1, 0, code_hash)),
+            ],
+        );
+
+        // The parent header only needs its parent hash.
+        let grandparent_hash = B256::repeat_byte(0x11);
+        let parent_hash = oracle.insert(rlp_list_of(&[rlp_bytes(grandparent_hash.as_slice())]));
+
+        let db = OracleDatabase::new(oracle, state_root, 9, parent_hash);
+        let spec = OpSpec::Op(OpSpecId::REGOLITH);
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_tx(OpTransaction::default())
+            .with_cfg(CfgEnv::default().with_spec(spec))
+            .with_chain(L1BlockInfo::default());
+        ctx.block.number = 10;
+
+        let deposit = |nonce_source: u8| {
+            let mut tx = OpTransaction::new(TxEnv {
+                tx_type: DEPOSIT_TRANSACTION_TYPE,
+                caller,
+                kind: TxKind::Call(counter),
+                gas_limit: 100_000,
+                gas_price: 0,
+                gas_priority_fee: None,
+                ..Default::default()
+            });
+            tx.deposit.source_hash = B256::repeat_byte(nonce_source);
+            tx
+        };
+        let receipts = execute_block(&mut ctx, spec, [deposit(1), deposit(2)]).unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert!(receipts.iter().all(|receipt| receipt.inner.success));
+
+        let db = &mut ctx.journaled_state.database;
+        assert_eq!(db.storage(counter, U256::ZERO), Ok(U256::from(2)));
+        assert_eq!(db.basic(caller).unwrap().unwrap().nonce, 2);
+        assert_eq!(
+            db.basic(caller).unwrap().unwrap().balance,
+            U256::from(1_000)
+        );
+        assert_eq!(db.block_hash(9), Ok(parent_hash));
+        assert_eq!(db.block_hash(8), Ok(grandparent_hash));
+        assert!(matches!(
+            db.block_hash(10),
+            Err(OracleDatabaseError::BlockHashUnavailable(10))
+        ));
+        assert!(db.accounts().map(|(address, _)| address).is_sorted());
+    }
+
+    #[test]
+    fn reject_invalid_preimage() {
+        let mut oracle = MemoryOracle::default();
+        let root = B256::repeat_byte(0x22);
+        oracle.0.insert(root, bytes!("c0"));
+        let mut db = OracleDatabase::new(oracle, root, 0, B256::ZERO);
+        assert!(matches!(
+            db.basic(Address::ZERO),
+            Err(OracleDatabaseError::InvalidPreimage(hash)) if hash == root
+        ));
+    }
+}
@@ -1,35 +1,45 @@
 //! Handler related to Optimism chain
 
+pub mod canyon;
+pub mod gas_token;
 pub mod precompiles;
 
 use crate::{
+    result::DepositHaltReason,
     transaction::{
         abstraction::OpTxGetter,
         deposit::{DepositTransaction, DEPOSIT_TRANSACTION_TYPE},
+        interop::executing_messages,
         OpTransactionError, OpTxTrait,
     },
-    L1BlockInfoGetter, OpSpec, OpSpecId, OptimismHaltReason, BASE_FEE_RECIPIENT, L1_FEE_RECIPIENT,
+    L1BlockInfo, L1BlockInfoGetter, OpSpec, OpSpecId, OptimismHaltReason, L1_BLOCK_CONTRACT,
 };
-use precompiles::OpPrecompileProvider;
+use precompiles::{CustomPrecompiles, OpPrecompileProvider};
 use revm::{
     context_interface::{
+        journaled_state::JournalDBError,
         result::{EVMError, ExecutionResult, FromStringError, InvalidTransaction, ResultAndState},
-        Block, Cfg, CfgGetter, Journal, Transaction, TransactionGetter,
+        Block, Cfg, CfgGetter, Journal, JournalGetter, Transaction, TransactionGetter,
     },
     handler::{
-        instructions::InstructionExecutor, EthContext, EthError, EthHandler, FrameContext,
-        FrameResult, MainnetHandler,
+        instructions::InstructionExecutor, validation, EthContext, EthError, EthHandler,
+        FrameContext, FrameResult, MainnetHandler,
     },
-    handler_interface::Frame,
-    interpreter::{interpreter::EthInterpreter, FrameInput, Gas, Host},
-    primitives::{hash_map::HashMap, U256},
+    handler_interface::{Frame, FrameOrResult, ItemOrResult},
+    interpreter::{
+        interpreter::EthInterpreter, CallOutcome, CallValue, CreateOutcome, FrameInput, Gas, Host,
+        InstructionResult, InterpreterResult,
+    },
+    primitives::{hash_map::HashMap, Address, Bytes, TxKind, U256},
     specification::hardfork::SpecId,
-    state::Account,
+    state::{Account, EvmStorageSlot},
     Database,
 };
 
 pub struct OpHandler<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS> {
     pub main: MainnetHandler<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS>,
+    /// Precompiles of the chain added to the ones of the optimism specs.
+    pub precompiles: CustomPrecompiles,
 }
 
 impl<CTX: Host, ERROR, FRAME, INSTRUCTIONS>
@@ -42,6 +52,16 @@ where
             main: MainnetHandler {
                 _phantom: Default::default(),
             },
+            precompiles: CustomPrecompiles::default(),
+        }
+    }
+
+    /// Returns a handler running the given precompiles in addition to, or instead of, the ones of
+    /// the optimism specs.
+    pub fn with_precompiles(precompiles: CustomPrecompiles) -> Self {
+        Self {
+            precompiles,
+            ..Self::new()
         }
     }
 }
@@ -56,14 +76,120 @@ where
     }
 }
 
+/// Destination of the base fee of transactions, see [`L1BlockInfo::base_fee_routing`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BaseFeeRouting {
+    /// The base fee is sent to the base fee vault.
+    #[default]
+    Vault,
+    /// The base fee is burned.
+    Burn,
+    /// A share of the base fee, in basis points, is sent to `recipient` and the rest to the base
+    /// fee vault.
+    Split { recipient: Address, share_bps: u16 },
+}
+
+impl BaseFeeRouting {
+    /// Returns the recipients of the base fee with the amount each receives.
+    pub fn shares(&self, base_fee: U256, vault: Address) -> [Option<(Address, U256)>; 2] {
+        match *self {
+            Self::Vault => [Some((vault, base_fee)), None],
+            Self::Burn => [None, None],
+            Self::Split {
+                recipient,
+                share_bps,
+            } => {
+                let share = base_fee * U256::from(share_bps.min(10_000)) / U256::from(10_000);
+                [Some((recipient, share)), Some((vault, base_fee - share))]
+            }
+        }
+    }
+}
+
+/// Gas accounting of deposit transactions, see [`L1BlockInfo::deposit_gas_rules`].
+///
+/// Networks that activated Regolith differently, or test networks, can force the rules of either
+/// hardfork instead of the ones of the spec.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DepositGasRules {
+    /// The Bedrock rules before Regolith, the Regolith rules after.
+    #[default]
+    Spec,
+    /// Deposits use their gas limit, system deposits no gas, and unused gas is not refunded.
+    Bedrock,
+    /// Deposits use the gas they spent, and unused gas is refunded.
+    Regolith,
+}
+
+impl DepositGasRules {
+    /// Returns `true` if deposits executed with the given spec follow the Regolith rules.
+    pub fn is_regolith(&self, spec: OpSpec) -> bool {
+        match self {
+            Self::Spec => spec.is_enabled_in(OpSpecId::REGOLITH),
+            Self::Bedrock => false,
+            Self::Regolith => true,
+        }
+    }
+}
+
+/// Returns the balance of `account` paying fees, in the gas token of the chain if it has one.
+fn fee_balance<CTX: JournalGetter + L1BlockInfoGetter>(
+    context: &mut CTX,
+    account: Address,
+) -> Result<U256, JournalDBError<CTX>> {
+    match context.l1_block_info().gas_token {
+        Some(gas_token) => gas_token.balance(context.journal(), account),
+        None => Ok(context.journal().load_account(account)?.info.balance),
+    }
+}
+
+/// Sets the balance of `account` paying fees, in the gas token of the chain if it has one.
+fn set_fee_balance<CTX: JournalGetter + L1BlockInfoGetter>(
+    context: &mut CTX,
+    account: Address,
+    balance: U256,
+) -> Result<(), JournalDBError<CTX>> {
+    match context.l1_block_info().gas_token {
+        Some(gas_token) => gas_token.set_balance(context.journal(), account, balance),
+        None => {
+            let mut account = context.journal().load_account(account)?;
+            account.mark_touch();
+            account.info.balance = balance;
+            Ok(())
+        }
+    }
+}
+
+/// Adds `amount` to the balance of `account` paying fees.
+fn add_fee_balance<CTX: JournalGetter + L1BlockInfoGetter>(
+    context: &mut CTX,
+    account: Address,
+    amount: U256,
+) -> Result<(), JournalDBError<CTX>> {
+    let balance = fee_balance(context, account)?;
+    set_fee_balance(context, account, balance.saturating_add(amount))
+}
+
 pub trait IsTxError {
     fn is_tx_error(&self) -> bool;
+
+    /// Returns the reason of the failed deposit raising this error, if known.
+    fn deposit_halt_reason(&self) -> Option<DepositHaltReason>;
 }
 
-impl<DB, TX> IsTxError for EVMError<DB, TX> {
+impl<DB> IsTxError for EVMError<DB, OpTransactionError> {
     fn is_tx_error(&self) -> bool {
         matches!(self, EVMError::Transaction(_))
     }
+
+    fn deposit_halt_reason(&self) -> Option<DepositHaltReason> {
+        match self {
+            EVMError::Transaction(OpTransactionError::FailedDeposit(reason)) => Some(*reason),
+            _ => None,
+        }
+    }
 }
 
 impl<CTX, ERROR, FRAME, INSTRUCTIONS> EthHandler
@@ -92,7 +218,7 @@ where
     type HaltReason = OptimismHaltReason;
 
     fn precompile(&self, _context: &mut Self::Context) -> Self::Precompiles {
-        OpPrecompileProvider::default()
+        OpPrecompileProvider::default().with_custom(self.precompiles.clone())
     }
 
     fn validate_env(&self, context: &Self::Context) -> Result<(), Self::Error> {
@@ -107,6 +233,15 @@ where
             }
             return Ok(());
         }
+        // Executing messages are declared in the access list, and must be executable in this block.
+        if context.cfg().spec().is_enabled_in(OpSpecId::INTEROP) {
+            if let Some(access_list) = context.tx().access_list() {
+                let block_timestamp = context.block().timestamp();
+                for message in executing_messages(access_list)? {
+                    message.validate_timestamp(block_timestamp)?;
+                }
+            }
+        }
         self.main.validate_env(context)
     }
 
@@ -114,6 +249,13 @@ where
         if context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE {
             return Ok(());
         }
+        if let Some(gas_token) = context.l1_block_info().gas_token {
+            // The fees and the value are paid with the balance of the caller in the gas token.
+            let caller = context.tx().caller();
+            let mut account = context.journal().load_account_code(caller)?.info.clone();
+            account.balance = gas_token.balance(context.journal(), caller)?;
+            return validation::validate_tx_against_account(&account, context).map_err(From::from);
+        }
         self.main.validate_tx_against_state(context)
     }
 
@@ -121,11 +263,18 @@ where
         // The L1-cost fee is only computed for Optimism non-deposit transactions.
         let spec = context.cfg().spec();
         if context.tx().tx_type() != DEPOSIT_TRANSACTION_TYPE {
-            let l1_block_info: crate::L1BlockInfo =
-                super::L1BlockInfo::try_fetch(context.db(), spec)?;
-
-            // Storage L1 block info for later use.
-            *context.l1_block_info_mut() = l1_block_info;
+            // The L1 block info is set by the deposits at the start of the block, so it is only
+            // fetched for the first transaction of each block.
+            let block_number = context.block().number();
+            if context.l1_block_info().l2_block != Some(block_number) {
+                let mut l1_block_info: crate::L1BlockInfo =
+                    super::L1BlockInfo::try_fetch(context.db(), spec)?;
+                l1_block_info.l2_block = Some(block_number);
+                l1_block_info.keep_config(context.l1_block_info());
+
+                // Storage L1 block info for later use.
+                *context.l1_block_info_mut() = l1_block_info;
+            }
         }
 
         self.main.load_accounts(context)
@@ -139,11 +288,18 @@ where
         // in wei to the caller's balance. This should be persisted to the database
         // prior to the rest of execution.
         let mut tx_l1_cost = U256::ZERO;
+        let mut operator_fee_charge = U256::ZERO;
         if is_deposit {
             let tx = context.op_tx();
             if let Some(mint) = tx.mint() {
-                let mut caller_account = context.journal().load_account(caller)?;
-                caller_account.info.balance += U256::from(mint);
+                let balance = fee_balance(context, caller)?;
+                let minted = balance.checked_add(U256::from(mint)).ok_or(
+                    OpTransactionError::FailedDeposit(DepositHaltReason::MintOverflow {
+                        mint,
+                        balance,
+                    }),
+                )?;
+                set_fee_balance(context, caller, minted)?;
             }
         } else {
             let enveloped_tx = context
@@ -151,44 +307,203 @@ where
                 .enveloped_tx()
                 .expect("all not deposit tx have enveloped tx")
                 .clone();
-            tx_l1_cost = context
-                .l1_block_info()
-                .calculate_tx_l1_cost(&enveloped_tx, context.cfg().spec());
+            let spec = context.cfg().spec();
+            tx_l1_cost = context.calculate_tx_l1_cost(&enveloped_tx, spec);
+            // The operator fee is charged for the gas limit, unused gas is refunded with the
+            // rest of the unused gas.
+            if spec.is_enabled_in(OpSpecId::ISTHMUS) {
+                let gas_limit = U256::from(context.tx().gas_limit());
+                operator_fee_charge = context
+                    .l1_block_info()
+                    .operator_fee_charge(&enveloped_tx, gas_limit);
+            }
         }
 
         // We deduct caller max balance after minting and before deducing the
         // L1 cost, max values is already checked in pre_validate but L1 cost wasn't.
-        self.main.deduct_caller(context)?;
+        if context.l1_block_info().gas_token.is_some() {
+            // The gas is bought with the gas token, only the nonce of the caller account changes.
+            let basefee = context.block().basefee() as u128;
+            let effective_gas_price = context.tx().effective_gas_price(basefee);
+            let gas_cost = (context.tx().gas_limit() as u128).saturating_mul(effective_gas_price);
+            let is_call = context.tx().kind().is_call();
+            let mut caller_account = context.journal().load_account(caller)?;
+            // Bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
+            if is_call {
+                caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+            }
+            caller_account.mark_touch();
+            let balance = fee_balance(context, caller)?;
+            set_fee_balance(
+                context,
+                caller,
+                balance.saturating_sub(U256::from(gas_cost)),
+            )?;
+        } else {
+            self.main.deduct_caller(context)?;
+        }
 
-        // If the transaction is not a deposit transaction, subtract the L1 data fee from the
-        // caller's balance directly after minting the requested amount of ETH.
+        // If the transaction is not a deposit transaction, subtract the L1 data fee and the
+        // operator fee from the caller's balance directly after minting the requested amount of ETH.
         if !is_deposit {
-            let mut caller_account = context.journal().load_account(caller)?;
+            let is_balance_check_disabled = context.cfg().is_balance_check_disabled();
+            let balance = fee_balance(context, caller)?;
+            let fee = tx_l1_cost.saturating_add(operator_fee_charge);
 
-            if tx_l1_cost > caller_account.info.balance {
+            if fee > balance && !is_balance_check_disabled {
                 return Err(InvalidTransaction::LackOfFundForMaxFee {
-                    fee: tx_l1_cost.into(),
-                    balance: caller_account.info.balance.into(),
+                    fee: fee.into(),
+                    balance: balance.into(),
                 }
                 .into());
             }
-            caller_account.info.balance = caller_account.info.balance.saturating_sub(tx_l1_cost);
+            set_fee_balance(context, caller, balance.saturating_sub(fee))?;
         }
         Ok(())
     }
 
+    fn frame_init_first(
+        &mut self,
+        context: &mut Self::Context,
+        frame_context: &mut <Self::Frame as Frame>::FrameContext,
+        mut frame_input: <Self::Frame as Frame>::FrameInit,
+    ) -> Result<FrameOrResult<Self::Frame>, Self::Error> {
+        let value = context.tx().value();
+        let Some(gas_token) = context.l1_block_info().gas_token else {
+            return self
+                .main
+                .frame_init_first(context, frame_context, frame_input);
+        };
+        if value.is_zero() {
+            return self
+                .main
+                .frame_init_first(context, frame_context, frame_input);
+        }
+
+        // The value of the transaction is moved in the gas token before the first frame, which
+        // only reports it to the called contract. Init code reads a zero call value.
+        let caller = context.tx().caller();
+        let (recipient, out_of_funds) = match &mut frame_input {
+            FrameInput::Call(inputs) => {
+                inputs.value = CallValue::Apparent(value);
+                let result = InterpreterResult::new(
+                    InstructionResult::OutOfFunds,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                );
+                (
+                    inputs.target_address,
+                    FrameResult::Call(CallOutcome::new(result, 0..0)),
+                )
+            }
+            FrameInput::Create(inputs) => {
+                inputs.value = U256::ZERO;
+                let nonce = context.journal().load_account(caller)?.info.nonce;
+                let result = InterpreterResult::new(
+                    InstructionResult::OutOfFunds,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                );
+                (
+                    caller.create(nonce),
+                    FrameResult::Create(CreateOutcome::new(result, None)),
+                )
+            }
+            // EOF is not enabled by the optimism specs.
+            FrameInput::EOFCreate(_) => {
+                return self
+                    .main
+                    .frame_init_first(context, frame_context, frame_input)
+            }
+        };
+        if !gas_token.transfer(context.journal(), caller, recipient, value)? {
+            return Ok(ItemOrResult::Result(out_of_funds));
+        }
+        self.main
+            .frame_init_first(context, frame_context, frame_input)
+    }
+
+    fn frame_init(
+        &mut self,
+        frame: &Self::Frame,
+        context: &mut Self::Context,
+        frame_context: &mut <Self::Frame as Frame>::FrameContext,
+        frame_input: <Self::Frame as Frame>::FrameInit,
+    ) -> Result<FrameOrResult<Self::Frame>, Self::Error> {
+        if context.l1_block_info().gas_token.is_none() {
+            return self
+                .main
+                .frame_init(frame, context, frame_context, frame_input);
+        }
+
+        // Values are held in the gas token, which calls and creates do not move. Frames moving
+        // a value fail as if the caller could not pay it, and the gas they were given is returned.
+        let out_of_funds = |gas_limit| {
+            InterpreterResult::new(
+                InstructionResult::OutOfFunds,
+                Bytes::new(),
+                Gas::new(gas_limit),
+            )
+        };
+        let result = match &frame_input {
+            FrameInput::Call(inputs) if inputs.transfers_value() => {
+                FrameResult::Call(CallOutcome::new(
+                    out_of_funds(inputs.gas_limit),
+                    inputs.return_memory_offset.clone(),
+                ))
+            }
+            FrameInput::Create(inputs) if !inputs.value.is_zero() => {
+                FrameResult::Create(CreateOutcome::new(out_of_funds(inputs.gas_limit), None))
+            }
+            FrameInput::EOFCreate(inputs) if !inputs.value.is_zero() => {
+                FrameResult::EOFCreate(CreateOutcome::new(out_of_funds(inputs.gas_limit), None))
+            }
+            _ => {
+                return self
+                    .main
+                    .frame_init(frame, context, frame_context, frame_input)
+            }
+        };
+        Ok(ItemOrResult::Result(result))
+    }
+
     fn last_frame_result(
         &self,
         context: &mut Self::Context,
         _frame_context: &mut <Self::Frame as Frame>::FrameContext,
         frame_result: &mut <Self::Frame as Frame>::FrameResult,
     ) -> Result<(), Self::Error> {
+        let instruction_result = frame_result.interpreter_result().result;
+
+        // The value moved in the gas token before the first frame is returned if the frame failed,
+        // unless the caller could not pay it and it was not moved.
+        let value = context.tx().value();
+        if let Some(gas_token) = context.l1_block_info().gas_token {
+            if !value.is_zero()
+                && !instruction_result.is_ok()
+                && instruction_result != InstructionResult::OutOfFunds
+            {
+                let caller = context.tx().caller();
+                let recipient = match context.tx().kind() {
+                    TxKind::Call(target) => target,
+                    // The nonce of the caller was bumped by the frame creating the contract.
+                    TxKind::Create => {
+                        let nonce = context.journal().load_account(caller)?.info.nonce;
+                        caller.create(nonce.saturating_sub(1))
+                    }
+                };
+                gas_token.transfer(context.journal(), recipient, caller, value)?;
+            }
+        }
+
         let tx = context.tx();
         let is_deposit = tx.tx_type() == DEPOSIT_TRANSACTION_TYPE;
         let tx_gas_limit = tx.gas_limit();
-        let is_regolith = context.cfg().spec().is_enabled_in(OpSpecId::REGOLITH);
+        let is_regolith = context
+            .l1_block_info()
+            .deposit_gas_rules
+            .is_regolith(context.cfg().spec());
 
-        let instruction_result = frame_result.interpreter_result().result;
         let gas = frame_result.gas_mut();
         let remaining = gas.remaining();
         let refunded = gas.refunded();
@@ -215,13 +530,6 @@ where
                 // Regolith, gas is reported as normal.
                 gas.erase_cost(remaining);
                 gas.record_refund(refunded);
-            } else if is_deposit {
-                let tx = context.op_tx();
-                if tx.is_system_transaction() {
-                    // System transactions were a special type of deposit transaction in
-                    // the Bedrock hardfork that did not incur any gas costs.
-                    gas.erase_cost(tx_gas_limit);
-                }
             }
         } else if instruction_result.is_revert() {
             // On Optimism, deposit transactions report gas usage uniquely to other
@@ -240,6 +548,13 @@ where
                 gas.erase_cost(remaining);
             }
         }
+
+        // System transactions were a special type of deposit transaction in the Bedrock
+        // hardfork that did not incur any gas costs, whether they succeeded or not: they do not
+        // consume the gas of the block, and their receipts report no gas used.
+        if is_deposit && !is_regolith && context.op_tx().is_system_transaction() {
+            gas.erase_cost(tx_gas_limit);
+        }
         Ok(())
     }
 
@@ -252,7 +567,10 @@ where
         exec_result.gas_mut().record_refund(eip7702_refund);
 
         let is_deposit = context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
-        let is_regolith = context.cfg().spec().is_enabled_in(OpSpecId::REGOLITH);
+        let is_regolith = context
+            .l1_block_info()
+            .deposit_gas_rules
+            .is_regolith(context.cfg().spec());
 
         // Prior to Regolith, deposit transactions did not receive gas refunds.
         let is_gas_refund_disabled = is_deposit && !is_regolith;
@@ -263,43 +581,98 @@ where
         }
     }
 
+    fn reimburse_caller(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        let caller = context.tx().caller();
+        if context.l1_block_info().gas_token.is_some() {
+            // Return the gas token paying for the unused gas.
+            let basefee = context.block().basefee() as u128;
+            let effective_gas_price = context.tx().effective_gas_price(basefee);
+            let gas = exec_result.gas();
+            let reimbursed = effective_gas_price
+                .saturating_mul((gas.remaining() + gas.refunded() as u64) as u128);
+            add_fee_balance(context, caller, U256::from(reimbursed))?;
+        } else {
+            self.main.reimburse_caller(context, exec_result)?;
+        }
+
+        if context.tx().tx_type() != DEPOSIT_TRANSACTION_TYPE {
+            // Refund the operator fee charged for the unused gas.
+            let operator_fee_refund = context
+                .l1_block_info()
+                .operator_fee_refund(exec_result.gas(), context.cfg().spec());
+            add_fee_balance(context, caller, operator_fee_refund)?;
+        }
+        Ok(())
+    }
+
     fn reward_beneficiary(
         &self,
         context: &mut Self::Context,
         exec_result: &mut <Self::Frame as Frame>::FrameResult,
     ) -> Result<(), Self::Error> {
-        self.main.reward_beneficiary(context, exec_result)?;
+        if context.l1_block_info().gas_token.is_some() {
+            // Pay the priority fee to the beneficiary in the gas token.
+            let basefee = context.block().basefee() as u128;
+            let effective_gas_price = context.tx().effective_gas_price(basefee);
+            let coinbase_gas_price = if context.cfg().spec().is_enabled_in(SpecId::LONDON) {
+                effective_gas_price.saturating_sub(basefee)
+            } else {
+                effective_gas_price
+            };
+            let gas = exec_result.gas();
+            let reward =
+                coinbase_gas_price.saturating_mul((gas.spent() - gas.refunded() as u64) as u128);
+            let beneficiary = context.block().beneficiary();
+            add_fee_balance(context, beneficiary, U256::from(reward))?;
+        } else {
+            self.main.reward_beneficiary(context, exec_result)?;
+        }
 
         let is_deposit = context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
 
         // Transfer fee to coinbase/beneficiary.
         if !is_deposit {
-            self.main.reward_beneficiary(context, exec_result)?;
             let basefee = context.block().basefee() as u128;
 
             // If the transaction is not a deposit transaction, fees are paid out
             // to both the Base Fee Vault as well as the L1 Fee Vault.
-            let l1_block_info = context.l1_block_info();
-
             let Some(enveloped_tx) = &context.op_tx().enveloped_tx() else {
                 return Err(ERROR::from_string(
                     "[OPTIMISM] Failed to load enveloped transaction.".into(),
                 ));
             };
 
-            let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, context.cfg().spec());
+            let spec = context.cfg().spec();
+            let l1_cost = context.calculate_tx_l1_cost(enveloped_tx, spec);
+            let fee_recipients = context.l1_block_info().fee_recipients;
+            let gas_used = exec_result.gas().spent() - exec_result.gas().refunded() as u64;
+            let operator_fee_cost =
+                context
+                    .l1_block_info()
+                    .operator_fee_cost(enveloped_tx, gas_used, spec);
 
             // Send the L1 cost of the transaction to the L1 Fee Vault.
-            let mut l1_fee_vault_account = context.journal().load_account(L1_FEE_RECIPIENT)?;
-            l1_fee_vault_account.mark_touch();
-            l1_fee_vault_account.info.balance += l1_cost;
-
-            // Send the base fee of the transaction to the Base Fee Vault.
-            let mut base_fee_vault_account = context.journal().load_account(BASE_FEE_RECIPIENT)?;
-            base_fee_vault_account.mark_touch();
-            base_fee_vault_account.info.balance += U256::from(basefee.saturating_mul(
-                (exec_result.gas().spent() - exec_result.gas().refunded() as u64) as u128,
-            ));
+            add_fee_balance(context, fee_recipients.l1_fee, l1_cost)?;
+
+            // Send the base fee of the transaction to the Base Fee Vault, or as routed by the chain.
+            let base_fee = U256::from(basefee.saturating_mul(gas_used as u128));
+            let base_fee_routing = context.l1_block_info().base_fee_routing;
+            for (recipient, amount) in base_fee_routing
+                .shares(base_fee, fee_recipients.base_fee)
+                .into_iter()
+                .flatten()
+            {
+                add_fee_balance(context, recipient, amount)?;
+            }
+
+            // Send the operator fee of the transaction to the Operator Fee Vault, post-Isthmus.
+            if let Some(operator_fee_cost) = operator_fee_cost {
+                add_fee_balance(context, fee_recipients.operator_fee, operator_fee_cost)?;
+            }
         }
         Ok(())
     }
@@ -311,13 +684,38 @@ where
     ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
         let result = self.main.output(context, result)?;
         let result = result.map_haltreason(OptimismHaltReason::Base);
-        if result.result.is_halt() {
+        let is_deposit = context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
+        if let ExecutionResult::Halt {
+            reason: OptimismHaltReason::Base(reason),
+            ..
+        } = &result.result
+        {
             // Post-regolith, if the transaction is a deposit transaction and it halts,
             // we bubble up to the global return handler. The mint value will be persisted
             // and the caller nonce will be incremented there.
-            let is_deposit = context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
             if is_deposit && context.cfg().spec().is_enabled_in(OpSpecId::REGOLITH) {
-                return Err(ERROR::from(OpTransactionError::HaltedDepositPostRegolith));
+                let reason = DepositHaltReason::from_halt(*reason, context.tx().gas_limit());
+                return Err(ERROR::from(OpTransactionError::FailedDeposit(reason)));
+            }
+        }
+
+        // The L1 attributes deposit at the start of each block writes the L1 block info, which is
+        // decoded from its calldata instead of being fetched by the next transaction.
+        let writes_l1_block = result
+            .state
+            .get(&L1_BLOCK_CONTRACT)
+            .is_some_and(|account| account.storage.values().any(|slot| slot.is_changed()));
+        if is_deposit && writes_l1_block {
+            let block_number = context.block().number();
+            let spec = context.cfg().spec();
+            match L1BlockInfo::from_calldata(context.tx().input(), spec) {
+                Ok(mut l1_block_info) => {
+                    l1_block_info.l2_block = Some(block_number);
+                    l1_block_info.keep_config(context.l1_block_info());
+                    *context.l1_block_info_mut() = l1_block_info;
+                }
+                // Other writes are fetched from the database by the next transaction.
+                Err(_) => context.l1_block_info_mut().reset(),
             }
         }
         Ok(result)
@@ -333,7 +731,10 @@ where
         let is_deposit = context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
         end_output.or_else(|err| {
             if err.is_tx_error() && is_deposit {
-                let spec = context.cfg().spec();
+                let is_regolith = context
+                    .l1_block_info()
+                    .deposit_gas_rules
+                    .is_regolith(context.cfg().spec());
                 let tx = context.op_tx();
                 let caller = tx.caller();
                 let mint = tx.mint();
@@ -348,29 +749,49 @@ where
 
                 // Increment sender nonce and account balance for the mint amount. Deposits
                 // always persist the mint amount, even if the transaction fails.
+                let loaded = context.db().basic(caller);
+                let reason = match &loaded {
+                    Err(_) => {
+                        OptimismHaltReason::Deposit(DepositHaltReason::AccountTouch { caller })
+                    }
+                    Ok(_) => err.deposit_halt_reason().map_or(
+                        OptimismHaltReason::FailedDeposit,
+                        OptimismHaltReason::Deposit,
+                    ),
+                };
+                let mint = U256::from(mint.unwrap_or_default());
+                let gas_token = context.l1_block_info().gas_token;
                 let account = {
-                    let mut acc = Account::from(
-                        context
-                            .db()
-                            .basic(caller)
-                            .unwrap_or_default()
-                            .unwrap_or_default(),
-                    );
+                    let mut acc = Account::from(loaded.unwrap_or_default().unwrap_or_default());
                     acc.info.nonce = acc.info.nonce.saturating_add(1);
-                    acc.info.balance = acc
-                        .info
-                        .balance
-                        .saturating_add(U256::from(mint.unwrap_or_default()));
+                    if gas_token.is_none() {
+                        acc.info.balance = acc.info.balance.saturating_add(mint);
+                    }
                     acc.mark_touch();
                     acc
                 };
-                let state = HashMap::from_iter([(caller, account)]);
+                let mut state = HashMap::from_iter([(caller, account)]);
+
+                // Chains with a gas token mint it to the caller instead.
+                if let Some(gas_token) = gas_token.filter(|_| !mint.is_zero()) {
+                    let slot = gas_token.balance_slot(caller);
+                    let info = context.db().basic(gas_token.address);
+                    let balance = context.db().storage(gas_token.address, slot);
+                    let mut token = Account::from(info.unwrap_or_default().unwrap_or_default());
+                    let balance = balance.unwrap_or_default();
+                    token.storage.insert(
+                        slot,
+                        EvmStorageSlot::new_changed(balance, balance.saturating_add(mint)),
+                    );
+                    token.mark_touch();
+                    state.insert(gas_token.address, token);
+                }
 
                 // The gas used of a failed deposit post-regolith is the gas
                 // limit of the transaction. pre-regolith, it is the gas limit
                 // of the transaction for non system transactions and 0 for system
                 // transactions.
-                let gas_used = if spec.is_enabled_in(OpSpecId::REGOLITH) || !is_system_tx {
+                let gas_used = if is_regolith || !is_system_tx {
                     gas_limit
                 } else {
                     0
@@ -378,8 +799,11 @@ where
 
                 Ok(ResultAndState {
                     result: ExecutionResult::Halt {
-                        reason: OptimismHaltReason::FailedDeposit,
+                        reason,
                         gas_used,
+                        // Deposits do not pay for gas.
+                        effective_gas_price: 0,
+                        debug: None,
                     },
                     state,
                 })
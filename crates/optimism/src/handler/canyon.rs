@@ -0,0 +1,165 @@
+//! Irregular state change of the Canyon hardfork.
+//!
+//! Before its transactions, the first Canyon block deploys the create2deployer contract at its
+//! canonical address if the code there is not already the one of the contract. The balance, the
+//! nonce and the storage of the account are kept.
+use crate::{OpChainSpec, OpSpecId};
+use core::fmt;
+use revm::{
+    bytecode::Bytecode,
+    database_interface::{Database, DatabaseCommit},
+    primitives::{address, b256, hash_map::HashMap, Address, B256},
+    state::Account,
+};
+
+/// Canonical address of the create2deployer contract.
+pub const CREATE2_DEPLOYER_ADDR: Address = address!("13b0D85CcB8bf860b6b79AF3029fCA081AE9beF2");
+
+/// Hash of the code of the create2deployer contract.
+pub const CREATE2_DEPLOYER_CODEHASH: B256 =
+    b256!("b0550b5b431e30d38000efb7107aaa0ade03d48a7198a140edda9d27134468b2");
+
+/// Error deploying the create2deployer contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Create2DeployerError<E> {
+    /// The database failed to load the account.
+    Database(E),
+    /// The given code does not hash to [`CREATE2_DEPLOYER_CODEHASH`].
+    InvalidCode(B256),
+}
+
+impl<E: fmt::Display> fmt::Display for Create2DeployerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(error) => write!(f, "database error: {error}"),
+            Self::InvalidCode(hash) => {
+                write!(
+                    f,
+                    "create2deployer code has hash {hash}, expected {CREATE2_DEPLOYER_CODEHASH}"
+                )
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for Create2DeployerError<E> {}
+
+/// Deploys the create2deployer contract with the given `code` if the block with the given number
+/// and timestamp is the first Canyon block of the chain, and returns `true` if the code was set.
+///
+/// Called before the first transaction of each block. The code is not shipped with the crate, it
+/// is checked against [`CREATE2_DEPLOYER_CODEHASH`] when the contract is deployed.
+pub fn ensure_create2_deployer<DB: Database + DatabaseCommit>(
+    db: &mut DB,
+    chain_spec: &OpChainSpec,
+    number: u64,
+    timestamp: u64,
+    parent_timestamp: u64,
+    code: &Bytecode,
+) -> Result<bool, Create2DeployerError<DB::Error>> {
+    if !chain_spec.is_activation_block(OpSpecId::CANYON, number, timestamp, parent_timestamp) {
+        return Ok(false);
+    }
+    let code_hash = code.hash_slow();
+    if code_hash != CREATE2_DEPLOYER_CODEHASH {
+        return Err(Create2DeployerError::InvalidCode(code_hash));
+    }
+    deploy_code(db, CREATE2_DEPLOYER_ADDR, code, code_hash).map_err(Create2DeployerError::Database)
+}
+
+/// Sets the code of the account, unless it already has the code with the given hash.
+fn deploy_code<DB: Database + DatabaseCommit>(
+    db: &mut DB,
+    address: Address,
+    code: &Bytecode,
+    code_hash: B256,
+) -> Result<bool, DB::Error> {
+    let info = db.basic(address)?.unwrap_or_default();
+    if info.code_hash == code_hash {
+        return Ok(false);
+    }
+    let mut account = Account::from(info);
+    account.info.code_hash = code_hash;
+    account.info.code = Some(code.clone());
+    account.mark_touch();
+    db.commit(HashMap::from_iter([(address, account)]));
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OP_MAINNET_CHAIN_SPEC;
+    use database::CacheDB;
+    use revm::{
+        database_interface::EmptyDB,
+        primitives::{bytes, U256},
+        state::AccountInfo,
+    };
+
+    const CANYON_TIME: u64 = 1_704_992_401;
+
+    #[test]
+    fn canyon_activation_block() {
+        let spec = OP_MAINNET_CHAIN_SPEC;
+        assert!(spec.is_activation_block(OpSpecId::CANYON, 0, CANYON_TIME, CANYON_TIME - 2));
+        assert!(spec.is_activation_block(OpSpecId::CANYON, 0, CANYON_TIME + 1, CANYON_TIME - 1));
+        assert!(!spec.is_activation_block(OpSpecId::CANYON, 0, CANYON_TIME + 2, CANYON_TIME));
+        assert!(!spec.is_activation_block(OpSpecId::CANYON, 0, CANYON_TIME - 1, CANYON_TIME - 3));
+        assert!(spec.is_activation_block(OpSpecId::REGOLITH, 105_235_063, 0, 0));
+        assert!(!spec.is_activation_block(OpSpecId::INTEROP, 0, u64::MAX, 0));
+    }
+
+    #[test]
+    fn deploy_create2_deployer() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            CREATE2_DEPLOYER_ADDR,
+            AccountInfo::from_balance(U256::from(7)),
+        );
+
+        // Outside of the first Canyon block, the state is not changed.
+        let code = Bytecode::new_legacy(bytes!("6001600055"));
+        assert_eq!(
+            ensure_create2_deployer(
+                &mut db,
+                &OP_MAINNET_CHAIN_SPEC,
+                0,
+                CANYON_TIME + 2,
+                CANYON_TIME,
+                &code
+            ),
+            Ok(false)
+        );
+        // Other code than the one of the contract is rejected.
+        assert_eq!(
+            ensure_create2_deployer(
+                &mut db,
+                &OP_MAINNET_CHAIN_SPEC,
+                0,
+                CANYON_TIME,
+                CANYON_TIME - 2,
+                &code
+            ),
+            Err(Create2DeployerError::InvalidCode(code.hash_slow()))
+        );
+
+        // The code is set once, the balance of the account is kept.
+        let code_hash = code.hash_slow();
+        assert_eq!(
+            deploy_code(&mut db, CREATE2_DEPLOYER_ADDR, &code, code_hash),
+            Ok(true)
+        );
+        let info = db.basic(CREATE2_DEPLOYER_ADDR).unwrap().unwrap();
+        assert_eq!(info.code_hash, code_hash);
+        assert_eq!(info.balance, U256::from(7));
+        assert_eq!(
+            db.code_by_hash(code_hash).unwrap().original_bytes(),
+            code.original_bytes()
+        );
+        assert_eq!(
+            deploy_code(&mut db, CREATE2_DEPLOYER_ADDR, &code, code_hash),
+            Ok(false)
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Balances of chains paying fees in an ERC-20 token.
+//!
+//! The balances are the ones of the token predeploy, read from and written to its storage. Moving
+//! them does not emit `Transfer` events, like native fees do not.
+use revm::{
+    context_interface::Journal,
+    primitives::{keccak256, Address, U256},
+    Database,
+};
+
+/// ERC-20 predeploy whose balances pay the fees, the deposit mints and the values of the
+/// transactions of a chain, see [`L1BlockInfo::gas_token`][crate::L1BlockInfo::gas_token].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasToken {
+    /// Address of the token.
+    pub address: Address,
+    /// Storage slot of the `mapping(address => uint256)` of the balances of the token.
+    pub balances_slot: U256,
+}
+
+impl GasToken {
+    /// Returns the storage slot of the balance of `account`, as laid out by Solidity mappings.
+    pub fn balance_slot(&self, account: Address) -> U256 {
+        let mut preimage = [0; 64];
+        preimage[12..32].copy_from_slice(account.as_slice());
+        preimage[32..].copy_from_slice(&self.balances_slot.to_be_bytes::<32>());
+        U256::from_be_bytes(keccak256(preimage).0)
+    }
+
+    /// Returns the balance of `account`.
+    pub fn balance<J: Journal>(
+        &self,
+        journal: &mut J,
+        account: Address,
+    ) -> Result<U256, <J::Database as Database>::Error> {
+        journal.load_account(self.address)?;
+        Ok(journal
+            .sload(self.address, self.balance_slot(account))?
+            .data)
+    }
+
+    /// Sets the balance of `account`.
+    pub fn set_balance<J: Journal>(
+        &self,
+        journal: &mut J,
+        account: Address,
+        balance: U256,
+    ) -> Result<(), <J::Database as Database>::Error> {
+        journal.load_account(self.address)?;
+        journal.touch_account(self.address);
+        journal.sstore(self.address, self.balance_slot(account), balance)?;
+        Ok(())
+    }
+
+    /// Moves `value` from the balance of `from` to the one of `to`.
+    ///
+    /// Returns `false`, without changing the balances, if `from` does not have `value`.
+    pub fn transfer<J: Journal>(
+        &self,
+        journal: &mut J,
+        from: Address,
+        to: Address,
+        value: U256,
+    ) -> Result<bool, <J::Database as Database>::Error> {
+        let Some(from_balance) = self.balance(journal, from)?.checked_sub(value) else {
+            return Ok(false);
+        };
+        self.set_balance(journal, from, from_balance)?;
+        let to_balance = self.balance(journal, to)?;
+        self.set_balance(journal, to, to_balance.saturating_add(value))?;
+        Ok(true)
+    }
+}
@@ -1,21 +1,89 @@
 use crate::{OpSpec, OpSpecId};
 use once_cell::race::OnceBox;
-use precompile::{secp256r1, PrecompileErrors, Precompiles};
+use precompile::{
+    secp256r1, Address, PrecompileErrors, PrecompileFn, PrecompileSpecId, PrecompileWithAddress,
+    Precompiles,
+};
 use revm::{
-    context::Cfg, context_interface::CfgGetter, handler::EthPrecompileProvider,
-    handler_interface::PrecompileProvider, interpreter::InterpreterResult,
+    context::Cfg,
+    context_interface::CfgGetter,
+    handler::EthPrecompileProvider,
+    handler_interface::PrecompileProvider,
+    interpreter::{Gas, InstructionResult, InterpreterResult},
+    primitives::Bytes,
     specification::hardfork::SpecId,
 };
-use std::boxed::Box;
+use std::{boxed::Box, vec::Vec};
+
+/// Precompiles added to the ones of the optimism specs, or replacing them, from the spec they
+/// are registered for.
+///
+/// Lets chains shipping extra precompiles, e.g. of other signature schemes, run them with the
+/// optimism handler, see [`OpHandler::with_precompiles`][super::OpHandler::with_precompiles].
+#[derive(Clone, Debug, Default)]
+pub struct CustomPrecompiles {
+    precompiles: Vec<(OpSpec, PrecompileWithAddress)>,
+}
+
+impl CustomPrecompiles {
+    /// Returns an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the precompile in the given spec and the later ones. It replaces the precompile
+    /// of the spec and the ones registered before at its address.
+    pub fn insert(&mut self, spec: impl Into<OpSpec>, precompile: PrecompileWithAddress) {
+        self.precompiles.push((spec.into(), precompile));
+    }
+
+    /// Returns the registry with the precompile registered, see [`CustomPrecompiles::insert`].
+    pub fn with(mut self, spec: impl Into<OpSpec>, precompile: PrecompileWithAddress) -> Self {
+        self.insert(spec, precompile);
+        self
+    }
+
+    /// Returns `true` if no precompile is registered.
+    pub fn is_empty(&self) -> bool {
+        self.precompiles.is_empty()
+    }
+
+    /// Returns the precompile registered at the address in the given spec.
+    pub fn get(&self, spec: OpSpec, address: &Address) -> Option<PrecompileFn> {
+        self.precompiles
+            .iter()
+            .rev()
+            .find(|(from, precompile)| precompile.0 == *address && spec.is_enabled_in(*from))
+            .map(|(_, precompile)| precompile.1)
+    }
+
+    /// Returns the addresses of the precompiles registered in the given spec, without duplicates.
+    fn addresses(&self, spec: OpSpec) -> impl Iterator<Item = Address> + '_ {
+        self.precompiles
+            .iter()
+            .enumerate()
+            .filter(move |(index, (from, precompile))| {
+                spec.is_enabled_in(*from)
+                    && !self.precompiles[..*index]
+                        .iter()
+                        .any(|(from, other)| other.0 == precompile.0 && spec.is_enabled_in(*from))
+            })
+            .map(|(_, (_, precompile))| precompile.0)
+    }
+}
 
 pub struct OpPrecompileProvider<CTX, ERROR> {
     precompile_provider: EthPrecompileProvider<CTX, ERROR>,
+    spec: OpSpec,
+    custom: CustomPrecompiles,
 }
 
 impl<CTX, ERROR> Clone for OpPrecompileProvider<CTX, ERROR> {
     fn clone(&self) -> Self {
         Self {
             precompile_provider: self.precompile_provider.clone(),
+            spec: self.spec,
+            custom: self.custom.clone(),
         }
     }
 }
@@ -27,9 +95,18 @@ impl<CTX, ERROR> OpPrecompileProvider<CTX, ERROR> {
                 precompiles,
                 _phantom: core::marker::PhantomData,
             },
+            spec: OpSpec::Op(OpSpecId::ISTHMUS),
+            custom: CustomPrecompiles::default(),
         }
     }
 
+    /// Returns the provider with the given precompiles added to the ones of its spec, kept when
+    /// the spec is set.
+    pub fn with_custom(mut self, custom: CustomPrecompiles) -> Self {
+        self.custom = custom;
+        self
+    }
+
     #[inline]
     pub fn new_with_spec(spec: OpSpec) -> Self {
         match spec {
@@ -55,27 +132,53 @@ impl<CTX, ERROR> OpPrecompileProvider<CTX, ERROR> {
                 | SpecId::CANCUN,
             )
             | OpSpec::Op(
-                OpSpecId::BEDROCK
-                | OpSpecId::REGOLITH
-                | OpSpecId::CANYON
-                | OpSpecId::ECOTONE
-                | OpSpecId::HOLOCENE
-                | OpSpecId::ISTHMUS,
-            )) => Self::new(Precompiles::new(spec.into_eth_spec().into())),
+                OpSpecId::BEDROCK | OpSpecId::REGOLITH | OpSpecId::CANYON | OpSpecId::ECOTONE,
+            )) => Self::new(eth(spec.into_eth_spec().into())),
             OpSpec::Op(OpSpecId::FJORD) => Self::new(fjord()),
-            OpSpec::Op(OpSpecId::GRANITE)
+            // The BLS12-381 precompiles added by Isthmus are not supported yet.
+            OpSpec::Op(
+                OpSpecId::GRANITE | OpSpecId::HOLOCENE | OpSpecId::ISTHMUS | OpSpecId::INTEROP,
+            )
             | OpSpec::Eth(SpecId::PRAGUE | SpecId::OSAKA | SpecId::LATEST) => Self::new(granite()),
         }
+        .with_spec(spec)
+    }
+
+    fn with_spec(mut self, spec: OpSpec) -> Self {
+        self.spec = spec;
+        self
     }
 }
 
-/// Returns precompiles for Fjor spec.
+/// Returns the precompiles of the Ethereum spec, with the bn128 precompiles of the backend
+/// selected by features, see [`crate::bn128`].
+fn eth(spec: PrecompileSpecId) -> &'static Precompiles {
+    #[cfg(feature = "bn128-arkworks")]
+    {
+        use crate::bn128::{add, mul, pair};
+
+        static INSTANCES: [OnceBox<Precompiles>; 7] = [const { OnceBox::new() }; 7];
+        INSTANCES[spec as usize].get_or_init(|| {
+            let mut precompiles = Precompiles::new(spec).clone();
+            if spec >= PrecompileSpecId::ISTANBUL {
+                precompiles.extend([add::ISTANBUL, mul::ISTANBUL, pair::ISTANBUL]);
+            } else if spec >= PrecompileSpecId::BYZANTIUM {
+                precompiles.extend([add::BYZANTIUM, mul::BYZANTIUM, pair::BYZANTIUM]);
+            }
+            Box::new(precompiles)
+        })
+    }
+    #[cfg(not(feature = "bn128-arkworks"))]
+    Precompiles::new(spec)
+}
+
+/// Returns precompiles for Fjord spec.
 pub fn fjord() -> &'static Precompiles {
     static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
     INSTANCE.get_or_init(|| {
-        let mut precompiles = Precompiles::cancun().clone();
+        let mut precompiles = eth(PrecompileSpecId::CANCUN).clone();
         // EIP-7212: secp256r1 P256verify
-        precompiles.extend([crate::bn128::pair::GRANITE]);
+        precompiles.extend([secp256r1::P256VERIFY]);
         Box::new(precompiles)
     })
 }
@@ -84,9 +187,9 @@ pub fn fjord() -> &'static Precompiles {
 pub fn granite() -> &'static Precompiles {
     static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
     INSTANCE.get_or_init(|| {
-        let mut precompiles = Precompiles::cancun().clone();
+        let mut precompiles = fjord().clone();
         // Restrict bn256Pairing input size
-        precompiles.extend([secp256r1::P256VERIFY]);
+        precompiles.extend([crate::bn128::pair::GRANITE]);
         Box::new(precompiles)
     })
 }
@@ -104,7 +207,8 @@ where
 
     #[inline]
     fn set_spec(&mut self, spec: Self::Spec) {
-        *self = Self::new_with_spec(spec);
+        let custom = core::mem::take(&mut self.custom);
+        *self = Self::new_with_spec(spec).with_custom(custom);
     }
 
     #[inline]
@@ -115,19 +219,60 @@ where
         bytes: &precompile::Bytes,
         gas_limit: u64,
     ) -> Result<Option<Self::Output>, Self::Error> {
-        self.precompile_provider
-            .run(context, address, bytes, gas_limit)
+        match self.custom.get(self.spec, address) {
+            Some(precompile) => run_custom(precompile, bytes, gas_limit).map(Some),
+            None => self
+                .precompile_provider
+                .run(context, address, bytes, gas_limit),
+        }
     }
 
     #[inline]
     fn warm_addresses(&self) -> Box<impl Iterator<Item = precompile::Address> + '_> {
-        self.precompile_provider.warm_addresses()
+        let custom = self
+            .custom
+            .addresses(self.spec)
+            .filter(|address| !self.precompile_provider.contains(address));
+        Box::new(self.precompile_provider.warm_addresses().chain(custom))
     }
 
     #[inline]
     fn contains(&self, address: &precompile::Address) -> bool {
-        self.precompile_provider.contains(address)
+        self.precompile_provider.contains(address) || self.custom.get(self.spec, address).is_some()
+    }
+}
+
+/// Runs a custom precompile like the precompiles of the spec are run.
+fn run_custom<ERROR: From<PrecompileErrors>>(
+    precompile: PrecompileFn,
+    bytes: &Bytes,
+    gas_limit: u64,
+) -> Result<InterpreterResult, ERROR> {
+    let mut result = InterpreterResult {
+        result: InstructionResult::Return,
+        gas: Gas::new(gas_limit),
+        output: Bytes::new(),
+        halt_debug: None,
+    };
+    match precompile(bytes, gas_limit) {
+        Ok(output) => {
+            if result.gas.record_cost(output.gas_used) {
+                result.output = output.bytes;
+            } else {
+                result.gas.spend_all();
+                result.result = InstructionResult::PrecompileOOG;
+            }
+        }
+        Err(PrecompileErrors::Error(error)) => {
+            result.result = if error.is_oog() {
+                InstructionResult::PrecompileOOG
+            } else {
+                InstructionResult::PrecompileError
+            };
+        }
+        Err(error @ PrecompileErrors::Fatal { .. }) => return Err(error.into()),
     }
+    Ok(result)
 }
 
 impl<CTX, ERROR> Default for OpPrecompileProvider<CTX, ERROR> {
@@ -135,3 +280,40 @@ impl<CTX, ERROR> Default for OpPrecompileProvider<CTX, ERROR> {
         Self::new_with_spec(OpSpec::Op(OpSpecId::ISTHMUS))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn128::pair::GRANITE_MAX_INPUT_SIZE;
+    use precompile::{bn128, PrecompileError};
+    use std::vec;
+
+    #[test]
+    fn granite_pairing_limit() {
+        // Points at infinity pair fine, but the input is above the Granite limit.
+        let input = Bytes::from(vec![0; 587 * bn128::PAIR_ELEMENT_LEN]);
+        assert!(input.len() > GRANITE_MAX_INPUT_SIZE);
+        let run_pair = |spec: OpSpecId| {
+            let provider = OpPrecompileProvider::<(), ()>::new_with_spec(spec.into());
+            let pair = provider
+                .precompile_provider
+                .precompiles
+                .get(&bn128::pair::ADDRESS)
+                .unwrap();
+            pair(&input, u64::MAX)
+        };
+
+        assert!(run_pair(OpSpecId::FJORD).is_ok());
+        for spec in [
+            OpSpecId::GRANITE,
+            OpSpecId::HOLOCENE,
+            OpSpecId::ISTHMUS,
+            OpSpecId::INTEROP,
+        ] {
+            assert!(matches!(
+                run_pair(spec),
+                Err(PrecompileErrors::Error(PrecompileError::Bn128PairLength))
+            ));
+        }
+    }
+}
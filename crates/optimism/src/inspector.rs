@@ -0,0 +1,244 @@
+//! WithdrawalInspector. Inspector that collects the withdrawals initiated on the L2ToL1MessagePasser
+//! predeploy.
+//!
+//! Withdrawals are decoded from the `MessagePassed` events of successful transactions, events of
+//! reverted calls are not part of the transaction logs and are skipped.
+pub mod interop;
+
+use inspector::{Inspector, TxOutcome};
+use revm::{
+    interpreter::InterpreterTypes,
+    primitives::{address, b256, Address, Bytes, Log, B256, U256},
+};
+use std::vec::Vec;
+
+/// Address of the L2ToL1MessagePasser predeploy.
+pub const L2_TO_L1_MESSAGE_PASSER: Address = address!("4200000000000000000000000000000000000016");
+
+/// Topic of the `MessagePassed(uint256,address,address,uint256,uint256,bytes,bytes32)` event.
+pub const MESSAGE_PASSED_TOPIC: B256 =
+    b256!("02a52367d10742d8032712c1bb8e0144ff1ec5ffda1ed7d70bb05a2744955054");
+
+/// Withdrawal initiated on the L2ToL1MessagePasser predeploy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Nonce of the message, with the message version in its two most significant bytes.
+    pub nonce: U256,
+    /// Sender of the message on L2.
+    pub sender: Address,
+    /// Target of the message on L1.
+    pub target: Address,
+    /// Value withdrawn to the target.
+    pub value: U256,
+    /// Minimum gas limit of the call to the target.
+    pub gas_limit: U256,
+    /// Calldata of the call to the target.
+    pub data: Bytes,
+    /// Hash of the withdrawal, proven on L1.
+    pub withdrawal_hash: B256,
+}
+
+impl Withdrawal {
+    /// Decodes the withdrawal of a `MessagePassed` event of the L2ToL1MessagePasser predeploy.
+    ///
+    /// Returns `None` if the log is not such an event or is malformed.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        if log.address != L2_TO_L1_MESSAGE_PASSER {
+            return None;
+        }
+        let [topic, nonce, sender, target] = log.topics() else {
+            return None;
+        };
+        if *topic != MESSAGE_PASSED_TOPIC {
+            return None;
+        }
+
+        // Data is `abi.encode(value, gasLimit, data, withdrawalHash)`.
+        let payload = &log.data.data;
+        let word = |index: usize| payload.get(index * 32..(index + 1) * 32);
+        let offset = usize::try_from(U256::from_be_slice(word(2)?)).ok()?;
+        let len_word = payload.get(offset..offset.checked_add(32)?)?;
+        let len = usize::try_from(U256::from_be_slice(len_word)).ok()?;
+        let data = payload.get(offset + 32..(offset + 32).checked_add(len)?)?;
+
+        Some(Self {
+            nonce: U256::from_be_bytes(nonce.0),
+            sender: Address::from_word(*sender),
+            target: Address::from_word(*target),
+            value: U256::from_be_slice(word(0)?),
+            gas_limit: U256::from_be_slice(word(1)?),
+            data: Bytes::copy_from_slice(data),
+            withdrawal_hash: B256::from_slice(word(3)?),
+        })
+    }
+}
+
+/// Inspector that collects the [`Withdrawal`]s of the inspected transactions, in order.
+#[derive(Clone, Debug, Default)]
+pub struct WithdrawalInspector {
+    withdrawals: Vec<Withdrawal>,
+}
+
+impl WithdrawalInspector {
+    /// Returns a new inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the withdrawals collected so far.
+    pub fn withdrawals(&self) -> &[Withdrawal] {
+        &self.withdrawals
+    }
+
+    /// Returns the withdrawals collected so far and clears them.
+    pub fn take_withdrawals(&mut self) -> Vec<Withdrawal> {
+        core::mem::take(&mut self.withdrawals)
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for WithdrawalInspector {
+    fn tx_end(&mut self, _context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+        let Some(outcome) = outcome else {
+            return;
+        };
+        self.withdrawals
+            .extend(outcome.logs.iter().filter_map(Withdrawal::from_log));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::inspect::inspect_op_commit, L1BlockInfo, OpSpec, OpSpecId, OpTransaction};
+    use database::{CacheDB, BENCH_CALLER};
+    use inspector::inspector_context::InspectorContext;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        context::{BlockEnv, CfgEnv, TxEnv},
+        database_interface::EmptyDB,
+        primitives::{keccak256, Bytes, LogData, TxKind},
+        state::AccountInfo,
+        Context,
+    };
+
+    fn withdrawal() -> Withdrawal {
+        Withdrawal {
+            nonce: U256::from(1) << 240 | U256::from(5),
+            sender: address!("00000000000000000000000000000000000000aa"),
+            target: address!("00000000000000000000000000000000000000bb"),
+            value: U256::from(1000),
+            gas_limit: U256::from(21_000),
+            data: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+            withdrawal_hash: B256::repeat_byte(0x11),
+        }
+    }
+
+    fn message_passed(withdrawal: &Withdrawal) -> LogData {
+        let mut data = Vec::new();
+        data.extend_from_slice(&withdrawal.value.to_be_bytes::<32>());
+        data.extend_from_slice(&withdrawal.gas_limit.to_be_bytes::<32>());
+        data.extend_from_slice(&U256::from(128).to_be_bytes::<32>());
+        data.extend_from_slice(withdrawal.withdrawal_hash.as_slice());
+        data.extend_from_slice(&U256::from(withdrawal.data.len()).to_be_bytes::<32>());
+        data.extend_from_slice(&withdrawal.data);
+        data.resize(data.len().next_multiple_of(32), 0);
+        LogData::new_unchecked(
+            vec![
+                MESSAGE_PASSED_TOPIC,
+                withdrawal.nonce.into(),
+                withdrawal.sender.into_word(),
+                withdrawal.target.into_word(),
+            ],
+            data.into(),
+        )
+    }
+
+    /// Code that emits the given event and ends with `last`.
+    fn emitting(log: &LogData, last: u8) -> Bytecode {
+        let mut code = Vec::new();
+        for (index, word) in log.data.chunks(32).enumerate() {
+            code.push(opcode::PUSH32);
+            code.extend_from_slice(word);
+            code.extend_from_slice(&[opcode::PUSH2, 0, (index * 32) as u8, opcode::MSTORE]);
+        }
+        for topic in log.topics().iter().rev() {
+            code.push(opcode::PUSH32);
+            code.extend_from_slice(topic.as_slice());
+        }
+        code.extend_from_slice(&[opcode::PUSH2, 0, log.data.len() as u8]);
+        code.extend_from_slice(&[opcode::PUSH1, 0, opcode::LOG4]);
+        code.extend_from_slice(&[opcode::PUSH1, 0, opcode::DUP1, last]);
+        Bytecode::new_legacy(code.into())
+    }
+
+    #[test]
+    fn message_passed_topic() {
+        assert_eq!(
+            keccak256("MessagePassed(uint256,address,address,uint256,uint256,bytes,bytes32)"),
+            MESSAGE_PASSED_TOPIC
+        );
+    }
+
+    #[test]
+    fn decode_withdrawal() {
+        let withdrawal = withdrawal();
+        let log = Log {
+            address: L2_TO_L1_MESSAGE_PASSER,
+            data: message_passed(&withdrawal),
+        };
+        assert_eq!(Withdrawal::from_log(&log), Some(withdrawal));
+
+        let mut other = log.clone();
+        other.address = Address::ZERO;
+        assert_eq!(Withdrawal::from_log(&other), None);
+        let mut truncated = log.clone();
+        truncated.data.data = log.data.data.slice(..140);
+        assert_eq!(Withdrawal::from_log(&truncated), None);
+    }
+
+    #[test]
+    fn collect_withdrawals() {
+        let withdrawal = withdrawal();
+        let log = message_passed(&withdrawal);
+        let reverting = address!("0000000000000000000000000000000000000aaa");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            L2_TO_L1_MESSAGE_PASSER,
+            AccountInfo::from_bytecode(emitting(&log, opcode::RETURN)),
+        );
+        db.insert_account_info(
+            reverting,
+            AccountInfo::from_bytecode(emitting(&log, opcode::REVERT)),
+        );
+        db.insert_account_info(BENCH_CALLER, AccountInfo::default());
+
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_block(BlockEnv::default())
+            .with_tx(OpTransaction::<TxEnv>::default())
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::BEDROCK)))
+            .with_chain(L1BlockInfo::default());
+        let mut inspector = WithdrawalInspector::new();
+
+        for (nonce, target) in [(0, L2_TO_L1_MESSAGE_PASSER), (1, reverting)] {
+            let mut tx = OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(target),
+                gas_price: 0,
+                gas_priority_fee: None,
+                nonce,
+                ..Default::default()
+            });
+            tx.enveloped_tx = Some(Bytes::from_static(&[0xFA; 10]));
+            ctx.tx = tx;
+            let result =
+                inspect_op_commit(&mut InspectorContext::new(&mut ctx, &mut inspector)).unwrap();
+            assert_eq!(result.is_success(), nonce == 0);
+        }
+
+        // The event of the reverted call is skipped.
+        assert_eq!(inspector.take_withdrawals(), vec![withdrawal]);
+        assert!(inspector.withdrawals().is_empty());
+    }
+}
@@ -0,0 +1,338 @@
+//! InteropInspector. Inspector that checks the invariants of interop messages and SuperchainERC20
+//! transfers of the inspected transactions.
+//!
+//! Invariants are checked on the events of successful transactions, in order:
+//! - the `L2ToL2CrossDomainMessenger` relays a message only after the `CrossL2Inbox` validated an
+//!   executing message sent by the messenger of the source chain,
+//! - a SuperchainERC20 is minted cross chain only while such a message is relayed, and burnt
+//!   cross chain only before a message is sent,
+//! - cross chain mints and burns are made by the `SuperchainTokenBridge`.
+use crate::transaction::interop::CROSS_L2_INBOX_ADDRESS;
+use core::fmt;
+use inspector::{Inspector, TxOutcome};
+use revm::{
+    interpreter::InterpreterTypes,
+    primitives::{address, b256, Address, Log, B256, U256},
+};
+use std::vec::Vec;
+
+/// The address of the L2ToL2CrossDomainMessenger contract.
+pub const L2_TO_L2_CROSS_DOMAIN_MESSENGER: Address =
+    address!("4200000000000000000000000000000000000023");
+
+/// The address of the SuperchainTokenBridge contract.
+pub const SUPERCHAIN_TOKEN_BRIDGE: Address = address!("4200000000000000000000000000000000000028");
+
+/// Topic of the `ExecutingMessage(bytes32,(address,uint256,uint256,uint256,uint256))` event of
+/// the `CrossL2Inbox`.
+pub const EXECUTING_MESSAGE_TOPIC: B256 =
+    b256!("5c37832d2e8d10e346e55ad62071a6a2f9fa5130614ef2ec6617555c6f467ba7");
+
+/// Topic of the `SentMessage(uint256,address,uint256,address,bytes)` event of the
+/// `L2ToL2CrossDomainMessenger`.
+pub const SENT_MESSAGE_TOPIC: B256 =
+    b256!("382409ac69001e11931a28435afef442cbfd20d9891907e8fa373ba7d351f320");
+
+/// Topic of the `RelayedMessage(uint256,uint256,bytes32)` event of the
+/// `L2ToL2CrossDomainMessenger`.
+pub const RELAYED_MESSAGE_TOPIC: B256 =
+    b256!("5948076590932b9d173029c7df03fe386e755a61c86c7fe2671011a2faa2a379");
+
+/// Topic of the `CrosschainMint(address,uint256,address)` event of SuperchainERC20 tokens.
+pub const CROSSCHAIN_MINT_TOPIC: B256 =
+    b256!("de22baff038e3a3e08407cbdf617deed74e869a7ba517df611e33131c6e6ea04");
+
+/// Topic of the `CrosschainBurn(address,uint256,address)` event of SuperchainERC20 tokens.
+pub const CROSSCHAIN_BURN_TOPIC: B256 =
+    b256!("b90795a66650155983e242cac3e1ac1a4dc26f8ed2987f3ce416a34e00111fd4");
+
+/// Violation of an interop invariant, see [`check_interop_logs`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteropViolation {
+    /// A message was relayed without an executing message of the messenger validated before.
+    RelayWithoutExecutingMessage {
+        /// Chain id of the source chain of the message.
+        source: U256,
+        /// Nonce of the message.
+        nonce: U256,
+    },
+    /// A token was minted cross chain outside of a relayed message.
+    UnpairedMint {
+        token: Address,
+        to: Address,
+        amount: U256,
+    },
+    /// A token was burnt cross chain without a message sent after.
+    UnpairedBurn {
+        token: Address,
+        from: Address,
+        amount: U256,
+    },
+    /// A token was minted or burnt cross chain by another caller than the
+    /// `SuperchainTokenBridge`.
+    UnauthorizedSender { token: Address, sender: Address },
+}
+
+impl fmt::Display for InteropViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelayWithoutExecutingMessage { source, nonce } => write!(
+                f,
+                "message {nonce} of chain {source} relayed without executing message"
+            ),
+            Self::UnpairedMint { token, to, amount } => {
+                write!(f, "{amount} of {token} minted to {to} outside of a relay")
+            }
+            Self::UnpairedBurn {
+                token,
+                from,
+                amount,
+            } => write!(f, "{amount} of {token} burnt from {from} without message"),
+            Self::UnauthorizedSender { token, sender } => {
+                write!(f, "{token} minted or burnt cross chain by {sender}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for InteropViolation {}
+
+/// Checks the interop invariants on the logs of a successful transaction, and returns the
+/// violations in the order of the logs.
+pub fn check_interop_logs(logs: &[Log]) -> Vec<InteropViolation> {
+    let mut violations = Vec::new();
+    // Executing messages of the messenger validated and not relayed yet.
+    let mut validated = 0usize;
+    let mut burns = Vec::new();
+
+    for log in logs {
+        let topics = log.topics();
+        let Some(topic) = topics.first() else {
+            continue;
+        };
+        match (log.address, *topic) {
+            (CROSS_L2_INBOX_ADDRESS, EXECUTING_MESSAGE_TOPIC) => {
+                // The identifier starts with the origin of the initiating message.
+                let origin = log.data.data.get(12..32).map(Address::from_slice);
+                if origin == Some(L2_TO_L2_CROSS_DOMAIN_MESSENGER) {
+                    validated += 1;
+                }
+            }
+            (L2_TO_L2_CROSS_DOMAIN_MESSENGER, RELAYED_MESSAGE_TOPIC) if topics.len() > 2 => {
+                if validated == 0 {
+                    violations.push(InteropViolation::RelayWithoutExecutingMessage {
+                        source: topics[1].into(),
+                        nonce: topics[2].into(),
+                    });
+                } else {
+                    validated -= 1;
+                }
+            }
+            (L2_TO_L2_CROSS_DOMAIN_MESSENGER, SENT_MESSAGE_TOPIC) => {
+                burns.pop();
+            }
+            (token, CROSSCHAIN_MINT_TOPIC | CROSSCHAIN_BURN_TOPIC) if topics.len() == 3 => {
+                let account = Address::from_word(topics[1]);
+                let sender = Address::from_word(topics[2]);
+                let amount = log
+                    .data
+                    .data
+                    .get(..32)
+                    .map(U256::from_be_slice)
+                    .unwrap_or_default();
+                if sender != SUPERCHAIN_TOKEN_BRIDGE {
+                    violations.push(InteropViolation::UnauthorizedSender { token, sender });
+                }
+                if *topic == CROSSCHAIN_BURN_TOPIC {
+                    burns.push(InteropViolation::UnpairedBurn {
+                        token,
+                        from: account,
+                        amount,
+                    });
+                } else if validated == 0 {
+                    violations.push(InteropViolation::UnpairedMint {
+                        token,
+                        to: account,
+                        amount,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    violations.extend(burns);
+    violations
+}
+
+/// Inspector that collects the [`InteropViolation`]s of the inspected transactions, in order.
+#[derive(Clone, Debug, Default)]
+pub struct InteropInspector {
+    violations: Vec<InteropViolation>,
+}
+
+impl InteropInspector {
+    /// Returns a new inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the violations found so far.
+    pub fn violations(&self) -> &[InteropViolation] {
+        &self.violations
+    }
+
+    /// Returns the violations found so far and clears them.
+    pub fn take_violations(&mut self) -> Vec<InteropViolation> {
+        core::mem::take(&mut self.violations)
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for InteropInspector {
+    fn tx_end(&mut self, _context: &mut CTX, outcome: Option<&TxOutcome<'_>>) {
+        let Some(outcome) = outcome else {
+            return;
+        };
+        self.violations.extend(check_interop_logs(outcome.logs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{keccak256, LogData};
+
+    const TOKEN: Address = address!("00000000000000000000000000000000000000aa");
+    const USER: Address = address!("00000000000000000000000000000000000000bb");
+
+    fn log(address: Address, topics: Vec<B256>, data: Vec<u8>) -> Log {
+        Log {
+            address,
+            data: LogData::new_unchecked(topics, data.into()),
+        }
+    }
+
+    fn executing_message(origin: Address) -> Log {
+        let mut data = origin.into_word().to_vec();
+        data.resize(5 * 32, 0);
+        log(
+            CROSS_L2_INBOX_ADDRESS,
+            vec![EXECUTING_MESSAGE_TOPIC, B256::ZERO],
+            data,
+        )
+    }
+
+    fn relayed_message(nonce: u64) -> Log {
+        log(
+            L2_TO_L2_CROSS_DOMAIN_MESSENGER,
+            vec![
+                RELAYED_MESSAGE_TOPIC,
+                U256::from(10).into(),
+                U256::from(nonce).into(),
+                B256::ZERO,
+            ],
+            Vec::new(),
+        )
+    }
+
+    fn sent_message() -> Log {
+        log(
+            L2_TO_L2_CROSS_DOMAIN_MESSENGER,
+            vec![SENT_MESSAGE_TOPIC, B256::ZERO, B256::ZERO, B256::ZERO],
+            Vec::new(),
+        )
+    }
+
+    fn crosschain(topic: B256, sender: Address) -> Log {
+        log(
+            TOKEN,
+            vec![topic, USER.into_word(), sender.into_word()],
+            U256::from(5).to_be_bytes_vec(),
+        )
+    }
+
+    #[test]
+    fn topics() {
+        for (signature, topic) in [
+            (
+                "ExecutingMessage(bytes32,(address,uint256,uint256,uint256,uint256))",
+                EXECUTING_MESSAGE_TOPIC,
+            ),
+            (
+                "SentMessage(uint256,address,uint256,address,bytes)",
+                SENT_MESSAGE_TOPIC,
+            ),
+            (
+                "RelayedMessage(uint256,uint256,bytes32)",
+                RELAYED_MESSAGE_TOPIC,
+            ),
+            (
+                "CrosschainMint(address,uint256,address)",
+                CROSSCHAIN_MINT_TOPIC,
+            ),
+            (
+                "CrosschainBurn(address,uint256,address)",
+                CROSSCHAIN_BURN_TOPIC,
+            ),
+        ] {
+            assert_eq!(keccak256(signature), topic, "{signature}");
+        }
+    }
+
+    #[test]
+    fn interop_invariants() {
+        let mint = crosschain(CROSSCHAIN_MINT_TOPIC, SUPERCHAIN_TOKEN_BRIDGE);
+        let burn = crosschain(CROSSCHAIN_BURN_TOPIC, SUPERCHAIN_TOKEN_BRIDGE);
+        let messenger = executing_message(L2_TO_L2_CROSS_DOMAIN_MESSENGER);
+
+        // A relay of tokens, and a transfer of tokens to another chain.
+        assert!(
+            check_interop_logs(&[messenger.clone(), mint.clone(), relayed_message(0)]).is_empty()
+        );
+        assert!(check_interop_logs(&[burn.clone(), sent_message()]).is_empty());
+
+        // Each executing message is relayed once, executing messages of other origins are not
+        // relayed by the messenger.
+        assert_eq!(
+            check_interop_logs(&[
+                messenger.clone(),
+                relayed_message(0),
+                executing_message(USER),
+                relayed_message(1),
+                mint.clone(),
+            ]),
+            vec![
+                InteropViolation::RelayWithoutExecutingMessage {
+                    source: U256::from(10),
+                    nonce: U256::from(1),
+                },
+                InteropViolation::UnpairedMint {
+                    token: TOKEN,
+                    to: USER,
+                    amount: U256::from(5),
+                },
+            ]
+        );
+
+        // Burns are followed by a sent message, mints and burns are made by the bridge.
+        assert_eq!(
+            check_interop_logs(&[
+                sent_message(),
+                burn,
+                messenger,
+                crosschain(CROSSCHAIN_MINT_TOPIC, USER),
+            ]),
+            vec![
+                InteropViolation::UnauthorizedSender {
+                    token: TOKEN,
+                    sender: USER,
+                },
+                InteropViolation::UnpairedBurn {
+                    token: TOKEN,
+                    from: USER,
+                    amount: U256::from(5),
+                },
+            ]
+        );
+    }
+}
@@ -1,10 +1,17 @@
-use crate::{transaction::estimate_tx_compressed_size, OpSpecId};
+use crate::{
+    handler::{gas_token::GasToken, BaseFeeRouting, DepositGasRules},
+    transaction::{
+        deposit::DEPOSIT_TRANSACTION_TYPE, estimate_tx_compressed_size, pad_unsigned_tx,
+    },
+    OpSpecId,
+};
 use auto_impl::auto_impl;
-use core::ops::Mul;
+use core::{fmt, ops::Mul};
 use inspector::inspector_context::InspectorContext;
 use revm::{
     context_interface::{DatabaseGetter, Journal},
     database_interface::Database,
+    interpreter::Gas,
     primitives::{address, Address, U256},
     specification::hardfork::SpecId,
     Context,
@@ -33,18 +40,89 @@ pub const ECOTONE_L1_BLOB_BASE_FEE_SLOT: U256 = U256::from_limbs([7u64, 0, 0, 0]
 /// offsets [BASE_FEE_SCALAR_OFFSET] and [BLOB_BASE_FEE_SCALAR_OFFSET] respectively.
 pub const ECOTONE_L1_FEE_SCALARS_SLOT: U256 = U256::from_limbs([3u64, 0, 0, 0]);
 
+/// The 4-byte operatorFeeScalar and the 8-byte operatorFeeConstant are packed into the same storage slot.
+/// Byte offset within the storage slot of the 4-byte operatorFeeScalar attribute.
+pub const OPERATOR_FEE_SCALAR_OFFSET: usize = 20;
+/// The 4-byte operatorFeeScalar and the 8-byte operatorFeeConstant are packed into the same storage slot.
+/// Byte offset within the storage slot of the 8-byte operatorFeeConstant attribute.
+pub const OPERATOR_FEE_CONSTANT_OFFSET: usize = 24;
+
+/// [ISTHMUS_OPERATOR_FEE_SCALARS_SLOT] was added in the Isthmus upgrade and stores the operatorFeeScalar and
+/// operatorFeeConstant attributes at offsets [OPERATOR_FEE_SCALAR_OFFSET] and [OPERATOR_FEE_CONSTANT_OFFSET]
+/// respectively.
+pub const ISTHMUS_OPERATOR_FEE_SCALARS_SLOT: U256 = U256::from_limbs([8u64, 0, 0, 0]);
+
+/// The operatorFeeScalar is scaled by 1e6.
+pub const OPERATOR_FEE_SCALAR_DECIMAL: u64 = 1_000_000;
+
 /// An empty 64-bit set of scalar values.
 const EMPTY_SCALARS: [u8; 8] = [0u8; 8];
 
 /// The address of L1 fee recipient.
 pub const L1_FEE_RECIPIENT: Address = address!("420000000000000000000000000000000000001A");
 
+/// The address of the operator fee recipient.
+pub const OPERATOR_FEE_RECIPIENT: Address = address!("420000000000000000000000000000000000001B");
+
 /// The address of the base fee recipient.
 pub const BASE_FEE_RECIPIENT: Address = address!("4200000000000000000000000000000000000019");
 
 /// The address of the L1Block contract.
 pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000000000015");
 
+/// Selector of `setL1BlockValues`, the L1 attributes function before Ecotone.
+pub const BEDROCK_L1_ATTRIBUTES_SELECTOR: [u8; 4] = [0x01, 0x5d, 0x8e, 0xb9];
+/// Selector of `setL1BlockValuesEcotone`, the L1 attributes function from Ecotone to Isthmus.
+pub const ECOTONE_L1_ATTRIBUTES_SELECTOR: [u8; 4] = [0x44, 0x0a, 0x5e, 0x20];
+/// Selector of `setL1BlockValuesIsthmus`, the L1 attributes function since Isthmus.
+pub const ISTHMUS_L1_ATTRIBUTES_SELECTOR: [u8; 4] = [0x09, 0x89, 0x99, 0xbe];
+
+/// Length of the ABI encoded `setL1BlockValues` calldata: the selector and eight words.
+const BEDROCK_L1_ATTRIBUTES_LENGTH: usize = 4 + 8 * 32;
+/// Length of the tightly packed `setL1BlockValuesEcotone` calldata.
+const ECOTONE_L1_ATTRIBUTES_LENGTH: usize = 164;
+/// Length of the tightly packed `setL1BlockValuesIsthmus` calldata, the Ecotone one followed by
+/// the operator fee scalar and constant.
+const ISTHMUS_L1_ATTRIBUTES_LENGTH: usize = ECOTONE_L1_ATTRIBUTES_LENGTH + 4 + 8;
+
+/// Error decoding [`L1BlockInfo`] from the calldata of the L1 attributes deposit transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum L1AttributesError {
+    /// The calldata does not start with a known L1 attributes selector.
+    UnknownSelector,
+    /// The calldata length does not match the one of its selector.
+    InvalidLength { expected: usize, got: usize },
+}
+
+impl fmt::Display for L1AttributesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownSelector => f.write_str("unknown L1 attributes selector"),
+            Self::InvalidLength { expected, got } => {
+                write!(
+                    f,
+                    "L1 attributes calldata is {got} bytes long, expected {expected}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for L1AttributesError {}
+
+/// Error of [`L1BlockInfo::calculate_tx_l1_cost_checked`], an intermediate product of the L1 cost
+/// does not fit in 256 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct L1CostOverflow;
+
+impl fmt::Display for L1CostOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("L1 cost overflows 256 bits")
+    }
+}
+
+impl core::error::Error for L1CostOverflow {}
+
 /// L1 block info
 ///
 /// We can extract L1 epoch data from each L2 block, by looking at the `setL1BlockValues`
@@ -57,6 +135,7 @@ pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000
 ///
 /// For now, we only care about the fields necessary for L1 cost calculation.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct L1BlockInfo {
     /// The base fee of the L1 origin block.
     pub l1_base_fee: U256,
@@ -68,11 +147,93 @@ pub struct L1BlockInfo {
     pub l1_blob_base_fee: Option<U256>,
     /// The current L1 blob base fee scalar. None if Ecotone is not activated.
     pub l1_blob_base_fee_scalar: Option<U256>,
+    /// The current operator fee scalar. None if Isthmus is not activated.
+    pub operator_fee_scalar: Option<U256>,
+    /// The current operator fee constant. None if Isthmus is not activated.
+    pub operator_fee_constant: Option<U256>,
+    /// The number of the L2 block the info was loaded for, the handler loads it again for
+    /// transactions of other blocks. None if it is not cached.
+    pub l2_block: Option<u64>,
+    /// The model of the compressed size of transactions, post-Fjord.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compression_estimator: CompressionEstimator,
+    /// The vaults receiving the fees of transactions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee_recipients: FeeRecipients,
+    /// The destination of the base fee of transactions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub base_fee_routing: BaseFeeRouting,
+    /// The gas accounting of deposit transactions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deposit_gas_rules: DepositGasRules,
+    /// The ERC-20 predeploy paying the fees, the deposit mints and the values of transactions. None
+    /// if they are paid with the native balances. Calls and creates within a transaction can not
+    /// move a value with it, they fail as if the caller could not pay it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gas_token: Option<GasToken>,
     /// True if Ecotone is activated, but the L1 fee scalars have not yet been set.
     pub(crate) empty_scalars: bool,
 }
 
+/// Model of the compressed size of transactions, used by the L1 cost function since Fjord.
+///
+/// The FastLZ model is the one of the protocol, others allow fee estimation tooling to compare
+/// models and chains that changed the estimator to be executed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompressionEstimator {
+    /// Linear regression over the FastLZ compressed size, see [`estimate_tx_compressed_size`].
+    #[default]
+    FastLz,
+    /// Brotli compressed size, as compressed by batchers, see
+    /// [`estimate_tx_compressed_size_brotli`][crate::transaction::estimate_tx_compressed_size_brotli].
+    #[cfg(feature = "brotli")]
+    Brotli,
+}
+
+/// Addresses of the vaults receiving the fees of transactions.
+///
+/// The vaults are predeploys of OP stack chains, forks relocating them override the defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeRecipients {
+    /// Receives the base fee, [`BASE_FEE_RECIPIENT`] by default.
+    pub base_fee: Address,
+    /// Receives the L1 data fee, [`L1_FEE_RECIPIENT`] by default.
+    pub l1_fee: Address,
+    /// Receives the operator fee, [`OPERATOR_FEE_RECIPIENT`] by default.
+    pub operator_fee: Address,
+}
+
+impl Default for FeeRecipients {
+    fn default() -> Self {
+        Self {
+            base_fee: BASE_FEE_RECIPIENT,
+            l1_fee: L1_FEE_RECIPIENT,
+            operator_fee: OPERATOR_FEE_RECIPIENT,
+        }
+    }
+}
+
 impl L1BlockInfo {
+    /// Clears the cached L2 block number, so the info is loaded again for the next transaction.
+    ///
+    /// Needed if the L1Block contract storage changes without a change of block number, e.g.
+    /// when blocks are replayed at the same height.
+    pub fn reset(&mut self) {
+        self.l2_block = None;
+    }
+
+    /// Keeps the configuration of the chain set on `previous`, which is not stored in the L1Block
+    /// contract, when the info is loaded again.
+    pub(crate) fn keep_config(&mut self, previous: &L1BlockInfo) {
+        self.compression_estimator = previous.compression_estimator;
+        self.fee_recipients = previous.fee_recipients;
+        self.base_fee_routing = previous.base_fee_routing;
+        self.deposit_gas_rules = previous.deposit_gas_rules;
+        self.gas_token = previous.gas_token;
+    }
+
     /// Try to fetch the L1 block info from the database.
     pub fn try_fetch<DB: Database>(db: &mut DB, spec_id: OpSpec) -> Result<L1BlockInfo, DB::Error> {
         // Ensure the L1 Block account is loaded into the cache after Ecotone. With EIP-4788, it is no longer the case
@@ -116,17 +277,166 @@ impl L1BlockInfo {
                 .then(|| db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT))
                 .transpose()?;
 
+            let (operator_fee_scalar, operator_fee_constant) =
+                if spec_id.is_enabled_in(OpSpecId::ISTHMUS) {
+                    let operator_fee_scalars = db
+                        .storage(L1_BLOCK_CONTRACT, ISTHMUS_OPERATOR_FEE_SCALARS_SLOT)?
+                        .to_be_bytes::<32>();
+
+                    let operator_fee_scalar = U256::from_be_slice(
+                        operator_fee_scalars
+                            [OPERATOR_FEE_SCALAR_OFFSET..OPERATOR_FEE_SCALAR_OFFSET + 4]
+                            .as_ref(),
+                    );
+                    let operator_fee_constant = U256::from_be_slice(
+                        operator_fee_scalars
+                            [OPERATOR_FEE_CONSTANT_OFFSET..OPERATOR_FEE_CONSTANT_OFFSET + 8]
+                            .as_ref(),
+                    );
+                    (Some(operator_fee_scalar), Some(operator_fee_constant))
+                } else {
+                    (None, None)
+                };
+
             Ok(L1BlockInfo {
                 l1_base_fee,
                 l1_base_fee_scalar,
                 l1_blob_base_fee: Some(l1_blob_base_fee),
                 l1_blob_base_fee_scalar: Some(l1_blob_base_fee_scalar),
+                operator_fee_scalar,
+                operator_fee_constant,
+                l2_block: None,
+                compression_estimator: CompressionEstimator::default(),
+                fee_recipients: FeeRecipients::default(),
+                base_fee_routing: BaseFeeRouting::default(),
+                deposit_gas_rules: DepositGasRules::default(),
+                gas_token: None,
                 empty_scalars,
                 l1_fee_overhead,
             })
         }
     }
 
+    /// Decodes the L1 block info from the calldata of the L1 attributes deposit transaction, the
+    /// first transaction of each block, without reading the L1Block contract storage.
+    ///
+    /// `setL1BlockValues` is ABI encoded, while `setL1BlockValuesEcotone` and
+    /// `setL1BlockValuesIsthmus` are tightly packed:
+    ///
+    /// setL1BlockValuesEcotone(uint32 _baseFeeScalar, uint32 _blobBaseFeeScalar,
+    /// uint64 _sequenceNumber, uint64 _timestamp, uint64 _number, uint256 _basefee,
+    /// uint256 _blobBaseFee, bytes32 _hash, bytes32 _batcherHash)
+    ///
+    /// setL1BlockValuesIsthmus appends `uint32 _operatorFeeScalar, uint64 _operatorFeeConstant`.
+    ///
+    /// The attributes of the Ecotone activation block are still encoded with `setL1BlockValues`,
+    /// in which case the Bedrock cost function is used, as with [`L1BlockInfo::try_fetch`].
+    pub fn from_calldata(
+        calldata: &[u8],
+        spec_id: OpSpec,
+    ) -> Result<L1BlockInfo, L1AttributesError> {
+        let selector = calldata
+            .get(..4)
+            .ok_or(L1AttributesError::UnknownSelector)?;
+        let expected = match selector {
+            s if s == BEDROCK_L1_ATTRIBUTES_SELECTOR => BEDROCK_L1_ATTRIBUTES_LENGTH,
+            s if s == ECOTONE_L1_ATTRIBUTES_SELECTOR => ECOTONE_L1_ATTRIBUTES_LENGTH,
+            s if s == ISTHMUS_L1_ATTRIBUTES_SELECTOR => ISTHMUS_L1_ATTRIBUTES_LENGTH,
+            _ => return Err(L1AttributesError::UnknownSelector),
+        };
+        if calldata.len() != expected {
+            return Err(L1AttributesError::InvalidLength {
+                expected,
+                got: calldata.len(),
+            });
+        }
+        let word = |offset: usize| U256::from_be_slice(&calldata[offset..offset + 32]);
+
+        if expected == BEDROCK_L1_ATTRIBUTES_LENGTH {
+            let is_ecotone = spec_id.is_enabled_in(OpSpecId::ECOTONE);
+            return Ok(L1BlockInfo {
+                l1_base_fee: word(4 + 2 * 32),
+                l1_fee_overhead: Some(word(4 + 6 * 32)),
+                l1_base_fee_scalar: word(4 + 7 * 32),
+                l1_blob_base_fee: is_ecotone.then_some(U256::ZERO),
+                l1_blob_base_fee_scalar: is_ecotone.then_some(U256::ZERO),
+                empty_scalars: is_ecotone,
+                ..Default::default()
+            });
+        }
+
+        let (operator_fee_scalar, operator_fee_constant) =
+            if expected == ISTHMUS_L1_ATTRIBUTES_LENGTH {
+                (
+                    Some(U256::from_be_slice(&calldata[164..168])),
+                    Some(U256::from_be_slice(&calldata[168..176])),
+                )
+            } else {
+                (None, None)
+            };
+        Ok(L1BlockInfo {
+            l1_base_fee: word(36),
+            l1_fee_overhead: None,
+            l1_base_fee_scalar: U256::from_be_slice(&calldata[4..8]),
+            l1_blob_base_fee: Some(word(68)),
+            l1_blob_base_fee_scalar: Some(U256::from_be_slice(&calldata[8..12])),
+            operator_fee_scalar,
+            operator_fee_constant,
+            l2_block: None,
+            compression_estimator: CompressionEstimator::default(),
+            fee_recipients: FeeRecipients::default(),
+            base_fee_routing: BaseFeeRouting::default(),
+            deposit_gas_rules: DepositGasRules::default(),
+            gas_token: None,
+            empty_scalars: false,
+        })
+    }
+
+    /// Calculate the operator fee charged for the given amount of gas, post-Isthmus.
+    ///
+    /// [OpSpecId::ISTHMUS] operator fee function:
+    /// `gas*operatorFeeScalar/1e6 + operatorFeeConstant`
+    pub fn operator_fee_charge(&self, input: &[u8], gas: U256) -> U256 {
+        // Deposit transactions do not pay the operator fee.
+        if input.first() == Some(&DEPOSIT_TRANSACTION_TYPE) {
+            return U256::ZERO;
+        }
+
+        gas.saturating_mul(self.operator_fee_scalar.unwrap_or_default())
+            .wrapping_div(U256::from(OPERATOR_FEE_SCALAR_DECIMAL))
+            .saturating_add(self.operator_fee_constant.unwrap_or_default())
+    }
+
+    /// Calculate the operator fee refunded for the gas not used by the transaction, post-Isthmus.
+    ///
+    /// The operator fee is charged for the gas limit, the refund is the difference with the charge
+    /// for the gas used, so the constant part of the fee cancels out.
+    pub fn operator_fee_refund(&self, gas: &Gas, spec_id: OpSpec) -> U256 {
+        if !spec_id.is_enabled_in(OpSpecId::ISTHMUS) {
+            return U256::ZERO;
+        }
+
+        let gas_limit = U256::from(gas.limit());
+        let gas_used = U256::from(gas.spent() - gas.refunded() as u64);
+        let scalar = self.operator_fee_scalar.unwrap_or_default();
+        let decimal = U256::from(OPERATOR_FEE_SCALAR_DECIMAL);
+
+        gas_limit
+            .saturating_mul(scalar)
+            .wrapping_div(decimal)
+            .saturating_sub(gas_used.saturating_mul(scalar).wrapping_div(decimal))
+    }
+
+    /// Returns the operator fee paid by a transaction using `gas_used`, post-Isthmus.
+    ///
+    /// This is the charge for the gas limit minus the [refund](Self::operator_fee_refund) of the
+    /// unused gas, the fee credited to the operator fee vault.
+    pub fn operator_fee_cost(&self, input: &[u8], gas_used: u64, spec_id: OpSpec) -> Option<U256> {
+        spec_id
+            .is_enabled_in(OpSpecId::ISTHMUS)
+            .then(|| self.operator_fee_charge(input, U256::from(gas_used)))
+    }
+
     /// Calculate the data gas for posting the transaction on L1. Calldata costs 16 gas per byte
     /// after compression.
     ///
@@ -160,10 +470,16 @@ impl L1BlockInfo {
     }
 
     // Calculate the estimated compressed transaction size in bytes, scaled by 1e6.
-    // This value is computed based on the following formula:
+    // With the FastLZ model, this value is computed based on the following formula:
     // max(minTransactionSize, intercept + fastlzCoef*fastlzSize)
     fn tx_estimated_size_fjord(&self, input: &[u8]) -> U256 {
-        U256::from(estimate_tx_compressed_size(input))
+        match self.compression_estimator {
+            CompressionEstimator::FastLz => U256::from(estimate_tx_compressed_size(input)),
+            #[cfg(feature = "brotli")]
+            CompressionEstimator::Brotli => U256::from(
+                crate::transaction::estimate_tx_compressed_size_brotli(input),
+            ),
+        }
     }
 
     /// Calculate the gas cost of a transaction based on L1 block data posted on L2, depending on the [OpSpec] passed.
@@ -182,26 +498,65 @@ impl L1BlockInfo {
         }
     }
 
+    /// Calculate the gas cost of a transaction like [`L1BlockInfo::calculate_tx_l1_cost`], but
+    /// return an error instead of saturating when an intermediate product overflows.
+    ///
+    /// Execution saturates like op-geth, an error tells apart scalars no fee can be computed for
+    /// from a legitimately large fee.
+    pub fn calculate_tx_l1_cost_checked(
+        &self,
+        input: &[u8],
+        spec_id: OpSpec,
+    ) -> Result<U256, L1CostOverflow> {
+        // If the input is a deposit transaction or empty, the default value is zero.
+        if input.is_empty() || input.first() == Some(&0x7F) {
+            return Ok(U256::ZERO);
+        }
+
+        let blob_base_fee = self.l1_blob_base_fee.unwrap_or_default();
+        let blob_base_fee_scalar = self.l1_blob_base_fee_scalar.unwrap_or_default();
+        let cost_per_byte = || {
+            self.l1_base_fee
+                .checked_mul(U256::from(NON_ZERO_BYTE_COST))?
+                .checked_mul(self.l1_base_fee_scalar)?
+                .checked_add(blob_base_fee.checked_mul(blob_base_fee_scalar)?)
+        };
+        let cost = if spec_id.is_enabled_in(OpSpecId::FJORD) {
+            cost_per_byte()
+                .and_then(|cost| cost.checked_mul(self.tx_estimated_size_fjord(input)))
+                .map(|cost| cost / U256::from(1_000_000_000_000u64))
+        } else if spec_id.is_enabled_in(OpSpecId::ECOTONE) && !self.empty_scalars {
+            cost_per_byte()
+                .and_then(|cost| cost.checked_mul(self.data_gas(input, spec_id)))
+                .map(|cost| cost / U256::from(1_000_000 * NON_ZERO_BYTE_COST))
+        } else {
+            self.data_gas(input, spec_id)
+                .checked_add(self.l1_fee_overhead.unwrap_or_default())
+                .and_then(|gas| gas.checked_mul(self.l1_base_fee))
+                .and_then(|cost| cost.checked_mul(self.l1_base_fee_scalar))
+                .map(|cost| cost / U256::from(1_000_000))
+        };
+        cost.ok_or(L1CostOverflow)
+    }
+
+    /// Estimate the gas cost of a transaction before it is signed, e.g. to quote its fees in a
+    /// wallet, see [`pad_unsigned_tx`].
+    pub fn estimate_tx_l1_cost_unsigned(&self, unsigned_tx: &[u8], spec_id: OpSpec) -> U256 {
+        self.calculate_tx_l1_cost(&pad_unsigned_tx(unsigned_tx), spec_id)
+    }
+
     /// Calculate the gas cost of a transaction based on L1 block data posted on L2, pre-Ecotone.
     fn calculate_tx_l1_cost_bedrock(&self, input: &[u8], spec_id: OpSpec) -> U256 {
-        let rollup_data_gas_cost = self.data_gas(input, spec_id);
-        rollup_data_gas_cost
-            .saturating_add(self.l1_fee_overhead.unwrap_or_default())
-            .saturating_mul(self.l1_base_fee)
-            .saturating_mul(self.l1_base_fee_scalar)
-            .wrapping_div(U256::from(1_000_000))
+        l1_cost_bedrock(
+            self.data_gas(input, spec_id),
+            self.l1_fee_overhead.unwrap_or_default(),
+            self.l1_base_fee,
+            self.l1_base_fee_scalar,
+        )
     }
 
-    /// Calculate the gas cost of a transaction based on L1 block data posted on L2, post-Ecotone.
-    ///
-    /// [OpSpecId::ECOTONE] L1 cost function:
-    /// `(calldataGas/16)*(l1BaseFee*16*l1BaseFeeScalar + l1BlobBaseFee*l1BlobBaseFeeScalar)/1e6`
-    ///
-    /// We divide "calldataGas" by 16 to change from units of calldata gas to "estimated # of bytes when compressed".
-    /// Known as "compressedTxSize" in the spec.
-    ///
-    /// Function is actually computed as follows for better precision under integer arithmetic:
-    /// `calldataGas*(l1BaseFee*16*l1BaseFeeScalar + l1BlobBaseFee*l1BlobBaseFeeScalar)/16e6`
+    /// Calculate the gas cost of a transaction based on L1 block data posted on L2, post-Ecotone,
+    /// see [`l1_cost_ecotone`].
     fn calculate_tx_l1_cost_ecotone(&self, input: &[u8], spec_id: OpSpec) -> U256 {
         // There is an edgecase where, for the very first Ecotone block (unless it is activated at Genesis), we must
         // use the Bedrock cost function. To determine if this is the case, we can check if the Ecotone parameters are
@@ -210,39 +565,304 @@ impl L1BlockInfo {
             return self.calculate_tx_l1_cost_bedrock(input, spec_id);
         }
 
-        let rollup_data_gas_cost = self.data_gas(input, spec_id);
-        let l1_fee_scaled = self.calculate_l1_fee_scaled_ecotone();
-
-        l1_fee_scaled
-            .saturating_mul(rollup_data_gas_cost)
-            .wrapping_div(U256::from(1_000_000 * NON_ZERO_BYTE_COST))
+        l1_cost_ecotone(
+            self.data_gas(input, spec_id),
+            self.l1_base_fee,
+            self.l1_base_fee_scalar,
+            self.l1_blob_base_fee.unwrap_or_default(),
+            self.l1_blob_base_fee_scalar.unwrap_or_default(),
+        )
     }
 
-    /// Calculate the gas cost of a transaction based on L1 block data posted on L2, post-Fjord.
-    ///
-    /// [OpSpecId::FJORD] L1 cost function:
-    /// `estimatedSize*(baseFeeScalar*l1BaseFee*16 + blobFeeScalar*l1BlobBaseFee)/1e12`
+    /// Calculate the gas cost of a transaction based on L1 block data posted on L2, post-Fjord,
+    /// see [`l1_cost_fjord`].
     fn calculate_tx_l1_cost_fjord(&self, input: &[u8]) -> U256 {
-        let l1_fee_scaled = self.calculate_l1_fee_scaled_ecotone();
-        let estimated_size = self.tx_estimated_size_fjord(input);
+        l1_cost_fjord(
+            self.tx_estimated_size_fjord(input),
+            self.l1_base_fee,
+            self.l1_base_fee_scalar,
+            self.l1_blob_base_fee.unwrap_or_default(),
+            self.l1_blob_base_fee_scalar.unwrap_or_default(),
+        )
+    }
 
-        estimated_size
-            .saturating_mul(l1_fee_scaled)
-            .wrapping_div(U256::from(1_000_000_000_000u64))
+    // (l1BaseFee*16*l1BaseFeeScalar, l1BlobBaseFee*l1BlobBaseFeeScalar)
+    fn l1_fee_scaled_components(&self) -> (U256, U256) {
+        l1_fee_scaled_components(
+            self.l1_base_fee,
+            self.l1_base_fee_scalar,
+            self.l1_blob_base_fee.unwrap_or_default(),
+            self.l1_blob_base_fee_scalar.unwrap_or_default(),
+        )
     }
 
-    // l1BaseFee*16*l1BaseFeeScalar + l1BlobBaseFee*l1BlobBaseFeeScalar
-    fn calculate_l1_fee_scaled_ecotone(&self) -> U256 {
-        let calldata_cost_per_byte = self
-            .l1_base_fee
-            .saturating_mul(U256::from(NON_ZERO_BYTE_COST))
-            .saturating_mul(self.l1_base_fee_scalar);
-        let blob_cost_per_byte = self
-            .l1_blob_base_fee
-            .unwrap_or_default()
-            .saturating_mul(self.l1_blob_base_fee_scalar.unwrap_or_default());
+    /// Calculate the gas cost of a transaction like [`L1BlockInfo::calculate_tx_l1_cost`], with
+    /// the components of the cost and the values they were computed from.
+    pub fn calculate_tx_l1_cost_breakdown(&self, input: &[u8], spec_id: OpSpec) -> L1CostBreakdown {
+        let mut breakdown = L1CostBreakdown {
+            l1_base_fee_scalar: self.l1_base_fee_scalar,
+            l1_blob_base_fee_scalar: self.l1_blob_base_fee_scalar,
+            l1_fee_overhead: self.l1_fee_overhead,
+            ..Default::default()
+        };
+        // If the input is a deposit transaction or empty, the default value is zero.
+        if input.is_empty() || input.first() == Some(&0x7F) {
+            return breakdown;
+        }
+
+        breakdown.data_gas = self.data_gas(input, spec_id);
+        breakdown.total = self.calculate_tx_l1_cost(input, spec_id);
+        let (calldata_cost_per_byte, blob_cost_per_byte) = self.l1_fee_scaled_components();
+        if spec_id.is_enabled_in(OpSpecId::FJORD) {
+            let estimated_size = self.tx_estimated_size_fjord(input);
+            let divisor = U256::from(1_000_000_000_000u64);
+            breakdown.calldata_cost = estimated_size
+                .saturating_mul(calldata_cost_per_byte)
+                .wrapping_div(divisor);
+            breakdown.blob_cost = estimated_size
+                .saturating_mul(blob_cost_per_byte)
+                .wrapping_div(divisor);
+            breakdown.estimated_size = Some(estimated_size);
+        } else if spec_id.is_enabled_in(OpSpecId::ECOTONE) && !self.empty_scalars {
+            let divisor = U256::from(1_000_000 * NON_ZERO_BYTE_COST);
+            breakdown.calldata_cost = breakdown
+                .data_gas
+                .saturating_mul(calldata_cost_per_byte)
+                .wrapping_div(divisor);
+            breakdown.blob_cost = breakdown
+                .data_gas
+                .saturating_mul(blob_cost_per_byte)
+                .wrapping_div(divisor);
+        } else {
+            // The Bedrock cost function only charges for the L1 base fee.
+            breakdown.calldata_cost = breakdown.total;
+        }
+        breakdown
+    }
+}
+
+/// Calculate the L1 data fee of a transaction with the given calldata gas, pre-Ecotone.
+///
+/// [OpSpecId::BEDROCK] L1 cost function:
+/// `(calldataGas + l1FeeOverhead)*l1BaseFee*l1BaseFeeScalar/1e6`
+///
+/// The calldata gas is returned by [`L1BlockInfo::data_gas`].
+pub fn l1_cost_bedrock(
+    data_gas: U256,
+    l1_fee_overhead: U256,
+    l1_base_fee: U256,
+    l1_base_fee_scalar: U256,
+) -> U256 {
+    data_gas
+        .saturating_add(l1_fee_overhead)
+        .saturating_mul(l1_base_fee)
+        .saturating_mul(l1_base_fee_scalar)
+        .wrapping_div(U256::from(1_000_000))
+}
+
+/// Calculate the L1 data fee of a transaction with the given calldata gas, post-Ecotone.
+///
+/// [OpSpecId::ECOTONE] L1 cost function:
+/// `(calldataGas/16)*(l1BaseFee*16*l1BaseFeeScalar + l1BlobBaseFee*l1BlobBaseFeeScalar)/1e6`
+///
+/// We divide "calldataGas" by 16 to change from units of calldata gas to "estimated # of bytes when compressed".
+/// Known as "compressedTxSize" in the spec.
+///
+/// Function is actually computed as follows for better precision under integer arithmetic:
+/// `calldataGas*(l1BaseFee*16*l1BaseFeeScalar + l1BlobBaseFee*l1BlobBaseFeeScalar)/16e6`
+pub fn l1_cost_ecotone(
+    data_gas: U256,
+    l1_base_fee: U256,
+    l1_base_fee_scalar: U256,
+    l1_blob_base_fee: U256,
+    l1_blob_base_fee_scalar: U256,
+) -> U256 {
+    let (calldata_cost_per_byte, blob_cost_per_byte) = l1_fee_scaled_components(
+        l1_base_fee,
+        l1_base_fee_scalar,
+        l1_blob_base_fee,
+        l1_blob_base_fee_scalar,
+    );
+    calldata_cost_per_byte
+        .saturating_add(blob_cost_per_byte)
+        .saturating_mul(data_gas)
+        .wrapping_div(U256::from(1_000_000 * NON_ZERO_BYTE_COST))
+}
+
+/// Calculate the L1 data fee of a transaction with the given estimated compressed size scaled by
+/// 1e6, post-Fjord.
+///
+/// [OpSpecId::FJORD] L1 cost function:
+/// `estimatedSize*(baseFeeScalar*l1BaseFee*16 + blobFeeScalar*l1BlobBaseFee)/1e12`
+///
+/// The estimated size of a transaction is returned by [`estimate_tx_compressed_size`].
+pub fn l1_cost_fjord(
+    estimated_size: U256,
+    l1_base_fee: U256,
+    l1_base_fee_scalar: U256,
+    l1_blob_base_fee: U256,
+    l1_blob_base_fee_scalar: U256,
+) -> U256 {
+    let (calldata_cost_per_byte, blob_cost_per_byte) = l1_fee_scaled_components(
+        l1_base_fee,
+        l1_base_fee_scalar,
+        l1_blob_base_fee,
+        l1_blob_base_fee_scalar,
+    );
+    calldata_cost_per_byte
+        .saturating_add(blob_cost_per_byte)
+        .saturating_mul(estimated_size)
+        .wrapping_div(U256::from(1_000_000_000_000u64))
+}
+
+// (l1BaseFee*16*l1BaseFeeScalar, l1BlobBaseFee*l1BlobBaseFeeScalar)
+fn l1_fee_scaled_components(
+    l1_base_fee: U256,
+    l1_base_fee_scalar: U256,
+    l1_blob_base_fee: U256,
+    l1_blob_base_fee_scalar: U256,
+) -> (U256, U256) {
+    let calldata_cost_per_byte = l1_base_fee
+        .saturating_mul(U256::from(NON_ZERO_BYTE_COST))
+        .saturating_mul(l1_base_fee_scalar);
+    let blob_cost_per_byte = l1_blob_base_fee.saturating_mul(l1_blob_base_fee_scalar);
+    (calldata_cost_per_byte, blob_cost_per_byte)
+}
+
+/// Components of the L1 data fee of a transaction, see
+/// [`L1BlockInfo::calculate_tx_l1_cost_breakdown`].
+///
+/// The components are rounded down separately, so their sum can be lower than the total by one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct L1CostBreakdown {
+    /// The L1 data fee charged to the transaction.
+    pub total: U256,
+    /// The part of the fee paying for the L1 base fee, the whole fee before Ecotone.
+    pub calldata_cost: U256,
+    /// The part of the fee paying for the L1 blob base fee, zero before Ecotone.
+    pub blob_cost: U256,
+    /// The calldata gas of the transaction, see [`L1BlockInfo::data_gas`].
+    pub data_gas: U256,
+    /// The estimated compressed size of the transaction scaled by 1e6. None before Fjord.
+    pub estimated_size: Option<U256>,
+    /// The applied L1 base fee scalar.
+    pub l1_base_fee_scalar: U256,
+    /// The applied L1 blob base fee scalar. None before Ecotone.
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// The applied L1 fee overhead. None after Ecotone.
+    pub l1_fee_overhead: Option<U256>,
+}
 
-        calldata_cost_per_byte.saturating_add(blob_cost_per_byte)
+/// [`L1BlockInfo`] in the shape of RPC objects: camel case fields and hex quantities.
+///
+/// Fields not set in the [`L1BlockInfo`] are omitted, and the L2 block it was cached for is not
+/// part of the view.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RpcL1BlockInfo {
+    /// The base fee of the L1 origin block.
+    pub l1_base_fee: U256,
+    /// The L1 fee overhead, before Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_fee_overhead: Option<U256>,
+    /// The L1 base fee scalar.
+    pub l1_base_fee_scalar: U256,
+    /// The L1 blob base fee, since Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_blob_base_fee: Option<U256>,
+    /// The L1 blob base fee scalar, since Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// The operator fee scalar, since Isthmus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub operator_fee_scalar: Option<U256>,
+    /// The operator fee constant, since Isthmus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub operator_fee_constant: Option<U256>,
+}
+
+impl From<&L1BlockInfo> for RpcL1BlockInfo {
+    fn from(info: &L1BlockInfo) -> Self {
+        Self {
+            l1_base_fee: info.l1_base_fee,
+            l1_fee_overhead: info.l1_fee_overhead,
+            l1_base_fee_scalar: info.l1_base_fee_scalar,
+            l1_blob_base_fee: info.l1_blob_base_fee,
+            l1_blob_base_fee_scalar: info.l1_blob_base_fee_scalar,
+            operator_fee_scalar: info.operator_fee_scalar,
+            operator_fee_constant: info.operator_fee_constant,
+        }
+    }
+}
+
+impl From<RpcL1BlockInfo> for L1BlockInfo {
+    /// After Ecotone, the L1 fee overhead is only set when the Ecotone scalars are not, so the
+    /// Bedrock cost function is used.
+    fn from(info: RpcL1BlockInfo) -> Self {
+        Self {
+            l1_base_fee: info.l1_base_fee,
+            l1_fee_overhead: info.l1_fee_overhead,
+            l1_base_fee_scalar: info.l1_base_fee_scalar,
+            l1_blob_base_fee: info.l1_blob_base_fee,
+            l1_blob_base_fee_scalar: info.l1_blob_base_fee_scalar,
+            operator_fee_scalar: info.operator_fee_scalar,
+            operator_fee_constant: info.operator_fee_constant,
+            l2_block: None,
+            compression_estimator: CompressionEstimator::default(),
+            fee_recipients: FeeRecipients::default(),
+            base_fee_routing: BaseFeeRouting::default(),
+            deposit_gas_rules: DepositGasRules::default(),
+            gas_token: None,
+            empty_scalars: info.l1_blob_base_fee.is_some() && info.l1_fee_overhead.is_some(),
+        }
+    }
+}
+
+/// Calculates the L1 data fee of transactions from the [`L1BlockInfo`] of their block.
+///
+/// [`L1BlockInfo`] implements the cost functions of the OP stack. Chains with other cost
+/// functions, e.g. with custom scalars or compression models, use their own implementation as the
+/// chain context, see [`Context::with_chain`], and the optimism handler charges the fee it returns.
+#[auto_impl(&mut, Box)]
+pub trait L1CostCalculator {
+    /// Returns the L1 block info, fetched by the handler before each transaction.
+    fn l1_block_info(&self) -> &L1BlockInfo;
+
+    /// Returns the L1 block info, to set the one fetched for a transaction.
+    fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo;
+
+    /// Returns the L1 data fee of the enveloped transaction.
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256;
+}
+
+impl L1CostCalculator for L1BlockInfo {
+    fn l1_block_info(&self) -> &L1BlockInfo {
+        self
+    }
+
+    fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo {
+        self
+    }
+
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+        L1BlockInfo::calculate_tx_l1_cost(self, input, spec_id)
     }
 }
 
@@ -250,17 +870,26 @@ impl L1BlockInfo {
 pub trait L1BlockInfoGetter {
     fn l1_block_info(&self) -> &L1BlockInfo;
     fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo;
+
+    /// Returns the L1 data fee of the enveloped transaction, see [`L1CostCalculator`].
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+        self.l1_block_info().calculate_tx_l1_cost(input, spec_id)
+    }
 }
 
-impl<BLOCK, TX, SPEC, DB: Database, JOURNAL: Journal<Database = DB>> L1BlockInfoGetter
-    for Context<BLOCK, TX, SPEC, DB, JOURNAL, L1BlockInfo>
+impl<BLOCK, TX, SPEC, DB: Database, JOURNAL: Journal<Database = DB>, CHAIN: L1CostCalculator>
+    L1BlockInfoGetter for Context<BLOCK, TX, SPEC, DB, JOURNAL, CHAIN>
 {
     fn l1_block_info(&self) -> &L1BlockInfo {
-        &self.chain
+        self.chain.l1_block_info()
     }
 
     fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo {
-        &mut self.chain
+        self.chain.l1_block_info_mut()
+    }
+
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+        self.chain.calculate_tx_l1_cost(input, spec_id)
     }
 }
 
@@ -274,12 +903,76 @@ impl<INSP, DB, CTX: DatabaseGetter<Database = DB> + L1BlockInfoGetter> L1BlockIn
     fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo {
         self.inner.l1_block_info_mut()
     }
+
+    fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+        self.inner.calculate_tx_l1_cost(input, spec_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use revm::primitives::{bytes, hex};
+    use crate::{
+        api::exec_op::transact_op, transaction::estimate_unsigned_tx_compressed_size, OpTransaction,
+    };
+    use database::{CacheDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        context::{BlockEnv, CfgEnv, TxEnv},
+        database_interface::EmptyDB,
+        primitives::{bytes, hex, Bytes, TxKind},
+        state::AccountInfo,
+        JournaledState,
+    };
+
+    type TestContext<CHAIN = L1BlockInfo> = Context<
+        BlockEnv,
+        OpTransaction<TxEnv>,
+        CfgEnv<OpSpec>,
+        CacheDB<EmptyDB>,
+        JournaledState<CacheDB<EmptyDB>>,
+        CHAIN,
+    >;
+
+    /// Returns a database where [`BENCH_CALLER`] has `balance` and the L1Block contract has
+    /// `storage`.
+    fn l1_block_db(balance: U256, storage: &[(U256, U256)]) -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(BENCH_CALLER, AccountInfo::from_balance(balance));
+        db.insert_account_info(
+            L1_BLOCK_CONTRACT,
+            AccountInfo::from_bytecode(Bytecode::new()),
+        );
+        for &(slot, value) in storage {
+            db.insert_account_storage(L1_BLOCK_CONTRACT, slot, value)
+                .unwrap();
+        }
+        db
+    }
+
+    /// Returns a context calling [`BENCH_TARGET`] from [`BENCH_CALLER`] with 100k gas, enveloped
+    /// as `enveloped_tx`.
+    fn call_ctx(
+        db: CacheDB<EmptyDB>,
+        spec: OpSpecId,
+        gas_price: u128,
+        enveloped_tx: Bytes,
+    ) -> TestContext {
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_tx(OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                gas_limit: 100_000,
+                gas_price,
+                gas_priority_fee: None,
+                ..Default::default()
+            }))
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(spec)))
+            .with_chain(L1BlockInfo::default());
+        ctx.tx.enveloped_tx = Some(enveloped_tx);
+        ctx
+    }
 
     #[test]
     fn test_data_gas_non_zero_bytes() {
@@ -490,6 +1183,49 @@ mod tests {
         assert_eq!(gas_cost, U256::ZERO);
     }
 
+    #[test]
+    fn calculate_tx_l1_cost_checked() {
+        let mut l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1055991687),
+            l1_base_fee_scalar: U256::from(5227),
+            l1_blob_base_fee: Some(U256::from(1)),
+            l1_blob_base_fee_scalar: Some(U256::from(1014213)),
+            l1_fee_overhead: Some(U256::from(188)),
+            ..Default::default()
+        };
+        let input = bytes!("FACADE");
+        let specs = [OpSpecId::BEDROCK, OpSpecId::ECOTONE, OpSpecId::FJORD];
+
+        // Without overflow, the cost is the one charged to the transaction.
+        for spec in specs {
+            assert_eq!(
+                l1_block_info.calculate_tx_l1_cost_checked(&input, spec.into()),
+                Ok(l1_block_info.calculate_tx_l1_cost(&input, spec.into()))
+            );
+        }
+        assert_eq!(
+            l1_block_info.calculate_tx_l1_cost_checked(&bytes!("7FFACADE"), OpSpecId::FJORD.into()),
+            Ok(U256::ZERO)
+        );
+
+        // A legitimately large fee is returned.
+        l1_block_info.l1_base_fee_scalar = U256::from(u64::MAX);
+        l1_block_info.l1_base_fee = U256::from(u128::MAX);
+        for spec in specs {
+            let cost = l1_block_info.calculate_tx_l1_cost_checked(&input, spec.into());
+            assert!(cost.unwrap() > U256::from(u128::MAX));
+        }
+
+        // Saturated products are reported.
+        l1_block_info.l1_base_fee_scalar = U256::MAX / U256::from(2);
+        for spec in specs {
+            assert_eq!(
+                l1_block_info.calculate_tx_l1_cost_checked(&input, spec.into()),
+                Err(L1CostOverflow)
+            );
+        }
+    }
+
     #[test]
     fn calculate_tx_l1_cost_fjord() {
         // rig
@@ -523,6 +1259,514 @@ mod tests {
 
         let l1_fee = l1_block_info.calculate_tx_l1_cost_fjord(TX);
 
+        assert_eq!(l1_fee, expected_l1_fee);
+
+        let l1_fee = l1_cost_fjord(
+            U256::from(estimate_tx_compressed_size(TX)),
+            U256::from(1055991687),
+            U256::from(5227),
+            U256::from(1),
+            U256::from(1014213),
+        );
+
         assert_eq!(l1_fee, expected_l1_fee)
     }
+
+    #[test]
+    fn standalone_l1_cost() {
+        // (1000 + 188) * 1000 * 684000 / 1e6
+        assert_eq!(
+            l1_cost_bedrock(
+                U256::from(1_000),
+                U256::from(188),
+                U256::from(1_000),
+                U256::from(684_000)
+            ),
+            U256::from(812_592)
+        );
+
+        // 1600 * (1000 * 16 * 1000 + 1000 * 1000) / 16e6
+        let fees = [1_000, 1_000, 1_000, 1_000].map(U256::from);
+        assert_eq!(
+            l1_cost_ecotone(U256::from(1_600), fees[0], fees[1], fees[2], fees[3]),
+            U256::from(1_700)
+        );
+
+        // 100e6 * (1000 * 16 * 1000 + 1000 * 1000) / 1e12
+        assert_eq!(
+            l1_cost_fjord(U256::from(100_000_000), fees[0], fees[1], fees[2], fees[3]),
+            U256::from(1_700)
+        );
+    }
+
+    #[test]
+    fn test_calculate_tx_l1_cost_breakdown() {
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            ..Default::default()
+        };
+
+        // calldataCost = estimatedSize * l1BaseFee * 16 * baseFeeScalar / 1e12
+        //              = 100e6 * 16e6 / 1e12
+        //              = 1600
+        // blobCost = estimatedSize * l1BlobBaseFee * blobFeeScalar / 1e12
+        //          = 100e6 * 1e6 / 1e12
+        //          = 100
+        let input = bytes!("FACADE");
+        let breakdown =
+            l1_block_info.calculate_tx_l1_cost_breakdown(&input, OpSpecId::FJORD.into());
+        assert_eq!(
+            breakdown,
+            L1CostBreakdown {
+                total: U256::from(1700),
+                calldata_cost: U256::from(1600),
+                blob_cost: U256::from(100),
+                data_gas: U256::from(1600),
+                estimated_size: Some(U256::from(100_000_000)),
+                l1_base_fee_scalar: U256::from(1_000),
+                l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+                l1_fee_overhead: None,
+            }
+        );
+
+        // calldataGas = 3 * 16 = 48, calldataCost = 48 * 16e6 / 16e6 = 48, blobCost = 48 * 1e6 / 16e6 = 3
+        let breakdown =
+            l1_block_info.calculate_tx_l1_cost_breakdown(&input, OpSpecId::ECOTONE.into());
+        assert_eq!(breakdown.total, U256::from(51));
+        assert_eq!(breakdown.calldata_cost, U256::from(48));
+        assert_eq!(breakdown.blob_cost, U256::from(3));
+        assert_eq!(breakdown.estimated_size, None);
+
+        let breakdown = l1_block_info
+            .calculate_tx_l1_cost_breakdown(&bytes!("7FFACADE"), OpSpecId::FJORD.into());
+        assert_eq!(breakdown.total, U256::ZERO);
+    }
+
+    #[test]
+    fn test_estimate_tx_l1_cost_unsigned() {
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            ..Default::default()
+        };
+
+        // The signature is charged as 68 non-zero bytes.
+        // calldataGas = (3 + 68) * 16 = 1136, l1Cost = 1136 * 17e6 / 16e6 = 1207
+        let input = bytes!("FACADE");
+        let estimate = l1_block_info.estimate_tx_l1_cost_unsigned(&input, OpSpecId::ECOTONE.into());
+        assert_eq!(estimate, U256::from(1207));
+
+        // The estimate of the transaction without its signature bounds its cost.
+        let input = bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e");
+        let unsigned = &input[..input.len() - 67];
+        let cost = l1_block_info.calculate_tx_l1_cost(&input, OpSpecId::FJORD.into());
+        let estimate = l1_block_info.estimate_tx_l1_cost_unsigned(unsigned, OpSpecId::FJORD.into());
+        assert!(estimate >= cost);
+        assert!(
+            estimate_unsigned_tx_compressed_size(unsigned) >= estimate_tx_compressed_size(&input)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rpc_l1_block_info() {
+        let bedrock_calldata = {
+            let mut calldata = Vec::from(BEDROCK_L1_ATTRIBUTES_SELECTOR);
+            for word in [0, 0, 7, 0, 0, 0, 188, 684_000] {
+                calldata.extend(U256::from(word).to_be_bytes::<32>());
+            }
+            calldata
+        };
+        // The first Ecotone block uses the Bedrock cost function.
+        let info = L1BlockInfo::from_calldata(&bedrock_calldata, OpSpecId::ECOTONE.into()).unwrap();
+        assert!(info.empty_scalars);
+
+        let json = serde_json::to_string(&RpcL1BlockInfo::from(&info)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"l1BaseFee":"0x7","l1FeeOverhead":"0xbc","l1BaseFeeScalar":"0xa6fe0","l1BlobBaseFee":"0x0","l1BlobBaseFeeScalar":"0x0"}"#
+        );
+        let rpc: RpcL1BlockInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(L1BlockInfo::from(rpc), info);
+
+        let info = L1BlockInfo {
+            l1_base_fee: U256::from(7),
+            l1_base_fee_scalar: U256::from(1368),
+            l1_blob_base_fee: Some(U256::from(1)),
+            l1_blob_base_fee_scalar: Some(U256::from(810949)),
+            operator_fee_scalar: Some(U256::ZERO),
+            operator_fee_constant: Some(U256::ZERO),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&RpcL1BlockInfo::from(&info)).unwrap();
+        let rpc: RpcL1BlockInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(L1BlockInfo::from(rpc), info);
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<L1BlockInfo>(&json).unwrap(), info);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_brotli_compression_estimator() {
+        use crate::transaction::estimate_tx_compressed_size_brotli;
+
+        let mut l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            ..Default::default()
+        };
+        // Brotli compresses text better than FastLZ.
+        let input: Vec<u8> = (0..2000u32)
+            .flat_map(|i| std::format!("{i},").into_bytes())
+            .collect();
+        let brotli_size = estimate_tx_compressed_size_brotli(&input);
+        assert!(brotli_size < estimate_tx_compressed_size(&input));
+
+        let fastlz_cost = l1_block_info.calculate_tx_l1_cost(&input, OpSpecId::FJORD.into());
+        l1_block_info.compression_estimator = CompressionEstimator::Brotli;
+        let brotli_cost = l1_block_info.calculate_tx_l1_cost(&input, OpSpecId::FJORD.into());
+        assert!(brotli_cost < fastlz_cost);
+        // l1Cost = estimatedSize * l1FeeScaled / 1e12
+        assert_eq!(brotli_cost, U256::from(brotli_size * 17 / 1_000_000));
+    }
+
+    #[test]
+    fn test_try_fetch_operator_fee() {
+        // Bytes before the operator fee scalar are not part of the operator fee params.
+        let operator_fee_scalars = (U256::MAX << 96)
+            | (U256::from(0xdeadbeefu32) << 64)
+            | U256::from(0x0123456789abcdefu64);
+        let mut db = l1_block_db(
+            U256::ZERO,
+            &[(ISTHMUS_OPERATOR_FEE_SCALARS_SLOT, operator_fee_scalars)],
+        );
+
+        let l1_block_info = L1BlockInfo::try_fetch(&mut db, OpSpecId::ISTHMUS.into()).unwrap();
+        assert_eq!(
+            l1_block_info.operator_fee_scalar,
+            Some(U256::from(0xdeadbeefu32))
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_constant,
+            Some(U256::from(0x0123456789abcdefu64))
+        );
+
+        let l1_block_info = L1BlockInfo::try_fetch(&mut db, OpSpecId::HOLOCENE.into()).unwrap();
+        assert_eq!(l1_block_info.operator_fee_scalar, None);
+        assert_eq!(l1_block_info.operator_fee_constant, None);
+    }
+
+    #[test]
+    fn from_calldata() {
+        // setL1BlockValuesEcotone with base fee scalar 1368, blob base fee scalar 810949,
+        // base fee 1 gwei and blob base fee 10.
+        let mut ecotone = Vec::from(ECOTONE_L1_ATTRIBUTES_SELECTOR);
+        ecotone.extend(1368u32.to_be_bytes());
+        ecotone.extend(810949u32.to_be_bytes());
+        ecotone.extend([0; 24]);
+        ecotone.extend(U256::from(1_000_000_000).to_be_bytes::<32>());
+        ecotone.extend(U256::from(10).to_be_bytes::<32>());
+        ecotone.extend([0; 64]);
+        let spec = OpSpec::Op(OpSpecId::ECOTONE);
+        let expected = L1BlockInfo {
+            l1_base_fee: U256::from(1_000_000_000),
+            l1_base_fee_scalar: U256::from(1368),
+            l1_blob_base_fee: Some(U256::from(10)),
+            l1_blob_base_fee_scalar: Some(U256::from(810949)),
+            ..Default::default()
+        };
+        assert_eq!(
+            L1BlockInfo::from_calldata(&ecotone, spec),
+            Ok(expected.clone())
+        );
+
+        // setL1BlockValuesIsthmus appends the operator fee scalar and constant.
+        let mut isthmus = ecotone.clone();
+        isthmus[..4].copy_from_slice(&ISTHMUS_L1_ATTRIBUTES_SELECTOR);
+        isthmus.extend(2_000_000u32.to_be_bytes());
+        isthmus.extend(1000u64.to_be_bytes());
+        assert_eq!(
+            L1BlockInfo::from_calldata(&isthmus, OpSpec::Op(OpSpecId::ISTHMUS)),
+            Ok(L1BlockInfo {
+                operator_fee_scalar: Some(U256::from(2_000_000)),
+                operator_fee_constant: Some(U256::from(1000)),
+                ..expected
+            })
+        );
+
+        // setL1BlockValues with base fee 1 gwei, overhead 188 and scalar 684000.
+        let mut bedrock = Vec::from(BEDROCK_L1_ATTRIBUTES_SELECTOR);
+        for word in [0, 0, 1_000_000_000, 0, 0, 0, 188, 684_000] {
+            bedrock.extend(U256::from(word).to_be_bytes::<32>());
+        }
+        assert_eq!(
+            L1BlockInfo::from_calldata(&bedrock, OpSpec::Op(OpSpecId::REGOLITH)),
+            Ok(L1BlockInfo {
+                l1_base_fee: U256::from(1_000_000_000),
+                l1_fee_overhead: Some(U256::from(188)),
+                l1_base_fee_scalar: U256::from(684_000),
+                ..Default::default()
+            })
+        );
+        // The Ecotone activation block uses the Bedrock cost function.
+        assert!(
+            L1BlockInfo::from_calldata(&bedrock, spec)
+                .unwrap()
+                .empty_scalars
+        );
+
+        assert_eq!(
+            L1BlockInfo::from_calldata(&ecotone[..100], spec),
+            Err(L1AttributesError::InvalidLength {
+                expected: 164,
+                got: 100
+            })
+        );
+        assert_eq!(
+            L1BlockInfo::from_calldata(&[0; 164], spec),
+            Err(L1AttributesError::UnknownSelector)
+        );
+    }
+
+    #[test]
+    fn custom_l1_cost_calculator() {
+        /// Charges twice the L1 data fee of the OP stack.
+        #[derive(Default)]
+        struct DoubleCost(L1BlockInfo);
+
+        impl L1CostCalculator for DoubleCost {
+            fn l1_block_info(&self) -> &L1BlockInfo {
+                &self.0
+            }
+
+            fn l1_block_info_mut(&mut self) -> &mut L1BlockInfo {
+                &mut self.0
+            }
+
+            fn calculate_tx_l1_cost(&self, input: &[u8], spec_id: OpSpec) -> U256 {
+                self.0.calculate_tx_l1_cost(input, spec_id) * U256::from(2)
+            }
+        }
+
+        let db = l1_block_db(
+            U256::MAX,
+            &[
+                (L1_BASE_FEE_SLOT, U256::from(1_000_000_000)),
+                (ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(10)),
+                // Base fee scalar of 1368 and blob base fee scalar of 810949.
+                (
+                    ECOTONE_L1_FEE_SCALARS_SLOT,
+                    (U256::from(1368) << 96) | (U256::from(810949) << 64),
+                ),
+            ],
+        );
+        let enveloped_tx = Bytes::from_static(&[0xFA; 200]);
+        let spec = OpSpec::Op(OpSpecId::FJORD);
+        let mut ctx: TestContext<DoubleCost> =
+            call_ctx(db, OpSpecId::FJORD, 0, enveloped_tx.clone())
+                .with_chain(DoubleCost::default());
+        let state = transact_op(&mut ctx).unwrap().state;
+
+        let l1_block_info =
+            L1BlockInfo::try_fetch(&mut ctx.journaled_state.database, spec).unwrap();
+        let cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, spec);
+        assert!(!cost.is_zero());
+        assert_eq!(state[&L1_FEE_RECIPIENT].info.balance, cost * U256::from(2));
+    }
+
+    #[test]
+    fn isthmus_operator_fee() {
+        let balance = U256::from(1_000_000_000_000_000_000u64);
+        let db = l1_block_db(
+            balance,
+            &[
+                (L1_BASE_FEE_SLOT, U256::from(1_000_000_000)),
+                (ECOTONE_L1_BLOB_BASE_FEE_SLOT, U256::from(10)),
+                (
+                    ECOTONE_L1_FEE_SCALARS_SLOT,
+                    (U256::from(1368) << 96) | (U256::from(810949) << 64),
+                ),
+                // Operator fee scalar of 2e6 and operator fee constant of 1000.
+                (
+                    ISTHMUS_OPERATOR_FEE_SCALARS_SLOT,
+                    (U256::from(2_000_000) << 64) | U256::from(1000),
+                ),
+            ],
+        );
+        let enveloped_tx = Bytes::from_static(&[0xFA; 200]);
+        let spec = OpSpec::Op(OpSpecId::ISTHMUS);
+        let mut ctx = call_ctx(db, OpSpecId::ISTHMUS, 0, enveloped_tx.clone());
+        let result_and_state = transact_op(&mut ctx).unwrap();
+        assert_eq!(result_and_state.result.gas_used(), 21_000);
+
+        let l1_block_info =
+            L1BlockInfo::try_fetch(&mut ctx.journaled_state.database, spec).unwrap();
+        assert_eq!(
+            l1_block_info.operator_fee_scalar,
+            Some(U256::from(2_000_000))
+        );
+        assert_eq!(l1_block_info.operator_fee_constant, Some(U256::from(1000)));
+
+        // Operator fee is charged for the gas used only: 21000 * 2 + 1000.
+        let operator_fee = U256::from(43_000);
+        let l1_cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, spec);
+        let receipt = crate::OpReceiptBuilder::new(spec).build_with_l1_fee(
+            &ctx.tx,
+            &result_and_state,
+            &l1_block_info,
+        );
+        assert_eq!(receipt.l1_fee.unwrap().operator_fee, Some(operator_fee));
+        let state = result_and_state.state;
+        assert_eq!(state[&OPERATOR_FEE_RECIPIENT].info.balance, operator_fee);
+        assert_eq!(
+            state[&BENCH_CALLER].info.balance,
+            balance - l1_cost - operator_fee
+        );
+
+        // Relocated vaults are kept when the info is fetched for the next block.
+        let fee_recipients = FeeRecipients {
+            base_fee: address!("0000000000000000000000000000000000000001"),
+            l1_fee: address!("0000000000000000000000000000000000000002"),
+            operator_fee: address!("0000000000000000000000000000000000000003"),
+        };
+        ctx.chain.fee_recipients = fee_recipients;
+        ctx.block.number = 1;
+        let state = transact_op(&mut ctx).unwrap().state;
+        assert_eq!(ctx.chain.l2_block, Some(1));
+        assert_eq!(state[&fee_recipients.l1_fee].info.balance, l1_cost);
+        assert_eq!(
+            state[&fee_recipients.operator_fee].info.balance,
+            operator_fee
+        );
+        assert!(state.contains_key(&fee_recipients.base_fee));
+        assert!(!state.contains_key(&OPERATOR_FEE_RECIPIENT));
+    }
+
+    #[test]
+    fn operator_fee_of_deposit() {
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(2_000_000)),
+            operator_fee_constant: Some(U256::from(1000)),
+            ..Default::default()
+        };
+        let isthmus = OpSpec::Op(OpSpecId::ISTHMUS);
+        assert_eq!(
+            l1_block_info.operator_fee_cost(&[0x02, 0xc0], 21_000, isthmus),
+            Some(U256::from(43_000))
+        );
+        // Deposits do not pay the operator fee, nor do transactions before Isthmus.
+        assert_eq!(
+            l1_block_info.operator_fee_cost(&[0x7E, 0xc0], 21_000, isthmus),
+            Some(U256::ZERO)
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_cost(&[0x02, 0xc0], 21_000, OpSpecId::HOLOCENE.into()),
+            None
+        );
+    }
+
+    #[test]
+    fn base_fee_routing() {
+        let db = l1_block_db(U256::MAX, &[]);
+        let mut ctx = call_ctx(db, OpSpecId::FJORD, 10, [0xFA; 10].into());
+        ctx.block.basefee = 10;
+        let base_fee = U256::from(210_000);
+
+        let state = transact_op(&mut ctx).unwrap().state;
+        assert_eq!(state[&BASE_FEE_RECIPIENT].info.balance, base_fee);
+
+        ctx.chain.base_fee_routing = BaseFeeRouting::Burn;
+        let state = transact_op(&mut ctx).unwrap().state;
+        assert!(!state.contains_key(&BASE_FEE_RECIPIENT));
+
+        // A quarter of the base fee is shared with the recipient.
+        let recipient = address!("0000000000000000000000000000000000000001");
+        ctx.chain.base_fee_routing = BaseFeeRouting::Split {
+            recipient,
+            share_bps: 2_500,
+        };
+        let state = transact_op(&mut ctx).unwrap().state;
+        assert_eq!(state[&recipient].info.balance, U256::from(52_500));
+        assert_eq!(state[&BASE_FEE_RECIPIENT].info.balance, U256::from(157_500));
+    }
+
+    #[test]
+    fn cached_per_block() {
+        let db = l1_block_db(U256::MAX, &[(L1_BASE_FEE_SLOT, U256::from(1))]);
+        let mut ctx = call_ctx(db, OpSpecId::FJORD, 0, [0xFA; 10].into());
+        ctx.block.number = 10;
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l2_block, Some(10));
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(1));
+
+        // Storage is only read again for the next block, or after a reset.
+        ctx.journaled_state
+            .database
+            .insert_account_storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT, U256::from(2))
+            .unwrap();
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(1));
+
+        ctx.block.number = 11;
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(2));
+
+        ctx.journaled_state
+            .database
+            .insert_account_storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT, U256::from(3))
+            .unwrap();
+        ctx.chain.reset();
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(3));
+        assert_eq!(ctx.chain.l2_block, Some(11));
+    }
+
+    #[test]
+    fn refreshed_by_l1_attributes_deposit() {
+        // The L1Block contract stores 1 in its first slot.
+        let mut db = l1_block_db(U256::MAX, &[]);
+        db.insert_account_info(
+            L1_BLOCK_CONTRACT,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(bytes!("600160005500"))),
+        );
+
+        let mut ecotone = Vec::from(ECOTONE_L1_ATTRIBUTES_SELECTOR);
+        ecotone.extend(1368u32.to_be_bytes());
+        ecotone.extend(810949u32.to_be_bytes());
+        ecotone.extend([0; 24]);
+        ecotone.extend(U256::from(1_000_000_000).to_be_bytes::<32>());
+        ecotone.extend(U256::from(10).to_be_bytes::<32>());
+        ecotone.extend([0; 64]);
+
+        let mut ctx = call_ctx(db, OpSpecId::ECOTONE, 0, [0xFA; 10].into());
+        let call = ctx.tx.clone();
+        ctx.tx = OpTransaction::new(TxEnv {
+            tx_type: DEPOSIT_TRANSACTION_TYPE,
+            caller: BENCH_CALLER,
+            kind: TxKind::Call(L1_BLOCK_CONTRACT),
+            data: ecotone.into(),
+            gas_limit: 1_000_000,
+            gas_price: 0,
+            gas_priority_fee: None,
+            ..Default::default()
+        });
+        ctx.block.number = 10;
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l2_block, Some(10));
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(1_000_000_000));
+        assert_eq!(ctx.chain.l1_blob_base_fee_scalar, Some(U256::from(810949)));
+
+        // The next transaction of the block uses the decoded info, not the empty storage.
+        ctx.tx = call;
+        transact_op(&mut ctx).unwrap();
+        assert_eq!(ctx.chain.l1_base_fee, U256::from(1_000_000_000));
+    }
 }
@@ -8,16 +8,37 @@ extern crate alloc as std;
 pub mod api;
 pub mod bn128;
 pub mod context;
+pub mod eip1559;
 pub mod fast_lz;
+#[cfg(feature = "fault-proof")]
+pub mod fault_proof;
 pub mod handler;
+pub mod inspector;
 pub mod l1block;
+pub mod receipt;
+pub mod registry;
 pub mod result;
 pub mod spec;
 pub mod transaction;
 
+pub use handler::{
+    gas_token::GasToken, precompiles::CustomPrecompiles, BaseFeeRouting, DepositGasRules,
+};
+pub use inspector::{
+    interop::{InteropInspector, InteropViolation},
+    Withdrawal, WithdrawalInspector, L2_TO_L1_MESSAGE_PASSER,
+};
 pub use l1block::{
-    L1BlockInfo, L1BlockInfoGetter, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT,
+    l1_cost_bedrock, l1_cost_ecotone, l1_cost_fjord, CompressionEstimator, FeeRecipients,
+    L1BlockInfo, L1BlockInfoGetter, L1CostBreakdown, L1CostCalculator, L1CostOverflow,
+    RpcL1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT,
+    OPERATOR_FEE_RECIPIENT,
 };
-pub use result::OptimismHaltReason;
+pub use receipt::{L1FeeFields, OpReceipt, OpReceiptBuilder};
+pub use registry::{OpChainConfig, OpChainRegistry};
+pub use result::{DepositHaltReason, OptimismHaltReason};
 pub use spec::*;
-pub use transaction::{error::OpTransactionError, estimate_tx_compressed_size, OpTransaction};
+pub use transaction::{
+    error::OpTransactionError, estimate_tx_compressed_size, estimate_tx_compressed_size_many,
+    OpTransaction,
+};
@@ -0,0 +1,385 @@
+//! Receipts of Optimism transactions, with the fields of deposit receipts and the L1 data fee
+//! fields of other receipts.
+use crate::{
+    transaction::{deposit::DEPOSIT_TRANSACTION_TYPE, OpTxTrait},
+    L1BlockInfo, OpSpec, OpSpecId,
+};
+use revm::{
+    context_interface::{
+        receipt::{Receipt, ReceiptBuilder},
+        result::{ExecutionResult, HaltReasonTrait, ResultAndState},
+        Transaction,
+    },
+    primitives::{Address, U256},
+    state::EvmState,
+};
+
+/// Version of the receipts of deposits since Canyon.
+pub const DEPOSIT_RECEIPT_VERSION: u64 = 1;
+
+/// Returns the nonce of the caller of a deposit before it was executed, from the state of the
+/// deposit.
+///
+/// Deposits always increment the nonce of their caller, even when they fail.
+pub fn deposit_nonce(caller: Address, state: &EvmState) -> Option<u64> {
+    state
+        .get(&caller)
+        .map(|account| account.info.nonce.saturating_sub(1))
+}
+
+/// Receipt of an Optimism transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OpReceipt {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub inner: Receipt,
+    /// Nonce of the caller before a deposit, since Regolith.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub deposit_nonce: Option<u64>,
+    /// Version of the receipt of a deposit, 1 since Canyon.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub deposit_receipt_version: Option<u64>,
+    /// L1 data fee of a transaction other than a deposit, see
+    /// [`OpReceiptBuilder::build_with_l1_fee`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_fee: Option<L1FeeFields>,
+}
+
+/// L1 data fee fields of the receipt of a transaction, as returned by op-geth.
+///
+/// The decimal `l1FeeScalar` of receipts before Ecotone is not included, the integer scalar it is
+/// computed from is the [`l1_base_fee_scalar`][L1BlockInfo::l1_base_fee_scalar] of the block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct L1FeeFields {
+    /// L1 data fee charged to the transaction.
+    pub l1_fee: U256,
+    /// L1 gas used by the transaction data, with the L1 fee overhead before Ecotone.
+    pub l1_gas_used: U256,
+    /// Base fee of the L1 origin block.
+    pub l1_gas_price: U256,
+    /// L1 base fee scalar, since Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_base_fee_scalar: Option<U256>,
+    /// Blob base fee of the L1 origin block, since Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_blob_base_fee: Option<U256>,
+    /// L1 blob base fee scalar, since Ecotone.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// Operator fee scalar, since Isthmus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub operator_fee_scalar: Option<U256>,
+    /// Operator fee constant, since Isthmus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub operator_fee_constant: Option<U256>,
+    /// Operator fee paid by the transaction, after the refund of the unused gas, since Isthmus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub operator_fee: Option<U256>,
+}
+
+impl L1FeeFields {
+    /// Returns the L1 data fee fields of the enveloped transaction using `gas_used`, executed in a
+    /// block of the given spec with the given L1 block info.
+    pub fn new(
+        l1_block_info: &L1BlockInfo,
+        enveloped_tx: &[u8],
+        gas_used: u64,
+        spec: OpSpec,
+    ) -> Self {
+        let breakdown = l1_block_info.calculate_tx_l1_cost_breakdown(enveloped_tx, spec);
+        let is_ecotone = spec.is_enabled_in(OpSpecId::ECOTONE);
+        // The overhead is only set when the Bedrock cost function is used.
+        let l1_gas_used = if spec.is_enabled_in(OpSpecId::FJORD) {
+            breakdown.data_gas
+        } else {
+            breakdown
+                .data_gas
+                .saturating_add(breakdown.l1_fee_overhead.unwrap_or_default())
+        };
+        Self {
+            l1_fee: breakdown.total,
+            l1_gas_used,
+            l1_gas_price: l1_block_info.l1_base_fee,
+            l1_base_fee_scalar: is_ecotone.then_some(l1_block_info.l1_base_fee_scalar),
+            l1_blob_base_fee: l1_block_info.l1_blob_base_fee.filter(|_| is_ecotone),
+            l1_blob_base_fee_scalar: l1_block_info.l1_blob_base_fee_scalar.filter(|_| is_ecotone),
+            operator_fee_scalar: l1_block_info.operator_fee_scalar,
+            operator_fee_constant: l1_block_info.operator_fee_constant,
+            operator_fee: l1_block_info.operator_fee_cost(enveloped_tx, gas_used, spec),
+        }
+    }
+}
+
+/// Builds the receipts of consecutive Optimism transactions, e.g. of a block.
+#[derive(Clone, Debug)]
+pub struct OpReceiptBuilder {
+    inner: ReceiptBuilder,
+    spec: OpSpec,
+}
+
+impl OpReceiptBuilder {
+    /// Creates a builder for the first transaction of a block of the given spec.
+    pub fn new(spec: impl Into<OpSpec>) -> Self {
+        Self {
+            inner: ReceiptBuilder::new(),
+            spec: spec.into(),
+        }
+    }
+
+    /// Returns the receipt of the next transaction.
+    ///
+    /// `caller_nonce` is the nonce of the caller before the transaction, only used for deposits.
+    pub fn build<HaltReasonT: HaltReasonTrait>(
+        &mut self,
+        tx_type: u8,
+        result: &ExecutionResult<HaltReasonT>,
+        caller_nonce: u64,
+    ) -> OpReceipt {
+        let is_deposit = tx_type == DEPOSIT_TRANSACTION_TYPE;
+        OpReceipt {
+            inner: self.inner.build(tx_type, result),
+            deposit_nonce: (is_deposit && self.spec.is_enabled_in(OpSpecId::REGOLITH))
+                .then_some(caller_nonce),
+            deposit_receipt_version: (is_deposit && self.spec.is_enabled_in(OpSpecId::CANYON))
+                .then_some(DEPOSIT_RECEIPT_VERSION),
+            l1_fee: None,
+        }
+    }
+
+    /// Returns the receipt of the next transaction like [`OpReceiptBuilder::build_with_state`],
+    /// with the L1 data fee fields of a transaction other than a deposit computed from the L1
+    /// block info it was executed with.
+    pub fn build_with_l1_fee<HaltReasonT: HaltReasonTrait>(
+        &mut self,
+        tx: &impl OpTxTrait,
+        result: &ResultAndState<HaltReasonT>,
+        l1_block_info: &L1BlockInfo,
+    ) -> OpReceipt {
+        let mut receipt = self.build_with_state(tx, result);
+        if tx.tx_type() != DEPOSIT_TRANSACTION_TYPE {
+            let enveloped_tx = tx.enveloped_tx().map(|tx| tx.as_ref()).unwrap_or_default();
+            receipt.l1_fee = Some(L1FeeFields::new(
+                l1_block_info,
+                enveloped_tx,
+                result.result.gas_used(),
+                self.spec,
+            ));
+        }
+        receipt
+    }
+
+    /// Returns the receipt of the next transaction, with the nonce of the caller of a deposit
+    /// taken from the state of its execution, see [`deposit_nonce`].
+    pub fn build_with_state<HaltReasonT: HaltReasonTrait>(
+        &mut self,
+        tx: &impl Transaction,
+        result: &ResultAndState<HaltReasonT>,
+    ) -> OpReceipt {
+        let caller_nonce = deposit_nonce(tx.caller(), &result.state).unwrap_or_default();
+        self.build(tx.tx_type(), &result.result, caller_nonce)
+    }
+
+    /// Returns the inner builder, e.g. for the gas used and the logs bloom of the block.
+    pub fn inner(&self) -> &ReceiptBuilder {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::exec_op::transact_op, DepositGasRules, OpTransaction, OptimismHaltReason};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use revm::{
+        bytecode::Bytecode,
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::result::HaltReason,
+        primitives::{bytes, Bytes, TxKind},
+        Context, JournaledState,
+    };
+
+    type TestContext = Context<
+        BlockEnv,
+        OpTransaction<TxEnv>,
+        CfgEnv<OpSpec>,
+        BenchmarkDB,
+        JournaledState<BenchmarkDB>,
+        L1BlockInfo,
+    >;
+
+    /// Returns a context executing a deposit of [`BENCH_CALLER`] with 100k gas to
+    /// [`BENCH_TARGET`], whose code is `code`.
+    fn deposit_ctx(spec: OpSpecId, code: Bytes) -> TestContext {
+        Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code)))
+            .with_tx(OpTransaction::new(TxEnv {
+                tx_type: DEPOSIT_TRANSACTION_TYPE,
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                gas_limit: 100_000,
+                gas_price: 0,
+                gas_priority_fee: None,
+                ..Default::default()
+            }))
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(spec)))
+            .with_chain(L1BlockInfo::default())
+    }
+
+    #[test]
+    fn deposit_fields() {
+        let halt = ExecutionResult::<OptimismHaltReason>::Halt {
+            reason: OptimismHaltReason::Base(HaltReason::OpcodeNotFound),
+            gas_used: 21_000,
+            effective_gas_price: 0,
+            debug: None,
+        };
+
+        let mut builder = OpReceiptBuilder::new(OpSpecId::REGOLITH);
+        let deposit = builder.build(DEPOSIT_TRANSACTION_TYPE, &halt, 5);
+        assert_eq!(deposit.deposit_nonce, Some(5));
+        assert_eq!(deposit.deposit_receipt_version, None);
+        let legacy = builder.build(0, &halt, 5);
+        assert_eq!(legacy.deposit_nonce, None);
+        assert_eq!(legacy.inner.cumulative_gas_used, 42_000);
+
+        let mut builder = OpReceiptBuilder::new(OpSpecId::CANYON);
+        let deposit = builder.build(DEPOSIT_TRANSACTION_TYPE, &halt, 5);
+        assert_eq!(deposit.deposit_receipt_version, Some(1));
+
+        let mut builder = OpReceiptBuilder::new(OpSpecId::BEDROCK);
+        let deposit = builder.build(DEPOSIT_TRANSACTION_TYPE, &halt, 5);
+        assert_eq!(deposit.deposit_nonce, None);
+    }
+
+    #[test]
+    fn deposit_nonce_from_state() {
+        let spec = OpSpec::Op(OpSpecId::CANYON);
+        let mut ctx = deposit_ctx(OpSpecId::CANYON, Bytes::new());
+        let result = transact_op(&mut ctx).unwrap();
+        assert_eq!(result.state[&BENCH_CALLER].info.nonce, 1);
+
+        let receipt = OpReceiptBuilder::new(spec).build_with_state(&ctx.tx, &result);
+        assert_eq!(receipt.deposit_nonce, Some(0));
+        assert_eq!(
+            receipt.deposit_receipt_version,
+            Some(DEPOSIT_RECEIPT_VERSION)
+        );
+    }
+
+    #[test]
+    fn bedrock_system_deposit() {
+        // The targets stop, revert and loop until they run out of gas.
+        for code in [bytes!("00"), bytes!("60006000fd"), bytes!("5b600056")] {
+            let mut ctx = deposit_ctx(OpSpecId::BEDROCK, code);
+            let mut builder = OpReceiptBuilder::new(OpSpecId::BEDROCK);
+
+            // Bedrock deposits use their gas limit, system deposits no gas.
+            let result = transact_op(&mut ctx).unwrap();
+            assert_eq!(result.result.gas_used(), 100_000);
+            ctx.tx.deposit.is_system_transaction = true;
+            let result = transact_op(&mut ctx).unwrap();
+            assert_eq!(result.result.gas_used(), 0);
+            builder.build_with_state(&ctx.tx, &result);
+            assert_eq!(builder.inner().cumulative_gas_used(), 0);
+        }
+    }
+
+    #[test]
+    fn forced_deposit_gas_rules() {
+        let gas_used = |spec: OpSpecId, deposit_gas_rules| {
+            let mut ctx = deposit_ctx(spec, bytes!("00"));
+            ctx.chain.deposit_gas_rules = deposit_gas_rules;
+            transact_op(&mut ctx).unwrap().result.gas_used()
+        };
+        assert_eq!(gas_used(OpSpecId::BEDROCK, DepositGasRules::Spec), 100_000);
+        assert_eq!(gas_used(OpSpecId::REGOLITH, DepositGasRules::Spec), 21_000);
+        assert_eq!(
+            gas_used(OpSpecId::BEDROCK, DepositGasRules::Regolith),
+            21_000
+        );
+        assert_eq!(
+            gas_used(OpSpecId::REGOLITH, DepositGasRules::Bedrock),
+            100_000
+        );
+    }
+
+    #[test]
+    fn l1_fee_fields() {
+        let spec = OpSpec::Op(OpSpecId::ECOTONE);
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000_000),
+            l1_blob_base_fee: Some(U256::from(10)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000_000)),
+            ..Default::default()
+        };
+        let mut ctx = deposit_ctx(OpSpecId::ECOTONE, Bytes::new());
+        ctx.tx.base.tx_type = 0;
+        ctx.tx.enveloped_tx = Some(bytes!("FACADE"));
+        ctx.chain = l1_block_info.clone();
+        let result = transact_op(&mut ctx).unwrap();
+
+        // 3 non zero bytes use 48 gas, each paying 16 * 1000 + 10 L1 wei.
+        let receipt =
+            OpReceiptBuilder::new(spec).build_with_l1_fee(&ctx.tx, &result, &l1_block_info);
+        assert_eq!(
+            receipt.l1_fee,
+            Some(L1FeeFields {
+                l1_fee: U256::from(48_030),
+                l1_gas_used: U256::from(48),
+                l1_gas_price: U256::from(1_000),
+                l1_base_fee_scalar: Some(U256::from(1_000_000)),
+                l1_blob_base_fee: Some(U256::from(10)),
+                l1_blob_base_fee_scalar: Some(U256::from(1_000_000)),
+                operator_fee_scalar: None,
+                operator_fee_constant: None,
+                operator_fee: None,
+            })
+        );
+
+        // Before Ecotone, the L1 gas used includes the overhead.
+        let l1_block_info = L1BlockInfo {
+            l1_fee_overhead: Some(U256::from(100)),
+            ..l1_block_info
+        };
+        let fields = L1FeeFields::new(
+            &l1_block_info,
+            &bytes!("FACADE"),
+            21_000,
+            OpSpecId::REGOLITH.into(),
+        );
+        assert_eq!(fields.l1_gas_used, U256::from(148));
+        assert_eq!(fields.l1_fee, U256::from(148_000));
+        assert_eq!(fields.l1_base_fee_scalar, None);
+        assert_eq!(fields.l1_blob_base_fee, None);
+
+        ctx.tx.base.tx_type = DEPOSIT_TRANSACTION_TYPE;
+        let receipt =
+            OpReceiptBuilder::new(spec).build_with_l1_fee(&ctx.tx, &result, &l1_block_info);
+        assert_eq!(receipt.l1_fee, None);
+    }
+}
@@ -0,0 +1,230 @@
+//! Registry of OP stack chains.
+//!
+//! The registry maps chain ids to the configuration of each chain: its hardfork activations, the
+//! vaults receiving the fees and the default EIP-1559 parameters. It is shipped with the
+//! well-known chains, and other chains can be inserted or deserialized at runtime.
+use crate::{
+    eip1559::{Eip1559Params, CANYON_EIP1559_PARAMS},
+    l1block::FeeRecipients,
+    OpChainSpec, OpSpec, OpSpecId, BASE_CHAIN_SPEC, BASE_FEE_RECIPIENT, L1_FEE_RECIPIENT,
+    MODE_CHAIN_SPEC, OPERATOR_FEE_RECIPIENT, OP_MAINNET_CHAIN_SPEC, ZORA_CHAIN_SPEC,
+};
+use revm::{context::CfgEnv, primitives::Address, specification::schedule::ForkCondition};
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Configuration of an OP stack chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct OpChainConfig {
+    /// Chain id of the chain.
+    pub chain_id: u64,
+    /// Name of the chain.
+    pub name: String,
+    /// Hardforks of the chain, in activation order, with the condition activating each.
+    pub hardforks: Vec<(OpSpecId, ForkCondition)>,
+    /// Vault receiving the base fee.
+    pub base_fee_recipient: Address,
+    /// Vault receiving the L1 data fee.
+    pub l1_fee_recipient: Address,
+    /// Vault receiving the operator fee.
+    pub operator_fee_recipient: Address,
+    /// EIP-1559 parameters used until set by the system config.
+    pub eip1559_params: Eip1559Params,
+}
+
+impl OpChainConfig {
+    /// Creates the configuration of a chain with the given hardforks, the fee vault predeploys and
+    /// the Canyon EIP-1559 parameters.
+    pub fn new(name: impl Into<String>, chain_spec: &OpChainSpec) -> Self {
+        Self {
+            chain_id: chain_spec.chain_id,
+            name: name.into(),
+            hardforks: chain_spec.hardforks.forks().to_vec(),
+            base_fee_recipient: BASE_FEE_RECIPIENT,
+            l1_fee_recipient: L1_FEE_RECIPIENT,
+            operator_fee_recipient: OPERATOR_FEE_RECIPIENT,
+            eip1559_params: CANYON_EIP1559_PARAMS,
+        }
+    }
+
+    /// Returns the spec of the block with the given timestamp, or `None` if the chain has no
+    /// hardforks.
+    ///
+    /// As with [`OpChainSpec::spec_at`], hardforks activated by block number are always active.
+    pub fn spec_at(&self, timestamp: u64) -> Option<OpSpecId> {
+        self.hardforks
+            .iter()
+            .rev()
+            .find(|(_, condition)| condition.is_active_at(u64::MAX, timestamp))
+            .or(self.hardforks.first())
+            .map(|(spec, _)| *spec)
+    }
+
+    /// Returns the vaults receiving the fees, set as the
+    /// [`fee_recipients`][crate::L1BlockInfo::fee_recipients] of the L1 block info.
+    pub fn fee_recipients(&self) -> FeeRecipients {
+        FeeRecipients {
+            base_fee: self.base_fee_recipient,
+            l1_fee: self.l1_fee_recipient,
+            operator_fee: self.operator_fee_recipient,
+        }
+    }
+
+    /// Returns the configuration of the environment executing the block with the given timestamp.
+    pub fn cfg_at(&self, timestamp: u64) -> Option<CfgEnv<OpSpec>> {
+        let spec = self.spec_at(timestamp)?;
+        Some(
+            CfgEnv::default()
+                .with_chain_id(self.chain_id)
+                .with_spec(OpSpec::Op(spec)),
+        )
+    }
+}
+
+/// Configurations of OP stack chains by chain id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Vec<OpChainConfig>", into = "Vec<OpChainConfig>")
+)]
+pub struct OpChainRegistry {
+    chains: BTreeMap<u64, OpChainConfig>,
+}
+
+impl OpChainRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry of OP mainnet, Base, Zora and Mode.
+    pub fn known() -> Self {
+        [
+            OpChainConfig::new("OP Mainnet", &OP_MAINNET_CHAIN_SPEC),
+            OpChainConfig::new("Base", &BASE_CHAIN_SPEC),
+            OpChainConfig::new("Zora", &ZORA_CHAIN_SPEC),
+            OpChainConfig::new("Mode", &MODE_CHAIN_SPEC),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    /// Inserts the configuration of a chain, returning the previous one with the same chain id.
+    pub fn insert(&mut self, config: OpChainConfig) -> Option<OpChainConfig> {
+        self.chains.insert(config.chain_id, config)
+    }
+
+    /// Returns the configuration of the chain with the given id.
+    pub fn get(&self, chain_id: u64) -> Option<&OpChainConfig> {
+        self.chains.get(&chain_id)
+    }
+
+    /// Returns the configurations of the chains, ordered by chain id.
+    pub fn iter(&self) -> impl Iterator<Item = &OpChainConfig> {
+        self.chains.values()
+    }
+
+    /// Returns the spec of the block with the given timestamp of the chain with the given id.
+    pub fn spec_at(&self, chain_id: u64, timestamp: u64) -> Option<OpSpecId> {
+        self.get(chain_id)?.spec_at(timestamp)
+    }
+}
+
+impl Extend<OpChainConfig> for OpChainRegistry {
+    fn extend<T: IntoIterator<Item = OpChainConfig>>(&mut self, iter: T) {
+        for config in iter {
+            self.insert(config);
+        }
+    }
+}
+
+impl FromIterator<OpChainConfig> for OpChainRegistry {
+    fn from_iter<T: IntoIterator<Item = OpChainConfig>>(iter: T) -> Self {
+        let mut registry = Self::new();
+        registry.extend(iter);
+        registry
+    }
+}
+
+impl From<Vec<OpChainConfig>> for OpChainRegistry {
+    fn from(configs: Vec<OpChainConfig>) -> Self {
+        configs.into_iter().collect()
+    }
+}
+
+impl From<OpChainRegistry> for Vec<OpChainConfig> {
+    fn from(registry: OpChainRegistry) -> Self {
+        registry.chains.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BASE_CHAIN_ID, MODE_CHAIN_ID, OP_MAINNET_CHAIN_ID, ZORA_CHAIN_ID};
+
+    #[test]
+    fn known_chains() {
+        let registry = OpChainRegistry::known();
+        assert_eq!(
+            registry.iter().map(|c| c.chain_id).collect::<Vec<_>>(),
+            [
+                OP_MAINNET_CHAIN_ID,
+                BASE_CHAIN_ID,
+                MODE_CHAIN_ID,
+                ZORA_CHAIN_ID
+            ]
+        );
+        assert_eq!(
+            registry.spec_at(ZORA_CHAIN_ID, 1_710_374_401),
+            Some(OpSpecId::ECOTONE)
+        );
+        assert_eq!(
+            registry.spec_at(OP_MAINNET_CHAIN_ID, 0),
+            Some(OpSpecId::REGOLITH)
+        );
+        assert_eq!(registry.spec_at(1, 0), None);
+
+        let cfg = registry
+            .get(BASE_CHAIN_ID)
+            .unwrap()
+            .cfg_at(u64::MAX)
+            .unwrap();
+        assert_eq!(cfg.chain_id, BASE_CHAIN_ID);
+        assert_eq!(cfg.spec.into_eth_spec(), OpSpecId::ISTHMUS.into_eth_spec());
+    }
+
+    #[test]
+    fn insert_chain() {
+        let mut registry = OpChainRegistry::new();
+        let config = OpChainConfig {
+            chain_id: 901,
+            name: "devnet".into(),
+            hardforks: vec![
+                (OpSpecId::GRANITE, ForkCondition::Block(0)),
+                (OpSpecId::HOLOCENE, ForkCondition::Timestamp(100)),
+            ],
+            ..OpChainConfig::new("", &BASE_CHAIN_SPEC)
+        };
+        assert_eq!(registry.insert(config.clone()), None);
+        assert_eq!(registry.spec_at(901, 99), Some(OpSpecId::GRANITE));
+        assert_eq!(registry.spec_at(901, 100), Some(OpSpecId::HOLOCENE));
+        assert_eq!(registry.insert(config.clone()), Some(config));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_registry() {
+        let registry = OpChainRegistry::known();
+        let json = serde_json::to_string(&registry).unwrap();
+        assert_eq!(
+            serde_json::from_str::<OpChainRegistry>(&json).unwrap(),
+            registry
+        );
+    }
+}
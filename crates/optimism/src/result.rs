@@ -1,10 +1,58 @@
-use revm::context_interface::result::HaltReason;
+use revm::{
+    context_interface::result::{HaltReason, OutOfGasError},
+    primitives::{Address, U256},
+};
 
+/// Halt reason of an Optimism transaction.
+///
+/// Serialized as the base halt reason, `failedDeposit`, or the tagged deposit halt reason.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum OptimismHaltReason {
     Base(HaltReason),
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "failed_deposit::serialize",
+            deserialize_with = "failed_deposit::deserialize"
+        )
+    )]
     FailedDeposit,
+    /// A deposit failed for a known reason, its mint and nonce are persisted as with
+    /// [`OptimismHaltReason::FailedDeposit`].
+    Deposit(DepositHaltReason),
+}
+
+/// Reason of a failed deposit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+pub enum DepositHaltReason {
+    /// The mint overflows the balance of the caller.
+    MintOverflow { mint: u128, balance: U256 },
+    /// The deposit ran out of gas.
+    OutOfGas {
+        gas_limit: u64,
+        reason: OutOfGasError,
+    },
+    /// The deposit halted for another reason.
+    Halted(HaltReason),
+    /// The caller account could not be loaded to persist the mint and the nonce increment.
+    AccountTouch { caller: Address },
+}
+
+impl DepositHaltReason {
+    /// Returns the reason of a deposit with the given gas limit halting with `reason`.
+    pub fn from_halt(reason: HaltReason, gas_limit: u64) -> Self {
+        match reason {
+            HaltReason::OutOfGas(reason) => Self::OutOfGas { gas_limit, reason },
+            reason => Self::Halted(reason),
+        }
+    }
 }
 
 impl From<HaltReason> for OptimismHaltReason {
@@ -12,3 +60,130 @@ impl From<HaltReason> for OptimismHaltReason {
         Self::Base(value)
     }
 }
+
+#[cfg(feature = "serde")]
+mod failed_deposit {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::string::String;
+
+    const NAME: &str = "failedDeposit";
+
+    pub(super) fn serialize<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(NAME)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name == NAME {
+            Ok(())
+        } else {
+            Err(de::Error::invalid_value(de::Unexpected::Str(&name), &NAME))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_deposit_reason() {
+        use crate::{
+            api::exec_op::transact_op, transaction::deposit::DEPOSIT_TRANSACTION_TYPE, L1BlockInfo,
+            OpSpec, OpSpecId, OpTransaction,
+        };
+        use database::{CacheDB, BENCH_CALLER, BENCH_TARGET};
+        use revm::{
+            bytecode::Bytecode,
+            context::{CfgEnv, TxEnv},
+            context_interface::result::ExecutionResult,
+            database_interface::EmptyDB,
+            primitives::{bytes, TxKind},
+            state::AccountInfo,
+            Context,
+        };
+
+        // The target loops until it runs out of gas.
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(BENCH_CALLER, AccountInfo::default());
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(bytes!("5b600056"))),
+        );
+        let mut ctx = Context::default()
+            .with_db(db)
+            .with_tx(OpTransaction::new(TxEnv {
+                tx_type: DEPOSIT_TRANSACTION_TYPE,
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                gas_limit: 30_000,
+                gas_price: 0,
+                gas_priority_fee: None,
+                ..Default::default()
+            }))
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::ECOTONE)))
+            .with_chain(L1BlockInfo::default());
+        ctx.tx.deposit.mint = Some(1);
+
+        let halt = |result: ExecutionResult<OptimismHaltReason>| match result {
+            ExecutionResult::Halt { reason, .. } => reason,
+            result => panic!("deposit did not halt: {result:?}"),
+        };
+        let result = transact_op(&mut ctx).unwrap();
+        assert_eq!(
+            halt(result.result),
+            OptimismHaltReason::Deposit(DepositHaltReason::OutOfGas {
+                gas_limit: 30_000,
+                reason: OutOfGasError::Basic,
+            })
+        );
+        assert_eq!(result.state[&BENCH_CALLER].info.balance, U256::from(1));
+
+        ctx.journaled_state
+            .database
+            .insert_account_info(BENCH_CALLER, AccountInfo::from_balance(U256::MAX));
+        assert_eq!(
+            halt(transact_op(&mut ctx).unwrap().result),
+            OptimismHaltReason::Deposit(DepositHaltReason::MintOverflow {
+                mint: 1,
+                balance: U256::MAX,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        for (reason, json) in [
+            (OptimismHaltReason::FailedDeposit, r#""failedDeposit""#),
+            (
+                OptimismHaltReason::Base(HaltReason::InvalidJump),
+                r#""invalidJump""#,
+            ),
+            (
+                OptimismHaltReason::Base(HaltReason::OutOfGas(OutOfGasError::Memory)),
+                r#"{"outOfGas":"memory"}"#,
+            ),
+            (
+                OptimismHaltReason::Deposit(DepositHaltReason::from_halt(
+                    HaltReason::OutOfGas(OutOfGasError::Basic),
+                    21_000,
+                )),
+                r#"{"outOfGas":{"gasLimit":21000,"reason":"basic"}}"#,
+            ),
+            (
+                OptimismHaltReason::Deposit(DepositHaltReason::AccountTouch {
+                    caller: Address::ZERO,
+                }),
+                r#"{"accountTouch":{"caller":"0x0000000000000000000000000000000000000000"}}"#,
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&reason).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<OptimismHaltReason>(json).unwrap(),
+                reason
+            );
+        }
+        assert!(serde_json::from_str::<OptimismHaltReason>(r#""failed""#).is_err());
+    }
+}
@@ -1,16 +1,21 @@
-use revm::specification::hardfork::SpecId;
+use core::{fmt, str::FromStr};
+use revm::specification::{
+    hardfork::SpecId,
+    schedule::{ForkCondition, HardforkSchedule},
+};
+use std::string::String;
 
+/// Ethereum or Optimism spec, serialized as its name, see [`OpSpec::from_str`].
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpSpec {
     Eth(SpecId),
     Op(OpSpecId),
 }
 
+/// Optimism hardforks, serialized as their name, see [`OpSpecId::from_str`].
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[allow(non_camel_case_types)]
 pub enum OpSpecId {
     BEDROCK = 100,
@@ -21,6 +26,7 @@ pub enum OpSpecId {
     GRANITE,
     HOLOCENE,
     ISTHMUS,
+    INTEROP,
 }
 
 impl OpSpecId {
@@ -29,9 +35,12 @@ impl OpSpecId {
         match self {
             Self::BEDROCK | Self::REGOLITH => SpecId::MERGE,
             Self::CANYON => SpecId::SHANGHAI,
-            Self::ECOTONE | Self::FJORD | Self::GRANITE | Self::HOLOCENE | Self::ISTHMUS => {
-                SpecId::CANCUN
-            }
+            Self::ECOTONE
+            | Self::FJORD
+            | Self::GRANITE
+            | Self::HOLOCENE
+            | Self::ISTHMUS
+            | Self::INTEROP => SpecId::CANCUN,
         }
     }
 
@@ -71,6 +80,9 @@ impl TryFrom<&str> for OpSpecId {
             name::ECOTONE => Ok(OpSpecId::ECOTONE),
             name::FJORD => Ok(OpSpecId::FJORD),
             name::GRANITE => Ok(OpSpecId::GRANITE),
+            name::HOLOCENE => Ok(OpSpecId::HOLOCENE),
+            name::ISTHMUS => Ok(OpSpecId::ISTHMUS),
+            name::INTEROP => Ok(OpSpecId::INTEROP),
             _ => Err(()),
         }
     }
@@ -87,10 +99,57 @@ impl From<OpSpecId> for &'static str {
             OpSpecId::GRANITE => name::GRANITE,
             OpSpecId::HOLOCENE => name::HOLOCENE,
             OpSpecId::ISTHMUS => name::ISTHMUS,
+            OpSpecId::INTEROP => name::INTEROP,
         }
     }
 }
 
+impl FromStr for OpSpecId {
+    type Err = UnknownSpecError;
+
+    /// Parses the name of a hardfork, ignoring its case, e.g. `"ecotone"` or `"Ecotone"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        OP_SPECS
+            .into_iter()
+            .find(|spec| <&str>::from(*spec).eq_ignore_ascii_case(name))
+            .ok_or_else(|| UnknownSpecError { name: name.into() })
+    }
+}
+
+impl fmt::Display for OpSpecId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
+/// Optimism hardforks, in activation order.
+const OP_SPECS: [OpSpecId; 9] = [
+    OpSpecId::BEDROCK,
+    OpSpecId::REGOLITH,
+    OpSpecId::CANYON,
+    OpSpecId::ECOTONE,
+    OpSpecId::FJORD,
+    OpSpecId::GRANITE,
+    OpSpecId::HOLOCENE,
+    OpSpecId::ISTHMUS,
+    OpSpecId::INTEROP,
+];
+
+/// Error parsing the name of an unknown hardfork.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnknownSpecError {
+    /// The name that was parsed.
+    pub name: String,
+}
+
+impl fmt::Display for UnknownSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown hardfork {:?}", self.name)
+    }
+}
+
+impl core::error::Error for UnknownSpecError {}
+
 /// String identifiers for Optimism hardforks
 pub mod name {
     pub const BEDROCK: &str = "Bedrock";
@@ -101,6 +160,7 @@ pub mod name {
     pub const GRANITE: &str = "Granite";
     pub const HOLOCENE: &str = "Holocene";
     pub const ISTHMUS: &str = "Isthmus";
+    pub const INTEROP: &str = "Interop";
 }
 
 impl OpSpec {
@@ -146,10 +206,257 @@ impl From<OpSpec> for &'static str {
     }
 }
 
+impl FromStr for OpSpec {
+    type Err = UnknownSpecError;
+
+    /// Parses the name of an Optimism or an Ethereum hardfork, ignoring its case, e.g.
+    /// `"fjord"` or `"cancun"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Ok(op) = OpSpecId::from_str(name) {
+            return Ok(Self::Op(op));
+        }
+        (0..=u8::MAX)
+            .filter_map(SpecId::n)
+            .find(|spec| <&str>::from(*spec).eq_ignore_ascii_case(name))
+            .map(Self::Eth)
+            .ok_or_else(|| UnknownSpecError { name: name.into() })
+    }
+}
+
+impl fmt::Display for OpSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{OpSpec, OpSpecId};
+    use core::str::FromStr;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use std::string::String;
+
+    fn deserialize_name<'de, T: FromStr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T::Err: core::fmt::Display,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+
+    impl Serialize for OpSpecId {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str((*self).into())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OpSpecId {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserialize_name(deserializer)
+        }
+    }
+
+    impl Serialize for OpSpec {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str((*self).into())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OpSpec {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserialize_name(deserializer)
+        }
+    }
+}
+
+/// Chain id of OP mainnet.
+pub const OP_MAINNET_CHAIN_ID: u64 = 10;
+/// Chain id of Base mainnet.
+pub const BASE_CHAIN_ID: u64 = 8453;
+/// Chain id of Zora mainnet.
+pub const ZORA_CHAIN_ID: u64 = 7_777_777;
+/// Chain id of Mode mainnet.
+pub const MODE_CHAIN_ID: u64 = 34443;
+
+/// Hardforks of OP mainnet, which started with Bedrock and Regolith at the same block.
+///
+/// Blocks before Bedrock were executed by the legacy client.
+pub const OP_MAINNET: HardforkSchedule<OpSpecId> = HardforkSchedule::new(&[
+    (OpSpecId::REGOLITH, ForkCondition::Block(105_235_063)),
+    (OpSpecId::CANYON, ForkCondition::Timestamp(1_704_992_401)),
+    (OpSpecId::ECOTONE, ForkCondition::Timestamp(1_710_374_401)),
+    (OpSpecId::FJORD, ForkCondition::Timestamp(1_720_627_201)),
+    (OpSpecId::GRANITE, ForkCondition::Timestamp(1_726_070_401)),
+    (OpSpecId::HOLOCENE, ForkCondition::Timestamp(1_736_445_601)),
+    (OpSpecId::ISTHMUS, ForkCondition::Timestamp(1_746_806_401)),
+]);
+
+/// Hardforks of Base mainnet, which started with Regolith.
+pub const BASE: HardforkSchedule<OpSpecId> = HardforkSchedule::new(&[
+    (OpSpecId::REGOLITH, ForkCondition::Block(0)),
+    (OpSpecId::CANYON, ForkCondition::Timestamp(1_704_992_401)),
+    (OpSpecId::ECOTONE, ForkCondition::Timestamp(1_710_374_401)),
+    (OpSpecId::FJORD, ForkCondition::Timestamp(1_720_627_201)),
+    (OpSpecId::GRANITE, ForkCondition::Timestamp(1_726_070_401)),
+    (OpSpecId::HOLOCENE, ForkCondition::Timestamp(1_736_445_601)),
+    (OpSpecId::ISTHMUS, ForkCondition::Timestamp(1_746_806_401)),
+]);
+
+/// Hardforks of Zora mainnet, which started with Regolith and activated the later hardforks
+/// together with Base.
+pub const ZORA: HardforkSchedule<OpSpecId> = BASE;
+
+/// Hardforks of Mode mainnet, which started with Regolith and activated the later hardforks
+/// together with Base.
+pub const MODE: HardforkSchedule<OpSpecId> = BASE;
+
+/// Hardfork schedule of an OP stack chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpChainSpec {
+    /// Chain id of the chain.
+    pub chain_id: u64,
+    /// Hardforks of the chain.
+    pub hardforks: HardforkSchedule<OpSpecId>,
+}
+
+/// Chain spec of OP mainnet.
+pub const OP_MAINNET_CHAIN_SPEC: OpChainSpec = OpChainSpec::new(OP_MAINNET_CHAIN_ID, OP_MAINNET);
+
+/// Chain spec of Base mainnet.
+pub const BASE_CHAIN_SPEC: OpChainSpec = OpChainSpec::new(BASE_CHAIN_ID, BASE);
+
+/// Chain spec of Zora mainnet.
+pub const ZORA_CHAIN_SPEC: OpChainSpec = OpChainSpec::new(ZORA_CHAIN_ID, ZORA);
+
+/// Chain spec of Mode mainnet.
+pub const MODE_CHAIN_SPEC: OpChainSpec = OpChainSpec::new(MODE_CHAIN_ID, MODE);
+
+/// Chain specs of the well-known chains.
+pub const KNOWN_CHAIN_SPECS: [OpChainSpec; 4] = [
+    OP_MAINNET_CHAIN_SPEC,
+    BASE_CHAIN_SPEC,
+    ZORA_CHAIN_SPEC,
+    MODE_CHAIN_SPEC,
+];
+
+impl OpChainSpec {
+    /// Creates the chain spec of the chain with the given id and hardforks.
+    pub const fn new(chain_id: u64, hardforks: HardforkSchedule<OpSpecId>) -> Self {
+        Self {
+            chain_id,
+            hardforks,
+        }
+    }
+
+    /// Returns the chain spec of a well-known chain.
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        KNOWN_CHAIN_SPECS
+            .into_iter()
+            .find(|spec| spec.chain_id == chain_id)
+    }
+
+    /// Returns the spec of the block with the given timestamp.
+    ///
+    /// Hardforks activated by block number are the ones of the first block executed by the OP
+    /// EVM, they are always active.
+    pub fn spec_at(&self, timestamp: u64) -> OpSpecId {
+        self.hardforks.spec_at(u64::MAX, timestamp)
+    }
+
+    /// Returns the spec of the latest hardfork of the chain.
+    pub fn latest(&self) -> OpSpecId {
+        self.hardforks.latest()
+    }
+
+    /// Returns `true` if the block with the given number and timestamp is the first block of the
+    /// hardfork, the one applying its irregular state changes.
+    ///
+    /// A hardfork activated by timestamp is activated by the first block whose parent has an
+    /// earlier timestamp, one activated by number by the block with this number.
+    pub fn is_activation_block(
+        &self,
+        spec: OpSpecId,
+        number: u64,
+        timestamp: u64,
+        parent_timestamp: u64,
+    ) -> bool {
+        let Some((_, condition)) = self.hardforks.forks().iter().find(|(id, _)| *id == spec) else {
+            return false;
+        };
+        match *condition {
+            ForkCondition::Block(block) => number == block,
+            ForkCondition::Timestamp(time) => parent_timestamp < time && time <= timestamp,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn chain_spec_at() {
+        let spec = OpChainSpec::from_chain_id(OP_MAINNET_CHAIN_ID).unwrap();
+        assert_eq!(spec.spec_at(0), OpSpecId::REGOLITH);
+        assert_eq!(spec.spec_at(1_704_992_400), OpSpecId::REGOLITH);
+        assert_eq!(spec.spec_at(1_704_992_401), OpSpecId::CANYON);
+        assert_eq!(spec.spec_at(1_726_070_401), OpSpecId::GRANITE);
+        assert_eq!(spec.spec_at(u64::MAX), OpSpecId::ISTHMUS);
+        assert_eq!(
+            OpChainSpec::from_chain_id(BASE_CHAIN_ID)
+                .unwrap()
+                .spec_at(1_720_627_201),
+            OpSpecId::FJORD
+        );
+        assert_eq!(OpChainSpec::from_chain_id(1), None);
+    }
+
+    #[test]
+    fn spec_names() {
+        for spec in OP_SPECS {
+            assert_eq!(spec.to_string().parse(), Ok(spec));
+        }
+        assert_eq!("ecotone".parse(), Ok(OpSpecId::ECOTONE));
+        assert_eq!(OpSpecId::FJORD.to_string(), "Fjord");
+        assert_eq!(
+            "berlin".parse::<OpSpecId>(),
+            Err(UnknownSpecError {
+                name: "berlin".into()
+            })
+        );
+
+        assert_eq!(
+            "HOLOCENE".parse::<OpSpec>().map(OpSpec::into_eth_spec),
+            Ok(SpecId::CANCUN)
+        );
+        assert!(matches!(
+            "shanghai".parse(),
+            Ok(OpSpec::Eth(SpecId::SHANGHAI))
+        ));
+        assert_eq!(OpSpec::Eth(SpecId::LONDON).to_string(), "London");
+        assert!("unknown".parse::<OpSpec>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_spec_names() {
+        assert_eq!(
+            serde_json::to_string(&OpSpecId::GRANITE).unwrap(),
+            r#""Granite""#
+        );
+        assert_eq!(
+            serde_json::from_str::<OpSpecId>(r#""isthmus""#).unwrap(),
+            OpSpecId::ISTHMUS
+        );
+        assert!(matches!(
+            serde_json::from_str(r#""cancun""#),
+            Ok(OpSpec::Eth(SpecId::CANCUN))
+        ));
+        assert!(serde_json::from_str::<OpSpec>(r#""unknown""#).is_err());
+    }
+
     #[test]
     fn test_bedrock_post_merge_hardforks() {
         assert!(OpSpec::Op(OpSpecId::BEDROCK).is_enabled_in(SpecId::MERGE));
@@ -1,11 +1,15 @@
 pub mod abstraction;
 pub mod deposit;
+#[cfg(feature = "alloy")]
+pub mod envelope;
 pub mod error;
+pub mod interop;
 
 pub use abstraction::{OpTransaction, OpTxTrait};
 pub use error::OpTransactionError;
 
-use crate::fast_lz::flz_compress_len;
+use crate::fast_lz::{flz_compress_len, flz_compress_len_with, FlzCompressLen};
+use std::{boxed::Box, vec::Vec};
 
 /// <https://github.com/ethereum-optimism/op-geth/blob/647c346e2bef36219cc7b47d76b1cb87e7ca29e4/core/types/rollup_cost.go#L79>
 const L1_COST_FASTLZ_COEF: u64 = 836_500;
@@ -19,10 +23,112 @@ const MIN_TX_SIZE_SCALED: u64 = 100 * 1_000_000;
 
 /// Estimates the compressed size of a transaction.
 pub fn estimate_tx_compressed_size(input: &[u8]) -> u64 {
-    let fastlz_size = flz_compress_len(input) as u64;
+    estimate_from_flz_len(flz_compress_len(input))
+}
+
+/// Estimates the compressed size of a transaction assembled from the given parts, without
+/// concatenating them.
+pub fn estimate_tx_compressed_size_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    let mut compressor = FlzCompressLen::new();
+    for chunk in chunks {
+        compressor.update(chunk);
+    }
+    estimate_from_flz_len(compressor.finish())
+}
+
+/// Estimates the compressed sizes of many transactions, e.g. the candidates of a block, in order.
+///
+/// Same as [`estimate_tx_compressed_size`] on each input, with the FastLZ hash table allocated
+/// once instead of per transaction. With the `rayon` feature, the inputs are split across the
+/// current rayon pool, with one hash table per worker.
+pub fn estimate_tx_compressed_size_many(inputs: &[&[u8]]) -> Vec<u64> {
+    let new_htab = || Box::new([0; 8192]);
+    let estimate = |htab: &mut Box<[u32; 8192]>, input: &&[u8]| {
+        estimate_from_flz_len(flz_compress_len_with(input, htab))
+    };
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        inputs.par_iter().map_init(new_htab, estimate).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut htab = new_htab();
+        inputs
+            .iter()
+            .map(|input| estimate(&mut htab, input))
+            .collect()
+    }
+}
 
-    fastlz_size
+fn estimate_from_flz_len(fastlz_size: u32) -> u64 {
+    (fastlz_size as u64)
         .saturating_mul(L1_COST_FASTLZ_COEF)
         .saturating_sub(L1_COST_INTERCEPT)
         .max(MIN_TX_SIZE_SCALED)
 }
+
+/// Estimates the compressed size of a transaction with Brotli, scaled by 1e6 like
+/// [`estimate_tx_compressed_size`].
+///
+/// Batchers compress channels of many transactions, the size of a transaction compressed alone
+/// is an upper bound of its share.
+#[cfg(feature = "brotli")]
+pub fn estimate_tx_compressed_size_brotli(input: &[u8]) -> u64 {
+    let params = brotli::enc::BrotliEncoderParams {
+        quality: 10,
+        lgwin: 22,
+        ..Default::default()
+    };
+    let mut compressed = Vec::new();
+    // Writing to a vector does not fail.
+    let _ = brotli::enc::BrotliCompress(&mut &input[..], &mut compressed, &params);
+
+    (compressed.len() as u64)
+        .saturating_mul(1_000_000)
+        .max(MIN_TX_SIZE_SCALED)
+}
+
+/// Length of the signature of an RLP encoded transaction: `v`, `r` and `s` with their prefixes.
+pub const SIGNATURE_LENGTH: usize = 68;
+
+/// Placeholder of the signature of unsigned transactions, distinct non-zero bytes so it neither
+/// compresses nor gets the zero byte discount.
+const SIGNATURE_PLACEHOLDER: [u8; SIGNATURE_LENGTH] = {
+    let mut placeholder = [0; SIGNATURE_LENGTH];
+    let mut i = 0;
+    while i < SIGNATURE_LENGTH {
+        placeholder[i] = (i as u8).wrapping_mul(37).wrapping_add(1);
+        i += 1;
+    }
+    placeholder
+};
+
+/// Appends a worst case signature to an unsigned transaction, so the L1 data fee estimated for it
+/// is an upper bound of the one of the signed transaction.
+pub fn pad_unsigned_tx(unsigned_tx: &[u8]) -> Vec<u8> {
+    [unsigned_tx, &SIGNATURE_PLACEHOLDER].concat()
+}
+
+/// Estimates the compressed size of a transaction before it is signed, see [`pad_unsigned_tx`].
+pub fn estimate_unsigned_tx_compressed_size(unsigned_tx: &[u8]) -> u64 {
+    estimate_tx_compressed_size(&pad_unsigned_tx(unsigned_tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_many() {
+        let long: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let inputs: [&[u8]; 5] = [&long, &[], &[42; 1000], &long[..300], &[1, 2, 3]];
+        let expected: Vec<u64> = inputs
+            .iter()
+            .map(|input| estimate_tx_compressed_size(input))
+            .collect();
+        assert_eq!(estimate_tx_compressed_size_many(&inputs), expected);
+        assert!(estimate_tx_compressed_size_many(&[]).is_empty());
+    }
+}
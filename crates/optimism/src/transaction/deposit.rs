@@ -1,4 +1,4 @@
-use revm::primitives::B256;
+use revm::primitives::{keccak256, B256};
 
 pub const DEPOSIT_TRANSACTION_TYPE: u8 = 0x7E;
 
@@ -27,3 +27,98 @@ impl DepositTransactionParts {
         }
     }
 }
+
+/// Domain of the source hash of [`DepositSource::User`] deposits.
+const USER_DEPOSIT_DOMAIN: u64 = 0;
+/// Domain of the source hash of [`DepositSource::L1Info`] deposits.
+const L1_INFO_DEPOSIT_DOMAIN: u64 = 1;
+/// Domain of the source hash of [`DepositSource::Upgrade`] deposits.
+const UPGRADE_DEPOSIT_DOMAIN: u64 = 2;
+
+/// Origin of a deposit, from which its unique source hash is derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DepositSource<'a> {
+    /// Deposit of a `TransactionDeposited` log emitted by the portal on L1.
+    User { l1_block_hash: B256, log_index: u64 },
+    /// L1 attributes deposit at the start of an L2 block, with the number of the L2 block in its
+    /// sequencing epoch.
+    L1Info {
+        l1_block_hash: B256,
+        sequence_number: u64,
+    },
+    /// Deposit of a network upgrade, identified by its intent, e.g.
+    /// `"Ecotone: L1 Block Deployment"`.
+    Upgrade { intent: &'a str },
+}
+
+impl DepositSource<'_> {
+    /// Returns the source hash of the deposit:
+    /// `keccak256(bytes32(domain) ++ keccak256(...))` of the inputs of its kind.
+    pub fn source_hash(&self) -> B256 {
+        let (domain, inner) = match *self {
+            Self::User {
+                l1_block_hash,
+                log_index,
+            } => (USER_DEPOSIT_DOMAIN, indexed_hash(l1_block_hash, log_index)),
+            Self::L1Info {
+                l1_block_hash,
+                sequence_number,
+            } => (
+                L1_INFO_DEPOSIT_DOMAIN,
+                indexed_hash(l1_block_hash, sequence_number),
+            ),
+            Self::Upgrade { intent } => (UPGRADE_DEPOSIT_DOMAIN, keccak256(intent)),
+        };
+        let mut input = [0; 64];
+        input[24..32].copy_from_slice(&domain.to_be_bytes());
+        input[32..].copy_from_slice(inner.as_slice());
+        keccak256(input)
+    }
+}
+
+/// Returns `keccak256(block_hash ++ bytes32(index))`.
+fn indexed_hash(block_hash: B256, index: u64) -> B256 {
+    let mut input = [0; 64];
+    input[..32].copy_from_slice(block_hash.as_slice());
+    input[56..].copy_from_slice(&index.to_be_bytes());
+    keccak256(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{b256, U256};
+
+    #[test]
+    fn source_hashes() {
+        let l1_block_hash =
+            b256!("c3b4fb6ee8a5d3e6b6a1d8e9d6b2a5a4f3c2b1a09f8e7d6c5b4a3928171605f4");
+        let user = DepositSource::User {
+            l1_block_hash,
+            log_index: 3,
+        };
+        let inner = keccak256([l1_block_hash.0, U256::from(3).to_be_bytes()].concat());
+        assert_eq!(
+            user.source_hash(),
+            keccak256([B256::ZERO.0, inner.0].concat())
+        );
+
+        // The domain separates the kinds with the same inputs.
+        let l1_info = DepositSource::L1Info {
+            l1_block_hash,
+            sequence_number: 3,
+        };
+        assert_eq!(
+            l1_info.source_hash(),
+            keccak256([U256::from(1).to_be_bytes(), inner.0].concat())
+        );
+
+        assert_eq!(
+            DepositSource::Upgrade {
+                intent: "Ecotone: L1 Block Deployment"
+            }
+            .source_hash(),
+            b256!("877a6077205782ea15a6dc8699fa5ebcec5e0f4389f09cb8eda09488231346f8")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Decoding and encoding of raw [EIP-2718] transaction envelopes of [`OpTransaction`]s.
+//!
+//! Deposits are decoded from their `0x7E` envelope, other transactions with alloy, and their
+//! signer is recovered. The raw bytes are kept as the enveloped transaction the L1 data fee is
+//! computed from.
+//!
+//! Deposits are not signed and are encoded from their fields, other transactions are encoded as
+//! their kept envelope.
+//!
+//! [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+use super::{
+    deposit::{DepositTransactionParts, DEPOSIT_TRANSACTION_TYPE},
+    OpTransaction,
+};
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::{Decodable2718, Eip2718Error};
+use alloy_rlp::{Decodable, Encodable, Header};
+use core::fmt;
+use revm::{
+    context::{tx::TxEnvConversionError, TxEnv},
+    primitives::{Address, Bytes, TxKind, B256, U256},
+};
+use std::vec::Vec;
+
+/// Error decoding a raw transaction envelope.
+#[derive(Debug)]
+pub enum OpTxEnvelopeError {
+    /// The bytes are not a valid transaction envelope.
+    Decode(Eip2718Error),
+    /// The signer of the transaction could not be recovered.
+    Recover(TxEnvConversionError),
+}
+
+impl fmt::Display for OpTxEnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(error) => write!(f, "invalid transaction envelope: {error}"),
+            Self::Recover(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for OpTxEnvelopeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Decode(_) => None,
+            Self::Recover(error) => error.source(),
+        }
+    }
+}
+
+impl From<Eip2718Error> for OpTxEnvelopeError {
+    fn from(error: Eip2718Error) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl From<alloy_rlp::Error> for OpTxEnvelopeError {
+    fn from(error: alloy_rlp::Error) -> Self {
+        Self::Decode(error.into())
+    }
+}
+
+impl OpTransaction<TxEnv> {
+    /// Decodes a raw EIP-2718 transaction envelope and recovers its signer.
+    ///
+    /// The envelope must be the whole input, and is kept as the
+    /// [`enveloped_tx`][OpTransaction::enveloped_tx].
+    pub fn decode_2718(raw: Bytes) -> Result<Self, OpTxEnvelopeError> {
+        let buf = &mut raw.as_ref();
+        let mut tx = if buf.first() == Some(&DEPOSIT_TRANSACTION_TYPE) {
+            *buf = &buf[1..];
+            decode_deposit(buf)?
+        } else {
+            let envelope = TxEnvelope::decode_2718(buf)?;
+            Self::new(TxEnv::try_from(&envelope).map_err(OpTxEnvelopeError::Recover)?)
+        };
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength.into());
+        }
+        tx.enveloped_tx = Some(raw);
+        Ok(tx)
+    }
+
+    /// Encodes the transaction as an EIP-2718 envelope.
+    ///
+    /// Deposits are encoded from their fields. Other transactions are not signed, their
+    /// [`enveloped_tx`][OpTransaction::enveloped_tx] is returned if set.
+    pub fn encode_2718(&self) -> Option<Bytes> {
+        if self.base.tx_type != DEPOSIT_TRANSACTION_TYPE {
+            return self.enveloped_tx.clone();
+        }
+        let mut payload = Vec::new();
+        self.deposit.source_hash.encode(&mut payload);
+        self.base.caller.encode(&mut payload);
+        self.base.kind.encode(&mut payload);
+        self.deposit.mint.unwrap_or_default().encode(&mut payload);
+        self.base.value.encode(&mut payload);
+        self.base.gas_limit.encode(&mut payload);
+        self.deposit.is_system_transaction.encode(&mut payload);
+        self.base.data.encode(&mut payload);
+
+        let mut raw = vec![DEPOSIT_TRANSACTION_TYPE];
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut raw);
+        raw.extend(payload);
+        Some(raw.into())
+    }
+}
+
+/// Decodes the RLP list of the fields of a deposit, following its type byte.
+///
+/// A zero mint is decoded as no mint, deposits have no nonce, signature nor gas price.
+fn decode_deposit(buf: &mut &[u8]) -> alloy_rlp::Result<OpTransaction<TxEnv>> {
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString);
+    }
+    let remaining = buf.len();
+    let source_hash = B256::decode(buf)?;
+    let caller = Address::decode(buf)?;
+    let kind = TxKind::decode(buf)?;
+    let mint = u128::decode(buf)?;
+    let value = U256::decode(buf)?;
+    let gas_limit = u64::decode(buf)?;
+    let is_system_transaction = bool::decode(buf)?;
+    let data = Bytes::decode(buf)?;
+    if remaining - buf.len() != header.payload_length {
+        return Err(alloy_rlp::Error::ListLengthMismatch {
+            expected: header.payload_length,
+            got: remaining - buf.len(),
+        });
+    }
+
+    let mut tx = OpTransaction::new(TxEnv {
+        tx_type: DEPOSIT_TRANSACTION_TYPE,
+        caller,
+        gas_limit,
+        gas_price: 0,
+        kind,
+        value,
+        data,
+        chain_id: None,
+        gas_priority_fee: None,
+        ..Default::default()
+    });
+    tx.deposit = DepositTransactionParts::new(
+        source_hash,
+        (mint != 0).then_some(mint),
+        is_system_transaction,
+    );
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::exec_op::transact_op_raw,
+        transaction::{deposit::DepositTransaction, OpTxTrait},
+        L1BlockInfo, OpSpec, OpSpecId,
+    };
+    use alloy_consensus::{SignableTransaction, TxEip1559};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_rlp::Encodable;
+    use revm::Context;
+    use revm::{
+        context::CfgEnv,
+        context_interface::Transaction,
+        primitives::{address, alloy_primitives::PrimitiveSignature, b256},
+    };
+    use std::vec::Vec;
+
+    #[test]
+    fn decode_deposit_envelope() {
+        let source_hash = b256!("0101010101010101010101010101010101010101010101010101010101010101");
+        let from = address!("00000000000000000000000000000000000000aa");
+        let to = address!("00000000000000000000000000000000000000bb");
+        let mut payload = Vec::new();
+        source_hash.encode(&mut payload);
+        from.encode(&mut payload);
+        TxKind::Call(to).encode(&mut payload);
+        10u128.encode(&mut payload);
+        U256::from(7).encode(&mut payload);
+        100_000u64.encode(&mut payload);
+        false.encode(&mut payload);
+        Bytes::from_static(&[1, 2, 3]).encode(&mut payload);
+        let mut raw = vec![DEPOSIT_TRANSACTION_TYPE];
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut raw);
+        raw.extend(payload);
+
+        let tx = OpTransaction::decode_2718(raw.clone().into()).unwrap();
+        assert_eq!(tx.tx_type(), DEPOSIT_TRANSACTION_TYPE);
+        assert_eq!(tx.caller(), from);
+        assert_eq!(tx.kind(), TxKind::Call(to));
+        assert_eq!(tx.value(), U256::from(7));
+        assert_eq!(tx.gas_limit(), 100_000);
+        assert_eq!(tx.input().as_ref(), [1, 2, 3]);
+        assert_eq!(tx.source_hash(), source_hash);
+        assert_eq!(tx.mint(), Some(10));
+        assert!(!tx.is_system_transaction());
+        assert_eq!(tx.enveloped_tx(), Some(&Bytes::from(raw.clone())));
+
+        // The deposit is encoded from its fields, without its kept envelope.
+        let mut decoded = tx.clone();
+        decoded.enveloped_tx = None;
+        assert_eq!(decoded.encode_2718(), Some(Bytes::from(raw.clone())));
+
+        // The deposit mints 10 and transfers 7 of them.
+        let mut ctx = Context::default()
+            .with_tx(OpTransaction::default())
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::REGOLITH)))
+            .with_chain(L1BlockInfo::default());
+        let result = transact_op_raw(&mut ctx, raw.clone().into()).unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(result.state[&from].info.balance, U256::from(3));
+        assert_eq!(result.state[&to].info.balance, U256::from(7));
+
+        // Trailing bytes are not part of the envelope.
+        raw.push(0);
+        assert!(OpTransaction::decode_2718(raw.into()).is_err());
+    }
+
+    #[test]
+    fn decode_signed_envelope() {
+        let tx = TxEip1559 {
+            chain_id: 10,
+            nonce: 1,
+            gas_limit: 21_000,
+            max_fee_per_gas: 2,
+            max_priority_fee_per_gas: 1,
+            to: TxKind::Call(address!("00000000000000000000000000000000000000bb")),
+            ..Default::default()
+        };
+        let signature = PrimitiveSignature::new(U256::from(1), U256::from(2), false);
+        let envelope = TxEnvelope::from(tx.into_signed(signature));
+        let caller = envelope.recover_signer().unwrap();
+        let raw = Bytes::from(envelope.encoded_2718());
+
+        let tx = OpTransaction::decode_2718(raw.clone()).unwrap();
+        assert_eq!(tx.caller(), caller);
+        assert_eq!(tx.nonce(), 1);
+        assert_eq!(tx.chain_id(), Some(10));
+        assert_eq!(tx.enveloped_tx(), Some(&raw));
+        assert_eq!(tx.encode_2718(), Some(raw));
+
+        assert!(matches!(
+            OpTransaction::decode_2718(Bytes::from_static(&[0x05, 0xc0])),
+            Err(OpTxEnvelopeError::Decode(_))
+        ));
+    }
+}
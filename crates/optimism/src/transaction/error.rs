@@ -1,3 +1,4 @@
+use crate::result::DepositHaltReason;
 use core::fmt::Display;
 use revm::context_interface::{
     result::{EVMError, InvalidTransaction},
@@ -27,18 +28,26 @@ pub enum OpTransactionError {
     /// Deposit transaction haults bubble up to the global main return handler, wiping state and
     /// only increasing the nonce + persisting the mint value.
     ///
-    /// This is a catch-all error for any deposit transaction that is results in a [OptimismHaltReason][crate::OptimismHaltReason] error
-    /// post-regolith hardfork. This allows for a consumer to easily handle special cases where
-    /// a deposit transaction fails during validation, but must still be included in the block.
+    /// This error is raised for any deposit transaction that halts post-regolith hardfork, or
+    /// whose mint overflows the balance of its caller. This allows for a consumer to easily handle
+    /// special cases where a deposit transaction fails, but must still be included in the block.
     ///
-    /// In addition, this error is internal, and bubbles up into a [OptimismHaltReason::FailedDeposit][crate::OptimismHaltReason::FailedDeposit] error
+    /// In addition, this error is internal, and bubbles up into a [OptimismHaltReason::Deposit][crate::OptimismHaltReason::Deposit] error
     /// in the `revm` handler for the consumer to easily handle. This is due to a state transition
     /// rule on OP Stack chains where, if for any reason a deposit transaction fails, the transaction
     /// must still be included in the block, the sender nonce is bumped, the `mint` value persists, and
     /// special gas accounting rules are applied. Normally on L1, [EVMError::Transaction] errors
     /// are cause for non-inclusion, so a special [OptimismHaltReason][crate::OptimismHaltReason] variant was introduced to handle this
     /// case for failed deposit transactions.
-    HaltedDepositPostRegolith,
+    FailedDeposit(DepositHaltReason),
+    /// The `CrossL2Inbox` access list entries of a transaction are not a sequence of executing
+    /// messages, see [`ExecutingMessage`][crate::transaction::interop::ExecutingMessage].
+    MalformedExecutingMessage,
+    /// An executing message was initiated in a block later than the executing block.
+    ExecutingMessageInFuture,
+    /// An executing message was initiated more than the
+    /// [expiry window][crate::transaction::interop::MESSAGE_EXPIRY_WINDOW] before the executing block.
+    ExpiredExecutingMessage,
 }
 
 impl TransactionError for OpTransactionError {}
@@ -53,17 +62,38 @@ impl Display for OpTransactionError {
                     "deposit system transactions post regolith hardfork are not supported"
                 )
             }
-            Self::HaltedDepositPostRegolith => {
+            Self::FailedDeposit(reason) => {
                 write!(
                     f,
-                    "deposit transaction halted post-regolith; error will be bubbled up to main return handler"
+                    "deposit transaction failed ({reason:?}); error will be bubbled up to main return handler"
                 )
             }
+            Self::MalformedExecutingMessage => {
+                write!(f, "malformed executing message access list")
+            }
+            Self::ExecutingMessageInFuture => {
+                write!(f, "executing message initiated after the executing block")
+            }
+            Self::ExpiredExecutingMessage => {
+                write!(f, "executing message expired")
+            }
         }
     }
 }
 
-impl core::error::Error for OpTransactionError {}
+impl core::error::Error for OpTransactionError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        // Displayed as the wrapped error, so its source is the next one.
+        match self {
+            Self::Base(error) => error.source(),
+            Self::DepositSystemTxPostRegolith
+            | Self::FailedDeposit(_)
+            | Self::MalformedExecutingMessage
+            | Self::ExecutingMessageInFuture
+            | Self::ExpiredExecutingMessage => None,
+        }
+    }
+}
 
 impl From<InvalidTransaction> for OpTransactionError {
     fn from(value: InvalidTransaction) -> Self {
@@ -76,3 +106,21 @@ impl<DBError> From<OpTransactionError> for EVMError<DBError, OpTransactionError>
         Self::Transaction(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{convert::Infallible, error::Error};
+    use std::{string::ToString, vec::Vec};
+
+    #[test]
+    fn error_source_chain() {
+        let invalid = InvalidTransaction::NonceOverflowInTransaction;
+        let error =
+            EVMError::<Infallible, _>::Transaction(OpTransactionError::Base(invalid.clone()));
+        let chain: Vec<_> = core::iter::successors(Some(&error as &dyn Error), |e| (*e).source())
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(chain, [error.to_string(), invalid.to_string()]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Executing messages of the Interop hardfork.
+//!
+//! A transaction executing a message of another chain calls the `CrossL2Inbox` predeploy, and
+//! declares the message in its access list: the storage keys of the `CrossL2Inbox` encode the
+//! identifier of the initiating message and a checksum of it. The `CrossL2Inbox` only accepts
+//! messages whose checksum slot is warm, the node verifies the checksums against the initiating
+//! chains.
+use super::OpTransactionError;
+use revm::primitives::{address, Address, B256, U256};
+use std::vec::Vec;
+
+/// The address of the CrossL2Inbox contract.
+pub const CROSS_L2_INBOX_ADDRESS: Address = address!("4200000000000000000000000000000000000022");
+
+/// Time in seconds after which an initiating message can no longer be executed.
+pub const MESSAGE_EXPIRY_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+/// Type of the access list entry with the chain id, block number, timestamp and log index of the
+/// initiating message.
+pub const LOOKUP_ENTRY_TYPE: u8 = 1;
+/// Type of the access list entry with the upper bytes of a chain id not fitting in 64 bits.
+pub const CHAIN_ID_EXTENSION_ENTRY_TYPE: u8 = 2;
+/// Type of the access list entry with the checksum of the initiating message.
+pub const CHECKSUM_ENTRY_TYPE: u8 = 3;
+
+/// A message of another chain executed by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutingMessage {
+    /// Chain id of the initiating message.
+    pub chain_id: U256,
+    /// Number of the block of the initiating message.
+    pub block_number: u64,
+    /// Timestamp of the block of the initiating message.
+    pub timestamp: u64,
+    /// Index of the log of the initiating message in its block.
+    pub log_index: u32,
+    /// Checksum of the initiating message, the storage key the `CrossL2Inbox` checks is warm.
+    pub checksum: B256,
+}
+
+impl ExecutingMessage {
+    /// Decodes the executing messages from the storage keys of the `CrossL2Inbox` access list
+    /// entry.
+    ///
+    /// Each message is a lookup entry, optionally followed by a chain id extension entry, and
+    /// followed by the checksum entry. Reserved bytes of the entries must be zero.
+    pub fn decode_access_list(storage_keys: &[B256]) -> Result<Vec<Self>, OpTransactionError> {
+        let mut messages = Vec::new();
+        let mut keys = storage_keys.iter();
+        while let Some(lookup) = keys.next() {
+            if lookup[0] != LOOKUP_ENTRY_TYPE || lookup[1..4] != [0; 3] {
+                return Err(OpTransactionError::MalformedExecutingMessage);
+            }
+            let mut chain_id = U256::from_be_slice(&lookup[4..12]);
+            let mut next = keys.next();
+            if let Some(extension) = next.filter(|key| key[0] == CHAIN_ID_EXTENSION_ENTRY_TYPE) {
+                if extension[1..8] != [0; 7] {
+                    return Err(OpTransactionError::MalformedExecutingMessage);
+                }
+                chain_id |= U256::from_be_slice(&extension[8..]) << 64;
+                next = keys.next();
+            }
+            let checksum = next
+                .filter(|key| key[0] == CHECKSUM_ENTRY_TYPE)
+                .ok_or(OpTransactionError::MalformedExecutingMessage)?;
+
+            messages.push(Self {
+                chain_id,
+                block_number: u64::from_be_bytes(lookup[12..20].try_into().unwrap()),
+                timestamp: u64::from_be_bytes(lookup[20..28].try_into().unwrap()),
+                log_index: u32::from_be_bytes(lookup[28..32].try_into().unwrap()),
+                checksum: *checksum,
+            });
+        }
+        Ok(messages)
+    }
+
+    /// Checks that the initiating message can be executed in a block with the given timestamp:
+    /// it is not from a later block, and it is not expired.
+    pub fn validate_timestamp(&self, block_timestamp: u64) -> Result<(), OpTransactionError> {
+        if self.timestamp > block_timestamp {
+            return Err(OpTransactionError::ExecutingMessageInFuture);
+        }
+        if self.timestamp.saturating_add(MESSAGE_EXPIRY_WINDOW) < block_timestamp {
+            return Err(OpTransactionError::ExpiredExecutingMessage);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the messages executed by a transaction with the given access list.
+pub fn executing_messages<'a>(
+    access_list: impl IntoIterator<Item = (&'a Address, &'a [B256])>,
+) -> Result<Vec<ExecutingMessage>, OpTransactionError> {
+    let mut messages = Vec::new();
+    for (address, storage_keys) in access_list {
+        if *address == CROSS_L2_INBOX_ADDRESS {
+            messages.extend(ExecutingMessage::decode_access_list(storage_keys)?);
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::b256;
+
+    fn lookup(chain_id: u64, block_number: u64, timestamp: u64, log_index: u32) -> B256 {
+        let mut key = B256::ZERO;
+        key[0] = LOOKUP_ENTRY_TYPE;
+        key[4..12].copy_from_slice(&chain_id.to_be_bytes());
+        key[12..20].copy_from_slice(&block_number.to_be_bytes());
+        key[20..28].copy_from_slice(&timestamp.to_be_bytes());
+        key[28..32].copy_from_slice(&log_index.to_be_bytes());
+        key
+    }
+
+    #[test]
+    fn decode_access_list() {
+        let checksum = b256!("03aabbccddeeff00112233445566778899aabbccddeeff001122334455667788");
+        let message = ExecutingMessage {
+            chain_id: U256::from(10),
+            block_number: 100,
+            timestamp: 1000,
+            log_index: 3,
+            checksum,
+        };
+        assert_eq!(
+            ExecutingMessage::decode_access_list(&[lookup(10, 100, 1000, 3), checksum]),
+            Ok(vec![message.clone()])
+        );
+
+        // The chain id extension holds the bytes above the lower 64 bits.
+        let mut extension = B256::ZERO;
+        extension[0] = CHAIN_ID_EXTENSION_ENTRY_TYPE;
+        extension[31] = 1;
+        let access_list = [(
+            &CROSS_L2_INBOX_ADDRESS,
+            &[lookup(10, 100, 1000, 3), extension, checksum][..],
+        )];
+        assert_eq!(
+            executing_messages(access_list),
+            Ok(vec![ExecutingMessage {
+                chain_id: (U256::from(1) << 64) | U256::from(10),
+                ..message.clone()
+            }])
+        );
+
+        // Entries of other contracts are not messages.
+        assert_eq!(
+            executing_messages([(&Address::ZERO, &[checksum][..])]),
+            Ok(vec![])
+        );
+
+        for malformed in [
+            &[checksum][..],
+            &[lookup(10, 100, 1000, 3)],
+            &[lookup(10, 100, 1000, 3), extension],
+            &[lookup(10, 100, 1000, 3), checksum, checksum],
+        ] {
+            assert_eq!(
+                ExecutingMessage::decode_access_list(malformed),
+                Err(OpTransactionError::MalformedExecutingMessage)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_timestamp() {
+        let message = ExecutingMessage {
+            chain_id: U256::from(10),
+            block_number: 100,
+            timestamp: 1000,
+            log_index: 0,
+            checksum: B256::ZERO,
+        };
+        assert_eq!(message.validate_timestamp(1000), Ok(()));
+        assert_eq!(
+            message.validate_timestamp(1000 + MESSAGE_EXPIRY_WINDOW),
+            Ok(())
+        );
+        assert_eq!(
+            message.validate_timestamp(999),
+            Err(OpTransactionError::ExecutingMessageInFuture)
+        );
+        assert_eq!(
+            message.validate_timestamp(1001 + MESSAGE_EXPIRY_WINDOW),
+            Err(OpTransactionError::ExpiredExecutingMessage)
+        );
+    }
+
+    #[test]
+    fn handler_rejects_expired_message() {
+        use crate::{api::exec_op::transact_op, L1BlockInfo, OpSpec, OpSpecId, OpTransaction};
+        use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+        use revm::{
+            bytecode::Bytecode,
+            context::{CfgEnv, TxEnv},
+            context_interface::result::EVMError,
+            primitives::TxKind,
+            Context,
+        };
+
+        let checksum = b256!("03aabbccddeeff00112233445566778899aabbccddeeff001122334455667788");
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .with_tx(OpTransaction::new(TxEnv {
+                caller: BENCH_CALLER,
+                kind: TxKind::Call(BENCH_TARGET),
+                gas_price: 0,
+                gas_priority_fee: None,
+                access_list: vec![(
+                    CROSS_L2_INBOX_ADDRESS,
+                    vec![lookup(10, 100, 1000, 3), checksum],
+                )],
+                ..Default::default()
+            }))
+            .with_cfg(CfgEnv::default().with_spec(OpSpec::Op(OpSpecId::INTEROP)))
+            .with_chain(L1BlockInfo::default());
+        ctx.tx.enveloped_tx = Some([0xFA; 10].into());
+
+        ctx.block.timestamp = 2000;
+        assert!(transact_op(&mut ctx).is_ok());
+
+        ctx.block.timestamp = 1001 + MESSAGE_EXPIRY_WINDOW;
+        assert!(matches!(
+            transact_op(&mut ctx),
+            Err(EVMError::Transaction(
+                OpTransactionError::ExpiredExecutingMessage
+            ))
+        ));
+    }
+}
@@ -39,7 +39,15 @@ impl<DB, TXERROR> From<PrecompileErrors> for EVMError<DB, TXERROR> {
     }
 }
 
-impl core::error::Error for PrecompileErrors {}
+impl core::error::Error for PrecompileErrors {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        // Displayed as the wrapped error, so its source is the next one.
+        match self {
+            Self::Error(e) => e.source(),
+            Self::Fatal { .. } => None,
+        }
+    }
+}
 
 impl fmt::Display for PrecompileErrors {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -23,6 +23,9 @@ pub mod secp256k1;
 pub mod secp256r1;
 pub mod utilities;
 
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+// only depended on to enable its `js` feature.
+use getrandom as _;
 pub use interface::*;
 #[cfg(all(feature = "c-kzg", feature = "kzg-rs"))]
 // silence kzg-rs lint as c-kzg will be used as default if both are enabled.
@@ -71,12 +71,15 @@ pub fn transact_main<CTX: EthContext>(
 mod test {
     use super::*;
     use bytecode::{
-        opcode::{PUSH1, SSTORE},
+        opcode::{INVALID, PUSH0, PUSH1, REVERT, SSTORE},
         Bytecode,
     };
-    use context_interface::TransactionType;
-    use database::{BenchmarkDB, EEADDRESS, FFADDRESS};
-    use primitives::{address, TxKind, U256};
+    use context_interface::{
+        result::{ExecutionResult, HaltDebug, HaltReason},
+        TransactionType,
+    };
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET, EEADDRESS, FFADDRESS};
+    use primitives::{address, hex, Bytes, TxKind, U256};
     use specification::hardfork::SpecId;
 
     #[test]
@@ -106,4 +109,90 @@ mod test {
             U256::from(1)
         );
     }
+
+    #[test]
+    fn unvalidated_eof_code_halts() {
+        // One code section with `CALLF 5` and one with `RETF`, neither is valid.
+        for code in [
+            &hex!("ef000101000402000100040400000000800000e3000500")[..],
+            &hex!("ef000101000402000100010400000000800000e4")[..],
+        ] {
+            let mut ctx = Context::default()
+                .modify_cfg_chained(|cfg| cfg.spec = SpecId::OSAKA)
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                    Bytes::copy_from_slice(code),
+                )))
+                .modify_tx_chained(|tx| {
+                    tx.caller = BENCH_CALLER;
+                    tx.kind = TxKind::Call(BENCH_TARGET);
+                    tx.gas_limit = 100_000;
+                });
+
+            let result = ctx.exec_previous().unwrap().result;
+            assert!(matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::InvalidEOFCode,
+                    gas_used: 100_000,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn result_effective_gas_price() {
+        let mut ctx = Context::default()
+            .modify_block_chained(|block| block.basefee = 7)
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                [PUSH0, PUSH0, REVERT].into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.tx_type = TransactionType::Eip1559.into();
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+                tx.gas_price = 10;
+                tx.gas_priority_fee = Some(2);
+            });
+
+        let result = ctx.exec_previous().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Revert {
+                gas_refunded: 0,
+                effective_gas_price: 9,
+                ..
+            }
+        ));
+        assert_eq!(result.fee(), result.gas_used() as u128 * 9);
+    }
+
+    #[test]
+    fn halt_debug() {
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+                [PUSH1, 0x01, INVALID].into(),
+            )))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.gas_limit = 100_000;
+            });
+
+        let ExecutionResult::Halt { reason, debug, .. } = ctx.exec_previous().unwrap().result
+        else {
+            panic!("transaction did not halt");
+        };
+        assert_eq!(reason, HaltReason::InvalidFEOpcode);
+        assert_eq!(
+            debug.as_deref(),
+            Some(&HaltDebug {
+                pc: 2,
+                opcode: INVALID,
+                depth: 0,
+                address: BENCH_TARGET,
+            })
+        );
+    }
 }
@@ -22,6 +22,11 @@ pub use state;
 
 mod exec;
 mod exec_eth;
+#[cfg(feature = "std")]
+pub mod parallel;
+pub mod simulation;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 
 // Export items.
 
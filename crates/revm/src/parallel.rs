@@ -0,0 +1,681 @@
+//! Parallel execution of the transactions of a block, following Block-STM.
+//!
+//! Transactions are executed optimistically on worker threads against a multi-version state that
+//! holds the writes of every transaction, each reading the latest write of the transactions
+//! before it. Transactions are committed in order: the values a transaction read are read again
+//! once all transactions before it are committed, and it is re-executed if one of them changed.
+//! A committed transaction therefore read what it would have read sequentially, and the results
+//! are identical to executing the block sequentially on a database committing each state.
+//!
+//! Every transaction pays the beneficiary, so it is not rewarded during execution. The rewards are
+//! added when a transaction commits, and transactions reading the beneficiary see the rewards of
+//! the transactions before them.
+use crate::{Context, DatabaseRef};
+use context::Cfg;
+use context_interface::{
+    result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction, ResultAndState},
+    Block, Database, Transaction,
+};
+use core::{error::Error, fmt, marker::PhantomData};
+use handler::{
+    instructions::EthInstructionExecutor, EthContext, EthError, EthFrame, EthHandler,
+    EthPrecompileProvider, FrameContext, FrameResult,
+};
+use interpreter::interpreter::EthInterpreter;
+use primitives::{hash_map::Entry, Address, HashMap, B256, U256};
+use specification::hardfork::SpecId;
+use state::{Account, AccountInfo, Bytecode, EvmState};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, RwLock,
+    },
+    thread,
+    vec::Vec,
+};
+
+/// Executes the transactions of a block on `threads` threads, on the calling thread if `threads`
+/// is 1.
+///
+/// Returns the result and the changed state of each transaction, as executing them in order and
+/// committing each state to `db` would. Stops at the first transaction that fails.
+pub fn execute_block_parallel<DB, BLOCK, TX, CFG>(
+    db: &DB,
+    block: &BLOCK,
+    cfg: &CFG,
+    txs: &[TX],
+    threads: usize,
+) -> Result<Vec<ResultAndState<HaltReason>>, BlockExecutionError<DB::Error>>
+where
+    DB: DatabaseRef + Sync,
+    DB::Error: Send,
+    BLOCK: Block + Sync,
+    TX: Transaction + Sync,
+    CFG: Cfg + Sync,
+{
+    let executor = Executor {
+        db,
+        block,
+        cfg,
+        txs,
+        memory: RwLock::default(),
+        code: RwLock::default(),
+        credits: txs.iter().map(|_| Mutex::new(None)).collect(),
+        executions: txs.iter().map(|_| Mutex::new(None)).collect(),
+        next_execution: AtomicUsize::new(0),
+        commits: Mutex::new(Commits {
+            results: Vec::with_capacity(txs.len()),
+            error: None,
+        }),
+        done: AtomicBool::new(txs.is_empty()),
+    };
+    // Targets without threads, e.g. `wasm32-unknown-unknown`, can execute on the calling thread.
+    if threads <= 1 {
+        executor.work();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| executor.work());
+            }
+        });
+    }
+
+    let commits = executor.commits.into_inner().expect("worker panicked");
+    match commits.error {
+        Some(error) => Err(error),
+        None => Ok(commits.results),
+    }
+}
+
+/// Error of the transaction at `index` of a block, the transactions after it were not executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockExecutionError<E> {
+    pub index: usize,
+    pub error: EVMError<E, InvalidTransaction>,
+}
+
+impl<E: fmt::Display> fmt::Display for BlockExecutionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transaction {} failed: {}", self.index, self.error)
+    }
+}
+
+impl<E: Error + 'static> Error for BlockExecutionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Writes of the transactions, by transaction index.
+#[derive(Default)]
+struct MultiVersionState {
+    /// Accounts, [`None`] if selfdestructed.
+    accounts: HashMap<Address, BTreeMap<usize, Option<AccountInfo>>>,
+    storage: HashMap<(Address, U256), BTreeMap<usize, U256>>,
+    /// Transactions clearing the storage of an account, by selfdestructing or creating it.
+    storage_resets: HashMap<Address, BTreeSet<usize>>,
+}
+
+/// Changes of a transaction to the accounts touched by it.
+#[derive(Default)]
+struct WriteSet {
+    accounts: Vec<(Address, Option<AccountInfo>)>,
+    storage: Vec<((Address, U256), U256)>,
+    storage_resets: Vec<Address>,
+}
+
+impl WriteSet {
+    /// Collects the changes of a state as they are committed to a database.
+    fn new(state: &EvmState) -> Self {
+        let mut writes = Self::default();
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                writes.accounts.push((*address, None));
+                writes.storage_resets.push(*address);
+                continue;
+            }
+            if account.is_created() {
+                writes.storage_resets.push(*address);
+            }
+            writes.accounts.push((*address, Some(account.info.clone())));
+            writes.storage.extend(
+                account
+                    .changed_storage_slots()
+                    .map(|(slot, value)| ((*address, *slot), value.present_value)),
+            );
+        }
+        writes
+    }
+}
+
+/// A value read by a transaction.
+enum Read {
+    Account(Address, Option<AccountInfo>),
+    Storage(Address, U256, U256),
+}
+
+/// Outcome of the latest execution of a transaction.
+struct Execution<E> {
+    outcome: Result<ResultAndState<HaltReason>, EVMError<E, InvalidTransaction>>,
+    reads: Vec<Read>,
+    writes: WriteSet,
+}
+
+/// Results of the committed transactions.
+struct Commits<E> {
+    results: Vec<ResultAndState<HaltReason>>,
+    error: Option<BlockExecutionError<E>>,
+}
+
+struct Executor<'a, DB: DatabaseRef, BLOCK, TX, CFG> {
+    db: &'a DB,
+    block: &'a BLOCK,
+    cfg: &'a CFG,
+    txs: &'a [TX],
+    memory: RwLock<MultiVersionState>,
+    /// Code deployed by the transactions.
+    code: RwLock<HashMap<B256, Bytecode>>,
+    /// Reward of each transaction that the beneficiary keeps after it.
+    credits: Vec<Mutex<Option<U256>>>,
+    /// Executions waiting to be committed.
+    executions: Vec<Mutex<Option<Execution<DB::Error>>>>,
+    next_execution: AtomicUsize,
+    /// Also the lock of the committing thread.
+    commits: Mutex<Commits<DB::Error>>,
+    done: AtomicBool,
+}
+
+impl<DB, BLOCK, TX, CFG> Executor<'_, DB, BLOCK, TX, CFG>
+where
+    DB: DatabaseRef,
+    BLOCK: Block,
+    TX: Transaction,
+    CFG: Cfg,
+{
+    /// Executes transactions until the block is committed, committing them when no other thread
+    /// does.
+    fn work(&self) {
+        while !self.done.load(Ordering::Acquire) {
+            if let Ok(mut commits) = self.commits.try_lock() {
+                self.commit(&mut commits);
+                if self.done.load(Ordering::Acquire) {
+                    return;
+                }
+            }
+            let index = self.next_execution.fetch_add(1, Ordering::Relaxed);
+            if index < self.txs.len() {
+                let execution = self.execute(index);
+                self.publish(index, None, &execution);
+                *self.executions[index].lock().unwrap() = Some(execution);
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+
+    /// Commits the executed transactions following the committed ones, re-executing those that
+    /// read a value changed since.
+    fn commit(&self, commits: &mut Commits<DB::Error>) {
+        while commits.results.len() < self.txs.len() {
+            let index = commits.results.len();
+            let Some(mut execution) = self.executions[index].lock().unwrap().take() else {
+                return;
+            };
+            if !self.validate(index, &execution.reads) {
+                let outdated = execution;
+                execution = self.execute(index);
+                self.publish(index, Some(&outdated.writes), &execution);
+            }
+            let result = execution.outcome.and_then(|mut result| {
+                self.reward_beneficiary(index, &mut result)?;
+                Ok(result)
+            });
+            match result {
+                Ok(result) => commits.results.push(result),
+                Err(error) => {
+                    commits.error = Some(BlockExecutionError { index, error });
+                    break;
+                }
+            }
+        }
+        self.done.store(true, Ordering::Release);
+    }
+
+    /// Executes the transaction at `index` without rewarding the beneficiary.
+    fn execute(&self, index: usize) -> Execution<DB::Error> {
+        let view = TransactionView {
+            executor: self,
+            index,
+            reads: Vec::new(),
+        };
+        let mut ctx = Context::builder()
+            .with_block(self.block)
+            .with_tx(&self.txs[index])
+            .with_cfg(self.cfg)
+            .with_db(view);
+        let outcome = UnrewardedHandler::<_, EVMError<DB::Error, InvalidTransaction>> {
+            _phantom: PhantomData,
+        }
+        .run(&mut ctx);
+        let writes = outcome
+            .as_ref()
+            .map(|result| WriteSet::new(&result.state))
+            .unwrap_or_default();
+        Execution {
+            outcome,
+            reads: ctx.journaled_state.database.reads,
+            writes,
+        }
+    }
+
+    /// Replaces the writes of the previous execution of the transaction at `index`.
+    fn publish(&self, index: usize, outdated: Option<&WriteSet>, execution: &Execution<DB::Error>) {
+        let mut memory = self.memory.write().unwrap();
+        if let Some(outdated) = outdated {
+            for (address, _) in &outdated.accounts {
+                memory
+                    .accounts
+                    .get_mut(address)
+                    .map(|versions| versions.remove(&index));
+            }
+            for (key, _) in &outdated.storage {
+                memory
+                    .storage
+                    .get_mut(key)
+                    .map(|versions| versions.remove(&index));
+            }
+            for address in &outdated.storage_resets {
+                memory
+                    .storage_resets
+                    .get_mut(address)
+                    .map(|versions| versions.remove(&index));
+            }
+        }
+
+        let writes = &execution.writes;
+        for (address, info) in &writes.accounts {
+            if let Some(code) = info.as_ref().and_then(|info| info.code.clone()) {
+                self.code.write().unwrap().insert(code.hash_slow(), code);
+            }
+            memory
+                .accounts
+                .entry(*address)
+                .or_default()
+                .insert(index, info.clone());
+        }
+        for (key, value) in &writes.storage {
+            memory
+                .storage
+                .entry(*key)
+                .or_default()
+                .insert(index, *value);
+        }
+        for address in &writes.storage_resets {
+            memory
+                .storage_resets
+                .entry(*address)
+                .or_default()
+                .insert(index);
+        }
+        drop(memory);
+
+        let beneficiary = self.block.beneficiary();
+        *self.credits[index].lock().unwrap() = execution.outcome.as_ref().ok().and_then(|result| {
+            let selfdestructed = result
+                .state
+                .get(&beneficiary)
+                .is_some_and(Account::is_selfdestructed);
+            (!selfdestructed).then(|| self.reward(&result.result))
+        });
+    }
+
+    /// Returns `true` if the values read by the transaction at `index` did not change.
+    fn validate(&self, index: usize, reads: &[Read]) -> bool {
+        reads.iter().all(|read| match read {
+            Read::Account(address, info) => self
+                .read_account(index, *address)
+                .is_ok_and(|current| current == *info),
+            Read::Storage(address, slot, value) => self
+                .read_storage(index, *address, *slot)
+                .is_ok_and(|current| current == *value),
+        })
+    }
+
+    /// Reads an account as the transaction at `index` sees it.
+    fn read_account(
+        &self,
+        index: usize,
+        address: Address,
+    ) -> Result<Option<AccountInfo>, DB::Error> {
+        let latest = self
+            .memory
+            .read()
+            .unwrap()
+            .accounts
+            .get(&address)
+            .and_then(|versions| {
+                versions
+                    .range(..index)
+                    .next_back()
+                    .map(|(writer, info)| (*writer, info.clone()))
+            });
+        let (first_credit, mut info) = match latest {
+            Some((writer, info)) => (writer, info),
+            None => (0, self.db.basic_ref(address)?),
+        };
+
+        // The writer of the beneficiary did not include its own reward.
+        if address == self.block.beneficiary() {
+            for credit in &self.credits[first_credit..index] {
+                if let Some(reward) = *credit.lock().unwrap() {
+                    let info = info.get_or_insert_with(AccountInfo::default);
+                    info.balance = info.balance.saturating_add(reward);
+                }
+            }
+        }
+        Ok(info)
+    }
+
+    /// Reads a storage slot as the transaction at `index` sees it.
+    fn read_storage(&self, index: usize, address: Address, slot: U256) -> Result<U256, DB::Error> {
+        let memory = self.memory.read().unwrap();
+        let latest = memory
+            .storage
+            .get(&(address, slot))
+            .and_then(|versions| versions.range(..index).next_back());
+        let reset = memory
+            .storage_resets
+            .get(&address)
+            .and_then(|versions| versions.range(..index).next_back());
+        match (latest, reset) {
+            (Some((writer, value)), reset) if reset.is_none_or(|reset| writer >= reset) => {
+                Ok(*value)
+            }
+            (_, Some(_)) => Ok(U256::ZERO),
+            _ => {
+                drop(memory);
+                self.db.storage_ref(address, slot)
+            }
+        }
+    }
+
+    /// Returns the reward of the beneficiary for a transaction.
+    fn reward(&self, result: &ExecutionResult<HaltReason>) -> U256 {
+        let basefee = self.block.basefee() as u128;
+        // EIP-1559 discards the basefee.
+        let coinbase_gas_price = if self.cfg.spec().into().is_enabled_in(SpecId::LONDON) {
+            result.effective_gas_price().saturating_sub(basefee)
+        } else {
+            result.effective_gas_price()
+        };
+        U256::from(coinbase_gas_price.saturating_mul(result.gas_used() as u128))
+    }
+
+    /// Adds the reward to the beneficiary, loading it as the journal does if the transaction did
+    /// not.
+    fn reward_beneficiary(
+        &self,
+        index: usize,
+        result: &mut ResultAndState<HaltReason>,
+    ) -> Result<(), EVMError<DB::Error, InvalidTransaction>> {
+        let beneficiary = self.block.beneficiary();
+        let account = match result.state.entry(beneficiary) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let info = self.read_account(index, beneficiary)?;
+                entry.insert(info.map_or_else(Account::new_not_existing, Account::from))
+            }
+        };
+        account.mark_touch();
+        account.info.balance = account
+            .info
+            .balance
+            .saturating_add(self.reward(&result.result));
+        Ok(())
+    }
+}
+
+/// Database of a transaction reading the multi-version state and recording the values it reads.
+struct TransactionView<'a, 'e, DB: DatabaseRef, BLOCK, TX, CFG> {
+    executor: &'e Executor<'a, DB, BLOCK, TX, CFG>,
+    index: usize,
+    reads: Vec<Read>,
+}
+
+impl<DB, BLOCK, TX, CFG> Database for TransactionView<'_, '_, DB, BLOCK, TX, CFG>
+where
+    DB: DatabaseRef,
+    BLOCK: Block,
+    TX: Transaction,
+    CFG: Cfg,
+{
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.executor.read_account(self.index, address)?;
+        self.reads.push(Read::Account(address, info.clone()));
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.executor.code.read().unwrap().get(&code_hash) {
+            return Ok(code.clone());
+        }
+        self.executor.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.executor.read_storage(self.index, address, index)?;
+        self.reads.push(Read::Storage(address, index, value));
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.executor.db.block_hash_ref(number)
+    }
+}
+
+/// Mainnet handler that does not reward the beneficiary.
+struct UnrewardedHandler<CTX, ERROR> {
+    _phantom: PhantomData<(CTX, ERROR)>,
+}
+
+impl<CTX, ERROR> EthHandler for UnrewardedHandler<CTX, ERROR>
+where
+    CTX: EthContext,
+    ERROR: EthError<CTX>,
+{
+    type Context = CTX;
+    type Error = ERROR;
+    type Precompiles = EthPrecompileProvider<CTX, ERROR>;
+    type Instructions = EthInstructionExecutor<EthInterpreter, CTX>;
+    type Frame =
+        EthFrame<CTX, ERROR, EthInterpreter, FrameContext<Self::Precompiles, Self::Instructions>>;
+    type HaltReason = HaltReason;
+
+    fn reward_beneficiary(
+        &self,
+        _context: &mut Self::Context,
+        _exec_result: &mut FrameResult,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bytecode::opcode,
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::DatabaseGetter,
+        primitives::{address, Bytes, TxKind},
+        DatabaseCommit, ExecuteEvm,
+    };
+    use database::InMemoryDB;
+    use std::vec;
+
+    const COINBASE: Address = address!("0000000000000000000000000000000000c01bba");
+    const COUNTER: Address = address!("0000000000000000000000000000000000001000");
+    const COINBASE_READER: Address = address!("0000000000000000000000000000000000001001");
+
+    /// Increments slot 0.
+    const COUNTER_CODE: [u8; 8] = [
+        opcode::PUSH0,
+        opcode::SLOAD,
+        opcode::PUSH1,
+        0x01,
+        opcode::ADD,
+        opcode::PUSH0,
+        opcode::SSTORE,
+        opcode::STOP,
+    ];
+
+    fn sender(index: u8) -> Address {
+        Address::with_last_byte(index + 1)
+    }
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        for index in 0..5 {
+            db.insert_account_info(sender(index), AccountInfo::from_balance(U256::MAX >> 1));
+        }
+        // Stores the balance of the beneficiary at slot 0.
+        let reader = [
+            opcode::COINBASE,
+            opcode::BALANCE,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        for (address, code) in [(COUNTER, &COUNTER_CODE[..]), (COINBASE_READER, &reader)] {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    code_hash: code.hash_slow(),
+                    code: Some(code),
+                    ..Default::default()
+                },
+            );
+        }
+        db
+    }
+
+    fn block() -> BlockEnv {
+        BlockEnv {
+            beneficiary: COINBASE,
+            basefee: 1,
+            gas_limit: 30_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn tx(sender_index: u8, nonce: u64, kind: TxKind) -> TxEnv {
+        TxEnv {
+            caller: sender(sender_index),
+            nonce,
+            kind,
+            gas_limit: 100_000,
+            gas_price: 10,
+            ..Default::default()
+        }
+    }
+
+    fn execute_sequential(
+        block: &BlockEnv,
+        cfg: &CfgEnv,
+        txs: &[TxEnv],
+    ) -> Vec<ResultAndState<HaltReason>> {
+        let mut ctx = Context::builder()
+            .with_block(block.clone())
+            .with_cfg(cfg.clone())
+            .with_db(db());
+        txs.iter()
+            .map(|tx| {
+                ctx.tx = tx.clone();
+                let result = ctx.exec_previous().unwrap();
+                ctx.db().commit(result.state.clone());
+                result
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_sequential_execution() {
+        // Deploys the counter code.
+        let mut init_code = vec![opcode::PUSH8];
+        init_code.extend(COUNTER_CODE);
+        init_code.extend([
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x08,
+            opcode::PUSH1,
+            0x18,
+            opcode::RETURN,
+        ]);
+        let created = sender(3).create(0);
+        let mut transfer = tx(2, 0, TxKind::Call(COINBASE));
+        transfer.value = U256::from(1_000);
+        let txs = vec![
+            tx(0, 0, TxKind::Call(COUNTER)),
+            tx(1, 0, TxKind::Call(COUNTER)),
+            transfer,
+            tx(0, 1, TxKind::Call(COINBASE_READER)),
+            TxEnv {
+                data: init_code.into(),
+                ..tx(3, 0, TxKind::Create)
+            },
+            tx(4, 0, TxKind::Call(created)),
+            tx(1, 1, TxKind::Call(created)),
+            tx(3, 1, TxKind::Call(Address::with_last_byte(0xff))),
+        ];
+        let block = block();
+        let cfg = CfgEnv::default();
+
+        let sequential = execute_sequential(&block, &cfg, &txs);
+        assert!(sequential.iter().all(|result| result.result.is_success()));
+        for threads in [1, 2, 4, 8] {
+            let parallel = execute_block_parallel(&db(), &block, &cfg, &txs, threads).unwrap();
+            assert_eq!(parallel, sequential, "{threads} threads");
+        }
+    }
+
+    #[test]
+    fn stops_at_failed_transaction() {
+        let txs = vec![
+            tx(0, 0, TxKind::Call(COUNTER)),
+            tx(0, 0, TxKind::Call(COUNTER)),
+            tx(1, 0, TxKind::Call(COUNTER)),
+        ];
+        let error =
+            execute_block_parallel(&db(), &block(), &CfgEnv::default(), &txs, 4).unwrap_err();
+        assert_eq!(
+            error,
+            BlockExecutionError {
+                index: 1,
+                error: EVMError::Transaction(InvalidTransaction::NonceTooLow { tx: 0, state: 1 }),
+            }
+        );
+    }
+
+    #[test]
+    fn saturates_large_rewards() {
+        let txs = vec![TxEnv {
+            gas_price: u128::MAX,
+            gas_priority_fee: Some(u128::MAX),
+            ..tx(0, 0, TxKind::Call(COUNTER))
+        }];
+        let results = execute_block_parallel(&db(), &block(), &CfgEnv::default(), &txs, 2).unwrap();
+        assert!(results[0].result.is_success());
+        assert_eq!(
+            results[0].state[&COINBASE].info.balance,
+            U256::from(u128::MAX)
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! Simulation of bundles, ordered transactions executed atomically on top of the same state.
+//!
+//! Each transaction sees the changes of the transactions before it, which are merged into the
+//! state diff of the bundle instead of being committed, so the database is never changed.
+use crate::{exec_eth::transact_main, Context, DatabaseRef};
+use context::Cfg;
+use context_interface::{
+    result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+    Block, Database, Transaction,
+};
+use core::{error::Error, fmt};
+use primitives::{hash_map::Entry, Address, HashMap, B256, U256};
+use specification::hardfork::SpecId;
+use state::{AccountInfo, Bytecode, EvmState};
+use std::{boxed::Box, vec::Vec};
+
+/// Simulates the transactions of a bundle in order, on top of the state of `db`.
+///
+/// If `revert_on_failure` is set, a transaction that reverts or halts fails the whole bundle, as
+/// it would be excluded from a block. A transaction that is not valid always fails the bundle.
+pub fn simulate_bundle<DB, BLOCK, TX, CFG>(
+    db: &DB,
+    block: &BLOCK,
+    cfg: &CFG,
+    txs: &[TX],
+    revert_on_failure: bool,
+) -> Result<BundleSimulation, BundleSimulationError<DB::Error>>
+where
+    DB: DatabaseRef,
+    BLOCK: Block,
+    TX: Transaction,
+    CFG: Cfg,
+{
+    let beneficiary = block.beneficiary();
+    let is_london = cfg.spec().into().is_enabled_in(SpecId::LONDON);
+    let mut bundle_db = BundleDb {
+        db,
+        state: EvmState::default(),
+        contracts: HashMap::default(),
+    };
+    let mut results = Vec::with_capacity(txs.len());
+    for (index, tx) in txs.iter().enumerate() {
+        let invalid = |error| BundleSimulationError::Invalid { index, error };
+        let balance_before = bundle_db
+            .balance(beneficiary)
+            .map_err(|error| invalid(EVMError::Database(error)))?;
+
+        let mut ctx = Context::builder()
+            .with_block(block)
+            .with_tx(tx)
+            .with_cfg(cfg)
+            .with_db(bundle_db);
+        let result = transact_main(&mut ctx).map_err(invalid)?;
+        bundle_db = ctx.journaled_state.database;
+        if revert_on_failure && !result.result.is_success() {
+            return Err(BundleSimulationError::Failed {
+                index,
+                result: Box::new(result.result),
+            });
+        }
+        bundle_db.merge(result.state);
+
+        let balance_after = bundle_db
+            .balance(beneficiary)
+            .map_err(|error| invalid(EVMError::Database(error)))?;
+        // EIP-1559 discards the basefee.
+        let coinbase_gas_price = if is_london {
+            result
+                .result
+                .effective_gas_price()
+                .saturating_sub(block.basefee() as u128)
+        } else {
+            result.result.effective_gas_price()
+        };
+        results.push(BundleTxResult {
+            gas_fees: U256::from(coinbase_gas_price * result.result.gas_used() as u128),
+            coinbase_payment: balance_after.saturating_sub(balance_before),
+            result: result.result,
+        });
+    }
+    Ok(BundleSimulation {
+        results,
+        state: bundle_db.state,
+    })
+}
+
+/// Results of a bundle simulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleSimulation {
+    /// Results of the transactions, in order.
+    pub results: Vec<BundleTxResult>,
+    /// Accounts changed by the bundle, as a single transaction changing them would.
+    ///
+    /// Storage slots keep their value before the bundle as original value, and accounts
+    /// created or selfdestructed by any transaction are marked as created or selfdestructed, so
+    /// committing the state to a database applies the whole bundle.
+    pub state: EvmState,
+}
+
+impl BundleSimulation {
+    /// Returns the gas used by the bundle.
+    pub fn gas_used(&self) -> u64 {
+        self.results.iter().map(|tx| tx.result.gas_used()).sum()
+    }
+
+    /// Returns the increase of the balance of the beneficiary, the profit of including the
+    /// bundle.
+    pub fn coinbase_payment(&self) -> U256 {
+        self.results.iter().fold(U256::ZERO, |total, tx| {
+            total.saturating_add(tx.coinbase_payment)
+        })
+    }
+
+    /// Returns the fees paid to the beneficiary for the gas used by the bundle.
+    pub fn gas_fees(&self) -> U256 {
+        self.results
+            .iter()
+            .fold(U256::ZERO, |total, tx| total.saturating_add(tx.gas_fees))
+    }
+}
+
+/// Result of a transaction of a bundle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleTxResult {
+    pub result: ExecutionResult<HaltReason>,
+    /// Increase of the balance of the beneficiary, gas fees and value sent to it, zero if the
+    /// balance decreased.
+    pub coinbase_payment: U256,
+    /// Fees paid to the beneficiary for the gas used, the gas price above the basefee since London.
+    pub gas_fees: U256,
+}
+
+/// Error failing the bundle at the transaction at `index`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleSimulationError<E> {
+    /// The transaction is not valid or the database failed.
+    Invalid {
+        index: usize,
+        error: EVMError<E, InvalidTransaction>,
+    },
+    /// The transaction reverted or halted, and the bundle reverts on failure.
+    Failed {
+        index: usize,
+        result: Box<ExecutionResult<HaltReason>>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for BundleSimulationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid { index, error } => write!(f, "transaction {index} failed: {error}"),
+            Self::Failed { index, result } => match result.revert_reason() {
+                Some(reason) => write!(f, "transaction {index} reverted: {reason}"),
+                None => write!(f, "transaction {index} did not succeed"),
+            },
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for BundleSimulationError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Invalid { error, .. } => Some(error),
+            Self::Failed { .. } => None,
+        }
+    }
+}
+
+/// Database reading the changes of the transactions simulated so far, then the wrapped database.
+struct BundleDb<'a, DB> {
+    db: &'a DB,
+    /// Merged changes of the simulated transactions.
+    state: EvmState,
+    /// Code of the accounts of `state`.
+    contracts: HashMap<B256, Bytecode>,
+}
+
+impl<DB: DatabaseRef> BundleDb<'_, DB> {
+    fn balance(&mut self, address: Address) -> Result<U256, DB::Error> {
+        Ok(self
+            .basic(address)?
+            .map(|info| info.balance)
+            .unwrap_or_default())
+    }
+
+    /// Merges the changes of a transaction into the changes of the transactions before it.
+    fn merge(&mut self, changes: EvmState) {
+        for (address, mut account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if let Some(code) = &account.info.code {
+                self.contracts
+                    .entry(account.info.code_hash)
+                    .or_insert_with(|| code.clone());
+            }
+            let merged = match self.state.entry(address) {
+                Entry::Vacant(entry) => {
+                    entry.insert(account);
+                    continue;
+                }
+                Entry::Occupied(entry) => entry.into_mut(),
+            };
+            // Keep the values before the bundle as original values.
+            for (slot, value) in &mut account.storage {
+                if let Some(previous) = merged.storage.get(slot) {
+                    value.original_value = previous.original_value;
+                }
+            }
+            if account.is_created() || account.is_selfdestructed() {
+                // The storage written before is cleared.
+                merged.storage = account.storage;
+                merged.status = account.status;
+            } else {
+                merged.storage.extend(account.storage);
+                if merged.is_selfdestructed() {
+                    // The account exists again, with the storage cleared.
+                    merged.unmark_selfdestruct();
+                    merged.mark_created();
+                }
+                merged.status |= account.status;
+            }
+            merged.info = account.info;
+        }
+    }
+}
+
+impl<DB: DatabaseRef> Database for BundleDb<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.state.get(&address) {
+            Some(account) if account.is_selfdestructed() => Ok(None),
+            Some(account) => Ok(Some(account.info.clone())),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let Some(account) = self.state.get(&address) else {
+            return self.db.storage_ref(address, index);
+        };
+        match account.storage.get(&index) {
+            Some(slot) => Ok(slot.present_value),
+            None if account.is_created() || account.is_selfdestructed() => Ok(U256::ZERO),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bytecode::opcode,
+        context::{BlockEnv, CfgEnv, TxEnv},
+        primitives::{address, Bytes, TxKind},
+    };
+    use database::InMemoryDB;
+
+    const COINBASE: Address = address!("0000000000000000000000000000000000c01bba");
+    const COUNTER: Address = address!("0000000000000000000000000000000000001000");
+    const REVERTER: Address = address!("0000000000000000000000000000000000001001");
+    const SENDER: Address = address!("0000000000000000000000000000000000000001");
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(SENDER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        // Increments slot 0.
+        let counter = [
+            opcode::PUSH0,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x01,
+            opcode::ADD,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let reverter = [opcode::PUSH0, opcode::PUSH0, opcode::REVERT];
+        for (address, code) in [(COUNTER, &counter[..]), (REVERTER, &reverter)] {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    code_hash: code.hash_slow(),
+                    code: Some(code),
+                    ..Default::default()
+                },
+            );
+        }
+        db
+    }
+
+    fn tx(nonce: u64, to: Address) -> TxEnv {
+        TxEnv {
+            caller: SENDER,
+            nonce,
+            kind: TxKind::Call(to),
+            gas_limit: 100_000,
+            gas_price: 10,
+            gas_priority_fee: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn simulates_bundle() {
+        let db = db();
+        let block = BlockEnv {
+            beneficiary: COINBASE,
+            basefee: 1,
+            ..Default::default()
+        };
+        let cfg = CfgEnv::default();
+        let payment = TxEnv {
+            value: U256::from(1_000),
+            ..tx(2, COINBASE)
+        };
+        let txs = [tx(0, COUNTER), tx(1, COUNTER), payment];
+
+        let simulation = simulate_bundle(&db, &block, &cfg, &txs, true).unwrap();
+        assert!(simulation.results.iter().all(|tx| tx.result.is_success()));
+        let slot = &simulation.state[&COUNTER].storage[&U256::ZERO];
+        assert_eq!(slot.original_value, U256::ZERO);
+        assert_eq!(slot.present_value, U256::from(2));
+        assert_eq!(simulation.state[&SENDER].info.nonce, 3);
+        assert_eq!(simulation.gas_fees(), U256::from(9 * simulation.gas_used()));
+        assert_eq!(
+            simulation.coinbase_payment(),
+            simulation.gas_fees() + U256::from(1_000)
+        );
+        assert_eq!(
+            simulation.state[&COINBASE].info.balance,
+            simulation.coinbase_payment()
+        );
+        // The database is not changed.
+        assert_eq!(db.storage_ref(COUNTER, U256::ZERO), Ok(U256::ZERO));
+
+        let txs = [tx(0, COUNTER), tx(1, REVERTER)];
+        assert!(matches!(
+            simulate_bundle(&db, &block, &cfg, &txs, true),
+            Err(BundleSimulationError::Failed { index: 1, .. })
+        ));
+        let simulation = simulate_bundle(&db, &block, &cfg, &txs, false).unwrap();
+        assert!(!simulation.results[1].result.is_success());
+
+        let txs = [tx(0, COUNTER), tx(0, COUNTER)];
+        assert!(matches!(
+            simulate_bundle(&db, &block, &cfg, &txs, false),
+            Err(BundleSimulationError::Invalid {
+                index: 1,
+                error: EVMError::Transaction(InvalidTransaction::NonceTooLow { .. }),
+            })
+        ));
+    }
+}
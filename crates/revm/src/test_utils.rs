@@ -0,0 +1,295 @@
+//! Utilities to test contracts and handler changes.
+//!
+//! [`TestEvm`] keeps an in-memory state between transactions:
+//!
+//! ```
+//! use revm::{
+//!     bytecode::opcode,
+//!     primitives::{Bytes, U256},
+//!     test_utils::TestEvm,
+//! };
+//!
+//! let mut evm = TestEvm::new();
+//! // Stores the call value at slot 0.
+//! let contract = evm.set_code([opcode::CALLVALUE, opcode::PUSH0, opcode::SSTORE]);
+//! evm.call_with_value(contract, Bytes::new(), U256::from(1));
+//! evm.assert_storage(contract, U256::ZERO, U256::from(1));
+//! evm.assert_balance(contract, U256::from(1));
+//! ```
+use crate::{
+    context::{BlockEnv, CfgEnv, TxEnv},
+    context_interface::result::{
+        EVMError, ExecutionResult, HaltReason, InvalidTransaction, Output,
+    },
+    primitives::{address, Address, Bytes, TxKind, U256},
+    state::{AccountInfo, Bytecode},
+    Context, DatabaseRef, ExecuteCommitEvm,
+};
+use core::convert::Infallible;
+use database::InMemoryDB;
+use specification::hardfork::SpecId;
+use std::boxed::Box;
+
+mod failure;
+
+pub use failure::*;
+
+/// Default sender of the transactions of a [`TestEvm`].
+pub const TEST_CALLER: Address = address!("1000000000000000000000000000000000000001");
+
+/// Balance [`TEST_CALLER`] starts with, 1M ether.
+pub const TEST_CALLER_BALANCE: U256 = U256::from_limbs([0x1bcecceda1000000, 0xd3c2, 0, 0]);
+
+/// Context of a [`TestEvm`].
+pub type TestContext = Context<BlockEnv, TxEnv, CfgEnv, InMemoryDB>;
+
+/// EVM with an in-memory state, sending transactions from a funded caller.
+///
+/// Transactions are committed, have no gas price and a gas limit of 30M.
+///
+/// Methods panic if a transaction is invalid, as tests are expected to send valid ones.
+#[derive(Clone, Debug)]
+pub struct TestEvm {
+    context: TestContext,
+    caller: Address,
+}
+
+impl Default for TestEvm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEvm {
+    /// Gas limit of the transactions.
+    pub const GAS_LIMIT: u64 = 30_000_000;
+
+    /// Creates an EVM running the latest spec.
+    pub fn new() -> Self {
+        Self::with_spec(SpecId::LATEST)
+    }
+
+    /// Creates an EVM running the given spec.
+    pub fn with_spec(spec: SpecId) -> Self {
+        let mut context = TestContext::new(InMemoryDB::default(), spec);
+        context.block.gas_limit = u64::MAX;
+        let mut evm = Self {
+            context,
+            caller: TEST_CALLER,
+        };
+        evm.fund(TEST_CALLER, TEST_CALLER_BALANCE);
+        evm
+    }
+
+    /// Sets the sender of the next transactions.
+    pub fn with_caller(mut self, caller: Address) -> Self {
+        self.caller = caller;
+        self
+    }
+
+    /// Returns the sender of the transactions.
+    pub fn caller(&self) -> Address {
+        self.caller
+    }
+
+    /// Returns the context, to change the environment or the state.
+    pub fn context(&mut self) -> &mut TestContext {
+        &mut self.context
+    }
+
+    /// Returns the state.
+    pub fn db(&mut self) -> &mut InMemoryDB {
+        &mut self.context.journaled_state.database
+    }
+
+    /// Returns the account, [`None`] if it does not exist.
+    pub fn account(&self, address: Address) -> Option<AccountInfo> {
+        let Ok(info) = self.context.journaled_state.database.basic_ref(address);
+        info
+    }
+
+    /// Returns the balance of the account.
+    pub fn balance(&self, address: Address) -> U256 {
+        self.account(address)
+            .map(|info| info.balance)
+            .unwrap_or_default()
+    }
+
+    /// Returns the value of the storage slot.
+    pub fn storage(&self, address: Address, slot: U256) -> U256 {
+        let Ok(value) = self
+            .context
+            .journaled_state
+            .database
+            .storage_ref(address, slot);
+        value
+    }
+
+    /// Adds `amount` to the balance of the account.
+    pub fn fund(&mut self, address: Address, amount: U256) {
+        let mut info = self.account(address).unwrap_or_default();
+        info.balance = info.balance.saturating_add(amount);
+        self.db().insert_account_info(address, info);
+    }
+
+    /// Sets the code of an account without executing a transaction and returns its address.
+    ///
+    /// Accounts get consecutive addresses starting at `0x...1000`.
+    pub fn set_code(&mut self, code: impl Into<Bytes>) -> Address {
+        let address = (0x1000u64..)
+            .map(|index| Address::left_padding_from(&index.to_be_bytes()))
+            .find(|address| self.account(*address).is_none())
+            .expect("address space is not exhausted");
+        let code = Bytecode::new_raw(code.into());
+        self.db().insert_account_info(
+            address,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        address
+    }
+
+    /// Deploys a contract with `init_code`, returning its address or the failed execution.
+    pub fn deploy(
+        &mut self,
+        init_code: impl Into<Bytes>,
+    ) -> Result<Address, Box<ExecutionResult<HaltReason>>> {
+        let result = self.transact(TxKind::Create, init_code.into(), U256::ZERO);
+        match result {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => Ok(address),
+            result => Err(Box::new(result)),
+        }
+    }
+
+    /// Calls the contract.
+    pub fn call(&mut self, to: Address, calldata: impl Into<Bytes>) -> ExecutionResult<HaltReason> {
+        self.call_with_value(to, calldata, U256::ZERO)
+    }
+
+    /// Calls the contract, transferring `value` to it.
+    pub fn call_with_value(
+        &mut self,
+        to: Address,
+        calldata: impl Into<Bytes>,
+        value: U256,
+    ) -> ExecutionResult<HaltReason> {
+        self.transact(TxKind::Call(to), calldata.into(), value)
+    }
+
+    /// Executes and commits the transaction in the context, returning the error if it is invalid.
+    pub fn exec(
+        &mut self,
+    ) -> Result<ExecutionResult<HaltReason>, EVMError<Infallible, InvalidTransaction>> {
+        self.context.exec_commit_previous()
+    }
+
+    fn transact(&mut self, kind: TxKind, data: Bytes, value: U256) -> ExecutionResult<HaltReason> {
+        let nonce = self
+            .account(self.caller)
+            .map(|info| info.nonce)
+            .unwrap_or_default();
+        self.context.tx = TxEnv {
+            caller: self.caller,
+            gas_limit: Self::GAS_LIMIT,
+            kind,
+            value,
+            data,
+            nonce,
+            ..Default::default()
+        };
+        self.exec()
+            .unwrap_or_else(|error| panic!("invalid transaction: {error}"))
+    }
+
+    /// Asserts the balance of the account.
+    #[track_caller]
+    pub fn assert_balance(&self, address: Address, expected: U256) {
+        assert_eq!(
+            self.balance(address),
+            expected,
+            "balance of {address} mismatch"
+        );
+    }
+
+    /// Asserts the value of the storage slot.
+    #[track_caller]
+    pub fn assert_storage(&self, address: Address, slot: U256, expected: U256) {
+        assert_eq!(
+            self.storage(address, slot),
+            expected,
+            "storage slot {slot} of {address} mismatch"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::opcode;
+
+    #[test]
+    fn deploy_and_call() {
+        let mut evm = TestEvm::new();
+        // Returns runtime code adding 1 to slot 0.
+        let runtime = [
+            opcode::PUSH0,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x01,
+            opcode::ADD,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let mut init_code = vec![
+            opcode::PUSH1,
+            runtime.len() as u8,
+            opcode::PUSH1,
+            0x0c,
+            opcode::PUSH0,
+            opcode::CODECOPY,
+            opcode::PUSH1,
+            runtime.len() as u8,
+            opcode::PUSH0,
+            opcode::RETURN,
+            opcode::INVALID,
+            opcode::INVALID,
+        ];
+        init_code.extend(runtime);
+
+        let contract = evm.deploy(init_code).unwrap();
+        assert_eq!(contract, TEST_CALLER.create(0));
+        assert!(evm.call(contract, Bytes::new()).is_success());
+        assert!(evm.call(contract, Bytes::new()).is_success());
+        evm.assert_storage(contract, U256::ZERO, U256::from(2));
+        assert_eq!(evm.account(TEST_CALLER).unwrap().nonce, 3);
+
+        let reverted = evm.deploy([opcode::PUSH0, opcode::PUSH0, opcode::REVERT]);
+        assert!(matches!(
+            reverted.map_err(|result| *result),
+            Err(ExecutionResult::Revert { .. })
+        ));
+    }
+
+    #[test]
+    fn fund() {
+        let mut evm = TestEvm::new();
+        let contract = evm.set_code([opcode::STOP]);
+        assert_eq!(evm.balance(TEST_CALLER), TEST_CALLER_BALANCE);
+        assert_eq!(
+            TEST_CALLER_BALANCE,
+            U256::from(1_000_000) * U256::from(10).pow(U256::from(18))
+        );
+        evm.fund(contract, U256::from(10));
+        evm.fund(contract, U256::from(5));
+        evm.assert_balance(contract, U256::from(15));
+        assert!(evm.account(contract).unwrap().code.is_some());
+        assert_ne!(evm.set_code([opcode::STOP]), contract);
+    }
+}
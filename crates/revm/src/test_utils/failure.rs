@@ -0,0 +1,420 @@
+//! Scheduled failures of the database and the precompiles, to test error paths of the handler.
+use crate::{
+    context_interface::{
+        result::{EVMError, HaltReason, InvalidTransaction, ResultAndState},
+        Cfg, CfgGetter, DatabaseGetter,
+    },
+    handler::{
+        instructions::EthInstructionExecutor, EthContext, EthFrame, EthHandler,
+        EthPrecompileProvider, MainnetHandler,
+    },
+    handler_interface::PrecompileProvider,
+    interpreter::{interpreter::EthInterpreter, Gas, InstructionResult, InterpreterResult},
+    precompile::PrecompileErrors,
+    primitives::{Address, Bytes, HashMap, HashSet, B256, U256},
+    state::{Account, AccountInfo, Bytecode},
+    Database, DatabaseCommit,
+};
+use core::{error::Error, fmt, marker::PhantomData};
+use database_interface::DBErrorMarker;
+
+/// Read of a [`Database`], counted by [`FailingDb`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DbOperation {
+    Basic,
+    CodeByHash,
+    Storage,
+    BlockHash,
+}
+
+/// A [`Database`] wrapper that fails the reads and precompile calls scheduled on it.
+///
+/// Reads and precompile calls are counted from 1, e.g. `fail_read(DbOperation::Storage, 3)` fails
+/// the third storage read and lets the following ones through. Only reads that reach the database
+/// are counted, values already loaded in the journal are not read again.
+///
+/// Precompile failures fire when transactions are executed with [`exec_with_failures`].
+#[derive(Debug, Default)]
+pub struct FailingDb<DB> {
+    /// The wrapped database.
+    pub db: DB,
+    reads: HashMap<DbOperation, u64>,
+    failing_reads: HashSet<(DbOperation, u64)>,
+    precompile_calls: HashMap<Address, u64>,
+    failing_precompile_calls: HashMap<(Address, u64), PrecompileErrors>,
+}
+
+impl<DB> FailingDb<DB> {
+    /// Wraps the database without scheduled failures.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            reads: HashMap::default(),
+            failing_reads: HashSet::default(),
+            precompile_calls: HashMap::default(),
+            failing_precompile_calls: HashMap::default(),
+        }
+    }
+
+    /// Fails the `nth` read of `operation` with [`FailingDbError::Injected`].
+    pub fn fail_read(&mut self, operation: DbOperation, nth: u64) -> &mut Self {
+        self.failing_reads.insert((operation, nth));
+        self
+    }
+
+    /// Fails the `nth` call to the precompile at `address` with `error`.
+    ///
+    /// [`PrecompileErrors::Error`] fails the call, [`PrecompileErrors::Fatal`] the transaction.
+    pub fn fail_precompile_call(
+        &mut self,
+        address: Address,
+        nth: u64,
+        error: PrecompileErrors,
+    ) -> &mut Self {
+        self.failing_precompile_calls.insert((address, nth), error);
+        self
+    }
+
+    /// Returns the number of reads of `operation` so far.
+    pub fn reads(&self, operation: DbOperation) -> u64 {
+        self.reads.get(&operation).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of calls to the precompile at `address` so far.
+    pub fn precompile_calls(&self, address: Address) -> u64 {
+        self.precompile_calls
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if all scheduled failures fired.
+    pub fn is_schedule_done(&self) -> bool {
+        self.failing_precompile_calls.is_empty()
+            && self
+                .failing_reads
+                .iter()
+                .all(|(operation, nth)| self.reads(*operation) >= *nth)
+    }
+
+    /// Counts a read, returning an error if it is scheduled to fail.
+    fn read<E>(&mut self, operation: DbOperation) -> Result<(), FailingDbError<E>> {
+        let call = self.reads.entry(operation).or_default();
+        *call += 1;
+        if self.failing_reads.contains(&(operation, *call)) {
+            return Err(FailingDbError::Injected {
+                operation,
+                call: *call,
+            });
+        }
+        Ok(())
+    }
+
+    /// Counts a precompile call, returning the error it is scheduled to fail with.
+    fn precompile_call(&mut self, address: Address) -> Option<PrecompileErrors> {
+        let call = self.precompile_calls.entry(address).or_default();
+        *call += 1;
+        self.failing_precompile_calls.remove(&(address, *call))
+    }
+}
+
+impl<DB: Database> Database for FailingDb<DB> {
+    type Error = FailingDbError<DB::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.read(DbOperation::Basic)?;
+        self.db.basic(address).map_err(FailingDbError::Database)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.read(DbOperation::CodeByHash)?;
+        self.db
+            .code_by_hash(code_hash)
+            .map_err(FailingDbError::Database)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.read(DbOperation::Storage)?;
+        self.db
+            .storage(address, index)
+            .map_err(FailingDbError::Database)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.read(DbOperation::BlockHash)?;
+        self.db.block_hash(number).map_err(FailingDbError::Database)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for FailingDb<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Error of a [`FailingDb`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailingDbError<E> {
+    /// The read was scheduled to fail.
+    Injected { operation: DbOperation, call: u64 },
+    /// The wrapped database failed.
+    Database(E),
+}
+
+impl<E> DBErrorMarker for FailingDbError<E> {}
+
+impl<E: fmt::Display> fmt::Display for FailingDbError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Injected { operation, call } => {
+                write!(f, "injected failure of {operation:?} read {call}")
+            }
+            Self::Database(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error> Error for FailingDbError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        // Displayed as the wrapped error, so its source is the next one.
+        match self {
+            Self::Injected { .. } => None,
+            Self::Database(e) => e.source(),
+        }
+    }
+}
+
+/// Mainnet precompiles failing the calls scheduled on the [`FailingDb`] of the context.
+pub struct FailingPrecompiles<CTX, ERROR> {
+    pub precompiles: EthPrecompileProvider<CTX, ERROR>,
+}
+
+impl<CTX, ERROR> Clone for FailingPrecompiles<CTX, ERROR> {
+    fn clone(&self) -> Self {
+        Self {
+            precompiles: self.precompiles.clone(),
+        }
+    }
+}
+
+impl<CTX: CfgGetter, ERROR> Default for FailingPrecompiles<CTX, ERROR> {
+    fn default() -> Self {
+        Self {
+            precompiles: EthPrecompileProvider::default(),
+        }
+    }
+}
+
+impl<CTX, ERROR, DB> PrecompileProvider for FailingPrecompiles<CTX, ERROR>
+where
+    CTX: CfgGetter + DatabaseGetter<Database = FailingDb<DB>>,
+    DB: Database,
+    ERROR: From<PrecompileErrors>,
+{
+    type Context = CTX;
+    type Error = ERROR;
+    type Output = InterpreterResult;
+    type Spec = <<CTX as CfgGetter>::Cfg as Cfg>::Spec;
+
+    fn set_spec(&mut self, spec: Self::Spec) {
+        self.precompiles.set_spec(spec);
+    }
+
+    fn run(
+        &mut self,
+        context: &mut Self::Context,
+        address: &Address,
+        bytes: &Bytes,
+        gas_limit: u64,
+    ) -> Result<Option<InterpreterResult>, Self::Error> {
+        if !self.precompiles.contains(address) {
+            return Ok(None);
+        }
+        match context.db().precompile_call(*address) {
+            None => self.precompiles.run(context, address, bytes, gas_limit),
+            Some(PrecompileErrors::Error(e)) => Ok(Some(InterpreterResult {
+                result: if e.is_oog() {
+                    InstructionResult::PrecompileOOG
+                } else {
+                    InstructionResult::PrecompileError
+                },
+                gas: Gas::new(gas_limit),
+                output: Bytes::new(),
+                halt_debug: None,
+            })),
+            Some(err @ PrecompileErrors::Fatal { .. }) => Err(err.into()),
+        }
+    }
+
+    fn warm_addresses(&self) -> Box<impl Iterator<Item = Address> + '_> {
+        self.precompiles.warm_addresses()
+    }
+
+    fn contains(&self, address: &Address) -> bool {
+        self.precompiles.contains(address)
+    }
+}
+
+/// Executes the transaction of the context with the mainnet handler and [`FailingPrecompiles`].
+pub fn exec_with_failures<CTX, DB>(
+    ctx: &mut CTX,
+) -> Result<ResultAndState<HaltReason>, EVMError<FailingDbError<DB::Error>, InvalidTransaction>>
+where
+    CTX: EthContext + DatabaseGetter<Database = FailingDb<DB>>,
+    DB: Database,
+{
+    let mut handler: MainnetHandler<
+        CTX,
+        _,
+        EthFrame<CTX, _, _, _>,
+        FailingPrecompiles<CTX, _>,
+        EthInstructionExecutor<EthInterpreter, CTX>,
+    > = MainnetHandler {
+        _phantom: PhantomData,
+    };
+    handler.run(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bytecode::opcode,
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::result::ExecutionResult,
+        precompile::PrecompileError,
+        primitives::{address, TxKind},
+        test_utils::{TEST_CALLER, TEST_CALLER_BALANCE},
+        Context, DatabaseRef,
+    };
+    use database::InMemoryDB;
+    use specification::hardfork::SpecId;
+
+    type FailingContext = Context<BlockEnv, TxEnv, CfgEnv, FailingDb<InMemoryDB>>;
+
+    const CONTRACT: Address = address!("0000000000000000000000000000000000001000");
+    const IDENTITY: Address = address!("0000000000000000000000000000000000000004");
+
+    /// Context calling a contract with `code` from a funded caller.
+    fn context(code: &[u8]) -> FailingContext {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(TEST_CALLER, AccountInfo::from_balance(TEST_CALLER_BALANCE));
+        let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        let mut ctx = FailingContext::new(FailingDb::new(db), SpecId::LATEST);
+        ctx.block.gas_limit = u64::MAX;
+        ctx.tx = TxEnv {
+            caller: TEST_CALLER,
+            gas_limit: 1_000_000,
+            kind: TxKind::Call(CONTRACT),
+            ..Default::default()
+        };
+        ctx
+    }
+
+    fn commit(ctx: &mut FailingContext) -> ExecutionResult<HaltReason> {
+        let result = exec_with_failures(ctx).unwrap();
+        ctx.db().commit(result.state);
+        ctx.tx.nonce += 1;
+        result.result
+    }
+
+    #[test]
+    fn failed_storage_read_rolls_back() {
+        // Stores 1 at slot 0, then loads slots 1, 2 and 3.
+        let mut ctx = context(&[
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0x01,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x02,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x03,
+            opcode::SLOAD,
+            opcode::STOP,
+        ]);
+        ctx.db().fail_read(DbOperation::Storage, 3);
+
+        let error = exec_with_failures(&mut ctx).unwrap_err();
+        assert_eq!(
+            error,
+            EVMError::Database(FailingDbError::Injected {
+                operation: DbOperation::Storage,
+                call: 3
+            })
+        );
+        assert!(ctx.db().is_schedule_done());
+        assert_eq!(ctx.db().reads(DbOperation::Storage), 3);
+        assert_eq!(
+            ctx.db().db.basic_ref(TEST_CALLER).unwrap().unwrap().nonce,
+            0
+        );
+
+        // The journal is cleared, the retry executes from the committed state.
+        assert!(commit(&mut ctx).is_success());
+        assert_eq!(
+            ctx.db().db.storage_ref(CONTRACT, U256::ZERO).unwrap(),
+            U256::from(1)
+        );
+    }
+
+    #[test]
+    fn precompile_fails_once() {
+        // Stores the success of a call to the identity precompile at slot 0.
+        let mut ctx = context(&[
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x04,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]);
+        ctx.db()
+            .fail_precompile_call(
+                IDENTITY,
+                1,
+                PrecompileErrors::Error(PrecompileError::Other("injected".into())),
+            )
+            .fail_precompile_call(
+                IDENTITY,
+                3,
+                PrecompileErrors::Fatal {
+                    msg: "injected".into(),
+                },
+            );
+
+        assert!(commit(&mut ctx).is_success());
+        assert_eq!(
+            ctx.db().db.storage_ref(CONTRACT, U256::ZERO).unwrap(),
+            U256::ZERO
+        );
+        assert!(commit(&mut ctx).is_success());
+        assert_eq!(
+            ctx.db().db.storage_ref(CONTRACT, U256::ZERO).unwrap(),
+            U256::from(1)
+        );
+
+        let error = exec_with_failures(&mut ctx).unwrap_err();
+        assert_eq!(error, EVMError::Precompile("injected".into()));
+        assert_eq!(ctx.db().precompile_calls(IDENTITY), 3);
+        assert!(ctx.db().is_schedule_done());
+    }
+}
@@ -28,7 +28,7 @@ pub enum SpecId {
     MERGE,            // Paris/Merge            15537394 (TTD: 58750000000000000000000)
     SHANGHAI,         // Shanghai               17034870 (Timestamp: 1681338455)
     CANCUN,           // Cancun                 19426587 (Timestamp: 1710338135)
-    PRAGUE,           // Prague                 TBD
+    PRAGUE,           // Prague                 22431084 (Timestamp: 1746612311)
     OSAKA,            // Osaka                  TBD
     #[default]
     LATEST = u8::MAX,
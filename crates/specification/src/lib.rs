@@ -11,3 +11,4 @@ pub mod eip2;
 pub mod eip4844;
 pub mod eip7702;
 pub mod hardfork;
+pub mod schedule;
@@ -0,0 +1,114 @@
+//! Hardfork schedules of well-known chains.
+use crate::hardfork::SpecId;
+
+/// Chain id of Ethereum mainnet.
+pub const MAINNET_CHAIN_ID: u64 = 1;
+/// Chain id of the Sepolia testnet.
+pub const SEPOLIA_CHAIN_ID: u64 = 11155111;
+
+/// Condition activating a hardfork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForkCondition {
+    /// Activated at the block with this number.
+    Block(u64),
+    /// Activated at the first block with at least this timestamp.
+    Timestamp(u64),
+}
+
+impl ForkCondition {
+    /// Returns `true` if the hardfork is active in the block with the given number and timestamp.
+    pub const fn is_active_at(&self, number: u64, timestamp: u64) -> bool {
+        match *self {
+            Self::Block(block) => number >= block,
+            Self::Timestamp(time) => timestamp >= time,
+        }
+    }
+}
+
+/// Hardforks of a chain, in activation order, with the condition activating each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HardforkSchedule<SPEC: 'static> {
+    forks: &'static [(SPEC, ForkCondition)],
+}
+
+impl<SPEC: Copy> HardforkSchedule<SPEC> {
+    /// Creates a schedule of hardforks, the first one must be active at genesis.
+    pub const fn new(forks: &'static [(SPEC, ForkCondition)]) -> Self {
+        assert!(!forks.is_empty(), "schedule has no hardforks");
+        Self { forks }
+    }
+
+    /// Returns the hardforks with the condition activating each.
+    pub const fn forks(&self) -> &'static [(SPEC, ForkCondition)] {
+        self.forks
+    }
+
+    /// Returns the spec of the block with the given number and timestamp.
+    pub fn spec_at(&self, number: u64, timestamp: u64) -> SPEC {
+        self.forks
+            .iter()
+            .rev()
+            .find(|(_, condition)| condition.is_active_at(number, timestamp))
+            .unwrap_or(&self.forks[0])
+            .0
+    }
+
+    /// Returns the spec of the last scheduled hardfork.
+    pub fn latest(&self) -> SPEC {
+        self.forks[self.forks.len() - 1].0
+    }
+}
+
+/// Hardforks of Ethereum mainnet.
+///
+/// Constantinople is replaced by Petersburg, and the merge is activated at the first
+/// proof-of-stake block.
+pub const MAINNET: HardforkSchedule<SpecId> = HardforkSchedule::new(&[
+    (SpecId::FRONTIER, ForkCondition::Block(0)),
+    (SpecId::HOMESTEAD, ForkCondition::Block(1_150_000)),
+    (SpecId::DAO_FORK, ForkCondition::Block(1_920_000)),
+    (SpecId::TANGERINE, ForkCondition::Block(2_463_000)),
+    (SpecId::SPURIOUS_DRAGON, ForkCondition::Block(2_675_000)),
+    (SpecId::BYZANTIUM, ForkCondition::Block(4_370_000)),
+    (SpecId::PETERSBURG, ForkCondition::Block(7_280_000)),
+    (SpecId::ISTANBUL, ForkCondition::Block(9_069_000)),
+    (SpecId::MUIR_GLACIER, ForkCondition::Block(9_200_000)),
+    (SpecId::BERLIN, ForkCondition::Block(12_244_000)),
+    (SpecId::LONDON, ForkCondition::Block(12_965_000)),
+    (SpecId::ARROW_GLACIER, ForkCondition::Block(13_773_000)),
+    (SpecId::GRAY_GLACIER, ForkCondition::Block(15_050_000)),
+    (SpecId::MERGE, ForkCondition::Block(15_537_394)),
+    (SpecId::SHANGHAI, ForkCondition::Timestamp(1_681_338_455)),
+    (SpecId::CANCUN, ForkCondition::Timestamp(1_710_338_135)),
+    (SpecId::PRAGUE, ForkCondition::Timestamp(1_746_612_311)),
+]);
+
+/// Hardforks of the Sepolia testnet, which started with London.
+pub const SEPOLIA: HardforkSchedule<SpecId> = HardforkSchedule::new(&[
+    (SpecId::LONDON, ForkCondition::Block(0)),
+    (SpecId::MERGE, ForkCondition::Block(1_450_409)),
+    (SpecId::SHANGHAI, ForkCondition::Timestamp(1_677_557_088)),
+    (SpecId::CANCUN, ForkCondition::Timestamp(1_706_655_072)),
+    (SpecId::PRAGUE, ForkCondition::Timestamp(1_741_159_776)),
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mainnet_spec_at() {
+        assert_eq!(MAINNET.spec_at(0, 0), SpecId::FRONTIER);
+        assert_eq!(MAINNET.spec_at(7_280_000, 0), SpecId::PETERSBURG);
+        assert_eq!(
+            MAINNET.spec_at(15_537_393, 1_663_224_162),
+            SpecId::GRAY_GLACIER
+        );
+        assert_eq!(MAINNET.spec_at(15_537_394, 1_663_224_179), SpecId::MERGE);
+        assert_eq!(MAINNET.spec_at(17_034_870, 1_681_338_455), SpecId::SHANGHAI);
+        assert_eq!(MAINNET.spec_at(19_426_587, 1_710_338_135), SpecId::CANCUN);
+        assert_eq!(MAINNET.latest(), SpecId::PRAGUE);
+        assert_eq!(SEPOLIA.spec_at(1, 1), SpecId::LONDON);
+    }
+}
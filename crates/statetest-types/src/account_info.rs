@@ -1,15 +1,25 @@
 use revm::primitives::{Bytes, HashMap, U256};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::deserializer::deserialize_str_as_u64;
+use crate::deserializer::{deserialize_str_as_u64, serialize_u64_as_str};
 
 /// Account information
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AccountInfo {
     pub balance: U256,
+    #[serde(default)]
     pub code: Bytes,
-    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    #[serde(
+        deserialize_with = "deserialize_str_as_u64",
+        serialize_with = "serialize_u64_as_str"
+    )]
     pub nonce: u64,
+    #[serde(default)]
     pub storage: HashMap<U256, U256>,
+    /// Constructor of a pre state account, see [`TestUnit::deploy_pre_state`].
+    ///
+    /// [`TestUnit::deploy_pre_state`]: crate::TestUnit::deploy_pre_state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_code: Option<Bytes>,
 }
@@ -0,0 +1,676 @@
+use database::{states::plain_account::PlainAccount, CacheState, State};
+use revm::{
+    bytecode::Bytecode,
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::block::calc_excess_blob_gas,
+    database_interface::{Database, DatabaseCommit, EmptyDB},
+    primitives::{address, keccak256, Address, Bytes, TxKind, B256, U256},
+    specification::{
+        eip4844::{
+            GAS_PER_BLOB, MAX_BLOB_GAS_PER_BLOCK_CANCUN, MAX_BLOB_GAS_PER_BLOCK_PRAGUE,
+            TARGET_BLOB_GAS_PER_BLOCK_CANCUN, TARGET_BLOB_GAS_PER_BLOCK_PRAGUE,
+        },
+        hardfork::SpecId,
+    },
+    Context, ExecuteCommitEvm, ExecuteEvm,
+};
+use std::{collections::HashMap, fmt};
+
+use crate::{AccountInfo, BlockBody, BlockHeader, BlockTransaction, BlockchainTest, SpecName};
+
+/// Caller of the system contracts, `0xff..fe`.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// EIP-4788 beacon roots contract, called with the parent beacon block root before the
+/// transactions of a block.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// EIP-2935 history storage contract, called with the parent hash before the transactions of a
+/// block.
+pub const HISTORY_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+
+/// EIP-7002 withdrawal requests contract, called after the transactions of a block.
+pub const WITHDRAWAL_REQUEST_ADDRESS: Address =
+    address!("00000961Ef480Eb55e80D19ad83579A64c007002");
+
+/// EIP-7251 consolidation requests contract, called after the transactions of a block.
+pub const CONSOLIDATION_REQUEST_ADDRESS: Address =
+    address!("0000BBdDc7CE488642fb579F8B00f3a590007251");
+
+/// Gas limit of system contract calls.
+const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// EIP-1559 base fee of the first London block.
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Reason a [`BlockchainTest`] failed.
+///
+/// `block` is the index of the block in [`BlockchainTest::blocks`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockchainTestError {
+    /// The network is not supported by the runner.
+    UnsupportedNetwork(SpecName),
+    /// A valid block does not provide its decoded header and body.
+    MissingBlock { block: usize },
+    /// The header is inconsistent with its parent or its body.
+    InvalidHeader { block: usize, reason: String },
+    /// A transaction of a valid block was rejected.
+    InvalidTransaction {
+        block: usize,
+        tx: usize,
+        error: String,
+    },
+    /// A block expected to be invalid was imported.
+    UnexpectedValidBlock { block: usize, expected: String },
+    /// The head of the chain differs from `lastblockhash`.
+    LastBlockHashMismatch { got: B256, expected: B256 },
+    /// An account of the post state differs from the expected one.
+    PostStateMismatch { address: Address, reason: String },
+}
+
+impl fmt::Display for BlockchainTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedNetwork(network) => write!(f, "unsupported network {network:?}"),
+            Self::MissingBlock { block } => write!(f, "block {block}: decoded block is missing"),
+            Self::InvalidHeader { block, reason } => {
+                write!(f, "block {block}: invalid header: {reason}")
+            }
+            Self::InvalidTransaction { block, tx, error } => {
+                write!(f, "block {block}: transaction {tx} is invalid: {error}")
+            }
+            Self::UnexpectedValidBlock { block, expected } => {
+                write!(f, "block {block}: imported, expected exception {expected}")
+            }
+            Self::LastBlockHashMismatch { got, expected } => {
+                write!(
+                    f,
+                    "last block hash mismatch: got {got}, expected {expected}"
+                )
+            }
+            Self::PostStateMismatch { address, reason } => {
+                write!(f, "post state mismatch of {address}: {reason}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BlockchainTestError {}
+
+/// Executes a blockchain test, importing its blocks on top of the pre state.
+///
+/// Valid blocks must execute and match their header, invalid blocks must be rejected and leave
+/// the state untouched. Invalid blocks that can't be decoded are skipped. The resulting state is
+/// compared with the post state of the fixture if it provides one.
+///
+/// Headers are checked against their parent and their body, but trie roots, the bloom and
+/// `requestsHash` are not computed, so blocks only invalid because of those are reported as
+/// [`BlockchainTestError::UnexpectedValidBlock`].
+pub fn execute_blockchain_test(test: &BlockchainTest) -> Result<(), BlockchainTestError> {
+    if !test.network.is_supported()
+        || test.network.is_optimism()
+        || test.network == SpecName::HomesteadToDaoAt5
+    {
+        return Err(BlockchainTestError::UnsupportedNetwork(test.network));
+    }
+
+    let mut cache_state = CacheState::new(false);
+    for (address, info) in &test.pre {
+        let bytecode = Bytecode::new_raw_checked(info.code.clone())
+            .unwrap_or(Bytecode::new_legacy(info.code.clone()));
+        let acc_info = revm::state::AccountInfo {
+            balance: info.balance,
+            code_hash: keccak256(&info.code),
+            code: Some(bytecode),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(*address, acc_info, info.storage.clone());
+    }
+    let mut state = State::builder().with_cached_prestate(cache_state).build();
+
+    let mut parent = &test.genesis_block_header;
+    state
+        .block_hashes
+        .insert(parent.number.saturating_to(), parent.hash);
+
+    for (index, block) in test.blocks.iter().enumerate() {
+        let Some(body) = block.decoded() else {
+            if block.is_invalid() {
+                continue;
+            }
+            return Err(BlockchainTestError::MissingBlock { block: index });
+        };
+        let header = body
+            .block_header
+            .as_ref()
+            .expect("decoded block has a header");
+
+        // Rejected blocks must not change the state.
+        let snapshot = block
+            .is_invalid()
+            .then(|| (state.cache.clone(), state.block_hashes.clone()));
+        let result = execute_block(&mut state, test.network, index, parent, header, body);
+        match (result, &block.expect_exception) {
+            (Ok(()), None) => parent = header,
+            (Err(error), None) => return Err(error),
+            (Ok(()), Some(expected)) => {
+                return Err(BlockchainTestError::UnexpectedValidBlock {
+                    block: index,
+                    expected: expected.clone(),
+                })
+            }
+            (Err(_), Some(_)) => {
+                (state.cache, state.block_hashes) = snapshot.expect("snapshot of invalid block");
+            }
+        }
+    }
+
+    if parent.hash != test.last_block_hash {
+        return Err(BlockchainTestError::LastBlockHashMismatch {
+            got: parent.hash,
+            expected: test.last_block_hash,
+        });
+    }
+
+    if let Some(post_state) = &test.post_state {
+        check_post_state(&state, post_state)?;
+    }
+    Ok(())
+}
+
+/// Executes a block and applies its rewards, withdrawals and system calls.
+fn execute_block(
+    state: &mut State<EmptyDB>,
+    network: SpecName,
+    index: usize,
+    parent: &BlockHeader,
+    header: &BlockHeader,
+    body: &BlockBody,
+) -> Result<(), BlockchainTestError> {
+    let number: u64 = header.number.saturating_to();
+    let timestamp: u64 = header.timestamp.saturating_to();
+    let spec = network.to_spec_id_at(number, timestamp);
+    validate_header(spec, parent, header, body).map_err(|reason| {
+        BlockchainTestError::InvalidHeader {
+            block: index,
+            reason,
+        }
+    })?;
+
+    let mut cfg = CfgEnv::default();
+    cfg.chain_id = 1;
+    cfg.spec = spec;
+    state
+        .cache
+        .set_state_clear_flag(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+    let mut block = BlockEnv {
+        number,
+        beneficiary: header.coinbase,
+        timestamp,
+        gas_limit: header.gas_limit.saturating_to(),
+        basefee: header.base_fee_per_gas.unwrap_or_default().saturating_to(),
+        difficulty: header.difficulty,
+        prevrandao: spec.is_enabled_in(SpecId::MERGE).then_some(header.mix_hash),
+        blob_excess_gas_and_price: None,
+        parent_beacon_block_root: header.parent_beacon_block_root,
+    };
+    if let Some(excess_blob_gas) = header.excess_blob_gas {
+        block.set_blob_excess_gas_and_price(
+            excess_blob_gas.saturating_to(),
+            spec.is_enabled_in(SpecId::PRAGUE),
+        );
+    }
+
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        if let Some(root) = block.parent_beacon_block_root {
+            system_call(state, &cfg, &block, BEACON_ROOTS_ADDRESS, root.into());
+        }
+    }
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        system_call(
+            state,
+            &cfg,
+            &block,
+            HISTORY_STORAGE_ADDRESS,
+            header.parent_hash.into(),
+        );
+    }
+
+    let mut gas_used = 0u64;
+    for (tx_index, tx) in body.transactions.iter().enumerate() {
+        let invalid = |error: String| BlockchainTestError::InvalidTransaction {
+            block: index,
+            tx: tx_index,
+            error,
+        };
+        let tx = tx_env(tx);
+        if tx.gas_limit > block.gas_limit - gas_used {
+            return Err(invalid("gas allowance exceeded".into()));
+        }
+        let mut ctx = Context::builder()
+            .with_block(&block)
+            .with_tx(&tx)
+            .with_cfg(&cfg)
+            .with_db(&mut *state);
+        let result = ctx
+            .exec_commit_previous()
+            .map_err(|error| invalid(error.to_string()))?;
+        gas_used += result.gas_used();
+    }
+    if U256::from(gas_used) != header.gas_used {
+        return Err(BlockchainTestError::InvalidHeader {
+            block: index,
+            reason: format!("gas used {gas_used}, header has {}", header.gas_used),
+        });
+    }
+
+    if !spec.is_enabled_in(SpecId::MERGE) {
+        apply_block_rewards(state, spec, number, header, body);
+    }
+    if spec.is_enabled_in(SpecId::SHANGHAI) {
+        let withdrawals = body.withdrawals.iter().flatten().map(|withdrawal| {
+            let amount: u128 = withdrawal.amount.saturating_to();
+            (withdrawal.address, amount * 1_000_000_000)
+        });
+        state
+            .increment_balances(withdrawals)
+            .expect("EmptyDB is infallible");
+    }
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        system_call(
+            state,
+            &cfg,
+            &block,
+            WITHDRAWAL_REQUEST_ADDRESS,
+            Bytes::new(),
+        );
+        system_call(
+            state,
+            &cfg,
+            &block,
+            CONSOLIDATION_REQUEST_ADDRESS,
+            Bytes::new(),
+        );
+    }
+
+    state.block_hashes.insert(number, header.hash);
+    Ok(())
+}
+
+/// Checks the header against its parent and the fields derived from the body.
+fn validate_header(
+    spec: SpecId,
+    parent: &BlockHeader,
+    header: &BlockHeader,
+    body: &BlockBody,
+) -> Result<(), String> {
+    if header.number != parent.number + U256::from(1) {
+        return Err(format!("number {} after {}", header.number, parent.number));
+    }
+    if header.timestamp <= parent.timestamp {
+        return Err("timestamp not greater than parent".into());
+    }
+    if header.gas_used > header.gas_limit {
+        return Err("gas used above gas limit".into());
+    }
+
+    let gas_limit: u64 = header.gas_limit.saturating_to();
+    let mut parent_gas_limit: u64 = parent.gas_limit.saturating_to();
+    let london_fork_block = spec.is_enabled_in(SpecId::LONDON) && parent.base_fee_per_gas.is_none();
+    if london_fork_block {
+        // EIP-1559 doubles the gas limit to keep the gas target.
+        parent_gas_limit *= 2;
+    }
+    if gas_limit.abs_diff(parent_gas_limit) >= parent_gas_limit / 1024 || gas_limit < 5000 {
+        return Err(format!("gas limit {gas_limit} after {parent_gas_limit}"));
+    }
+
+    if spec.is_enabled_in(SpecId::LONDON) {
+        let expected = match parent.base_fee_per_gas {
+            Some(parent_base_fee) if !london_fork_block => next_base_fee(
+                parent.gas_used.saturating_to(),
+                parent_gas_limit,
+                parent_base_fee.saturating_to(),
+            ),
+            _ => INITIAL_BASE_FEE,
+        };
+        if header.base_fee_per_gas != Some(U256::from(expected)) {
+            return Err(format!(
+                "base fee {:?}, expected {expected}",
+                header.base_fee_per_gas
+            ));
+        }
+    }
+
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        let (target, max) = if spec.is_enabled_in(SpecId::PRAGUE) {
+            (
+                TARGET_BLOB_GAS_PER_BLOCK_PRAGUE,
+                MAX_BLOB_GAS_PER_BLOCK_PRAGUE,
+            )
+        } else {
+            (
+                TARGET_BLOB_GAS_PER_BLOCK_CANCUN,
+                MAX_BLOB_GAS_PER_BLOCK_CANCUN,
+            )
+        };
+        let expected_excess = match (parent.excess_blob_gas, parent.blob_gas_used) {
+            (Some(excess), Some(used)) => {
+                calc_excess_blob_gas(excess.saturating_to(), used.saturating_to(), target)
+            }
+            _ => 0,
+        };
+        if header.excess_blob_gas != Some(U256::from(expected_excess)) {
+            return Err(format!(
+                "excess blob gas {:?}, expected {expected_excess}",
+                header.excess_blob_gas
+            ));
+        }
+
+        let blob_gas_used = body
+            .transactions
+            .iter()
+            .map(|tx| tx.blob_versioned_hashes.len() as u64 * GAS_PER_BLOB)
+            .sum::<u64>();
+        if blob_gas_used > max {
+            return Err(format!("blob gas used {blob_gas_used} above {max}"));
+        }
+        if header.blob_gas_used != Some(U256::from(blob_gas_used)) {
+            return Err(format!(
+                "blob gas used {:?}, expected {blob_gas_used}",
+                header.blob_gas_used
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Calculates the EIP-1559 base fee of the child of the given parent.
+fn next_base_fee(parent_gas_used: u64, parent_gas_limit: u64, parent_base_fee: u64) -> u64 {
+    let target = (parent_gas_limit / 2).max(1) as u128;
+    let gas_used = parent_gas_used as u128;
+    let base_fee = parent_base_fee as u128;
+    let next = if gas_used > target {
+        base_fee + (base_fee * (gas_used - target) / target / 8).max(1)
+    } else {
+        base_fee - base_fee * (target - gas_used) / target / 8
+    };
+    next.try_into().unwrap_or(u64::MAX)
+}
+
+/// Rewards the miner of a proof of work block and the miners of its ommers.
+fn apply_block_rewards(
+    state: &mut State<EmptyDB>,
+    spec: SpecId,
+    number: u64,
+    header: &BlockHeader,
+    body: &BlockBody,
+) {
+    let reward: u128 = if spec.is_enabled_in(SpecId::PETERSBURG) {
+        2_000_000_000_000_000_000
+    } else if spec.is_enabled_in(SpecId::BYZANTIUM) {
+        3_000_000_000_000_000_000
+    } else {
+        5_000_000_000_000_000_000
+    };
+    let ommers = body.uncle_headers.iter().map(|ommer| {
+        let ommer_number: u64 = ommer.number.saturating_to();
+        let distance = number.saturating_sub(ommer_number) as u128;
+        (
+            ommer.coinbase,
+            (8u128.saturating_sub(distance)) * reward / 8,
+        )
+    });
+    let miner_reward = reward + reward / 32 * body.uncle_headers.len() as u128;
+    state
+        .increment_balances(core::iter::once((header.coinbase, miner_reward)).chain(ommers))
+        .expect("EmptyDB is infallible");
+}
+
+/// Calls a system contract from [`SYSTEM_ADDRESS`], keeping only the changes of the contract.
+///
+/// Nothing happens if the contract is not deployed or the call fails.
+fn system_call(
+    state: &mut State<EmptyDB>,
+    cfg: &CfgEnv,
+    block: &BlockEnv,
+    contract: Address,
+    data: Bytes,
+) {
+    let deployed = state
+        .basic(contract)
+        .ok()
+        .flatten()
+        .is_some_and(|info| !info.is_empty_code_hash());
+    if !deployed {
+        return;
+    }
+
+    let mut cfg = cfg.clone();
+    cfg.disable_nonce_check = true;
+    // System calls don't pay for gas and are not bound by the block gas limit.
+    let block = BlockEnv {
+        basefee: 0,
+        gas_limit: u64::MAX,
+        ..block.clone()
+    };
+    let tx = TxEnv {
+        caller: SYSTEM_ADDRESS,
+        kind: TxKind::Call(contract),
+        data,
+        gas_limit: SYSTEM_CALL_GAS_LIMIT,
+        ..TxEnv::default()
+    };
+    let mut ctx = Context::builder()
+        .with_block(&block)
+        .with_tx(&tx)
+        .with_cfg(&cfg)
+        .with_db(&mut *state);
+    if let Ok(mut output) = ctx.exec_previous() {
+        output.state.retain(|address, _| *address == contract);
+        state.commit(output.state);
+    }
+}
+
+/// Builds the environment of a block transaction.
+fn tx_env(tx: &BlockTransaction) -> TxEnv {
+    // Legacy transactions signed without EIP-155 replay protection.
+    let unprotected = tx.tx_type.is_zero() && (tx.v == U256::from(27) || tx.v == U256::from(28));
+    TxEnv {
+        tx_type: tx.tx_type.saturating_to(),
+        caller: tx.sender,
+        gas_limit: tx.gas_limit.saturating_to(),
+        gas_price: tx
+            .gas_price
+            .or(tx.max_fee_per_gas)
+            .unwrap_or_default()
+            .saturating_to(),
+        kind: match tx.to {
+            Some(address) => TxKind::Call(address),
+            None => TxKind::Create,
+        },
+        value: tx.value,
+        data: tx.data.clone(),
+        nonce: tx.nonce.saturating_to(),
+        chain_id: tx
+            .chain_id
+            .filter(|_| !unprotected)
+            .map(|chain_id| chain_id.saturating_to()),
+        access_list: tx
+            .access_list
+            .iter()
+            .flatten()
+            .map(|item| (item.address, item.storage_keys.clone()))
+            .collect(),
+        gas_priority_fee: tx.max_priority_fee_per_gas.map(|fee| fee.saturating_to()),
+        blob_hashes: tx.blob_versioned_hashes.clone(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas.unwrap_or_default().saturating_to(),
+        authorization_list: tx
+            .authorization_list
+            .iter()
+            .flatten()
+            .cloned()
+            .map(Into::into)
+            .collect(),
+    }
+}
+
+/// Compares the existing accounts of the state with the expected post state.
+fn check_post_state(
+    state: &State<EmptyDB>,
+    expected: &HashMap<Address, AccountInfo>,
+) -> Result<(), BlockchainTestError> {
+    let accounts: HashMap<Address, &PlainAccount> =
+        state.cache.trie_account().into_iter().collect();
+    let mismatch = |address: Address, reason: String| {
+        Err(BlockchainTestError::PostStateMismatch { address, reason })
+    };
+
+    for (address, expected) in expected {
+        let Some(account) = accounts.get(address) else {
+            return mismatch(*address, "account is missing".into());
+        };
+        if account.info.balance != expected.balance {
+            return mismatch(
+                *address,
+                format!(
+                    "balance {}, expected {}",
+                    account.info.balance, expected.balance
+                ),
+            );
+        }
+        if account.info.nonce != expected.nonce {
+            return mismatch(
+                *address,
+                format!("nonce {}, expected {}", account.info.nonce, expected.nonce),
+            );
+        }
+        if account.info.code_hash != keccak256(&expected.code) {
+            return mismatch(*address, "code differs".into());
+        }
+        // Zero slots don't exist in the trie.
+        let slots = expected.storage.keys().chain(account.storage.keys());
+        for key in slots {
+            let got = account.storage.get(key).copied().unwrap_or_default();
+            let value = expected.storage.get(key).copied().unwrap_or_default();
+            if got != value {
+                return mismatch(*address, format!("slot {key} is {got}, expected {value}"));
+            }
+        }
+    }
+
+    if let Some(address) = accounts
+        .keys()
+        .find(|address| !expected.contains_key(*address))
+    {
+        return mismatch(*address, "account is not expected to exist".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn header(number: u64, gas_used: u64, hash: u8) -> Value {
+        json!({
+            "parentHash": B256::ZERO,
+            "uncleHash": B256::ZERO,
+            "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "stateRoot": B256::ZERO,
+            "transactionsTrie": B256::ZERO,
+            "receiptTrie": B256::ZERO,
+            "bloom": format!("0x{}", "00".repeat(256)),
+            "difficulty": "0x00",
+            "number": U256::from(number),
+            "gasLimit": "0x989680",
+            "gasUsed": U256::from(gas_used),
+            "timestamp": U256::from(number * 12),
+            "extraData": "0x",
+            "mixHash": B256::ZERO,
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x07",
+            "withdrawalsRoot": B256::ZERO,
+            "hash": B256::with_last_byte(hash),
+        })
+    }
+
+    fn transfer(nonce: u64) -> Value {
+        json!({
+            "type": "0x00",
+            "chainId": "0x01",
+            "nonce": U256::from(nonce),
+            "gasPrice": "0x0a",
+            "gasLimit": "0x5208",
+            "to": "0x0000000000000000000000000000000000001000",
+            "value": "0x01",
+            "data": "0x",
+            "v": "0x25",
+            "r": "0x01",
+            "s": "0x01",
+            "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+        })
+    }
+
+    fn account(balance: u64, nonce: u64) -> Value {
+        json!({ "balance": U256::from(balance), "code": "0x", "nonce": U256::from(nonce), "storage": {} })
+    }
+
+    #[test]
+    fn imports_valid_and_rejects_invalid_blocks() {
+        let withdrawal = json!({
+            "index": "0x00",
+            "validatorIndex": "0x00",
+            "address": "0x0000000000000000000000000000000000002000",
+            "amount": "0x02"
+        });
+        let test = json!({
+            "network": "Shanghai",
+            "genesisBlockHeader": header(0, 0, 1),
+            "blocks": [
+                {
+                    "blockHeader": header(1, 21_000, 2),
+                    "transactions": [transfer(0)],
+                    "uncleHeaders": [],
+                    "withdrawals": [withdrawal],
+                    "rlp": "0x"
+                },
+                {
+                    // Nonce was already used.
+                    "expectException": "TransactionException.NONCE_MISMATCH_TOO_LOW",
+                    "rlp": "0x",
+                    "rlp_decoded": {
+                        "blockHeader": header(2, 21_000, 3),
+                        "transactions": [transfer(0)],
+                        "uncleHeaders": [],
+                        "withdrawals": []
+                    }
+                }
+            ],
+            "lastblockhash": B256::with_last_byte(2),
+            "pre": {
+                "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": account(1_000_000, 0)
+            },
+            "postState": {
+                "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": account(1_000_000 - 210_000 - 1, 1),
+                "0x0000000000000000000000000000000000001000": account(1, 0),
+                // Priority fee of 3 per gas.
+                "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba": account(63_000, 0),
+                "0x0000000000000000000000000000000000002000": account(2_000_000_000, 0)
+            }
+        });
+        let mut test: BlockchainTest = serde_json::from_value(test).unwrap();
+        assert_eq!(execute_blockchain_test(&test), Ok(()));
+
+        // Without its transaction the invalid block becomes valid.
+        let body = test.blocks[1].rlp_decoded.as_mut().unwrap();
+        body.transactions.clear();
+        body.block_header.as_mut().unwrap().gas_used = U256::ZERO;
+        assert!(matches!(
+            execute_blockchain_test(&test),
+            Err(BlockchainTestError::UnexpectedValidBlock { block: 1, .. })
+        ));
+    }
+}
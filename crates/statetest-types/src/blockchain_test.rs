@@ -0,0 +1,221 @@
+use revm::primitives::{
+    alloy_primitives::{Bloom, B64},
+    Address, Bytes, B256, U256,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    deserializer::deserialize_maybe_empty, AccessListItem, AccountInfo, SpecName, TestAuthorization,
+};
+
+/// The top level blockchain test suite struct
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTestSuite(pub BTreeMap<String, BlockchainTest>);
+
+/// Blockchain test, a chain of blocks executed on top of a genesis state.
+///
+/// Format of the `blockchain_tests` fixtures of execution-spec-tests.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTest {
+    /// Test info is optional.
+    #[serde(default, rename = "_info")]
+    pub info: Option<serde_json::Value>,
+
+    /// Fork, or fork transition, the chain runs on.
+    pub network: SpecName,
+    pub genesis_block_header: BlockHeader,
+    #[serde(rename = "genesisRLP", default)]
+    pub genesis_rlp: Option<Bytes>,
+    pub blocks: Vec<Block>,
+    /// Hash of the head of the chain after all valid blocks are imported.
+    #[serde(rename = "lastblockhash")]
+    pub last_block_hash: B256,
+    pub pre: HashMap<Address, AccountInfo>,
+    /// Post state, absent in fixtures with a large state that only set `post_state_hash`.
+    #[serde(default)]
+    pub post_state: Option<HashMap<Address, AccountInfo>>,
+    #[serde(default)]
+    pub post_state_hash: Option<B256>,
+    #[serde(default)]
+    pub seal_engine: Option<String>,
+}
+
+/// Block header.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub parent_hash: B256,
+    pub uncle_hash: B256,
+    pub coinbase: Address,
+    pub state_root: B256,
+    pub transactions_trie: B256,
+    pub receipt_trie: B256,
+    pub bloom: Bloom,
+    pub difficulty: U256,
+    pub number: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub mix_hash: B256,
+    pub nonce: B64,
+    pub base_fee_per_gas: Option<U256>,
+    pub withdrawals_root: Option<B256>,
+    pub blob_gas_used: Option<U256>,
+    pub excess_blob_gas: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+    pub requests_hash: Option<B256>,
+    pub hash: B256,
+}
+
+/// Block of a [`BlockchainTest`].
+///
+/// Invalid blocks only carry their RLP, the expected exception and, if it could be decoded,
+/// the decoded block in `rlp_decoded`.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    pub rlp: Bytes,
+    /// Reason the block is invalid, the block is valid if absent.
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+    #[serde(flatten)]
+    pub body: BlockBody,
+    #[serde(default, rename = "rlp_decoded")]
+    pub rlp_decoded: Option<BlockBody>,
+}
+
+impl Block {
+    /// Returns the decoded block, [`None`] if the fixture does not provide it.
+    pub fn decoded(&self) -> Option<&BlockBody> {
+        self.rlp_decoded
+            .as_ref()
+            .or(Some(&self.body))
+            .filter(|body| body.block_header.is_some())
+    }
+
+    /// Returns `true` if the block is expected to be rejected.
+    pub fn is_invalid(&self) -> bool {
+        self.expect_exception.is_some()
+    }
+}
+
+/// Decoded header and body of a [`Block`].
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockBody {
+    #[serde(default)]
+    pub block_header: Option<BlockHeader>,
+    #[serde(default)]
+    pub transactions: Vec<BlockTransaction>,
+    #[serde(default)]
+    pub uncle_headers: Vec<BlockHeader>,
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Signed transaction of a [`Block`], with its recovered sender.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransaction {
+    #[serde(rename = "type", default)]
+    pub tx_type: U256,
+    #[serde(default)]
+    pub chain_id: Option<U256>,
+    pub nonce: U256,
+    pub gas_price: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub gas_limit: U256,
+    #[serde(default, deserialize_with = "deserialize_maybe_empty")]
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    #[serde(default)]
+    pub access_list: Option<Vec<AccessListItem>>,
+    #[serde(default)]
+    pub authorization_list: Option<Vec<TestAuthorization>>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    pub sender: Address,
+    pub v: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// EIP-4895 withdrawal.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: U256,
+    pub validator_index: U256,
+    pub address: Address,
+    /// Amount in gwei.
+    pub amount: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_blocks() {
+        let blocks = r#"[
+            {
+                "blockHeader": {
+                    "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "uncleHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "transactionsTrie": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "receiptTrie": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                    "difficulty": "0x00",
+                    "number": "0x01",
+                    "gasLimit": "0x016345785d8a0000",
+                    "gasUsed": "0xa861",
+                    "timestamp": "0x0c",
+                    "extraData": "0x",
+                    "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "nonce": "0x0000000000000000",
+                    "baseFeePerGas": "0x07",
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                },
+                "transactions": [
+                    {
+                        "type": "0x00",
+                        "chainId": "0x01",
+                        "nonce": "0x00",
+                        "gasPrice": "0x0a",
+                        "gasLimit": "0x0f4240",
+                        "to": "",
+                        "value": "0x00",
+                        "data": "0x00",
+                        "v": "0x26",
+                        "r": "0x01",
+                        "s": "0x02",
+                        "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+                    }
+                ],
+                "uncleHeaders": [],
+                "rlp": "0x00"
+            },
+            {
+                "expectException": "TransactionException.INTRINSIC_GAS_TOO_LOW",
+                "rlp": "0x00"
+            }
+        ]"#;
+
+        let blocks: Vec<Block> = serde_json::from_str(blocks).unwrap();
+        let valid = blocks[0].decoded().unwrap();
+        assert_eq!(valid.transactions[0].to, None);
+        assert_eq!(
+            valid.block_header.as_ref().unwrap().gas_used,
+            U256::from(0xa861)
+        );
+        assert!(blocks[1].is_invalid() && blocks[1].decoded().is_none());
+    }
+}
@@ -0,0 +1,117 @@
+use revm::{
+    context_interface::block::{blob::fake_exponential, BlobExcessGasAndPrice},
+    primitives::U256,
+    specification::{
+        eip4844::{GAS_PER_BLOB, MIN_BLOB_GASPRICE},
+        hardfork::SpecId,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::SpecName;
+
+/// Chain configuration of a test, set by execution-spec-tests fixtures from Prague on.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestConfig {
+    /// EIP-7840 blob parameters of each fork since Cancun.
+    #[serde(default)]
+    pub blob_schedule: BTreeMap<SpecName, BlobSchedule>,
+    #[serde(default, rename = "chainid")]
+    pub chain_id: Option<U256>,
+}
+
+impl TestConfig {
+    /// Returns the blob parameters active in `spec`, [`None`] if the schedule does not cover it.
+    pub fn blob_schedule(&self, spec: SpecId) -> Option<&BlobSchedule> {
+        self.blob_schedule
+            .iter()
+            .rev()
+            .find(|(fork, _)| fork.is_supported() && fork.to_spec_id() <= spec)
+            .map(|(_, schedule)| schedule)
+    }
+
+    /// Returns the target and max blob count of each fork, in the format of
+    /// [`CfgEnv::set_blob_max_and_target_count`](revm::context::cfg::CfgEnv::set_blob_max_and_target_count).
+    pub fn blob_target_and_max_count(&self) -> Vec<(SpecId, u8, u8)> {
+        self.blob_schedule
+            .iter()
+            .filter(|(fork, _)| fork.is_supported())
+            .map(|(fork, schedule)| {
+                (
+                    fork.to_spec_id(),
+                    schedule.target.saturating_to(),
+                    schedule.max.saturating_to(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Blob parameters of a fork.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlobSchedule {
+    /// Target number of blobs per block.
+    pub target: U256,
+    /// Maximum number of blobs per block.
+    pub max: U256,
+    pub base_fee_update_fraction: U256,
+}
+
+impl BlobSchedule {
+    /// Target blob gas per block.
+    pub fn target_blob_gas_per_block(&self) -> u64 {
+        self.target
+            .saturating_to::<u64>()
+            .saturating_mul(GAS_PER_BLOB)
+    }
+
+    /// Returns the blob gas price of a block with the given excess blob gas.
+    pub fn blob_excess_gas_and_price(&self, excess_blob_gas: u64) -> BlobExcessGasAndPrice {
+        BlobExcessGasAndPrice {
+            excess_blob_gas,
+            blob_gasprice: fake_exponential(
+                MIN_BLOB_GASPRICE,
+                excess_blob_gas,
+                self.base_fee_update_fraction.saturating_to(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::specification::eip4844::{
+        BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE, TARGET_BLOB_GAS_PER_BLOCK_PRAGUE,
+    };
+
+    #[test]
+    fn blob_schedule() {
+        let config = r#"{
+            "chainid": "0x01",
+            "blobSchedule": {
+                "Cancun": { "target": "0x03", "max": "0x06", "baseFeeUpdateFraction": "0x32f0ed" },
+                "Prague": { "target": "0x06", "max": "0x09", "baseFeeUpdateFraction": "0x4c6964" }
+            }
+        }"#;
+
+        let config: TestConfig = serde_json::from_str(config).unwrap();
+        assert!(config.blob_schedule(SpecId::SHANGHAI).is_none());
+        let prague = config.blob_schedule(SpecId::OSAKA).unwrap();
+        assert_eq!(
+            prague.base_fee_update_fraction,
+            U256::from(BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE)
+        );
+        assert_eq!(
+            prague.target_blob_gas_per_block(),
+            TARGET_BLOB_GAS_PER_BLOCK_PRAGUE
+        );
+        assert_eq!(
+            config.blob_target_and_max_count(),
+            [(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use revm::primitives::Address;
-use serde::{de, Deserialize};
+use serde::{de, Deserialize, Serializer};
 
 /// Deserializes a [string][String] as a [u64].
 pub fn deserialize_str_as_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
@@ -16,6 +16,14 @@ where
     .map_err(serde::de::Error::custom)
 }
 
+/// Serializes a [u64] as a hex [string][String], the inverse of [deserialize_str_as_u64].
+pub fn serialize_u64_as_str<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("{value:#x}"))
+}
+
 /// Deserializes a [string][String] as an optional [Address].
 pub fn deserialize_maybe_empty<'de, D>(deserializer: D) -> Result<Option<Address>, D::Error>
 where
@@ -28,3 +36,14 @@ where
         string.parse().map_err(de::Error::custom).map(Some)
     }
 }
+
+/// Serializes an optional [Address] as a [string][String], empty if [None].
+pub fn serialize_maybe_empty<S>(address: &Option<Address>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match address {
+        Some(address) => serializer.serialize_str(&address.to_string()),
+        None => serializer.serialize_str(""),
+    }
+}
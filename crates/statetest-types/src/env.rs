@@ -1,8 +1,8 @@
 use revm::primitives::{Address, B256, U256};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Environment variables
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Env {
     pub current_coinbase: Address,
@@ -0,0 +1,143 @@
+use revm::{
+    bytecode::eof::{validate_raw_eof_inner, CodeType, Eof, EofError},
+    primitives::Bytes,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::SpecName;
+
+/// The top level EOF validation test suite struct
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct EofTestSuite(pub BTreeMap<String, EofTest>);
+
+/// EOF validation test, a set of containers with their expected validation result per fork.
+///
+/// Format of the `eof_tests` fixtures of execution-spec-tests.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EofTest {
+    /// Test info is optional.
+    #[serde(default, rename = "_info")]
+    pub info: Option<serde_json::Value>,
+    #[serde(default)]
+    pub vectors: BTreeMap<String, EofTestVector>,
+}
+
+/// Container validated by an [`EofTest`].
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EofTestVector {
+    pub code: Bytes,
+    /// Kind of the container, runtime code if absent.
+    #[serde(default)]
+    pub container_kind: Option<ContainerKind>,
+    pub results: BTreeMap<SpecName, EofTestResult>,
+}
+
+impl EofTestVector {
+    /// Returns the code type the first code section is validated as.
+    pub fn code_type(&self) -> CodeType {
+        match self.container_kind {
+            Some(ContainerKind::Initcode) => CodeType::ReturnContract,
+            Some(ContainerKind::Runtime) | None => CodeType::ReturnOrStop,
+        }
+    }
+
+    /// Decodes and validates the container.
+    pub fn validate(&self) -> Result<Eof, EofError> {
+        validate_raw_eof_inner(self.code.clone(), Some(self.code_type()))
+    }
+}
+
+/// Kind of an EOF container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ContainerKind {
+    /// Container deployed by a creation transaction or `EOFCREATE`.
+    Initcode,
+    /// Container of deployed code.
+    Runtime,
+}
+
+/// Expected validation result of an [`EofTestVector`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EofTestResult {
+    /// `true` if the container is valid.
+    pub result: bool,
+    /// Reason the container is invalid.
+    pub exception: Option<String>,
+}
+
+/// Vector of an [`EofTest`] whose validation result differs from the expected one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofTestFailure {
+    /// Name of the vector.
+    pub vector: String,
+    pub expected: EofTestResult,
+    /// Validation error, [`None`] if the container was found valid.
+    pub error: Option<EofError>,
+}
+
+/// Validates the containers of an EOF test and returns the ones that don't match their result
+/// for `fork`.
+///
+/// Vectors without a result for `fork` are skipped.
+pub fn execute_eof_test(test: &EofTest, fork: SpecName) -> Vec<EofTestFailure> {
+    test.vectors
+        .iter()
+        .filter_map(|(name, vector)| {
+            let expected = vector.results.get(&fork)?;
+            let error = vector.validate().err();
+            (expected.result != error.is_none()).then(|| EofTestFailure {
+                vector: name.clone(),
+                expected: expected.clone(),
+                error,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_vectors() {
+        let test = r#"{
+            "_info": {},
+            "vectors": {
+                "valid": {
+                    "code": "0xef000101000402000100010400000000800000fe",
+                    "containerKind": "RUNTIME",
+                    "results": { "Osaka": { "result": true } }
+                },
+                "invalid_magic": {
+                    "code": "0xef010101000402000100010400000000800000fe",
+                    "results": {
+                        "Osaka": { "exception": "EOFException.INVALID_MAGIC", "result": false }
+                    }
+                },
+                "wrong_result": {
+                    "code": "0xef000101000402000100010400000000800000fe",
+                    "results": { "Osaka": { "exception": "EOFException.UNKNOWN", "result": false } }
+                },
+                "other_fork": {
+                    "code": "0x00",
+                    "results": { "Prague": { "result": true } }
+                }
+            }
+        }"#;
+
+        let test: EofTest = serde_json::from_str(test).unwrap();
+        assert_eq!(
+            test.vectors["valid"].container_kind,
+            Some(ContainerKind::Runtime)
+        );
+        let failures = execute_eof_test(&test, SpecName::Osaka);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].vector, "wrong_result");
+        assert_eq!(failures[0].error, None);
+    }
+}
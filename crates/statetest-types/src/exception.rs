@@ -0,0 +1,229 @@
+use core::{fmt, str::FromStr};
+use revm::context_interface::result::{EVMError, InvalidTransaction};
+
+/// Transaction exception of execution-spec-tests, named `TransactionException.<NAME>` in
+/// `expectException`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionException {
+    SenderNotEoa,
+    NonceMismatchTooLow,
+    NonceMismatchTooHigh,
+    NonceIsMax,
+    InsufficientAccountFunds,
+    InsufficientMaxFeePerGas,
+    PriorityGreaterThanMaxFeePerGas,
+    InsufficientMaxFeePerBlobGas,
+    IntrinsicGasTooLow,
+    IntrinsicGasBelowFloorGasCost,
+    InitcodeSizeExceeded,
+    GasAllowanceExceeded,
+    InvalidChainId,
+    Type1TxPreFork,
+    Type2TxPreFork,
+    Type3TxPreFork,
+    Type3TxZeroBlobs,
+    Type3TxBlobCountExceeded,
+    Type3TxInvalidBlobVersionedHash,
+    Type3TxContractCreation,
+    Type4TxPreFork,
+    Type4EmptyAuthorizationList,
+}
+
+impl TransactionException {
+    /// Exceptions with their name in `expectException`, without the `TransactionException.`
+    /// prefix.
+    pub const NAMES: [(Self, &'static str); 22] = [
+        (Self::SenderNotEoa, "SENDER_NOT_EOA"),
+        (Self::NonceMismatchTooLow, "NONCE_MISMATCH_TOO_LOW"),
+        (Self::NonceMismatchTooHigh, "NONCE_MISMATCH_TOO_HIGH"),
+        (Self::NonceIsMax, "NONCE_IS_MAX"),
+        (Self::InsufficientAccountFunds, "INSUFFICIENT_ACCOUNT_FUNDS"),
+        (
+            Self::InsufficientMaxFeePerGas,
+            "INSUFFICIENT_MAX_FEE_PER_GAS",
+        ),
+        (
+            Self::PriorityGreaterThanMaxFeePerGas,
+            "PRIORITY_GREATER_THAN_MAX_FEE_PER_GAS",
+        ),
+        (
+            Self::InsufficientMaxFeePerBlobGas,
+            "INSUFFICIENT_MAX_FEE_PER_BLOB_GAS",
+        ),
+        (Self::IntrinsicGasTooLow, "INTRINSIC_GAS_TOO_LOW"),
+        (
+            Self::IntrinsicGasBelowFloorGasCost,
+            "INTRINSIC_GAS_BELOW_FLOOR_GAS_COST",
+        ),
+        (Self::InitcodeSizeExceeded, "INITCODE_SIZE_EXCEEDED"),
+        (Self::GasAllowanceExceeded, "GAS_ALLOWANCE_EXCEEDED"),
+        (Self::InvalidChainId, "INVALID_CHAINID"),
+        (Self::Type1TxPreFork, "TYPE_1_TX_PRE_FORK"),
+        (Self::Type2TxPreFork, "TYPE_2_TX_PRE_FORK"),
+        (Self::Type3TxPreFork, "TYPE_3_TX_PRE_FORK"),
+        (Self::Type3TxZeroBlobs, "TYPE_3_TX_ZERO_BLOBS"),
+        (
+            Self::Type3TxBlobCountExceeded,
+            "TYPE_3_TX_BLOB_COUNT_EXCEEDED",
+        ),
+        (
+            Self::Type3TxInvalidBlobVersionedHash,
+            "TYPE_3_TX_INVALID_BLOB_VERSIONED_HASH",
+        ),
+        (Self::Type3TxContractCreation, "TYPE_3_TX_CONTRACT_CREATION"),
+        (Self::Type4TxPreFork, "TYPE_4_TX_PRE_FORK"),
+        (
+            Self::Type4EmptyAuthorizationList,
+            "TYPE_4_EMPTY_AUTHORIZATION_LIST",
+        ),
+    ];
+
+    /// Returns the name of the exception, without the `TransactionException.` prefix.
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(exception, _)| *exception == self)
+            .map(|(_, name)| *name)
+            .expect("all exceptions are named")
+    }
+
+    /// Returns whether revm rejects a transaction with `error` for this exception.
+    pub fn matches(self, error: &InvalidTransaction) -> bool {
+        use InvalidTransaction as E;
+        match self {
+            Self::SenderNotEoa => matches!(error, E::RejectCallerWithCode),
+            Self::NonceMismatchTooLow => matches!(error, E::NonceTooLow { .. }),
+            Self::NonceMismatchTooHigh => matches!(error, E::NonceTooHigh { .. }),
+            Self::NonceIsMax => matches!(error, E::NonceOverflowInTransaction),
+            Self::InsufficientAccountFunds => matches!(
+                error,
+                E::LackOfFundForMaxFee { .. } | E::OverflowPaymentInTransaction
+            ),
+            Self::InsufficientMaxFeePerGas => matches!(error, E::GasPriceLessThanBasefee),
+            Self::PriorityGreaterThanMaxFeePerGas => {
+                matches!(error, E::PriorityFeeGreaterThanMaxFee)
+            }
+            Self::InsufficientMaxFeePerBlobGas => matches!(error, E::BlobGasPriceGreaterThanMax),
+            Self::IntrinsicGasTooLow => matches!(
+                error,
+                E::CallGasCostMoreThanGasLimit | E::GasFloorMoreThanGasLimit
+            ),
+            Self::IntrinsicGasBelowFloorGasCost => matches!(error, E::GasFloorMoreThanGasLimit),
+            Self::InitcodeSizeExceeded => matches!(error, E::CreateInitCodeSizeLimit),
+            Self::GasAllowanceExceeded => matches!(error, E::CallerGasLimitMoreThanBlock),
+            Self::InvalidChainId => matches!(error, E::InvalidChainId),
+            Self::Type1TxPreFork => matches!(error, E::Eip2930NotSupported),
+            Self::Type2TxPreFork => matches!(error, E::Eip1559NotSupported),
+            Self::Type3TxPreFork => matches!(error, E::Eip4844NotSupported),
+            Self::Type3TxZeroBlobs => matches!(error, E::EmptyBlobs),
+            Self::Type3TxBlobCountExceeded => matches!(error, E::TooManyBlobs { .. }),
+            Self::Type3TxInvalidBlobVersionedHash => matches!(error, E::BlobVersionNotSupported),
+            Self::Type3TxContractCreation => matches!(error, E::BlobCreateTransaction),
+            Self::Type4TxPreFork => matches!(error, E::Eip7702NotSupported),
+            Self::Type4EmptyAuthorizationList => matches!(error, E::EmptyAuthorizationList),
+        }
+    }
+}
+
+impl fmt::Display for TransactionException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TransactionException.{}", self.name())
+    }
+}
+
+impl FromStr for TransactionException {
+    type Err = UnknownExceptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("TransactionException.")
+            .and_then(|name| {
+                Self::NAMES
+                    .iter()
+                    .find(|(_, known)| *known == name)
+                    .map(|(exception, _)| *exception)
+            })
+            .ok_or_else(|| UnknownExceptionError(s.to_string()))
+    }
+}
+
+/// Exceptions of `expectException`, separated by `|` when any of them is accepted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedException(pub Vec<TransactionException>);
+
+impl ExpectedException {
+    /// Returns whether `error` is one of the expected exceptions.
+    ///
+    /// Errors other than an invalid transaction never match.
+    pub fn matches<DB>(&self, error: &EVMError<DB, InvalidTransaction>) -> bool {
+        match error {
+            EVMError::Transaction(error) => self.0.iter().any(|exception| exception.matches(error)),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ExpectedException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, exception) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str("|")?;
+            }
+            write!(f, "{exception}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ExpectedException {
+    type Err = UnknownExceptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('|')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// The exception is not in the table of [`TransactionException`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownExceptionError(pub String);
+
+impl fmt::Display for UnknownExceptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown exception {}", self.0)
+    }
+}
+
+impl core::error::Error for UnknownExceptionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    #[test]
+    fn parse_and_match() {
+        let expected: ExpectedException =
+            "TransactionException.TYPE_3_TX_PRE_FORK|TransactionException.TYPE_3_TX_ZERO_BLOBS"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            expected.to_string(),
+            "TransactionException.TYPE_3_TX_PRE_FORK|TransactionException.TYPE_3_TX_ZERO_BLOBS"
+        );
+        let error = |error| EVMError::<Infallible, _>::Transaction(error);
+        assert!(expected.matches(&error(InvalidTransaction::EmptyBlobs)));
+        assert!(expected.matches(&error(InvalidTransaction::Eip4844NotSupported)));
+        assert!(!expected.matches(&error(InvalidTransaction::BlobCreateTransaction)));
+        assert!(!expected.matches(&EVMError::<Infallible, _>::Custom("empty blobs".into())));
+
+        assert_eq!(
+            "TransactionException.UNKNOWN".parse::<ExpectedException>(),
+            Err(UnknownExceptionError("TransactionException.UNKNOWN".into()))
+        );
+        for (exception, _) in TransactionException::NAMES {
+            assert_eq!(exception.to_string().parse(), Ok(exception));
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use database::{CacheState, State, Witness};
+use revm::{
+    bytecode::Bytecode,
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::{
+        block::BlobExcessGasAndPrice,
+        result::{HaltReasonTrait, ResultAndState},
+        transaction::TransactionType,
+    },
+    database_interface::DatabaseCommit,
+    primitives::{keccak256, Address, TxKind, B256, KECCAK_EMPTY, U256},
+    specification::hardfork::SpecId,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{
+    merkle_trie::{log_rlp_hash, state_merkle_trie_root},
+    AccessListItem, AccountInfo, Env, SpecName, Test, TestConfig, TestUnit, TransactionParts,
+    TxPartIndices,
+};
+
+/// Reason an execution can't be turned into a state test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixtureError {
+    /// State tests can't run on the fork.
+    UnsupportedSpec(SpecId),
+    /// State tests can't express the transaction type.
+    UnsupportedTxType(u8),
+    /// The code of an account was not loaded during the execution.
+    MissingCode(Address),
+    /// A block hash read by the execution differs from the one state test runners return,
+    /// `keccak256(number.to_string())`.
+    BlockHash { number: u64, hash: B256 },
+    /// The blob gas price is not derived from the excess blob gas with the fork parameters.
+    BlobGasPrice(BlobExcessGasAndPrice),
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedSpec(spec) => write!(f, "unsupported spec {spec:?}"),
+            Self::UnsupportedTxType(tx_type) => write!(f, "unsupported transaction type {tx_type}"),
+            Self::MissingCode(address) => write!(f, "code of {address} was not loaded"),
+            Self::BlockHash { number, hash } => {
+                write!(f, "hash {hash} of block {number} can't be reproduced")
+            }
+            Self::BlobGasPrice(blob) => write!(
+                f,
+                "blob gas price {} can't be derived from excess blob gas {}",
+                blob.blob_gasprice, blob.excess_blob_gas
+            ),
+        }
+    }
+}
+
+impl core::error::Error for FixtureError {}
+
+/// Builds a state test reproducing an executed transaction.
+///
+/// `witness` holds the state the execution read, as recorded by
+/// [`WitnessDB`](database::WitnessDB), and `result` is the output of executing `tx` on top of it
+/// without committing. The pre state only contains the accounts and storage slots in the witness,
+/// so the expected post state hash is computed from the fixture itself, not from the full state
+/// of the chain the transaction ran on.
+///
+/// The transaction is given by its sender, state tests don't sign it.
+pub fn state_test_from_execution<H: HaltReasonTrait>(
+    witness: &Witness,
+    block: &BlockEnv,
+    tx: &TxEnv,
+    cfg: &CfgEnv,
+    result: &ResultAndState<H>,
+) -> Result<TestUnit, FixtureError> {
+    let spec_name = SpecName::from_spec_id(cfg.spec);
+    if !spec_name.is_supported() {
+        return Err(FixtureError::UnsupportedSpec(cfg.spec));
+    }
+
+    for (&number, &hash) in &witness.block_hashes {
+        if hash != keccak256(number.to_string().as_bytes()) {
+            return Err(FixtureError::BlockHash { number, hash });
+        }
+    }
+
+    let mut pre = HashMap::new();
+    for (&address, info) in &witness.accounts {
+        let Some(info) = info else {
+            continue;
+        };
+        let code = match witness.code(info) {
+            Some(code) => code.original_bytes(),
+            None if info.code_hash == KECCAK_EMPTY => Default::default(),
+            None => return Err(FixtureError::MissingCode(address)),
+        };
+        let storage = witness
+            .storage
+            .get(&address)
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(&slot, &value)| (slot, value))
+            .collect();
+        pre.insert(
+            address,
+            AccountInfo {
+                balance: info.balance,
+                code,
+                nonce: info.nonce,
+                storage,
+                init_code: None,
+            },
+        );
+    }
+
+    let current_excess_blob_gas = match block.blob_excess_gas_and_price {
+        Some(blob) => {
+            let is_prague = cfg.spec.is_enabled_in(SpecId::PRAGUE);
+            if blob != BlobExcessGasAndPrice::new(blob.excess_blob_gas, is_prague) {
+                return Err(FixtureError::BlobGasPrice(blob));
+            }
+            Some(U256::from(blob.excess_blob_gas))
+        }
+        None => None,
+    };
+    let env = Env {
+        current_coinbase: block.beneficiary,
+        current_difficulty: block.difficulty,
+        current_gas_limit: U256::from(block.gas_limit),
+        current_number: U256::from(block.number),
+        current_timestamp: U256::from(block.timestamp),
+        current_base_fee: Some(U256::from(block.basefee)),
+        previous_hash: None,
+        current_random: block.prevrandao,
+        current_beacon_root: None,
+        current_withdrawals_root: None,
+        parent_blob_gas_used: None,
+        parent_excess_blob_gas: None,
+        parent_target_blobs_per_block: None,
+        current_excess_blob_gas,
+    };
+
+    let tx_type = TransactionType::from(tx.tx_type);
+    if tx_type == TransactionType::Custom {
+        return Err(FixtureError::UnsupportedTxType(tx.tx_type));
+    }
+    let is_fee_market = tx.tx_type >= TransactionType::Eip1559 as u8;
+    let access_list = (tx_type != TransactionType::Legacy).then(|| {
+        tx.access_list
+            .iter()
+            .map(|(address, storage_keys)| AccessListItem {
+                address: *address,
+                storage_keys: storage_keys.clone(),
+            })
+            .collect()
+    });
+    let transaction = TransactionParts {
+        data: vec![tx.data.clone()],
+        gas_limit: vec![U256::from(tx.gas_limit)],
+        gas_price: (!is_fee_market).then(|| U256::from(tx.gas_price)),
+        nonce: U256::from(tx.nonce),
+        secret_key: B256::ZERO,
+        sender: Some(tx.caller),
+        to: match tx.kind {
+            TxKind::Call(address) => Some(address),
+            TxKind::Create => None,
+        },
+        value: vec![tx.value],
+        max_fee_per_gas: is_fee_market.then(|| U256::from(tx.gas_price)),
+        max_priority_fee_per_gas: is_fee_market
+            .then(|| U256::from(tx.gas_priority_fee.unwrap_or_default())),
+        access_lists: vec![access_list],
+        authorization_list: (tx_type == TransactionType::Eip7702).then(|| {
+            tx.authorization_list
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect()
+        }),
+        blob_versioned_hashes: tx.blob_hashes.clone(),
+        max_fee_per_blob_gas: (tx_type == TransactionType::Eip4844)
+            .then(|| U256::from(tx.max_fee_per_blob_gas)),
+        ..Default::default()
+    };
+
+    // Accounts missing from the pre state are loaded as not existing by the runners.
+    let mut cache = cache_state(&pre, cfg.spec);
+    for address in result.state.keys() {
+        if !pre.contains_key(address) {
+            cache.insert_not_existing(*address);
+        }
+    }
+    let mut state = State::builder().with_cached_prestate(cache).build();
+    state.commit(result.state.clone());
+    let post_state = state
+        .cache
+        .trie_account()
+        .into_iter()
+        .map(|(address, account)| {
+            let code = account
+                .info
+                .code
+                .as_ref()
+                .map(Bytecode::original_bytes)
+                .unwrap_or_default();
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(&slot, &value)| (slot, value))
+                .collect();
+            let info = AccountInfo {
+                balance: account.info.balance,
+                code,
+                nonce: account.info.nonce,
+                storage,
+                init_code: None,
+            };
+            (address, info)
+        })
+        .collect();
+    let test = Test {
+        expect_exception: None,
+        indexes: TxPartIndices {
+            data: 0,
+            gas: 0,
+            value: 0,
+        },
+        hash: state_merkle_trie_root(state.cache.trie_account()),
+        post_state,
+        logs: log_rlp_hash(result.result.logs()),
+        state: Default::default(),
+        txbytes: None,
+        l1_cost: None,
+    };
+
+    Ok(TestUnit {
+        info: None,
+        env,
+        pre,
+        post: BTreeMap::from([(spec_name, vec![test])]),
+        transaction,
+        out: result.result.output().cloned(),
+        config: TestConfig {
+            blob_schedule: BTreeMap::new(),
+            chain_id: Some(U256::from(cfg.chain_id)),
+        },
+    })
+}
+
+/// Builds the cache of the pre state, the way state test runners load it.
+pub(crate) fn cache_state(pre: &HashMap<Address, AccountInfo>, spec: SpecId) -> CacheState {
+    let mut cache = CacheState::new(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+    for (&address, info) in pre {
+        let bytecode = Bytecode::new_raw_checked(info.code.clone())
+            .unwrap_or(Bytecode::new_legacy(info.code.clone()));
+        let info_with_code = revm::state::AccountInfo {
+            balance: info.balance,
+            code_hash: keccak256(&info.code),
+            code: Some(bytecode),
+            nonce: info.nonce,
+        };
+        cache.insert_account_with_storage(address, info_with_code, info.storage.clone());
+    }
+    cache
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestSuite;
+    use database::{CacheDB, WitnessDB};
+    use revm::{
+        bytecode::opcode,
+        context_interface::result::{ExecutionResult, HaltReason},
+        database_interface::{Database, EmptyDB},
+        primitives::address,
+        Context, ExecuteEvm,
+    };
+
+    #[test]
+    fn fixture_from_execution() {
+        let caller = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let contract = address!("0000000000000000000000000000000000001000");
+        // Stores the block hash of block 0 at slot 1 and logs it.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x00,
+                opcode::BLOCKHASH,
+                opcode::DUP1,
+                opcode::PUSH1,
+                0x01,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x00,
+                opcode::MSTORE,
+                opcode::PUSH1,
+                0x20,
+                opcode::PUSH1,
+                0x00,
+                opcode::LOG0,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            revm::state::AccountInfo::from_balance(U256::from(10).pow(U256::from(18))),
+        );
+        db.insert_account_info(
+            contract,
+            revm::state::AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(contract, U256::from(2), U256::from(3))
+            .unwrap();
+        let mut db = WitnessDB::new(db);
+        // Read but not needed by the transaction.
+        db.storage(contract, U256::from(2)).unwrap();
+
+        let block = BlockEnv {
+            number: 1,
+            basefee: 7,
+            ..Default::default()
+        };
+        let tx = TxEnv {
+            tx_type: TransactionType::Eip1559 as u8,
+            caller,
+            gas_limit: 100_000,
+            gas_price: 10,
+            gas_priority_fee: Some(1),
+            kind: TxKind::Call(contract),
+            ..Default::default()
+        };
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::CANCUN;
+        let mut ctx = Context::builder()
+            .with_block(&block)
+            .with_tx(&tx)
+            .with_cfg(&cfg)
+            .with_db(&mut db);
+        let result = ctx.exec_previous().unwrap();
+        assert!(result.result.is_success());
+
+        let unit = state_test_from_execution(db.witness(), &block, &tx, &cfg, &result).unwrap();
+        assert_eq!(unit.pre[&contract].storage.len(), 1);
+        assert_eq!(unit.transaction.tx_type(0), Some(TransactionType::Eip1559));
+        let test = &unit.post[&SpecName::Cancun][0];
+        assert_eq!(
+            test.post_state[&contract].storage[&U256::from(1)],
+            U256::from_be_bytes(keccak256("0").0)
+        );
+
+        let suite = TestSuite(BTreeMap::from([("fixture".to_string(), unit)]));
+        let json = serde_json::to_string(&suite).unwrap();
+        assert_eq!(serde_json::from_str::<TestSuite>(&json).unwrap(), suite);
+    }
+
+    #[test]
+    fn reject_block_hash() {
+        let mut witness = Witness::default();
+        witness.block_hashes.insert(0, B256::ZERO);
+        let result = ResultAndState {
+            result: ExecutionResult::<HaltReason>::Revert {
+                gas_used: 0,
+                gas_refunded: 0,
+                effective_gas_price: 0,
+                output: Default::default(),
+            },
+            state: Default::default(),
+        };
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::CANCUN;
+        let error = state_test_from_execution(
+            &witness,
+            &BlockEnv::default(),
+            &TxEnv::default(),
+            &cfg,
+            &result,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            FixtureError::BlockHash {
+                number: 0,
+                hash: B256::ZERO
+            }
+        );
+    }
+}
@@ -5,10 +5,25 @@
 //!
 //! It includes structures for representing account information, environment settings,
 //! test cases, and transaction data used in Ethereum state tests.
+//!
+//! Blockchain tests of execution-spec-tests are supported by [`BlockchainTestSuite`] and
+//! executed with [`execute_blockchain_test`], EOF validation tests by [`EofTestSuite`] and
+//! [`execute_eof_test`].
+//!
+//! State tests can be generated from executed transactions with [`state_test_from_execution`].
+//! Pre state accounts can be declared by their constructor, see [`TestUnit::deploy_pre_state`].
 
 mod account_info;
+mod blockchain_runner;
+mod blockchain_test;
+mod config;
 mod deserializer;
 mod env;
+mod eof_test;
+mod exception;
+mod fixture;
+pub mod merkle_trie;
+mod pre_state;
 mod spec;
 mod test;
 mod test_authorization;
@@ -17,8 +32,15 @@ mod test_unit;
 mod transaction;
 
 pub use account_info::*;
+pub use blockchain_runner::*;
+pub use blockchain_test::*;
+pub use config::*;
 pub use deserializer::*;
 pub use env::*;
+pub use eof_test::*;
+pub use exception::*;
+pub use fixture::*;
+pub use pre_state::*;
 pub use spec::*;
 pub use test::*;
 pub use test_authorization::*;
@@ -5,12 +5,14 @@ use plain_hasher::PlainHasher;
 use revm::primitives::{keccak256, Address, Log, B256, U256};
 use triehash::sec_trie_root;
 
+/// Returns the hash of the RLP encoded logs, the `logs` field of a state test.
 pub fn log_rlp_hash(logs: &[Log]) -> B256 {
     let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
     alloy_rlp::encode_list(logs, &mut out);
     keccak256(&out)
 }
 
+/// Returns the state root of the accounts.
 pub fn state_merkle_trie_root<'a>(
     accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
 ) -> B256 {
@@ -46,6 +48,7 @@ impl TrieAccount {
     }
 }
 
+/// Returns the root of a secure trie, a trie keyed by the hash of the keys.
 #[inline]
 pub fn trie_root<I, A, B>(input: I) -> B256
 where
@@ -56,6 +59,7 @@ where
     sec_trie_root::<KeccakHasher, _, _, _>(input)
 }
 
+/// Keccak-256 [Hasher] of the trie.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct KeccakHasher;
 
@@ -0,0 +1,178 @@
+use database::State;
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::result::{ExecutionResult, Output},
+    primitives::{address, Address, TxKind, B256},
+    specification::hardfork::SpecId,
+    Context, ExecuteEvm,
+};
+use std::fmt;
+
+use crate::{fixture::cache_state, SpecName, TestUnit};
+
+/// Sender of the transactions running the constructors of [`TestUnit::deploy_pre_state`].
+pub const PRE_STATE_DEPLOYER: Address = address!("00000000000000000000000000000000000de910");
+
+/// The constructor of a pre state account did not return its code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstructorError {
+    pub address: Address,
+    pub reason: String,
+}
+
+impl fmt::Display for ConstructorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constructor of {} failed: {}", self.address, self.reason)
+    }
+}
+
+impl core::error::Error for ConstructorError {}
+
+impl TestUnit {
+    /// Runs the constructors of the pre state accounts declared with `initCode`.
+    ///
+    /// The init code is called at the address of the account from [`PRE_STATE_DEPLOYER`], in the
+    /// block of the test and the latest fork it runs on. The returned code becomes the code of
+    /// the account and the written slots are added to its storage, other changes are discarded.
+    /// Constructors run in order of address and see the accounts deployed before them.
+    ///
+    /// On error the pre state is left partially deployed.
+    pub fn deploy_pre_state(&mut self) -> Result<(), ConstructorError> {
+        let mut addresses: Vec<_> = self
+            .pre
+            .iter()
+            .filter(|(_, info)| info.init_code.is_some())
+            .map(|(address, _)| *address)
+            .collect();
+        if addresses.is_empty() {
+            return Ok(());
+        }
+        addresses.sort();
+
+        let mut cfg = CfgEnv::default();
+        cfg.spec = self
+            .post
+            .keys()
+            .rev()
+            .find(|spec_name| spec_name.is_supported())
+            .map_or(SpecId::LATEST, SpecName::to_spec_id);
+        let block = BlockEnv {
+            number: self.env.current_number.saturating_to(),
+            beneficiary: self.env.current_coinbase,
+            timestamp: self.env.current_timestamp.saturating_to(),
+            gas_limit: self.env.current_gas_limit.saturating_to(),
+            difficulty: self.env.current_difficulty,
+            prevrandao: self
+                .env
+                .current_random
+                .or(cfg.spec.is_enabled_in(SpecId::MERGE).then_some(B256::ZERO)),
+            ..Default::default()
+        };
+
+        for address in addresses {
+            let info = self.pre.get_mut(&address).expect("account is in pre state");
+            info.code = info.init_code.take().unwrap_or_default();
+
+            let state = State::builder()
+                .with_cached_prestate(cache_state(&self.pre, cfg.spec))
+                .build();
+            let tx = TxEnv {
+                caller: PRE_STATE_DEPLOYER,
+                kind: TxKind::Call(address),
+                gas_limit: block.gas_limit,
+                ..Default::default()
+            };
+            let error = |reason| ConstructorError { address, reason };
+            let result = Context::builder()
+                .with_block(&block)
+                .with_tx(&tx)
+                .with_cfg(&cfg)
+                .with_db(state)
+                .exec_previous()
+                .map_err(|e| error(e.to_string()))?;
+            let code = match result.result {
+                ExecutionResult::Success {
+                    output: Output::Call(code),
+                    ..
+                } => code,
+                result => return Err(error(format!("{result:?}"))),
+            };
+
+            let info = self.pre.get_mut(&address).expect("account is in pre state");
+            info.code = code;
+            let slots = result
+                .state
+                .get(&address)
+                .into_iter()
+                .flat_map(|account| account.changed_storage_slots());
+            for (slot, value) in slots {
+                if value.present_value.is_zero() {
+                    info.storage.remove(slot);
+                } else {
+                    info.storage.insert(*slot, value.present_value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{bytecode::opcode, primitives::U256};
+
+    #[test]
+    fn deploy_pre_state() {
+        let mut unit: TestUnit = serde_json::from_str(
+            r#"{
+                "env": {
+                    "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "currentGasLimit": "0x1000000",
+                    "currentNumber": "0x01",
+                    "currentTimestamp": "0x03e8",
+                    "currentBaseFee": "0x07",
+                    "currentRandom": "0x0000000000000000000000000000000000000000000000000000000000020000"
+                },
+                "pre": {
+                    "0x0000000000000000000000000000000000001000": {
+                        "balance": "0x00",
+                        "nonce": "0x01",
+                        "storage": { "0x02": "0x03" },
+                        "initCode": "0x602a60015560016000f3"
+                    },
+                    "0x0000000000000000000000000000000000001001": {
+                        "balance": "0x00",
+                        "nonce": "0x01",
+                        "initCode": "0x60006000f3"
+                    }
+                },
+                "post": { "Cancun": [] },
+                "transaction": {
+                    "data": [ "0x" ],
+                    "gasLimit": [ "0x0186a0" ],
+                    "gasPrice": "0x0a",
+                    "nonce": "0x00",
+                    "secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+                    "to": "0x0000000000000000000000000000000000001000",
+                    "value": [ "0x00" ]
+                }
+            }"#,
+        )
+        .unwrap();
+        unit.deploy_pre_state().unwrap();
+
+        // Stores 42 at slot 1 and returns the `STOP` byte at memory offset 0.
+        let first = address!("0000000000000000000000000000000000001000");
+        assert_eq!(unit.pre[&first].code.as_ref(), &[opcode::STOP]);
+        assert_eq!(unit.pre[&first].init_code, None);
+        assert_eq!(unit.pre[&first].storage.len(), 2);
+        assert_eq!(unit.pre[&first].storage[&U256::from(1)], U256::from(42));
+        // Returns empty code.
+        let second = address!("0000000000000000000000000000000000001001");
+        assert!(unit.pre[&second].code.is_empty());
+
+        unit.pre.get_mut(&second).unwrap().init_code = Some([opcode::INVALID].into());
+        assert_eq!(unit.deploy_pre_state().unwrap_err().address, second);
+    }
+}
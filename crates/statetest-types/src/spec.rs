@@ -1,8 +1,10 @@
+#[cfg(feature = "optimism")]
+use optimism::OpSpecId;
 use revm::specification::hardfork::SpecId;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-/// Ethereum specification names
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
+/// Ethereum and Optimism specification names
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub enum SpecName {
     Frontier,
     FrontierToHomesteadAt5,
@@ -24,15 +26,93 @@ pub enum SpecName {
     Paris,
     Merge,
     Shanghai,
+    ParisToShanghaiAtTime15k,
     Cancun,
+    ShanghaiToCancunAtTime15k,
     Prague,
-    Osaka, // SKIPPED
+    CancunToPragueAtTime15k,
+    Osaka,
+    PragueToOsakaAtTime15k,
+    Bedrock,
+    Regolith,
+    Canyon,
+    Ecotone,
+    Fjord,
+    Granite,
+    Holocene,
+    Isthmus,
     #[serde(other)]
     Unknown,
 }
 
 impl SpecName {
+    /// Returns `false` for forks [`SpecName::to_spec_id`] panics on.
+    pub fn is_supported(&self) -> bool {
+        !matches!(
+            self,
+            Self::ByzantiumToConstantinopleAt5 | Self::Constantinople | Self::Unknown
+        )
+    }
+
+    /// Returns `true` for the forks of OP stack chains, executed by the optimism handler.
+    pub fn is_optimism(&self) -> bool {
+        matches!(
+            self,
+            Self::Bedrock
+                | Self::Regolith
+                | Self::Canyon
+                | Self::Ecotone
+                | Self::Fjord
+                | Self::Granite
+                | Self::Holocene
+                | Self::Isthmus
+        )
+    }
+
+    /// Converts an Optimism fork to its [`OpSpecId`], `None` for Ethereum forks.
+    #[cfg(feature = "optimism")]
+    pub fn to_op_spec_id(&self) -> Option<OpSpecId> {
+        Some(match self {
+            Self::Bedrock => OpSpecId::BEDROCK,
+            Self::Regolith => OpSpecId::REGOLITH,
+            Self::Canyon => OpSpecId::CANYON,
+            Self::Ecotone => OpSpecId::ECOTONE,
+            Self::Fjord => OpSpecId::FJORD,
+            Self::Granite => OpSpecId::GRANITE,
+            Self::Holocene => OpSpecId::HOLOCENE,
+            Self::Isthmus => OpSpecId::ISTHMUS,
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of the fork `spec` behaves as.
+    ///
+    /// Forks that only delay the difficulty bomb map to the fork they follow,
+    /// [`SpecId::LATEST`] maps to [`SpecName::Unknown`].
+    pub fn from_spec_id(spec: SpecId) -> Self {
+        match spec {
+            SpecId::FRONTIER | SpecId::FRONTIER_THAWING => Self::Frontier,
+            SpecId::HOMESTEAD | SpecId::DAO_FORK => Self::Homestead,
+            SpecId::TANGERINE => Self::EIP150,
+            SpecId::SPURIOUS_DRAGON => Self::EIP158,
+            SpecId::BYZANTIUM => Self::Byzantium,
+            SpecId::CONSTANTINOPLE => Self::Constantinople,
+            SpecId::PETERSBURG => Self::ConstantinopleFix,
+            SpecId::ISTANBUL | SpecId::MUIR_GLACIER => Self::Istanbul,
+            SpecId::BERLIN => Self::Berlin,
+            SpecId::LONDON | SpecId::ARROW_GLACIER | SpecId::GRAY_GLACIER => Self::London,
+            SpecId::MERGE => Self::Paris,
+            SpecId::SHANGHAI => Self::Shanghai,
+            SpecId::CANCUN => Self::Cancun,
+            SpecId::PRAGUE => Self::Prague,
+            SpecId::OSAKA => Self::Osaka,
+            SpecId::LATEST => Self::Unknown,
+        }
+    }
+
     /// Converts to a [SpecId].
+    ///
+    /// Optimism forks are converted to the Ethereum fork they are based on.
     pub fn to_spec_id(&self) -> SpecId {
         match self {
             Self::Frontier => SpecId::FRONTIER,
@@ -47,14 +127,49 @@ impl SpecName {
             Self::Berlin => SpecId::BERLIN,
             Self::London | Self::BerlinToLondonAt5 => SpecId::LONDON,
             Self::Paris | Self::Merge => SpecId::MERGE,
-            Self::Shanghai => SpecId::SHANGHAI,
-            Self::Cancun => SpecId::CANCUN,
-            Self::Prague => SpecId::PRAGUE,
-            Self::Osaka => SpecId::OSAKA,
+            Self::Shanghai | Self::ParisToShanghaiAtTime15k => SpecId::SHANGHAI,
+            Self::Cancun | Self::ShanghaiToCancunAtTime15k => SpecId::CANCUN,
+            Self::Prague | Self::CancunToPragueAtTime15k => SpecId::PRAGUE,
+            Self::Osaka | Self::PragueToOsakaAtTime15k => SpecId::OSAKA,
+            Self::Bedrock | Self::Regolith => SpecId::MERGE,
+            Self::Canyon => SpecId::SHANGHAI,
+            Self::Ecotone | Self::Fjord | Self::Granite | Self::Holocene | Self::Isthmus => {
+                SpecId::CANCUN
+            }
             Self::ByzantiumToConstantinopleAt5 | Self::Constantinople => {
                 panic!("Overridden with PETERSBURG")
             }
             Self::Unknown => panic!("Unknown spec"),
         }
     }
+
+    /// Converts to the [SpecId] active in the block with the given number and timestamp.
+    ///
+    /// Transition networks of blockchain tests switch to the later fork at block 5 or at
+    /// timestamp 15000, other networks return [`SpecName::to_spec_id`].
+    pub fn to_spec_id_at(&self, number: u64, timestamp: u64) -> SpecId {
+        let (before, after, switched) = match self {
+            Self::FrontierToHomesteadAt5 => (SpecId::FRONTIER, SpecId::HOMESTEAD, number >= 5),
+            Self::HomesteadToEIP150At5 => (SpecId::HOMESTEAD, SpecId::TANGERINE, number >= 5),
+            Self::EIP158ToByzantiumAt5 => (SpecId::SPURIOUS_DRAGON, SpecId::BYZANTIUM, number >= 5),
+            Self::ByzantiumToConstantinopleFixAt5 => {
+                (SpecId::BYZANTIUM, SpecId::PETERSBURG, number >= 5)
+            }
+            Self::BerlinToLondonAt5 => (SpecId::BERLIN, SpecId::LONDON, number >= 5),
+            Self::ParisToShanghaiAtTime15k => {
+                (SpecId::MERGE, SpecId::SHANGHAI, timestamp >= 15_000)
+            }
+            Self::ShanghaiToCancunAtTime15k => {
+                (SpecId::SHANGHAI, SpecId::CANCUN, timestamp >= 15_000)
+            }
+            Self::CancunToPragueAtTime15k => (SpecId::CANCUN, SpecId::PRAGUE, timestamp >= 15_000),
+            Self::PragueToOsakaAtTime15k => (SpecId::PRAGUE, SpecId::OSAKA, timestamp >= 15_000),
+            _ => return self.to_spec_id(),
+        };
+        if switched {
+            after
+        } else {
+            before
+        }
+    }
 }
@@ -1,10 +1,10 @@
-use revm::primitives::{Address, Bytes, HashMap, B256};
-use serde::Deserialize;
+use revm::primitives::{Address, Bytes, HashMap, B256, U256};
+use serde::{Deserialize, Serialize};
 
 use crate::{transaction::TxPartIndices, AccountInfo};
 
 /// State test indexed state result deserialization.
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Test {
     pub expect_exception: Option<String>,
@@ -23,9 +23,13 @@ pub struct Test {
     /// Output state.
     ///
     /// Note: Not used.
-    #[serde(default)]
-    state: HashMap<Address, AccountInfo>,
+    #[serde(default, skip_serializing)]
+    pub(crate) state: HashMap<Address, AccountInfo>,
 
     /// Tx bytes
     pub txbytes: Option<Bytes>,
+
+    /// L1 data fee of the transaction bytes, checked by the runner of Optimism forks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_cost: Option<U256>,
 }
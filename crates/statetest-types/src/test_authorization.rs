@@ -16,6 +16,9 @@ pub struct TestAuthorization {
     /// The nonce for the authorization.
     pub nonce: U256,
     v: U256,
+    /// Same as `v`, set by blockchain tests.
+    #[serde(default)]
+    y_parity: Option<U256>,
     r: U256,
     s: U256,
     signer: Option<Address>,
@@ -29,7 +32,7 @@ impl From<TestAuthorization> for AuthorizationItem {
             signer = None
         }
 
-        if auth.v > U256::from(1) {
+        if auth.y_parity.unwrap_or(auth.v) > U256::from(1) {
             signer = None
         }
 
@@ -42,6 +45,22 @@ impl From<TestAuthorization> for AuthorizationItem {
     }
 }
 
+impl From<AuthorizationItem> for TestAuthorization {
+    /// Converts a recovered authorization, the signature is zeroed as only the signer is used.
+    fn from((signer, chain_id, nonce, address): AuthorizationItem) -> TestAuthorization {
+        TestAuthorization {
+            chain_id,
+            address,
+            nonce: U256::from(nonce),
+            v: U256::ZERO,
+            y_parity: None,
+            r: U256::ZERO,
+            s: U256::ZERO,
+            signer,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
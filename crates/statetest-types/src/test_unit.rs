@@ -1,13 +1,11 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
-use crate::{AccountInfo, Env, SpecName, Test, TransactionParts};
+use crate::{AccountInfo, Env, SpecName, Test, TestConfig, TransactionParts};
 use revm::primitives::{Address, Bytes};
 
 /// Single test unit struct
-#[derive(Debug, PartialEq, Eq, Deserialize)]
-//#[serde(deny_unknown_fields)]
-// field config
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestUnit {
     /// Test info is optional.
     #[serde(default, rename = "_info")]
@@ -19,5 +17,7 @@ pub struct TestUnit {
     pub transaction: TransactionParts,
     #[serde(default)]
     pub out: Option<Bytes>,
-    //pub config
+    /// Chain configuration, blob parameters of the forks from Prague on.
+    #[serde(default)]
+    pub config: TestConfig,
 }
@@ -4,7 +4,10 @@ use revm::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{deserializer::deserialize_maybe_empty, TestAuthorization};
+use crate::{
+    deserializer::{deserialize_maybe_empty, serialize_maybe_empty},
+    TestAuthorization,
+};
 
 /// Transaction parts.
 #[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -18,7 +21,11 @@ pub struct TransactionParts {
     /// if sender is not present we need to derive it from secret key.
     #[serde(default)]
     pub sender: Option<Address>,
-    #[serde(default, deserialize_with = "deserialize_maybe_empty")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_maybe_empty",
+        serialize_with = "serialize_maybe_empty"
+    )]
     pub to: Option<Address>,
     pub value: Vec<U256>,
     pub max_fee_per_gas: Option<U256>,
@@ -30,10 +37,25 @@ pub struct TransactionParts {
     #[serde(default)]
     pub blob_versioned_hashes: Vec<B256>,
     pub max_fee_per_blob_gas: Option<U256>,
+
+    /// Source hash of an Optimism deposit, the transaction is a deposit if it is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<B256>,
+    /// Value minted on L2 by an Optimism deposit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<U256>,
+    /// Whether an Optimism deposit is a system transaction, before Regolith.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_system_tx: bool,
 }
 
 impl TransactionParts {
-    /// Returns the transaction type.   
+    /// Returns `true` if the transaction is an Optimism deposit.
+    pub fn is_deposit(&self) -> bool {
+        self.source_hash.is_some()
+    }
+
+    /// Returns the transaction type.
     ///
     /// As this information is derived from the fields it is not stored in the struct.
     ///
@@ -137,4 +159,32 @@ mod test {
 
         let _: TransactionParts = serde_json::from_str(tx).unwrap();
     }
+
+    #[test]
+    fn decode_deposit_tx_parts() {
+        let tx = r#"{
+            "nonce": "0x00",
+            "gasPrice": "0x00",
+            "gasLimit": [
+                "0x0f4240"
+            ],
+            "to": "0x4200000000000000000000000000000000000015",
+            "value": [
+                "0x00"
+            ],
+            "data": [
+                "0x"
+            ],
+            "sender": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+            "secretKey": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "sourceHash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "mint": "0x0a",
+            "isSystemTx": true
+        }"#;
+
+        let tx: TransactionParts = serde_json::from_str(tx).unwrap();
+        assert!(tx.is_deposit());
+        assert_eq!(tx.mint, Some(U256::from(10)));
+        assert!(tx.is_system_tx);
+    }
 }
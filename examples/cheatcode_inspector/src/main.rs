@@ -268,6 +268,18 @@ impl JournalExt for Backend {
     fn evm_state_mut(&mut self) -> &mut EvmState {
         &mut self.journaled_state.state
     }
+
+    fn depth(&self) -> usize {
+        self.journaled_state.depth
+    }
+
+    fn transient_storage(&self) -> &TransientStorage {
+        &self.journaled_state.transient_storage
+    }
+
+    fn warm_preloaded_addresses(&self) -> &HashSet<Address> {
+        &self.journaled_state.warm_preloaded_addresses
+    }
 }
 
 /// Used in Foundry to provide extended functionality to cheatcodes.